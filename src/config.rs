@@ -4,7 +4,8 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
 /// Application configuration
@@ -22,15 +23,31 @@ pub struct AppConfig {
     /// Scroll behavior configuration
     pub scroll: ScrollConfig,
 
+    /// Scroll animation for keyboard navigation, TOC jumps and search
+    #[serde(default)]
+    pub animation: AnimationConfig,
+
     /// Theme and styling configuration
     pub theme: ThemeConfig,
 
     /// PDF export configuration
     pub pdf_export: PdfExportConfig,
 
+    /// HTML export configuration
+    #[serde(default)]
+    pub html_export: HtmlExportConfig,
+
+    /// Image loading configuration
+    #[serde(default)]
+    pub images: ImageConfig,
+
     /// Logging configuration
     pub logging: LoggingConfig,
 
+    /// Toast notification timeouts
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+
     /// Search history
     #[serde(default)]
     pub search_history: Vec<String>,
@@ -39,13 +56,84 @@ pub struct AppConfig {
     #[serde(default = "default_max_history_items")]
     pub max_history_items: usize,
 
-    /// Recent files history
+    /// Recently opened files, most recent first (Cmd+Shift+O)
     #[serde(default)]
-    pub recent_files: Vec<String>,
+    pub recent_files: Vec<PathBuf>,
 
     /// Maximum number of recent files to keep
     #[serde(default = "default_max_recent_files")]
     pub max_recent_files: usize,
+
+    /// Exports, most recent first, for re-exporting with the same settings
+    #[serde(default)]
+    pub export_history: Vec<ExportRecord>,
+
+    /// Session saved on exit and restored on the next launch
+    #[serde(default)]
+    pub session: Option<SessionState>,
+
+    /// Plugin libraries to load at startup (requires the `plugin-dylib` feature)
+    #[serde(default)]
+    pub plugins: Vec<String>,
+
+    /// Rules turning bare text such as issue keys into links
+    #[serde(default)]
+    pub link_rewrites: Vec<LinkRewriteRule>,
+}
+
+/// Turn text matching `pattern` into a link to `url`
+///
+/// `url` may refer to capture groups: `$0` is the whole match, `$1` or
+/// `${name}` a group.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinkRewriteRule {
+    /// Regular expression matched against plain text
+    pub pattern: String,
+
+    /// URL template for the link
+    pub url: String,
+}
+
+/// Document formats the viewer exports to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExportFormat {
+    Pdf,
+    Html,
+    Epub,
+}
+
+/// A document and where it was last exported in one format
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportRecord {
+    /// Path of the exported document
+    pub document: String,
+
+    pub format: ExportFormat,
+
+    /// File the export was written to
+    pub destination: String,
+}
+
+/// Viewer state saved on exit so the next launch can pick up where it left off
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct SessionState {
+    /// File that was open
+    pub file: String,
+
+    /// Files queued from the command line, if any
+    #[serde(default)]
+    pub queue: Vec<String>,
+
+    /// Scroll position in pixels
+    #[serde(default)]
+    pub scroll_y: f32,
+
+    /// Whether the TOC sidebar was visible
+    #[serde(default)]
+    pub show_toc: bool,
+
+    /// Base text size (zoom level)
+    pub text_size: f32,
 }
 
 fn default_max_history_items() -> usize {
@@ -77,6 +165,31 @@ pub struct FileConfig {
 
     /// Supported file extensions
     pub supported_extensions: Vec<String>,
+
+    /// Render mode per extension (without dots), e.g. `{"mdx": Markdown, "log": Plain}`
+    ///
+    /// Extensions listed here are supported even if missing from
+    /// `supported_extensions`; unlisted extensions render as markdown.
+    #[serde(default = "default_extension_modes")]
+    pub extension_modes: BTreeMap<String, RenderMode>,
+}
+
+fn default_extension_modes() -> BTreeMap<String, RenderMode> {
+    BTreeMap::from([("txt".to_string(), RenderMode::Plain)])
+}
+
+impl FileConfig {
+    /// All supported extensions: `supported_extensions` plus any extension
+    /// that has a configured render mode
+    pub fn all_extensions(&self) -> Vec<String> {
+        let mut extensions = self.supported_extensions.clone();
+        for ext in self.extension_modes.keys() {
+            if !extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)) {
+                extensions.push(ext.clone());
+            }
+        }
+        extensions
+    }
 }
 
 /// File watcher configuration
@@ -100,6 +213,83 @@ pub struct ScrollConfig {
 
     /// Space key scroll percentage (0.0 to 1.0)
     pub space_scroll_percentage: f32,
+
+    /// Scroll wheel and trackpad speed multiplier
+    #[serde(default = "default_wheel_multiplier")]
+    pub wheel_multiplier: f32,
+
+    /// Reverse the scroll wheel direction
+    #[serde(default)]
+    pub invert_wheel: bool,
+
+    /// Lines of the previous page kept visible on PageUp/PageDown
+    #[serde(default = "default_page_overlap_lines")]
+    pub page_overlap_lines: usize,
+
+    /// Speed in pixels per second while j/k or an arrow key is held
+    #[serde(default = "default_key_hold_speed")]
+    pub key_hold_speed: f32,
+
+    /// How quickly held-key scrolling speeds up (multiples of the base speed per second)
+    #[serde(default = "default_key_hold_acceleration")]
+    pub key_hold_acceleration: f32,
+}
+
+fn default_page_overlap_lines() -> usize {
+    1
+}
+
+fn default_key_hold_speed() -> f32 {
+    600.0
+}
+
+fn default_key_hold_acceleration() -> f32 {
+    1.5
+}
+
+fn default_wheel_multiplier() -> f32 {
+    1.0
+}
+
+/// How an animated scroll moves from its start to its target
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Constant speed
+    Linear,
+    /// Fast start, gentle stop
+    #[default]
+    EaseOut,
+    /// Gentle start and stop
+    EaseInOut,
+}
+
+impl Easing {
+    /// Share of the distance covered after share `t` (0.0 to 1.0) of the time
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => match t < 0.5 {
+                true => 4.0 * t.powi(3),
+                false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            },
+        }
+    }
+}
+
+/// Scroll animation configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AnimationConfig {
+    /// Animate scrolling; when false, jumps land immediately
+    pub enabled: bool,
+
+    /// Length of a scroll animation in milliseconds
+    pub duration_ms: u64,
+
+    /// Curve the animation follows
+    pub easing: Easing,
 }
 
 /// Theme configuration
@@ -123,12 +313,30 @@ pub struct ThemeConfig {
 
     /// Content height buffer in pixels
     pub content_height_buffer: f32,
+
+    /// Render plain-text files in the code font
+    #[serde(default = "default_plain_text_monospace")]
+    pub plain_text_monospace: bool,
 }
 
 fn default_theme_name() -> String {
     "Zoegi Light".to_string()
 }
 
+fn default_plain_text_monospace() -> bool {
+    true
+}
+
+/// How a file's content is interpreted for display
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// Parse as CommonMark/GFM markdown
+    #[default]
+    Markdown,
+    /// Show the text as-is, preserving line breaks, with no markdown semantics
+    Plain,
+}
+
 /// PDF export configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PdfExportConfig {
@@ -145,6 +353,45 @@ pub struct PdfExportConfig {
     pub enable_subsetting: bool,
 }
 
+/// HTML export configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HtmlExportConfig {
+    /// Embed local images as base64 data URIs so the page is self-contained;
+    /// when false, image paths are kept as written in the document
+    pub embed_images: bool,
+}
+
+/// Image loading configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ImageConfig {
+    /// Start in offline mode: remote images are not fetched and show their
+    /// alt text instead
+    pub offline: bool,
+
+    /// Directory for downloaded images; the platform cache directory when unset
+    pub cache_dir: Option<PathBuf>,
+
+    /// Size limit of the image cache directory in megabytes; 0 turns the disk
+    /// cache off
+    pub cache_max_mb: u64,
+}
+
+/// Toast notification timeouts, in milliseconds; 0 keeps a toast until it is
+/// dismissed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// Export successes
+    pub success_timeout_ms: u64,
+
+    /// Informational messages (search history, bookmarks, clipboard)
+    pub info_timeout_ms: u64,
+
+    /// Failures
+    pub error_timeout_ms: u64,
+}
+
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LoggingConfig {
@@ -170,6 +417,7 @@ impl Default for FileConfig {
         Self {
             default_files: vec!["README.md".to_string(), "TODO.md".to_string()],
             supported_extensions: vec!["md".to_string(), "markdown".to_string(), "txt".to_string()],
+            extension_modes: default_extension_modes(),
         }
     }
 }
@@ -189,6 +437,11 @@ impl Default for ScrollConfig {
             page_scroll_percentage: 0.8,
             arrow_key_increment: 20.0,
             space_scroll_percentage: 0.2,
+            wheel_multiplier: default_wheel_multiplier(),
+            invert_wheel: false,
+            page_overlap_lines: default_page_overlap_lines(),
+            key_hold_speed: default_key_hold_speed(),
+            key_hold_acceleration: default_key_hold_acceleration(),
         }
     }
 }
@@ -202,6 +455,7 @@ impl Default for ThemeConfig {
             base_text_size: 19.2,
             line_height_multiplier: 1.5,
             content_height_buffer: 200.0,
+            plain_text_monospace: default_plain_text_monospace(),
         }
     }
 }
@@ -217,6 +471,42 @@ impl Default for PdfExportConfig {
     }
 }
 
+impl Default for HtmlExportConfig {
+    fn default() -> Self {
+        Self { embed_images: true }
+    }
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            offline: false,
+            cache_dir: None,
+            cache_max_mb: 100,
+        }
+    }
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duration_ms: 150,
+            easing: Easing::EaseOut,
+        }
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            success_timeout_ms: 4000,
+            info_timeout_ms: 4000,
+            error_timeout_ms: 0,
+        }
+    }
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
@@ -269,6 +559,20 @@ impl AppConfig {
         Ok(())
     }
 
+    /// Move `path` to the front of the recent files, dropping the oldest
+    /// past `max_recent_files`
+    ///
+    /// A limit of 0 (a config built with `Default`) keeps the default number.
+    pub fn add_recent_file(&mut self, path: &Path) {
+        let limit = match self.max_recent_files {
+            0 => default_max_recent_files(),
+            limit => limit,
+        };
+        self.recent_files.retain(|recent| recent != path);
+        self.recent_files.insert(0, path.to_path_buf());
+        self.recent_files.truncate(limit);
+    }
+
     /// Validate configuration values
     pub fn validate(&self) -> Result<()> {
         // Validate window dimensions
@@ -290,6 +594,22 @@ impl AppConfig {
             anyhow::bail!("Arrow key increment must be positive");
         }
 
+        if self.scroll.wheel_multiplier <= 0.0 {
+            anyhow::bail!("Wheel multiplier must be positive");
+        }
+
+        if self.scroll.key_hold_speed <= 0.0 {
+            anyhow::bail!("Key hold speed must be positive");
+        }
+
+        if self.scroll.key_hold_acceleration < 0.0 {
+            anyhow::bail!("Key hold acceleration must not be negative");
+        }
+
+        if self.animation.duration_ms > 2000 {
+            anyhow::bail!("Animation duration must be at most 2000 ms");
+        }
+
         // Validate theme values
         if self.theme.base_text_size <= 0.0 {
             anyhow::bail!("Base text size must be positive");
@@ -299,6 +619,21 @@ impl AppConfig {
             anyhow::bail!("Line height multiplier must be positive");
         }
 
+        crate::internal::link_rules::validate(&self.link_rewrites)?;
+
+        // Validate extension mode keys
+        if let Some(ext) = self
+            .files
+            .extension_modes
+            .keys()
+            .find(|ext| ext.is_empty() || ext.starts_with('.'))
+        {
+            anyhow::bail!(
+                "Extension mode keys must be non-empty and without a leading dot, got '{}'",
+                ext
+            );
+        }
+
         // Validate logging level
         let valid_levels = ["trace", "debug", "info", "warn", "error"];
         if !valid_levels.contains(&self.logging.default_level.as_str()) {
@@ -323,6 +658,18 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn easing_curves_run_from_start_to_target() {
+        for easing in [Easing::Linear, Easing::EaseOut, Easing::EaseInOut] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+        assert!(Easing::EaseOut.apply(0.5) > Easing::Linear.apply(0.5));
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::EaseInOut.apply(0.25) < 0.25);
+    }
+
     #[test]
     fn default_window_config() {
         let config = WindowConfig::default();
@@ -336,6 +683,29 @@ mod tests {
         let config = FileConfig::default();
         assert_eq!(config.default_files, vec!["README.md", "TODO.md"]);
         assert_eq!(config.supported_extensions, vec!["md", "markdown", "txt"]);
+        assert_eq!(config.extension_modes.get("txt"), Some(&RenderMode::Plain));
+    }
+
+    #[test]
+    fn extension_modes_extend_supported_extensions() {
+        let ron_content = r#"(
+            default_files: ["README.md"],
+            supported_extensions: ["md", "TXT"],
+            extension_modes: {"mdx": Markdown, "log": Plain, "txt": Plain},
+        )"#;
+        let config: FileConfig = ron::from_str(ron_content).expect("Failed to parse");
+        assert_eq!(config.all_extensions(), vec!["md", "TXT", "log", "mdx"]);
+        assert_eq!(config.extension_modes.get("log"), Some(&RenderMode::Plain));
+    }
+
+    #[test]
+    fn extension_mode_keys_with_dots_are_invalid() {
+        let mut config = AppConfig::default();
+        config
+            .files
+            .extension_modes
+            .insert(".log".to_string(), RenderMode::Plain);
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -344,6 +714,9 @@ mod tests {
         assert_eq!(config.page_scroll_percentage, 0.8);
         assert_eq!(config.arrow_key_increment, 20.0);
         assert_eq!(config.space_scroll_percentage, 0.2);
+        assert_eq!(config.wheel_multiplier, 1.0);
+        assert!(!config.invert_wheel);
+        assert_eq!(config.page_overlap_lines, 1);
     }
 
     #[test]
@@ -419,6 +792,27 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn validate_rejects_invalid_wheel_multiplier() {
+        let mut config = AppConfig::default();
+        config.scroll.wheel_multiplier = 0.0;
+        assert!(config.validate().is_err());
+
+        config.scroll.wheel_multiplier = -2.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_invalid_key_hold_settings() {
+        let mut config = AppConfig::default();
+        config.scroll.key_hold_speed = 0.0;
+        assert!(config.validate().is_err());
+
+        config.scroll.key_hold_speed = 600.0;
+        config.scroll.key_hold_acceleration = -1.0;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn validate_rejects_invalid_text_size() {
         let mut config = AppConfig::default();
@@ -500,9 +894,11 @@ mod tests {
 
     #[test]
     fn test_search_history_config() {
-        let mut config = AppConfig::default();
-        config.search_history = vec!["foo".to_string(), "bar".to_string()];
-        config.max_history_items = 10;
+        let config = AppConfig {
+            search_history: vec!["foo".to_string(), "bar".to_string()],
+            max_history_items: 10,
+            ..Default::default()
+        };
 
         let path = "test_config_history.ron";
         config.save_to_file(path).expect("Failed to save config");
@@ -516,8 +912,10 @@ mod tests {
 
     #[test]
     fn test_clear_search_history() {
-        let mut config = AppConfig::default();
-        config.search_history = vec!["foo".to_string(), "bar".to_string()];
+        let mut config = AppConfig {
+            search_history: vec!["foo".to_string(), "bar".to_string()],
+            ..Default::default()
+        };
 
         // Verify initial state
         assert_eq!(config.search_history.len(), 2);
@@ -536,19 +934,70 @@ mod tests {
         fs::remove_file(path).ok();
     }
 
+    #[test]
+    fn session_round_trips_through_config_file() {
+        let config = AppConfig {
+            session: Some(SessionState {
+                file: "/path/to/a.md".to_string(),
+                queue: vec!["/path/to/a.md".to_string(), "/path/to/b.md".to_string()],
+                scroll_y: 420.0,
+                show_toc: true,
+                text_size: 22.0,
+            }),
+            ..AppConfig::default()
+        };
+        let path = "test_config_session.ron";
+        config.save_to_file(path).expect("Failed to save config");
+        let loaded = AppConfig::load_from_file(path).expect("Failed to load config");
+        fs::remove_file(path).ok();
+        assert_eq!(loaded.session, config.session);
+    }
+
     #[test]
     fn test_recent_files_config() {
-        let mut config = AppConfig::default();
-        config.recent_files = vec!["/path/to/a.md".to_string(), "/path/to/b.md".to_string()];
-        config.max_recent_files = 5;
+        let config = AppConfig {
+            recent_files: vec![
+                PathBuf::from("/path/to/a.md"),
+                PathBuf::from("/path/to/b.md"),
+            ],
+            max_recent_files: 5,
+            ..Default::default()
+        };
 
         let path = "test_config_recent_files.ron";
         config.save_to_file(path).expect("Failed to save config");
 
         let loaded = AppConfig::load_from_file(path).expect("Failed to load config");
-        assert_eq!(loaded.recent_files, vec!["/path/to/a.md", "/path/to/b.md"]);
+        assert_eq!(loaded.recent_files, config.recent_files);
         assert_eq!(loaded.max_recent_files, 5);
 
         fs::remove_file(path).ok();
     }
+
+    #[test]
+    fn test_add_recent_file() {
+        let mut config = AppConfig {
+            max_recent_files: 2,
+            ..Default::default()
+        };
+        config.add_recent_file(Path::new("/a.md"));
+        config.add_recent_file(Path::new("/b.md"));
+        config.add_recent_file(Path::new("/a.md"));
+        assert_eq!(
+            config.recent_files,
+            vec![PathBuf::from("/a.md"), PathBuf::from("/b.md")]
+        );
+        config.add_recent_file(Path::new("/c.md"));
+        assert_eq!(
+            config.recent_files,
+            vec![PathBuf::from("/c.md"), PathBuf::from("/a.md")]
+        );
+
+        // Without a configured limit the default applies
+        let mut config = AppConfig::default();
+        for index in 0..20 {
+            config.add_recent_file(Path::new(&format!("/{}.md", index)));
+        }
+        assert_eq!(config.recent_files.len(), default_max_recent_files());
+    }
 }
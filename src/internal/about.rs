@@ -0,0 +1,126 @@
+//! About overlay contents and the diagnostics copied for bug reports
+//!
+//! The About overlay (menu "About Markdown Viewer", or a click on the
+//! version in the status bar) lists the version, how the viewer was built,
+//! the loaded themes and where the config is read from. "Copy Diagnostics"
+//! puts the same facts on the clipboard as plain text to paste into an issue.
+
+use std::path::{Path, PathBuf};
+
+/// Facts about the running viewer and its environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostics {
+    pub version: &'static str,
+    /// "debug" or "release"
+    pub profile: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// Name of the active theme
+    pub theme: String,
+    /// Number of themes in the registry
+    pub theme_count: usize,
+    /// Absolute path of the config file
+    pub config_path: PathBuf,
+    /// Whether the config file exists (defaults are used otherwise)
+    pub config_found: bool,
+    pub working_dir: PathBuf,
+}
+
+impl Diagnostics {
+    /// Gather the diagnostics; `config_file` is resolved against the
+    /// working directory
+    pub fn collect(theme: &str, theme_count: usize, config_file: &Path) -> Self {
+        let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let config_path = working_dir.join(config_file);
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            profile: match cfg!(debug_assertions) {
+                true => "debug",
+                false => "release",
+            },
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            theme: theme.to_string(),
+            theme_count,
+            config_found: config_path.exists(),
+            config_path,
+            working_dir,
+        }
+    }
+
+    /// Label and value of each line shown in the overlay
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Version", self.version.to_string()),
+            (
+                "Build",
+                format!("{} ({}-{})", self.profile, self.os, self.arch),
+            ),
+            (
+                "Themes",
+                format!("{} loaded, active: {}", self.theme_count, self.theme),
+            ),
+            (
+                "Config",
+                format!(
+                    "{}{}",
+                    self.config_path.display(),
+                    match self.config_found {
+                        true => "",
+                        false => " (not found, using defaults)",
+                    }
+                ),
+            ),
+            ("Working Directory", self.working_dir.display().to_string()),
+        ]
+    }
+
+    /// Plain-text report for the clipboard
+    pub fn report(&self) -> String {
+        std::iter::once("Markdown Viewer diagnostics".to_string())
+            .chain(
+                self.rows()
+                    .into_iter()
+                    .map(|(label, value)| format!("{}: {}", label, value)),
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_every_row() {
+        let diagnostics = Diagnostics {
+            version: "1.2.3",
+            profile: "release",
+            os: "linux",
+            arch: "x86_64",
+            theme: "Dracula Dark".to_string(),
+            theme_count: 12,
+            config_path: PathBuf::from("/home/me/config.ron"),
+            config_found: false,
+            working_dir: PathBuf::from("/home/me"),
+        };
+        assert_eq!(
+            diagnostics.report(),
+            "Markdown Viewer diagnostics\n\
+             Version: 1.2.3\n\
+             Build: release (linux-x86_64)\n\
+             Themes: 12 loaded, active: Dracula Dark\n\
+             Config: /home/me/config.ron (not found, using defaults)\n\
+             Working Directory: /home/me"
+        );
+    }
+
+    #[test]
+    fn config_path_is_resolved_against_the_working_directory() {
+        let diagnostics = Diagnostics::collect("Light", 2, Path::new("config.ron"));
+        assert!(diagnostics.config_path.is_absolute());
+        assert!(diagnostics.config_path.ends_with("config.ron"));
+        assert_eq!(diagnostics.version, env!("CARGO_PKG_VERSION"));
+    }
+}
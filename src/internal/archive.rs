@@ -0,0 +1,217 @@
+//! Markdown files inside zip and epub archives
+//!
+//! A file inside an archive is addressed as `<archive>!/<entry>`, e.g.
+//! `book.zip!/docs/intro.md`. Such paths open like regular files, and
+//! relative images in them resolve to entries of the same archive, which the
+//! default image provider reads through [`ArchivePath::read`].
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use zip::ZipArchive;
+
+use super::file_handling::{DIRECTORY_INDEX_FILES, is_supported_extension};
+
+/// Separator between the archive path and the entry inside it
+pub const ARCHIVE_SEPARATOR: &str = "!/";
+
+/// Extensions of archives that can be browsed (epub files are zip archives)
+pub const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "epub"];
+
+/// A file inside a zip or epub archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivePath {
+    /// The archive on disk
+    pub archive: PathBuf,
+    /// Entry name inside the archive, with `/` separators
+    pub entry: String,
+}
+
+impl ArchivePath {
+    pub fn new(archive: impl Into<PathBuf>, entry: impl Into<String>) -> Self {
+        Self {
+            archive: archive.into(),
+            entry: entry.into(),
+        }
+    }
+
+    /// Split `book.zip!/docs/intro.md` into the archive and the entry
+    ///
+    /// Returns `None` for paths that do not point into a zip or epub archive.
+    pub fn parse(path: &str) -> Option<Self> {
+        path.match_indices(ARCHIVE_SEPARATOR)
+            .find_map(|(index, _)| {
+                let archive = Path::new(&path[..index]);
+                let entry = path[index + ARCHIVE_SEPARATOR.len()..].trim_start_matches('/');
+                (is_archive(archive) && !entry.is_empty()).then(|| Self::new(archive, entry))
+            })
+    }
+
+    /// The `<archive>!/<entry>` path used for opening and image resolution
+    pub fn to_path_buf(&self) -> PathBuf {
+        PathBuf::from(self.to_string())
+    }
+
+    /// Uncompressed size of the entry in bytes
+    pub fn size(&self) -> Result<u64> {
+        let mut archive = open_archive(&self.archive)?;
+        let file = archive
+            .by_name(&self.entry)
+            .with_context(|| format!("Failed to read file '{}'", self))?;
+        Ok(file.size())
+    }
+
+    /// Read the whole entry
+    pub fn read(&self) -> Result<Vec<u8>> {
+        debug!("Reading archive entry: {}", self);
+        let mut archive = open_archive(&self.archive)?;
+        let mut file = archive
+            .by_name(&self.entry)
+            .with_context(|| format!("Failed to read file '{}'", self))?;
+        let mut bytes = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut bytes)
+            .with_context(|| format!("Failed to read file '{}'", self))?;
+        Ok(bytes)
+    }
+
+    /// Whether the archive exists and contains the entry
+    pub fn exists(&self) -> bool {
+        open_archive(&self.archive)
+            .map(|mut archive| archive.by_name(&self.entry).is_ok())
+            .unwrap_or(false)
+    }
+}
+
+impl std::fmt::Display for ArchivePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.archive.display(),
+            ARCHIVE_SEPARATOR,
+            self.entry
+        )
+    }
+}
+
+/// Whether `path` has a zip or epub extension
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            ARCHIVE_EXTENSIONS
+                .iter()
+                .any(|archive_ext| archive_ext.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// The file on disk holding `path`: the archive for archive entries,
+/// otherwise `path` itself
+///
+/// Used to watch the archive when a file inside it is open.
+pub fn containing_file(path: &Path) -> PathBuf {
+    match ArchivePath::parse(&path.to_string_lossy()) {
+        Some(archive_path) => archive_path.archive,
+        None => path.to_path_buf(),
+    }
+}
+
+fn open_archive(archive: &Path) -> Result<ZipArchive<File>> {
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to read file '{}'", archive.display()))?;
+    ZipArchive::new(file).with_context(|| format!("'{}' is not a zip archive", archive.display()))
+}
+
+/// Files inside `archive` with a supported extension, sorted by entry name
+pub fn list_entries(archive: &Path, supported_extensions: &[String]) -> Result<Vec<ArchivePath>> {
+    let archive_file = open_archive(archive)?;
+    let mut entries: Vec<ArchivePath> = archive_file
+        .file_names()
+        .filter(|name| !name.ends_with('/') && is_supported_extension(name, supported_extensions))
+        .map(|name| ArchivePath::new(archive, name))
+        .collect();
+    entries.sort_by(|a, b| a.entry.cmp(&b.entry));
+    debug!(
+        "Found {} supported files in {}",
+        entries.len(),
+        archive.display()
+    );
+    Ok(entries)
+}
+
+/// Find the index file (README.md, then index.md) at the root of an archive
+pub fn find_archive_index(archive: &Path) -> Option<ArchivePath> {
+    DIRECTORY_INDEX_FILES
+        .iter()
+        .map(|name| ArchivePath::new(archive, *name))
+        .find(ArchivePath::exists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    /// Write a zip archive holding `files` (name, content) to `path`
+    fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn archive_paths_split_at_the_archive() {
+        assert_eq!(
+            ArchivePath::parse("book.zip!/docs/intro.md"),
+            Some(ArchivePath::new("book.zip", "docs/intro.md"))
+        );
+        assert_eq!(
+            ArchivePath::parse("/tmp/novel.EPUB!/ch1.md"),
+            Some(ArchivePath::new("/tmp/novel.EPUB", "ch1.md"))
+        );
+        assert_eq!(ArchivePath::parse("notes!/intro.md"), None);
+        assert_eq!(ArchivePath::parse("book.zip!/"), None);
+        assert_eq!(ArchivePath::parse("docs/intro.md"), None);
+    }
+
+    #[test]
+    fn entries_are_listed_and_read_from_archive() {
+        let dir = std::env::temp_dir().join("mdv_archive_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let zip_path = dir.join("book.zip");
+        write_zip(
+            &zip_path,
+            &[
+                ("docs/intro.md", "# Intro"),
+                ("README.md", "# Book"),
+                ("cover.png", "png"),
+            ],
+        );
+
+        let entries = list_entries(&zip_path, &["md".to_string()]).unwrap();
+        let intro = ArchivePath::new(&zip_path, "docs/intro.md");
+        let content = intro.read().unwrap();
+        let index = find_archive_index(&zip_path);
+        let missing_exists = ArchivePath::new(&zip_path, "missing.md").exists();
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(
+            entries,
+            vec![
+                ArchivePath::new(&zip_path, "README.md"),
+                ArchivePath::new(&zip_path, "docs/intro.md"),
+            ]
+        );
+        assert_eq!(content, b"# Intro");
+        assert_eq!(index, Some(ArchivePath::new(&zip_path, "README.md")));
+        assert!(!missing_exists);
+    }
+}
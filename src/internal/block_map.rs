@@ -0,0 +1,356 @@
+//! Vertical positions of the document's lines and top-level blocks
+//!
+//! Positions come from the rendered document: after each layout the viewer
+//! measures where every top-level block landed ([`MeasuredLayout`]), and
+//! lines inside a block are spread over its height. Until a document has been
+//! laid out once, a per-line height estimate stands in. Search, go-to-line,
+//! the TOC, bookmarks and marks all convert between source positions and
+//! scroll offsets through this one map, the scrollbar is sized by it, and page
+//! navigation snaps to its block boundaries so a new page does not start
+//! halfway through a paragraph, code block or image.
+
+/// A top-level block and where it is laid out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockExtent {
+    /// First source line (0-based)
+    pub start_line: usize,
+    /// Last source line (0-based, inclusive)
+    pub end_line: usize,
+    /// Y position of the block's top edge
+    pub top: f32,
+    /// Y position of the block's bottom edge
+    pub bottom: f32,
+}
+
+/// A point in the document, kept as a line so it survives layout changes
+/// (zoom, resizing, images loading, reloads)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LinePosition {
+    /// Source line (0-based)
+    pub line: usize,
+    /// How far down the line the point is, from 0.0 (top) to 1.0 (bottom)
+    pub fraction: f32,
+}
+
+/// Source lines and top-level blocks with their Y positions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockMap {
+    /// Y position of each source line's top edge
+    line_tops: Vec<f32>,
+    /// Y position just below the last line
+    bottom: f32,
+    blocks: Vec<BlockExtent>,
+}
+
+impl BlockMap {
+    /// Lay out blocks spanning `ranges` (0-based inclusive source lines)
+    ///
+    /// `line_tops[n]` is the Y position of line `n` and `content_height` the
+    /// end of the last line; `offset` is added to every position.
+    pub fn from_line_ranges(
+        ranges: impl IntoIterator<Item = (usize, usize)>,
+        line_tops: &[f32],
+        content_height: f32,
+        offset: f32,
+    ) -> Self {
+        let mut map = Self {
+            line_tops: line_tops.iter().map(|top| top + offset).collect(),
+            bottom: content_height + offset,
+            blocks: Vec::new(),
+        };
+        map.blocks = ranges
+            .into_iter()
+            .map(|(start_line, end_line)| BlockExtent {
+                start_line,
+                end_line,
+                top: map.y_for_line(start_line),
+                bottom: map.y_for_line(end_line + 1),
+            })
+            .collect();
+        map
+    }
+
+    /// One block per source line (plain text)
+    pub fn from_lines(line_tops: &[f32], content_height: f32, offset: f32) -> Self {
+        Self::from_line_ranges(
+            (0..line_tops.len()).map(|line| (line, line)),
+            line_tops,
+            content_height,
+            offset,
+        )
+    }
+
+    /// Y position just below the last line
+    pub fn bottom(&self) -> f32 {
+        self.bottom
+    }
+
+    pub fn blocks(&self) -> &[BlockExtent] {
+        &self.blocks
+    }
+
+    /// Y position of the top of `line` (0-based); past the end is the bottom
+    pub fn y_for_line(&self, line: usize) -> f32 {
+        self.line_tops.get(line).copied().unwrap_or(self.bottom)
+    }
+
+    /// Y position of the line holding byte `offset` of `content`
+    pub fn y_for_offset(&self, content: &str, offset: usize) -> f32 {
+        let line = content.as_bytes()[..offset.min(content.len())]
+            .iter()
+            .filter(|&&byte| byte == b'\n')
+            .count();
+        self.y_for_line(line)
+    }
+
+    /// Line (0-based) shown at Y position `y`
+    pub fn line_at_y(&self, y: f32) -> usize {
+        self.line_tops
+            .partition_point(|&top| top <= y)
+            .saturating_sub(1)
+    }
+
+    /// Line and fraction of its height at Y position `y`
+    pub fn position_at(&self, y: f32) -> LinePosition {
+        let line = self.line_at_y(y);
+        let top = self.y_for_line(line);
+        let height = self.y_for_line(line + 1) - top;
+        LinePosition {
+            line,
+            fraction: match height > 0.0 {
+                true => ((y - top) / height).clamp(0.0, 1.0),
+                false => 0.0,
+            },
+        }
+    }
+
+    /// Y position of `position` in the current layout
+    pub fn y_for_position(&self, position: LinePosition) -> f32 {
+        let top = self.y_for_line(position.line);
+        top + (self.y_for_line(position.line + 1) - top) * position.fraction
+    }
+
+    /// Scroll position one page below `scroll_y`
+    ///
+    /// The block cut off at the bottom of the viewport becomes the top of the
+    /// next page, with `overlap` pixels of the previous page kept above it.
+    /// Blocks taller than the viewport fall back to scrolling by `fallback`.
+    pub fn page_down_target(
+        &self,
+        scroll_y: f32,
+        viewport_height: f32,
+        overlap: f32,
+        fallback: f32,
+    ) -> f32 {
+        let bottom_edge = scroll_y + viewport_height;
+        let snapped = self
+            .blocks
+            .iter()
+            .find(|block| block.bottom > bottom_edge)
+            .map(|block| block.top.min(bottom_edge))
+            .unwrap_or(bottom_edge)
+            - overlap;
+        match snapped > scroll_y + overlap {
+            true => snapped,
+            false => scroll_y + fallback,
+        }
+    }
+
+    /// Scroll position one page above `scroll_y`
+    ///
+    /// The block cut off at the top of the viewport ends up at the bottom of
+    /// the new page with `overlap` pixels below it, and the new page starts
+    /// at a block boundary. Blocks taller than the viewport fall back to
+    /// scrolling by `fallback`.
+    pub fn page_up_target(
+        &self,
+        scroll_y: f32,
+        viewport_height: f32,
+        overlap: f32,
+        fallback: f32,
+    ) -> f32 {
+        let raw = scroll_y + overlap - viewport_height;
+        let snapped = self
+            .blocks
+            .iter()
+            .map(|block| block.top)
+            .find(|&top| top >= raw)
+            .unwrap_or(raw);
+        match snapped < scroll_y - overlap {
+            true => snapped.max(0.0),
+            false => (scroll_y - fallback).max(0.0),
+        }
+    }
+}
+
+/// Where the rendered top-level blocks of a document were laid out
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeasuredLayout {
+    /// Content revision the blocks were rendered from
+    pub revision: u64,
+    /// Source lines (0-based, inclusive) of each block
+    pub ranges: Vec<(usize, usize)>,
+    /// Top and bottom edge of each block, relative to the content's top edge
+    pub extents: Vec<(f32, f32)>,
+    /// Height of the whole content, margins included
+    pub height: f32,
+}
+
+impl MeasuredLayout {
+    /// Whether `other` places any block differently, ignoring sub-pixel noise
+    pub fn differs_from(&self, other: &Self) -> bool {
+        let moved = |a: f32, b: f32| (a - b).abs() > 0.5;
+        self.revision != other.revision
+            || self.ranges != other.ranges
+            || self.extents.len() != other.extents.len()
+            || moved(self.height, other.height)
+            || self
+                .extents
+                .iter()
+                .zip(&other.extents)
+                .any(|(a, b)| moved(a.0, b.0) || moved(a.1, b.1))
+    }
+
+    /// Line and block positions of a document with `line_count` lines
+    ///
+    /// Lines inside a block are spread evenly over its height; blank lines
+    /// between blocks sit at the top of the next one.
+    pub fn block_map(&self, line_count: usize, offset: f32) -> BlockMap {
+        let mut tops: Vec<Option<f32>> = vec![None; line_count];
+        for (&(start, end), &(top, bottom)) in self.ranges.iter().zip(&self.extents) {
+            let step = (bottom - top) / (end - start + 1) as f32;
+            for (line, slot) in tops.iter_mut().enumerate().take(end + 1).skip(start) {
+                *slot = Some(top + step * (line - start) as f32);
+            }
+        }
+        let mut next = self.height;
+        let mut line_tops: Vec<f32> = tops
+            .into_iter()
+            .rev()
+            .map(|top| {
+                next = top.unwrap_or(next);
+                next + offset
+            })
+            .collect();
+        line_tops.reverse();
+
+        BlockMap {
+            line_tops,
+            bottom: self.height + offset,
+            blocks: self
+                .ranges
+                .iter()
+                .zip(&self.extents)
+                .map(|(&(start_line, end_line), &(top, bottom))| BlockExtent {
+                    start_line,
+                    end_line,
+                    top: top + offset,
+                    bottom: bottom + offset,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Blocks of 100px: lines 0-1, 2-3, 4-5, 6-7, each line 50px
+    fn map() -> BlockMap {
+        let line_tops: Vec<f32> = (0..8).map(|line| line as f32 * 50.0).collect();
+        BlockMap::from_line_ranges([(0, 1), (2, 3), (4, 5), (6, 7)], &line_tops, 400.0, 0.0)
+    }
+
+    #[test]
+    fn page_down_starts_at_the_cut_block() {
+        // Viewport 0..250 cuts the block at 200..300
+        assert_eq!(map().page_down_target(0.0, 250.0, 20.0, 200.0), 180.0);
+        // Viewport ends exactly on a boundary
+        assert_eq!(map().page_down_target(0.0, 200.0, 20.0, 160.0), 180.0);
+    }
+
+    #[test]
+    fn page_up_starts_at_a_block_boundary() {
+        // Raw target 300 + 20 - 250 = 70 snaps to the block at 100
+        assert_eq!(map().page_up_target(300.0, 250.0, 20.0, 200.0), 100.0);
+        assert_eq!(map().page_up_target(100.0, 250.0, 20.0, 200.0), 0.0);
+    }
+
+    #[test]
+    fn lines_offsets_and_positions_round_trip() {
+        let map = map();
+        let content = "a\nb\nc\nd\ne\nf\ng\nh";
+        assert_eq!(map.y_for_line(3), 150.0);
+        assert_eq!(map.y_for_line(42), 400.0);
+        assert_eq!(map.y_for_offset(content, content.find('d').unwrap()), 150.0);
+        assert_eq!(map.line_at_y(0.0), 0);
+        assert_eq!(map.line_at_y(175.0), 3);
+        assert_eq!(map.line_at_y(10_000.0), 7);
+    }
+
+    #[test]
+    fn windows_line_endings_land_on_the_same_lines() {
+        let map = map();
+        let lf = "a\nb\nc\nd\ne\nf\ng\nh";
+        let (crlf, _) = crate::internal::text_format::prepare(lf.replace('\n', "\r\n"));
+        for target in ['b', 'e', 'h'] {
+            assert_eq!(
+                map.y_for_offset(&crlf, crlf.find(target).unwrap()),
+                map.y_for_offset(lf, lf.find(target).unwrap())
+            );
+        }
+        assert_eq!(map.y_for_offset(&crlf, crlf.find('h').unwrap()), 350.0);
+    }
+
+    #[test]
+    fn tall_blocks_fall_back_to_fixed_steps() {
+        let line_tops = [0.0, 1000.0];
+        let map = BlockMap::from_line_ranges([(0, 0), (1, 1)], &line_tops, 1100.0, 0.0);
+        assert_eq!(map.page_down_target(0.0, 250.0, 20.0, 200.0), 200.0);
+        assert_eq!(map.page_up_target(500.0, 250.0, 20.0, 200.0), 300.0);
+    }
+
+    #[test]
+    fn measured_blocks_place_their_lines() {
+        // A heading on line 0, a blank line, then a paragraph on lines 2-5
+        let layout = MeasuredLayout {
+            revision: 1,
+            ranges: vec![(0, 0), (2, 5)],
+            extents: vec![(0.0, 40.0), (56.0, 136.0)],
+            height: 144.0,
+        };
+        let map = layout.block_map(7, 32.0);
+        assert_eq!(map.y_for_line(0), 32.0);
+        // The blank line starts where the paragraph does
+        assert_eq!(map.y_for_line(1), 88.0);
+        assert_eq!(map.y_for_line(4), 128.0);
+        // Past the last block is the end of the content
+        assert_eq!(map.y_for_line(6), 176.0);
+        assert_eq!(map.bottom(), 176.0);
+        assert_eq!(map.blocks()[1].bottom, 168.0);
+        assert_eq!(map.line_at_y(130.0), 4);
+
+        let mut shifted = layout.clone();
+        shifted.extents[1].1 += 0.2;
+        assert!(!layout.differs_from(&shifted));
+        shifted.height += 20.0;
+        assert!(layout.differs_from(&shifted));
+    }
+
+    #[test]
+    fn positions_survive_a_new_layout() {
+        let position = map().position_at(175.0);
+        assert_eq!(
+            position,
+            LinePosition {
+                line: 3,
+                fraction: 0.5
+            }
+        );
+        // Text twice as large: every line is 100px
+        let line_tops: Vec<f32> = (0..8).map(|line| line as f32 * 100.0).collect();
+        let zoomed = BlockMap::from_line_ranges([(0, 7)], &line_tops, 800.0, 0.0);
+        assert_eq!(zoomed.y_for_position(position), 350.0);
+    }
+}
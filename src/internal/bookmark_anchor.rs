@@ -0,0 +1,259 @@
+//! Content anchors that keep bookmarks on their text across edits
+//!
+//! A bookmark is a line number, so an edit above it used to leave it
+//! pointing at whatever moved into its place. Each bookmark now also keeps a
+//! [`LineAnchor`]: the headings the line sits under and a hash of the start
+//! of its text. After the file is reloaded, [`follow`] looks the anchor up in
+//! the new content, nearest to the old line within the same section; a line
+//! whose text is unique in the document is found in any section. Bookmarks
+//! whose text is gone stay on their old line and are reported as stale.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Characters of a line's trimmed text that go into its hash
+const PREFIX_CHARS: usize = 80;
+
+/// What a bookmarked line was attached to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineAnchor {
+    /// Text of the headings enclosing the line, outermost first
+    pub heading_path: Vec<String>,
+    /// Hash of the start of the line's trimmed text
+    pub text_hash: u64,
+}
+
+/// Text of an ATX heading and its level, if `line` is one
+fn heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    let rest = &trimmed[level..];
+    match (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        true => Some((
+            level,
+            rest.trim().trim_end_matches('#').trim_end().to_string(),
+        )),
+        false => None,
+    }
+}
+
+/// FNV-1a hash of the start of the trimmed line, stable across builds so
+/// anchors can be stored in the state file
+fn text_hash(line: &str) -> u64 {
+    let prefix: String = line.trim().chars().take(PREFIX_CHARS).collect();
+    prefix.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Section and text hash of every line of a document
+#[derive(Debug, Clone, Default)]
+pub struct AnchorIndex {
+    /// Distinct heading paths, referenced by index from `lines`
+    sections: Vec<Vec<String>>,
+    /// Section index and text hash of each line (0-based)
+    lines: Vec<(usize, u64)>,
+}
+
+impl AnchorIndex {
+    /// Index `content`, skipping `#` lines inside fenced code blocks
+    pub fn new(content: &str) -> Self {
+        let mut index = Self {
+            sections: vec![Vec::new()],
+            lines: Vec::new(),
+        };
+        let mut open: Vec<(usize, String)> = Vec::new();
+        let mut fence: Option<char> = None;
+        let mut section = 0;
+
+        for line in content.lines() {
+            let marker = line.trim_start().chars().next();
+            let is_fence =
+                line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~");
+            match (fence, is_fence) {
+                (None, true) => fence = marker,
+                (Some(open_fence), true) if marker == Some(open_fence) => fence = None,
+                _ => {}
+            }
+
+            match heading(line).filter(|_| fence.is_none() && !is_fence) {
+                Some((level, text)) => {
+                    // The heading itself belongs to its parents' section
+                    open.retain(|(open_level, _)| *open_level < level);
+                    let parents = index.push_section(&open);
+                    index.lines.push((parents, text_hash(line)));
+                    open.push((level, text));
+                    section = index.push_section(&open);
+                }
+                None => index.lines.push((section, text_hash(line))),
+            }
+        }
+        index
+    }
+
+    /// Record the heading path `open`; returns its section index
+    fn push_section(&mut self, open: &[(usize, String)]) -> usize {
+        let path: Vec<String> = open.iter().map(|(_, text)| text.clone()).collect();
+        match self.sections.last() == Some(&path) {
+            true => self.sections.len() - 1,
+            false => {
+                self.sections.push(path);
+                self.sections.len() - 1
+            }
+        }
+    }
+
+    /// Number of indexed lines
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Whether the document has no lines
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Anchor for `line` (1-based)
+    pub fn anchor(&self, line: usize) -> Option<LineAnchor> {
+        let &(section, text_hash) = self.lines.get(line.checked_sub(1)?)?;
+        Some(LineAnchor {
+            heading_path: self.sections[section].clone(),
+            text_hash,
+        })
+    }
+
+    /// Line (1-based) `anchor` points at now, nearest to `hint`
+    pub fn resolve(&self, anchor: &LineAnchor, hint: usize) -> Option<usize> {
+        let matching: Vec<usize> = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, hash))| *hash == anchor.text_hash)
+            .map(|(index, _)| index + 1)
+            .collect();
+        let in_section = matching
+            .iter()
+            .copied()
+            .filter(|&line| self.sections[self.lines[line - 1].0] == anchor.heading_path)
+            .min_by_key(|&line| line.abs_diff(hint));
+        match (in_section, matching.as_slice()) {
+            (Some(line), _) => Some(line),
+            // The section was renamed or the text moved elsewhere
+            (None, &[line]) if anchor.text_hash != text_hash("") => Some(line),
+            (None, _) => None,
+        }
+    }
+}
+
+/// Bookmarks after following their anchors into a new version of the file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Followed {
+    /// Bookmarked lines (1-based), sorted
+    pub bookmarks: Vec<usize>,
+    /// Anchor of each bookmark, keyed by its line
+    pub anchors: BTreeMap<usize, LineAnchor>,
+    /// Bookmarks whose text could not be found
+    pub stale: BTreeSet<usize>,
+}
+
+/// Anchors for `bookmarks` in the document `index` was built from; known
+/// anchors are kept
+pub fn anchors_for(
+    bookmarks: &[usize],
+    anchors: &BTreeMap<usize, LineAnchor>,
+    index: &AnchorIndex,
+) -> BTreeMap<usize, LineAnchor> {
+    bookmarks
+        .iter()
+        .filter_map(|&line| {
+            let anchor = anchors.get(&line).cloned().or_else(|| index.anchor(line))?;
+            Some((line, anchor))
+        })
+        .collect()
+}
+
+/// Move `bookmarks` from the document `old` indexes to the one `new` does
+///
+/// Bookmarks without a known anchor are anchored in `old` first. Stale
+/// bookmarks keep their anchor so they are found again if the text returns;
+/// unanchored bookmarks past the end of `old` are dropped.
+pub fn follow(
+    bookmarks: &[usize],
+    anchors: &BTreeMap<usize, LineAnchor>,
+    old: &AnchorIndex,
+    new: &AnchorIndex,
+) -> Followed {
+    let mut followed = Followed::default();
+    if new.is_empty() {
+        return followed;
+    }
+    let mut resolved = BTreeSet::new();
+    for (line, anchor) in anchors_for(bookmarks, anchors, old) {
+        match new.resolve(&anchor, line) {
+            Some(line) => {
+                resolved.insert(line);
+                followed.anchors.insert(line, anchor);
+            }
+            None => {
+                let kept = line.min(new.len());
+                followed.stale.insert(kept);
+                followed.anchors.entry(kept).or_insert(anchor);
+            }
+        }
+    }
+    // A line some other bookmark resolved to is not stale
+    followed.stale.retain(|line| !resolved.contains(line));
+    followed.bookmarks = followed.anchors.keys().copied().collect();
+    followed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# Guide\n\nIntro text\n\n## Install\n\nRun the installer\n\n```sh\n# not a heading\n```\n\n## Usage\n\nOpen a file\n";
+
+    #[test]
+    fn bookmarks_follow_their_text_past_edits_above() {
+        let old = AnchorIndex::new(DOC);
+        let edited = DOC.replace("Intro text\n", "Intro text\nMore intro\n\nEven more\n");
+        let new = AnchorIndex::new(&edited);
+
+        let followed = follow(&[7, 15], &BTreeMap::new(), &old, &new);
+        assert_eq!(followed.bookmarks, vec![10, 18]);
+        assert!(followed.stale.is_empty());
+        assert_eq!(
+            followed.anchors[&18].heading_path,
+            vec!["Guide".to_string(), "Usage".to_string()]
+        );
+        // The fenced `#` line does not open a section
+        assert_eq!(old.anchor(10).unwrap().heading_path.len(), 2);
+    }
+
+    #[test]
+    fn removed_text_leaves_a_stale_bookmark() {
+        let old = AnchorIndex::new(DOC);
+        let new = AnchorIndex::new(&DOC.replace("Run the installer", "Use the package"));
+        let followed = follow(&[7], &BTreeMap::new(), &old, &new);
+        assert_eq!(followed.bookmarks, vec![7]);
+        assert_eq!(followed.stale, BTreeSet::from([7]));
+
+        // The kept anchor finds the text again once it is back
+        let restored = AnchorIndex::new(DOC);
+        let again = follow(&followed.bookmarks, &followed.anchors, &new, &restored);
+        assert_eq!(again.bookmarks, vec![7]);
+        assert!(again.stale.is_empty());
+    }
+
+    #[test]
+    fn unique_text_is_found_in_a_renamed_section() {
+        let old = AnchorIndex::new(DOC);
+        let new = AnchorIndex::new(&DOC.replace("## Usage", "## Getting Started"));
+        let followed = follow(&[15], &BTreeMap::new(), &old, &new);
+        assert_eq!(followed.bookmarks, vec![15]);
+        assert!(followed.stale.is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+//! Selection and scrolling inside the bookmarks overlay
+//!
+//! The overlay is capped to the window height; when it holds more bookmarks
+//! than fit, its list scrolls on its own (mouse wheel) and follows the
+//! selection moved with Up/Down. Enter jumps to the selected bookmark and
+//! Delete removes it. Each row shows the bookmarked line's text and the
+//! heading of the section it is in.
+
+use crate::internal::style::{BOOKMARK_ROW_HEIGHT, BOOKMARKS_PANEL_CHROME, BOOKMARKS_PANEL_MARGIN};
+
+/// Characters of a bookmarked line shown in its row
+const SNIPPET_CHARS: usize = 48;
+
+/// Selected row and scroll offset of the bookmarks list
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BookmarkListState {
+    /// Index of the selected bookmark
+    pub selected: usize,
+    /// Pixels the list is scrolled down by
+    pub scroll_y: f32,
+}
+
+/// Height available to the list rows in a window `viewport_height` tall
+pub fn list_height(viewport_height: f32) -> f32 {
+    (viewport_height - 2.0 * BOOKMARKS_PANEL_MARGIN - BOOKMARKS_PANEL_CHROME)
+        .max(BOOKMARK_ROW_HEIGHT)
+}
+
+/// Trimmed text of `line` (1-based) in `content`, shortened to
+/// [`SNIPPET_CHARS`]; `None` for a blank or missing line
+pub fn line_snippet(content: &str, line: usize) -> Option<String> {
+    let text = content.lines().nth(line.checked_sub(1)?)?.trim();
+    match text.char_indices().nth(SNIPPET_CHARS) {
+        _ if text.is_empty() => None,
+        Some((cut, _)) => Some(format!("{}…", text[..cut].trim_end())),
+        None => Some(text.to_string()),
+    }
+}
+
+impl BookmarkListState {
+    /// Largest scroll offset for `len` rows
+    pub fn max_scroll(len: usize, viewport_height: f32) -> f32 {
+        (len as f32 * BOOKMARK_ROW_HEIGHT - list_height(viewport_height)).max(0.0)
+    }
+
+    /// Move the selection by `delta` rows and scroll it into view
+    pub fn move_selection(&mut self, delta: isize, len: usize, viewport_height: f32) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+        self.scroll_into_view(len, viewport_height);
+    }
+
+    /// Keep the selection and scroll offset valid after the list changed
+    pub fn clamp(&mut self, len: usize, viewport_height: f32) {
+        self.selected = self.selected.min(len.saturating_sub(1));
+        self.scroll_into_view(len, viewport_height);
+    }
+
+    /// Scroll the list by `delta` pixels (positive scrolls down)
+    pub fn scroll_by(&mut self, delta: f32, len: usize, viewport_height: f32) {
+        self.scroll_y = (self.scroll_y + delta).clamp(0.0, Self::max_scroll(len, viewport_height));
+    }
+
+    /// Scroll just enough for the selected row to be fully visible
+    fn scroll_into_view(&mut self, len: usize, viewport_height: f32) {
+        let top = self.selected as f32 * BOOKMARK_ROW_HEIGHT;
+        let bottom = top + BOOKMARK_ROW_HEIGHT;
+        let visible = list_height(viewport_height);
+        self.scroll_y = match self.scroll_y {
+            y if top < y => top,
+            y if bottom > y + visible => bottom - visible,
+            y => y,
+        }
+        .clamp(0.0, Self::max_scroll(len, viewport_height));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_scrolls_into_view() {
+        // Room for four rows
+        let viewport =
+            4.0 * BOOKMARK_ROW_HEIGHT + 2.0 * BOOKMARKS_PANEL_MARGIN + BOOKMARKS_PANEL_CHROME;
+        let mut list = BookmarkListState::default();
+
+        list.move_selection(5, 10, viewport);
+        assert_eq!(list.selected, 5);
+        assert_eq!(list.scroll_y, 2.0 * BOOKMARK_ROW_HEIGHT);
+
+        list.move_selection(-4, 10, viewport);
+        assert_eq!(list.selected, 1);
+        assert_eq!(list.scroll_y, BOOKMARK_ROW_HEIGHT);
+
+        list.move_selection(20, 10, viewport);
+        assert_eq!(list.selected, 9);
+        assert_eq!(list.scroll_y, 6.0 * BOOKMARK_ROW_HEIGHT);
+    }
+
+    #[test]
+    fn snippets_are_trimmed_and_shortened() {
+        let long = "word ".repeat(20);
+        let content = format!("# Title\n\n   - item text  \n{}", long);
+        assert_eq!(line_snippet(&content, 1).as_deref(), Some("# Title"));
+        assert_eq!(line_snippet(&content, 2), None);
+        assert_eq!(line_snippet(&content, 3).as_deref(), Some("- item text"));
+        let snippet = line_snippet(&content, 4).unwrap();
+        assert!(snippet.ends_with('…'));
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 1);
+        assert_eq!(line_snippet(&content, 0), None);
+        assert_eq!(line_snippet(&content, 9), None);
+    }
+
+    #[test]
+    fn removing_the_last_rows_clamps_the_selection() {
+        let viewport = 1000.0;
+        let mut list = BookmarkListState {
+            selected: 4,
+            scroll_y: 0.0,
+        };
+        list.clamp(3, viewport);
+        assert_eq!(list.selected, 2);
+        list.clamp(0, viewport);
+        assert_eq!(list, BookmarkListState::default());
+
+        list.scroll_by(500.0, 2, viewport);
+        assert_eq!(list.scroll_y, 0.0);
+    }
+}
@@ -0,0 +1,173 @@
+//! Contrast checks for theme colors
+//!
+//! Theme JSON files go straight into rendering, so a user theme can easily
+//! put text on a background it cannot be read against. [`check_theme`]
+//! computes the WCAG contrast ratio of the pairs the document is read
+//! through (body text, links, code blocks, search highlights, table headers
+//! and the table of contents) and reports those below
+//! [`MIN_READABLE_CONTRAST`]. Translucent colors are blended over what they
+//! are drawn on first. The registry logs the issues as themes load and
+//! `markdown_viewer lint` reports them for the configured theme.
+
+use gpui::Rgba;
+use std::fmt;
+
+use crate::internal::theme::ThemeColors;
+
+/// Lowest contrast ratio accepted: the WCAG AA minimum for large text.
+/// Below it, text is hard to read at any size.
+pub const MIN_READABLE_CONTRAST: f32 = 3.0;
+
+/// A color pair of a theme with too little contrast
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastIssue {
+    /// What is drawn on what, e.g. "link on background"
+    pub pair: &'static str,
+    pub ratio: f32,
+}
+
+impl fmt::Display for ContrastIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} has contrast {:.1}:1, below {:.1}:1",
+            self.pair, self.ratio, MIN_READABLE_CONTRAST
+        )
+    }
+}
+
+/// `color` drawn over the opaque `background`
+fn blend(color: Rgba, background: Rgba) -> Rgba {
+    let mix = |fg: f32, bg: f32| fg * color.a + bg * (1.0 - color.a);
+    Rgba {
+        r: mix(color.r, background.r),
+        g: mix(color.g, background.g),
+        b: mix(color.b, background.b),
+        a: 1.0,
+    }
+}
+
+/// Relative luminance of an opaque sRGB color, per WCAG 2
+pub fn relative_luminance(color: Rgba) -> f32 {
+    let linear = |channel: f32| match channel <= 0.04045 {
+        true => channel / 12.92,
+        false => ((channel + 0.055) / 1.055).powf(2.4),
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+/// WCAG contrast ratio (1 to 21) of `foreground` on `background`, both
+/// drawn on the opaque `page`
+pub fn contrast_ratio(foreground: Rgba, background: Rgba, page: Rgba) -> f32 {
+    let opaque_black = Rgba {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+        a: 1.0,
+    };
+    let background = blend(background, blend(page, opaque_black));
+    let foreground = blend(foreground, background);
+    let (lighter, darker) = match relative_luminance(foreground) > relative_luminance(background) {
+        true => (foreground, background),
+        false => (background, foreground),
+    };
+    (relative_luminance(lighter) + 0.05) / (relative_luminance(darker) + 0.05)
+}
+
+/// Color pairs of `colors` whose contrast is below [`MIN_READABLE_CONTRAST`]
+pub fn check_theme(colors: &ThemeColors) -> Vec<ContrastIssue> {
+    let page = colors.bg_color;
+    let pairs = [
+        ("text on background", colors.text_color, page),
+        ("link on background", colors.link_color, page),
+        (
+            "text on code blocks",
+            colors.text_color,
+            colors.code_bg_color,
+        ),
+        (
+            "text on search matches",
+            colors.text_color,
+            colors.search_bg_color,
+        ),
+        (
+            "text on the current match",
+            colors.text_color,
+            colors.current_match_bg_color,
+        ),
+        (
+            "text on table headers",
+            colors.text_color,
+            colors.table_header_bg,
+        ),
+        (
+            "table of contents text",
+            colors.toc_text_color,
+            colors.toc_bg_color,
+        ),
+        (
+            "search bar text",
+            colors.search_overlay_text_color,
+            colors.search_overlay_bg_color,
+        ),
+    ];
+    pairs
+        .into_iter()
+        .map(|(pair, foreground, background)| ContrastIssue {
+            pair,
+            ratio: contrast_ratio(foreground, background, page),
+        })
+        .filter(|issue| issue.ratio < MIN_READABLE_CONTRAST)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::theme::{ThemeMode, rgba_from_hex};
+    use std::collections::HashMap;
+
+    #[test]
+    fn ratios_match_wcag() {
+        let white = rgba_from_hex("#ffffff");
+        let black = rgba_from_hex("#000000");
+        assert!((contrast_ratio(black, white, white) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(white, white, white) - 1.0).abs() < 0.01);
+        // #777777 on white is the classic 4.48:1
+        let grey = rgba_from_hex("#777777");
+        assert!((contrast_ratio(grey, white, white) - 4.48).abs() < 0.01);
+        // Half-transparent black over white reads as mid grey
+        let translucent = rgba_from_hex("#00000080");
+        assert!((contrast_ratio(translucent, white, white) - 4.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn unreadable_pairs_are_reported() {
+        let colors = HashMap::from([
+            ("background".to_string(), "#ffffffff".to_string()),
+            ("foreground".to_string(), "#222222ff".to_string()),
+            (
+                "list.active.background".to_string(),
+                "#111111ff".to_string(),
+            ),
+        ]);
+        let theme = ThemeColors::from_json(
+            "Broken",
+            ThemeMode::Light,
+            &colors,
+            &serde_json::Value::Null,
+        );
+        let issues = check_theme(&theme);
+        assert_eq!(
+            issues.iter().map(|issue| issue.pair).collect::<Vec<_>>(),
+            vec!["text on table headers"]
+        );
+        assert!(
+            issues[0]
+                .to_string()
+                .starts_with("text on table headers has contrast 1.2:1")
+        );
+
+        assert!(check_theme(&ThemeColors::default()).is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Modal confirmation dialogs
+//!
+//! A dialog is drawn on top of every other overlay behind a dimmed backdrop
+//! and traps keyboard focus: while it is open every key goes to it. Tab,
+//! Shift+Tab and the arrow keys move focus between its buttons, Enter or
+//! Space presses the focused one, Escape presses Cancel, and Y / N stay as
+//! shortcuts for the two buttons. The default (confirm) button has focus
+//! when the dialog opens.
+
+use std::path::PathBuf;
+
+use crate::internal::file_handling::{LoadGuard, LoadGuardError};
+
+/// A button of a confirmation dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogButton {
+    /// Carries out the action; focused when the dialog opens
+    Confirm,
+    /// Closes the dialog without acting; also pressed by Escape
+    Cancel,
+}
+
+impl DialogButton {
+    /// The other button, for moving focus
+    fn other(self) -> Self {
+        match self {
+            DialogButton::Confirm => DialogButton::Cancel,
+            DialogButton::Cancel => DialogButton::Confirm,
+        }
+    }
+}
+
+/// What a dialog asks to confirm
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogAction {
+    /// Export the PDF again over the existing file
+    OverwritePdf(PathBuf),
+    /// Open an oversized or binary-looking file despite its guard, in a new
+    /// tab when that is where it was going to open
+    OpenAnyway { path: PathBuf, new_tab: bool },
+}
+
+/// Result of a key press or click in a dialog
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogOutcome {
+    /// The dialog stays open
+    Pending,
+    Confirmed(DialogAction),
+    Cancelled,
+}
+
+/// A question with a confirm and a cancel button
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmDialog {
+    pub title: String,
+    pub message: String,
+    pub confirm_label: &'static str,
+    pub cancel_label: &'static str,
+    pub action: DialogAction,
+    /// Button with keyboard focus
+    pub focused: DialogButton,
+}
+
+impl ConfirmDialog {
+    /// Ask before a PDF export replaces the file at `path`
+    pub fn overwrite_pdf(path: PathBuf) -> Self {
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("output.pdf")
+            .to_string();
+        Self {
+            title: "Replace existing PDF?".to_string(),
+            message: format!("{} already exists. Exporting will overwrite it.", filename),
+            confirm_label: "Overwrite",
+            cancel_label: "Cancel",
+            action: DialogAction::OverwritePdf(path),
+            focused: DialogButton::Confirm,
+        }
+    }
+
+    /// Ask before opening a file that tripped a load guard
+    pub fn open_anyway(guard: &LoadGuardError, new_tab: bool) -> Self {
+        Self {
+            title: match guard.guard {
+                LoadGuard::TooLarge { .. } => "Large file".to_string(),
+                LoadGuard::Binary => "Binary file".to_string(),
+            },
+            message: format!("{}. Open it anyway?", guard),
+            confirm_label: "Open Anyway",
+            cancel_label: "Cancel",
+            action: DialogAction::OpenAnyway {
+                path: PathBuf::from(&guard.path),
+                new_tab,
+            },
+            focused: DialogButton::Confirm,
+        }
+    }
+
+    /// Handle a key press; every key is consumed while the dialog is open
+    pub fn handle_key(&mut self, key: &str) -> DialogOutcome {
+        match key {
+            "tab" | "left" | "right" | "up" | "down" => {
+                self.focused = self.focused.other();
+                DialogOutcome::Pending
+            }
+            "enter" | "space" => self.press(self.focused),
+            "y" | "Y" => self.press(DialogButton::Confirm),
+            "n" | "N" | "escape" => self.press(DialogButton::Cancel),
+            _ => DialogOutcome::Pending,
+        }
+    }
+
+    /// Press `button`, by click or key
+    pub fn press(&self, button: DialogButton) -> DialogOutcome {
+        match button {
+            DialogButton::Confirm => DialogOutcome::Confirmed(self.action.clone()),
+            DialogButton::Cancel => DialogOutcome::Cancelled,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_stays_on_the_two_buttons() {
+        let path = PathBuf::from("/docs/guide.pdf");
+        let mut dialog = ConfirmDialog::overwrite_pdf(path.clone());
+        assert_eq!(dialog.focused, DialogButton::Confirm);
+        assert_eq!(
+            dialog.message,
+            "guide.pdf already exists. Exporting will overwrite it."
+        );
+
+        assert_eq!(dialog.handle_key("tab"), DialogOutcome::Pending);
+        assert_eq!(dialog.focused, DialogButton::Cancel);
+        assert_eq!(dialog.handle_key("tab"), DialogOutcome::Pending);
+        assert_eq!(dialog.focused, DialogButton::Confirm);
+
+        // Keys that mean something elsewhere do nothing here
+        assert_eq!(dialog.handle_key("j"), DialogOutcome::Pending);
+        assert_eq!(
+            dialog.handle_key("enter"),
+            DialogOutcome::Confirmed(DialogAction::OverwritePdf(path))
+        );
+    }
+
+    #[test]
+    fn open_anyway_dialog_names_the_guard() {
+        let guard = LoadGuardError {
+            path: "/tmp/data.bin".to_string(),
+            guard: LoadGuard::Binary,
+        };
+        let dialog = ConfirmDialog::open_anyway(&guard, true);
+        assert_eq!(dialog.title, "Binary file");
+        assert_eq!(
+            dialog.message,
+            "'/tmp/data.bin' appears to be binary. Open it anyway?"
+        );
+        assert_eq!(
+            dialog.press(DialogButton::Confirm),
+            DialogOutcome::Confirmed(DialogAction::OpenAnyway {
+                path: PathBuf::from("/tmp/data.bin"),
+                new_tab: true,
+            })
+        );
+    }
+
+    #[test]
+    fn escape_and_focused_cancel_cancel() {
+        let mut dialog = ConfirmDialog::overwrite_pdf(PathBuf::from("out.pdf"));
+        assert_eq!(dialog.handle_key("escape"), DialogOutcome::Cancelled);
+        dialog.handle_key("left");
+        assert_eq!(dialog.handle_key("space"), DialogOutcome::Cancelled);
+    }
+}
@@ -0,0 +1,210 @@
+//! Parsed document kept between frames
+//!
+//! comrak's AST lives in an arena that is borrowed for as long as the tree is
+//! used, so it cannot be stored in the viewer. [`DocNode`] is an owned copy of
+//! the tree, made once per content change by [`ParsedDocument::parse`] after
+//! the transform passes ran. Frames build their elements from it instead of
+//! parsing the markdown again.
+
+use comrak::nodes::{AstNode, ListDelimType, ListType, NodeValue, Sourcepos};
+use comrak::{Arena, parse_document};
+use std::path::Path;
+
+use crate::internal::toc::HeadingAnchors;
+use crate::internal::transform::{self, TransformContext};
+
+/// Bullets of unordered lists, by nesting depth
+const BULLETS: [&str; 3] = ["•", "◦", "▪"];
+
+/// An owned node of a parsed document
+#[derive(Debug, Clone)]
+pub struct DocNode {
+    pub value: NodeValue,
+    pub sourcepos: Sourcepos,
+    /// Whether the node sits directly in a list item
+    pub in_list_item: bool,
+    /// Lists enclosing the node, so 0 for a top-level list
+    pub list_depth: usize,
+    /// Number shown for a footnote definition
+    pub footnote_number: Option<usize>,
+    /// Anchor of a heading, numbered like in the TOC and the HTML export
+    pub anchor: Option<String>,
+    pub children: Vec<DocNode>,
+}
+
+impl DocNode {
+    /// Copy `node` and everything below it out of the arena
+    pub fn from_ast<'a>(node: &'a AstNode<'a>) -> Self {
+        let mut copy = Self::copy(node);
+        copy.assign_anchors(&mut HeadingAnchors::default());
+        copy
+    }
+
+    fn copy<'a>(node: &'a AstNode<'a>) -> Self {
+        let ast = node.data.borrow();
+        Self {
+            value: ast.value.clone(),
+            sourcepos: ast.sourcepos,
+            in_list_item: node
+                .parent()
+                .is_some_and(|parent| matches!(parent.data.borrow().value, NodeValue::Item(_))),
+            list_depth: node
+                .ancestors()
+                .skip(1)
+                .filter(|ancestor| matches!(ancestor.data.borrow().value, NodeValue::List(_)))
+                .count(),
+            footnote_number: matches!(ast.value, NodeValue::FootnoteDefinition(_))
+                .then(|| crate::internal::render::footnote_number(node)),
+            anchor: None,
+            children: node.children().map(DocNode::copy).collect(),
+        }
+    }
+
+    /// Give every heading below the node its anchor, in document order
+    fn assign_anchors(&mut self, anchors: &mut HeadingAnchors) {
+        if matches!(self.value, NodeValue::Heading(_)) {
+            self.anchor = Some(anchors.assign(&self.text().replace('\n', " ")));
+        }
+        for child in &mut self.children {
+            child.assign_anchors(anchors);
+        }
+    }
+
+    pub fn children(&self) -> std::slice::Iter<'_, DocNode> {
+        self.children.iter()
+    }
+
+    /// Source lines (0-based, inclusive) the node spans
+    pub fn line_range(&self) -> (usize, usize) {
+        let start = self.sourcepos.start.line.saturating_sub(1);
+        (start, self.sourcepos.end.line.saturating_sub(1).max(start))
+    }
+
+    /// Source lines of each child, i.e. of each top-level block of a document
+    pub fn block_ranges(&self) -> Vec<(usize, usize)> {
+        self.children().map(DocNode::line_range).collect()
+    }
+
+    /// Inline text of the node, with line breaks as newlines
+    pub fn text(&self) -> String {
+        match &self.value {
+            NodeValue::Text(text) => text.to_string(),
+            NodeValue::Code(code) => code.literal.clone(),
+            NodeValue::LineBreak | NodeValue::SoftBreak => "\n".to_string(),
+            _ => self.children().map(DocNode::text).collect(),
+        }
+    }
+
+    /// Marker of the list item at `index`, if the node is a list
+    ///
+    /// Ordered lists count on from their start number with the delimiter
+    /// they were written with; bullets change with the nesting depth.
+    pub fn item_marker(&self, index: usize) -> Option<String> {
+        let NodeValue::List(list) = &self.value else {
+            return None;
+        };
+        Some(match list.list_type {
+            ListType::Bullet => BULLETS[self.list_depth % BULLETS.len()].to_string(),
+            ListType::Ordered => {
+                let delimiter = match list.delimiter {
+                    ListDelimType::Period => '.',
+                    ListDelimType::Paren => ')',
+                };
+                format!("{}{}", list.start + index, delimiter)
+            }
+        })
+    }
+}
+
+/// The viewer's document, parsed for one content revision
+#[derive(Debug, Clone)]
+pub struct ParsedDocument {
+    /// Content revision the document was parsed from
+    pub revision: u64,
+    pub root: DocNode,
+}
+
+impl ParsedDocument {
+    /// Parse `content` and run the transform passes over it
+    ///
+    /// Transform errors are reported by the TOC rebuild, which runs the
+    /// passes on the same content.
+    pub fn parse(content: &str, path: &Path, revision: u64) -> Self {
+        let arena = Arena::new();
+        let root = parse_document(&arena, content, &crate::internal::render::parse_options());
+        transform::apply(&arena, root, &TransformContext { path: Some(path) });
+        Self {
+            revision,
+            root: DocNode::from_ast(root),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_keeps_blocks_and_their_lines() {
+        let document = ParsedDocument::parse(
+            "# Title\n\nSome text\nwrapped\n\n- item\n",
+            Path::new("doc.md"),
+            3,
+        );
+        assert_eq!(document.revision, 3);
+        assert!(matches!(document.root.value, NodeValue::Document));
+        assert_eq!(document.root.block_ranges(), vec![(0, 0), (2, 3), (5, 5)]);
+
+        let item = &document.root.children[2].children[0];
+        assert!(item.children().all(|child| child.in_list_item));
+        assert!(!document.root.children[1].in_list_item);
+    }
+
+    #[test]
+    fn headings_get_their_toc_anchors() {
+        let document = ParsedDocument::parse(
+            "# Usage
+
+## Usage
+
+Setext
+heading
+---
+
+text
+",
+            Path::new("doc.md"),
+            1,
+        );
+        let anchors: Vec<_> = document
+            .root
+            .children()
+            .map(|block| block.anchor.as_deref())
+            .collect();
+        assert_eq!(
+            anchors,
+            vec![Some("usage"), Some("usage-1"), Some("setext-heading"), None]
+        );
+    }
+
+    #[test]
+    fn list_markers_follow_start_delimiter_and_depth() {
+        let document = ParsedDocument::parse(
+            "5. five\n6. six\n   - nested\n     1) deeper\n",
+            Path::new("doc.md"),
+            1,
+        );
+        let ordered = &document.root.children[0];
+        assert_eq!(ordered.item_marker(0).as_deref(), Some("5."));
+        assert_eq!(ordered.item_marker(1).as_deref(), Some("6."));
+
+        let bullets = &ordered.children[1].children[1];
+        assert_eq!(bullets.list_depth, 1);
+        assert_eq!(bullets.item_marker(0).as_deref(), Some("◦"));
+
+        let deeper = &bullets.children[0].children[1];
+        assert_eq!(deeper.list_depth, 2);
+        assert_eq!(deeper.item_marker(0).as_deref(), Some("1)"));
+        assert_eq!(document.root.children[0].children[0].item_marker(0), None);
+    }
+}
@@ -0,0 +1,595 @@
+//! EPUB export functionality for the markdown viewer
+//!
+//! This module packages markdown content as an EPUB 3 book for e-readers.
+//! The document is split into chapters at every level 1 and level 2
+//! heading, local images are copied into the book and the title, authors,
+//! language, date and description come from the YAML front matter when the
+//! document has one.
+//!
+//! Chapters are cut with [`SplitDocument`] and rendered by the HTML backend,
+//! so footnotes and link references resolve within each chapter.
+
+use anyhow::{Context, Result};
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{Arena, parse_document};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::internal::file_handling::resolve_image_path;
+use crate::internal::html_export::document_title;
+use crate::internal::render::html::{self, HtmlOptions, escape_html, image_mime_type};
+use crate::internal::render::split::SplitDocument;
+use crate::internal::theme::ActiveTheme;
+use crate::internal::transform::{self, TransformContext};
+
+/// Front matter fields, in document order; list fields hold every item
+pub type FrontMatter = Vec<(String, Vec<String>)>;
+
+/// Book metadata written to the package document
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpubMetadata {
+    pub title: String,
+    pub authors: Vec<String>,
+    pub language: String,
+    pub date: Option<String>,
+    pub description: Option<String>,
+    /// Unique identifier; derived from the title and authors when the front
+    /// matter has none
+    pub identifier: String,
+}
+
+impl EpubMetadata {
+    /// Metadata from front matter fields, with `fallback_title` for documents
+    /// without a `title` field
+    pub fn from_front_matter(front_matter: &FrontMatter, fallback_title: &str) -> Self {
+        let field = |names: &[&str]| {
+            front_matter
+                .iter()
+                .find(|(key, _)| names.contains(&key.to_lowercase().as_str()))
+                .map(|(_, values)| values.clone())
+                .unwrap_or_default()
+        };
+        let first = |names: &[&str]| field(names).into_iter().next();
+
+        let title = first(&["title"]).unwrap_or_else(|| fallback_title.to_string());
+        let authors = field(&["author", "authors", "creator"]);
+        let identifier = first(&["identifier", "isbn", "uuid"]).unwrap_or_else(|| {
+            let mut hasher = DefaultHasher::new();
+            (&title, &authors).hash(&mut hasher);
+            format!("urn:markdown-viewer:{:016x}", hasher.finish())
+        });
+        Self {
+            language: first(&["lang", "language"]).unwrap_or_else(|| "en".to_string()),
+            date: first(&["date"]),
+            description: first(&["description", "summary", "subject"]),
+            title,
+            authors,
+            identifier,
+        }
+    }
+}
+
+/// A chapter of the book and the markdown it is rendered from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub markdown: String,
+}
+
+/// A local image copied into the book
+struct EpubImage {
+    /// Resolved path of the source file
+    source: String,
+    /// Path inside the book, relative to the chapters
+    href: String,
+    media_type: &'static str,
+    bytes: Vec<u8>,
+}
+
+/// Export markdown content to an EPUB file
+///
+/// # Arguments
+/// * `markdown_content` - The raw markdown text to export
+/// * `markdown_file_path` - File the content came from, for relative images
+/// * `output_path` - Path where the EPUB file should be saved
+/// * `theme` - Theme whose syntax colors highlight code blocks
+///
+/// # Returns
+/// * `Ok(())` if the book was successfully written
+/// * `Err` if the file could not be created or written
+pub fn export_to_epub(
+    markdown_content: &str,
+    markdown_file_path: &Path,
+    output_path: &Path,
+    theme: &ActiveTheme,
+) -> Result<()> {
+    info!("Exporting markdown to EPUB: {:?}", output_path);
+    debug!("Markdown content length: {} bytes", markdown_content.len());
+
+    let (front_matter, body) = split_front_matter(markdown_content);
+    let arena = Arena::new();
+    let root = parse_document(&arena, body, &crate::internal::render::parse_options());
+    let metadata =
+        EpubMetadata::from_front_matter(&front_matter, &document_title(root, markdown_file_path));
+    let chapters = split_chapters(body, &metadata.title);
+    debug!("EPUB has {} chapters", chapters.len());
+
+    let mut images = Vec::new();
+    let chapter_pages: Vec<String> = chapters
+        .iter()
+        .map(|chapter| chapter_page(chapter, &metadata, markdown_file_path, &mut images))
+        .collect();
+
+    let file = File::create(output_path)
+        .with_context(|| format!("Failed to create '{}'", output_path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    // The mimetype entry must come first and be stored uncompressed
+    zip.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored),
+    )?;
+    zip.write_all(b"application/epub+zip")?;
+
+    let mut entries = vec![
+        (
+            "META-INF/container.xml".to_string(),
+            CONTAINER_XML.as_bytes().to_vec(),
+        ),
+        (
+            "OEBPS/content.opf".to_string(),
+            package_document(&metadata, &chapters, &images, SystemTime::now()).into_bytes(),
+        ),
+        (
+            "OEBPS/nav.xhtml".to_string(),
+            navigation_page(&metadata, &chapters).into_bytes(),
+        ),
+        (
+            "OEBPS/style.css".to_string(),
+            format!("{}{}", BOOK_CSS, html::syntect_css(theme)).into_bytes(),
+        ),
+    ];
+    entries.extend(
+        chapter_pages
+            .into_iter()
+            .enumerate()
+            .map(|(index, page)| (format!("OEBPS/{}", chapter_href(index)), page.into_bytes())),
+    );
+    entries.extend(
+        images
+            .into_iter()
+            .map(|image| (format!("OEBPS/{}", image.href), image.bytes)),
+    );
+    for (name, bytes) in entries {
+        zip.start_file(name, SimpleFileOptions::default())?;
+        zip.write_all(&bytes)?;
+    }
+    zip.finish()
+        .with_context(|| format!("Failed to write '{}'", output_path.display()))?;
+
+    info!("Successfully exported EPUB to {:?}", output_path);
+    Ok(())
+}
+
+/// Split YAML front matter off the document
+///
+/// Front matter is a block of `key: value` lines between `---` fences at the
+/// very start. Lists may be written inline (`[a, b]`) or as `- item` lines.
+/// Returns no fields and the whole content when there is no front matter.
+pub fn split_front_matter(content: &str) -> (FrontMatter, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (Vec::new(), content);
+    };
+
+    let mut fields: FrontMatter = Vec::new();
+    let mut offset = content.len() - rest.len();
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return (fields, &content[offset..]);
+        }
+        match (
+            trimmed.trim_start().strip_prefix("- "),
+            trimmed.split_once(':'),
+        ) {
+            (Some(item), _) => {
+                if let Some((_, values)) = fields.last_mut() {
+                    values.push(unquote(item));
+                }
+            }
+            (None, Some((key, value))) => {
+                let value = value.trim();
+                let values = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                    Some(list) => list.split(',').map(unquote).collect(),
+                    None if value.is_empty() => Vec::new(),
+                    None => vec![unquote(value)],
+                };
+                fields.push((key.trim().to_string(), values));
+            }
+            (None, None) => {}
+        }
+    }
+    // No closing fence: not front matter after all
+    (Vec::new(), content)
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// Split the document into chapters at level 1 and level 2 headings
+///
+/// Content before the first such heading becomes a chapter titled
+/// `fallback_title`. Footnote and link reference definitions are appended
+/// to every chapter.
+pub fn split_chapters(content: &str, fallback_title: &str) -> Vec<Chapter> {
+    let split = SplitDocument::parse(content);
+    let mut chapters: Vec<(String, Range<usize>)> = Vec::new();
+    for (index, block) in split.blocks.iter().enumerate() {
+        match (&block.heading, chapters.last_mut()) {
+            (Some((level, text)), _) if *level <= 2 => {
+                chapters.push((text.clone(), index..index + 1))
+            }
+            (_, Some((_, range))) => range.end = index + 1,
+            (_, None) => chapters.push((fallback_title.to_string(), index..index + 1)),
+        }
+    }
+
+    chapters
+        .into_iter()
+        .map(|(title, range)| Chapter {
+            title,
+            markdown: split.piece(range),
+        })
+        .collect()
+}
+
+/// File name of a chapter inside the book
+fn chapter_href(index: usize) -> String {
+    format!("chapter-{}.xhtml", index + 1)
+}
+
+/// A chapter as an XHTML page, with its local images added to `images`
+fn chapter_page(
+    chapter: &Chapter,
+    metadata: &EpubMetadata,
+    markdown_file_path: &Path,
+    images: &mut Vec<EpubImage>,
+) -> String {
+    let arena = Arena::new();
+    let root = parse_document(
+        &arena,
+        &chapter.markdown,
+        &crate::internal::render::parse_options(),
+    );
+    transform::apply(
+        &arena,
+        root,
+        &TransformContext {
+            path: Some(markdown_file_path),
+        },
+    );
+    package_images(root, markdown_file_path, images);
+    let body = html::render_document(
+        root,
+        &HtmlOptions {
+            markdown_file_path: Some(markdown_file_path),
+            embed_images: false,
+        },
+    );
+    xhtml_page(&chapter.title, &metadata.language, &body)
+}
+
+/// Point local images at copies inside the book, reading each file once
+fn package_images<'a>(
+    root: &'a AstNode<'a>,
+    markdown_file_path: &Path,
+    images: &mut Vec<EpubImage>,
+) {
+    for node in root.descendants() {
+        if let NodeValue::Image(link) = &mut node.data.borrow_mut().value {
+            if link.url.contains("://") || link.url.starts_with("data:") {
+                continue;
+            }
+            let source = resolve_image_path(&link.url, markdown_file_path);
+            if let Some(image) = images.iter().find(|image| image.source == source) {
+                link.url = image.href.clone();
+                continue;
+            }
+            match std::fs::read(&source) {
+                Ok(bytes) => {
+                    let extension = Path::new(&source)
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .unwrap_or("img")
+                        .to_ascii_lowercase();
+                    let href = format!("images/image-{}.{}", images.len() + 1, extension);
+                    debug!("Packaging image {} as {}", source, href);
+                    link.url = href.clone();
+                    images.push(EpubImage {
+                        media_type: image_mime_type(Path::new(&source)),
+                        source,
+                        href,
+                        bytes,
+                    });
+                }
+                Err(e) => warn!("Failed to package image {}: {}", source, e),
+            }
+        }
+    }
+}
+
+fn xhtml_page(title: &str, language: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+         lang=\"{lang}\" xml:lang=\"{lang}\">\n<head>\n<meta charset=\"utf-8\" />\n\
+         <title>{title}</title>\n<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\" />\n\
+         </head>\n<body>\n{body}</body>\n</html>\n",
+        lang = escape_html(language),
+        title = escape_html(title),
+        body = body
+    )
+}
+
+/// The table of contents page listing every chapter
+fn navigation_page(metadata: &EpubMetadata, chapters: &[Chapter]) -> String {
+    let items: String = chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| {
+            format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                chapter_href(index),
+                escape_html(&chapter.title)
+            )
+        })
+        .collect();
+    xhtml_page(
+        &metadata.title,
+        &metadata.language,
+        &format!(
+            "<nav epub:type=\"toc\" id=\"toc\">\n<h1>Contents</h1>\n<ol>\n{}</ol>\n</nav>\n",
+            items
+        ),
+    )
+}
+
+/// The OPF package document: metadata, manifest and reading order
+fn package_document(
+    metadata: &EpubMetadata,
+    chapters: &[Chapter],
+    images: &[EpubImage],
+    modified: SystemTime,
+) -> String {
+    let mut dc = format!(
+        "<dc:identifier id=\"book-id\">{}</dc:identifier>\n<dc:title>{}</dc:title>\n\
+         <dc:language>{}</dc:language>\n",
+        escape_html(&metadata.identifier),
+        escape_html(&metadata.title),
+        escape_html(&metadata.language)
+    );
+    for author in &metadata.authors {
+        dc.push_str(&format!(
+            "<dc:creator>{}</dc:creator>\n",
+            escape_html(author)
+        ));
+    }
+    if let Some(date) = &metadata.date {
+        dc.push_str(&format!("<dc:date>{}</dc:date>\n", escape_html(date)));
+    }
+    if let Some(description) = &metadata.description {
+        dc.push_str(&format!(
+            "<dc:description>{}</dc:description>\n",
+            escape_html(description)
+        ));
+    }
+
+    let mut manifest = String::from(
+        "<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\" />\n\
+         <item id=\"css\" href=\"style.css\" media-type=\"text/css\" />\n",
+    );
+    let mut spine = String::new();
+    for index in 0..chapters.len() {
+        manifest.push_str(&format!(
+            "<item id=\"chapter-{n}\" href=\"{href}\" media-type=\"application/xhtml+xml\" />\n",
+            n = index + 1,
+            href = chapter_href(index)
+        ));
+        spine.push_str(&format!("<itemref idref=\"chapter-{}\" />\n", index + 1));
+    }
+    for (index, image) in images.iter().enumerate() {
+        manifest.push_str(&format!(
+            "<item id=\"image-{}\" href=\"{}\" media-type=\"{}\" />\n",
+            index + 1,
+            escape_html(&image.href),
+            image.media_type
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\">\n\
+         <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n{}\
+         <meta property=\"dcterms:modified\">{}</meta>\n</metadata>\n\
+         <manifest>\n{}</manifest>\n<spine>\n{}</spine>\n</package>\n",
+        dc,
+        utc_timestamp(modified),
+        manifest,
+        spine
+    )
+}
+
+/// `YYYY-MM-DDThh:mm:ssZ`, the format EPUB requires for `dcterms:modified`
+fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, time_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = match mp < 10 {
+        true => mp + 3,
+        false => mp - 9,
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+const CONTAINER_XML: &str = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+<rootfiles>\n\
+<rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\" />\n\
+</rootfiles>\n\
+</container>\n";
+
+/// Layout only; colors are left to the reader's theme
+const BOOK_CSS: &str = "pre.code { padding: 0.5em; overflow-x: auto; white-space: pre-wrap; }\n\
+code, pre { font-family: monospace; }\n\
+blockquote { margin-left: 1em; padding-left: 1em; border-left: 3px solid #999; font-style: italic; }\n\
+.markdown-alert { margin-left: 1em; padding-left: 1em; border-left: 3px solid #999; }\n\
+.markdown-alert-title { font-weight: bold; }\n\
+table { border-collapse: collapse; }\n\
+th, td { border: 1px solid #999; padding: 0.25em 0.5em; }\n\
+img { max-width: 100%; }\n\
+.math.display { text-align: center; }\n\
+.footnote { font-size: 0.9em; }\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::theme::test_theme;
+    use std::io::Read;
+    use std::time::Duration;
+
+    #[test]
+    fn front_matter_fields_and_lists() {
+        let content =
+            "---\ntitle: \"My Book\"\nauthors:\n  - Ann\n  - Bo\ntags: [a, 'b']\n---\n# Start\n";
+        let (fields, body) = split_front_matter(content);
+        assert_eq!(body, "# Start\n");
+        assert_eq!(
+            fields[0],
+            ("title".to_string(), vec!["My Book".to_string()])
+        );
+        assert_eq!(fields[1].1, vec!["Ann", "Bo"]);
+        assert_eq!(fields[2].1, vec!["a", "b"]);
+
+        let metadata = EpubMetadata::from_front_matter(&fields, "fallback");
+        assert_eq!(metadata.title, "My Book");
+        assert_eq!(metadata.authors, vec!["Ann", "Bo"]);
+        assert_eq!(metadata.language, "en");
+
+        assert_eq!(split_front_matter("---\nno end").1, "---\nno end");
+        assert!(split_front_matter("# No front matter").0.is_empty());
+    }
+
+    #[test]
+    fn chapters_split_at_top_level_headings() {
+        let content = "Intro.\n\n# One\n\nText[^n].\n\n### Sub\n\n## Two\n\nSee [x].\n\n[^n]: Note.\n\n[x]: https://example.com\n";
+        let chapters = split_chapters(content, "Book");
+        let titles: Vec<&str> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Book", "One", "Two"]);
+        assert!(
+            chapters[1]
+                .markdown
+                .starts_with("# One\n\nText[^n].\n\n### Sub")
+        );
+        assert!(chapters[1].markdown.contains("[^n]: Note."));
+        assert!(chapters[2].markdown.ends_with("[x]: https://example.com"));
+    }
+
+    #[test]
+    fn timestamps_use_utc_calendar_dates() {
+        let time = UNIX_EPOCH + Duration::from_secs(951_782_400 + 3_661);
+        assert_eq!(utc_timestamp(time), "2000-02-29T01:01:01Z");
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_export_to_epub_packages_book() {
+        let dir = std::env::temp_dir().join("mdv_epub_export_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("dot.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let output_path = dir.join("book.epub");
+        let markdown = "---\ntitle: Test Book\nauthor: Ann\n---\n# One\n\n![dot](dot.png)\n\n# Two\n\n![again](dot.png)\n";
+
+        let result = export_to_epub(markdown, &dir.join("book.md"), &output_path, &test_theme());
+        assert!(result.is_ok(), "EPUB export should succeed");
+
+        let mut archive = zip::ZipArchive::new(File::open(&output_path).unwrap()).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+        let mut opf = String::new();
+        archive
+            .by_name("OEBPS/content.opf")
+            .unwrap()
+            .read_to_string(&mut opf)
+            .unwrap();
+        let mut chapter = String::new();
+        archive
+            .by_name("OEBPS/chapter-2.xhtml")
+            .unwrap()
+            .read_to_string(&mut chapter)
+            .unwrap();
+        let image_count = archive
+            .file_names()
+            .filter(|name| name.starts_with("OEBPS/images/"))
+            .count();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(opf.contains("<dc:title>Test Book</dc:title>"));
+        assert!(opf.contains("<dc:creator>Ann</dc:creator>"));
+        assert!(opf.contains("<itemref idref=\"chapter-2\" />"));
+        assert!(chapter.contains("src=\"images/image-1.png\""));
+        assert_eq!(
+            image_count, 1,
+            "Images shared by chapters are packaged once"
+        );
+    }
+
+    #[test]
+    fn test_export_to_epub_validates_path() {
+        let result = export_to_epub(
+            "# Test",
+            Path::new("doc.md"),
+            Path::new("/invalid/nonexistent/directory/test.epub"),
+            &test_theme(),
+        );
+        assert!(result.is_err(), "Export to an invalid path should fail");
+    }
+}
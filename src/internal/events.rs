@@ -1,9 +1,16 @@
-use gpui::{Context, KeyDownEvent, ScrollWheelEvent, px};
+use gpui::{Context, KeyDownEvent, KeyUpEvent, ScrollDelta, ScrollWheelEvent};
 use tracing::{debug, info};
 
-use crate::internal::search::SearchState;
+use crate::internal::file_tree::rows_height;
+use crate::internal::keymap::turn_help_page;
+use crate::internal::notifications::{NotificationKind, NotificationSource};
+use crate::internal::search::{SearchMode, SearchState};
+use crate::internal::tabs::MAX_NUMBERED_TABS;
 use crate::internal::viewer::MarkdownViewer;
 
+/// Text lines scrolled per mouse wheel notch
+const WHEEL_LINES_PER_NOTCH: f32 = 3.0;
+
 pub fn handle_key_down(
     viewer: &mut MarkdownViewer,
     event: &KeyDownEvent,
@@ -11,7 +18,6 @@ pub fn handle_key_down(
     cx: &mut Context<MarkdownViewer>,
 ) {
     let arrow_increment = viewer.config.scroll.arrow_key_increment;
-    let page_percent = viewer.config.scroll.page_scroll_percentage;
     let space_percent = viewer.config.scroll.space_scroll_percentage;
 
     // Debug: log all key events
@@ -24,6 +30,121 @@ pub fn handle_key_down(
         event.keystroke.modifiers.alt
     );
 
+    // A confirmation dialog traps focus: every key goes to it until it closes
+    if let Some(dialog) = viewer.confirm_dialog.as_mut() {
+        let outcome = dialog.handle_key(event.keystroke.key.as_str());
+        viewer.finish_dialog(outcome, cx);
+        cx.notify();
+        return;
+    }
+
+    // The first-run tour takes every key until it is finished or skipped
+    if let Some(tour) = viewer.tour.as_mut() {
+        let outcome = tour.handle_key(event.keystroke.key.as_str());
+        viewer.finish_tour(outcome);
+        cx.notify();
+        return;
+    }
+
+    // About Overlay: Escape closes
+    if viewer.about.is_some() && event.keystroke.key == "escape" {
+        viewer.about = None;
+        cx.notify();
+        return;
+    }
+
+    // Help Overlay: Left/Right turn pages, Escape closes
+    // This comes before every other handler so the arrows are not taken by
+    // the file tree or the Alt+Left/Right file stepping
+    if viewer.show_help {
+        match event.keystroke.key.as_str() {
+            key @ ("right" | "left") => {
+                viewer.help_page = turn_help_page(viewer.help_page, key == "right");
+                cx.notify();
+                return;
+            }
+            "escape" => {
+                viewer.show_help = false;
+                viewer.help_page = 0; // Reset to first page
+                cx.notify();
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    // Project Search Overlay
+    if viewer.project_search.is_some() {
+        match event.keystroke.key.as_str() {
+            "escape" => {
+                viewer.close_project_search();
+                cx.notify();
+                return;
+            }
+            "up" | "down" => {
+                if let Some(search) = viewer.project_search.as_mut() {
+                    search.move_selection(match event.keystroke.key.as_str() {
+                        "up" => -1,
+                        _ => 1,
+                    });
+                }
+                cx.notify();
+                return;
+            }
+            "enter" => {
+                let selected = viewer.project_search.as_ref().and_then(|search| {
+                    search
+                        .selected_match()
+                        .map(|(path, line)| (path.to_path_buf(), line.line))
+                });
+                if let Some((path, line)) = selected {
+                    viewer.open_project_match(path, line, cx);
+                }
+                cx.notify();
+                return;
+            }
+            "backspace" => {
+                if let Some(search) = viewer.project_search.as_mut() {
+                    search.query.pop();
+                }
+                viewer.run_project_search(cx);
+                cx.notify();
+                return;
+            }
+            "r" | "w" | "c"
+                if event.keystroke.modifiers.alt
+                    && !event.keystroke.modifiers.control
+                    && !event.keystroke.modifiers.platform =>
+            {
+                let mode = match event.keystroke.key.as_str() {
+                    "r" => SearchMode::Regex,
+                    "w" => SearchMode::WholeWord,
+                    _ => SearchMode::CaseSensitive,
+                };
+                viewer.search_mode = viewer.search_mode.toggle(mode);
+                viewer.run_project_search(cx);
+                cx.notify();
+                return;
+            }
+            key => {
+                if !event.keystroke.modifiers.platform
+                    && !event.keystroke.modifiers.control
+                    && !event.keystroke.modifiers.alt
+                    && key.len() == 1
+                {
+                    if let Some(search) = viewer.project_search.as_mut() {
+                        search.query.push_str(key);
+                    }
+                    viewer.run_project_search(cx);
+                    cx.notify();
+                    return;
+                }
+            }
+        }
+        // Consume all other keys while the project search is open
+        return;
+    }
+
     // Fuzzy File Finder Shortcuts
     if viewer.show_file_finder {
         match event.keystroke.key.as_str() {
@@ -52,7 +173,7 @@ pub fn handle_key_down(
                     .get(viewer.finder_selected_index)
                     .cloned()
                 {
-                    viewer.load_file(path, cx);
+                    viewer.choose_finder_entry(path, cx);
                 }
                 cx.notify();
                 return;
@@ -81,9 +202,46 @@ pub fn handle_key_down(
         return;
     }
 
-    // Global shortcut to open finder (Cmd+P)
+    // File Tree Sidebar, while it has keyboard focus
+    let tree_focused =
+        viewer.show_file_tree && viewer.file_tree.as_ref().is_some_and(|tree| tree.focused);
+    if tree_focused
+        && !event.keystroke.modifiers.platform
+        && !event.keystroke.modifiers.control
+        && !event.keystroke.modifiers.alt
+    {
+        let tree_height = rows_height(viewer.viewport_height);
+        let handled = match (event.keystroke.key.as_str(), viewer.file_tree.as_mut()) {
+            ("escape", Some(tree)) => {
+                debug!("Returning focus from the file tree to the document");
+                tree.focused = false;
+                true
+            }
+            (key, Some(tree)) => {
+                let (open, handled) = tree.handle_key(key, tree_height);
+                if let Some(path) = open {
+                    viewer.open_tree_file(path, cx);
+                }
+                handled
+            }
+            (_, None) => false,
+        };
+        if handled {
+            cx.notify();
+            return;
+        }
+    }
+
+    // Global shortcut to print (Cmd+P)
     if event.keystroke.modifiers.platform && event.keystroke.key == "p" {
-        debug!("Toggle Fuzzy File Finder (Cmd+P)");
+        debug!("Print (Cmd+P)");
+        viewer.print(cx);
+        return;
+    }
+
+    // Global shortcut to open finder (Cmd+K)
+    if event.keystroke.modifiers.platform && event.keystroke.key == "k" {
+        debug!("Toggle Fuzzy File Finder (Cmd+K)");
         viewer.show_file_finder = true;
         viewer.finder_mode = crate::internal::viewer::FinderMode::AllFiles;
         viewer.refresh_file_list();
@@ -104,22 +262,58 @@ pub fn handle_key_down(
         return;
     }
 
+    // Check for Cmd+Shift+F (macOS) or Ctrl+Shift+F (other platforms) to filter
+    // the document down to blocks with search matches
+    // This must come BEFORE the Cmd+F check below
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.as_str() == "f"
+    {
+        debug!("Filter view shortcut triggered (Cmd/Ctrl+Shift+F)");
+        // Without a query to filter for, a directory session searches all its files
+        match (
+            viewer.document().search_input.is_empty(),
+            viewer.project_root().is_some(),
+        ) {
+            (true, true) => viewer.open_project_search(),
+            (true, false) => debug!("Nothing to filter for; type a search first"),
+            (false, _) => viewer.toggle_filter(),
+        }
+        cx.notify();
+        return;
+    }
+
     // Check for Cmd+F (macOS) or Ctrl+F (other platforms) to toggle search
     if event.keystroke.key.as_str() == "f"
         && (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
     {
         debug!("Search shortcut triggered (Cmd/Ctrl+F)");
-        match viewer.search_state.take() {
+        match viewer.document_mut().search_state.take() {
+            // Bring back the bar for the kept highlights
+            Some(state) if viewer.search_highlights_kept => {
+                debug!("Reopening search bar");
+                viewer.search_highlights_kept = false;
+                viewer.document_mut().search_state = Some(state);
+                viewer.show_bookmarks = false;
+            }
             Some(_) => {
                 // Exit search mode
                 debug!("Exiting search mode");
-                viewer.search_input.clear();
+                viewer.clear_filter();
+                viewer.document_mut().search_state = None;
+                viewer.document_mut().search_input.clear();
             }
             None => {
                 // Enter search mode
                 debug!("Entering search mode");
-                viewer.search_state =
-                    Some(SearchState::new(String::new(), &viewer.markdown_content));
+                viewer.search_highlights_kept = false;
+                viewer.document_mut().search_state = Some(SearchState::new(
+                    String::new(),
+                    viewer.document().shown_content(),
+                    viewer.search_mode,
+                ));
+                // The search bar takes the keys the bookmarks overlay uses
+                viewer.show_bookmarks = false;
             }
         }
         cx.notify();
@@ -141,8 +335,7 @@ pub fn handle_key_down(
             false => {
                 // Enter go-to-line mode
                 debug!("Entering go-to-line mode");
-                viewer.show_goto_line = true;
-                viewer.goto_line_input.clear();
+                viewer.open_goto_prompt(false);
             }
         }
         cx.notify();
@@ -161,17 +354,37 @@ pub fn handle_key_down(
         match viewer.config.save_to_file("config.ron") {
             Err(e) => {
                 debug!("Failed to save cleared search history: {}", e);
-                viewer.search_history_message = Some(format!("Failed to save: {}", e));
+                viewer.notifications.show(
+                    NotificationSource::Message,
+                    NotificationKind::Error,
+                    format!("Failed to save: {}", e),
+                );
             }
             Ok(_) => {
                 info!("Search history cleared");
-                viewer.search_history_message = Some("Search history cleared".to_string());
+                viewer.notifications.show(
+                    NotificationSource::Message,
+                    NotificationKind::Info,
+                    "Search history cleared",
+                );
             }
         }
         cx.notify();
         return;
     }
 
+    // Check for Cmd+Alt+T (macOS) or Ctrl+Alt+T (other platforms) to reopen the last
+    // closed tab; Cmd+Shift+T stays the theme toggle
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.alt
+        && event.keystroke.key.as_str() == "t"
+    {
+        debug!("Reopen closed tab shortcut triggered (Cmd/Ctrl+Alt+T)");
+        viewer.reopen_closed_tab(cx);
+        cx.notify();
+        return;
+    }
+
     // Check for Cmd+Shift+T (macOS) or Ctrl+Shift+T (other platforms) to toggle theme
     // This must come BEFORE the platform modifier checks to avoid conflicts with Cmd+T
     if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
@@ -179,15 +392,42 @@ pub fn handle_key_down(
         && event.keystroke.key.as_str() == "t"
     {
         debug!("Theme toggle shortcut triggered (Cmd/Ctrl+Shift+T)");
-        if let Some(new_theme) =
-            crate::internal::theme::registry().toggle_theme(&viewer.config.theme.theme)
-        {
-            viewer.config.theme.theme = new_theme;
-            // Save config to persist theme preference
-            if let Err(e) = viewer.config.save_to_file("config.ron") {
-                debug!("Failed to save theme preference: {}", e);
-            }
-        }
+        viewer.toggle_theme();
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+Shift+M (macOS) or Ctrl+Shift+M (other platforms) to toggle plain text mode
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.as_str() == "m"
+    {
+        debug!("Toggle plain text rendering (Cmd/Ctrl+Shift+M)");
+        viewer.toggle_render_mode();
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+Alt+E (macOS) or Ctrl+Alt+E (other platforms) to export EPUB
+    // This must come BEFORE the Cmd+E (PDF export) check below
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.alt
+        && event.keystroke.key.eq_ignore_ascii_case("e")
+    {
+        debug!("Export to EPUB (Cmd/Ctrl+Alt+E)");
+        viewer.export_epub();
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+Shift+E (macOS) or Ctrl+Shift+E (other platforms) to export HTML
+    // This must come BEFORE the Cmd+E (PDF export) check below
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.eq_ignore_ascii_case("e")
+    {
+        debug!("Export to HTML (Cmd/Ctrl+Shift+E)");
+        viewer.export_html();
         cx.notify();
         return;
     }
@@ -198,7 +438,41 @@ pub fn handle_key_down(
         && event.keystroke.key.as_str() == "b"
     {
         debug!("Toggle bookmarks list shortcut triggered (Cmd/Ctrl+Shift+B)");
-        viewer.show_bookmarks = !viewer.show_bookmarks;
+        viewer.toggle_bookmarks();
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+Shift+R (macOS) or Ctrl+Shift+R (other platforms) to repeat the last export
+    // This must come BEFORE the Cmd+R (reload) check below
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.eq_ignore_ascii_case("r")
+    {
+        debug!("Re-export shortcut triggered (Cmd/Ctrl+Shift+R)");
+        viewer.reexport();
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+Shift+L (macOS) or Ctrl+Shift+L (other platforms) to toggle the exports list
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.eq_ignore_ascii_case("l")
+    {
+        debug!("Toggle exports list shortcut triggered (Cmd/Ctrl+Shift+L)");
+        viewer.show_export_history = !viewer.show_export_history;
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+Shift+I (macOS) or Ctrl+Shift+I (other platforms) to toggle the image report
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.eq_ignore_ascii_case("i")
+    {
+        debug!("Toggle image report shortcut triggered (Cmd/Ctrl+Shift+I)");
+        viewer.toggle_image_report();
         cx.notify();
         return;
     }
@@ -209,15 +483,30 @@ pub fn handle_key_down(
         && event.keystroke.key.as_str() == "n"
     {
         debug!("Theme cycle shortcut triggered (Cmd/Ctrl+Shift+N)");
-        if let Some(new_theme) =
-            crate::internal::theme::registry().cycle_theme(&viewer.config.theme.theme)
-        {
-            info!("Cycling theme to: {}", new_theme);
-            viewer.config.theme.theme = new_theme;
-            // Save config to persist theme preference
-            if let Err(e) = viewer.config.save_to_file("config.ron") {
-                debug!("Failed to save theme preference: {}", e);
-            }
+        viewer.cycle_theme();
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+Shift+D (macOS) or Ctrl+Shift+D (other platforms) to bookmark all
+    // search matches
+    // This must come BEFORE the Cmd+D check below
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.as_str() == "d"
+    {
+        debug!("Bookmark all matches shortcut triggered (Cmd/Ctrl+Shift+D)");
+        if viewer.document().search_state.is_some() {
+            let added = viewer.bookmark_all_matches();
+            viewer.notifications.show(
+                NotificationSource::Message,
+                NotificationKind::Info,
+                match added {
+                    0 => "Every line with a match is already bookmarked".to_string(),
+                    1 => "Bookmarked 1 line with matches".to_string(),
+                    n => format!("Bookmarked {} lines with matches", n),
+                },
+            );
         }
         cx.notify();
         return;
@@ -230,16 +519,22 @@ pub fn handle_key_down(
         debug!("Toggle bookmark shortcut triggered (Cmd/Ctrl+D)");
         let current_line = viewer.get_current_line_number();
 
-        match viewer.bookmarks.iter().position(|&l| l == current_line) {
+        match viewer
+            .document()
+            .bookmarks
+            .iter()
+            .position(|&l| l == current_line)
+        {
             Some(pos) => {
                 // Remove existing bookmark
-                viewer.bookmarks.remove(pos);
+                viewer.document_mut().bookmarks.remove(pos);
+                viewer.forget_bookmark_anchor(current_line);
                 debug!("Removed bookmark at line {}", current_line);
             }
             None => {
                 // Add new bookmark
-                viewer.bookmarks.push(current_line);
-                viewer.bookmarks.sort(); // Keep sorted
+                viewer.document_mut().bookmarks.push(current_line);
+                viewer.document_mut().bookmarks.sort(); // Keep sorted
                 debug!("Added bookmark at line {}", current_line);
             }
         }
@@ -247,42 +542,71 @@ pub fn handle_key_down(
         return;
     }
 
-    // Handle global shortcuts (Cmd+T, Cmd+B, Cmd+Q, Cmd+=, Cmd+-, Cmd+H)
+    // Check for Cmd+Shift+V (macOS) or Ctrl+Shift+V (other platforms) to toggle the source view
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.eq_ignore_ascii_case("v")
+    {
+        debug!("Toggle source view shortcut triggered (Cmd/Ctrl+Shift+V)");
+        viewer.toggle_source_view();
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+Shift+Z (macOS) or Ctrl+Shift+Z (other platforms) to toggle the file tree
+    // This must come BEFORE the Cmd+Z (TOC) check below
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && event.keystroke.modifiers.shift
+        && event.keystroke.key.eq_ignore_ascii_case("z")
+    {
+        debug!("Toggle file tree shortcut triggered (Cmd/Ctrl+Shift+Z)");
+        viewer.toggle_file_tree();
+        cx.notify();
+        return;
+    }
+
+    // Check for Cmd+O (macOS) or Ctrl+O (other platforms) to show the open dialog
+    // Cmd+Shift+O (recent files) is handled above
+    if (event.keystroke.modifiers.platform || event.keystroke.modifiers.control)
+        && !event.keystroke.modifiers.shift
+        && event.keystroke.key == "o"
+    {
+        debug!("Open file dialog (Cmd/Ctrl+O)");
+        viewer.open_file_dialog(cx);
+        return;
+    }
+
+    // Handle global shortcuts (Cmd+T, Cmd+B, Cmd+Q, Cmd+=, Cmd+-, Cmd+H, Cmd+], Cmd+[,
+    // Cmd+Right, Cmd+Left, Cmd+R, Cmd+W, Cmd+1..9)
     if event.keystroke.modifiers.platform {
         match event.keystroke.key.as_str() {
             "t" => {
                 debug!("Scroll to top (Cmd+T)");
-                viewer.scroll_state.scroll_to_top();
+                viewer.scroll_to_y(0.0);
                 cx.notify();
                 return;
             }
             "b" => {
                 debug!("Scroll to bottom (Cmd+B)");
-                viewer.scroll_state.scroll_to_bottom();
+                viewer.scroll_to_y(viewer.document().scroll_state.max_scroll_y);
                 cx.notify();
                 return;
             }
             "q" => {
                 debug!("Quit application (Cmd+Q)");
-                cx.quit();
+                viewer.quit(cx);
                 return;
             }
             "=" | "+" => {
                 debug!("Increase font size (Cmd+=)");
-                let new_size = (viewer.config.theme.base_text_size + 2.0).min(64.0);
-                if (new_size - viewer.config.theme.base_text_size).abs() > 0.01 {
-                    viewer.config.theme.base_text_size = new_size;
-                    viewer.recompute_max_scroll();
+                if viewer.adjust_text_size(2.0) {
                     cx.notify();
                 }
                 return;
             }
             "-" => {
                 debug!("Decrease font size (Cmd+-)");
-                let new_size = (viewer.config.theme.base_text_size - 2.0).max(8.0);
-                if (new_size - viewer.config.theme.base_text_size).abs() > 0.01 {
-                    viewer.config.theme.base_text_size = new_size;
-                    viewer.recompute_max_scroll();
+                if viewer.adjust_text_size(-2.0) {
                     cx.notify();
                 }
                 return;
@@ -295,11 +619,54 @@ pub fn handle_key_down(
             }
             "z" => {
                 debug!("Toggle TOC sidebar (Cmd+Z)");
-                viewer.show_toc = !viewer.show_toc;
-                viewer.recompute_max_scroll();
+                viewer.toggle_toc();
                 cx.notify();
                 return;
             }
+            "]" => {
+                debug!("Next queued file (Cmd+])");
+                viewer.open_next_queued_file(cx);
+                cx.notify();
+                return;
+            }
+            "[" => {
+                debug!("Previous queued file (Cmd+[)");
+                viewer.open_previous_queued_file(cx);
+                cx.notify();
+                return;
+            }
+            "right" => {
+                debug!("Forward in link history (Cmd+Right)");
+                viewer.navigate_forward(cx);
+                cx.notify();
+                return;
+            }
+            "left" => {
+                debug!("Back in link history (Cmd+Left)");
+                viewer.navigate_back(cx);
+                cx.notify();
+                return;
+            }
+            "r" => {
+                debug!("Force reload from disk (Cmd+R)");
+                viewer.force_reload();
+                cx.notify();
+                return;
+            }
+            "w" => {
+                debug!("Close tab (Cmd+W)");
+                viewer.close_tab(viewer.active_tab, cx);
+                return;
+            }
+            key if key
+                .parse::<usize>()
+                .is_ok_and(|n| (1..=MAX_NUMBERED_TABS).contains(&n)) =>
+            {
+                let number: usize = key.parse().unwrap_or_default();
+                debug!("Switch to tab {} (Cmd+{})", number, key);
+                viewer.switch_tab(number - 1, cx);
+                return;
+            }
             "e" => {
                 debug!("Export to PDF (Cmd+E)");
                 // Trigger PDF export
@@ -312,6 +679,25 @@ pub fn handle_key_down(
         }
     }
 
+    // Alt+Right / Alt+Left step through supported files in the current directory
+    if event.keystroke.modifiers.alt {
+        match event.keystroke.key.as_str() {
+            "right" => {
+                debug!("Next file in directory (Alt+Right)");
+                viewer.open_sibling_file(true, cx);
+                cx.notify();
+                return;
+            }
+            "left" => {
+                debug!("Previous file in directory (Alt+Left)");
+                viewer.open_sibling_file(false, cx);
+                cx.notify();
+                return;
+            }
+            _ => {}
+        }
+    }
+
     // Also handle Ctrl+E on non-Mac platforms
     if event.keystroke.modifiers.control && event.keystroke.key.as_str() == "e" {
         debug!("Export to PDF (Ctrl+E)");
@@ -320,24 +706,37 @@ pub fn handle_key_down(
         return;
     }
 
-    // Handle Help Overlay navigation (Left/Right arrows)
-    if viewer.show_help {
+    // Bookmarks overlay: Up/Down select, Enter jumps, Delete removes, Escape closes
+    if viewer.show_bookmarks && !viewer.search_input_active() && !viewer.show_goto_line {
+        let bookmark_count = viewer.document().bookmarks.len();
+        let viewport_height = viewer.viewport_height;
         match event.keystroke.key.as_str() {
-            "right" => {
-                // Next page (max 1 for now)
-                viewer.help_page = (viewer.help_page + 1).min(1);
+            "up" | "down" => {
+                let delta = match event.keystroke.key.as_str() {
+                    "up" => -1,
+                    _ => 1,
+                };
+                viewer
+                    .bookmark_list
+                    .move_selection(delta, bookmark_count, viewport_height);
                 cx.notify();
                 return;
             }
-            "left" => {
-                // Previous page
-                viewer.help_page = viewer.help_page.saturating_sub(1);
+            "enter"
+                if viewer.focused_element()
+                    != Some(&crate::internal::viewer::FocusableElement::BookmarksCloseButton) =>
+            {
+                viewer.open_selected_bookmark();
+                cx.notify();
+                return;
+            }
+            "delete" | "backspace" => {
+                viewer.remove_selected_bookmark();
                 cx.notify();
                 return;
             }
             "escape" => {
-                viewer.show_help = false;
-                viewer.help_page = 0; // Reset to first page
+                viewer.show_bookmarks = false;
                 cx.notify();
                 return;
             }
@@ -345,45 +744,51 @@ pub fn handle_key_down(
         }
     }
 
-    // Handle Escape to close help overlay (fallback if above match didn't catch it)
-    if viewer.show_help && event.keystroke.key.as_str() == "escape" {
-        viewer.show_help = false;
-        viewer.help_page = 0;
+    // Handle Escape to close the TOC entry menu
+    if viewer.toc_menu.is_some() && event.keystroke.key.as_str() == "escape" {
+        viewer.toc_menu = None;
         cx.notify();
         return;
     }
 
-    // Handle Escape to close PDF export notification
-    if viewer.pdf_export_message.is_some() && event.keystroke.key.as_str() == "escape" {
-        viewer.pdf_export_message = None;
+    // Handle Escape to close the exports overlay
+    if viewer.show_export_history && event.keystroke.key.as_str() == "escape" {
+        viewer.show_export_history = false;
         cx.notify();
         return;
     }
 
-    // Handle PDF overwrite confirmation (Y/N)
-    if viewer.show_pdf_overwrite_confirm {
-        match event.keystroke.key.as_str() {
-            "y" | "Y" => {
-                debug!("User confirmed PDF overwrite");
-                viewer.show_pdf_overwrite_confirm = false;
-                // Export will happen in render() when show_pdf_overwrite_confirm is false
-                cx.notify();
-                return;
-            }
-            "n" | "N" | "escape" => {
-                debug!("User cancelled PDF overwrite");
-                viewer.show_pdf_overwrite_confirm = false;
-                viewer.pdf_overwrite_path = None;
-                cx.notify();
-                return;
-            }
-            _ => {}
-        }
+    // Handle Escape to close the lightbox
+    if viewer.lightbox.is_some() && event.keystroke.key.as_str() == "escape" {
+        viewer.lightbox = None;
+        cx.notify();
+        return;
+    }
+
+    // Handle Escape to close the image report
+    if viewer.image_report.is_some() && event.keystroke.key.as_str() == "escape" {
+        viewer.image_report = None;
+        cx.notify();
+        return;
+    }
+
+    // Handle Escape to close the open-file error notification
+    if viewer.open_file_error.is_some() && event.keystroke.key.as_str() == "escape" {
+        viewer.open_file_error = None;
+        cx.notify();
+        return;
+    }
+
+    // Handle Escape to close the newest toast notification
+    if !viewer.notifications.is_empty() && event.keystroke.key.as_str() == "escape" {
+        viewer.notifications.dismiss_latest();
+        cx.notify();
+        return;
     }
 
     // ========== KEYBOARD-ONLY NAVIGATION ==========
     // Handle Tab/Shift-Tab for focus cycling (only when not in input modes)
-    if viewer.search_state.is_none() && !viewer.show_goto_line {
+    if !viewer.search_input_active() && !viewer.show_goto_line {
         if event.keystroke.key.as_str() == "tab" {
             match event.keystroke.modifiers.shift {
                 true => {
@@ -404,7 +809,7 @@ pub fn handle_key_down(
         // Handle Enter key to activate focused element (when not in input modes)
         if event.keystroke.key.as_str() == "enter" && viewer.current_focus_index.is_some() {
             debug!("Enter: activating focused element");
-            if viewer.activate_focused_element() {
+            if viewer.activate_focused_element(cx) {
                 cx.notify();
             }
             return;
@@ -417,22 +822,16 @@ pub fn handle_key_down(
             // Only accept single characters for marks
             match mode {
                 crate::internal::viewer::MarkMode::Set => {
-                    debug!("Set mark '{}' at {}", char, viewer.scroll_state.scroll_y);
-                    viewer.marks.insert(char, viewer.scroll_state.scroll_y);
+                    viewer.set_mark(char);
                     info!("Mark '{}' set", char);
                 }
+                crate::internal::viewer::MarkMode::SetAt(line) => {
+                    viewer.set_mark_at(char, line);
+                    info!("Mark '{}' set at line {}", char, line + 1);
+                }
                 crate::internal::viewer::MarkMode::Jump => {
-                    match viewer.marks.get(&char) {
-                        Some(&y) => {
-                            debug!("Jump to mark '{}' at {}", char, y);
-                            viewer.scroll_state.scroll_y = y;
-                            // Clamp scroll
-                            viewer.scroll_state.reclamp();
-                        }
-                        None => {
-                            debug!("Mark '{}' not set", char);
-                            info!("Mark '{}' not set", char);
-                        }
+                    if !viewer.jump_to_mark(char) {
+                        info!("Mark '{}' not set", char);
                     }
                 }
             }
@@ -444,19 +843,27 @@ pub fn handle_key_down(
     }
 
     // Vi-style navigation (j/k for down/up) - only when not in input modes
-    if viewer.search_state.is_none() && !viewer.show_goto_line {
+    if !viewer.search_input_active() && !viewer.show_goto_line {
         match event.keystroke.key.as_str() {
+            "j" if event.is_held => {
+                viewer.start_held_scroll("j", 1.0, cx);
+                return;
+            }
             "j" => {
                 viewer.z_pressed_once = false; // Reset z state
                 debug!("Vi-style: j (scroll down)");
-                viewer.scroll_state.scroll_down(arrow_increment);
+                viewer.scroll_by(arrow_increment);
                 cx.notify();
                 return;
             }
+            "k" if event.is_held => {
+                viewer.start_held_scroll("k", -1.0, cx);
+                return;
+            }
             "k" => {
                 viewer.z_pressed_once = false; // Reset z state
                 debug!("Vi-style: k (scroll up)");
-                viewer.scroll_state.scroll_up(arrow_increment);
+                viewer.scroll_by(-arrow_increment);
                 cx.notify();
                 return;
             }
@@ -464,32 +871,54 @@ pub fn handle_key_down(
                 viewer.z_pressed_once = false; // Reset z state
                 if event.keystroke.modifiers.shift {
                     debug!("Vi-style: G (scroll to bottom)");
-                    viewer.scroll_state.scroll_to_bottom();
+                    viewer.scroll_to_y(viewer.document().scroll_state.max_scroll_y);
                 } else {
                     debug!("Vi-style: g (scroll to top)");
-                    viewer.scroll_state.scroll_to_top();
+                    viewer.scroll_to_y(0.0);
                 }
                 cx.notify();
                 return;
             }
+            "n" if viewer.document().search_state.is_some() => {
+                viewer.z_pressed_once = false;
+                let forward = !event.keystroke.modifiers.shift;
+                debug!(
+                    "Vi-style: {} (search match)",
+                    match forward {
+                        true => "n",
+                        false => "N",
+                    }
+                );
+                viewer.step_search_match(forward);
+                cx.notify();
+                return;
+            }
+            "escape" if viewer.document().search_state.is_some() => {
+                // Clear the highlights left behind by the search bar
+                debug!("Clearing kept search highlights (Escape)");
+                viewer.clear_filter();
+                viewer.document_mut().search_state = None;
+                viewer.document_mut().search_input.clear();
+                viewer.search_highlights_kept = false;
+                cx.notify();
+                return;
+            }
             "q" => {
                 debug!("Quit application (q)");
-                cx.quit();
+                viewer.quit(cx);
                 return;
             }
             "d" if event.keystroke.modifiers.control => {
                 viewer.z_pressed_once = false;
                 debug!("Half-page down (Ctrl+d)");
-                viewer
-                    .scroll_state
-                    .scroll_down(viewer.viewport_height * 0.5);
+                viewer.scroll_by(viewer.viewport_height * 0.5);
                 cx.notify();
                 return;
             }
             "u" if event.keystroke.modifiers.control => {
                 viewer.z_pressed_once = false;
                 debug!("Half-page up (Ctrl+u)");
-                viewer.scroll_state.scroll_up(viewer.viewport_height * 0.5);
+                viewer.scroll_by(-viewer.viewport_height * 0.5);
                 cx.notify();
                 return;
             }
@@ -497,7 +926,8 @@ pub fn handle_key_down(
                 if viewer.z_pressed_once {
                     // Second 'z' -> zz command (center view)
                     debug!("Center view (zz)");
-                    let _center_y = viewer.scroll_state.scroll_y + (viewer.viewport_height / 2.0);
+                    let _center_y =
+                        viewer.document().scroll_state.scroll_y + (viewer.viewport_height / 2.0);
                     // This is simple centering. For more advanced "current line" centering:
                     // We'd need to approximate which line is "current".
                     // But effectively scroll_y IS the top line, so we want that top line to be in the middle?
@@ -507,8 +937,9 @@ pub fn handle_key_down(
                     // But wait, scroll_y determines what is at the top.
                     // If we want the content currently at TOP to be at CENTER, we need to scroll UP.
                     // New Scroll Y = Old Scroll Y - (Viewport / 2)
-                    let target = viewer.scroll_state.scroll_y - (viewer.viewport_height / 2.0);
-                    viewer.scroll_state.scroll_y = target.max(0.0);
+                    let target = viewer.document().scroll_state.destination()
+                        - (viewer.viewport_height / 2.0);
+                    viewer.scroll_to_y(target);
 
                     viewer.z_pressed_once = false;
                     info!("Centered view");
@@ -546,25 +977,44 @@ pub fn handle_key_down(
     // Check for Ctrl+C to quit (Global)
     if event.keystroke.modifiers.control && event.keystroke.key.as_str() == "c" {
         debug!("Quit application (Ctrl+C)");
-        cx.quit();
+        viewer.quit(cx);
         return;
     }
 
     // Handle search mode input
-    if viewer.search_state.is_some() {
+    if viewer.search_input_active() {
         match event.keystroke.key.as_str() {
             "escape" => {
+                // Restore the full document first when filtering
+                if viewer.clear_filter() {
+                    debug!("Leaving filter view (Escape)");
+                    cx.notify();
+                    return;
+                }
+                // Keep the matches highlighted for n / N; Escape again clears them
+                if viewer
+                    .document()
+                    .search_state
+                    .as_ref()
+                    .is_some_and(|state| state.match_count() > 0)
+                {
+                    debug!("Closing search bar, keeping highlights (Escape)");
+                    viewer.search_highlights_kept = true;
+                    viewer.search_history_index = None;
+                    cx.notify();
+                    return;
+                }
                 // Exit search mode
                 debug!("Exiting search mode (Escape)");
-                viewer.search_state = None;
-                viewer.search_input.clear();
+                viewer.document_mut().search_state = None;
+                viewer.document_mut().search_input.clear();
                 viewer.search_history_index = None;
                 cx.notify();
                 return;
             }
             "enter" if event.keystroke.modifiers.shift => {
                 // Previous match
-                if let Some(state) = &mut viewer.search_state {
+                if let Some(state) = &mut viewer.document_mut().search_state {
                     state.prev_match();
                     debug!(
                         "Previous match (key_down): {:?}",
@@ -577,8 +1027,8 @@ pub fn handle_key_down(
             }
             "enter" => {
                 // Next match AND save to history
-                if !viewer.search_input.trim().is_empty() {
-                    let input = viewer.search_input.clone();
+                if !viewer.document().search_input.trim().is_empty() {
+                    let input = viewer.document().search_input.clone();
                     let history = &mut viewer.config.search_history;
 
                     // Add to history if it's different from the last item
@@ -600,7 +1050,7 @@ pub fn handle_key_down(
                     }
                 }
 
-                if let Some(state) = &mut viewer.search_state {
+                if let Some(state) = &mut viewer.document_mut().search_state {
                     state.next_match();
                     debug!("Next match (key_down): {:?}", state.current_match_number());
                     viewer.scroll_to_current_match();
@@ -620,12 +1070,8 @@ pub fn handle_key_down(
 
                     viewer.search_history_index = Some(new_index);
                     if let Some(item) = viewer.config.search_history.get(new_index) {
-                        viewer.search_input = item.clone();
-                        viewer.search_state = Some(SearchState::new(
-                            viewer.search_input.clone(),
-                            &viewer.markdown_content,
-                        ));
-                        viewer.scroll_to_current_match();
+                        viewer.document_mut().search_input = item.clone();
+                        viewer.update_search();
                     }
                 }
                 cx.notify();
@@ -639,36 +1085,42 @@ pub fn handle_key_down(
                         Some(new_index) if new_index < history_len => {
                             viewer.search_history_index = Some(new_index);
                             if let Some(item) = viewer.config.search_history.get(new_index) {
-                                viewer.search_input = item.clone();
-                                viewer.search_state = Some(SearchState::new(
-                                    viewer.search_input.clone(),
-                                    &viewer.markdown_content,
-                                ));
-                                viewer.scroll_to_current_match();
+                                viewer.document_mut().search_input = item.clone();
+                                viewer.update_search();
                             }
                         }
                         _ => {
                             // End of history, clear input
                             viewer.search_history_index = None;
-                            viewer.search_input.clear();
-                            viewer.search_state =
-                                Some(SearchState::new(String::new(), &viewer.markdown_content));
+                            viewer.document_mut().search_input.clear();
+                            viewer.update_search();
                         }
                     }
                     cx.notify();
                 }
                 return;
             }
+            "r" | "w" | "c"
+                if event.keystroke.modifiers.alt
+                    && !event.keystroke.modifiers.control
+                    && !event.keystroke.modifiers.platform =>
+            {
+                let mode = match event.keystroke.key.as_str() {
+                    "r" => SearchMode::Regex,
+                    "w" => SearchMode::WholeWord,
+                    _ => SearchMode::CaseSensitive,
+                };
+                viewer.toggle_search_mode(mode);
+                debug!("Search mode: {:?}", viewer.search_mode);
+                cx.notify();
+                return;
+            }
             "backspace" => {
                 // Remove last character
-                viewer.search_input.pop();
+                viewer.document_mut().search_input.pop();
                 viewer.search_history_index = None; // Reset history index on manual edit
-                viewer.search_state = Some(SearchState::new(
-                    viewer.search_input.clone(),
-                    &viewer.markdown_content,
-                ));
-                debug!("Search query: '{}'", viewer.search_input);
-                viewer.scroll_to_current_match();
+                viewer.update_search();
+                debug!("Search query: '{}'", viewer.document().search_input);
                 cx.notify();
                 return;
             }
@@ -677,14 +1129,10 @@ pub fn handle_key_down(
                 && !event.keystroke.modifiers.platform =>
             {
                 // Add character to search
-                viewer.search_input.push_str(key);
+                viewer.document_mut().search_input.push_str(key);
                 viewer.search_history_index = None; // Reset history index on manual edit
-                viewer.search_state = Some(SearchState::new(
-                    viewer.search_input.clone(),
-                    &viewer.markdown_content,
-                ));
-                debug!("Search query: '{}'", viewer.search_input);
-                viewer.scroll_to_current_match();
+                viewer.update_search();
+                debug!("Search query: '{}'", viewer.document().search_input);
                 cx.notify();
                 return;
             }
@@ -703,6 +1151,23 @@ pub fn handle_key_down(
                 cx.notify();
                 return;
             }
+            "enter" if viewer.goto_percent => {
+                debug!("Jump-to-percent execute: '{}'", viewer.goto_line_input);
+                match MarkdownViewer::parse_percent(&viewer.goto_line_input) {
+                    Some(percent) => {
+                        debug!("Scrolled to {}%", percent);
+                        viewer.scroll_to_percent(percent);
+                        viewer.show_goto_line = false;
+                        viewer.goto_line_input.clear();
+                    }
+                    None => {
+                        debug!("Invalid percentage: '{}'", viewer.goto_line_input);
+                        // Keep dialog open for invalid input
+                    }
+                }
+                cx.notify();
+                return;
+            }
             "enter" => {
                 // Execute go-to-line
                 debug!("Go-to-line execute: '{}'", viewer.goto_line_input);
@@ -750,41 +1215,108 @@ pub fn handle_key_down(
     }
 
     match event.keystroke.key.as_str() {
-        "up" => viewer.scroll_state.scroll_up(arrow_increment),
-        "down" => viewer.scroll_state.scroll_down(arrow_increment),
-        "pageup" => viewer
-            .scroll_state
-            .page_up(viewer.viewport_height * page_percent),
-        "pagedown" => viewer
-            .scroll_state
-            .page_down(viewer.viewport_height * page_percent),
-        "home" => viewer.scroll_state.scroll_to_top(),
-        "end" => viewer.scroll_state.scroll_to_bottom(),
-        "space" if event.keystroke.modifiers.shift => viewer
-            .scroll_state
-            .page_up(viewer.viewport_height * space_percent),
-        "space" => viewer
-            .scroll_state
-            .page_down(viewer.viewport_height * space_percent),
+        "up" if event.is_held => viewer.start_held_scroll("up", -1.0, cx),
+        "down" if event.is_held => viewer.start_held_scroll("down", 1.0, cx),
+        "up" => viewer.scroll_by(-arrow_increment),
+        "down" => viewer.scroll_by(arrow_increment),
+        "pageup" => viewer.page_up(),
+        "pagedown" => viewer.page_down(),
+        "home" => viewer.scroll_to_y(0.0),
+        "end" => viewer.scroll_to_y(viewer.document().scroll_state.max_scroll_y),
+        "space" if event.keystroke.modifiers.shift => {
+            viewer.scroll_by(-viewer.viewport_height * space_percent * 0.8)
+        }
+        "space" => viewer.scroll_by(viewer.viewport_height * space_percent * 0.8),
+        "escape" if viewer.peek_mode => {
+            debug!("Close peek window (Escape)");
+            viewer.quit(cx);
+            return;
+        }
         _ => {}
     }
     cx.notify();
 }
 
+/// Stop continuous scrolling when the held scroll key is released
+pub fn handle_key_up(
+    viewer: &mut MarkdownViewer,
+    event: &KeyUpEvent,
+    _window: &mut gpui::Window,
+    _cx: &mut Context<MarkdownViewer>,
+) {
+    viewer.stop_held_scroll(&event.keystroke.key);
+}
+
 pub fn handle_scroll_wheel(
     viewer: &mut MarkdownViewer,
     event: &ScrollWheelEvent,
     _window: &mut gpui::Window,
     cx: &mut Context<MarkdownViewer>,
 ) {
-    let delta = event
-        .delta
-        .pixel_delta(px(viewer.config.theme.base_text_size))
-        .y;
-    let delta_f32: f32 = delta.into();
-    match delta_f32 {
-        d if d > 0.0 => viewer.scroll_state.scroll_up(d),
-        d => viewer.scroll_state.scroll_down(-d),
+    // The document stays put behind a confirmation dialog, the tour or the lightbox
+    if viewer.confirm_dialog.is_some() || viewer.tour.is_some() || viewer.lightbox.is_some() {
+        return;
+    }
+
+    match wheel_delta(viewer, event) {
+        d if d > 0.0 => viewer.document_mut().scroll_state.scroll_up(d),
+        d => viewer.document_mut().scroll_state.scroll_down(-d),
     }
     cx.notify();
 }
+
+/// Zoom the lightbox image around the mouse
+pub fn handle_lightbox_scroll_wheel(
+    viewer: &mut MarkdownViewer,
+    event: &ScrollWheelEvent,
+    window: &mut gpui::Window,
+    cx: &mut Context<MarkdownViewer>,
+) {
+    cx.stop_propagation();
+    let delta = wheel_delta(viewer, event);
+    let viewport = window.viewport_size();
+    let anchor = (
+        f32::from(event.position.x - viewport.width / 2.0),
+        f32::from(event.position.y - viewport.height / 2.0),
+    );
+    if let Some(lightbox) = viewer.lightbox.as_mut() {
+        lightbox.zoom_by(delta, anchor);
+        cx.notify();
+    }
+}
+
+/// Scroll the source pane, which moves the rendered document along with it
+pub fn handle_source_scroll_wheel(
+    viewer: &mut MarkdownViewer,
+    event: &ScrollWheelEvent,
+    _window: &mut gpui::Window,
+    cx: &mut Context<MarkdownViewer>,
+) {
+    cx.stop_propagation();
+    if viewer.confirm_dialog.is_some() || viewer.tour.is_some() {
+        return;
+    }
+
+    viewer.scroll_source_by(-wheel_delta(viewer, event));
+    cx.notify();
+}
+
+/// Pixels a wheel event scrolls by (positive towards the top), after the
+/// configured inversion and multiplier
+fn wheel_delta(viewer: &MarkdownViewer, event: &ScrollWheelEvent) -> f32 {
+    let scroll = &viewer.config.scroll;
+    let delta_f32 = match event.delta {
+        // Trackpads report precise pixel deltas
+        ScrollDelta::Pixels(delta) => f32::from(delta.y),
+        // Mouse wheels report notches; move a few text lines per notch
+        ScrollDelta::Lines(delta) => {
+            let line_height =
+                viewer.config.theme.base_text_size * viewer.config.theme.line_height_multiplier;
+            delta.y * line_height * WHEEL_LINES_PER_NOTCH
+        }
+    };
+    (match scroll.invert_wheel {
+        true => -delta_f32,
+        false => delta_f32,
+    }) * scroll.wheel_multiplier
+}
@@ -0,0 +1,99 @@
+//! History of document exports
+//!
+//! Each successful PDF, HTML or EPUB export is recorded in the config with
+//! its document, format and destination, most recent first. Re-exporting
+//! (Cmd+Shift+R) repeats the document's latest export to the same file
+//! without asking to overwrite it again, and the exports overlay
+//! (Cmd+Shift+L) lists the whole history.
+
+use std::path::Path;
+
+use crate::config::{ExportFormat, ExportRecord};
+
+/// Exports kept in the history
+pub const MAX_EXPORT_HISTORY: usize = 20;
+
+impl ExportFormat {
+    /// Name shown in notifications and the exports overlay
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "PDF",
+            ExportFormat::Html => "HTML",
+            ExportFormat::Epub => "EPUB",
+        }
+    }
+
+    /// Extension of the file written next to the document
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Html => "html",
+            ExportFormat::Epub => "epub",
+        }
+    }
+}
+
+/// Put an export at the front of `history`
+///
+/// An earlier export of the same document in the same format is replaced,
+/// and the history is capped at [`MAX_EXPORT_HISTORY`] entries.
+pub fn record_export(history: &mut Vec<ExportRecord>, record: ExportRecord) {
+    history.retain(|existing| {
+        !(existing.document == record.document && existing.format == record.format)
+    });
+    history.insert(0, record);
+    history.truncate(MAX_EXPORT_HISTORY);
+}
+
+/// The latest export of `document`, in any format
+pub fn last_export<'h>(history: &'h [ExportRecord], document: &Path) -> Option<&'h ExportRecord> {
+    history
+        .iter()
+        .find(|record| Path::new(&record.document) == document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(document: &str, format: ExportFormat) -> ExportRecord {
+        ExportRecord {
+            document: document.to_string(),
+            format,
+            destination: format!("{}.{}", document, format.extension()),
+        }
+    }
+
+    #[test]
+    fn latest_export_per_document_comes_first() {
+        let mut history = Vec::new();
+        record_export(&mut history, record("/a.md", ExportFormat::Pdf));
+        record_export(&mut history, record("/b.md", ExportFormat::Html));
+        record_export(&mut history, record("/a.md", ExportFormat::Epub));
+        record_export(&mut history, record("/b.md", ExportFormat::Html));
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0], record("/b.md", ExportFormat::Html));
+        assert_eq!(
+            last_export(&history, Path::new("/a.md")).map(|r| r.format),
+            Some(ExportFormat::Epub)
+        );
+        assert!(last_export(&history, Path::new("/c.md")).is_none());
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut history = Vec::new();
+        for index in 0..MAX_EXPORT_HISTORY + 5 {
+            record_export(
+                &mut history,
+                record(&format!("/{}.md", index), ExportFormat::Pdf),
+            );
+        }
+        assert_eq!(history.len(), MAX_EXPORT_HISTORY);
+        assert_eq!(
+            history[0].document,
+            format!("/{}.md", MAX_EXPORT_HISTORY + 4)
+        );
+    }
+}
@@ -4,9 +4,15 @@
 //! markdown content with proper error handling.
 
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
 use tracing::{debug, info};
 
+use super::archive::{self, ArchivePath};
+use crate::config::RenderMode;
+
 /// Check if a file has a supported extension
 ///
 /// # Arguments
@@ -27,23 +33,52 @@ pub fn is_supported_extension(file_path: &str, supported_extensions: &[String])
         .unwrap_or(false)
 }
 
+/// `path` resolved against `base_dir`
+///
+/// A base of `.` (or an empty path) is the process working directory and
+/// leaves `path` as given; absolute paths are never changed.
+pub fn in_base_dir(base_dir: &Path, path: &str) -> PathBuf {
+    match base_dir
+        .components()
+        .all(|component| component == Component::CurDir)
+    {
+        true => PathBuf::from(path),
+        false => base_dir.join(path),
+    }
+}
+
 /// Resolves the markdown file path based on CLI argument or default
 ///
+/// Relative paths, given or default, are looked up in `base_dir` rather than
+/// the process working directory, so tests and embedders can work in a
+/// directory of their own.
+///
 /// # Arguments
+/// * `base_dir` - Directory relative paths are resolved against; `.` for the
+///   working directory
 /// * `file_path` - Optional file path from CLI arguments
+/// * `default_files` - Files (or glob patterns like `docs/*.md`) to try in order
+///   when no path is given
 /// * `supported_extensions` - List of supported file extensions (without dots)
 ///
 /// # Returns
 /// * `Ok(String)` - The resolved file path
 /// * `Err` - Error if file resolution fails
 pub fn resolve_markdown_file_path(
+    base_dir: &Path,
     file_path: Option<&str>,
+    default_files: &[String],
     supported_extensions: &[String],
 ) -> Result<String> {
     match file_path {
         Some(path) => {
             debug!("Resolving file path: {}", path);
-            if !Path::new(path).exists() {
+            let resolved = in_base_dir(base_dir, path).to_string_lossy().to_string();
+            let exists = match ArchivePath::parse(&resolved) {
+                Some(archive_path) => archive_path.exists(),
+                None => Path::new(&resolved).exists(),
+            };
+            if !exists {
                 anyhow::bail!("File not found: {}", path);
             }
 
@@ -56,30 +91,26 @@ pub fn resolve_markdown_file_path(
                 );
             }
 
-            info!("File found: {}", path);
-            Ok(path.to_string())
+            info!("File found: {}", resolved);
+            Ok(resolved)
         }
         None => {
-            debug!("No file specified, trying default files");
-            // Try README.md first, then TODO.md as fallback
-            let readme_path = "README.md";
-            let todo_path = "TODO.md";
-
-            match (
-                Path::new(readme_path).exists(),
-                Path::new(todo_path).exists(),
-            ) {
-                (true, _) => {
-                    info!("Using default file: {}", readme_path);
-                    Ok(readme_path.to_string())
+            debug!(
+                "No file specified, trying default files: {:?}",
+                default_files
+            );
+            match find_default_file(base_dir, default_files, supported_extensions) {
+                Some(found) => {
+                    info!("Using default file: {}", found);
+                    Ok(found)
                 }
-                (false, true) => {
-                    info!("Using fallback file: {}", todo_path);
-                    Ok(todo_path.to_string())
+                None if default_files.is_empty() => {
+                    anyhow::bail!("No default files configured. Please specify a markdown file.");
                 }
-                _ => {
+                None => {
                     anyhow::bail!(
-                        "Default files README.md and TODO.md not found. Please specify a markdown file."
+                        "Default files {} not found. Please specify a markdown file.",
+                        join_with_and(default_files)
                     );
                 }
             }
@@ -87,18 +118,437 @@ pub fn resolve_markdown_file_path(
     }
 }
 
+/// Expand command-line file arguments, resolving glob patterns
+///
+/// Glob arguments expand to their alphabetically-sorted matches with a
+/// supported extension; other arguments are kept as given, in order.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The expanded paths
+/// * `Err` - If a glob pattern matches no supported files
+pub fn expand_file_arguments(
+    args: &[String],
+    supported_extensions: &[String],
+) -> Result<Vec<String>> {
+    let mut expanded = Vec::new();
+    for arg in args {
+        match is_glob_pattern(arg) {
+            true => {
+                let matches: Vec<String> = expand_glob(arg)
+                    .into_iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .filter(|p| is_supported_extension(p, supported_extensions))
+                    .collect();
+                if matches.is_empty() {
+                    anyhow::bail!("No supported files match pattern: {}", arg);
+                }
+                debug!("Pattern {} expanded to {} files", arg, matches.len());
+                expanded.extend(matches);
+            }
+            false => expanded.push(arg.clone()),
+        }
+    }
+    Ok(expanded)
+}
+
+/// Find the supported file next to `current` in its directory
+///
+/// Files are ordered alphabetically (case-insensitively) by name. Returns the
+/// file after `current` when `forward` is true and the one before it
+/// otherwise, or `None` at either end of the directory listing.
+pub fn sibling_file(
+    current: &Path,
+    supported_extensions: &[String],
+    forward: bool,
+) -> Option<PathBuf> {
+    let dir = match current.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let current_name = current.file_name()?;
+
+    let mut names: Vec<_> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name())
+        .filter(|name| {
+            name == current_name
+                || is_supported_extension(&name.to_string_lossy(), supported_extensions)
+        })
+        .collect();
+    let sort_key = |name: &OsStr| (name.to_string_lossy().to_lowercase(), name.to_os_string());
+    names.sort_by_key(|name| sort_key(name));
+
+    // The current file may have been deleted; fall back to where it would sort
+    let target = match (
+        names.binary_search_by_key(&sort_key(current_name), |name| sort_key(name)),
+        forward,
+    ) {
+        (Ok(index), true) => index + 1,
+        (Err(index), true) => index,
+        (Ok(index) | Err(index), false) => index.checked_sub(1)?,
+    };
+
+    names.get(target).map(|name| dir.join(name))
+}
+
+/// Choose how a file is rendered from its extension
+///
+/// Looks the extension up (case-insensitively) in the configured
+/// `extension_modes`; unlisted extensions are parsed as markdown.
+pub fn render_mode_for_path(
+    path: &Path,
+    extension_modes: &BTreeMap<String, RenderMode>,
+) -> RenderMode {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| {
+            extension_modes
+                .iter()
+                .find(|(configured, _)| configured.eq_ignore_ascii_case(ext))
+                .map(|(_, mode)| *mode)
+        })
+        .unwrap_or_default()
+}
+
+/// Index files looked up, in order, when a directory is passed on the command line
+pub const DIRECTORY_INDEX_FILES: &[&str] = &["README.md", "index.md"];
+
+/// What the viewer should open at startup
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaunchTarget {
+    /// A markdown file to render
+    File(String),
+    /// A directory or zip/epub archive without an index file; the file finder
+    /// is opened scoped to it
+    Directory(PathBuf),
+}
+
+/// Resolves the startup target, accepting a directory as well as a file
+///
+/// A directory resolves to its README.md or index.md when present, otherwise
+/// to [`LaunchTarget::Directory`]. A zip or epub archive is treated the same
+/// way, looking for the index at the root of the archive. Everything else is
+/// handled by [`resolve_markdown_file_path`]; relative paths are resolved
+/// against `base_dir` in every case.
+pub fn resolve_launch_target(
+    base_dir: &Path,
+    file_path: Option<&str>,
+    default_files: &[String],
+    supported_extensions: &[String],
+) -> Result<LaunchTarget> {
+    let resolved = file_path.map(|path| in_base_dir(base_dir, path).to_string_lossy().to_string());
+    if let Some(path) = resolved.as_deref()
+        && Path::new(path).is_dir()
+    {
+        debug!("Resolving directory argument: {}", path);
+        return Ok(match find_directory_index(Path::new(path)) {
+            Some(index) => {
+                info!("Using directory index: {:?}", index);
+                LaunchTarget::File(index.to_string_lossy().to_string())
+            }
+            None => {
+                info!("No index file in {}, opening file finder", path);
+                LaunchTarget::Directory(PathBuf::from(path))
+            }
+        });
+    }
+
+    if let Some(path) = resolved.as_deref()
+        && archive::is_archive(Path::new(path))
+        && Path::new(path).is_file()
+    {
+        debug!("Resolving archive argument: {}", path);
+        return Ok(match archive::find_archive_index(Path::new(path)) {
+            Some(index) => {
+                info!("Using archive index: {}", index);
+                LaunchTarget::File(index.to_string())
+            }
+            None => {
+                info!("No index file in {}, opening file finder", path);
+                LaunchTarget::Directory(PathBuf::from(path))
+            }
+        });
+    }
+
+    resolve_markdown_file_path(base_dir, file_path, default_files, supported_extensions)
+        .map(LaunchTarget::File)
+}
+
+/// Find the index file (README.md, then index.md) inside a directory
+pub fn find_directory_index(dir: &Path) -> Option<PathBuf> {
+    DIRECTORY_INDEX_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Find the first existing default file, searching entries in configured order
+///
+/// Glob entries expand to their alphabetically-sorted matches, keeping only
+/// files with a supported extension.
+fn find_default_file(
+    base_dir: &Path,
+    default_files: &[String],
+    supported_extensions: &[String],
+) -> Option<String> {
+    default_files.iter().find_map(|entry| {
+        let entry = in_base_dir(base_dir, entry);
+        let candidates = match is_glob_pattern(&entry.to_string_lossy()) {
+            true => expand_glob(&entry.to_string_lossy())
+                .into_iter()
+                .filter(|p| is_supported_extension(&p.to_string_lossy(), supported_extensions))
+                .collect(),
+            false => vec![entry],
+        };
+        candidates
+            .into_iter()
+            .find(|candidate| candidate.is_file())
+            .map(|found| found.to_string_lossy().to_string())
+    })
+}
+
+/// Join names for messages: `a`, `a and b`, `a, b and c`
+fn join_with_and(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [init @ .., last] => format!("{} and {}", init.join(", "), last),
+    }
+}
+
+/// Check whether a path contains glob wildcards (`*` or `?`)
+pub fn is_glob_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Expand a glob pattern such as `docs/*.md` into the matching paths
+///
+/// Wildcards (`*` and `?`) are supported within any single path component;
+/// recursive `**` matching is not. Results are sorted alphabetically and a
+/// pattern without wildcards yields the path itself when it exists.
+pub fn expand_glob(pattern: &str) -> Vec<PathBuf> {
+    let mut candidates = vec![PathBuf::new()];
+
+    for component in Path::new(pattern).components() {
+        candidates = match component {
+            Component::Normal(part) if is_glob_pattern(&part.to_string_lossy()) => {
+                let part = part.to_string_lossy();
+                candidates
+                    .iter()
+                    .flat_map(|dir| matching_entries(dir, &part))
+                    .collect()
+            }
+            other => candidates
+                .into_iter()
+                .map(|dir| dir.join(other.as_os_str()))
+                .collect(),
+        };
+    }
+
+    let mut matches: Vec<PathBuf> = candidates.into_iter().filter(|p| p.exists()).collect();
+    matches.sort();
+    matches
+}
+
+/// List entries of `dir` whose names match a single-component wildcard pattern
+fn matching_entries(dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let read_from = match dir.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => dir,
+    };
+    match std::fs::read_dir(read_from) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| wildcard_match(pattern, &entry.file_name().to_string_lossy()))
+            .map(|entry| dir.join(entry.file_name()))
+            .collect(),
+        Err(e) => {
+            debug!("Skipping unreadable glob directory {:?}: {}", read_from, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Match a name against a wildcard pattern where `*` matches any run of
+/// characters and `?` matches exactly one character
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` absorb one more character and retry
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Resolve a path to its canonical absolute form, following symlinks
+///
+/// A file that does not exist (e.g. just deleted) cannot be canonicalized
+/// itself, so its parent directory is canonicalized and the file name
+/// re-attached. Falls back to the path as given when neither exists. For a
+/// file inside an archive only the archive part is canonicalized.
+pub fn canonical_path(path: &Path) -> PathBuf {
+    if let Some(archive_path) = ArchivePath::parse(&path.to_string_lossy()) {
+        return ArchivePath::new(canonical_path(&archive_path.archive), archive_path.entry)
+            .to_path_buf();
+    }
+
+    std::fs::canonicalize(path)
+        .or_else(|_| {
+            let parent = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            std::fs::canonicalize(parent).map(|dir| dir.join(path.file_name().unwrap_or_default()))
+        })
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Last modification time of the file on disk, if it can be read
+///
+/// For a file inside an archive this is the archive's modification time.
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(archive::containing_file(path))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Files larger than this need confirmation before opening
+pub const MAX_FILE_SIZE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Number of leading bytes inspected when checking for binary content
+const BINARY_SAMPLE_BYTES: usize = 8 * 1024;
+
+/// Reason a file needs confirmation before it is opened
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadGuard {
+    /// File exceeds [`MAX_FILE_SIZE_BYTES`]
+    TooLarge { size: u64 },
+    /// File content does not look like text
+    Binary,
+}
+
+/// Error returned by [`load_markdown_content`] when a file trips a guard
+///
+/// Callers can `downcast_ref::<LoadGuardError>()` the returned `anyhow::Error`
+/// to offer opening the file anyway via [`load_markdown_content_unchecked`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadGuardError {
+    pub path: String,
+    pub guard: LoadGuard,
+}
+
+impl std::fmt::Display for LoadGuardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.guard {
+            LoadGuard::TooLarge { size } => {
+                write!(f, "'{}' is {}", self.path, format_file_size(size))
+            }
+            LoadGuard::Binary => write!(f, "'{}' appears to be binary", self.path),
+        }
+    }
+}
+
+impl std::error::Error for LoadGuardError {}
+
+/// Format a byte count for display, e.g. `120.0 MB`
+pub fn format_file_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    let bytes_f = bytes as f64;
+    match bytes_f {
+        b if b >= GB => format!("{:.1} GB", b / GB),
+        b if b >= MB => format!("{:.1} MB", b / MB),
+        b if b >= KB => format!("{:.1} KB", b / KB),
+        _ => format!("{} bytes", bytes),
+    }
+}
+
+/// Heuristic check for binary content in a sample of a file
+///
+/// A NUL byte, invalid UTF-8 or a high share of control characters marks the
+/// sample as binary. A multi-byte character cut off at the end of the sample
+/// is not treated as invalid.
+pub fn looks_binary(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return true;
+    }
+    if let Err(e) = std::str::from_utf8(sample)
+        && e.error_len().is_some()
+    {
+        return true;
+    }
+
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+        .count();
+    control * 10 > sample.len()
+}
+
 /// Loads markdown content from a file
 ///
+/// Files larger than [`MAX_FILE_SIZE_BYTES`] or that look binary are rejected
+/// with a [`LoadGuardError`] so the caller can ask before opening them.
+/// `file_path` may also name a file inside an archive (`book.zip!/intro.md`).
+///
 /// # Arguments
 /// * `file_path` - Path to the markdown file
 ///
 /// # Returns
 /// * `Ok(String)` - The file content
-/// * `Err` - Error if loading fails
+/// * `Err` - Error if loading fails or a guard is tripped
 pub fn load_markdown_content(file_path: &str) -> Result<String> {
     debug!("Loading markdown content from: {}", file_path);
-    let content = std::fs::read_to_string(file_path)
-        .context(format!("Failed to read file '{}'", file_path))?;
+    let size = match ArchivePath::parse(file_path) {
+        Some(archive_path) => archive_path.size()?,
+        None => std::fs::metadata(file_path)
+            .context(format!("Failed to read file '{}'", file_path))?
+            .len(),
+    };
+    if size > MAX_FILE_SIZE_BYTES {
+        return Err(LoadGuardError {
+            path: file_path.to_string(),
+            guard: LoadGuard::TooLarge { size },
+        }
+        .into());
+    }
+
+    let bytes = read_source(file_path)?;
+    if looks_binary(&bytes[..bytes.len().min(BINARY_SAMPLE_BYTES)]) {
+        return Err(LoadGuardError {
+            path: file_path.to_string(),
+            guard: LoadGuard::Binary,
+        }
+        .into());
+    }
+
+    let content =
+        String::from_utf8(bytes).context(format!("File '{}' is not valid UTF-8", file_path))?;
     info!(
         "Successfully loaded {} bytes from {}",
         content.len(),
@@ -107,6 +557,29 @@ pub fn load_markdown_content(file_path: &str) -> Result<String> {
     Ok(content)
 }
 
+/// Loads a file without size or binary checks, after the user confirmed it
+///
+/// Invalid UTF-8 sequences are replaced so binary content can still be shown.
+pub fn load_markdown_content_unchecked(file_path: &str) -> Result<String> {
+    debug!("Loading content without guards from: {}", file_path);
+    let bytes = read_source(file_path)?;
+    let content = String::from_utf8_lossy(&bytes).into_owned();
+    info!(
+        "Loaded {} bytes from {} (guards skipped)",
+        content.len(),
+        file_path
+    );
+    Ok(content)
+}
+
+/// Read a file from disk or from inside an archive
+fn read_source(file_path: &str) -> Result<Vec<u8>> {
+    match ArchivePath::parse(file_path) {
+        Some(archive_path) => archive_path.read(),
+        None => std::fs::read(file_path).context(format!("Failed to read file '{}'", file_path)),
+    }
+}
+
 /// Resolves an image path relative to the markdown file
 ///
 /// # Arguments
@@ -157,6 +630,42 @@ pub fn resolve_image_path(image_path: &str, markdown_file_path: &Path) -> String
     }
 }
 
+/// Resolve a link to another document relative to the markdown file
+///
+/// Returns `None` for links the viewer should not open itself: URLs with a
+/// scheme (`https:`, `mailto:`), in-page anchors and files without a
+/// supported extension. A `#fragment` or `?query` suffix is ignored and
+/// `%20` is decoded, so `./docs/DESIGN%20NOTES.md#usage` opens
+/// `docs/DESIGN NOTES.md` next to the current file.
+pub fn resolve_link_target(
+    url: &str,
+    markdown_file_path: &Path,
+    supported_extensions: &[String],
+) -> Option<PathBuf> {
+    let has_scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    });
+    let target = url.split(['#', '?']).next().unwrap_or_default();
+    if has_scheme || target.is_empty() {
+        return None;
+    }
+    let target = target.replace("%20", " ");
+    if !is_supported_extension(&target, supported_extensions) {
+        return None;
+    }
+
+    let target_path = Path::new(&target);
+    let resolved = match (target_path.is_absolute(), markdown_file_path.parent()) {
+        (true, _) | (false, None) => target_path.to_path_buf(),
+        (false, Some(parent)) => normalize_path(&parent.join(target_path)),
+    };
+    debug!("Resolved link '{}' to {}", url, resolved.display());
+    Some(resolved)
+}
+
 /// Normalize a path by removing `.` and `..` components
 ///
 /// This is a simplified path normalization that doesn't require file system access.
@@ -184,3 +693,293 @@ fn normalize_path(path: &Path) -> std::path::PathBuf {
 
     components.iter().collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_matches_star_and_question_mark() {
+        assert!(wildcard_match("*.md", "notes.md"));
+        assert!(wildcard_match("ch??.md", "ch01.md"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(!wildcard_match("*.md", "notes.txt"));
+        assert!(!wildcard_match("ch?.md", "ch01.md"));
+    }
+
+    #[test]
+    fn join_with_and_formats_lists() {
+        let items = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(join_with_and(&items(&["a"])), "a");
+        assert_eq!(join_with_and(&items(&["a", "b"])), "a and b");
+        assert_eq!(join_with_and(&items(&["a", "b", "c"])), "a, b and c");
+    }
+
+    #[test]
+    fn expand_glob_returns_sorted_matches() {
+        let dir = std::env::temp_dir().join("mdv_expand_glob_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.md", "a.md", "c.txt"] {
+            std::fs::write(dir.join(name), "# x").unwrap();
+        }
+
+        let pattern = format!("{}/*.md", dir.display());
+        let matches = expand_glob(&pattern);
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(matches, vec![dir.join("a.md"), dir.join("b.md")]);
+    }
+
+    #[test]
+    fn default_files_are_searched_in_configured_order() {
+        let dir = std::env::temp_dir().join("mdv_default_order_test");
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        std::fs::write(dir.join("docs/guide.md"), "# Guide").unwrap();
+        std::fs::write(dir.join("docs/notes.txt"), "notes").unwrap();
+
+        let defaults = vec![
+            dir.join("missing.md").display().to_string(),
+            format!("{}/docs/*", dir.display()),
+        ];
+        let supported = vec!["md".to_string()];
+        let result = resolve_markdown_file_path(Path::new("."), None, &defaults, &supported);
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(
+            result.unwrap(),
+            dir.join("docs/guide.md").display().to_string()
+        );
+    }
+
+    #[test]
+    fn directory_with_readme_resolves_to_index() {
+        let dir = std::env::temp_dir().join("mdv_dir_index_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("index.md"), "# Index").unwrap();
+        std::fs::write(dir.join("README.md"), "# Readme").unwrap();
+
+        let result =
+            resolve_launch_target(Path::new("."), Some(&dir.display().to_string()), &[], &[]);
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(
+            result.unwrap(),
+            LaunchTarget::File(dir.join("README.md").display().to_string())
+        );
+    }
+
+    #[test]
+    fn directory_without_index_opens_finder() {
+        let dir = std::env::temp_dir().join("mdv_dir_no_index_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.md"), "# Notes").unwrap();
+
+        let result =
+            resolve_launch_target(Path::new("."), Some(&dir.display().to_string()), &[], &[]);
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(result.unwrap(), LaunchTarget::Directory(dir));
+    }
+
+    #[test]
+    fn file_arguments_expand_globs_in_place() {
+        let dir = std::env::temp_dir().join("mdv_file_args_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.md", "a.md", "skip.pdf"] {
+            std::fs::write(dir.join(name), "# x").unwrap();
+        }
+
+        let args = vec!["first.md".to_string(), format!("{}/*", dir.display())];
+        let result = expand_file_arguments(&args, &["md".to_string()]);
+        let missing = expand_file_arguments(
+            &[format!("{}/*.txt", dir.display())],
+            &["md".to_string(), "txt".to_string()],
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                "first.md".to_string(),
+                dir.join("a.md").display().to_string(),
+                dir.join("b.md").display().to_string(),
+            ]
+        );
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn sibling_file_steps_alphabetically_through_supported_files() {
+        let dir = std::env::temp_dir().join("mdv_sibling_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.md", "A.md", "c.md", "image.png"] {
+            std::fs::write(dir.join(name), "# x").unwrap();
+        }
+        let supported = vec!["md".to_string()];
+
+        let next = sibling_file(&dir.join("A.md"), &supported, true);
+        let last = sibling_file(&dir.join("c.md"), &supported, true);
+        let previous = sibling_file(&dir.join("c.md"), &supported, false);
+        let first = sibling_file(&dir.join("A.md"), &supported, false);
+        let from_deleted = sibling_file(&dir.join("bb.md"), &supported, true);
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(next, Some(dir.join("b.md")));
+        assert_eq!(last, None);
+        assert_eq!(previous, Some(dir.join("b.md")));
+        assert_eq!(first, None);
+        assert_eq!(from_deleted, Some(dir.join("c.md")));
+    }
+
+    #[test]
+    fn canonical_path_resolves_missing_files_through_parent() {
+        let dir = std::env::temp_dir().join("mdv_canonical_missing_test");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let missing = dir.join(".").join("gone.md");
+        let resolved = canonical_path(&missing);
+        let expected = std::fs::canonicalize(&dir).unwrap().join("gone.md");
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(resolved, expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn canonical_path_follows_symlinks() {
+        let dir = std::env::temp_dir().join("mdv_canonical_symlink_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("target.md"), "# Target").unwrap();
+        std::os::unix::fs::symlink(dir.join("target.md"), dir.join("link.md")).ok();
+
+        let resolved = canonical_path(&dir.join("link.md"));
+        let expected = std::fs::canonicalize(dir.join("target.md")).unwrap();
+
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(resolved, expected);
+    }
+
+    #[test]
+    fn modified_time_tracks_writes_to_the_file() {
+        let path = std::env::temp_dir().join("mdv_modified_time_test.md");
+        std::fs::write(&path, "# Before").unwrap();
+        let earlier = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(earlier))
+            .unwrap();
+        let before = modified_time(&path);
+
+        std::fs::write(&path, "# After").unwrap();
+        let after = modified_time(&path);
+
+        std::fs::remove_file(&path).ok();
+        assert_eq!(before, Some(earlier));
+        assert_ne!(after, before);
+        assert_eq!(modified_time(&path), None);
+    }
+
+    #[test]
+    fn binary_detection_flags_nul_and_invalid_utf8() {
+        assert!(looks_binary(b"PK\x03\x04\x00\x00"));
+        assert!(looks_binary(&[0xff, 0xfe, 0x41]));
+        assert!(!looks_binary(
+            "# Title\n\nText with ümlauts\t\r\n".as_bytes()
+        ));
+        // A multi-byte character cut at the end of the sample is still text
+        assert!(!looks_binary(&"é".as_bytes()[..1]));
+    }
+
+    #[test]
+    fn binary_file_is_rejected_with_guard_error() {
+        let path = std::env::temp_dir().join("mdv_binary_guard_test.txt");
+        std::fs::write(&path, [0x00u8, 0x01, 0x02, 0x03]).unwrap();
+        let path_str = path.display().to_string();
+
+        let guarded = load_markdown_content(&path_str);
+        let forced = load_markdown_content_unchecked(&path_str);
+
+        std::fs::remove_file(&path).ok();
+        let err = guarded.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LoadGuardError>().map(|e| &e.guard),
+            Some(&LoadGuard::Binary)
+        );
+        assert_eq!(forced.unwrap().len(), 4);
+    }
+
+    #[test]
+    fn oversized_file_is_rejected_with_guard_error() {
+        let path = std::env::temp_dir().join("mdv_size_guard_test.md");
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_len(MAX_FILE_SIZE_BYTES + 1).unwrap();
+
+        let result = load_markdown_content(&path.display().to_string());
+
+        std::fs::remove_file(&path).ok();
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<LoadGuardError>().map(|e| &e.guard),
+            Some(LoadGuard::TooLarge { .. })
+        ));
+        assert!(err.to_string().contains("10.0 MB"));
+    }
+
+    #[test]
+    fn file_sizes_are_formatted_for_display() {
+        assert_eq!(format_file_size(512), "512 bytes");
+        assert_eq!(format_file_size(1536), "1.5 KB");
+        assert_eq!(format_file_size(120 * 1024 * 1024), "120.0 MB");
+    }
+
+    #[test]
+    fn render_mode_follows_configured_extensions() {
+        let modes = BTreeMap::from([
+            ("txt".to_string(), RenderMode::Plain),
+            ("log".to_string(), RenderMode::Plain),
+            ("mdx".to_string(), RenderMode::Markdown),
+        ]);
+        let mode = |p: &str| render_mode_for_path(Path::new(p), &modes);
+        assert_eq!(mode("notes.txt"), RenderMode::Plain);
+        assert_eq!(mode("NOTES.TXT"), RenderMode::Plain);
+        assert_eq!(mode("server.log"), RenderMode::Plain);
+        assert_eq!(mode("page.mdx"), RenderMode::Markdown);
+        assert_eq!(mode("doc.md"), RenderMode::Markdown);
+        assert_eq!(mode("README"), RenderMode::Markdown);
+    }
+
+    #[test]
+    fn empty_default_files_reports_configuration() {
+        let result = resolve_markdown_file_path(Path::new("."), None, &[], &["md".to_string()]);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No default files configured")
+        );
+    }
+
+    #[test]
+    fn relative_document_links_resolve_next_to_the_current_file() {
+        let extensions = vec!["md".to_string(), "txt".to_string()];
+        let current = Path::new("/repo/README.md");
+        let resolve = |url: &str| resolve_link_target(url, current, &extensions);
+        assert_eq!(
+            resolve("./docs/DESIGN.md"),
+            Some(PathBuf::from("/repo/docs/DESIGN.md"))
+        );
+        assert_eq!(
+            resolve("../other/NOTES%20v2.md#usage"),
+            Some(PathBuf::from("/other/NOTES v2.md"))
+        );
+        assert_eq!(
+            resolve("/abs/notes.txt"),
+            Some(PathBuf::from("/abs/notes.txt"))
+        );
+        assert_eq!(resolve("https://example.com/page.md"), None);
+        assert_eq!(resolve("mailto:someone@example.com"), None);
+        assert_eq!(resolve("#installation"), None);
+        assert_eq!(resolve("diagram.png"), None);
+    }
+}
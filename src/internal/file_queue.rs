@@ -0,0 +1,158 @@
+//! Sequential queue of files opened from the command line
+//!
+//! When several paths (or a glob) are passed on the command line the viewer
+//! shows them one at a time. This module tracks the queue and the position of
+//! the file currently on screen so next/previous shortcuts can step through it.
+
+use std::path::{Path, PathBuf};
+
+/// Ordered list of files with a cursor pointing at the displayed one
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileQueue {
+    /// Files in the order they were given
+    files: Vec<PathBuf>,
+    /// Index of the file currently displayed
+    current_index: usize,
+}
+
+impl FileQueue {
+    /// Create a queue positioned at the first file
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        Self {
+            files,
+            current_index: 0,
+        }
+    }
+
+    /// Number of files in the queue
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether the queue holds no files
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// 1-based position and total, e.g. `(2, 5)` for "file 2 of 5"
+    ///
+    /// Returns `None` for queues of fewer than two files, where there is
+    /// nothing to navigate.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self.files.len() {
+            0 | 1 => None,
+            len => Some((self.current_index + 1, len)),
+        }
+    }
+
+    /// Files in queue order
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Advance to the next file, returning it (stops at the end)
+    pub fn advance(&mut self) -> Option<&Path> {
+        match self.current_index + 1 < self.files.len() {
+            true => {
+                self.current_index += 1;
+                Some(&self.files[self.current_index])
+            }
+            false => None,
+        }
+    }
+
+    /// Step back to the previous file, returning it (stops at the start)
+    pub fn go_back(&mut self) -> Option<&Path> {
+        match self.current_index {
+            0 => None,
+            _ => {
+                self.current_index -= 1;
+                Some(&self.files[self.current_index])
+            }
+        }
+    }
+
+    /// Move the cursor to `path` if it is part of the queue
+    ///
+    /// Keeps the position in sync when a queued file is opened another way,
+    /// such as through the file finder.
+    pub fn select(&mut self, path: &Path) -> bool {
+        match self.files.iter().position(|file| file == path) {
+            Some(index) => {
+                self.current_index = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace `from` with `to` after the file was renamed or moved
+    pub fn rename(&mut self, from: &Path, to: &Path) -> bool {
+        match self.files.iter_mut().find(|file| file.as_path() == from) {
+            Some(file) => {
+                *file = to.to_path_buf();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> FileQueue {
+        FileQueue::new(vec![
+            PathBuf::from("a.md"),
+            PathBuf::from("b.md"),
+            PathBuf::from("c.md"),
+        ])
+    }
+
+    #[test]
+    fn next_and_previous_step_through_files() {
+        let mut queue = queue();
+        assert_eq!(queue.advance(), Some(Path::new("b.md")));
+        assert_eq!(queue.advance(), Some(Path::new("c.md")));
+        assert_eq!(queue.advance(), None);
+        assert_eq!(queue.position(), Some((3, 3)));
+
+        assert_eq!(queue.go_back(), Some(Path::new("b.md")));
+        assert_eq!(queue.go_back(), Some(Path::new("a.md")));
+        assert_eq!(queue.go_back(), None);
+        assert_eq!(queue.position(), Some((1, 3)));
+    }
+
+    #[test]
+    fn single_file_has_no_position() {
+        let mut queue = FileQueue::new(vec![PathBuf::from("a.md")]);
+        assert_eq!(queue.position(), None);
+        assert_eq!(queue.advance(), None);
+        assert!(FileQueue::default().is_empty());
+    }
+
+    #[test]
+    fn select_syncs_cursor_to_queued_file() {
+        let mut queue = queue();
+        assert!(queue.select(Path::new("c.md")));
+        assert_eq!(queue.position(), Some((3, 3)));
+        assert!(!queue.select(Path::new("other.md")));
+        assert_eq!(queue.position(), Some((3, 3)));
+    }
+
+    #[test]
+    fn rename_replaces_the_queued_path() {
+        let mut queue = queue();
+        assert!(queue.rename(Path::new("b.md"), Path::new("renamed.md")));
+        assert!(!queue.rename(Path::new("other.md"), Path::new("x.md")));
+        assert_eq!(
+            queue.files(),
+            [
+                PathBuf::from("a.md"),
+                PathBuf::from("renamed.md"),
+                PathBuf::from("c.md")
+            ]
+        );
+    }
+}
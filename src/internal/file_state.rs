@@ -0,0 +1,160 @@
+//! Per-file reading state kept across sessions
+//!
+//! When a file is left (another file is opened, its tab is closed or the
+//! viewer quits), its scroll position, bookmarks and active search are
+//! stored in `.markdown_viewer/state.ron`, keyed by the file's canonical
+//! path. Opening the same file again, in this or a later session, picks up
+//! where it was left. The same file records whether the first-run tour was
+//! seen.
+
+use crate::internal::bookmark_anchor::LineAnchor;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{debug, info};
+
+/// Where the per-file states are stored, relative to the working directory
+pub const FILE_STATE_PATH: &str = ".markdown_viewer/state.ron";
+
+/// Files whose state is remembered; the least recently left are dropped
+pub const MAX_FILE_STATES: usize = 200;
+
+/// Reading state of one file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FileState {
+    /// Canonical path of the file
+    pub path: String,
+
+    /// Scroll position in pixels
+    #[serde(default)]
+    pub scroll_y: f32,
+
+    /// Bookmarked lines (1-based)
+    #[serde(default)]
+    pub bookmarks: Vec<usize>,
+
+    /// Content anchors of the bookmarks, keyed by line
+    #[serde(default)]
+    pub bookmark_anchors: BTreeMap<usize, LineAnchor>,
+
+    /// Query of the search that was active, if any
+    #[serde(default)]
+    pub search: Option<String>,
+}
+
+/// The state store: remembered states of recently read files, most
+/// recently left first, and what the viewer has shown once
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FileStates {
+    #[serde(default)]
+    pub files: Vec<FileState>,
+
+    /// Whether the first-run tour was finished or skipped
+    #[serde(default)]
+    pub tour_seen: bool,
+}
+
+impl FileStates {
+    /// Load the states from `path`; a missing file holds no states
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            debug!("No file state at {:?}", path);
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .context(format!("Failed to read file state: {:?}", path))?;
+        let states: FileStates = ron::from_str(&content).context("Failed to parse file state")?;
+        info!("Loaded state for {} file(s)", states.files.len());
+        Ok(states)
+    }
+
+    /// Save the states to `path`, creating its directory if needed
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .context(format!("Failed to create state directory: {:?}", dir))?;
+        }
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .context("Failed to serialize file state")?;
+        std::fs::write(path, content).context(format!("Failed to write file state: {:?}", path))?;
+        debug!("Saved state for {} file(s) to {:?}", self.files.len(), path);
+        Ok(())
+    }
+
+    /// The remembered state of `path`
+    pub fn get(&self, path: &Path) -> Option<&FileState> {
+        self.files
+            .iter()
+            .find(|state| Path::new(&state.path) == path)
+    }
+
+    /// Remember `state`, replacing an earlier state of the same file
+    pub fn remember(&mut self, state: FileState) {
+        self.files.retain(|existing| existing.path != state.path);
+        self.files.insert(0, state);
+        self.files.truncate(MAX_FILE_STATES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(path: &str, scroll_y: f32) -> FileState {
+        FileState {
+            path: path.to_string(),
+            scroll_y,
+            bookmarks: vec![3, 12],
+            bookmark_anchors: BTreeMap::from([(
+                3,
+                LineAnchor {
+                    heading_path: vec!["Setup".to_string()],
+                    text_hash: 42,
+                },
+            )]),
+            search: Some("parse".to_string()),
+        }
+    }
+
+    #[test]
+    fn latest_state_per_file_is_kept() {
+        let mut states = FileStates::default();
+        states.remember(state("/a.md", 100.0));
+        states.remember(state("/b.md", 50.0));
+        states.remember(state("/a.md", 300.0));
+
+        assert_eq!(states.files.len(), 2);
+        assert_eq!(states.files[0].path, "/a.md");
+        assert_eq!(
+            states.get(Path::new("/a.md")).map(|s| s.scroll_y),
+            Some(300.0)
+        );
+        assert!(states.get(Path::new("/c.md")).is_none());
+
+        for index in 0..MAX_FILE_STATES {
+            states.remember(state(&format!("/{}.md", index), 0.0));
+        }
+        assert_eq!(states.files.len(), MAX_FILE_STATES);
+        assert!(states.get(Path::new("/a.md")).is_none());
+    }
+
+    #[test]
+    fn states_round_trip_through_the_state_file() {
+        let dir = std::env::temp_dir().join("mdv_file_state_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("nested/state.ron");
+
+        assert_eq!(
+            FileStates::load_from_file(&path).unwrap(),
+            FileStates::default()
+        );
+        let mut states = FileStates::default();
+        states.remember(state("/docs/guide.md", 420.0));
+        states.save_to_file(&path).unwrap();
+        assert_eq!(FileStates::load_from_file(&path).unwrap(), states);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,327 @@
+//! File tree sidebar for directory sessions
+//!
+//! When the viewer is launched on a directory, a sidebar on the left lists
+//! its supported files as a tree of collapsible folders (Cmd+Shift+Z toggles
+//! it). Folders come before files, both sorted by name, and only folders that
+//! hold supported files are shown. While the tree has keyboard focus,
+//! Up/Down (or j/k) move the selection, Left/Right (or h/l) collapse and
+//! expand folders, Enter opens the selected file and Escape hands focus back
+//! to the document.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::info;
+use walkdir::WalkDir;
+
+use crate::internal::file_handling::is_supported_extension;
+use crate::internal::style::{FILE_TREE_HEADER_HEIGHT, FILE_TREE_ROW_HEIGHT};
+
+/// A folder and the supported files below it
+#[derive(Debug, Clone, Default)]
+struct Folder {
+    folders: BTreeMap<String, Folder>,
+    files: Vec<PathBuf>,
+}
+
+/// What a row of the tree shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    Folder { expanded: bool },
+    File,
+}
+
+/// A visible row of the tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeRow {
+    pub path: PathBuf,
+    pub name: String,
+    /// Nesting level below the root (0 for its direct children)
+    pub depth: usize,
+    pub kind: RowKind,
+}
+
+/// The file tree sidebar
+#[derive(Debug, Clone, Default)]
+pub struct FileTree {
+    /// Directory the tree lists
+    pub root: PathBuf,
+    folder: Folder,
+    expanded: HashSet<PathBuf>,
+    /// Rows of the expanded part of the tree, top to bottom
+    pub rows: Vec<TreeRow>,
+    /// Index of the selected row
+    pub selected: usize,
+    /// Pixels the rows are scrolled down by
+    pub scroll_y: f32,
+    /// Whether keys go to the tree instead of the document
+    pub focused: bool,
+}
+
+/// Height available to the rows in a window `viewport_height` tall
+pub fn rows_height(viewport_height: f32) -> f32 {
+    (viewport_height - FILE_TREE_HEADER_HEIGHT).max(FILE_TREE_ROW_HEIGHT)
+}
+
+impl FileTree {
+    /// List every file under `root` with one of `extensions`
+    pub fn scan(root: PathBuf, extensions: &[String]) -> Self {
+        let files: Vec<PathBuf> = WalkDir::new(&root)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| is_supported_extension(&path.to_string_lossy(), extensions))
+            .collect();
+        info!("File tree of {:?}: {} file(s)", root, files.len());
+        Self::from_files(root, files)
+    }
+
+    /// Build the tree of `files`, which must lie under `root`
+    pub fn from_files(root: PathBuf, files: Vec<PathBuf>) -> Self {
+        let mut folder = Folder::default();
+        for path in files {
+            let Ok(relative) = path.strip_prefix(&root) else {
+                continue;
+            };
+            let parents: Vec<String> = relative
+                .parent()
+                .map(|parent| {
+                    parent
+                        .iter()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let target = parents.into_iter().fold(&mut folder, |folder, name| {
+                folder.folders.entry(name).or_default()
+            });
+            target.files.push(path);
+        }
+        let mut tree = Self {
+            root,
+            folder,
+            ..Self::default()
+        };
+        tree.rebuild_rows();
+        tree
+    }
+
+    /// Recompute the visible rows after a folder was expanded or collapsed
+    fn rebuild_rows(&mut self) {
+        fn push_rows(
+            folder: &Folder,
+            path: &Path,
+            depth: usize,
+            expanded: &HashSet<PathBuf>,
+            rows: &mut Vec<TreeRow>,
+        ) {
+            for (name, child) in &folder.folders {
+                let child_path = path.join(name);
+                let is_expanded = expanded.contains(&child_path);
+                rows.push(TreeRow {
+                    path: child_path.clone(),
+                    name: name.clone(),
+                    depth,
+                    kind: RowKind::Folder {
+                        expanded: is_expanded,
+                    },
+                });
+                if is_expanded {
+                    push_rows(child, &child_path, depth + 1, expanded, rows);
+                }
+            }
+            let mut files: Vec<&PathBuf> = folder.files.iter().collect();
+            files.sort_by_key(|file| file.file_name().map(|name| name.to_ascii_lowercase()));
+            rows.extend(files.into_iter().map(|file| {
+                TreeRow {
+                    path: file.clone(),
+                    name: file
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    depth,
+                    kind: RowKind::File,
+                }
+            }));
+        }
+
+        let mut rows = Vec::new();
+        push_rows(&self.folder, &self.root, 0, &self.expanded, &mut rows);
+        self.rows = rows;
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// The selected row
+    pub fn selected_row(&self) -> Option<&TreeRow> {
+        self.rows.get(self.selected)
+    }
+
+    /// Expand or collapse the folder at `path`
+    pub fn toggle_folder(&mut self, path: &Path) {
+        if !self.expanded.remove(path) {
+            self.expanded.insert(path.to_path_buf());
+        }
+        self.rebuild_rows();
+    }
+
+    /// Expand the folders holding `file` and select it
+    pub fn reveal(&mut self, file: &Path, viewport_height: f32) {
+        let Ok(relative) = file.strip_prefix(&self.root) else {
+            return;
+        };
+        let mut folder = self.root.clone();
+        for name in relative.parent().into_iter().flat_map(Path::iter) {
+            folder.push(name);
+            self.expanded.insert(folder.clone());
+        }
+        self.rebuild_rows();
+        if let Some(index) = self.rows.iter().position(|row| row.path == file) {
+            self.selected = index;
+            self.scroll_into_view(viewport_height);
+        }
+    }
+
+    /// Handle a key while the tree has focus
+    ///
+    /// Returns the file to open when Enter is pressed on one, and whether
+    /// the key was used at all.
+    pub fn handle_key(&mut self, key: &str, viewport_height: f32) -> (Option<PathBuf>, bool) {
+        let Some(row) = self.selected_row().cloned() else {
+            return (None, false);
+        };
+        match (key, row.kind) {
+            ("up" | "k", _) => self.move_selection(-1, viewport_height),
+            ("down" | "j", _) => self.move_selection(1, viewport_height),
+            ("left" | "h", RowKind::Folder { expanded: true }) => self.toggle_folder(&row.path),
+            ("left" | "h", _) => {
+                // Jump to the folder the row is in
+                if let Some(parent) = self.rows[..self.selected]
+                    .iter()
+                    .rposition(|candidate| candidate.depth + 1 == row.depth)
+                {
+                    self.selected = parent;
+                    self.scroll_into_view(viewport_height);
+                }
+            }
+            ("right" | "l", RowKind::Folder { expanded: false }) => self.toggle_folder(&row.path),
+            ("right" | "l", RowKind::Folder { expanded: true }) => {
+                self.move_selection(1, viewport_height)
+            }
+            ("enter" | "space", RowKind::Folder { .. }) => self.toggle_folder(&row.path),
+            ("enter" | "space", RowKind::File) => return (Some(row.path), true),
+            _ => return (None, false),
+        }
+        (None, true)
+    }
+
+    /// Move the selection by `delta` rows and scroll it into view
+    pub fn move_selection(&mut self, delta: isize, viewport_height: f32) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+        self.scroll_into_view(viewport_height);
+    }
+
+    /// Largest scroll offset in a sidebar `viewport_height` tall
+    pub fn max_scroll(&self, viewport_height: f32) -> f32 {
+        (self.rows.len() as f32 * FILE_TREE_ROW_HEIGHT - viewport_height).max(0.0)
+    }
+
+    /// Scroll the rows by `delta` pixels (positive scrolls down)
+    pub fn scroll_by(&mut self, delta: f32, viewport_height: f32) {
+        self.scroll_y = (self.scroll_y + delta).clamp(0.0, self.max_scroll(viewport_height));
+    }
+
+    /// Scroll just enough for the selected row to be fully visible
+    fn scroll_into_view(&mut self, viewport_height: f32) {
+        let top = self.selected as f32 * FILE_TREE_ROW_HEIGHT;
+        let bottom = top + FILE_TREE_ROW_HEIGHT;
+        self.scroll_y = match self.scroll_y {
+            y if top < y => top,
+            y if bottom > y + viewport_height => bottom - viewport_height,
+            y => y,
+        }
+        .clamp(0.0, self.max_scroll(viewport_height));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> FileTree {
+        let root = PathBuf::from("/docs");
+        FileTree::from_files(
+            root.clone(),
+            [
+                "README.md",
+                "guide/intro.md",
+                "guide/api/ref.md",
+                "about.md",
+            ]
+            .iter()
+            .map(|file| root.join(file))
+            .collect(),
+        )
+    }
+
+    fn names(tree: &FileTree) -> Vec<String> {
+        tree.rows
+            .iter()
+            .map(|row| format!("{}{}", "  ".repeat(row.depth), row.name))
+            .collect()
+    }
+
+    #[test]
+    fn folders_come_first_and_start_collapsed() {
+        let mut tree = tree();
+        assert_eq!(names(&tree), vec!["guide", "about.md", "README.md"]);
+
+        tree.toggle_folder(Path::new("/docs/guide"));
+        assert_eq!(
+            names(&tree),
+            vec!["guide", "  api", "  intro.md", "about.md", "README.md"]
+        );
+
+        tree.reveal(Path::new("/docs/guide/api/ref.md"), 1000.0);
+        assert_eq!(tree.selected_row().unwrap().name, "ref.md");
+        assert_eq!(tree.rows.len(), 6);
+    }
+
+    #[test]
+    fn keys_walk_and_fold_the_tree() {
+        let mut tree = tree();
+        let viewport = 1000.0;
+        assert_eq!(tree.handle_key("right", viewport), (None, true));
+        assert_eq!(tree.rows.len(), 5);
+        tree.handle_key("down", viewport);
+        tree.handle_key("down", viewport);
+        assert_eq!(
+            tree.handle_key("enter", viewport),
+            (Some(PathBuf::from("/docs/guide/intro.md")), true)
+        );
+
+        // Left from a file goes to its folder, then collapses it
+        tree.handle_key("left", viewport);
+        assert_eq!(tree.selected, 0);
+        tree.handle_key("left", viewport);
+        assert_eq!(tree.rows.len(), 3);
+        assert_eq!(tree.handle_key("x", viewport), (None, false));
+    }
+
+    #[test]
+    fn selection_scrolls_into_view() {
+        let root = PathBuf::from("/notes");
+        let files = (0..30)
+            .map(|index| root.join(format!("{:02}.md", index)))
+            .collect();
+        let mut tree = FileTree::from_files(root, files);
+        let viewport = 10.0 * FILE_TREE_ROW_HEIGHT;
+
+        tree.move_selection(15, viewport);
+        assert_eq!(tree.scroll_y, 6.0 * FILE_TREE_ROW_HEIGHT);
+        tree.scroll_by(1000.0, viewport);
+        assert_eq!(tree.scroll_y, 20.0 * FILE_TREE_ROW_HEIGHT);
+    }
+}
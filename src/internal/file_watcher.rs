@@ -4,13 +4,18 @@
 //! with debouncing to handle rapid file changes gracefully.
 
 use anyhow::{Context, Result};
-use notify::RecursiveMode;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecursiveMode};
 use notify_debouncer_full::{DebouncedEvent, Debouncer, FileIdMap, new_debouncer};
-use std::path::Path;
-use std::sync::mpsc::{Receiver, channel};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
 use std::time::Duration;
 use tracing::{debug, error, info};
 
+use crate::internal::archive::containing_file;
+use crate::internal::file_handling::canonical_path;
+
 /// Events emitted by the file watcher
 #[derive(Debug, Clone)]
 pub enum FileWatcherEvent {
@@ -18,6 +23,8 @@ pub enum FileWatcherEvent {
     Modified,
     /// File was deleted
     Deleted,
+    /// File was renamed or moved to the given path
+    Renamed(PathBuf),
     /// Error occurred while watching
     Error(String),
 }
@@ -28,7 +35,7 @@ pub enum FileWatcherEvent {
 /// The watcher runs in a background thread and will continue until the receiver is dropped.
 ///
 /// # Arguments
-/// * `file_path` - Path to the file to watch
+/// * `file_path` - Path to the file to watch (or to a file inside an archive)
 /// * `debounce_ms` - Debounce timeout in milliseconds (typically 100ms)
 ///
 /// # Returns
@@ -42,7 +49,10 @@ pub fn start_watching(
     Debouncer<notify::RecommendedWatcher, FileIdMap>,
 )> {
     let (tx, rx) = channel();
-    let file_path = file_path.to_path_buf();
+    // Events carry canonical paths under the watched directory, so resolve
+    // symlinks and relative components up front; a file inside an archive is
+    // reloaded whenever the archive itself changes
+    let file_path = canonical_path(&containing_file(file_path));
 
     info!("Starting file watcher for: {:?}", file_path);
 
@@ -58,28 +68,26 @@ pub fn start_watching(
         move |result: Result<Vec<DebouncedEvent>, Vec<notify::Error>>| {
             match result {
                 Ok(events) => {
-                    for event in events {
+                    // One batch may hold several events for an atomic save
+                    // (e.g. Remove + Create); only the final state matters
+                    for event in &events {
                         debug!("File watcher event: {:?}", event);
-
-                        // Check if any of the paths match our watched file
-                        for path in &event.paths {
-                            if path == &file_path_for_closure {
-                                match event.kind {
-                                    notify::EventKind::Remove(_) => {
-                                        info!("File deleted: {:?}", path);
-                                        tx_clone.send(FileWatcherEvent::Deleted).ok();
-                                    }
-                                    notify::EventKind::Create(_) | notify::EventKind::Modify(_) => {
-                                        info!("File modified: {:?}", path);
-                                        tx_clone.send(FileWatcherEvent::Modified).ok();
-                                    }
-                                    _ => {
-                                        debug!("Ignoring event kind: {:?}", event.kind);
-                                    }
-                                }
-                                break;
-                            }
+                    }
+                    let last = events.iter().rev().find_map(|event| {
+                        classify_event(&event.kind, &event.paths, &file_path_for_closure)
+                    });
+                    match last {
+                        Some(FileWatcherEvent::Renamed(to)) => settle_rename(
+                            file_path_for_closure.clone(),
+                            to,
+                            debounce_duration * RENAME_SETTLE_WINDOWS,
+                            tx_clone.clone(),
+                        ),
+                        Some(event) => {
+                            info!("File {:?}: {:?}", event, file_path_for_closure);
+                            tx_clone.send(event).ok();
                         }
+                        None => {}
                     }
                 }
                 Err(errors) => {
@@ -95,13 +103,12 @@ pub fn start_watching(
     )
     .context("Failed to create file watcher debouncer")?;
 
-    // Watch the file's parent directory (watching individual files isn't supported on all platforms)
-    let watch_path = match file_path.is_file() {
-        true => file_path
-            .parent()
-            .context("File has no parent directory")?
-            .to_path_buf(),
-        false => file_path.clone(),
+    // Watch the file's parent directory rather than the file itself: watching
+    // individual files isn't supported on all platforms, and a directory watch
+    // survives the file being deleted and recreated with a new inode
+    let watch_path = match file_path.parent() {
+        Some(parent) if parent.is_dir() => parent.to_path_buf(),
+        _ => file_path.clone(),
     };
 
     // Call watch directly on debouncer (watcher() is deprecated)
@@ -114,12 +121,165 @@ pub fn start_watching(
     Ok((rx, debouncer))
 }
 
+/// Debounce windows to wait before believing the watched file was renamed
+const RENAME_SETTLE_WINDOWS: u32 = 2;
+
+/// Report a rename of the watched file once `window` has passed
+///
+/// Vim's default save renames `doc.md` to `doc.md~` before writing a new
+/// `doc.md`, and the two steps can land in different debounce batches.
+/// Following the backup would leave the viewer on a file that is about to be
+/// deleted, so the rename only counts if the original did not come back.
+fn settle_rename(
+    watched_path: PathBuf,
+    to: PathBuf,
+    window: Duration,
+    tx: Sender<FileWatcherEvent>,
+) {
+    thread::spawn(move || {
+        thread::sleep(window);
+        let event = renamed_or_replaced(&watched_path, to);
+        info!("File {:?}: {:?}", event, watched_path);
+        tx.send(event).ok();
+    });
+}
+
+/// What a rename of the watched file turned out to be once it settled: a
+/// replacement when the original path exists again, a move when only the
+/// target does, and a deletion when neither does
+fn renamed_or_replaced(watched_path: &Path, to: PathBuf) -> FileWatcherEvent {
+    match (watched_path.exists(), to.exists()) {
+        (true, _) => FileWatcherEvent::Modified,
+        (false, true) => FileWatcherEvent::Renamed(to),
+        (false, false) => FileWatcherEvent::Deleted,
+    }
+}
+
+/// Whether an event path refers to the watched (canonical) path
+///
+/// Most backends report paths under the directory as it was registered, so a
+/// literal comparison usually suffices; paths that differ only through a
+/// symlinked component are compared by their canonical form.
+fn is_watched_path(path: &Path, watched_path: &Path) -> bool {
+    path == watched_path
+        || (path.file_name() == watched_path.file_name() && canonical_path(path) == watched_path)
+}
+
+/// Map a raw notify event to a watcher event for the watched file
+///
+/// Editors that save atomically write a temporary file and rename it over the
+/// original, which can surface as Create, Remove or rename events on the
+/// watched path instead of a plain Modify. Remove and rename events check
+/// whether the file still exists afterwards to tell a replacement from a
+/// real deletion. A rename that carries both paths and moves the watched file
+/// to a path that exists is classified as `Renamed`, which [`start_watching`]
+/// only reports once [`settle_rename`] saw the original stay away.
+///
+/// Returns `None` when the event does not concern the watched file.
+fn classify_event(
+    kind: &EventKind,
+    paths: &[PathBuf],
+    watched_path: &Path,
+) -> Option<FileWatcherEvent> {
+    if !paths.iter().any(|path| is_watched_path(path, watched_path)) {
+        return None;
+    }
+
+    match kind {
+        EventKind::Create(_) => Some(FileWatcherEvent::Modified),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both))
+            if paths.len() == 2
+                && is_watched_path(&paths[0], watched_path)
+                && !watched_path.exists()
+                && paths[1].exists() =>
+        {
+            Some(FileWatcherEvent::Renamed(paths[1].clone()))
+        }
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_)) => {
+            match watched_path.exists() {
+                true => Some(FileWatcherEvent::Modified),
+                false => Some(FileWatcherEvent::Deleted),
+            }
+        }
+        EventKind::Modify(_) => Some(FileWatcherEvent::Modified),
+        _ => {
+            debug!("Ignoring event kind: {:?}", kind);
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
     use std::thread;
 
+    #[test]
+    fn classify_ignores_other_paths() {
+        let watched = PathBuf::from("/nonexistent/watched.md");
+        let paths = vec![PathBuf::from("/nonexistent/other.md")];
+        let kind = EventKind::Modify(ModifyKind::Any);
+        assert!(classify_event(&kind, &paths, &watched).is_none());
+    }
+
+    #[test]
+    fn classify_treats_create_as_modified() {
+        let watched = PathBuf::from("/nonexistent/watched.md");
+        let kind = EventKind::Create(notify::event::CreateKind::File);
+        assert!(matches!(
+            classify_event(&kind, std::slice::from_ref(&watched), &watched),
+            Some(FileWatcherEvent::Modified)
+        ));
+    }
+
+    #[test]
+    fn classify_rename_depends_on_whether_file_remains() {
+        let existing = fs::canonicalize("Cargo.toml").expect("Cargo.toml should exist");
+        let missing = PathBuf::from("/nonexistent/watched.md");
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+
+        // Temporary file renamed over the watched path
+        let onto = vec![PathBuf::from("/nonexistent/.tmp"), existing.clone()];
+        assert!(matches!(
+            classify_event(&rename, &onto, &existing),
+            Some(FileWatcherEvent::Modified)
+        ));
+
+        // Watched file renamed away to a path that no longer exists either
+        let away = vec![missing.clone(), PathBuf::from("/nonexistent/backup~")];
+        assert!(matches!(
+            classify_event(&rename, &away, &missing),
+            Some(FileWatcherEvent::Deleted)
+        ));
+
+        // Watched file renamed to a path that exists is followed
+        let moved = vec![missing.clone(), existing.clone()];
+        assert!(matches!(
+            classify_event(&rename, &moved, &missing),
+            Some(FileWatcherEvent::Renamed(to)) if to == existing
+        ));
+    }
+
+    #[test]
+    fn settled_rename_depends_on_whether_the_original_came_back() {
+        let existing = fs::canonicalize("Cargo.toml").expect("Cargo.toml should exist");
+        let missing = PathBuf::from("/nonexistent/watched.md");
+
+        assert!(matches!(
+            renamed_or_replaced(&existing, PathBuf::from("/nonexistent/backup~")),
+            FileWatcherEvent::Modified
+        ));
+        assert!(matches!(
+            renamed_or_replaced(&missing, existing.clone()),
+            FileWatcherEvent::Renamed(to) if to == existing
+        ));
+        assert!(matches!(
+            renamed_or_replaced(&missing, PathBuf::from("/nonexistent/backup~")),
+            FileWatcherEvent::Deleted
+        ));
+    }
+
     #[test]
     fn test_file_watcher_detects_modification() {
         // Create a temporary file
@@ -196,6 +356,89 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg_attr(
+        target_os = "macos",
+        ignore = "File deletion detection is unreliable on macOS"
+    )]
+    fn test_file_watcher_detects_recreation() {
+        let temp_file = "test_watch_recreate.txt";
+        fs::write(temp_file, "content").expect("Failed to create test file");
+        let abs_path = fs::canonicalize(temp_file).expect("Failed to canonicalize path");
+
+        let (rx, _debouncer) = start_watching(&abs_path, 50).expect("Failed to start watcher");
+        thread::sleep(Duration::from_millis(100));
+
+        // Delete, let the deletion be reported, then recreate the file
+        fs::remove_file(temp_file).expect("Failed to delete test file");
+        thread::sleep(Duration::from_millis(300));
+        fs::write(temp_file, "recreated").expect("Failed to recreate test file");
+        thread::sleep(Duration::from_millis(300));
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+
+        fs::remove_file(temp_file).ok();
+
+        assert!(
+            matches!(events.last(), Some(FileWatcherEvent::Modified)),
+            "Recreation should end with a Modified event, got: {:?}",
+            events
+        );
+    }
+
+    #[test]
+    fn test_file_watcher_watches_missing_file() {
+        let temp_file = "test_watch_missing.txt";
+        fs::remove_file(temp_file).ok();
+        let abs_path = std::env::current_dir()
+            .expect("Failed to get current dir")
+            .join(temp_file);
+
+        // Watching a file that does not exist yet watches its directory
+        let (rx, _debouncer) = start_watching(&abs_path, 50).expect("Failed to start watcher");
+        thread::sleep(Duration::from_millis(100));
+
+        fs::write(temp_file, "created").expect("Failed to create test file");
+        let event = rx.recv_timeout(Duration::from_secs(2));
+
+        fs::remove_file(temp_file).ok();
+
+        assert!(
+            matches!(event, Ok(FileWatcherEvent::Modified)),
+            "Creating the file should report Modified, got: {:?}",
+            event
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_watcher_follows_symlinked_path() {
+        let dir = std::env::temp_dir().join("mdv_watch_symlink_test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(dir.join("real")).expect("Failed to create test directory");
+        fs::write(dir.join("real/doc.md"), "initial").expect("Failed to create test file");
+        std::os::unix::fs::symlink(dir.join("real/doc.md"), dir.join("link.md"))
+            .expect("Failed to create symlink");
+
+        // Watch through the symlink, then modify the target
+        let (rx, _debouncer) =
+            start_watching(&dir.join("link.md"), 50).expect("Failed to start watcher");
+        thread::sleep(Duration::from_millis(100));
+        fs::write(dir.join("real/doc.md"), "modified").expect("Failed to modify target");
+
+        let event = rx.recv_timeout(Duration::from_secs(2));
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(
+            matches!(event, Ok(FileWatcherEvent::Modified)),
+            "Modifying the symlink target should report Modified, got: {:?}",
+            event
+        );
+    }
+
     #[test]
     fn test_file_watcher_debounces_rapid_changes() {
         // Create a temporary file
@@ -238,4 +481,36 @@ mod tests {
         );
         assert!(event_count >= 1, "Should receive at least 1 event");
     }
+
+    #[test]
+    fn test_file_watcher_reports_rename() {
+        let dir = std::env::temp_dir().join("mdv_watch_rename_test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).expect("Failed to create test directory");
+        let dir = fs::canonicalize(&dir).expect("Failed to canonicalize test directory");
+        fs::write(dir.join("before.md"), "content").expect("Failed to create test file");
+
+        let (rx, _debouncer) =
+            start_watching(&dir.join("before.md"), 50).expect("Failed to start watcher");
+        thread::sleep(Duration::from_millis(100));
+        fs::rename(dir.join("before.md"), dir.join("after.md")).expect("Failed to rename");
+        thread::sleep(Duration::from_millis(300));
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        fs::remove_dir_all(&dir).ok();
+
+        // Backends that cannot pair the two halves of a rename report a deletion
+        assert!(
+            events.iter().any(|event| match event {
+                FileWatcherEvent::Renamed(to) => to == &dir.join("after.md"),
+                FileWatcherEvent::Deleted => !cfg!(target_os = "linux"),
+                _ => false,
+            }),
+            "Renaming the file should report Renamed, got: {:?}",
+            events
+        );
+    }
 }
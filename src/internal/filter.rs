@@ -0,0 +1,188 @@
+//! Filter view: show only the blocks that contain search matches
+//!
+//! Like `grep` for a document: the top-level blocks (paragraphs, lists, code
+//! blocks, tables, ...) whose source contains the query are kept,
+//! optionally with one block of context on either side, and runs of hidden
+//! blocks collapse into a `⋯` separator. The filtered markdown is rendered in
+//! place of the document until the filter is cleared; the document itself is
+//! left untouched, so bookmarks, marks, exports and printing keep using its
+//! lines.
+
+use comrak::{Arena, parse_document};
+
+use crate::internal::search::SearchMode;
+
+/// Paragraph standing in for hidden blocks
+pub const FILTER_SEPARATOR: &str = "⋯";
+
+/// Filtered projection of a document shown in its place
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterView {
+    /// Filtered markdown that is rendered while the filter is active
+    pub content: String,
+    /// Blocks of context kept around each matching block
+    pub context: usize,
+    /// Source line (0-based) in the full document of each filtered line;
+    /// separator lines map to the line of the next shown block
+    pub source_lines: Vec<usize>,
+}
+
+impl FilterView {
+    /// Line in the full document for `line` of the filtered content
+    pub fn source_line(&self, line: usize) -> usize {
+        self.source_lines
+            .get(line)
+            .or(self.source_lines.last())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Line of the filtered content showing `source` of the full document,
+    /// or the last shown line before it when `source` is hidden
+    pub fn shown_line(&self, source: usize) -> usize {
+        self.source_lines
+            .partition_point(|&line| line <= source)
+            .saturating_sub(1)
+    }
+}
+
+/// Indices of the blocks to show: blocks whose text matches `query` in
+/// `mode`, plus `context` blocks before and after each
+pub fn visible_blocks(
+    block_texts: &[&str],
+    query: &str,
+    mode: SearchMode,
+    context: usize,
+) -> Vec<usize> {
+    let pattern = match query {
+        "" => None,
+        _ => mode.pattern(query).ok(),
+    };
+    let mut visible = vec![false; block_texts.len()];
+    for (index, text) in block_texts.iter().enumerate() {
+        if pattern
+            .as_ref()
+            .is_some_and(|pattern| pattern.is_match(text))
+        {
+            let last = (index + context).min(block_texts.len() - 1);
+            visible[index.saturating_sub(context)..=last].fill(true);
+        }
+    }
+    (0..block_texts.len())
+        .filter(|&index| visible[index])
+        .collect()
+}
+
+/// Markdown holding only the blocks of `content` that match `query`
+///
+/// Returns the filtered content and the full-document line of each of its
+/// lines (see [`FilterView::source_lines`]).
+pub fn filter_content(
+    content: &str,
+    query: &str,
+    mode: SearchMode,
+    context: usize,
+) -> (String, Vec<usize>) {
+    let lines: Vec<&str> = content.lines().collect();
+    let arena = Arena::new();
+    let root = parse_document(&arena, content, &crate::internal::render::parse_options());
+    let ranges: Vec<(usize, usize)> = root
+        .children()
+        .map(|node| {
+            let sourcepos = node.data.borrow().sourcepos;
+            let start = sourcepos.start.line.saturating_sub(1);
+            let end = sourcepos.end.line.saturating_sub(1).max(start);
+            (start.min(lines.len()), (end + 1).min(lines.len()))
+        })
+        .collect();
+    let block_texts: Vec<String> = ranges
+        .iter()
+        .map(|&(start, end)| lines[start..end].join("\n"))
+        .collect();
+    let block_refs: Vec<&str> = block_texts.iter().map(String::as_str).collect();
+
+    let mut filtered: Vec<&str> = Vec::new();
+    let mut source_lines = Vec::new();
+    let mut previous: Option<usize> = None;
+    for index in visible_blocks(&block_refs, query, mode, context) {
+        let (start, end) = ranges[index];
+        let gap = match previous {
+            Some(prev) => index > prev + 1,
+            None => index > 0,
+        };
+        if let Some(prev) = previous {
+            filtered.push("");
+            source_lines.push(ranges[prev].1);
+        }
+        if gap {
+            filtered.extend([FILTER_SEPARATOR, ""]);
+            source_lines.extend([start, start]);
+        }
+        filtered.extend(&lines[start..end]);
+        source_lines.extend(start..end);
+        previous = Some(index);
+    }
+    match previous {
+        Some(prev) if prev + 1 < ranges.len() => {
+            filtered.extend(["", FILTER_SEPARATOR]);
+            source_lines.extend([lines.len(), lines.len()]);
+        }
+        None if !ranges.is_empty() => {
+            filtered.push(FILTER_SEPARATOR);
+            source_lines.push(0);
+        }
+        _ => {}
+    }
+
+    (filtered.join("\n"), source_lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_blocks_are_kept_with_context() {
+        let blocks = ["intro", "install foo", "usage", "config", "more foo", "end"];
+        assert_eq!(
+            visible_blocks(&blocks, "FOO", SearchMode::Plain, 0),
+            vec![1, 4]
+        );
+        assert_eq!(
+            visible_blocks(&blocks, "foo", SearchMode::Plain, 1),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            visible_blocks(&blocks, "", SearchMode::Plain, 1),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            visible_blocks(&blocks, r"^\w+ foo$", SearchMode::Regex, 0),
+            vec![1, 4]
+        );
+    }
+
+    #[test]
+    fn hidden_blocks_collapse_into_separators() {
+        let content = "# Title\n\nfirst foo\n\nskipped\n\nsecond foo\n\nlast";
+        let (filtered, source_lines) = filter_content(content, "foo", SearchMode::Plain, 0);
+        assert_eq!(filtered, "⋯\n\nfirst foo\n\n⋯\n\nsecond foo\n\n⋯");
+        assert_eq!(source_lines, vec![2, 2, 2, 3, 6, 6, 6, 9, 9]);
+
+        let view = FilterView {
+            content: filtered,
+            context: 0,
+            source_lines,
+        };
+        assert_eq!(view.source_line(6), 6);
+        assert_eq!(view.source_line(100), 9);
+        assert_eq!(view.shown_line(2), 2);
+        assert_eq!(view.shown_line(6), 6);
+        // Hidden lines show up where their blocks were left out
+        assert_eq!(view.shown_line(4), 3);
+        assert_eq!(view.shown_line(0), 0);
+
+        let (filtered, _) = filter_content(content, "missing", SearchMode::Plain, 0);
+        assert_eq!(filtered, "⋯");
+    }
+}
@@ -0,0 +1,121 @@
+//! Keyboard focus list construction
+//!
+//! Builds the ordered list of focusable elements (the Tab order) from the
+//! document and any open overlays. The viewer keeps this list across renders
+//! and only rebuilds it when its inputs change, so `current_focus_index`
+//! keeps pointing at the same element while overlays open and close.
+
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{Arena, parse_document};
+
+use super::viewer::FocusableElement;
+
+/// Build the Tab order for a document
+///
+/// Links come first in document order, followed by the bookmark items and the
+/// close button when the bookmarks overlay is open.
+///
+/// # Arguments
+/// * `markdown` - The raw markdown content
+/// * `bookmarks_overlay` - Bookmarked lines when the bookmarks overlay is visible
+pub fn build_focusable_elements(
+    markdown: &str,
+    bookmarks_overlay: Option<&[usize]>,
+) -> Vec<FocusableElement> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &super::render::parse_options());
+    super::transform::apply(&arena, root, &Default::default());
+
+    let mut elements = Vec::new();
+    collect_links(root, &mut elements);
+
+    if let Some(bookmarks) = bookmarks_overlay {
+        elements.extend(
+            bookmarks
+                .iter()
+                .map(|&line_number| FocusableElement::BookmarkItem(line_number)),
+        );
+        elements.push(FocusableElement::BookmarksCloseButton);
+    }
+
+    elements
+}
+
+/// Find the index of the previously focused element in a rebuilt list
+///
+/// Returns `None` when nothing was focused or the element no longer exists.
+pub fn remap_focus_index(
+    old_elements: &[FocusableElement],
+    old_index: Option<usize>,
+    new_elements: &[FocusableElement],
+) -> Option<usize> {
+    let focused = old_index.and_then(|idx| old_elements.get(idx))?;
+    new_elements.iter().position(|element| element == focused)
+}
+
+/// Collect all non-empty links from a markdown AST node in document order
+fn collect_links<'a>(node: &'a AstNode<'a>, elements: &mut Vec<FocusableElement>) {
+    // Links do not nest, so there is nothing more to find inside
+    if let NodeValue::Link(link) = &node.data.borrow().value {
+        if !link.url.trim().is_empty() {
+            elements.push(FocusableElement::Link(link.url.clone()));
+        }
+        return;
+    }
+
+    for child in node.children() {
+        collect_links(child, elements);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "[one](https://one.example)\n\n[empty]()\n\n[two](https://two.example)";
+
+    #[test]
+    fn links_are_collected_in_document_order() {
+        let elements = build_focusable_elements(DOC, None);
+        assert_eq!(
+            elements,
+            vec![
+                FocusableElement::Link("https://one.example".to_string()),
+                FocusableElement::Link("https://two.example".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tab_order_is_stable_across_rebuilds() {
+        let first = build_focusable_elements(DOC, Some(&[3, 7]));
+        let second = build_focusable_elements(DOC, Some(&[3, 7]));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn bookmarks_overlay_appends_items_and_close_button() {
+        let elements = build_focusable_elements(DOC, Some(&[3, 7]));
+        assert_eq!(elements.len(), 5);
+        assert_eq!(elements[2], FocusableElement::BookmarkItem(3));
+        assert_eq!(elements[3], FocusableElement::BookmarkItem(7));
+        assert_eq!(elements[4], FocusableElement::BookmarksCloseButton);
+    }
+
+    #[test]
+    fn focus_follows_element_when_overlay_opens_and_closes() {
+        let closed = build_focusable_elements(DOC, None);
+        let open = build_focusable_elements(DOC, Some(&[3]));
+
+        // Focus on the second link survives opening the overlay
+        assert_eq!(remap_focus_index(&closed, Some(1), &open), Some(1));
+        // Focus on the close button is dropped when the overlay closes
+        assert_eq!(remap_focus_index(&open, Some(3), &closed), None);
+    }
+
+    #[test]
+    fn remap_without_focus_stays_unfocused() {
+        let elements = build_focusable_elements(DOC, None);
+        assert_eq!(remap_focus_index(&elements, None, &elements), None);
+    }
+}
@@ -0,0 +1,99 @@
+//! Back/forward history of documents opened by following links
+//!
+//! Following a link to another markdown file records the document being left
+//! together with its scroll position. Going back reopens it where the reader
+//! was; going forward retraces the link again. Following a new link discards
+//! the forward entries, like a web browser.
+
+use std::path::PathBuf;
+
+/// A document and the scroll position it was left at
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub path: PathBuf,
+    pub scroll_y: f32,
+}
+
+impl HistoryEntry {
+    pub fn new(path: impl Into<PathBuf>, scroll_y: f32) -> Self {
+        Self {
+            path: path.into(),
+            scroll_y,
+        }
+    }
+}
+
+/// Documents behind and ahead of the current one
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NavigationHistory {
+    back: Vec<HistoryEntry>,
+    forward: Vec<HistoryEntry>,
+}
+
+impl NavigationHistory {
+    /// Record `current` before following a link away from it
+    pub fn push(&mut self, current: HistoryEntry) {
+        self.back.push(current);
+        self.forward.clear();
+    }
+
+    /// Step back from `current`, returning the document to reopen
+    pub fn go_back(&mut self, current: HistoryEntry) -> Option<HistoryEntry> {
+        let previous = self.back.pop()?;
+        self.forward.push(current);
+        Some(previous)
+    }
+
+    /// Step forward from `current`, returning the document to reopen
+    pub fn go_forward(&mut self, current: HistoryEntry) -> Option<HistoryEntry> {
+        let next = self.forward.pop()?;
+        self.back.push(current);
+        Some(next)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn back_and_forward_retrace_followed_links() {
+        let mut history = NavigationHistory::default();
+        history.push(HistoryEntry::new("/docs/README.md", 120.0));
+        history.push(HistoryEntry::new("/docs/DESIGN.md", 40.0));
+
+        let back = history.go_back(HistoryEntry::new("/docs/api.md", 0.0));
+        assert_eq!(back, Some(HistoryEntry::new("/docs/DESIGN.md", 40.0)));
+        let back = history.go_back(HistoryEntry::new("/docs/DESIGN.md", 80.0));
+        assert_eq!(back, Some(HistoryEntry::new("/docs/README.md", 120.0)));
+        assert!(!history.can_go_back());
+        assert_eq!(
+            history.go_back(HistoryEntry::new("/docs/README.md", 0.0)),
+            None
+        );
+
+        let forward = history.go_forward(HistoryEntry::new("/docs/README.md", 120.0));
+        assert_eq!(forward, Some(HistoryEntry::new("/docs/DESIGN.md", 80.0)));
+        assert!(history.can_go_forward());
+    }
+
+    #[test]
+    fn following_a_new_link_drops_forward_entries() {
+        let mut history = NavigationHistory::default();
+        history.push(HistoryEntry::new("/a.md", 0.0));
+        history.go_back(HistoryEntry::new("/b.md", 0.0));
+        assert!(history.can_go_forward());
+
+        history.push(HistoryEntry::new("/a.md", 0.0));
+        assert!(!history.can_go_forward());
+        assert!(history.can_go_back());
+    }
+}
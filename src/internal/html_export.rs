@@ -0,0 +1,230 @@
+//! HTML export functionality for the markdown viewer
+//!
+//! This module exports markdown content to a standalone HTML file. The page
+//! is styled with the active theme's colors, code is highlighted through
+//! syntect CSS classes and, depending on the configuration, local images are
+//! embedded so the file can be shared on its own.
+
+use anyhow::{Context, Result};
+use comrak::nodes::{AlertType, AstNode, NodeValue};
+use comrak::{Arena, parse_document};
+use gpui::Rgba;
+use std::path::Path;
+use tracing::{debug, info};
+
+use crate::config::HtmlExportConfig;
+use crate::internal::inspect::node_text;
+use crate::internal::render::html::{self, HtmlOptions, escape_html};
+use crate::internal::theme::ActiveTheme;
+use crate::internal::transform::{self, TransformContext};
+
+/// Export markdown content to an HTML file
+///
+/// # Arguments
+/// * `markdown_content` - The raw markdown text to export
+/// * `markdown_file_path` - File the content came from, for relative images
+/// * `output_path` - Path where the HTML file should be saved
+/// * `theme` - Theme whose colors style the page
+/// * `html_config` - HTML export configuration (image embedding)
+///
+/// # Returns
+/// * `Ok(())` if the HTML file was successfully written
+/// * `Err` if the file could not be written
+pub fn export_to_html(
+    markdown_content: &str,
+    markdown_file_path: &Path,
+    output_path: &Path,
+    theme: &ActiveTheme,
+    html_config: &HtmlExportConfig,
+) -> Result<()> {
+    info!("Exporting markdown to HTML: {:?}", output_path);
+    debug!("Markdown content length: {} bytes", markdown_content.len());
+
+    let document = html_document(markdown_content, markdown_file_path, theme, html_config);
+    std::fs::write(output_path, document)
+        .with_context(|| format!("Failed to write '{}'", output_path.display()))?;
+
+    info!("Successfully exported HTML to {:?}", output_path);
+    Ok(())
+}
+
+/// The complete HTML page for `markdown_content`
+pub fn html_document(
+    markdown_content: &str,
+    markdown_file_path: &Path,
+    theme: &ActiveTheme,
+    html_config: &HtmlExportConfig,
+) -> String {
+    let arena = Arena::new();
+    let root = parse_document(
+        &arena,
+        markdown_content,
+        &crate::internal::render::parse_options(),
+    );
+    transform::apply(
+        &arena,
+        root,
+        &TransformContext {
+            path: Some(markdown_file_path),
+        },
+    );
+
+    let title = document_title(root, markdown_file_path);
+    let body = html::render_document(
+        root,
+        &HtmlOptions {
+            markdown_file_path: Some(markdown_file_path),
+            embed_images: html_config.embed_images,
+        },
+    );
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{}{}</style>\n</head>\n<body>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape_html(&title),
+        theme_css(theme),
+        html::syntect_css(theme),
+        body
+    )
+}
+
+/// The first level 1 heading, or the file name without its extension
+pub fn document_title<'a>(root: &'a AstNode<'a>, markdown_file_path: &Path) -> String {
+    root.descendants()
+        .find(|node| matches!(&node.data.borrow().value, NodeValue::Heading(heading) if heading.level == 1))
+        .map(node_text)
+        .unwrap_or_else(|| {
+            markdown_file_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("Document")
+                .to_string()
+        })
+}
+
+fn css_color(color: Rgba) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "rgba({}, {}, {}, {:.3})",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        color.a.clamp(0.0, 1.0)
+    )
+}
+
+/// Page stylesheet built from the theme colors
+fn theme_css(theme: &ActiveTheme) -> String {
+    let colors = theme.colors;
+    let alerts: String = [
+        AlertType::Note,
+        AlertType::Tip,
+        AlertType::Important,
+        AlertType::Warning,
+        AlertType::Caution,
+    ]
+    .into_iter()
+    .map(|alert_type| {
+        let (accent, background) = colors.alert_colors(alert_type);
+        format!(
+            ".{class} {{ border-color: {accent}; background: {background}; }}\n\
+             .{class} .markdown-alert-title {{ color: {accent}; }}\n",
+            class = alert_type.css_class(),
+            accent = css_color(accent),
+            background = css_color(background),
+        )
+    })
+    .collect();
+    format!(
+        "body {{ margin: 0; background: {bg}; color: {text}; \
+         font-family: -apple-system, BlinkMacSystemFont, \"Segoe UI\", Helvetica, Arial, sans-serif; \
+         line-height: 1.6; }}\n\
+         main {{ max-width: 860px; margin: 0 auto; padding: 32px; }}\n\
+         a {{ color: {link}; }}\n\
+         a:hover {{ color: {hover_link}; }}\n\
+         code, pre {{ font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }}\n\
+         code {{ background: {code_bg}; padding: 0.1em 0.3em; border-radius: 3px; }}\n\
+         pre.code {{ background: {code_bg}; padding: 12px 16px; border-radius: 6px; overflow-x: auto; }}\n\
+         pre.code code {{ background: none; padding: 0; }}\n\
+         blockquote {{ margin: 0; padding-left: 16px; border-left: 4px solid {quote}; font-style: italic; }}\n\
+         .markdown-alert {{ margin: 8px 0; padding: 8px 16px; border-left: 4px solid; border-radius: 0 4px 4px 0; }}\n\
+         .markdown-alert-title {{ margin: 0 0 4px; font-weight: 600; }}\n\
+         {alerts}\
+         table {{ border-collapse: collapse; margin: 8px 0; }}\n\
+         th, td {{ border: 1px solid {table_border}; padding: 6px 12px; }}\n\
+         th {{ background: {table_header}; }}\n\
+         img {{ max-width: 100%; }}\n\
+         .math {{ font-style: italic; }}\n\
+         .math.display {{ text-align: center; margin: 8px 0; }}\n\
+         hr.footnotes {{ margin-top: 32px; }}\n\
+         .footnote {{ display: flex; gap: 8px; font-size: 0.9em; }}\n\
+         .footnote p {{ margin: 0; }}\n",
+        bg = css_color(colors.bg_color),
+        text = css_color(colors.text_color),
+        link = css_color(colors.link_color),
+        hover_link = css_color(colors.hover_link_color),
+        code_bg = css_color(colors.code_bg_color),
+        quote = css_color(colors.blockquote_border_color),
+        table_border = css_color(colors.table_border_color),
+        table_header = css_color(colors.table_header_bg),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::theme::test_theme;
+    use std::fs;
+
+    #[test]
+    fn test_export_to_html_creates_page() {
+        let markdown = "# Test Document\n\nThis is a test.\n\n```rust\nlet x = 1;\n```";
+        let output_path = std::env::temp_dir().join("test_export.html");
+        let theme = test_theme();
+        let _ = fs::remove_file(&output_path);
+
+        let result = export_to_html(
+            markdown,
+            Path::new("doc.md"),
+            &output_path,
+            &theme,
+            &HtmlExportConfig::default(),
+        );
+        assert!(result.is_ok(), "HTML export should succeed");
+
+        let html = fs::read_to_string(&output_path).unwrap();
+        fs::remove_file(&output_path).unwrap();
+        assert!(html.contains("<title>Test Document</title>"));
+        assert!(html.contains(&format!("background: {}", css_color(theme.colors.bg_color))));
+        assert!(
+            html.contains("generated by syntect"),
+            "Code CSS should be inlined"
+        );
+    }
+
+    #[test]
+    fn title_falls_back_to_file_name() {
+        let theme = test_theme();
+        let html = html_document(
+            "## Only a section",
+            Path::new("/docs/notes.md"),
+            &theme,
+            &HtmlExportConfig::default(),
+        );
+        assert!(html.contains("<title>notes</title>"));
+    }
+
+    #[test]
+    fn test_export_to_html_validates_path() {
+        let invalid_path = Path::new("/invalid/nonexistent/directory/test.html");
+        let result = export_to_html(
+            "# Test",
+            Path::new("doc.md"),
+            invalid_path,
+            &test_theme(),
+            &HtmlExportConfig::default(),
+        );
+        assert!(result.is_err(), "Export to an invalid path should fail");
+    }
+}
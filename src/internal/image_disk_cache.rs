@@ -0,0 +1,263 @@
+//! Remote images kept on disk between runs
+//!
+//! The in-memory [`ImageCache`](super::image_loader::ImageCache) only lives
+//! as long as the document, so remote images were downloaded again on every
+//! start and every reload. [`DiskCache`] keeps each response body with its
+//! `ETag` and `Last-Modified` headers in a directory, named by a hash of the
+//! URL. The next fetch of the URL sends them back and a `304 Not Modified`
+//! answer is served from disk. The least recently used images are removed
+//! once the directory grows past `images.cache_max_mb`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::SystemTime;
+use tracing::debug;
+
+use crate::config::ImageConfig;
+
+/// Cache used by image fetches, set from the config with [`configure`]
+static CACHE: RwLock<Option<DiskCache>> = RwLock::new(None);
+
+const BODY_EXTENSION: &str = "img";
+const META_EXTENSION: &str = "json";
+
+/// Use the cache described by `config` for image fetches from now on
+pub fn configure(config: &ImageConfig) {
+    let cache = match config.cache_max_mb {
+        0 => None,
+        mb => Some(DiskCache::new(
+            config.cache_dir.clone().unwrap_or_else(default_dir),
+            mb.saturating_mul(1024 * 1024),
+        )),
+    };
+    debug!("Image disk cache: {:?}", cache);
+    *CACHE.write().unwrap_or_else(|e| e.into_inner()) = cache;
+}
+
+/// The configured cache; none until [`configure`] turns it on
+pub fn current() -> Option<DiskCache> {
+    CACHE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// `markdown_viewer/images` in the platform cache directory
+fn default_dir() -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let base = match std::env::consts::OS {
+        "macos" => home.map(|home| home.join("Library/Caches")),
+        "windows" => std::env::var_os("LOCALAPPDATA").map(PathBuf::from),
+        _ => std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.map(|home| home.join(".cache"))),
+    };
+    base.unwrap_or_else(std::env::temp_dir)
+        .join("markdown_viewer")
+        .join("images")
+}
+
+/// FNV-1a hash of the URL, stable across builds so files are found again
+fn url_hash(url: &str) -> u64 {
+    url.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Response headers the server can compare to answer "not modified"
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Stored next to each body
+#[derive(Serialize, Deserialize)]
+struct Meta {
+    url: String,
+    #[serde(flatten)]
+    validators: Validators,
+}
+
+/// A cached response body with the headers to revalidate it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedImage {
+    pub bytes: Vec<u8>,
+    pub validators: Validators,
+}
+
+/// Directory of downloaded images, limited to `max_bytes`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes,
+        }
+    }
+
+    fn path(&self, url: &str, extension: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.{}", url_hash(url), extension))
+    }
+
+    /// The cached response for `url`, which now counts as recently used
+    pub fn get(&self, url: &str) -> Option<CachedImage> {
+        let meta: Meta =
+            serde_json::from_slice(&fs::read(self.path(url, META_EXTENSION)).ok()?).ok()?;
+        // Two URLs can share a hash
+        if meta.url != url {
+            return None;
+        }
+        let body = self.path(url, BODY_EXTENSION);
+        let bytes = fs::read(&body).ok()?;
+        if let Err(e) = touch(&body) {
+            debug!("Failed to mark cached image {:?} as used: {}", body, e);
+        }
+        Some(CachedImage {
+            bytes,
+            validators: meta.validators,
+        })
+    }
+
+    /// Keep `bytes` downloaded from `url`, then prune the directory to its
+    /// size limit
+    ///
+    /// Responses without validators are not kept since they could not be
+    /// revalidated, nor are bodies larger than the whole cache.
+    pub fn put(&self, url: &str, bytes: &[u8], validators: &Validators) -> Result<()> {
+        if validators.is_empty() || bytes.len() as u64 > self.max_bytes {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create image cache {:?}", self.dir))?;
+        fs::write(self.path(url, BODY_EXTENSION), bytes).context("Failed to cache image")?;
+        let meta = Meta {
+            url: url.to_string(),
+            validators: validators.clone(),
+        };
+        fs::write(self.path(url, META_EXTENSION), serde_json::to_vec(&meta)?)
+            .context("Failed to cache image headers")?;
+        self.prune()?;
+        Ok(())
+    }
+
+    /// Remove the least recently used images until the directory fits its
+    /// size limit; returns the number removed
+    pub fn prune(&self) -> Result<usize> {
+        let mut bodies = fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read image cache {:?}", self.dir))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_some_and(|extension| extension == BODY_EXTENSION)
+            })
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                Some((used, metadata.len(), entry.path()))
+            })
+            .collect::<Vec<_>>();
+        bodies.sort();
+
+        let mut total: u64 = bodies.iter().map(|(_, len, _)| len).sum();
+        let mut removed = 0;
+        for (_, len, path) in bodies {
+            if total <= self.max_bytes {
+                break;
+            }
+            fs::remove_file(&path)?;
+            fs::remove_file(path.with_extension(META_EXTENSION)).ok();
+            total -= len;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+/// Set the modification time, which records when a cached image was last used
+fn touch(path: &Path) -> std::io::Result<()> {
+    fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn validators(etag: &str) -> Validators {
+        Validators {
+            etag: Some(etag.to_string()),
+            last_modified: None,
+        }
+    }
+
+    #[test]
+    fn responses_with_validators_are_kept() {
+        let dir = std::env::temp_dir().join("mdv_image_disk_cache_test");
+        fs::remove_dir_all(&dir).ok();
+        let cache = DiskCache::new(&dir, 1024);
+
+        cache
+            .put("https://example.com/a.png", b"png", &validators("\"v1\""))
+            .unwrap();
+        cache
+            .put("https://example.com/b.png", b"png", &Validators::default())
+            .unwrap();
+
+        let cached = cache.get("https://example.com/a.png").unwrap();
+        assert_eq!(cached.bytes, b"png");
+        assert_eq!(cached.validators, validators("\"v1\""));
+        assert_eq!(cache.get("https://example.com/b.png"), None);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn least_recently_used_images_are_pruned() {
+        let dir = std::env::temp_dir().join("mdv_image_disk_cache_prune_test");
+        fs::remove_dir_all(&dir).ok();
+        let cache = DiskCache::new(&dir, 10);
+        cache
+            .put("https://example.com/old.png", b"0123", &validators("a"))
+            .unwrap();
+        cache
+            .put("https://example.com/new.png", b"4567", &validators("b"))
+            .unwrap();
+        let long_ago = SystemTime::now() - Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(cache.path("https://example.com/old.png", BODY_EXTENSION))
+            .unwrap()
+            .set_modified(long_ago)
+            .unwrap();
+
+        cache
+            .put("https://example.com/third.png", b"89ab", &validators("c"))
+            .unwrap();
+        assert_eq!(cache.get("https://example.com/old.png"), None);
+        assert!(cache.get("https://example.com/new.png").is_some());
+        assert!(cache.get("https://example.com/third.png").is_some());
+        assert!(
+            !cache
+                .path("https://example.com/old.png", META_EXTENSION)
+                .exists()
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -7,6 +7,8 @@ It provides:
 - `fetch_bytes_with_optional_png_fallback`:
   Fetches bytes from a URL and returns the raw bytes. The function logs
   status and content-type information and returns the raw body as a `Vec<u8>`.
+  With the disk cache turned on (see `image_disk_cache`), a cached copy is
+  revalidated with its `ETag`/`Last-Modified` and reused when unchanged.
 
 - `png_fallback_url`:
   Utility to construct a server-side PNG fallback URL from an existing URL.
@@ -21,6 +23,29 @@ It provides:
   3) if it looks like SVG, attempts SVG rasterization via crate helper,
   4) attempts a PNG fallback URL when available.
 
+  Images inside zip/epub archives (`book.zip!/img/a.png`) are read from
+  the archive and decoded the same way.
+
+- `ImageProvider`:
+  Where images come from. The viewer resolves every image reference and
+  fetches it through its provider; `DefaultImageProvider` reads local files
+  and HTTP(S) URLs with `fetch_and_decode_image`. Embedders implement the
+  trait to serve images from databases, archives or authenticated APIs.
+
+- `ImageCache`:
+  Decoded images by resolved key. Renderers look images up here; keys that
+  are not loaded yet are queued so the owner can fetch them after the frame.
+  Fetches in flight are tracked by key, so a key requested again while its
+  fetch is running (e.g. after a reload) waits for that fetch instead of
+  downloading the image a second time.
+
+- `FetchTicket`:
+  Handed out by `ImageCache::begin_fetch` for each fetch. At most
+  `MAX_CONCURRENT_DOWNLOADS` remote images download at once and the rest
+  wait for a slot. Switching documents, or a reload that drops an image,
+  cancels its fetch. Failures that may pass (unreachable network, broken
+  connection, 429 and 5xx answers) are retried with exponential backoff.
+
 Notes:
 - This module intentionally keeps fetching simple and returns raw bytes so
   callers may attempt to decode (e.g., as raster image or SVG) and then
@@ -28,32 +53,366 @@ Notes:
 */
 
 use anyhow::Result;
-use reqwest::header::CONTENT_TYPE;
+use gpui::ImageSource;
+use reqwest::StatusCode;
+use reqwest::header::{
+    CONTENT_TYPE, ETAG, HeaderName, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Semaphore, watch};
 use tracing::{debug, info};
 
+use super::archive::ArchivePath;
+use super::file_handling::resolve_image_path;
+use super::image_disk_cache::{self, Validators};
+use super::network;
+
+/// Remote images downloaded at the same time; the others wait for a slot
+pub const MAX_CONCURRENT_DOWNLOADS: usize = 6;
+
+/// Pause before the first retry of a fetch; it doubles for each further retry
+pub const FETCH_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Future returned by [`ImageProvider::fetch`]
+pub type ImageFuture<'a> = Pin<Box<dyn Future<Output = Result<image::DynamicImage>> + Send + 'a>>;
+
+/// A source of images for rendered documents
+pub trait ImageProvider: Send + Sync {
+    /// Key under which the image referenced as `url` is fetched and cached
+    ///
+    /// `markdown_file_path` is the document containing the reference. The
+    /// default resolves relative paths against the document's directory.
+    fn resolve(&self, url: &str, markdown_file_path: Option<&Path>) -> String {
+        match markdown_file_path {
+            Some(md_path) => resolve_image_path(url, md_path),
+            None => url.to_string(),
+        }
+    }
+
+    /// Fetch and decode the image for a key returned by [`resolve`](Self::resolve)
+    ///
+    /// Runs on the background runtime, never on the UI thread.
+    fn fetch<'a>(&'a self, key: &'a str) -> ImageFuture<'a>;
+}
+
+/// Local files, archive entries and HTTP(S) URLs, via [`fetch_and_decode_image`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultImageProvider;
+
+impl ImageProvider for DefaultImageProvider {
+    fn fetch<'a>(&'a self, key: &'a str) -> ImageFuture<'a> {
+        Box::pin(fetch_and_decode_image(key))
+    }
+}
+
+/// Loading state of one image
+pub enum ImageState {
+    Loading,
+    Loaded(ImageSource),
+    Error,
+}
+
+/// Images of the current document, keyed by [`ImageProvider::resolve`]
+pub struct ImageCache {
+    provider: Arc<dyn ImageProvider>,
+    states: HashMap<String, ImageState>,
+    requested: HashSet<String>,
+    /// Keys being fetched, with the switch that cancels each fetch
+    in_flight: HashMap<String, watch::Sender<bool>>,
+    /// Shared by the remote fetches, see [`MAX_CONCURRENT_DOWNLOADS`]
+    download_slots: Arc<Semaphore>,
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new(Arc::new(DefaultImageProvider))
+    }
+}
+
+impl ImageCache {
+    pub fn new(provider: Arc<dyn ImageProvider>) -> Self {
+        Self {
+            provider,
+            states: HashMap::new(),
+            requested: HashSet::new(),
+            in_flight: HashMap::new(),
+            download_slots: Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS)),
+        }
+    }
+
+    pub fn provider(&self) -> Arc<dyn ImageProvider> {
+        self.provider.clone()
+    }
+
+    /// Resolve `url` with the provider (see [`ImageProvider::resolve`])
+    pub fn resolve(&self, url: &str, markdown_file_path: Option<&Path>) -> String {
+        self.provider.resolve(url, markdown_file_path)
+    }
+
+    /// The loaded image for a resolved `key`, queueing it for fetching when unknown
+    pub fn image_for(&mut self, key: &str) -> Option<ImageSource> {
+        match self.states.get(key) {
+            Some(ImageState::Loaded(source)) => Some(source.clone()),
+            Some(_) => None,
+            None => {
+                self.requested.insert(key.to_string());
+                None
+            }
+        }
+    }
+
+    /// Keys looked up since the last call that have not been fetched yet
+    pub fn take_requested(&mut self) -> Vec<String> {
+        self.requested
+            .drain()
+            .filter(|key| !self.states.contains_key(key))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ImageState> {
+        self.states.get(key)
+    }
+
+    /// Whether loading the image for `key` failed
+    pub fn failed(&self, key: &str) -> bool {
+        matches!(self.states.get(key), Some(ImageState::Error))
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.states.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: String, state: ImageState) {
+        self.states.insert(key, state);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.states.remove(key);
+    }
+
+    /// Claim the fetch for `key`; none when a fetch for it is already
+    /// running, whose result will land in the cache for both requests
+    pub fn begin_fetch(&mut self, key: &str) -> Option<FetchTicket> {
+        if self.in_flight.contains_key(key) {
+            return None;
+        }
+        let (cancel, cancelled) = watch::channel(false);
+        self.in_flight.insert(key.to_string(), cancel);
+        Some(FetchTicket {
+            cancelled,
+            download_slots: is_remote(key).then(|| self.download_slots.clone()),
+        })
+    }
+
+    /// The fetch `ticket` was handed out for finished
+    pub fn finish_fetch(&mut self, key: &str, ticket: &FetchTicket) {
+        let current = self
+            .in_flight
+            .get(key)
+            .is_some_and(|cancel| cancel.subscribe().same_channel(&ticket.cancelled));
+        if current {
+            self.in_flight.remove(key);
+        }
+    }
+
+    /// Cancel the fetches of keys `keep` rejects
+    fn cancel_fetches(&mut self, keep: impl Fn(&str) -> bool) {
+        self.in_flight.retain(|key, cancel| {
+            let kept = keep(key);
+            if !kept {
+                debug!("Cancelling image fetch: {}", key);
+                cancel.send_replace(true);
+            }
+            kept
+        });
+    }
+
+    /// Keep only images whose key is in `keys`, e.g. after a reload
+    ///
+    /// Failed images are dropped too so they are retried, and fetches of
+    /// other keys are cancelled. Returns the number of entries removed.
+    pub fn retain_keys(&mut self, keys: &HashSet<String>) -> usize {
+        self.cancel_fetches(|key| keys.contains(key));
+        let before = self.states.len();
+        self.states
+            .retain(|key, state| keys.contains(key) && !matches!(state, ImageState::Error));
+        self.requested.retain(|key| keys.contains(key));
+        before - self.states.len()
+    }
+
+    /// Forget the images that failed so they are fetched again
+    pub fn forget_failed(&mut self) {
+        self.states
+            .retain(|_, state| !matches!(state, ImageState::Error));
+    }
+
+    /// Forget every image and cancel the fetches, e.g. after the document
+    /// changed
+    pub fn clear(&mut self) {
+        self.cancel_fetches(|_| false);
+        self.states.clear();
+        self.requested.clear();
+    }
+}
+
+fn is_remote(key: &str) -> bool {
+    key.starts_with("http://") || key.starts_with("https://")
+}
+
+/// Error of a fetch whose image is no longer wanted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Image fetch cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// One fetch claimed with [`ImageCache::begin_fetch`]
+///
+/// The fetch is cancelled when its key is dropped from the cache or the
+/// cache itself is dropped, e.g. when another image provider is set.
+#[derive(Debug, Clone)]
+pub struct FetchTicket {
+    cancelled: watch::Receiver<bool>,
+    /// Slots to wait for, for remote images
+    download_slots: Option<Arc<Semaphore>>,
+}
+
+impl FetchTicket {
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow() || self.cancelled.has_changed().is_err()
+    }
+
+    /// Run `attempt` once a download slot is free, retrying failures that
+    /// may pass ([`is_transient`]) up to `retries` times with exponential
+    /// backoff
+    ///
+    /// Fails with [`Cancelled`] as soon as the fetch is cancelled, dropping
+    /// the attempt in progress.
+    pub async fn run<T, F, Fut>(&self, retries: usize, attempt: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut cancelled = self.cancelled.clone();
+        let attempts = async {
+            let mut delay = FETCH_RETRY_DELAY;
+            let mut retries_left = retries;
+            loop {
+                let result = {
+                    let _slot = match &self.download_slots {
+                        Some(slots) => Some(slots.acquire().await?),
+                        None => None,
+                    };
+                    attempt().await
+                };
+                match result {
+                    Err(e) if retries_left > 0 && is_transient(&e) => {
+                        debug!("Retrying image fetch in {:?}: {:#}", delay, e);
+                        tokio::time::sleep(delay).await;
+                        delay *= 2;
+                        retries_left -= 1;
+                    }
+                    result => return result,
+                }
+            }
+        };
+        tokio::select! {
+            biased;
+            // A dropped cache cancels its fetches too
+            _ = cancelled.wait_for(|cancelled| *cancelled) => Err(Cancelled.into()),
+            result = attempts => result,
+        }
+    }
+}
+
+/// Whether a failed fetch may succeed when tried again: the network could
+/// not be reached, the connection broke off or the server answered 429 or 5xx
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    network::is_unreachable(error)
+        || error.chain().any(|cause| {
+            cause.downcast_ref::<reqwest::Error>().is_some_and(|error| {
+                error.is_body()
+                    || error.status().is_some_and(|status| {
+                        status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+                    })
+            })
+        })
+}
+
 /// Fetch bytes from the given URL and return them as a Vec<u8>.
 ///
 /// This function logs the HTTP status and Content-Type header when available.
 /// It does not attempt to interpret or decode the bytes — callers should
 /// decide how to treat the returned payload (raster decode, SVG rasterize, etc).
 ///
+/// When the disk cache holds the URL, the request carries its validators and
+/// a `304 Not Modified` answer returns the cached bytes. Successful responses
+/// with an `ETag` or `Last-Modified` header are cached.
+///
 /// # Errors
 ///
 /// Returns an error if the underlying HTTP request fails or the body cannot be
 /// read into memory.
 pub async fn fetch_bytes_with_optional_png_fallback(url: &str) -> Result<Vec<u8>, anyhow::Error> {
-    // Perform a simple GET request. Use reqwest's convenience `get` for brevity.
-    let resp = reqwest::get(url).await?;
+    let disk_cache = image_disk_cache::current();
+    let cached = disk_cache.as_ref().and_then(|cache| cache.get(url));
+
+    let mut request = reqwest::Client::new().get(url);
+    if let Some(cached) = &cached {
+        if let Some(etag) = &cached.validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let resp = request.send().await?;
     let status = resp.status();
-    let content_type = resp
-        .headers()
-        .get(CONTENT_TYPE)
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s.to_string());
+    // Overloaded and failing servers are worth another try (see `is_transient`)
+    let resp = match status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        true => resp.error_for_status()?,
+        false => resp,
+    };
+    if status == StatusCode::NOT_MODIFIED
+        && let Some(cached) = cached
+    {
+        debug!("Using cached image for {} (not modified)", url);
+        return Ok(cached.bytes);
+    }
+
+    let header = |name: HeaderName| {
+        resp.headers()
+            .get(name)
+            .and_then(|h| h.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    let content_type = header(CONTENT_TYPE);
+    let validators = Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
 
     // Read body into owned Vec<u8>
     let bytes = resp.bytes().await?.to_vec();
 
+    if status.is_success()
+        && let Some(cache) = &disk_cache
+        && let Err(e) = cache.put(url, &bytes, &validators)
+    {
+        debug!("Failed to cache image {}: {:#}", url, e);
+    }
+
     debug!(
         "Fetched {} bytes from {} (status={}, ct={:?})",
         bytes.len(),
@@ -74,7 +433,8 @@ pub async fn fetch_bytes_with_optional_png_fallback(url: &str) -> Result<Vec<u8>
 ///    attempt crate::rasterize_svg_to_dynamic_image to rasterize into a DynamicImage.
 /// 4) If that fails, attempt a server-side PNG fallback (replace `?` with `.png?` or append `.png`)
 ///    and try decoding that response as a raster image.
-/// 5) If `path` is a local filesystem path, use `image::open`.
+/// 5) If `path` points into a zip/epub archive, decode the entry's bytes.
+/// 6) If `path` is a local filesystem path, use `image::open`.
 pub async fn fetch_and_decode_image(path: &str) -> Result<image::DynamicImage, anyhow::Error> {
     match path {
         p if p.starts_with("http://") || p.starts_with("https://") => {
@@ -119,12 +479,34 @@ pub async fn fetch_and_decode_image(path: &str) -> Result<image::DynamicImage, a
                 }
             }
         }
-        _ => {
-            // Local file
-            info!("Loading local image: {}", path);
-            let img = image::open(path)?;
-            Ok(img)
+        _ => match ArchivePath::parse(path) {
+            Some(archive_path) => decode_archive_image(&archive_path),
+            None => {
+                // Local file
+                info!("Loading local image: {}", path);
+                let img = image::open(path)?;
+                Ok(img)
+            }
+        },
+    }
+}
+
+/// Read an image entry from a zip/epub archive and decode it (raster or SVG)
+fn decode_archive_image(archive_path: &ArchivePath) -> Result<image::DynamicImage> {
+    info!("Loading image from archive: {}", archive_path);
+    let bytes = archive_path.read()?;
+    match image::load_from_memory(&bytes) {
+        Ok(img) => Ok(img),
+        Err(e)
+            if bytes.starts_with(b"<") || archive_path.entry.to_lowercase().ends_with(".svg") =>
+        {
+            debug!(
+                "Raster decode failed for {}, trying SVG: {}",
+                archive_path, e
+            );
+            crate::rasterize_svg_to_dynamic_image(&bytes)
         }
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -159,6 +541,178 @@ mod tests {
         );
     }
 
+    /// Serves every key as a 1x1 image
+    struct StubProvider;
+
+    impl ImageProvider for StubProvider {
+        fn resolve(&self, url: &str, _markdown_file_path: Option<&Path>) -> String {
+            format!("stub:{}", url)
+        }
+
+        fn fetch<'a>(&'a self, _key: &'a str) -> ImageFuture<'a> {
+            Box::pin(async { Ok(image::DynamicImage::new_rgba8(1, 1)) })
+        }
+    }
+
+    #[test]
+    fn cache_queues_unknown_images_once() {
+        let mut cache = ImageCache::new(Arc::new(StubProvider));
+        let key = cache.resolve("a.png", None);
+        assert!(cache.image_for(&key).is_none());
+        assert!(cache.image_for(&key).is_none());
+        assert_eq!(cache.take_requested(), vec!["stub:a.png".to_string()]);
+
+        cache.insert(key.clone(), ImageState::Loading);
+        assert!(cache.image_for(&key).is_none());
+        assert!(cache.take_requested().is_empty());
+    }
+
+    #[test]
+    fn default_provider_resolves_relative_to_document() {
+        let provider = DefaultImageProvider;
+        assert_eq!(
+            provider.resolve(
+                "https://example.com/a.png",
+                Some(Path::new("/docs/readme.md"))
+            ),
+            "https://example.com/a.png"
+        );
+        assert_eq!(provider.resolve("img/a.png", None), "img/a.png");
+    }
+
+    #[test]
+    fn retain_keeps_unchanged_loaded_images() {
+        let mut cache = ImageCache::new(Arc::new(StubProvider));
+        cache.insert("stub:kept.png".to_string(), ImageState::Loading);
+        cache.insert("stub:removed.png".to_string(), ImageState::Loading);
+        cache.insert("stub:broken.png".to_string(), ImageState::Error);
+        assert!(cache.failed("stub:broken.png"));
+        assert!(!cache.failed("stub:kept.png"));
+
+        let keys: HashSet<String> = ["stub:kept.png", "stub:broken.png"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(cache.retain_keys(&keys), 2);
+        assert!(cache.contains_key("stub:kept.png"));
+        assert!(!cache.contains_key("stub:removed.png"));
+        assert!(!cache.contains_key("stub:broken.png"));
+    }
+
+    #[test]
+    fn forgotten_failures_are_queued_again() {
+        let mut cache = ImageCache::new(Arc::new(StubProvider));
+        cache.insert("stub:kept.png".to_string(), ImageState::Loading);
+        cache.insert("stub:broken.png".to_string(), ImageState::Error);
+        cache.forget_failed();
+        assert!(cache.contains_key("stub:kept.png"));
+        assert!(cache.image_for("stub:broken.png").is_none());
+        assert_eq!(cache.take_requested(), vec!["stub:broken.png".to_string()]);
+    }
+
+    #[test]
+    fn fetches_in_flight_are_shared_until_cancelled() {
+        let mut cache = ImageCache::new(Arc::new(StubProvider));
+        let kept = cache.begin_fetch("stub:kept.png").unwrap();
+        let dropped = cache.begin_fetch("stub:dropped.png").unwrap();
+        assert!(cache.begin_fetch("stub:kept.png").is_none());
+
+        let keys: HashSet<String> = ["stub:kept.png".to_string()].into_iter().collect();
+        cache.retain_keys(&keys);
+        assert!(!kept.is_cancelled());
+        assert!(dropped.is_cancelled());
+        assert!(cache.begin_fetch("stub:kept.png").is_none());
+
+        // A new fetch for a cancelled key is not finished by the old one
+        let again = cache.begin_fetch("stub:dropped.png").unwrap();
+        cache.finish_fetch("stub:dropped.png", &dropped);
+        assert!(!again.is_cancelled());
+        assert!(cache.begin_fetch("stub:dropped.png").is_none());
+
+        cache.clear();
+        assert!(kept.is_cancelled() && again.is_cancelled());
+        assert!(cache.begin_fetch("stub:kept.png").is_some());
+    }
+
+    #[tokio::test]
+    async fn downloads_wait_for_a_free_slot() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut cache = ImageCache::new(Arc::new(StubProvider));
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let downloads = (0..MAX_CONCURRENT_DOWNLOADS * 2).map(|i| {
+            let ticket = cache
+                .begin_fetch(&format!("https://example.com/{}.png", i))
+                .unwrap();
+            let (running, most) = (running.clone(), most.clone());
+            tokio::spawn(async move {
+                ticket
+                    .run(0, || async {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        most.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        Ok(())
+                    })
+                    .await
+            })
+        });
+        for download in downloads.collect::<Vec<_>>() {
+            download.await.unwrap().unwrap();
+        }
+        assert_eq!(most.load(Ordering::SeqCst), MAX_CONCURRENT_DOWNLOADS);
+    }
+
+    #[tokio::test]
+    async fn cancelling_stops_a_fetch_in_progress() {
+        let mut cache = ImageCache::new(Arc::new(StubProvider));
+        let ticket = cache.begin_fetch("https://example.com/slow.png").unwrap();
+        let fetch = tokio::spawn({
+            let ticket = ticket.clone();
+            async move { ticket.run(0, std::future::pending::<Result<()>>).await }
+        });
+        cache.clear();
+        let error = fetch.await.unwrap().unwrap_err();
+        assert!(error.is::<Cancelled>());
+    }
+
+    #[tokio::test]
+    async fn only_transient_failures_are_retried() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut cache = ImageCache::new(Arc::new(StubProvider));
+        let ticket = cache.begin_fetch("http://127.0.0.1:9/a.png").unwrap();
+        let runs = AtomicUsize::new(0);
+        let result = ticket
+            .run(2, || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(anyhow::anyhow!("Unsupported image format"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // Nothing listens on the discard port of the loopback address
+        let runs = AtomicUsize::new(0);
+        let result = ticket
+            .run(1, || async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                reqwest::get("http://127.0.0.1:9/a.png")
+                    .await
+                    .map_err(anyhow::Error::new)
+            })
+            .await;
+        assert!(is_transient(&result.unwrap_err()));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn custom_provider_fetches_images() {
+        let image = StubProvider.fetch("stub:a.png").await.unwrap();
+        assert_eq!((image.width(), image.height()), (1, 1));
+    }
+
     #[test]
     fn png_fallback_without_query() {
         let in_url = "https://placehold.co/800";
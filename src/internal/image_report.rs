@@ -0,0 +1,88 @@
+//! Images that failed to load or have no alt text
+//!
+//! Broken images only show up as placeholders somewhere in a long document,
+//! and missing alt text does not show up at all. [`image_issues`] lists both
+//! from the [`inspect`](crate::internal::inspect) summary, so markdown and
+//! HTML `<img>` images are covered, with the line each one is on. The viewer
+//! shows the list in the image report overlay (Cmd+Shift+I), where clicking
+//! an entry jumps to its line.
+
+use crate::internal::inspect::ImageInfo;
+
+/// An image with a problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageIssue {
+    pub url: String,
+    pub alt: String,
+    pub line: usize,
+    /// Loading the image failed
+    pub failed: bool,
+}
+
+impl ImageIssue {
+    pub fn missing_alt(&self) -> bool {
+        self.alt.trim().is_empty()
+    }
+
+    /// What is wrong, e.g. "Failed to load · No alt text"
+    pub fn problem(&self) -> &'static str {
+        match (self.failed, self.missing_alt()) {
+            (true, true) => "Failed to load · No alt text",
+            (true, false) => "Failed to load",
+            (false, _) => "No alt text",
+        }
+    }
+}
+
+/// The images of a document that failed to load, by `failed(url)`, or
+/// lack alt text, in document order
+pub fn image_issues(images: &[ImageInfo], failed: impl Fn(&str) -> bool) -> Vec<ImageIssue> {
+    images
+        .iter()
+        .map(|image| ImageIssue {
+            url: image.url.clone(),
+            alt: image.alt.clone(),
+            line: image.line,
+            failed: failed(&image.url),
+        })
+        .filter(|issue| issue.failed || issue.missing_alt())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::inspect::inspect_markdown;
+
+    #[test]
+    fn failed_images_and_missing_alt_text_are_listed() {
+        let summary = inspect_markdown(
+            "![Logo](logo.png)\n\n![](diagram.svg)\n\n![Chart](https://example.com/chart.png)\n\n<img src=\"badge.svg\">\n",
+            None,
+        );
+        let issues = image_issues(&summary.images, |url| url.starts_with("https://"));
+        let listed: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.line, issue.url.as_str(), issue.problem()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (3, "diagram.svg", "No alt text"),
+                (5, "https://example.com/chart.png", "Failed to load"),
+                (7, "badge.svg", "No alt text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn blank_alt_text_counts_as_missing() {
+        let issue = ImageIssue {
+            url: "a.png".to_string(),
+            alt: "  ".to_string(),
+            line: 1,
+            failed: true,
+        };
+        assert_eq!(issue.problem(), "Failed to load · No alt text");
+    }
+}
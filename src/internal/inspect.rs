@@ -0,0 +1,252 @@
+//! Structured document summaries
+//!
+//! Walks the parsed document (after the transform pipeline, like every
+//! renderer) and collects its headings, links, images and code blocks with
+//! their source lines, plus a prose word count. `markdown_viewer inspect`
+//! prints the summary, as JSON with `--json`, for tooling pipelines.
+
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{Arena, parse_document};
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+use crate::internal::raw_html;
+use crate::internal::transform::{self, TransformContext};
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HeadingInfo {
+    pub level: u8,
+    pub text: String,
+    /// GitHub-style anchor for the heading
+    pub slug: String,
+    /// 1-based source line
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LinkInfo {
+    pub text: String,
+    pub url: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ImageInfo {
+    pub alt: String,
+    pub url: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CodeBlockInfo {
+    /// First word of the info string, if any
+    pub language: Option<String>,
+    pub line: usize,
+    pub line_count: usize,
+}
+
+/// Everything `inspect` reports about a document
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+pub struct DocumentSummary {
+    pub headings: Vec<HeadingInfo>,
+    pub links: Vec<LinkInfo>,
+    pub images: Vec<ImageInfo>,
+    pub code_blocks: Vec<CodeBlockInfo>,
+    /// Words of prose, excluding code blocks
+    pub word_count: usize,
+}
+
+/// GitHub-style heading anchor: lowercase, punctuation dropped, spaces to dashes
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// First heading whose text (case-insensitive) or slug matches `query`
+///
+/// A leading `#` is ignored, so `#next-steps` finds the "Next Steps!" heading.
+pub fn find_heading<'h>(headings: &'h [HeadingInfo], query: &str) -> Option<&'h HeadingInfo> {
+    let query = query.trim();
+    let text = query.to_lowercase();
+    let slug = query.strip_prefix('#').unwrap_or(query);
+    headings
+        .iter()
+        .find(|heading| heading.text.to_lowercase() == text || heading.slug == slug)
+}
+
+/// Text content of `node`, including inline code
+pub(crate) fn node_text<'a>(node: &'a AstNode<'a>) -> String {
+    node.descendants()
+        .filter_map(|descendant| match &descendant.data.borrow().value {
+            NodeValue::Text(text) => Some(text.to_string()),
+            NodeValue::Code(code) => Some(code.literal.to_string()),
+            NodeValue::SoftBreak | NodeValue::LineBreak => Some(" ".to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse `content` and summarise it
+///
+/// `path` is the file the content came from, passed on to transform passes.
+pub fn inspect_markdown(content: &str, path: Option<&Path>) -> DocumentSummary {
+    let arena = Arena::new();
+    let root = parse_document(&arena, content, &crate::internal::render::parse_options());
+    transform::apply(&arena, root, &TransformContext { path });
+    inspect_document(root)
+}
+
+/// Summarise a parsed document
+pub fn inspect_document<'a>(root: &'a AstNode<'a>) -> DocumentSummary {
+    let mut summary = DocumentSummary::default();
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        let line = data.sourcepos.start.line;
+        match &data.value {
+            NodeValue::Heading(heading) => {
+                let text = node_text(node);
+                summary.headings.push(HeadingInfo {
+                    level: heading.level,
+                    slug: slugify(&text),
+                    text,
+                    line,
+                });
+            }
+            NodeValue::Link(link) => summary.links.push(LinkInfo {
+                text: node_text(node),
+                url: link.url.to_string(),
+                line,
+            }),
+            NodeValue::Image(link) => summary.images.push(ImageInfo {
+                alt: node_text(node),
+                url: link.url.to_string(),
+                line,
+            }),
+            // `<img>` tags in raw HTML are shown too
+            NodeValue::HtmlBlock(html) => summary.images.extend(html_images(&html.literal, line)),
+            NodeValue::HtmlInline(html) => summary.images.extend(html_images(html, line)),
+            NodeValue::CodeBlock(code_block) => summary.code_blocks.push(CodeBlockInfo {
+                language: code_block
+                    .info
+                    .split_whitespace()
+                    .next()
+                    .map(str::to_string),
+                line,
+                line_count: code_block.literal.lines().count(),
+            }),
+            NodeValue::Text(text) => summary.word_count += text.split_whitespace().count(),
+            NodeValue::Code(code) => summary.word_count += code.literal.split_whitespace().count(),
+            _ => {}
+        }
+    }
+    summary
+}
+
+fn html_images(html: &str, line: usize) -> impl Iterator<Item = ImageInfo> {
+    raw_html::image_sources(html)
+        .into_iter()
+        .map(move |(url, alt)| ImageInfo { alt, url, line })
+}
+
+impl fmt::Display for DocumentSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Words: {}", self.word_count)?;
+        writeln!(f, "Headings: {}", self.headings.len())?;
+        for heading in &self.headings {
+            writeln!(
+                f,
+                "  {:>4}: {}{} {}",
+                heading.line,
+                "  ".repeat(heading.level.saturating_sub(1) as usize),
+                "#".repeat(heading.level as usize),
+                heading.text
+            )?;
+        }
+        writeln!(f, "Links: {}", self.links.len())?;
+        for link in &self.links {
+            writeln!(f, "  {:>4}: {} -> {}", link.line, link.text, link.url)?;
+        }
+        writeln!(f, "Images: {}", self.images.len())?;
+        for image in &self.images {
+            writeln!(f, "  {:>4}: {} -> {}", image.line, image.alt, image.url)?;
+        }
+        writeln!(f, "Code blocks: {}", self.code_blocks.len())?;
+        for code_block in &self.code_blocks {
+            writeln!(
+                f,
+                "  {:>4}: {} ({} lines)",
+                code_block.line,
+                code_block.language.as_deref().unwrap_or("plain"),
+                code_block.line_count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# Intro `v2`\n\nSee [docs](docs.md) and ![logo](img/logo.png).\n\n```rust\nfn main() {}\n```\n\n## Next Steps!\n";
+
+    #[test]
+    fn collects_structure_with_lines() {
+        let summary = inspect_markdown(DOC, None);
+        assert_eq!(
+            summary.headings,
+            vec![
+                HeadingInfo {
+                    level: 1,
+                    text: "Intro v2".to_string(),
+                    slug: "intro-v2".to_string(),
+                    line: 1,
+                },
+                HeadingInfo {
+                    level: 2,
+                    text: "Next Steps!".to_string(),
+                    slug: "next-steps".to_string(),
+                    line: 9,
+                },
+            ]
+        );
+        assert_eq!(summary.links[0].url, "docs.md");
+        assert_eq!(summary.images[0].alt, "logo");
+        assert_eq!(summary.images[0].line, 3);
+        assert_eq!(
+            summary.code_blocks,
+            vec![CodeBlockInfo {
+                language: Some("rust".to_string()),
+                line: 5,
+                line_count: 1,
+            }]
+        );
+        // "Intro v2", "See docs and logo .", "Next Steps!"
+        assert_eq!(summary.word_count, 9);
+    }
+
+    #[test]
+    fn finds_headings_by_text_or_slug() {
+        let summary = inspect_markdown(DOC, None);
+        let line = |query| find_heading(&summary.headings, query).map(|heading| heading.line);
+        assert_eq!(line("next steps!"), Some(9));
+        assert_eq!(line("#next-steps"), Some(9));
+        assert_eq!(line("intro-v2"), Some(1));
+        assert_eq!(line("missing"), None);
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let json = serde_json::to_value(inspect_markdown("# Title", None)).unwrap();
+        assert_eq!(json["headings"][0]["slug"], "title");
+        assert_eq!(json["word_count"], 1);
+    }
+}
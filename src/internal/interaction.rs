@@ -0,0 +1,215 @@
+//! Hover, pressed, focused and disabled styles of clickable elements
+//!
+//! Links, copy buttons, TOC entries, the status bar and the TOC toggle used
+//! to set their own cursor and hover colors, so some had no hover color, none
+//! showed a press and focus looked different from panel to panel.
+//! [`Interactive::interactive`] styles them all from the theme: the element
+//! says what kind of control it is ([`Affordance`]) and which of the states
+//! gpui cannot track it is in ([`ClickState`]), and [`InteractionStyle`]
+//! picks the colors.
+
+use gpui::{Rgba, StatefulInteractiveElement, StyleRefinement, Styled};
+
+use crate::internal::style::{DISABLED_OPACITY, HOVER_FADE_OPACITY, PRESSED_FADE_OPACITY};
+use crate::internal::theme::ThemeColors;
+
+/// What kind of control a clickable element is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Affordance {
+    /// Text link: the text turns the hover link color
+    Link,
+    /// Row, list entry, menu item, tab or outlined button: the background
+    /// is tinted
+    Row,
+    /// Button with a fill of its own, e.g. the code block copy button:
+    /// the button fades
+    Button,
+    /// The TOC toggle, which has a theme color for hovering
+    Toggle,
+}
+
+/// States of a clickable element that gpui does not track itself
+///
+/// Hover and press come from the mouse; selection, keyboard focus and
+/// whether the element can be used at all are up to the viewer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClickState {
+    /// The current entry of a list, e.g. the current TOC section
+    pub selected: bool,
+    /// Has the keyboard focus
+    pub focused: bool,
+    /// Shown but not usable
+    pub disabled: bool,
+}
+
+impl ClickState {
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+}
+
+/// A change applied while the element is hovered or pressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tint {
+    Background(Rgba),
+    Text(Rgba),
+    Fade(f32),
+}
+
+impl Tint {
+    fn apply(self, style: StyleRefinement) -> StyleRefinement {
+        match self {
+            Tint::Background(color) => style.bg(color),
+            Tint::Text(color) => style.text_color(color),
+            Tint::Fade(opacity) => style.opacity(opacity),
+        }
+    }
+}
+
+/// Colors of a clickable element in its current state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InteractionStyle {
+    /// Background at rest, for selected and focused elements
+    pub background: Option<Rgba>,
+    pub hover: Option<Tint>,
+    pub pressed: Option<Tint>,
+    /// Whether the mouse shows a pointing hand over the element
+    pub pointer: bool,
+    pub opacity: f32,
+}
+
+impl InteractionStyle {
+    /// Style of an `affordance` in `state`, with colors from the theme
+    ///
+    /// Focus is drawn over selection, so the keyboard position stays
+    /// visible on the current entry. Disabled elements keep neither and do
+    /// not react to the mouse.
+    pub fn new(affordance: Affordance, state: ClickState, colors: &ThemeColors) -> Self {
+        if state.disabled {
+            return Self {
+                background: None,
+                hover: None,
+                pressed: None,
+                pointer: false,
+                opacity: DISABLED_OPACITY,
+            };
+        }
+        let (hover, pressed) = match affordance {
+            Affordance::Link => (
+                Tint::Text(colors.hover_link_color),
+                Tint::Fade(PRESSED_FADE_OPACITY),
+            ),
+            Affordance::Row => (
+                Tint::Background(colors.toc_hover_color),
+                Tint::Background(colors.toc_active_color),
+            ),
+            Affordance::Button => (
+                Tint::Fade(HOVER_FADE_OPACITY),
+                Tint::Fade(PRESSED_FADE_OPACITY),
+            ),
+            Affordance::Toggle => (
+                Tint::Background(colors.toc_toggle_hover_color),
+                Tint::Fade(HOVER_FADE_OPACITY),
+            ),
+        };
+        let background = match (state.focused, state.selected) {
+            (true, _) => Some(colors.focus_bg_color),
+            (false, true) => Some(colors.toc_active_color),
+            (false, false) => None,
+        };
+        Self {
+            background,
+            hover: Some(hover),
+            pressed: Some(pressed),
+            pointer: true,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Interaction styling for clickable gpui elements
+///
+/// Pressed styles need the element's id, so `.id(..)` comes first.
+pub trait Interactive: StatefulInteractiveElement + Styled {
+    /// Cursor, state background, hover and pressed styles of an
+    /// `affordance` in `state`
+    fn interactive(self, affordance: Affordance, state: ClickState, colors: &ThemeColors) -> Self {
+        let style = InteractionStyle::new(affordance, state, colors);
+        let mut element = match style.pointer {
+            true => self.cursor_pointer(),
+            false => self.cursor_default().opacity(style.opacity),
+        };
+        if let Some(color) = style.background {
+            element = element.bg(color);
+        }
+        if let Some(tint) = style.hover {
+            element = element.hover(move |style| tint.apply(style));
+        }
+        if let Some(tint) = style.pressed {
+            element = element.active(move |style| tint.apply(style));
+        }
+        element
+    }
+}
+
+impl<E: StatefulInteractiveElement + Styled> Interactive for E {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_is_drawn_over_selection() {
+        let colors = ThemeColors::default();
+        let style = |state| InteractionStyle::new(Affordance::Row, state, &colors).background;
+        assert_eq!(style(ClickState::default()), None);
+        assert_eq!(
+            style(ClickState::default().selected(true)),
+            Some(colors.toc_active_color)
+        );
+        assert_eq!(
+            style(ClickState::default().selected(true).focused(true)),
+            Some(colors.focus_bg_color)
+        );
+    }
+
+    #[test]
+    fn affordances_take_their_colors_from_the_theme() {
+        let colors = ThemeColors::default();
+        let style = |affordance| InteractionStyle::new(affordance, ClickState::default(), &colors);
+        assert_eq!(
+            style(Affordance::Link).hover,
+            Some(Tint::Text(colors.hover_link_color))
+        );
+        assert_eq!(
+            style(Affordance::Row).pressed,
+            Some(Tint::Background(colors.toc_active_color))
+        );
+        assert_eq!(
+            style(Affordance::Toggle).hover,
+            Some(Tint::Background(colors.toc_toggle_hover_color))
+        );
+        assert!(style(Affordance::Button).pointer);
+    }
+
+    #[test]
+    fn disabled_elements_ignore_the_mouse() {
+        let colors = ThemeColors::default();
+        let state = ClickState {
+            disabled: true,
+            ..ClickState::default().focused(true)
+        };
+        let style = InteractionStyle::new(Affordance::Row, state, &colors);
+        assert_eq!(style.background, None);
+        assert_eq!(style.hover, None);
+        assert_eq!(style.pressed, None);
+        assert!(!style.pointer);
+        assert_eq!(style.opacity, DISABLED_OPACITY);
+    }
+}
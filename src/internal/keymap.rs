@@ -0,0 +1,232 @@
+//! Keyboard shortcuts as shown in the help overlay
+//!
+//! Shortcuts are described once here with abstract modifiers and rendered
+//! with the names the current platform uses, so the help overlay reads
+//! "Cmd + F" on macOS and "Ctrl + F" elsewhere. [`Modifier::Primary`]
+//! matches the `platform || control` checks in `events.rs`, while
+//! [`Modifier::Platform`] is a shortcut only the platform key triggers
+//! (Super on Linux, the Windows key on Windows).
+
+use Modifier::{Alt, Control, Platform as Plat, Primary, Shift};
+
+/// Operating system whose key names are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    MacOs,
+    Linux,
+    Windows,
+}
+
+impl Platform {
+    /// The platform the viewer was built for
+    pub fn current() -> Self {
+        match (cfg!(target_os = "macos"), cfg!(target_os = "windows")) {
+            (true, _) => Platform::MacOs,
+            (false, true) => Platform::Windows,
+            (false, false) => Platform::Linux,
+        }
+    }
+}
+
+/// A modifier key of a shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    /// Cmd on macOS, Ctrl elsewhere (either is accepted)
+    Primary,
+    /// Cmd on macOS, Super or the Windows key elsewhere
+    Platform,
+    Control,
+    Shift,
+    Alt,
+}
+
+impl Modifier {
+    /// Name of the key on `platform`
+    pub fn name(self, platform: Platform) -> &'static str {
+        match (self, platform) {
+            (Modifier::Primary | Modifier::Platform, Platform::MacOs) => "Cmd",
+            (Modifier::Primary | Modifier::Control, _) => "Ctrl",
+            (Modifier::Platform, Platform::Linux) => "Super",
+            (Modifier::Platform, Platform::Windows) => "Win",
+            (Modifier::Shift, _) => "Shift",
+            (Modifier::Alt, Platform::MacOs) => "Option",
+            (Modifier::Alt, _) => "Alt",
+        }
+    }
+}
+
+/// A shortcut and what it does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub modifiers: &'static [Modifier],
+    /// Key (or keys, e.g. "] / [") pressed with the modifiers
+    pub key: &'static str,
+    pub description: &'static str,
+}
+
+impl Binding {
+    /// The shortcut as written on `platform`, e.g. "Ctrl + Shift + F"
+    pub fn label(&self, platform: Platform) -> String {
+        self.modifiers
+            .iter()
+            .map(|modifier| modifier.name(platform))
+            .chain(std::iter::once(self.key))
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+/// A page of the help overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HelpPage {
+    pub title: &'static str,
+    pub bindings: &'static [Binding],
+}
+
+const fn bind(
+    modifiers: &'static [Modifier],
+    key: &'static str,
+    description: &'static str,
+) -> Binding {
+    Binding {
+        modifiers,
+        key,
+        description,
+    }
+}
+
+/// Pages of the help overlay, flipped with Left/Right
+pub const HELP_PAGES: &[HelpPage] = &[
+    HelpPage {
+        title: "Files & Tabs",
+        bindings: &[
+            bind(&[Plat], "H", "Toggle Help"),
+            bind(&[Primary], "O", "Open File..."),
+            bind(&[Plat], "K", "Go to File"),
+            bind(&[Plat, Shift], "O", "Open Recent"),
+            bind(&[Plat], "R", "Reload from Disk"),
+            bind(&[Plat], "Right / Left", "Forward / Back Through Links"),
+            bind(&[Plat], "] / [", "Next / Previous Queued File"),
+            bind(&[Plat], "1..9", "Switch Tab"),
+            bind(&[Plat], "W", "Close Tab"),
+            bind(&[Primary, Alt], "T", "Reopen Closed Tab"),
+            bind(&[Alt], "Right / Left", "Next / Previous File in Folder"),
+            bind(&[Primary, Shift], "Z", "Toggle File Tree (Folders)"),
+            bind(&[Plat], "Q", "Quit"),
+            bind(&[], "Esc", "Close Overlay / Search"),
+        ],
+    },
+    HelpPage {
+        title: "Search & Bookmarks",
+        bindings: &[
+            bind(&[Primary], "F", "Search (Up/Down for History)"),
+            bind(
+                &[Alt],
+                "R / W / C",
+                "Regex / Whole Word / Match Case (in Search)",
+            ),
+            bind(&[], "Enter / Shift + Enter", "Next / Previous Match"),
+            bind(&[], "Esc (in Search)", "Close Bar, Keep Highlights"),
+            bind(&[], "n / N", "Next / Previous Match (Bar Closed)"),
+            bind(
+                &[Primary, Shift],
+                "F",
+                "Show Only Matching Blocks (again: ±1 Context)",
+            ),
+            bind(
+                &[Primary, Shift],
+                "F",
+                "Search All Files (Opened on a Folder, No Query)",
+            ),
+            bind(&[Primary, Shift], "H", "Clear Search History"),
+            bind(&[Primary], "G", "Go to Line"),
+            bind(&[Primary], "D", "Toggle Bookmark"),
+            bind(&[Primary, Shift], "D", "Bookmark All Matches"),
+            bind(&[Primary, Shift], "B", "View Bookmarks"),
+        ],
+    },
+    HelpPage {
+        title: "Export & View",
+        bindings: &[
+            bind(&[Primary], "E", "Export to PDF"),
+            bind(&[Primary, Shift], "E", "Export to HTML"),
+            bind(&[Primary, Alt], "E", "Export to EPUB"),
+            bind(&[Primary, Shift], "R", "Repeat Last Export"),
+            bind(&[Primary, Shift], "L", "Recent Exports"),
+            bind(&[Plat], "P", "Print"),
+            bind(&[Plat], "Z", "Toggle TOC"),
+            bind(
+                &[],
+                "Right-Click (TOC)",
+                "Link / Export / Collapse / Mark Section",
+            ),
+            bind(
+                &[],
+                "Click # (Heading)",
+                "Copy Anchor (+Shift: file.md#anchor)",
+            ),
+            bind(&[Primary, Shift], "T", "Toggle Theme"),
+            bind(&[Primary, Shift], "N", "Cycle Theme Family"),
+            bind(&[Primary, Shift], "M", "Toggle Plain Text / Markdown"),
+            bind(&[Primary, Shift], "V", "Toggle Source View (Split Pane)"),
+            bind(&[Primary, Shift], "I", "Broken Images / Missing Alt Text"),
+            bind(&[Alt], "Click", "Show Block in Source View"),
+            bind(&[], "Click Image", "Lightbox (Wheel: Zoom, Drag: Pan)"),
+            bind(&[Plat], "+ / -", "Zoom In / Out"),
+        ],
+    },
+    HelpPage {
+        title: "Navigation",
+        bindings: &[
+            bind(&[], "j / k", "Scroll Down / Up"),
+            bind(&[], "Up / Down", "Scroll by a Line"),
+            bind(&[Control], "D / U", "Half-Page Down / Up"),
+            bind(&[], "PageUp / PageDown", "Page Scroll"),
+            bind(&[], "Space (+Shift)", "Page Scroll"),
+            bind(&[], "g", "Scroll to Top"),
+            bind(&[], "G (Shift + g)", "Scroll to Bottom"),
+            bind(&[Plat], "T / B", "Scroll to Top / Bottom"),
+            bind(&[], "zz", "Center View"),
+            bind(&[], "m + <char>", "Set Mark"),
+            bind(&[], "' + <char>", "Jump to Mark"),
+            bind(&[], "Tab / Shift + Tab", "Focus Links and Controls"),
+        ],
+    },
+];
+
+/// Help page shown after pressing Right (`forward`) or Left on `page`
+pub fn turn_help_page(page: usize, forward: bool) -> usize {
+    match forward {
+        true => (page + 1).min(HELP_PAGES.len() - 1),
+        false => page.saturating_sub(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_use_the_platform_key_names() {
+        let search = bind(&[Primary, Shift], "F", "Filter");
+        assert_eq!(search.label(Platform::MacOs), "Cmd + Shift + F");
+        assert_eq!(search.label(Platform::Linux), "Ctrl + Shift + F");
+
+        let zoom = bind(&[Plat], "+ / -", "Zoom");
+        assert_eq!(zoom.label(Platform::Windows), "Win + + / -");
+        assert_eq!(zoom.label(Platform::Linux), "Super + + / -");
+
+        let epub = bind(&[Primary, Alt], "E", "EPUB");
+        assert_eq!(epub.label(Platform::MacOs), "Cmd + Option + E");
+        assert_eq!(bind(&[], "zz", "Center").label(Platform::MacOs), "zz");
+    }
+
+    #[test]
+    fn help_pages_turn_within_bounds() {
+        let last = HELP_PAGES.len() - 1;
+        assert_eq!(turn_help_page(0, false), 0);
+        assert_eq!(turn_help_page(0, true), 1);
+        assert_eq!(turn_help_page(last, true), last);
+        assert!(HELP_PAGES.iter().all(|page| !page.bindings.is_empty()));
+    }
+}
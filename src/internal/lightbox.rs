@@ -0,0 +1,143 @@
+//! Full-window view of one image
+//!
+//! Images in the document are shrunk to the text width, which makes
+//! diagrams and screenshots hard to read. Clicking an image opens it in a
+//! lightbox over the whole window at its native size. The scroll wheel
+//! zooms around the mouse, dragging pans and Esc closes it. [`Lightbox`]
+//! keeps the zoom and pan; `ui::render_lightbox` draws it.
+
+/// Smallest zoom, a tenth of the native size
+pub const MIN_ZOOM: f32 = 0.1;
+
+/// Largest zoom, eight times the native size
+pub const MAX_ZOOM: f32 = 8.0;
+
+/// Zoom change per pixel of scroll wheel movement; 100 pixels zoom by
+/// about 1.65×
+const ZOOM_PER_WHEEL_PIXEL: f32 = 0.005;
+
+/// Zoom and pan of the image shown in the lightbox
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lightbox {
+    /// Image cache key of the image
+    pub key: String,
+    /// Alt text, shown as the caption
+    pub alt: String,
+    /// Scale relative to the native size
+    pub zoom: f32,
+    /// Offset of the image center from the window center, in pixels
+    pub offset: (f32, f32),
+    /// Mouse position the drag in progress last moved to
+    drag_from: Option<(f32, f32)>,
+}
+
+impl Lightbox {
+    /// Show the image cached under `key` at its native size, centered
+    pub fn new(key: impl Into<String>, alt: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            alt: alt.into(),
+            zoom: 1.0,
+            offset: (0.0, 0.0),
+            drag_from: None,
+        }
+    }
+
+    /// Zoom for a scroll wheel movement of `delta` pixels (positive zooms
+    /// in), keeping the point under the mouse in place
+    ///
+    /// `anchor` is the mouse position relative to the window center.
+    pub fn zoom_by(&mut self, delta: f32, anchor: (f32, f32)) {
+        if !delta.is_finite() {
+            return;
+        }
+        let zoom = (self.zoom * (delta * ZOOM_PER_WHEEL_PIXEL).exp()).clamp(MIN_ZOOM, MAX_ZOOM);
+        let factor = zoom / self.zoom;
+        self.offset = (
+            anchor.0 - (anchor.0 - self.offset.0) * factor,
+            anchor.1 - (anchor.1 - self.offset.1) * factor,
+        );
+        self.zoom = zoom;
+    }
+
+    /// Start panning with the mouse pressed at `position`
+    pub fn start_drag(&mut self, position: (f32, f32)) {
+        self.drag_from = Some(position);
+    }
+
+    /// Pan by the mouse movement to `position` while a drag is in progress
+    pub fn drag_to(&mut self, position: (f32, f32)) {
+        if let Some(from) = self.drag_from.replace(position) {
+            self.offset.0 += position.0 - from.0;
+            self.offset.1 += position.1 - from.1;
+        }
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag_from = None;
+    }
+
+    /// Back to the native size, centered
+    pub fn reset(&mut self) {
+        self.zoom = 1.0;
+        self.offset = (0.0, 0.0);
+    }
+
+    /// Size of an image of `native` size at the current zoom
+    pub fn scaled(&self, native: (f32, f32)) -> (f32, f32) {
+        (native.0 * self.zoom, native.1 * self.zoom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_keeps_the_point_under_the_mouse() {
+        let mut lightbox = Lightbox::new("a.png", "");
+        let anchor = (100.0, -50.0);
+        // Image point under the mouse, relative to the image center
+        let under_mouse = |lightbox: &Lightbox| {
+            (
+                (anchor.0 - lightbox.offset.0) / lightbox.zoom,
+                (anchor.1 - lightbox.offset.1) / lightbox.zoom,
+            )
+        };
+        let before = under_mouse(&lightbox);
+        lightbox.zoom_by(120.0, anchor);
+        assert!(lightbox.zoom > 1.0);
+        let after = under_mouse(&lightbox);
+        assert!((before.0 - after.0).abs() < 1e-3 && (before.1 - after.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn zoom_stays_within_limits() {
+        let mut lightbox = Lightbox::new("a.png", "");
+        lightbox.zoom_by(1e6, (0.0, 0.0));
+        assert_eq!(lightbox.zoom, MAX_ZOOM);
+        lightbox.zoom_by(-1e6, (0.0, 0.0));
+        assert_eq!(lightbox.zoom, MIN_ZOOM);
+        lightbox.zoom_by(f32::NAN, (0.0, 0.0));
+        assert_eq!(lightbox.zoom, MIN_ZOOM);
+    }
+
+    #[test]
+    fn dragging_pans_by_the_mouse_movement() {
+        let mut lightbox = Lightbox::new("a.png", "");
+        lightbox.drag_to((50.0, 50.0));
+        assert_eq!(lightbox.offset, (0.0, 0.0));
+
+        lightbox.start_drag((10.0, 10.0));
+        lightbox.drag_to((30.0, 5.0));
+        lightbox.drag_to((40.0, 0.0));
+        assert_eq!(lightbox.offset, (30.0, -10.0));
+
+        lightbox.end_drag();
+        lightbox.drag_to((100.0, 100.0));
+        assert_eq!(lightbox.offset, (30.0, -10.0));
+
+        lightbox.reset();
+        assert_eq!((lightbox.zoom, lightbox.offset), (1.0, (0.0, 0.0)));
+    }
+}
@@ -0,0 +1,188 @@
+//! Line mapping between two versions of a document
+//!
+//! When the file changes on disk, marks still point at line numbers from the
+//! previous version. This module diffs the old and new content line by line
+//! and maps old line numbers to their new positions so marks can follow the
+//! text they were attached to. Bookmarks carry their own content anchors
+//! (see `bookmark_anchor`).
+
+/// Upper bound on the diff table size (old lines × new lines) for the changed
+/// region; larger edits fall back to positional mapping around common
+/// prefix/suffix to keep reloads responsive.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Mapping from lines of an old document to lines of a new one
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineMap {
+    /// For each old line (0-based), the matching new line (0-based), if unchanged
+    matches: Vec<Option<usize>>,
+    /// Number of lines in the new document
+    new_len: usize,
+}
+
+impl LineMap {
+    /// Diff `old` against `new` and record which old lines survived where
+    pub fn between(old: &str, new: &str) -> Self {
+        let old_lines: Vec<&str> = old.lines().collect();
+        let new_lines: Vec<&str> = new.lines().collect();
+        let mut matches = vec![None; old_lines.len()];
+
+        // Unchanged lines at the start and end are the common case for edits
+        let prefix = old_lines
+            .iter()
+            .zip(&new_lines)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = old_lines[prefix..]
+            .iter()
+            .rev()
+            .zip(new_lines[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        for (i, slot) in matches.iter_mut().enumerate().take(prefix) {
+            *slot = Some(i);
+        }
+        for k in 0..suffix {
+            matches[old_lines.len() - 1 - k] = Some(new_lines.len() - 1 - k);
+        }
+
+        let old_mid = &old_lines[prefix..old_lines.len() - suffix];
+        let new_mid = &new_lines[prefix..new_lines.len() - suffix];
+        if old_mid.len().saturating_mul(new_mid.len()) <= MAX_DIFF_CELLS {
+            for (i, j) in longest_common_subsequence(old_mid, new_mid) {
+                matches[prefix + i] = Some(prefix + j);
+            }
+        }
+
+        Self {
+            matches,
+            new_len: new_lines.len(),
+        }
+    }
+
+    /// Map a 1-based line number from the old document to the new one
+    ///
+    /// Unchanged lines map exactly. Edited or deleted lines keep their offset
+    /// from the nearest unchanged line above them, clamped to the new document.
+    pub fn remap(&self, line: usize) -> usize {
+        let index = line.saturating_sub(1);
+        let mapped = match self.matches.get(index).copied().flatten() {
+            Some(new_index) => new_index,
+            None => {
+                let anchor = self.matches[..index.min(self.matches.len())]
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .find_map(|(old, new)| new.map(|new| (old, new)));
+                match anchor {
+                    Some((old, new)) => new + (index - old),
+                    None => index,
+                }
+            }
+        };
+        (mapped + 1).min(self.new_len.max(1))
+    }
+
+    /// Count how many of the given 1-based lines would change position
+    pub fn moved_count(&self, lines: impl IntoIterator<Item = usize>) -> usize {
+        lines
+            .into_iter()
+            .filter(|&line| self.remap(line) != line)
+            .count()
+    }
+}
+
+/// Toast text offering to remap marks after `moved_marks` of them moved
+pub fn remap_offer(moved_marks: usize) -> String {
+    let moved = match moved_marks {
+        1 => "1 mark".to_string(),
+        n => format!("{} marks", n),
+    };
+    format!("File changed on disk: the lines of {} moved", moved)
+}
+
+/// Index pairs (old, new) of a longest common subsequence of lines
+fn longest_common_subsequence(old: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    // lengths[i][j] = LCS length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_document_maps_identically() {
+        let doc = "a\nb\nc";
+        let map = LineMap::between(doc, doc);
+        assert_eq!(map.remap(1), 1);
+        assert_eq!(map.remap(3), 3);
+        assert_eq!(map.moved_count([1, 2, 3]), 0);
+    }
+
+    #[test]
+    fn inserted_lines_shift_following_lines() {
+        let map = LineMap::between("a\nb\nc\nd", "a\nnew 1\nnew 2\nb\nc\nd");
+        assert_eq!(map.remap(1), 1);
+        assert_eq!(map.remap(2), 4);
+        assert_eq!(map.remap(4), 6);
+        assert_eq!(map.moved_count([1, 2, 4]), 2);
+    }
+
+    #[test]
+    fn deleted_lines_shift_following_lines_up() {
+        let map = LineMap::between("a\nb\nc\nd\ne", "a\nd\ne");
+        assert_eq!(map.remap(4), 2);
+        assert_eq!(map.remap(5), 3);
+        // A deleted line stays just below its nearest surviving neighbour
+        assert_eq!(map.remap(2), 2);
+    }
+
+    #[test]
+    fn edited_line_keeps_its_position() {
+        let map = LineMap::between("a\nb\nc", "a\nB!\nc");
+        assert_eq!(map.remap(2), 2);
+        assert_eq!(map.remap(3), 3);
+    }
+
+    #[test]
+    fn remap_is_clamped_to_new_length() {
+        let map = LineMap::between("a\nb\nc\nd", "x");
+        assert_eq!(map.remap(4), 1);
+        assert_eq!(LineMap::between("a", "").remap(1), 1);
+    }
+
+    #[test]
+    fn remap_offer_counts_the_moved_marks() {
+        assert_eq!(
+            remap_offer(1),
+            "File changed on disk: the lines of 1 mark moved"
+        );
+        assert!(remap_offer(3).contains("3 marks"));
+    }
+}
@@ -0,0 +1,299 @@
+//! Link rewrite rules
+//!
+//! Turns issue shorthands and similar bare text into links, e.g. `JIRA-123`
+//! → `https://jira.example.com/browse/JIRA-123`. Rules come from
+//! `link_rewrites` in `config.ron`: a regex and a URL template that may
+//! refer to capture groups (`$0`, `$1`, `${name}`).
+//!
+//! [`LinkRewritePass`] applies the rules in the transform pipeline, splitting
+//! text nodes into text and link nodes, so every backend renders the links
+//! like ones written in the document. Rules apply to plain text only;
+//! existing links, image descriptions and code spans are left alone. When
+//! several rules match, the earliest match wins, and the first rule wins
+//! ties.
+
+use anyhow::{Context, Result};
+use comrak::Arena;
+use comrak::nodes::{AstNode, NodeLink, NodeValue};
+use regex::Regex;
+use std::sync::{Once, RwLock};
+use tracing::{debug, warn};
+
+use crate::config::LinkRewriteRule;
+use crate::internal::transform::{self, TransformContext, TransformPass};
+
+/// Order of [`LinkRewritePass`]: before plugin hooks, so they see the links
+pub const LINK_REWRITE_PASS_ORDER: i32 = transform::PLUGIN_PASS_ORDER - 100;
+
+/// A piece of text after rewriting
+#[derive(Debug, Clone, PartialEq)]
+enum LinkSegment {
+    Text(String),
+    Link { text: String, url: String },
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    regex: Regex,
+    url: String,
+}
+
+static RULES: RwLock<Vec<CompiledRule>> = RwLock::new(Vec::new());
+
+/// Compile a rule, rejecting patterns that can match empty text
+fn compile(rule: &LinkRewriteRule) -> Result<CompiledRule> {
+    let regex = Regex::new(&rule.pattern)
+        .with_context(|| format!("Invalid link rewrite pattern '{}'", rule.pattern))?;
+    if regex.is_match("") {
+        anyhow::bail!(
+            "Link rewrite pattern '{}' must not match empty text",
+            rule.pattern
+        );
+    }
+    Ok(CompiledRule {
+        regex,
+        url: rule.url.clone(),
+    })
+}
+
+/// Check that every rule compiles (used by config validation)
+pub fn validate(rules: &[LinkRewriteRule]) -> Result<()> {
+    rules.iter().try_for_each(|rule| compile(rule).map(|_| ()))
+}
+
+/// Replace the active rules; invalid rules are logged and skipped
+///
+/// The first call registers [`LinkRewritePass`].
+pub fn set_rules(rules: &[LinkRewriteRule]) {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| transform::register_pass(LINK_REWRITE_PASS_ORDER, LinkRewritePass));
+
+    let compiled = rules
+        .iter()
+        .filter_map(|rule| {
+            compile(rule)
+                .inspect_err(|e| warn!("Skipping link rewrite rule: {:#}", e))
+                .ok()
+        })
+        .collect::<Vec<_>>();
+    debug!("Active link rewrite rules: {}", compiled.len());
+    *RULES.write().unwrap_or_else(|e| e.into_inner()) = compiled;
+}
+
+/// Turns text matching the active rules into links
+pub struct LinkRewritePass;
+
+impl TransformPass for LinkRewritePass {
+    fn name(&self) -> &str {
+        "link-rewrites"
+    }
+
+    fn run<'a>(
+        &self,
+        arena: &'a Arena<'a>,
+        root: &'a AstNode<'a>,
+        _context: &TransformContext,
+    ) -> Result<()> {
+        rewrite_tree(
+            arena,
+            root,
+            &RULES.read().unwrap_or_else(|e| e.into_inner()),
+        );
+        Ok(())
+    }
+}
+
+/// Whether text below `node` is already a link, an image description or code
+fn is_excluded(node: &AstNode) -> bool {
+    matches!(
+        node.data.borrow().value,
+        NodeValue::Link(_) | NodeValue::Image(_) | NodeValue::Code(_)
+    )
+}
+
+/// Split the text nodes below `root` into text and link nodes using `rules`
+fn rewrite_tree<'a>(arena: &'a Arena<'a>, root: &'a AstNode<'a>, rules: &[CompiledRule]) {
+    if rules.is_empty() {
+        return;
+    }
+    let texts: Vec<_> = root
+        .descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::Text(_)))
+        .filter(|node| !node.ancestors().skip(1).any(is_excluded))
+        .collect();
+    for node in texts {
+        let (segments, sourcepos) = {
+            let data = node.data.borrow();
+            let NodeValue::Text(text) = &data.value else {
+                continue;
+            };
+            (rewrite_with(rules, text), data.sourcepos)
+        };
+        if !segments
+            .iter()
+            .any(|segment| matches!(segment, LinkSegment::Link { .. }))
+        {
+            continue;
+        }
+        let new_node = |value: NodeValue| -> &'a AstNode<'a> {
+            let new = arena.alloc(AstNode::from(value));
+            new.data.borrow_mut().sourcepos = sourcepos;
+            new
+        };
+        for segment in segments {
+            let replacement = match segment {
+                LinkSegment::Text(text) => new_node(NodeValue::Text(text.into())),
+                LinkSegment::Link { text, url } => {
+                    let link = new_node(NodeValue::Link(Box::new(NodeLink {
+                        url,
+                        title: String::new(),
+                    })));
+                    link.append(new_node(NodeValue::Text(text.into())));
+                    link
+                }
+            };
+            node.insert_before(replacement);
+        }
+        node.detach();
+    }
+}
+
+fn rewrite_with(rules: &[CompiledRule], text: &str) -> Vec<LinkSegment> {
+    let mut segments = Vec::new();
+    let mut position = 0;
+    loop {
+        let next = rules
+            .iter()
+            .filter_map(|rule| {
+                rule.regex
+                    .captures_at(text, position)
+                    .map(|captures| (rule, captures))
+            })
+            .min_by_key(|(_, captures)| captures.get(0).map_or(usize::MAX, |m| m.start()));
+        let Some((rule, captures)) = next else {
+            break;
+        };
+        let matched = captures.get(0).expect("capture 0 is the whole match");
+
+        if matched.start() > position {
+            segments.push(LinkSegment::Text(
+                text[position..matched.start()].to_string(),
+            ));
+        }
+        let mut url = String::new();
+        captures.expand(&rule.url, &mut url);
+        segments.push(LinkSegment::Link {
+            text: matched.as_str().to_string(),
+            url,
+        });
+        position = matched.end();
+    }
+    if position < text.len() || segments.is_empty() {
+        segments.push(LinkSegment::Text(text[position..].to_string()));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(pairs: &[(&str, &str)]) -> Vec<CompiledRule> {
+        pairs
+            .iter()
+            .map(|(pattern, url)| {
+                compile(&LinkRewriteRule {
+                    pattern: pattern.to_string(),
+                    url: url.to_string(),
+                })
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rewrites_shorthands_with_captures() {
+        let rules = rules(&[
+            (r"\bJIRA-\d+\b", "https://jira.example.com/browse/$0"),
+            (
+                r"\bgh#(?<number>\d+)",
+                "https://github.com/org/repo/issues/${number}",
+            ),
+        ]);
+        assert_eq!(
+            rewrite_with(&rules, "Fixes JIRA-12 and gh#456."),
+            vec![
+                LinkSegment::Text("Fixes ".to_string()),
+                LinkSegment::Link {
+                    text: "JIRA-12".to_string(),
+                    url: "https://jira.example.com/browse/JIRA-12".to_string(),
+                },
+                LinkSegment::Text(" and ".to_string()),
+                LinkSegment::Link {
+                    text: "gh#456".to_string(),
+                    url: "https://github.com/org/repo/issues/456".to_string(),
+                },
+                LinkSegment::Text(".".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_without_matches_is_one_segment() {
+        let rules = rules(&[(r"JIRA-\d+", "https://jira/$0")]);
+        assert_eq!(
+            rewrite_with(&rules, "nothing here"),
+            vec![LinkSegment::Text("nothing here".to_string())]
+        );
+    }
+
+    /// Links in `markdown` after rewriting, as (text, url)
+    fn links_after_rewriting(markdown: &str, rules: &[CompiledRule]) -> Vec<(String, String)> {
+        let arena = Arena::new();
+        let root =
+            comrak::parse_document(&arena, markdown, &crate::internal::render::parse_options());
+        rewrite_tree(&arena, root, rules);
+        root.descendants()
+            .filter_map(|node| match &node.data.borrow().value {
+                NodeValue::Link(link) => {
+                    let text = node
+                        .descendants()
+                        .filter_map(|child| match &child.data.borrow().value {
+                            NodeValue::Text(text) => Some(text.to_string()),
+                            _ => None,
+                        })
+                        .collect();
+                    Some((text, link.url.to_string()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn existing_links_and_code_are_left_alone() {
+        let rules = rules(&[(r"\bJIRA-\d+\b", "https://jira/$0")]);
+        let links = links_after_rewriting(
+            "[JIRA-1](https://example.com) fixes *JIRA-2*, not `JIRA-3` or ![JIRA-4](a.png)\n",
+            &rules,
+        );
+        assert_eq!(
+            links,
+            vec![
+                ("JIRA-1".to_string(), "https://example.com".to_string()),
+                ("JIRA-2".to_string(), "https://jira/JIRA-2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_and_empty_matching_patterns() {
+        let rule = |pattern: &str| LinkRewriteRule {
+            pattern: pattern.to_string(),
+            url: String::new(),
+        };
+        assert!(validate(&[rule("JIRA-(")]).is_err());
+        assert!(validate(&[rule(r"\d*")]).is_err());
+        assert!(validate(&[rule(r"\d+")]).is_ok());
+    }
+}
@@ -0,0 +1,196 @@
+//! Documentation lint checks
+//!
+//! `markdown_viewer lint <files>` runs these checks and exits non-zero when
+//! any issue is found, so it can gate CI:
+//! - relative links whose target file does not exist, and `#anchor` links to
+//!   headings that are not in the document
+//! - local images whose file does not exist
+//! - headings that produce the same anchor slug
+//! - heading levels that skip a level (e.g. `#` followed by `###`)
+//!
+//! The checks work on the [`inspect`](crate::internal::inspect) summary, so
+//! they see the document exactly as the renderers do. [`lint_theme`] also
+//! checks the configured theme for unreadable color pairs.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::internal::contrast::{ContrastIssue, check_theme};
+use crate::internal::file_handling::resolve_image_path;
+use crate::internal::inspect::{DocumentSummary, inspect_markdown};
+use crate::internal::style::get_theme_colors;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    BrokenLink,
+    MissingImage,
+    DuplicateSlug,
+    HeadingLevelSkip,
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintKind::BrokenLink => "broken-link",
+            LintKind::MissingImage => "missing-image",
+            LintKind::DuplicateSlug => "duplicate-slug",
+            LintKind::HeadingLevelSkip => "heading-level-skip",
+        })
+    }
+}
+
+/// A problem found in a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    /// 1-based source line
+    pub line: usize,
+    pub kind: LintKind,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.line, self.kind, self.message)
+    }
+}
+
+/// Whether `url` points outside the local file system (`https:`, `mailto:`, ...)
+fn is_external(url: &str) -> bool {
+    url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Lint the markdown file at `path` with the given content
+pub fn lint_markdown(content: &str, path: &Path) -> Vec<LintIssue> {
+    lint_summary(&inspect_markdown(content, Some(path)), path)
+}
+
+/// Lint an already inspected document that was loaded from `path`
+pub fn lint_summary(summary: &DocumentSummary, path: &Path) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let slugs: HashSet<&str> = summary
+        .headings
+        .iter()
+        .map(|heading| heading.slug.as_str())
+        .collect();
+
+    for link in summary.links.iter().filter(|link| !is_external(&link.url)) {
+        let (target, anchor) = link.url.split_once('#').unwrap_or((&link.url, ""));
+        let message = match target {
+            "" if !anchor.is_empty() && !slugs.contains(anchor) => {
+                Some(format!("no heading for anchor '#{}'", anchor))
+            }
+            "" => None,
+            _ => {
+                let resolved = resolve_image_path(target, path);
+                match Path::new(&resolved).exists() {
+                    true => None,
+                    false => Some(format!("'{}' does not exist", target)),
+                }
+            }
+        };
+        if let Some(message) = message {
+            issues.push(LintIssue {
+                line: link.line,
+                kind: LintKind::BrokenLink,
+                message,
+            });
+        }
+    }
+
+    for image in summary
+        .images
+        .iter()
+        .filter(|image| !is_external(&image.url))
+    {
+        if !Path::new(&resolve_image_path(&image.url, path)).exists() {
+            issues.push(LintIssue {
+                line: image.line,
+                kind: LintKind::MissingImage,
+                message: format!("'{}' does not exist", image.url),
+            });
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut previous_level = None;
+    for heading in &summary.headings {
+        if !seen.insert(heading.slug.as_str()) {
+            issues.push(LintIssue {
+                line: heading.line,
+                kind: LintKind::DuplicateSlug,
+                message: format!("'{}' repeats anchor '#{}'", heading.text, heading.slug),
+            });
+        }
+        if let Some(previous) = previous_level
+            && heading.level > previous + 1
+        {
+            issues.push(LintIssue {
+                line: heading.line,
+                kind: LintKind::HeadingLevelSkip,
+                message: format!("level {} heading follows level {}", heading.level, previous),
+            });
+        }
+        previous_level = Some(heading.level);
+    }
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// Contrast issues of the theme registered under `name`, or of the default
+/// theme it falls back to
+pub fn lint_theme(name: &str) -> Vec<ContrastIssue> {
+    check_theme(get_theme_colors(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_each_kind_with_lines() {
+        let dir = std::env::temp_dir().join("mdv_lint_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("exists.md"), "").unwrap();
+        let doc = dir.join("doc.md");
+
+        let content = "# Title\n\n\
+            [ok](exists.md) [gone](missing.md) [web](https://example.com) [top](#title) [bad](#nope)\n\n\
+            ![pic](missing.png)\n\n\
+            ### Deep\n\n\
+            ## Title\n";
+        let issues = lint_markdown(content, &doc);
+        std::fs::remove_dir_all(&dir).ok();
+
+        let kinds: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.line, issue.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (3, LintKind::BrokenLink),
+                (3, LintKind::BrokenLink),
+                (5, LintKind::MissingImage),
+                (7, LintKind::HeadingLevelSkip),
+                (9, LintKind::DuplicateSlug),
+            ]
+        );
+        assert_eq!(issues[0].message, "'missing.md' does not exist");
+        assert_eq!(issues[1].message, "no heading for anchor '#nope'");
+    }
+
+    #[test]
+    fn external_urls_are_not_checked() {
+        assert!(is_external("https://example.com"));
+        assert!(is_external("mailto:someone@example.com"));
+        assert!(!is_external("docs/guide.md"));
+        assert!(!is_external("#anchor"));
+    }
+}
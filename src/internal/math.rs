@@ -0,0 +1,381 @@
+//! TeX math formulas laid out as Unicode glyph runs
+//!
+//! `$...$` and `$$...$$` formulas (comrak's `math_dollars` extension) are
+//! parsed into a small tree: runs of text with TeX commands replaced by their
+//! Unicode symbols (`\alpha` → `α`, `\leq` → `≤`), superscripts, subscripts,
+//! fractions and square roots. The GUI renderer draws the tree with nested
+//! text elements; [`to_plain`] flattens it for the terminal backend.
+//!
+//! This covers the notation common in technical docs, not full TeX: unknown
+//! commands are shown as written, and `\left`/`\right` sizing is ignored.
+
+/// A piece of a parsed formula
+#[derive(Debug, Clone, PartialEq)]
+pub enum MathNode {
+    /// Letters, digits, operators and symbols
+    Text(String),
+    Superscript(Vec<MathNode>),
+    Subscript(Vec<MathNode>),
+    Fraction(Vec<MathNode>, Vec<MathNode>),
+    Sqrt(Vec<MathNode>),
+}
+
+/// Unicode symbol for a TeX command name (without the backslash)
+fn symbol(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" | "vartheta" => "θ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" | "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "sum" => "∑",
+        "prod" => "∏",
+        "int" => "∫",
+        "oint" => "∮",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "infty" => "∞",
+        "cdot" => "·",
+        "times" => "×",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "propto" => "∝",
+        "to" | "rightarrow" => "→",
+        "leftarrow" => "←",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "in" => "∈",
+        "notin" => "∉",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "cup" => "∪",
+        "cap" => "∩",
+        "emptyset" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "circ" => "∘",
+        "deg" => "°",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "{" => "{",
+        "}" => "}",
+        "%" => "%",
+        "$" => "$",
+        "_" => "_",
+        "," | ":" | ";" | " " => " ",
+        "quad" => "  ",
+        "qquad" => "    ",
+        "!" => "",
+        _ => return None,
+    })
+}
+
+/// Commands whose argument is shown as-is, e.g. `\text{if }`
+const TEXT_COMMANDS: &[&str] = &["text", "mathrm", "textrm", "operatorname"];
+
+/// Commands that only change the font of their argument
+const STYLE_COMMANDS: &[&str] = &["mathbf", "mathit", "mathsf", "mathtt", "boldsymbol"];
+
+/// Sizing commands that are dropped
+const IGNORED_COMMANDS: &[&str] = &["left", "right", "big", "Big", "displaystyle"];
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// Parse until the end of input or the `}` closing the current group
+    fn parse_sequence(&mut self) -> Vec<MathNode> {
+        let mut nodes = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '}' => {
+                    self.chars.next();
+                    break;
+                }
+                '^' | '_' => {
+                    self.chars.next();
+                    let argument = self.parse_argument();
+                    nodes.push(match c {
+                        '^' => MathNode::Superscript(argument),
+                        _ => MathNode::Subscript(argument),
+                    });
+                }
+                _ => nodes.extend(self.parse_atom()),
+            }
+        }
+        merge_text(nodes)
+    }
+
+    /// A `{group}`, a command or a single character
+    fn parse_argument(&mut self) -> Vec<MathNode> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match self.chars.peek() {
+            Some('{') => {
+                self.chars.next();
+                self.parse_sequence()
+            }
+            _ => self.parse_atom(),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Vec<MathNode> {
+        match self.chars.next() {
+            Some('{') => self.parse_sequence(),
+            Some('\\') => self.parse_command(),
+            Some(c) if c.is_whitespace() => {
+                while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+                vec![MathNode::Text(" ".to_string())]
+            }
+            Some(c) => vec![MathNode::Text(c.to_string())],
+            None => Vec::new(),
+        }
+    }
+
+    fn parse_command(&mut self) -> Vec<MathNode> {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+        }
+        if name.is_empty()
+            && let Some(c) = self.chars.next()
+        {
+            name.push(c);
+        }
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                vec![MathNode::Fraction(numerator, denominator)]
+            }
+            "sqrt" => vec![MathNode::Sqrt(self.parse_argument())],
+            name if TEXT_COMMANDS.contains(&name) => vec![MathNode::Text(self.raw_group())],
+            name if STYLE_COMMANDS.contains(&name) => self.parse_argument(),
+            name if IGNORED_COMMANDS.contains(&name) => Vec::new(),
+            name => vec![MathNode::Text(
+                symbol(name)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("\\{}", name)),
+            )],
+        }
+    }
+
+    /// The literal text of a `{group}` argument
+    fn raw_group(&mut self) -> String {
+        if self.chars.next_if_eq(&'{').is_none() {
+            return String::new();
+        }
+        let mut depth = 1;
+        let mut text = String::new();
+        for c in self.chars.by_ref() {
+            depth += match c {
+                '{' => 1,
+                '}' => -1,
+                _ => 0,
+            };
+            if depth == 0 {
+                break;
+            }
+            text.push(c);
+        }
+        text
+    }
+}
+
+/// Join neighbouring text runs so they render as one glyph run, collapsing
+/// runs of spaces
+fn merge_text(nodes: Vec<MathNode>) -> Vec<MathNode> {
+    let mut merged: Vec<MathNode> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        match (merged.last_mut(), node) {
+            (Some(MathNode::Text(previous)), MathNode::Text(text)) => {
+                match previous.ends_with(' ') {
+                    true => previous.push_str(text.trim_start_matches(' ')),
+                    false => previous.push_str(&text),
+                }
+            }
+            (_, node) => merged.push(node),
+        }
+    }
+    merged
+}
+
+/// Parse a TeX formula (without the `$` delimiters)
+pub fn parse(tex: &str) -> Vec<MathNode> {
+    let mut parser = Parser {
+        chars: tex.trim().chars().peekable(),
+    };
+    let mut nodes = Vec::new();
+    // A stray `}` ends a sequence early; keep going so nothing is dropped
+    while parser.chars.peek().is_some() {
+        nodes.extend(parser.parse_sequence());
+    }
+    merge_text(nodes)
+}
+
+/// The formula on one line, e.g. `x² + (a)/(b)` for `x^2 + \frac{a}{b}`
+///
+/// Single-character scripts use Unicode super/subscript digits where they
+/// exist; everything else falls back to `^(...)` and `_(...)`.
+pub fn to_plain(nodes: &[MathNode]) -> String {
+    nodes
+        .iter()
+        .map(|node| match node {
+            MathNode::Text(text) => text.clone(),
+            MathNode::Superscript(inner) => script(inner, '^', superscript_char),
+            MathNode::Subscript(inner) => script(inner, '_', subscript_char),
+            MathNode::Fraction(numerator, denominator) => {
+                format!("{}/{}", grouped(numerator), grouped(denominator))
+            }
+            MathNode::Sqrt(inner) => format!("√{}", grouped(inner)),
+        })
+        .collect()
+}
+
+/// Plain text of `nodes`, parenthesized unless it is a single character
+fn grouped(nodes: &[MathNode]) -> String {
+    let text = to_plain(nodes);
+    match text.chars().count() {
+        1 => text,
+        _ => format!("({})", text),
+    }
+}
+
+fn script(inner: &[MathNode], marker: char, convert: fn(char) -> Option<char>) -> String {
+    let text = to_plain(inner);
+    let converted: Option<String> = text.chars().map(convert).collect();
+    match (converted, text.chars().count()) {
+        (Some(converted), 1..) => converted,
+        (_, 1) => format!("{}{}", marker, text),
+        _ => format!("{}({})", marker, text),
+    }
+}
+
+fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' => '⁻',
+        'n' => 'ⁿ',
+        'i' => 'ⁱ',
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' => '₋',
+        'i' => 'ᵢ',
+        'n' => 'ₙ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> MathNode {
+        MathNode::Text(s.to_string())
+    }
+
+    #[test]
+    fn commands_become_symbols_and_scripts_nest() {
+        assert_eq!(
+            parse(r"\alpha^2 + x_{i+1} \leq \infty"),
+            vec![
+                text("α"),
+                MathNode::Superscript(vec![text("2")]),
+                text(" + x"),
+                MathNode::Subscript(vec![text("i+1")]),
+                text(" ≤ ∞"),
+            ]
+        );
+    }
+
+    #[test]
+    fn fractions_roots_and_text_arguments() {
+        assert_eq!(
+            parse(r"\frac{a}{b} = \sqrt{2} \text{ if } \mathbf{x}"),
+            vec![
+                MathNode::Fraction(vec![text("a")], vec![text("b")]),
+                text(" = "),
+                MathNode::Sqrt(vec![text("2")]),
+                text(" if x"),
+            ]
+        );
+        assert_eq!(parse(r"\unknown"), vec![text(r"\unknown")]);
+    }
+
+    #[test]
+    fn plain_output_uses_unicode_scripts() {
+        assert_eq!(to_plain(&parse(r"E = mc^2")), "E = mc²");
+        assert_eq!(to_plain(&parse(r"x_{n} + y^{ab}")), "xₙ + y^(ab)");
+        assert_eq!(to_plain(&parse(r"\frac{1}{n+1}")), "1/(n+1)");
+    }
+}
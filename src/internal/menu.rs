@@ -0,0 +1,98 @@
+//! Native application menu
+//!
+//! Exposes the most common commands in the menu bar (macOS) so they can be
+//! discovered without the help overlay. Each item dispatches an action that
+//! the viewer handles by calling the same methods as the keyboard shortcuts.
+
+use gpui::{App, Menu, MenuItem, actions};
+
+actions!(
+    markdown_viewer,
+    [
+        Quit,
+        OpenFile,
+        OpenRecent,
+        GoToFile,
+        ReopenClosedTab,
+        ExportPdf,
+        ExportHtml,
+        ExportEpub,
+        Reexport,
+        ShowExports,
+        Print,
+        ToggleToc,
+        ShowImageReport,
+        ToggleOffline,
+        ToggleTheme,
+        CycleTheme,
+        ZoomIn,
+        ZoomOut,
+        ShowHelp,
+        ShowAbout,
+    ]
+);
+
+/// Menus shown in the menu bar
+pub fn app_menus() -> Vec<Menu> {
+    vec![
+        Menu {
+            name: "Markdown Viewer".into(),
+            items: vec![
+                MenuItem::action("About Markdown Viewer", ShowAbout),
+                MenuItem::separator(),
+                MenuItem::action("Quit Markdown Viewer", Quit),
+            ],
+        },
+        Menu {
+            name: "File".into(),
+            items: vec![
+                MenuItem::action("Open...", OpenFile),
+                MenuItem::action("Open Recent...", OpenRecent),
+                MenuItem::action("Go to File...", GoToFile),
+                MenuItem::action("Reopen Closed Tab", ReopenClosedTab),
+                MenuItem::separator(),
+                MenuItem::action("Export to PDF", ExportPdf),
+                MenuItem::action("Export to HTML", ExportHtml),
+                MenuItem::action("Export to EPUB", ExportEpub),
+                MenuItem::action("Repeat Last Export", Reexport),
+                MenuItem::action("Recent Exports...", ShowExports),
+                MenuItem::separator(),
+                MenuItem::action("Print...", Print),
+            ],
+        },
+        Menu {
+            name: "View".into(),
+            items: vec![
+                MenuItem::action("Table of Contents", ToggleToc),
+                MenuItem::action("Image Report...", ShowImageReport),
+                MenuItem::action("Offline Mode", ToggleOffline),
+                MenuItem::action("Toggle Light/Dark Theme", ToggleTheme),
+                MenuItem::action("Next Theme Family", CycleTheme),
+                MenuItem::separator(),
+                MenuItem::action("Zoom In", ZoomIn),
+                MenuItem::action("Zoom Out", ZoomOut),
+            ],
+        },
+        Menu {
+            name: "Help".into(),
+            items: vec![MenuItem::action("Keyboard Shortcuts", ShowHelp)],
+        },
+    ]
+}
+
+/// Install the menu bar and the app-wide actions that need no open document
+pub fn install(cx: &mut App) {
+    cx.on_action(|_: &Quit, cx| cx.quit());
+    cx.set_menus(app_menus());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menus_cover_file_view_and_help() {
+        let names: Vec<_> = app_menus().into_iter().map(|menu| menu.name).collect();
+        assert_eq!(names, vec!["Markdown Viewer", "File", "View", "Help"]);
+    }
+}
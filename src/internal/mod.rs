@@ -5,22 +5,64 @@
 //! by functionality. These modules are not part of the public API but
 //! are re-exported through the main lib.rs as needed.
 
+pub mod about;
+pub mod archive;
+pub mod block_map;
+pub mod bookmark_anchor;
+pub mod bookmark_list;
+pub mod contrast;
+pub mod dialog;
+pub mod document;
+pub mod epub_export;
 pub mod events;
+pub mod export_history;
 pub mod file_handling;
+pub mod file_queue;
+pub mod file_state;
+pub mod file_tree;
 pub mod file_watcher;
+pub mod filter;
+pub mod focus;
 pub mod help_overlay;
+pub mod history;
+pub mod html_export;
 pub mod image;
+pub mod image_disk_cache;
 pub mod image_loader;
+pub mod image_report;
+pub mod inspect;
+pub mod interaction;
+pub mod keymap;
+pub mod lightbox;
+pub mod line_map;
+pub mod link_rules;
+pub mod lint;
+pub mod math;
+pub mod menu;
+pub mod network;
+pub mod notifications;
 pub mod pdf_export;
+pub mod plugin;
+pub mod print;
+pub mod project_search;
+pub mod raw_html;
+pub mod render;
 pub mod rendering;
 pub mod scroll;
 pub mod search;
+pub mod source_view;
 pub mod style;
+pub mod tabs;
+pub mod text_format;
 pub mod text_highlight;
 pub mod theme;
 pub mod toc;
+pub mod tour;
+pub mod transform;
 pub mod ui;
 pub mod viewer;
+pub mod viewer_events;
+pub mod watchdog;
 
 // Note: selected helpers from internal submodules are re-exported from
 // `lib.rs` when the binary needs them. Avoid re-exporting here to prevent
@@ -0,0 +1,128 @@
+//! Offline mode for remote images
+//!
+//! Without a network, every remote image waited for its request to time out
+//! (and its retries) before showing a placeholder, which stalled image-heavy
+//! documents. [`NetworkStatus`] tracks whether remote images should be
+//! fetched at all: the user can switch offline mode on (`images.offline` or
+//! View → Offline Mode), and the first fetch that fails because the network
+//! is unreachable switches it on by itself. While offline, remote images
+//! fail right away and show their alt text; going online again retries them.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the network is unreachable or the user chose to stay offline
+///
+/// Clones share the detected state, so fetches running in the background
+/// can skip their retries once another fetch found the network down.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkStatus {
+    manual: bool,
+    unreachable: Arc<AtomicBool>,
+    /// Whether the user was told about the detected outage
+    announced: bool,
+}
+
+impl NetworkStatus {
+    /// Start offline when `offline` is set, e.g. from `images.offline`
+    pub fn new(offline: bool) -> Self {
+        Self {
+            manual: offline,
+            unreachable: Arc::new(AtomicBool::new(false)),
+            announced: false,
+        }
+    }
+
+    /// Whether remote images should be skipped
+    pub fn is_offline(&self) -> bool {
+        self.manual || self.unreachable.load(Ordering::Relaxed)
+    }
+
+    /// Whether offline mode was switched on because a fetch could not reach
+    /// the network, rather than by the user
+    pub fn is_detected(&self) -> bool {
+        !self.manual && self.unreachable.load(Ordering::Relaxed)
+    }
+
+    /// Record a fetch that could not reach the network
+    pub fn mark_unreachable(&self) {
+        self.unreachable.store(true, Ordering::Relaxed);
+    }
+
+    /// True once per detected outage, so the user is told about it once
+    pub fn announce(&mut self) -> bool {
+        let first = self.is_detected() && !self.announced;
+        self.announced |= first;
+        first
+    }
+
+    /// Switch offline mode on or off by hand; going online forgets a
+    /// detected outage so remote images are tried again
+    pub fn set_offline(&mut self, offline: bool) {
+        self.manual = offline;
+        if !offline {
+            self.unreachable.store(false, Ordering::Relaxed);
+            self.announced = false;
+        }
+    }
+}
+
+/// Whether `error` means the network could not be reached (connection
+/// refused, no route, DNS failure or timeout) rather than a bad response
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|error| error.is_connect() || error.is_timeout())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detected_outage_is_reported_once_and_cleared_online() {
+        let mut status = NetworkStatus::new(false);
+        let background = status.clone();
+        assert!(!status.is_offline());
+
+        background.mark_unreachable();
+        background.mark_unreachable();
+        assert!(status.is_offline() && status.is_detected());
+        assert!(status.announce());
+        assert!(!status.announce());
+
+        status.set_offline(false);
+        assert!(!status.is_offline());
+        assert!(!background.is_offline());
+
+        background.mark_unreachable();
+        assert!(status.announce());
+    }
+
+    #[test]
+    fn manual_offline_mode_is_not_a_detected_outage() {
+        let mut status = NetworkStatus::new(true);
+        status.mark_unreachable();
+        assert!(status.is_offline());
+        assert!(!status.is_detected());
+        assert!(!status.announce());
+
+        status.set_offline(false);
+        assert!(!status.is_offline());
+    }
+
+    #[tokio::test]
+    async fn refused_connections_count_as_unreachable() {
+        // Nothing listens on the discard port of the loopback address
+        let error = reqwest::get("http://127.0.0.1:9/image.png")
+            .await
+            .map_err(anyhow::Error::new)
+            .unwrap_err();
+        assert!(is_unreachable(&error));
+        assert!(!is_unreachable(&anyhow::anyhow!(
+            "Unsupported image format"
+        )));
+    }
+}
@@ -0,0 +1,314 @@
+//! Toast notifications shown along the top of the window
+//!
+//! Export results and short informational messages all go through one
+//! [`NotificationManager`]. Each source has at most one toast; a new message
+//! from the same source replaces the old one. Toasts close when
+//! clicked, on Escape (newest first) or when their configured timeout runs
+//! out, and a thin bar shows how much of the timeout is left.
+//!
+//! Toasts for a written file carry [`NotificationAction`] buttons to open
+//! the file or show it in the system file manager, and the offer to remap
+//! marks after an external change carries a button accepting it.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::NotificationConfig;
+
+/// How often a timed toast's progress bar is redrawn
+pub const NOTIFICATION_TICK: Duration = Duration::from_millis(100);
+
+/// Coloring and icon of a toast
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    Success,
+    Error,
+    Info,
+}
+
+impl NotificationKind {
+    pub fn icon(self) -> &'static str {
+        match self {
+            NotificationKind::Success => "✓",
+            NotificationKind::Error => "✗",
+            NotificationKind::Info => "ℹ",
+        }
+    }
+}
+
+/// What raised a toast; each source shows at most one at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationSource {
+    /// PDF, HTML and EPUB export results
+    Export,
+    /// Search history, bookmark and clipboard messages
+    Message,
+    /// Background work that failed, e.g. an image fetch (see `watchdog`)
+    Background,
+    /// Offer to remap marks after the file changed on disk
+    Remap,
+}
+
+/// A button on a toast
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationAction {
+    /// Open the file with its default application
+    Open(PathBuf),
+    /// Select the file in Finder, Explorer or the platform file manager
+    Reveal(PathBuf),
+    /// Move marks to follow their text after the file changed on disk
+    RemapMarks,
+}
+
+impl NotificationAction {
+    /// Open and reveal buttons for a file that was just written
+    pub fn for_file(path: &Path) -> Vec<Self> {
+        vec![
+            Self::Open(path.to_path_buf()),
+            Self::Reveal(path.to_path_buf()),
+        ]
+    }
+
+    /// Button text, e.g. `Open PDF` or `Reveal in Finder`
+    pub fn label(&self) -> String {
+        match self {
+            Self::Open(path) => match path.extension().and_then(|ext| ext.to_str()) {
+                Some(ext) => format!("Open {}", ext.to_uppercase()),
+                None => "Open".to_string(),
+            },
+            Self::Reveal(_) => match std::env::consts::OS {
+                "macos" => "Reveal in Finder".to_string(),
+                "windows" => "Show in Explorer".to_string(),
+                _ => "Show in Folder".to_string(),
+            },
+            Self::RemapMarks => "Remap".to_string(),
+        }
+    }
+}
+
+/// A toast on screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub source: NotificationSource,
+    pub kind: NotificationKind,
+    pub message: String,
+    /// Buttons shown after the message
+    pub actions: Vec<NotificationAction>,
+    shown_at: Instant,
+    /// `None` keeps the toast until it is dismissed
+    timeout: Option<Duration>,
+}
+
+impl Notification {
+    /// Fraction of the timeout left at `now`, from 1.0 down to 0.0; `None`
+    /// for toasts without a timeout
+    pub fn remaining(&self, now: Instant) -> Option<f32> {
+        self.timeout.map(|timeout| {
+            let elapsed = now.saturating_duration_since(self.shown_at);
+            1.0 - (elapsed.as_secs_f32() / timeout.as_secs_f32()).min(1.0)
+        })
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.timeout
+            .is_some_and(|timeout| now.saturating_duration_since(self.shown_at) >= timeout)
+    }
+}
+
+/// The toasts currently shown, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct NotificationManager {
+    config: NotificationConfig,
+    notifications: Vec<Notification>,
+}
+
+impl NotificationManager {
+    pub fn new(config: NotificationConfig) -> Self {
+        Self {
+            config,
+            notifications: Vec::new(),
+        }
+    }
+
+    /// Use new timeouts for toasts shown from now on
+    pub fn set_config(&mut self, config: NotificationConfig) {
+        self.config = config;
+    }
+
+    /// Show `message`, replacing any toast from the same source
+    pub fn show(
+        &mut self,
+        source: NotificationSource,
+        kind: NotificationKind,
+        message: impl Into<String>,
+    ) {
+        self.show_at(source, kind, message.into(), Vec::new(), Instant::now());
+    }
+
+    /// Show `message` with buttons, replacing any toast from the same source
+    pub fn show_with_actions(
+        &mut self,
+        source: NotificationSource,
+        kind: NotificationKind,
+        message: impl Into<String>,
+        actions: Vec<NotificationAction>,
+    ) {
+        self.show_at(source, kind, message.into(), actions, Instant::now());
+    }
+
+    fn show_at(
+        &mut self,
+        source: NotificationSource,
+        kind: NotificationKind,
+        message: String,
+        actions: Vec<NotificationAction>,
+        now: Instant,
+    ) {
+        let timeout_ms = match kind {
+            NotificationKind::Success => self.config.success_timeout_ms,
+            NotificationKind::Error => self.config.error_timeout_ms,
+            NotificationKind::Info => self.config.info_timeout_ms,
+        };
+        self.dismiss(source);
+        self.notifications.push(Notification {
+            source,
+            kind,
+            message,
+            actions,
+            shown_at: now,
+            timeout: (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms)),
+        });
+    }
+
+    /// Close the toast from `source`, if any
+    pub fn dismiss(&mut self, source: NotificationSource) {
+        self.notifications
+            .retain(|notification| notification.source != source);
+    }
+
+    /// Close the newest toast; returns whether there was one
+    pub fn dismiss_latest(&mut self) -> bool {
+        self.notifications.pop().is_some()
+    }
+
+    /// The toast from `source`, if one is shown
+    pub fn get(&self, source: NotificationSource) -> Option<&Notification> {
+        self.notifications
+            .iter()
+            .find(|notification| notification.source == source)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Notification> {
+        self.notifications.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifications.is_empty()
+    }
+
+    /// Whether any shown toast closes on its own
+    pub fn has_timers(&self) -> bool {
+        self.notifications
+            .iter()
+            .any(|notification| notification.timeout.is_some())
+    }
+
+    /// Close toasts whose timeout ran out; returns whether any closed
+    pub fn remove_expired(&mut self, now: Instant) -> bool {
+        let before = self.notifications.len();
+        self.notifications
+            .retain(|notification| !notification.is_expired(now));
+        self.notifications.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> NotificationManager {
+        NotificationManager::new(NotificationConfig {
+            success_timeout_ms: 1000,
+            info_timeout_ms: 2000,
+            error_timeout_ms: 0,
+        })
+    }
+
+    #[test]
+    fn same_source_replaces_and_escape_closes_newest() {
+        let mut notifications = manager();
+        let now = Instant::now();
+        notifications.show_at(
+            NotificationSource::Export,
+            NotificationKind::Success,
+            "first".to_string(),
+            Vec::new(),
+            now,
+        );
+        notifications.show_at(
+            NotificationSource::Message,
+            NotificationKind::Info,
+            "info".to_string(),
+            Vec::new(),
+            now,
+        );
+        notifications.show_at(
+            NotificationSource::Export,
+            NotificationKind::Error,
+            "second".to_string(),
+            Vec::new(),
+            now,
+        );
+
+        let messages: Vec<&str> = notifications.iter().map(|n| n.message.as_str()).collect();
+        assert_eq!(messages, vec!["info", "second"]);
+        assert!(notifications.dismiss_latest());
+        assert!(notifications.get(NotificationSource::Export).is_none());
+        assert!(notifications.get(NotificationSource::Message).is_some());
+    }
+
+    #[test]
+    fn timed_toasts_expire_and_errors_stay() {
+        let mut notifications = manager();
+        let now = Instant::now();
+        notifications.show_at(
+            NotificationSource::Export,
+            NotificationKind::Success,
+            "saved".to_string(),
+            Vec::new(),
+            now,
+        );
+        notifications.show_at(
+            NotificationSource::Message,
+            NotificationKind::Error,
+            "failed".to_string(),
+            Vec::new(),
+            now,
+        );
+
+        let toast = notifications.get(NotificationSource::Export).unwrap();
+        assert_eq!(
+            toast.remaining(now + Duration::from_millis(250)),
+            Some(0.75)
+        );
+        let error = notifications.get(NotificationSource::Message).unwrap();
+        assert_eq!(error.remaining(now), None);
+
+        assert!(!notifications.remove_expired(now + Duration::from_millis(999)));
+        assert!(notifications.remove_expired(now + Duration::from_millis(1000)));
+        assert!(notifications.get(NotificationSource::Export).is_none());
+        assert!(!notifications.has_timers());
+        assert!(!notifications.is_empty());
+    }
+
+    #[test]
+    fn file_actions_are_labelled_by_extension() {
+        let actions = NotificationAction::for_file(Path::new("/docs/guide.pdf"));
+        assert_eq!(
+            actions[0],
+            NotificationAction::Open(PathBuf::from("/docs/guide.pdf"))
+        );
+        assert_eq!(actions[0].label(), "Open PDF");
+        assert!(matches!(actions[1], NotificationAction::Reveal(_)));
+    }
+}
@@ -0,0 +1,187 @@
+//! Plugin hooks
+//!
+//! Plugins let downstream users customise the viewer without forking it: they
+//! are told when a document is loaded, may rewrite the parsed AST before any
+//! backend renders it (e.g. turning `plantuml` code blocks into images), and
+//! may contribute small overlay panels for the current document.
+//!
+//! Plugins are registered once at startup, either in code via [`register`] or,
+//! with the `plugin-dylib` feature, by loading shared libraries listed in
+//! `config.ron` (see [`load_dylib`]). Registered plugins stay active for the
+//! lifetime of the process.
+
+use comrak::nodes::AstNode;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info};
+
+/// A panel contributed by a plugin, shown over the document
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginOverlay {
+    pub title: String,
+    pub body: String,
+}
+
+/// Hooks a plugin can implement; every hook has a no-op default
+pub trait Plugin: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> &str;
+
+    /// Called after a document is loaded or reloaded from disk
+    fn on_document_loaded(&self, _path: &Path, _content: &str) {}
+
+    /// Rewrite the parsed document before it is rendered
+    ///
+    /// Runs as part of the transform pipeline (see `transform::PLUGIN_PASS_ORDER`)
+    /// on every parse, including each GUI frame, so it should be cheap.
+    /// Nodes can be changed in place through `node.data.borrow_mut()` or
+    /// detached from the tree.
+    fn transform_ast<'a>(&self, _root: &'a AstNode<'a>) {}
+
+    /// Overlay panels to show for the document just loaded
+    fn extra_overlays(&self, _path: &Path, _content: &str) -> Vec<PluginOverlay> {
+        Vec::new()
+    }
+}
+
+static PLUGINS: RwLock<Vec<Arc<dyn Plugin>>> = RwLock::new(Vec::new());
+
+/// Register a plugin; hooks run in registration order
+pub fn register(plugin: impl Plugin + 'static) {
+    info!("Registered plugin: {}", plugin.name());
+    PLUGINS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(Arc::new(plugin));
+}
+
+/// Snapshot of the registered plugins, so hooks run without holding the lock
+fn plugins() -> Vec<Arc<dyn Plugin>> {
+    PLUGINS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Run every plugin's `on_document_loaded` hook and collect their overlays
+pub fn document_loaded(path: &Path, content: &str) -> Vec<PluginOverlay> {
+    plugins()
+        .iter()
+        .flat_map(|plugin| {
+            debug!("Running document hooks for plugin: {}", plugin.name());
+            plugin.on_document_loaded(path, content);
+            plugin.extra_overlays(path, content)
+        })
+        .collect()
+}
+
+/// Run every plugin's `transform_ast` hook on `root`
+pub fn transform_ast<'a>(root: &'a AstNode<'a>) {
+    for plugin in plugins() {
+        plugin.transform_ast(root);
+    }
+}
+
+/// Name of the constructor symbol a plugin library must export
+pub const PLUGIN_ENTRY_SYMBOL: &str = "markdown_viewer_plugin_create";
+
+/// Export a plugin constructor from a `cdylib` so it can be loaded at runtime
+///
+/// The plugin must be built with the same compiler and `markdown_viewer`
+/// version as the viewer, since trait objects cross the library boundary.
+///
+/// ```ignore
+/// markdown_viewer::declare_plugin!(PlantUmlPlugin::default());
+/// ```
+#[macro_export]
+macro_rules! declare_plugin {
+    ($constructor:expr) => {
+        #[unsafe(no_mangle)]
+        pub fn markdown_viewer_plugin_create() -> Box<dyn $crate::plugin::Plugin> {
+            Box::new($constructor)
+        }
+    };
+}
+
+/// Load a plugin library and register the plugin it exports
+///
+/// The library stays loaded for the rest of the process.
+#[cfg(feature = "plugin-dylib")]
+pub fn load_dylib(path: &Path) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    // SAFETY: the library is trusted configuration and must export the
+    // constructor generated by `declare_plugin!` with a matching toolchain.
+    let plugin = unsafe {
+        let library = libloading::Library::new(path)
+            .with_context(|| format!("Failed to load plugin library {:?}", path))?;
+        let create = library
+            .get::<fn() -> Box<dyn Plugin>>(PLUGIN_ENTRY_SYMBOL.as_bytes())
+            .with_context(|| format!("{:?} does not export {}", path, PLUGIN_ENTRY_SYMBOL))?;
+        let plugin = create();
+        // The plugin's code lives in the library, so it must never be unloaded
+        std::mem::forget(library);
+        plugin
+    };
+
+    info!("Registered plugin: {} (from {:?})", plugin.name(), path);
+    PLUGINS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(Arc::from(plugin));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use comrak::nodes::NodeValue;
+    use comrak::{Arena, parse_document};
+
+    /// Turns emphasised marker text into strong text and reports the document length
+    ///
+    /// Plugins are global, so the transform only touches text no other test uses.
+    struct TestPlugin;
+
+    impl Plugin for TestPlugin {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn transform_ast<'a>(&self, root: &'a AstNode<'a>) {
+            for node in root.descendants() {
+                let is_marker = node.first_child().is_some_and(|child| {
+                    matches!(&child.data.borrow().value, NodeValue::Text(text) if text.contains("plugin marker"))
+                });
+                let mut data = node.data.borrow_mut();
+                if is_marker && matches!(data.value, NodeValue::Emph) {
+                    data.value = NodeValue::Strong;
+                }
+            }
+        }
+
+        fn extra_overlays(&self, path: &Path, content: &str) -> Vec<PluginOverlay> {
+            vec![PluginOverlay {
+                title: path.display().to_string(),
+                body: format!("{} bytes", content.len()),
+            }]
+        }
+    }
+
+    #[test]
+    fn registered_plugins_transform_and_add_overlays() {
+        register(TestPlugin);
+
+        let content = "_plugin marker_";
+        let overlays = document_loaded(Path::new("doc.md"), content);
+        assert!(overlays.contains(&PluginOverlay {
+            title: "doc.md".to_string(),
+            body: "15 bytes".to_string(),
+        }));
+
+        let arena = Arena::new();
+        let root = parse_document(&arena, content, &crate::internal::render::parse_options());
+        transform_ast(root);
+        let has_strong = root
+            .descendants()
+            .any(|node| matches!(node.data.borrow().value, NodeValue::Strong));
+        assert!(has_strong);
+    }
+}
@@ -0,0 +1,267 @@
+//! Printing through the system print dialog
+//!
+//! gpui has no printing API, so the document is laid out as print-ready HTML
+//! and opened in the default browser, which brings up the system print
+//! dialog as soon as the page has loaded. Pages are cut at top-level block
+//! boundaries using the viewer's measured block positions (the [`BlockMap`]),
+//! scaled from the window's text width to the paper width, and every page
+//! gets a header with the document title and a footer with its page number.
+
+use anyhow::{Context, Result};
+use comrak::{Arena, parse_document};
+use std::ops::Range;
+use std::path::Path;
+use tracing::{debug, info};
+
+use crate::config::RenderMode;
+use crate::internal::block_map::BlockMap;
+use crate::internal::html_export::document_title;
+use crate::internal::render::html::{self, HtmlOptions, escape_html};
+use crate::internal::render::split::SplitDocument;
+use crate::internal::theme::ActiveTheme;
+use crate::internal::transform::{self, TransformContext};
+
+/// Printable width of a US Letter page with 1 inch margins, in CSS pixels
+pub const PRINT_PAGE_WIDTH: f32 = 624.0;
+
+/// Printable height of a US Letter page with 1 inch margins, less the page
+/// header and footer, in CSS pixels
+pub const PRINT_PAGE_HEIGHT: f32 = 800.0;
+
+/// What to print and how the viewer laid it out
+pub struct PrintJob<'a> {
+    pub markdown_content: &'a str,
+    pub markdown_file_path: &'a Path,
+    pub render_mode: RenderMode,
+    /// Positions of the document's lines in the window
+    pub block_map: &'a BlockMap,
+    /// Width the window wraps text at, which `block_map` assumes
+    pub text_width: f32,
+    /// Base text size in the window
+    pub text_size: f32,
+    pub theme: &'a ActiveTheme,
+}
+
+/// Group consecutive blocks into pages no taller than `page_height`
+///
+/// Returns the block indices of each page. A block taller than a page gets
+/// a page of its own.
+pub fn paginate(heights: &[f32], page_height: f32) -> Vec<Range<usize>> {
+    let mut pages: Vec<Range<usize>> = Vec::new();
+    let mut used = 0.0;
+    for (index, &height) in heights.iter().enumerate() {
+        match pages.last_mut() {
+            Some(page) if used + height <= page_height => {
+                page.end = index + 1;
+                used += height;
+            }
+            _ => {
+                pages.push(index..index + 1);
+                used = height;
+            }
+        }
+    }
+    pages
+}
+
+/// Write the paginated document to `output_path` as HTML that opens the
+/// print dialog when loaded; returns the number of pages
+pub fn write_print_document(job: &PrintJob, output_path: &Path) -> Result<usize> {
+    info!("Preparing print document: {:?}", output_path);
+    let (document, page_count) = print_document(job);
+    std::fs::write(output_path, document)
+        .with_context(|| format!("Failed to write '{}'", output_path.display()))?;
+    info!("Prepared {} pages for printing", page_count);
+    Ok(page_count)
+}
+
+/// The print-ready HTML page and its number of pages
+pub fn print_document(job: &PrintJob) -> (String, usize) {
+    // On paper the text wraps at the same place as in the window, so every
+    // height scales by the ratio of the two widths
+    let scale = PRINT_PAGE_WIDTH / job.text_width.max(1.0);
+    let page_height = PRINT_PAGE_HEIGHT / scale;
+
+    let (title, pages) = match job.render_mode {
+        RenderMode::Markdown => markdown_pages(job, page_height),
+        RenderMode::Plain => plain_pages(job, page_height),
+    };
+    debug!(
+        "Paginated at {:.0}px of window height per page ({} pages)",
+        page_height,
+        pages.len()
+    );
+
+    let page_count = pages.len();
+    let body: String = pages
+        .iter()
+        .enumerate()
+        .map(|(index, page)| {
+            format!(
+                "<section class=\"page\">\n<header><span>{title}</span><span>{file}</span></header>\n\
+                 <main>\n{page}</main>\n<footer>Page {number} of {count}</footer>\n</section>\n",
+                title = escape_html(&title),
+                file = escape_html(&job.markdown_file_path.display().to_string()),
+                page = page,
+                number = index + 1,
+                count = page_count
+            )
+        })
+        .collect();
+
+    let document = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>\n{}{}</style>\n</head>\n<body>\n{}\
+         <script>window.addEventListener(\"load\", () => window.print());</script>\n\
+         </body>\n</html>\n",
+        escape_html(&title),
+        print_css(job.text_size * scale),
+        html::syntect_css(job.theme),
+        body
+    );
+    (document, page_count)
+}
+
+/// Rendered pages of a markdown document, cut at top-level blocks
+fn markdown_pages(job: &PrintJob, page_height: f32) -> (String, Vec<String>) {
+    let split = SplitDocument::parse(job.markdown_content);
+    // Each block extends to the next one, so the spacing between them counts
+    let heights: Vec<f32> = split
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| {
+            let end = match split.blocks.get(index + 1) {
+                Some(next) => job.block_map.y_for_line(next.start_line),
+                None => job.block_map.y_for_line(block.end_line + 1),
+            };
+            end - job.block_map.y_for_line(block.start_line)
+        })
+        .collect();
+
+    let arena = Arena::new();
+    let root = parse_document(
+        &arena,
+        job.markdown_content,
+        &crate::internal::render::parse_options(),
+    );
+    let title = document_title(root, job.markdown_file_path);
+
+    let pages = paginate(&heights, page_height)
+        .into_iter()
+        .map(|range| {
+            let arena = Arena::new();
+            let root = parse_document(
+                &arena,
+                &split.piece(range),
+                &crate::internal::render::parse_options(),
+            );
+            transform::apply(
+                &arena,
+                root,
+                &TransformContext {
+                    path: Some(job.markdown_file_path),
+                },
+            );
+            html::render_document(
+                root,
+                &HtmlOptions {
+                    markdown_file_path: Some(job.markdown_file_path),
+                    embed_images: true,
+                },
+            )
+        })
+        .collect();
+    (title, pages)
+}
+
+/// Pages of a plain text document, cut between lines
+fn plain_pages(job: &PrintJob, page_height: f32) -> (String, Vec<String>) {
+    let lines: Vec<&str> = job.markdown_content.lines().collect();
+    let heights: Vec<f32> = (0..lines.len())
+        .map(|line| job.block_map.y_for_line(line + 1) - job.block_map.y_for_line(line))
+        .collect();
+    let title = job
+        .markdown_file_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("Document")
+        .to_string();
+
+    let pages = paginate(&heights, page_height)
+        .into_iter()
+        .map(|range| {
+            format!(
+                "<pre class=\"plain\">{}</pre>\n",
+                escape_html(&lines[range].join("\n"))
+            )
+        })
+        .collect();
+    (title, pages)
+}
+
+/// Print stylesheet; black on white whatever the window theme
+fn print_css(text_size: f32) -> String {
+    format!(
+        "@page {{ size: letter; margin: 1in; }}\n\
+         body {{ margin: 0; color: #000; background: #fff; font-size: {:.1}px; line-height: 1.5; \
+         font-family: -apple-system, BlinkMacSystemFont, \"Segoe UI\", Helvetica, Arial, sans-serif; }}\n\
+         .page {{ break-after: page; page-break-after: always; }}\n\
+         .page:last-of-type {{ break-after: auto; page-break-after: auto; }}\n\
+         header, footer {{ display: flex; justify-content: space-between; font-size: 9pt; color: #666; }}\n\
+         header {{ border-bottom: 1px solid #ccc; margin-bottom: 12px; padding-bottom: 4px; }}\n\
+         footer {{ justify-content: center; border-top: 1px solid #ccc; margin-top: 12px; padding-top: 4px; }}\n\
+         code, pre {{ font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }}\n\
+         pre.code, pre.plain {{ white-space: pre-wrap; }}\n\
+         pre.code {{ padding: 8px; border: 1px solid #ddd; }}\n\
+         blockquote {{ margin: 0; padding-left: 12px; border-left: 3px solid #999; font-style: italic; }}\n\
+         .markdown-alert {{ padding-left: 12px; border-left: 3px solid #999; }}\n\
+         .markdown-alert-title {{ font-weight: bold; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ border: 1px solid #999; padding: 4px 8px; }}\n\
+         img {{ max-width: 100%; }}\n\
+         .math.display {{ text-align: center; }}\n\
+         .footnote {{ display: flex; gap: 6px; font-size: 0.85em; }}\n\
+         .footnote p {{ margin: 0; }}\n",
+        text_size
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::theme::test_theme;
+
+    #[test]
+    fn pages_break_between_blocks() {
+        assert_eq!(
+            paginate(&[300.0, 300.0, 300.0, 900.0, 50.0], 800.0),
+            vec![0..2, 2..3, 3..4, 4..5]
+        );
+        assert!(paginate(&[], 800.0).is_empty());
+    }
+
+    #[test]
+    fn print_document_has_numbered_pages() {
+        let content = "# Guide\n\nfirst\n\nsecond\n\nthird";
+        let line_tops: Vec<f32> = (0..7).map(|line| line as f32 * 400.0).collect();
+        let block_map =
+            BlockMap::from_line_ranges([(0, 0), (2, 2), (4, 4), (6, 6)], &line_tops, 2800.0, 0.0);
+        let theme = test_theme();
+        let job = PrintJob {
+            markdown_content: content,
+            markdown_file_path: Path::new("/docs/guide.md"),
+            render_mode: RenderMode::Markdown,
+            block_map: &block_map,
+            text_width: PRINT_PAGE_WIDTH,
+            text_size: 16.0,
+            theme: &theme,
+        };
+
+        let (document, pages) = print_document(&job);
+        assert_eq!(pages, 4);
+        assert!(document.contains("<title>Guide</title>"));
+        assert!(document.contains("<footer>Page 4 of 4</footer>"));
+        assert!(document.contains("window.print()"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Search across every supported file of a directory
+//!
+//! When the viewer is launched on a directory, Cmd+Shift+F opens the project
+//! search overlay. Each query walks the directory on the background runtime,
+//! reads every supported file (skipping oversized and binary ones, like
+//! opening them would) and collects the matching lines, grouped by file.
+//! Queries use the same [`SearchMode`] as the in-document search. Choosing a
+//! result opens the file and jumps to the match.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info};
+use walkdir::WalkDir;
+
+use crate::internal::file_handling::{is_supported_extension, load_markdown_content};
+use crate::internal::search::{MatchPosition, SearchMode};
+
+/// Matches collected before the search stops
+pub const MAX_PROJECT_MATCHES: usize = 500;
+
+/// Characters of a line shown around a match
+const PREVIEW_CHARS: usize = 100;
+
+/// Pause after a keystroke before the directory is searched
+pub const PROJECT_SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// A matching line
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineMatch {
+    /// Line number (1-based)
+    pub line: usize,
+    /// The line, shortened around the first match when long
+    pub preview: String,
+    /// Byte range of the first match within `preview`
+    pub highlight: std::ops::Range<usize>,
+}
+
+/// The matching lines of one file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileMatches {
+    pub path: PathBuf,
+    pub lines: Vec<LineMatch>,
+}
+
+/// The project search overlay
+#[derive(Debug, Clone, Default)]
+pub struct ProjectSearch {
+    /// Directory being searched
+    pub root: PathBuf,
+    pub query: String,
+    /// Results for `query`, in path order
+    pub results: Vec<FileMatches>,
+    /// Index of the selected line among all results
+    pub selected: usize,
+    /// Whether a search for `query` is still running
+    pub searching: bool,
+    /// Whether the search stopped at [`MAX_PROJECT_MATCHES`]
+    pub truncated: bool,
+    /// Bumped for every query so late results of an older one are dropped
+    pub generation: u64,
+}
+
+impl ProjectSearch {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            ..Self::default()
+        }
+    }
+
+    /// Number of matching lines across all files
+    pub fn line_count(&self) -> usize {
+        self.results.iter().map(|file| file.lines.len()).sum()
+    }
+
+    /// File and line of the selected result
+    pub fn selected_match(&self) -> Option<(&Path, &LineMatch)> {
+        self.results
+            .iter()
+            .flat_map(|file| {
+                file.lines
+                    .iter()
+                    .map(move |line| (file.path.as_path(), line))
+            })
+            .nth(self.selected)
+    }
+
+    /// Move the selection by `delta` lines, staying within the results
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.line_count().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Store the results of search `generation`; returns false when a newer
+    /// query has been typed since
+    pub fn finish(&mut self, generation: u64, results: Vec<FileMatches>) -> bool {
+        if generation != self.generation {
+            return false;
+        }
+        self.truncated =
+            results.iter().map(|file| file.lines.len()).sum::<usize>() >= MAX_PROJECT_MATCHES;
+        self.results = results;
+        self.selected = 0;
+        self.searching = false;
+        true
+    }
+}
+
+/// Search every supported file under `root` for `query`
+pub fn search_directory(
+    root: &Path,
+    query: &str,
+    mode: SearchMode,
+    extensions: &[String],
+) -> Vec<FileMatches> {
+    let pattern = match query {
+        "" => None,
+        _ => mode.pattern(query).ok(),
+    };
+    let Some(pattern) = pattern else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| is_supported_extension(&path.to_string_lossy(), extensions))
+        .collect();
+    paths.sort();
+
+    let mut results = Vec::new();
+    let mut remaining = MAX_PROJECT_MATCHES;
+    for path in paths {
+        let content = match load_markdown_content(&path.to_string_lossy()) {
+            Ok(content) => content,
+            Err(e) => {
+                debug!("Skipping {:?} in project search: {}", path, e);
+                continue;
+            }
+        };
+        let lines: Vec<LineMatch> = content
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                pattern
+                    .find_iter(line)
+                    .find(|m| !m.is_empty())
+                    .map(|m| line_match(index + 1, line, m.start()..m.end()))
+            })
+            .take(remaining)
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        remaining -= lines.len();
+        results.push(FileMatches { path, lines });
+        if remaining == 0 {
+            break;
+        }
+    }
+    info!(
+        "Project search for '{}' in {:?}: {} file(s)",
+        query,
+        root,
+        results.len()
+    );
+    results
+}
+
+/// A result line, cut to [`PREVIEW_CHARS`] characters around the match
+fn line_match(line: usize, text: &str, found: std::ops::Range<usize>) -> LineMatch {
+    let trimmed_start = text.len() - text.trim_start().len();
+    let before = text[trimmed_start..found.start.max(trimmed_start)]
+        .chars()
+        .count();
+    // Keep a little context before the match when the line is long
+    let skip = before.saturating_sub(PREVIEW_CHARS / 4);
+    let start = text[trimmed_start..]
+        .char_indices()
+        .nth(skip)
+        .map(|(offset, _)| trimmed_start + offset)
+        .unwrap_or(trimmed_start)
+        .min(found.start);
+    let end = text[start..]
+        .char_indices()
+        .nth(PREVIEW_CHARS)
+        .map(|(offset, _)| start + offset)
+        .unwrap_or(text.len())
+        .max(found.end);
+
+    let prefix = match start > trimmed_start {
+        true => "…",
+        false => "",
+    };
+    let suffix = match end < text.trim_end().len() {
+        true => "…",
+        false => "",
+    };
+    let preview = format!("{}{}{}", prefix, text[start..end].trim_end(), suffix);
+    let offset = prefix.len() + found.start - start;
+    LineMatch {
+        line,
+        highlight: offset..(offset + found.len()).min(preview.len() - suffix.len()),
+        preview,
+    }
+}
+
+/// Index of the first match in `matches` on or after `line` (1-based) of
+/// `content`
+pub fn match_index_at_line(matches: &[MatchPosition], content: &str, line: usize) -> Option<usize> {
+    let line_start: usize = content
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    matches.iter().position(|m| m.start >= line_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_are_grouped_by_file() {
+        let dir = std::env::temp_dir().join("mdv_project_search_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        std::fs::write(dir.join("a.md"), "# Parse\n\nno match\n\nparse again").unwrap();
+        std::fs::write(dir.join("docs/b.md"), "fn parse() {}").unwrap();
+        std::fs::write(dir.join("skip.rs"), "parse").unwrap();
+
+        let extensions = vec!["md".to_string()];
+        let results = search_directory(&dir, "parse", SearchMode::Plain, &extensions);
+        let summary: Vec<(String, Vec<usize>)> = results
+            .iter()
+            .map(|file| {
+                (
+                    file.path.strip_prefix(&dir).unwrap().display().to_string(),
+                    file.lines.iter().map(|line| line.line).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a.md".to_string(), vec![1, 5]),
+                ("docs/b.md".to_string(), vec![1])
+            ]
+        );
+        let words = search_directory(&dir, "parse(", SearchMode::WholeWord, &extensions);
+        assert_eq!(words.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn long_lines_are_cut_around_the_match() {
+        let line = format!("{}needle{}", "a".repeat(200), "b".repeat(200));
+        let found = line.find("needle").unwrap();
+        let result = line_match(3, &line, found..found + 6);
+        assert!(result.preview.starts_with('…') && result.preview.ends_with('…'));
+        assert_eq!(&result.preview[result.highlight.clone()], "needle");
+
+        let short = line_match(1, "  - a needle here", 6..12);
+        assert_eq!(short.preview, "- a needle here");
+        assert_eq!(&short.preview[short.highlight], "needle");
+    }
+
+    #[test]
+    fn selection_and_stale_results() {
+        let file = |name: &str, count: usize| FileMatches {
+            path: PathBuf::from(name),
+            lines: (1..=count)
+                .map(|line| line_match(line, "match", 0..5))
+                .collect(),
+        };
+        let mut search = ProjectSearch::new(PathBuf::from("/docs"));
+        search.generation = 2;
+        assert!(!search.finish(1, vec![file("old.md", 1)]));
+        assert!(search.finish(2, vec![file("a.md", 2), file("b.md", 1)]));
+
+        search.move_selection(2);
+        let (path, line) = search.selected_match().unwrap();
+        assert_eq!((path, line.line), (Path::new("b.md"), 1));
+        search.move_selection(5);
+        assert_eq!(search.selected, 2);
+        search.move_selection(-9);
+        assert_eq!(search.selected, 0);
+    }
+
+    #[test]
+    fn match_index_is_found_by_line() {
+        let content = "foo\nbar foo\nfoo";
+        let matches = vec![
+            MatchPosition { start: 0, end: 3 },
+            MatchPosition { start: 8, end: 11 },
+            MatchPosition { start: 12, end: 15 },
+        ];
+        assert_eq!(match_index_at_line(&matches, content, 2), Some(1));
+        assert_eq!(match_index_at_line(&matches, content, 3), Some(2));
+        assert_eq!(match_index_at_line(&matches, content, 4), None);
+    }
+}
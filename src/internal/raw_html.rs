@@ -0,0 +1,493 @@
+//! Subset of raw HTML shown by the GUI renderer
+//!
+//! READMEs often use HTML for things markdown has no syntax for: centered
+//! logos with `<img width=...>`, `<br>` line breaks, `<details>` sections and
+//! `<table>` layouts. comrak hands such HTML over as literal text in
+//! `HtmlBlock`/`HtmlInline` nodes, which the GUI used to drop. [`parse`]
+//! turns a fragment into a small tree the renderer walks. It is tolerant
+//! rather than complete: unclosed tags are closed at the end of the
+//! fragment, stray closing tags are ignored, unknown tags keep their content
+//! and comments, `<script>` and `<style>` are dropped.
+
+/// A node of a parsed HTML fragment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HtmlNode {
+    /// Text with entities decoded
+    Text(String),
+    Element(HtmlElement),
+}
+
+/// An HTML element with its attributes and content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlElement {
+    /// Lowercase tag name
+    pub tag: String,
+    /// Attributes in source order, names lowercase
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<HtmlNode>,
+}
+
+impl HtmlElement {
+    fn new(tag: String, attrs: Vec<(String, String)>) -> Self {
+        Self {
+            tag,
+            attrs,
+            children: Vec::new(),
+        }
+    }
+
+    /// Value of attribute `name`
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(attr, _)| attr == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Text of the element and everything in it, whitespace collapsed
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        collect_text(&self.children, &mut out);
+        collapse_whitespace(&out).trim().to_string()
+    }
+
+    /// Text of the element and everything in it as written, for `<pre>`
+    pub fn raw_text(&self) -> String {
+        let mut out = String::new();
+        collect_text(&self.children, &mut out);
+        out.trim_matches('\n').to_string()
+    }
+
+    /// Width in pixels from a `width="120"` or `width="120px"` attribute;
+    /// percentages are ignored
+    pub fn width(&self) -> Option<f32> {
+        self.attr("width")?
+            .trim()
+            .trim_end_matches("px")
+            .parse::<f32>()
+            .ok()
+            .filter(|width| *width > 0.0)
+    }
+
+    /// Whether the element is laid out on lines of its own
+    pub fn is_block(&self) -> bool {
+        matches!(
+            self.tag.as_str(),
+            "p" | "div"
+                | "center"
+                | "section"
+                | "article"
+                | "header"
+                | "footer"
+                | "details"
+                | "summary"
+                | "table"
+                | "ul"
+                | "ol"
+                | "li"
+                | "h1"
+                | "h2"
+                | "h3"
+                | "h4"
+                | "h5"
+                | "h6"
+                | "hr"
+                | "blockquote"
+                | "pre"
+        )
+    }
+
+    /// Whether `align="center"` (or `<center>`) centers the content
+    pub fn is_centered(&self) -> bool {
+        self.tag == "center" || self.attr("align").is_some_and(|align| align == "center")
+    }
+
+    /// Rows of a table, looking through `thead`, `tbody` and `tfoot`
+    pub fn table_rows(&self) -> Vec<&HtmlElement> {
+        let mut rows = Vec::new();
+        for child in elements(&self.children) {
+            match child.tag.as_str() {
+                "tr" => rows.push(child),
+                "thead" | "tbody" | "tfoot" => {
+                    rows.extend(elements(&child.children).filter(|row| row.tag == "tr"))
+                }
+                _ => {}
+            }
+        }
+        rows
+    }
+}
+
+impl HtmlNode {
+    /// Text as shown: whitespace collapsed, `None` when only whitespace
+    /// (the indentation between tags)
+    pub fn visible_text(&self) -> Option<String> {
+        match self {
+            HtmlNode::Text(text) if !text.trim().is_empty() => Some(collapse_whitespace(text)),
+            _ => None,
+        }
+    }
+}
+
+/// The elements among `nodes`
+pub fn elements(nodes: &[HtmlNode]) -> impl Iterator<Item = &HtmlElement> {
+    nodes.iter().filter_map(|node| match node {
+        HtmlNode::Element(element) => Some(element),
+        HtmlNode::Text(_) => None,
+    })
+}
+
+fn collect_text(nodes: &[HtmlNode], out: &mut String) {
+    for node in nodes {
+        match node {
+            HtmlNode::Text(text) => out.push_str(text),
+            HtmlNode::Element(element) if element.tag == "br" => out.push(' '),
+            HtmlNode::Element(element) => collect_text(&element.children, out),
+        }
+    }
+}
+
+/// Runs of whitespace as a single space, like HTML lays out text
+pub fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        match c.is_whitespace() && c != '\u{a0}' {
+            true if in_space => {}
+            true => {
+                in_space = true;
+                out.push(' ');
+            }
+            false => {
+                in_space = false;
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+/// Elements without content or closing tag
+fn is_void(tag: &str) -> bool {
+    matches!(
+        tag,
+        "br" | "img" | "hr" | "input" | "meta" | "link" | "source" | "wbr" | "col" | "area"
+    )
+}
+
+/// Open elements a new `tag` implicitly closes, and the elements containing
+/// them, beyond which nothing is closed
+fn closes_open(tag: &str) -> Option<(&'static [&'static str], &'static [&'static str])> {
+    match tag {
+        "li" => Some((&["li"], &["ul", "ol"])),
+        "tr" => Some((&["tr", "td", "th"], &["table", "thead", "tbody", "tfoot"])),
+        "td" | "th" => Some((&["td", "th"], &["tr", "table"])),
+        "p" => Some((&["p"], &["div", "td", "th", "li", "details", "blockquote"])),
+        _ => None,
+    }
+}
+
+/// Parse an HTML fragment
+pub fn parse(html: &str) -> Vec<HtmlNode> {
+    let mut root: Vec<HtmlNode> = Vec::new();
+    let mut open: Vec<HtmlElement> = Vec::new();
+    let mut rest = html;
+
+    // Attach `node` to the innermost open element
+    fn push(open: &mut [HtmlElement], root: &mut Vec<HtmlNode>, node: HtmlNode) {
+        match open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => root.push(node),
+        }
+    }
+    // Close open elements down to and including the one at `index`
+    fn close_to(open: &mut Vec<HtmlElement>, root: &mut Vec<HtmlNode>, index: usize) {
+        while open.len() > index {
+            let element = open.pop().expect("open element");
+            push(open, root, HtmlNode::Element(element));
+        }
+    }
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            push(&mut open, &mut root, HtmlNode::Text(decode_entities(rest)));
+            break;
+        };
+        if start > 0 {
+            push(
+                &mut open,
+                &mut root,
+                HtmlNode::Text(decode_entities(&rest[..start])),
+            );
+        }
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+        if let Some(closing) = rest.strip_prefix("</") {
+            let end = closing.find('>').unwrap_or(closing.len());
+            let tag = closing[..end].trim().to_ascii_lowercase();
+            rest = closing.get(end + 1..).unwrap_or("");
+            if let Some(index) = open.iter().rposition(|element| element.tag == tag) {
+                close_to(&mut open, &mut root, index);
+            }
+            continue;
+        }
+        let Some((tag, after)) = parse_open_tag(&rest[1..]) else {
+            // A `<` that starts no tag is text
+            push(&mut open, &mut root, HtmlNode::Text("<".to_string()));
+            rest = &rest[1..];
+            continue;
+        };
+        rest = after;
+
+        if tag.name == "script" || tag.name == "style" {
+            let closing = format!("</{}", tag.name);
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&closing)
+                .and_then(|end| rest[end..].find('>').map(|close| &rest[end + close + 1..]))
+                .unwrap_or("");
+            continue;
+        }
+        if let Some((siblings, boundaries)) = closes_open(&tag.name) {
+            // A new row closes the open cell and the row around it
+            let mut outermost = None;
+            for (index, element) in open.iter().enumerate().rev() {
+                if boundaries.contains(&element.tag.as_str()) {
+                    break;
+                }
+                if siblings.contains(&element.tag.as_str()) {
+                    outermost = Some(index);
+                }
+            }
+            if let Some(index) = outermost {
+                close_to(&mut open, &mut root, index);
+            }
+        }
+        let element = HtmlElement::new(tag.name, tag.attrs);
+        match tag.self_closing || is_void(&element.tag) {
+            true => push(&mut open, &mut root, HtmlNode::Element(element)),
+            false => open.push(element),
+        }
+    }
+    close_to(&mut open, &mut root, 0);
+    root
+}
+
+/// An opening tag as written
+struct OpenTag {
+    name: String,
+    attrs: Vec<(String, String)>,
+    /// Whether it ends in `/>`
+    self_closing: bool,
+}
+
+/// The opening tag `text` starts with (after its `<`), and the text after
+/// its `>`
+fn parse_open_tag(text: &str) -> Option<(OpenTag, &str)> {
+    let name_len = text
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .unwrap_or(text.len());
+    if name_len == 0 || !text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut tag = OpenTag {
+        name: text[..name_len].to_ascii_lowercase(),
+        attrs: Vec::new(),
+        self_closing: false,
+    };
+    let mut rest = &text[name_len..];
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            tag.self_closing = true;
+            return Some((tag, after));
+        }
+        match rest.chars().next() {
+            None => return Some((tag, "")),
+            Some('>') => return Some((tag, &rest[1..])),
+            Some('/') => {
+                rest = &rest[1..];
+                continue;
+            }
+            Some(_) => {}
+        }
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = value[1..].find(quote).map_or(value.len(), |end| end + 1);
+                        rest = value.get(end + 1..).unwrap_or("");
+                        &value[1..end]
+                    }
+                    _ => {
+                        let end = value
+                            .find(|c: char| c.is_whitespace() || c == '>')
+                            .unwrap_or(value.len());
+                        rest = &value[end..];
+                        &value[..end]
+                    }
+                }
+            }
+            None => "",
+        };
+        tag.attrs.push((name, decode_entities(value)));
+    }
+}
+
+/// `text` with character references such as `&amp;` and `&#169;` decoded;
+/// unknown references are kept as written
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| {
+                let name = &rest[1..end + 1];
+                let c = match name {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some('\u{a0}'),
+                    "copy" => Some('©'),
+                    "reg" => Some('®'),
+                    "trade" => Some('™'),
+                    "mdash" => Some('—'),
+                    "ndash" => Some('–'),
+                    "hellip" => Some('…'),
+                    _ => match name.strip_prefix('#') {
+                        Some(hex) if hex.starts_with(['x', 'X']) => {
+                            u32::from_str_radix(&hex[1..], 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                        }
+                        Some(decimal) => decimal.parse().ok().and_then(char::from_u32),
+                        None => None,
+                    },
+                };
+                c.map(|c| (c, end + 2))
+            });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `src` and `alt` of every `<img>` in `html`
+pub fn image_sources(html: &str) -> Vec<(String, String)> {
+    fn collect(nodes: &[HtmlNode], out: &mut Vec<(String, String)>) {
+        for element in elements(nodes) {
+            match (element.tag.as_str(), element.attr("src")) {
+                ("img", Some(src)) => out.push((
+                    src.to_string(),
+                    element.attr("alt").unwrap_or_default().to_string(),
+                )),
+                _ => collect(&element.children, out),
+            }
+        }
+    }
+    let mut out = Vec::new();
+    collect(&parse(html), &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(node: &HtmlNode) -> &HtmlElement {
+        match node {
+            HtmlNode::Element(element) => element,
+            HtmlNode::Text(text) => panic!("expected an element, got text {:?}", text),
+        }
+    }
+
+    #[test]
+    fn tags_attributes_and_entities_are_parsed() {
+        let nodes = parse(
+            "<p align=\"center\">\n  <img src='logo.png' width=120px alt=\"A &amp; B\"/>\n  <br>\n  <b>Bold</b> &copy; 2024 &unknown; &#x41;\n</p>",
+        );
+        assert_eq!(nodes.len(), 1);
+        let p = element(&nodes[0]);
+        assert!(p.is_block() && p.is_centered());
+
+        let img = element(&p.children[1]);
+        assert_eq!(img.attr("src"), Some("logo.png"));
+        assert_eq!(img.attr("alt"), Some("A & B"));
+        assert_eq!(img.width(), Some(120.0));
+        assert_eq!(element(&p.children[3]).tag, "br");
+        assert_eq!(p.text(), "Bold © 2024 &unknown; A");
+        assert_eq!(p.children[0].visible_text(), None);
+    }
+
+    #[test]
+    fn unclosed_and_stray_tags_are_tolerated() {
+        let nodes = parse("<ul><li>one<li>two</ul></span><!-- note --><script>x()</script>tail");
+        let list = element(&nodes[0]);
+        let items: Vec<String> = elements(&list.children).map(HtmlElement::text).collect();
+        assert_eq!(items, vec!["one", "two"]);
+        assert_eq!(nodes[1], HtmlNode::Text("tail".to_string()));
+
+        // A fragment that only opens an element keeps it open to the end
+        let details = parse("<details>\n<summary>More</summary>\n");
+        assert_eq!(element(&details[0]).text(), "More");
+        assert!(
+            parse("</details>\n")
+                .iter()
+                .all(|node| node.visible_text().is_none())
+        );
+        assert_eq!(
+            parse("a < b"),
+            vec![
+                HtmlNode::Text("a ".to_string()),
+                HtmlNode::Text("<".to_string()),
+                HtmlNode::Text(" b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn table_rows_are_found_through_sections() {
+        let nodes = parse(
+            "<table><thead><tr><th>A<th>B</thead><tbody><tr><td>1<td>2<tr><td>3</tbody></table>",
+        );
+        let rows = element(&nodes[0]).table_rows();
+        assert_eq!(rows.len(), 3);
+        let cells: Vec<String> = elements(&rows[1].children).map(HtmlElement::text).collect();
+        assert_eq!(cells, vec!["1", "2"]);
+        assert_eq!(
+            image_sources("<a href=x><img src=\"b.svg\" alt=badge></a>"),
+            vec![("b.svg".to_string(), "badge".to_string())]
+        );
+    }
+}
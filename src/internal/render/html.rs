@@ -0,0 +1,426 @@
+//! HTML renderer backend
+//!
+//! Writes a parsed document as an HTML fragment for the HTML export. Code
+//! blocks are highlighted by syntect with CSS classes (the stylesheet comes
+//! from [`syntect_css`]), headings carry the same anchors as in the viewer and
+//! footnotes link back and forth like on screen.
+//!
+//! Images are either embedded as base64 data URIs, so the page stands on its
+//! own, or keep the path written in the document. Void elements are written
+//! self-closed so the fragment is also well-formed XHTML for the EPUB export.
+
+use base64::Engine;
+use comrak::nodes::{AstNode, ListType, NodeValue, TableAlignment};
+use std::path::Path;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator, css_for_theme_with_class_style};
+use syntect::util::LinesWithEndings;
+use tracing::{debug, warn};
+
+use crate::internal::file_handling::resolve_image_path;
+use crate::internal::inspect::node_text;
+use crate::internal::math;
+use crate::internal::render::{alert_icon, alert_title};
+use crate::internal::rendering::get_syntax_set;
+use crate::internal::theme::ActiveTheme;
+use crate::internal::toc::{HeadingAnchors, footnote_anchor, footnote_ref_anchor};
+
+/// Where images and relative paths are resolved from, and whether images are
+/// embedded
+#[derive(Debug, Clone, Copy)]
+pub struct HtmlOptions<'p> {
+    /// File the document was loaded from
+    pub markdown_file_path: Option<&'p Path>,
+    /// Inline local images as base64 data URIs
+    pub embed_images: bool,
+}
+
+/// Stylesheet for the classes emitted on highlighted code
+pub fn syntect_css(theme: &ActiveTheme) -> String {
+    css_for_theme_with_class_style(theme.syntect_theme, ClassStyle::Spaced).unwrap_or_else(|e| {
+        warn!("Failed to build code highlighting CSS: {}", e);
+        String::new()
+    })
+}
+
+/// Render a parsed document as an HTML fragment
+pub fn render_document<'a>(root: &'a AstNode<'a>, options: &HtmlOptions) -> String {
+    let mut renderer = Renderer {
+        options,
+        anchors: HeadingAnchors::default(),
+        referenced_footnotes: Vec::new(),
+        out: String::new(),
+    };
+    renderer.render(root);
+    renderer.out
+}
+
+/// Escape text for use in element content and attribute values
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+struct Renderer<'o, 'p> {
+    options: &'o HtmlOptions<'p>,
+    /// Heading anchors handed out so far
+    anchors: HeadingAnchors,
+    /// Footnotes whose first reference already carries the back-link anchor
+    referenced_footnotes: Vec<String>,
+    out: String,
+}
+
+impl Renderer<'_, '_> {
+    fn render_children<'a>(&mut self, node: &'a AstNode<'a>) {
+        for child in node.children() {
+            self.render(child);
+        }
+    }
+
+    /// Render the children of `node` wrapped in `<tag>...</tag>`
+    fn wrap<'a>(&mut self, tag: &str, node: &'a AstNode<'a>) {
+        self.out.push_str(&format!("<{tag}>"));
+        self.render_children(node);
+        self.out.push_str(&format!("</{tag}>"));
+    }
+
+    fn render<'a>(&mut self, node: &'a AstNode<'a>) {
+        match &node.data.borrow().value {
+            NodeValue::Document => self.render_children(node),
+
+            NodeValue::Paragraph => {
+                // Tight list items hold their text without paragraph spacing
+                let in_tight_list =
+                    node.parent()
+                        .and_then(|item| item.parent())
+                        .is_some_and(|list| match &list.data.borrow().value {
+                            NodeValue::List(list) => list.tight,
+                            _ => false,
+                        });
+                match in_tight_list {
+                    true => self.render_children(node),
+                    false => {
+                        self.wrap("p", node);
+                        self.out.push('\n');
+                    }
+                }
+            }
+
+            NodeValue::Heading(heading) => {
+                let id = self.anchors.assign(&node_text(node));
+                self.out
+                    .push_str(&format!("<h{} id=\"{}\">", heading.level, escape_html(&id)));
+                self.render_children(node);
+                self.out.push_str(&format!("</h{}>\n", heading.level));
+            }
+
+            NodeValue::BlockQuote => {
+                self.out.push_str("<blockquote>\n");
+                self.render_children(node);
+                self.out.push_str("</blockquote>\n");
+            }
+
+            // Same markup as GitHub, so its stylesheets apply too
+            NodeValue::Alert(alert) => {
+                self.out.push_str(&format!(
+                    "<div class=\"markdown-alert {}\">\n<p class=\"markdown-alert-title\">{} {}</p>\n",
+                    alert.alert_type.css_class(),
+                    alert_icon(alert.alert_type),
+                    escape_html(&alert_title(alert))
+                ));
+                self.render_children(node);
+                self.out.push_str("</div>\n");
+            }
+
+            NodeValue::List(list) => {
+                let (open, close) = match list.list_type {
+                    ListType::Bullet => ("<ul>".to_string(), "</ul>"),
+                    ListType::Ordered if list.start != 1 => {
+                        (format!("<ol start=\"{}\">", list.start), "</ol>")
+                    }
+                    ListType::Ordered => ("<ol>".to_string(), "</ol>"),
+                };
+                self.out.push_str(&open);
+                self.out.push('\n');
+                self.render_children(node);
+                self.out.push_str(close);
+                self.out.push('\n');
+            }
+
+            NodeValue::Item(_) => {
+                self.wrap("li", node);
+                self.out.push('\n');
+            }
+
+            NodeValue::CodeBlock(code_block) => {
+                self.out
+                    .push_str(&highlight_code(&code_block.literal, &code_block.info));
+            }
+
+            NodeValue::ThematicBreak => self.out.push_str("<hr />\n"),
+
+            NodeValue::HtmlBlock(html) => self.out.push_str(&html.literal),
+
+            NodeValue::Table(table) => {
+                let alignments = table.alignments.clone();
+                self.out.push_str("<table>\n");
+                for row in node.children() {
+                    let is_header = matches!(row.data.borrow().value, NodeValue::TableRow(true));
+                    let tag = match is_header {
+                        true => "th",
+                        false => "td",
+                    };
+                    self.out.push_str("<tr>");
+                    for (column, cell) in row.children().enumerate() {
+                        let align = match alignments.get(column) {
+                            Some(TableAlignment::Left) => " style=\"text-align: left\"",
+                            Some(TableAlignment::Center) => " style=\"text-align: center\"",
+                            Some(TableAlignment::Right) => " style=\"text-align: right\"",
+                            _ => "",
+                        };
+                        self.out.push_str(&format!("<{tag}{align}>"));
+                        self.render_children(cell);
+                        self.out.push_str(&format!("</{tag}>"));
+                    }
+                    self.out.push_str("</tr>\n");
+                }
+                self.out.push_str("</table>\n");
+            }
+
+            NodeValue::FootnoteDefinition(definition) => {
+                let number = super::footnote_number(node);
+                if number == 1 {
+                    self.out.push_str("<hr class=\"footnotes\" />\n");
+                }
+                self.out.push_str(&format!(
+                    "<div class=\"footnote\" id=\"{}\"><span class=\"footnote-number\">{}.</span><div>",
+                    escape_html(&footnote_anchor(&definition.name)),
+                    number
+                ));
+                self.render_children(node);
+                self.out.push_str(&format!(
+                    "</div><a class=\"footnote-back\" href=\"#{}\">↩</a></div>\n",
+                    escape_html(&footnote_ref_anchor(&definition.name))
+                ));
+            }
+
+            NodeValue::FootnoteReference(reference) => {
+                let id = match self.referenced_footnotes.contains(&reference.name) {
+                    true => String::new(),
+                    false => {
+                        self.referenced_footnotes.push(reference.name.clone());
+                        format!(
+                            " id=\"{}\"",
+                            escape_html(&footnote_ref_anchor(&reference.name))
+                        )
+                    }
+                };
+                self.out.push_str(&format!(
+                    "<sup class=\"footnote-ref\"><a href=\"#{}\"{}>{}</a></sup>",
+                    escape_html(&footnote_anchor(&reference.name)),
+                    id,
+                    reference.ix
+                ));
+            }
+
+            NodeValue::FrontMatter(_) => {}
+
+            NodeValue::Text(text) => self.out.push_str(&escape_html(text)),
+
+            NodeValue::Code(code) => {
+                self.out
+                    .push_str(&format!("<code>{}</code>", escape_html(&code.literal)));
+            }
+
+            NodeValue::HtmlInline(html) => self.out.push_str(html),
+
+            NodeValue::Math(formula) => {
+                let (tag, class) = match formula.display_math {
+                    true => ("div", "math display"),
+                    false => ("span", "math"),
+                };
+                self.out.push_str(&format!(
+                    "<{tag} class=\"{class}\">{}</{tag}>",
+                    escape_html(&math::to_plain(&math::parse(&formula.literal)))
+                ));
+            }
+
+            NodeValue::SoftBreak => self.out.push('\n'),
+            NodeValue::LineBreak => self.out.push_str("<br />\n"),
+            NodeValue::Emph => self.wrap("em", node),
+            NodeValue::Strong => self.wrap("strong", node),
+            NodeValue::Strikethrough => self.wrap("del", node),
+
+            NodeValue::Link(link) => {
+                self.out
+                    .push_str(&format!("<a href=\"{}\">", escape_html(&link.url)));
+                self.render_children(node);
+                self.out.push_str("</a>");
+            }
+
+            NodeValue::Image(link) => {
+                let src = self.image_source(&link.url);
+                self.out.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\" />",
+                    escape_html(&src),
+                    escape_html(&node_text(node))
+                ));
+            }
+
+            _ => self.render_children(node),
+        }
+    }
+
+    /// `src` for an image: a data URI when embedding a local file, otherwise
+    /// the URL as written
+    fn image_source(&self, url: &str) -> String {
+        let Some(markdown_file_path) = self.options.markdown_file_path else {
+            return url.to_string();
+        };
+        if !self.options.embed_images || url.contains("://") || url.starts_with("data:") {
+            return url.to_string();
+        }
+
+        let path = resolve_image_path(url, markdown_file_path);
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                debug!("Embedding image {} ({} bytes)", path, bytes.len());
+                format!(
+                    "data:{};base64,{}",
+                    image_mime_type(Path::new(&path)),
+                    base64::engine::general_purpose::STANDARD.encode(bytes)
+                )
+            }
+            Err(e) => {
+                warn!("Failed to embed image {}: {}", path, e);
+                url.to_string()
+            }
+        }
+    }
+}
+
+/// MIME type for an image file, from its extension
+pub fn image_mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A fenced code block highlighted with syntect CSS classes
+fn highlight_code(code: &str, info: &str) -> String {
+    let syntax_set = get_syntax_set();
+    let language = info.split_whitespace().next().unwrap_or("");
+    let syntax = syntax_set
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+    let mut generator =
+        ClassedHTMLGenerator::new_with_class_style(syntax, syntax_set, ClassStyle::Spaced);
+    for line in LinesWithEndings::from(code) {
+        if let Err(e) = generator.parse_html_for_line_which_includes_newline(line) {
+            warn!("Failed to highlight code block: {}", e);
+            return format!(
+                "<pre class=\"code\"><code>{}</code></pre>\n",
+                escape_html(code)
+            );
+        }
+    }
+    format!(
+        "<pre class=\"code\"><code>{}</code></pre>\n",
+        generator.finalize()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use comrak::{Arena, parse_document};
+
+    fn render(markdown: &str) -> String {
+        let arena = Arena::new();
+        let root = parse_document(&arena, markdown, &super::super::parse_options());
+        render_document(
+            root,
+            &HtmlOptions {
+                markdown_file_path: None,
+                embed_images: false,
+            },
+        )
+    }
+
+    #[test]
+    fn blocks_and_inlines_become_html() {
+        assert_eq!(
+            render("# Title\n\nSome *emph* & `code`.\n\n- one\n- two\n"),
+            "<h1 id=\"title\">Title</h1>\n<p>Some <em>emph</em> &amp; <code>code</code>.</p>\n\
+             <ul>\n<li>one</li>\n<li>two</li>\n</ul>\n"
+        );
+        assert!(render("## A\n\n## A\n").contains("<h2 id=\"a-1\">"));
+    }
+
+    #[test]
+    fn code_blocks_use_syntect_classes() {
+        let html = render("```rust\nfn main() {}\n```\n");
+        assert!(html.starts_with("<pre class=\"code\"><code>"));
+        assert!(html.contains("class=\"source rust\""));
+    }
+
+    #[test]
+    fn alerts_use_github_markup() {
+        let html = render("> [!WARNING]\n> Mind the *gap*.\n");
+        assert_eq!(
+            html,
+            "<div class=\"markdown-alert markdown-alert-warning\">\n\
+             <p class=\"markdown-alert-title\">⚠️ Warning</p>\n\
+             <p>Mind the <em>gap</em>.</p>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn footnotes_link_both_ways() {
+        let html = render("Text[^a].\n\n[^a]: Note.\n");
+        assert!(html.contains("<a href=\"#fn-a\" id=\"fnref-a\">1</a>"));
+        assert!(html.contains("id=\"fn-a\""));
+        assert!(html.contains("href=\"#fnref-a\""));
+    }
+
+    #[test]
+    fn local_images_are_embedded_as_data_uris() {
+        let dir = std::env::temp_dir().join("mdv_html_image_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("dot.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let doc = dir.join("doc.md");
+
+        let arena = Arena::new();
+        let root = parse_document(&arena, "![dot](dot.png)", &super::super::parse_options());
+        let embedded = render_document(
+            root,
+            &HtmlOptions {
+                markdown_file_path: Some(&doc),
+                embed_images: true,
+            },
+        );
+        let linked = render_document(
+            root,
+            &HtmlOptions {
+                markdown_file_path: Some(&doc),
+                embed_images: false,
+            },
+        );
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(embedded.contains("src=\"data:image/png;base64,iVBORw==\""));
+        assert!(linked.contains("src=\"dot.png\""));
+    }
+}
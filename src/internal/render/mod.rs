@@ -0,0 +1,64 @@
+//! Renderer backends
+//!
+//! Every backend walks the same comrak document tree, parsed with
+//! [`parse_options`] and passed through the plugin AST transforms, so the GUI,
+//! the focus/TOC helpers and the terminal output agree on its contents. The GUI backend
+//! lives in `rendering`; this module hosts the non-GUI backends (terminal
+//! output and the HTML export) and the outline of the GUI's elements used by
+//! the golden tests.
+
+pub mod html;
+pub mod outline;
+pub mod split;
+pub mod terminal;
+
+use comrak::Options;
+use comrak::nodes::{AlertType, AstNode, NodeAlert, NodeValue};
+
+/// comrak options shared by all renderer backends (GFM tables, footnotes,
+/// `> [!NOTE]` alerts and `$`/`$$` math enabled)
+pub fn parse_options() -> Options<'static> {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.footnotes = true;
+    options.extension.alerts = true;
+    options.extension.math_dollars = true;
+    options
+}
+
+/// Icon shown before the title of a GitHub alert
+pub fn alert_icon(alert_type: AlertType) -> &'static str {
+    match alert_type {
+        AlertType::Note => "ℹ️",
+        AlertType::Tip => "💡",
+        AlertType::Important => "❗",
+        AlertType::Warning => "⚠️",
+        AlertType::Caution => "🛑",
+    }
+}
+
+/// Title of a GitHub alert: the one written after the marker, or the name of
+/// its type
+pub fn alert_title(alert: &NodeAlert) -> String {
+    alert
+        .title
+        .clone()
+        .unwrap_or_else(|| alert.alert_type.default_title())
+}
+
+/// 1-based number of a footnote definition
+///
+/// comrak moves referenced definitions to the end of the document in the
+/// order they are first referenced, so counting the definitions up to this
+/// one gives the number its references show.
+pub fn footnote_number<'a>(definition: &'a AstNode<'a>) -> usize {
+    definition
+        .preceding_siblings()
+        .filter(|sibling| {
+            matches!(
+                sibling.data.borrow().value,
+                NodeValue::FootnoteDefinition(_)
+            )
+        })
+        .count()
+}
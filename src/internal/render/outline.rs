@@ -0,0 +1,251 @@
+//! Element outline of the GUI renderer, for golden tests
+//!
+//! gpui elements cannot be inspected once built, so renderer changes could
+//! only be checked by looking at the window. [`outline`] walks the parsed
+//! document the way `rendering` does and describes the elements it would
+//! build (kind, attributes such as list markers or table alignment, text and
+//! nesting) as a serializable [`Element`] tree. [`Element::to_snapshot`]
+//! writes the tree as indented text; `tests/golden.rs` compares it with the
+//! files in `tests/golden/`.
+
+use comrak::nodes::{ListType, NodeValue, TableAlignment};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::internal::document::{DocNode, ParsedDocument};
+use crate::internal::raw_html::{self, HtmlElement, HtmlNode};
+use crate::internal::render::{alert_icon, alert_title};
+use crate::internal::toc::{footnote_anchor, footnote_ref_anchor};
+
+/// One element the GUI renderer builds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Element {
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attrs: Vec<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            attrs: Vec::new(),
+            text: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn attr(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.attrs.push((name.into(), value.to_string()));
+        self
+    }
+
+    fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Element>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    /// The tree as indented text, one element per line, e.g.
+    /// `item marker="5."` with its children two spaces further in
+    pub fn to_snapshot(&self) -> String {
+        let mut out = String::new();
+        self.write_snapshot(0, &mut out);
+        out
+    }
+
+    fn write_snapshot(&self, depth: usize, out: &mut String) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(self.kind);
+        for (name, value) in &self.attrs {
+            out.push_str(&format!(" {}={:?}", name, value));
+        }
+        if let Some(text) = &self.text {
+            out.push_str(&format!(" {:?}", text));
+        }
+        out.push('\n');
+        for child in &self.children {
+            child.write_snapshot(depth + 1, out);
+        }
+    }
+}
+
+/// Outline of `content` as the viewer would render it from `path`
+pub fn outline(content: &str, path: &Path) -> Element {
+    element(&ParsedDocument::parse(content, path, 0).root)
+}
+
+fn children(node: &DocNode) -> Vec<Element> {
+    node.children().map(element).collect()
+}
+
+fn element(node: &DocNode) -> Element {
+    match &node.value {
+        NodeValue::Document => Element::new("document").children(children(node)),
+        NodeValue::Paragraph => Element::new("paragraph").children(children(node)),
+        NodeValue::Heading(heading) => Element::new("heading")
+            .attr("level", heading.level)
+            .children(children(node)),
+        NodeValue::Text(_) => Element::new("text").text(node.text()),
+        NodeValue::Code(code) => Element::new("code").text(code.literal.clone()),
+        NodeValue::Math(math) => Element::new("math")
+            .attr("display", math.display_math)
+            .text(math.literal.clone()),
+        NodeValue::CodeBlock(code_block) => {
+            let block = Element::new("code-block");
+            match code_block.info.is_empty() {
+                true => block,
+                false => block.attr("language", &code_block.info),
+            }
+            .text(code_block.literal.clone())
+        }
+        NodeValue::List(list) => Element::new("list")
+            .attr(
+                "type",
+                match list.list_type {
+                    ListType::Bullet => "bullet",
+                    ListType::Ordered => "ordered",
+                },
+            )
+            .attr(
+                "spacing",
+                match list.tight {
+                    true => "tight",
+                    false => "loose",
+                },
+            )
+            .children(node.children().enumerate().map(|(index, item)| {
+                Element::new("item")
+                    .attr("marker", node.item_marker(index).unwrap_or_default())
+                    .children(children(item))
+            })),
+        NodeValue::Image(link) => {
+            // The placeholder shows "Image" for images without alt text
+            let alt = node.text();
+            Element::new("image")
+                .attr("url", &link.url)
+                .text(match alt.is_empty() {
+                    true => "Image".to_string(),
+                    false => alt,
+                })
+        }
+        NodeValue::Link(link) => Element::new("link")
+            .attr("url", &link.url)
+            .text(node.text()),
+        NodeValue::FootnoteReference(reference) => Element::new("footnote-ref")
+            .attr("url", format!("#{}", footnote_anchor(&reference.name)))
+            .text(reference.ix.to_string()),
+        NodeValue::FootnoteDefinition(definition) => Element::new("footnote")
+            .attr("number", node.footnote_number.unwrap_or(1))
+            .attr(
+                "back",
+                format!("#{}", footnote_ref_anchor(&definition.name)),
+            )
+            .children(children(node)),
+        NodeValue::Strong => Element::new("strong").children(children(node)),
+        NodeValue::Emph => Element::new("emph").children(children(node)),
+        NodeValue::Strikethrough => Element::new("strikethrough").children(children(node)),
+        NodeValue::BlockQuote => Element::new("blockquote").children(children(node)),
+        NodeValue::Alert(alert) => Element::new("alert")
+            .attr("type", alert.alert_type.default_title().to_lowercase())
+            .attr("icon", alert_icon(alert.alert_type))
+            .text(alert_title(alert))
+            .children(children(node)),
+        NodeValue::Table(table) => Element::new("table")
+            .attr(
+                "columns",
+                node.children()
+                    .next()
+                    .map_or(0, |row| row.children().count()),
+            )
+            .children(node.children().map(|row| {
+                let cells = row.children().enumerate().map(|(index, cell)| {
+                    Element::new("cell")
+                        .attr("align", alignment(table.alignments.get(index)))
+                        .children(children(cell))
+                });
+                match row.value {
+                    NodeValue::TableRow(true) => Element::new("header-row"),
+                    _ => Element::new("row"),
+                }
+                .children(cells)
+            })),
+        NodeValue::HtmlBlock(html) => {
+            Element::new("html-block").children(html_nodes(&raw_html::parse(&html.literal)))
+        }
+        NodeValue::HtmlInline(html) => {
+            let mut nodes = html_nodes(&raw_html::parse(html));
+            match nodes.len() {
+                1 => nodes.remove(0),
+                _ => Element::new("container").children(nodes),
+            }
+        }
+        // Everything else is a plain container, as in the GUI
+        _ => Element::new("container").children(children(node)),
+    }
+}
+
+/// Raw HTML nodes as `html` elements with their tag and attributes,
+/// skipping the whitespace between tags
+fn html_nodes(nodes: &[HtmlNode]) -> Vec<Element> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            HtmlNode::Text(_) => node
+                .visible_text()
+                .map(|text| Element::new("text").text(text)),
+            HtmlNode::Element(element) => Some(html_element(element)),
+        })
+        .collect()
+}
+
+fn html_element(element: &HtmlElement) -> Element {
+    let mut outlined = Element::new("html").attr("tag", &element.tag);
+    for (name, value) in &element.attrs {
+        outlined = outlined.attr(name, value);
+    }
+    outlined.children(html_nodes(&element.children))
+}
+
+fn alignment(alignment: Option<&TableAlignment>) -> &'static str {
+    match alignment {
+        Some(TableAlignment::Center) => "center",
+        Some(TableAlignment::Right) => "right",
+        Some(TableAlignment::Left) | Some(TableAlignment::None) | None => "left",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_show_nesting_attributes_and_text() {
+        let tree = outline("## Hi *there*\n\n3. a\n", Path::new("doc.md"));
+        assert_eq!(
+            tree.to_snapshot(),
+            concat!(
+                "document\n",
+                "  heading level=\"2\"\n",
+                "    text \"Hi \"\n",
+                "    emph\n",
+                "      text \"there\"\n",
+                "  list type=\"ordered\" spacing=\"tight\"\n",
+                "    item marker=\"3.\"\n",
+                "      paragraph\n",
+                "        text \"a\"\n",
+            )
+        );
+
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json["children"][1]["children"][0]["attrs"][0][1], "3.");
+    }
+}
@@ -0,0 +1,110 @@
+//! Cutting a document into self-contained pieces
+//!
+//! EPUB chapters and printed pages are rendered one piece at a time. A piece
+//! is the source of a run of top-level blocks followed by every footnote and
+//! link reference definition of the document, so references resolve within
+//! the piece that uses them.
+
+use comrak::nodes::NodeValue;
+use comrak::{Arena, parse_document};
+use std::ops::Range;
+
+use crate::internal::inspect::node_text;
+
+/// A top-level block and its markdown source
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceBlock {
+    /// First source line (0-based)
+    pub start_line: usize,
+    /// Last source line (0-based, inclusive)
+    pub end_line: usize,
+    pub source: String,
+    /// Level and text when the block is a heading
+    pub heading: Option<(u8, String)>,
+}
+
+/// A document's blocks, in source order, and its definitions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SplitDocument {
+    pub blocks: Vec<SourceBlock>,
+    /// Footnote and link reference definitions, appended to every piece
+    pub definitions: Vec<String>,
+}
+
+impl SplitDocument {
+    pub fn parse(content: &str) -> Self {
+        let lines: Vec<&str> = content.lines().collect();
+        let arena = Arena::new();
+        let root = parse_document(&arena, content, &super::parse_options());
+
+        let mut covered = vec![false; lines.len()];
+        let mut split = Self::default();
+        for node in root.children() {
+            let sourcepos = node.data.borrow().sourcepos;
+            let start = sourcepos.start.line.saturating_sub(1).min(lines.len());
+            let end = sourcepos
+                .end
+                .line
+                .max(sourcepos.start.line)
+                .min(lines.len());
+            covered[start..end].fill(true);
+            let source = lines[start..end].join("\n");
+
+            match &node.data.borrow().value {
+                NodeValue::FootnoteDefinition(_) => split.definitions.push(source),
+                value => split.blocks.push(SourceBlock {
+                    start_line: start,
+                    end_line: end.saturating_sub(1).max(start),
+                    source,
+                    heading: match value {
+                        NodeValue::Heading(heading) => Some((heading.level, node_text(node))),
+                        _ => None,
+                    },
+                }),
+            }
+        }
+        // comrak moves footnote definitions to the end of the tree
+        split.blocks.sort_by_key(|block| block.start_line);
+        // Link reference definitions are not part of the tree
+        split.definitions.extend(
+            lines
+                .iter()
+                .zip(&covered)
+                .filter(|(line, covered)| !**covered && !line.trim().is_empty())
+                .map(|(line, _)| line.to_string()),
+        );
+        split
+    }
+
+    /// Markdown for the blocks in `range`, followed by every definition
+    pub fn piece(&self, range: Range<usize>) -> String {
+        self.blocks[range]
+            .iter()
+            .map(|block| block.source.as_str())
+            .chain(self.definitions.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pieces_carry_every_definition() {
+        let content =
+            "# Title\n\nText[^n] and [x].\n\n[^n]: Note.\n\n[x]: https://example.com\n\nEnd.";
+        let split = SplitDocument::parse(content);
+        assert_eq!(split.blocks.len(), 3);
+        assert_eq!(split.blocks[0].heading, Some((1, "Title".to_string())));
+        assert_eq!(
+            (split.blocks[2].start_line, split.blocks[2].end_line),
+            (8, 8)
+        );
+        assert_eq!(
+            split.piece(1..2),
+            "Text[^n] and [x].\n\n[^n]: Note.\n\n[x]: https://example.com"
+        );
+    }
+}
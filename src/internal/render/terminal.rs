@@ -0,0 +1,523 @@
+//! Terminal (ANSI) renderer backend
+//!
+//! Prints a parsed document to the terminal, in the spirit of `glow`: headings
+//! and emphasis use SGR attributes, links and quotes take their colors from
+//! the active theme, and fenced code is syntax highlighted with the theme's
+//! syntect theme. Colors are emitted as 24-bit escapes.
+//!
+//! Blocks are rendered to lists of lines so containers (lists, block quotes)
+//! can prefix their children without knowing how they were drawn.
+
+use comrak::nodes::{AstNode, ListType, NodeValue};
+use comrak::{Arena, parse_document};
+use gpui::Rgba;
+use std::path::Path;
+use syntect::easy::HighlightLines;
+use syntect::util::as_24_bit_terminal_escaped;
+
+use crate::internal::math;
+use crate::internal::render::{alert_icon, alert_title};
+use crate::internal::rendering::get_syntax_set;
+use crate::internal::theme::ActiveTheme;
+use crate::internal::transform::{self, TransformContext};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const BOLD_OFF: &str = "\x1b[22m";
+const DIM: &str = "\x1b[2m";
+const ITALIC: &str = "\x1b[3m";
+const ITALIC_OFF: &str = "\x1b[23m";
+const UNDERLINE: &str = "\x1b[4m";
+const UNDERLINE_OFF: &str = "\x1b[24m";
+const STRIKE: &str = "\x1b[9m";
+const STRIKE_OFF: &str = "\x1b[29m";
+const FG_DEFAULT: &str = "\x1b[39m";
+const BG_DEFAULT: &str = "\x1b[49m";
+
+/// Clear the screen and move the cursor to the top-left corner
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Width of horizontal rules in columns
+const RULE_WIDTH: usize = 40;
+
+/// Lines kept above the first changed line when watch mode scrolls to it
+const WATCH_CONTEXT_LINES: usize = 3;
+
+fn channel(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn fg(color: Rgba) -> String {
+    format!(
+        "\x1b[38;2;{};{};{}m",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
+
+fn bg(color: Rgba) -> String {
+    format!(
+        "\x1b[48;2;{};{};{}m",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
+
+/// Parse `content` and render it for the terminal
+///
+/// `path` is the file the content came from, passed on to transform passes.
+pub fn render_markdown(content: &str, path: Option<&Path>, theme: &ActiveTheme) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, content, &super::parse_options());
+    transform::apply(&arena, root, &TransformContext { path });
+    render_document(root, theme)
+}
+
+/// Render a parsed document as ANSI-styled text
+pub fn render_document<'a>(root: &'a AstNode<'a>, theme: &ActiveTheme) -> String {
+    let mut out = render_block(root, theme).join("\n");
+    out.push('\n');
+    out
+}
+
+/// Join the block children of `node`, separated by blank lines when `loose`
+fn render_children<'a>(node: &'a AstNode<'a>, theme: &ActiveTheme, loose: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    for child in node.children() {
+        if loose && !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(render_block(child, theme));
+    }
+    lines
+}
+
+fn render_block<'a>(node: &'a AstNode<'a>, theme: &ActiveTheme) -> Vec<String> {
+    match &node.data.borrow().value {
+        NodeValue::Document => render_children(node, theme, true),
+
+        NodeValue::BlockQuote => {
+            let bar = format!("{}│{} ", fg(theme.colors.blockquote_border_color), RESET);
+            render_children(node, theme, true)
+                .into_iter()
+                .map(|line| format!("{bar}{line}"))
+                .collect()
+        }
+
+        NodeValue::Alert(alert) => {
+            let (accent, _) = theme.colors.alert_colors(alert.alert_type);
+            let bar = format!("{}│{} ", fg(accent), RESET);
+            let title = format!(
+                "{}{BOLD}{} {}{RESET}",
+                fg(accent),
+                alert_icon(alert.alert_type),
+                alert_title(alert)
+            );
+            std::iter::once(title)
+                .chain(render_children(node, theme, true))
+                .map(|line| format!("{bar}{line}"))
+                .collect()
+        }
+
+        NodeValue::Heading(heading) => {
+            let underline = match heading.level {
+                1 => UNDERLINE,
+                _ => "",
+            };
+            vec![format!(
+                "{BOLD}{underline}{} {}{RESET}",
+                "#".repeat(heading.level as usize),
+                render_inline_children(node, theme)
+            )]
+        }
+
+        NodeValue::CodeBlock(code_block) => {
+            render_code_block(&code_block.literal, &code_block.info, theme)
+        }
+
+        NodeValue::List(list) => {
+            let mut lines = Vec::new();
+            for (index, item) in node.children().enumerate() {
+                let marker = match list.list_type {
+                    ListType::Bullet => "•".to_string(),
+                    ListType::Ordered => format!("{}.", list.start + index),
+                };
+                let indent = " ".repeat(marker.chars().count() + 1);
+                for (line_index, line) in
+                    render_children(item, theme, false).into_iter().enumerate()
+                {
+                    lines.push(match line_index {
+                        0 => format!("{marker} {line}"),
+                        _ => format!("{indent}{line}"),
+                    });
+                }
+            }
+            lines
+        }
+
+        NodeValue::Table(_) => render_table(node),
+
+        NodeValue::ThematicBreak => vec![format!("{DIM}{}{RESET}", "─".repeat(RULE_WIDTH))],
+
+        NodeValue::HtmlBlock(html) => html
+            .literal
+            .lines()
+            .map(|line| format!("{DIM}{line}{RESET}"))
+            .collect(),
+
+        NodeValue::FrontMatter(_) => Vec::new(),
+
+        NodeValue::FootnoteDefinition(_) => {
+            let number = super::footnote_number(node);
+            let marker = format!("[{number}]");
+            let indent = " ".repeat(marker.chars().count() + 1);
+            let mut lines = match number {
+                1 => vec![format!("{DIM}{}{RESET}", "─".repeat(RULE_WIDTH))],
+                _ => Vec::new(),
+            };
+            for (line_index, line) in render_children(node, theme, false).into_iter().enumerate() {
+                lines.push(match line_index {
+                    0 => format!("{DIM}{marker}{BOLD_OFF} {line}"),
+                    _ => format!("{indent}{line}"),
+                });
+            }
+            lines
+        }
+
+        _ => render_inline_children(node, theme)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    }
+}
+
+/// Keep the watch-mode viewport near the previous offset after a re-render
+///
+/// The offset is preserved when the first changed line is still on screen;
+/// otherwise the viewport moves so the change is visible with a few lines of
+/// context above it. The result is clamped to the new document length.
+///
+/// # Arguments
+/// * `previous` - Rendered lines before the change
+/// * `current` - Rendered lines after the change
+/// * `offset` - Index of the first line shown before the change
+/// * `height` - Number of lines that fit on the screen
+pub fn follow_changes(
+    previous: &[String],
+    current: &[String],
+    offset: usize,
+    height: usize,
+) -> usize {
+    let max_offset = current.len().saturating_sub(height);
+    let first_change = previous
+        .iter()
+        .zip(current)
+        .position(|(old, new)| old != new)
+        .or((previous.len() != current.len()).then(|| previous.len().min(current.len())));
+
+    let offset = match first_change {
+        Some(line) if line < offset || line >= offset + height => {
+            line.saturating_sub(WATCH_CONTEXT_LINES)
+        }
+        _ => offset,
+    };
+    offset.min(max_offset)
+}
+
+/// A paging key in watch mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchScroll {
+    LineDown,
+    LineUp,
+    PageDown,
+    PageUp,
+    Top,
+    Bottom,
+}
+
+/// The watch-mode offset after `scroll`, clamped so the last screen is full
+///
+/// A page keeps one line of the previous screen in view.
+pub fn scroll_watch(offset: usize, scroll: WatchScroll, height: usize, line_count: usize) -> usize {
+    let max_offset = line_count.saturating_sub(height);
+    let page = height.saturating_sub(1).max(1);
+    let offset = match scroll {
+        WatchScroll::LineDown => offset + 1,
+        WatchScroll::LineUp => offset.saturating_sub(1),
+        WatchScroll::PageDown => offset + page,
+        WatchScroll::PageUp => offset.saturating_sub(page),
+        WatchScroll::Top => 0,
+        WatchScroll::Bottom => max_offset,
+    };
+    offset.min(max_offset)
+}
+
+fn render_inline_children<'a>(node: &'a AstNode<'a>, theme: &ActiveTheme) -> String {
+    let mut out = String::new();
+    for child in node.children() {
+        render_inline(child, theme, &mut out);
+    }
+    out
+}
+
+fn render_inline<'a>(node: &'a AstNode<'a>, theme: &ActiveTheme, out: &mut String) {
+    match &node.data.borrow().value {
+        NodeValue::Text(text) => out.push_str(text),
+        NodeValue::Code(code) => {
+            out.push_str(&bg(theme.colors.code_bg_color));
+            out.push_str(&code.literal);
+            out.push_str(BG_DEFAULT);
+        }
+        NodeValue::HtmlInline(html) => out.push_str(html),
+        NodeValue::Math(math) => {
+            out.push_str(ITALIC);
+            out.push_str(&math::to_plain(&math::parse(&math.literal)));
+            out.push_str(ITALIC_OFF);
+        }
+        NodeValue::FootnoteReference(reference) => {
+            out.push_str(&format!("{DIM}[{}]{BOLD_OFF}", reference.ix));
+        }
+        NodeValue::SoftBreak => out.push(' '),
+        NodeValue::LineBreak => out.push('\n'),
+        NodeValue::Emph => {
+            out.push_str(ITALIC);
+            out.push_str(&render_inline_children(node, theme));
+            out.push_str(ITALIC_OFF);
+        }
+        NodeValue::Strong => {
+            out.push_str(BOLD);
+            out.push_str(&render_inline_children(node, theme));
+            out.push_str(BOLD_OFF);
+        }
+        NodeValue::Strikethrough => {
+            out.push_str(STRIKE);
+            out.push_str(&render_inline_children(node, theme));
+            out.push_str(STRIKE_OFF);
+        }
+        NodeValue::Link(link) => {
+            push_link(out, &render_inline_children(node, theme), &link.url, theme)
+        }
+        NodeValue::Image(link) => {
+            let alt_text = plain_text(node);
+            let label = match alt_text.is_empty() {
+                true => link.url.to_string(),
+                false => alt_text,
+            };
+            out.push_str(&format!("{DIM}[image: {label}]{BOLD_OFF}"));
+        }
+        _ => out.push_str(&render_inline_children(node, theme)),
+    }
+}
+
+/// Append a link, followed by its URL when that differs from the text
+fn push_link(out: &mut String, text: &str, url: &str, theme: &ActiveTheme) {
+    out.push_str(&fg(theme.colors.link_color));
+    out.push_str(UNDERLINE);
+    out.push_str(text);
+    out.push_str(UNDERLINE_OFF);
+    out.push_str(FG_DEFAULT);
+    if text != url {
+        out.push_str(&format!(" {DIM}({url}){BOLD_OFF}"));
+    }
+}
+
+/// Text content of `node` without any styling, used where widths matter
+fn plain_text<'a>(node: &'a AstNode<'a>) -> String {
+    node.descendants()
+        .filter_map(|descendant| match &descendant.data.borrow().value {
+            NodeValue::Text(text) => Some(text.to_string()),
+            NodeValue::Code(code) => Some(code.literal.to_string()),
+            NodeValue::SoftBreak => Some(" ".to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn render_code_block(code: &str, language: &str, theme: &ActiveTheme) -> Vec<String> {
+    let syntax_set = get_syntax_set();
+    let syntax = syntax_set
+        .find_syntax_by_token(language.split_whitespace().next().unwrap_or(""))
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, theme.syntect_theme);
+    let line_color = fg(theme.colors.code_line_color);
+
+    code.lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let highlighted = highlighter
+                .highlight_line(line, syntax_set)
+                .map(|ranges| as_24_bit_terminal_escaped(&ranges, false))
+                .unwrap_or_else(|_| line.to_string());
+            format!("{line_color}{:>4}{RESET}  {highlighted}{RESET}", index + 1)
+        })
+        .collect()
+}
+
+fn render_table<'a>(node: &'a AstNode<'a>) -> Vec<String> {
+    let rows: Vec<(bool, Vec<String>)> = node
+        .children()
+        .filter_map(|row| match row.data.borrow().value {
+            NodeValue::TableRow(is_header) => {
+                Some((is_header, row.children().map(plain_text).collect()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let columns = rows.iter().map(|(_, cells)| cells.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|(_, cells)| cells.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    for (is_header, cells) in &rows {
+        let row = widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| {
+                let cell = cells.get(column).map(String::as_str).unwrap_or("");
+                let padding = " ".repeat(width - cell.chars().count());
+                match is_header {
+                    true => format!(" {BOLD}{cell}{BOLD_OFF}{padding} "),
+                    false => format!(" {cell}{padding} "),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("│");
+        lines.push(format!("│{row}│"));
+        if *is_header {
+            let rule = widths
+                .iter()
+                .map(|width| "─".repeat(width + 2))
+                .collect::<Vec<_>>()
+                .join("┼");
+            lines.push(format!("├{rule}┤"));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme() -> ActiveTheme {
+        use crate::internal::theme::{ThemeColors, ThemeMode, syntect_theme};
+        let colors = ThemeColors::from_json(
+            "Test",
+            ThemeMode::Light,
+            &std::collections::HashMap::new(),
+            &serde_json::Value::Null,
+        );
+        ActiveTheme {
+            name: "Test".to_string(),
+            colors: Box::leak(Box::new(colors)),
+            syntect_theme: syntect_theme(ThemeMode::Light),
+        }
+    }
+
+    /// Drop escape sequences so assertions can look at the visible text
+    fn strip_ansi(text: &str) -> String {
+        let mut out = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\x1b' => {
+                    for c in chars.by_ref() {
+                        if c.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                }
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn headings_are_bold_and_keep_markers() {
+        let output = render_markdown("## Section", None, &theme());
+        assert!(output.contains(BOLD));
+        assert_eq!(strip_ansi(&output), "## Section\n");
+    }
+
+    #[test]
+    fn lists_quotes_and_links_are_laid_out() {
+        let output = render_markdown(
+            "3. first\n4. second\n\n> quoted\n\n[site](https://example.com)",
+            None,
+            &theme(),
+        );
+        assert_eq!(
+            strip_ansi(&output),
+            "3. first\n4. second\n\n│ quoted\n\nsite (https://example.com)\n"
+        );
+    }
+
+    #[test]
+    fn code_blocks_are_highlighted_with_line_numbers() {
+        let output = render_markdown("```rust\nfn main() {}\n```", None, &theme());
+        assert!(output.contains("\x1b[38;2;"));
+        assert_eq!(strip_ansi(&output), "   1  fn main() {}\n");
+    }
+
+    #[test]
+    fn watch_offset_follows_changes_off_screen() {
+        let lines = |count: usize| (0..count).map(|i| i.to_string()).collect::<Vec<_>>();
+        let before = lines(100);
+
+        // A change inside the viewport keeps the offset
+        let mut after = before.clone();
+        after[45] = "edited".to_string();
+        assert_eq!(follow_changes(&before, &after, 40, 20), 40);
+
+        // A change below the viewport scrolls to it with context
+        after[80] = "edited".to_string();
+        after[45] = before[45].clone();
+        assert_eq!(follow_changes(&before, &after, 40, 20), 77);
+
+        // Truncation clamps the offset to the new length
+        assert_eq!(follow_changes(&before, &lines(30), 0, 20), 10);
+        assert_eq!(follow_changes(&before, &before, 40, 20), 40);
+    }
+
+    #[test]
+    fn watch_scroll_keys_stay_within_the_document() {
+        assert_eq!(scroll_watch(0, WatchScroll::LineDown, 20, 100), 1);
+        assert_eq!(scroll_watch(0, WatchScroll::LineUp, 20, 100), 0);
+        assert_eq!(scroll_watch(10, WatchScroll::PageDown, 20, 100), 29);
+        assert_eq!(scroll_watch(75, WatchScroll::PageDown, 20, 100), 80);
+        assert_eq!(scroll_watch(10, WatchScroll::PageUp, 20, 100), 0);
+        assert_eq!(scroll_watch(40, WatchScroll::Top, 20, 100), 0);
+        assert_eq!(scroll_watch(0, WatchScroll::Bottom, 20, 100), 80);
+        // A document shorter than the screen never scrolls
+        assert_eq!(scroll_watch(0, WatchScroll::Bottom, 20, 5), 0);
+    }
+
+    #[test]
+    fn footnotes_are_numbered_below_a_rule() {
+        let output = render_markdown("Text[^note].\n\n[^note]: The note.", None, &theme());
+        assert_eq!(
+            strip_ansi(&output),
+            format!("Text[1].\n\n{}\n[1] The note.\n", "─".repeat(RULE_WIDTH))
+        );
+    }
+
+    #[test]
+    fn tables_pad_columns() {
+        let output = render_markdown("| A | Long |\n|---|---|\n| 1 | 2 |", None, &theme());
+        assert_eq!(
+            strip_ansi(&output),
+            "│ A │ Long │\n├───┼──────┤\n│ 1 │ 2    │\n"
+        );
+    }
+}
@@ -3,31 +3,35 @@
 //! This module handles rendering of the Markdown AST to GPUI elements,
 //! including support for headings, lists, code blocks, tables, and more.
 
+use super::document::DocNode;
+use super::image_loader::ImageCache;
+use super::interaction::{Affordance, ClickState, Interactive};
+use super::math::{self, MathNode};
+use super::raw_html::{self, HtmlElement, HtmlNode, elements};
+use super::render::{alert_icon, alert_title};
 use super::style::*;
-use super::theme::ThemeColors;
+use super::text_highlight::{MatchColors, MatchedPiece, render_match, split_runs_at_matches};
+use super::theme::ActiveTheme;
+use super::toc::{footnote_anchor, footnote_ref_anchor};
 use comrak::nodes::{AstNode, NodeValue};
 use gpui::{
-    AnyElement, ClipboardItem, Context, FontWeight, ImageSource, InteractiveElement, IntoElement,
-    MouseButton, Rgba, SharedString, div, img, prelude::*, px,
+    AnyElement, ClipboardItem, Context, FontWeight, InteractiveElement, IntoElement, MouseButton,
+    MouseDownEvent, Rgba, SharedString, div, img, prelude::*, px,
 };
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
 use std::sync::OnceLock;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 use tracing::{debug, error};
 
 static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
-static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
 
-fn get_syntax_set() -> &'static SyntaxSet {
+pub(crate) fn get_syntax_set() -> &'static SyntaxSet {
     SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
 }
 
-fn get_theme_set() -> &'static ThemeSet {
-    THEME_SET.get_or_init(ThemeSet::load_defaults)
-}
-
 fn syntect_color_to_gpui(color: syntect::highlighting::Color) -> Rgba {
     Rgba {
         r: color.r as f32 / 255.0,
@@ -60,28 +64,24 @@ fn calculate_column_width(num_columns: usize, viewport_width: f32) -> (f32, bool
     }
 }
 
+/// Render a fenced code block with syntax colors and line numbers
+///
+/// Search matches get their highlight backgrounds over the syntax colors,
+/// which are kept for the matched text as well.
 fn render_highlighted_code_block<T: 'static>(
     code: String,
     language: String,
-    theme_colors: &ThemeColors,
+    search_state: Option<&super::search::SearchState>,
+    theme: &ActiveTheme,
     cx: &mut Context<T>,
 ) -> AnyElement {
     let syntax_set = get_syntax_set();
-    let theme_set = get_theme_set();
-
-    // Use theme-appropriate syntect theme
-    let syntect_theme_name = theme_colors.mode.syntect_theme();
-    let theme = theme_set
-        .themes
-        .get(syntect_theme_name)
-        .or_else(|| theme_set.themes.values().next())
-        .unwrap();
 
     let syntax = syntax_set
         .find_syntax_by_token(&language)
         .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
 
-    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut highlighter = HighlightLines::new(syntax, theme.syntect_theme);
     let mut lines = Vec::new();
 
     for (i, line) in code.lines().enumerate() {
@@ -89,22 +89,32 @@ fn render_highlighted_code_block<T: 'static>(
             .highlight_line(line, syntax_set)
             .unwrap_or_default();
 
-        let mut line_elements = Vec::new();
-        for (style, text) in ranges {
-            let color = syntect_color_to_gpui(style.foreground);
-            line_elements.push(
-                div()
-                    .text_color(color)
-                    .child(text.to_string())
-                    .into_any_element(),
-            );
-        }
+        let matches = search_state
+            .map(|state| state.matches_in(line))
+            .unwrap_or_default();
+        let line_elements = match search_state.filter(|_| !matches.is_empty()) {
+            Some(search_state) => render_code_line_with_matches(
+                &ranges,
+                &matches,
+                search_state,
+                MatchColors::from_theme(theme.colors),
+            ),
+            None => ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    div()
+                        .text_color(syntect_color_to_gpui(style.foreground))
+                        .child(text.to_string())
+                        .into_any_element()
+                })
+                .collect(),
+        };
 
         // Line number
         let line_number = div()
             .w_8()
             .mr_4()
-            .text_color(theme_colors.code_line_color)
+            .text_color(theme.colors.code_line_color)
             .justify_end()
             .flex()
             .child((i + 1).to_string());
@@ -119,16 +129,19 @@ fn render_highlighted_code_block<T: 'static>(
     }
 
     let copy_code = code.clone();
+    let mut hasher = DefaultHasher::new();
+    code.hash(&mut hasher);
     let copy_button = div()
+        .id(("copy-code", hasher.finish()))
         .absolute()
         .top_2()
         .right_2()
-        .bg(theme_colors.copy_button_bg_color)
-        .text_color(theme_colors.copy_button_text_color)
+        .bg(theme.colors.copy_button_bg_color)
+        .text_color(theme.colors.copy_button_text_color)
         .px_2()
         .py_1()
         .rounded_md()
-        .cursor_pointer()
+        .interactive(Affordance::Button, ClickState::default(), theme.colors)
         .child("Copy")
         .on_mouse_down(
             MouseButton::Left,
@@ -140,7 +153,7 @@ fn render_highlighted_code_block<T: 'static>(
     div()
         .relative()
         .group("code_block")
-        .bg(theme_colors.code_bg_color)
+        .bg(theme.colors.code_bg_color)
         .p_3()
         .rounded_md()
         .font_family(CODE_FONT)
@@ -155,37 +168,249 @@ fn render_highlighted_code_block<T: 'static>(
         .into_any_element()
 }
 
-/// Helper: collect inline text content for wrapping within block containers
-fn collect_text<'a>(node: &'a AstNode<'a>) -> String {
-    let mut out = String::new();
-    match &node.data.borrow().value {
-        NodeValue::Text(text) => out.push_str(&String::from_utf8_lossy(text.as_bytes())),
-        NodeValue::Code(code) => out.push_str(&String::from_utf8_lossy(code.literal.as_bytes())),
-        NodeValue::LineBreak | NodeValue::SoftBreak => out.push('\n'),
+/// Syntax-colored spans of a code line with search match backgrounds
+///
+/// The spans are cut at match boundaries; the pieces of one match are
+/// grouped so each match gets one background (and one border when current).
+fn render_code_line_with_matches(
+    ranges: &[(syntect::highlighting::Style, &str)],
+    matches: &[super::search::MatchPosition],
+    search_state: &super::search::SearchState,
+    colors: MatchColors,
+) -> Vec<AnyElement> {
+    let colored = |piece: &MatchedPiece<syntect::highlighting::Style>| {
+        div()
+            .text_color(syntect_color_to_gpui(piece.style.foreground))
+            .child(piece.text.to_string())
+            .into_any_element()
+    };
+
+    let pieces = split_runs_at_matches(ranges, matches);
+    pieces
+        .chunk_by(|a, b| a.in_match.is_some() && a.in_match == b.in_match)
+        .map(|group| match group[0].in_match {
+            Some(_) => render_match(
+                div().flex().children(group.iter().map(colored)),
+                search_state.next_rendered_is_current(),
+                colors,
+            ),
+            None => colored(&group[0]),
+        })
+        .collect()
+}
+
+/// Render a `$...$` or `$$...$$` formula as Unicode glyph runs
+///
+/// Display formulas get a centered line of their own.
+fn render_math(tex: &str, display: bool, theme: &ActiveTheme) -> AnyElement {
+    let formula = div()
+        .flex()
+        .flex_row()
+        .items_center()
+        .px_1()
+        .text_color(theme.colors.text_color)
+        .children(render_math_nodes(&math::parse(tex), theme));
+    match display {
+        true => div()
+            .w_full()
+            .flex()
+            .justify_center()
+            .my_2()
+            .child(formula)
+            .into_any_element(),
+        false => formula.into_any_element(),
+    }
+}
+
+fn render_math_nodes(nodes: &[MathNode], theme: &ActiveTheme) -> Vec<AnyElement> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            // Letters are set in italic like TeX variables
+            MathNode::Text(text) => div()
+                .flex()
+                .flex_row()
+                .children(text.chars().map(|c| match c.is_alphabetic() {
+                    true => div().italic().child(c.to_string()),
+                    false => div().child(c.to_string()),
+                }))
+                .into_any_element(),
+            MathNode::Superscript(inner) => div()
+                .flex()
+                .flex_row()
+                .relative()
+                .top(px(-MATH_SCRIPT_SHIFT))
+                .text_size(px(MATH_SCRIPT_SIZE))
+                .children(render_math_nodes(inner, theme))
+                .into_any_element(),
+            MathNode::Subscript(inner) => div()
+                .flex()
+                .flex_row()
+                .relative()
+                .top(px(MATH_SCRIPT_SHIFT))
+                .text_size(px(MATH_SCRIPT_SIZE))
+                .children(render_math_nodes(inner, theme))
+                .into_any_element(),
+            MathNode::Fraction(numerator, denominator) => div()
+                .flex()
+                .flex_col()
+                .items_center()
+                .mx_1()
+                .child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .children(render_math_nodes(numerator, theme)),
+                )
+                .child(div().w_full().h(px(1.0)).bg(theme.colors.text_color))
+                .child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .children(render_math_nodes(denominator, theme)),
+                )
+                .into_any_element(),
+            MathNode::Sqrt(inner) => div()
+                .flex()
+                .flex_row()
+                .child("√")
+                .child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .border_t_1()
+                        .border_color(theme.colors.text_color)
+                        .children(render_math_nodes(inner, theme)),
+                )
+                .into_any_element(),
+        })
+        .collect()
+}
+
+/// Render a run of plain text, highlighting search matches when searching
+fn render_text(
+    text: String,
+    search_state: Option<&super::search::SearchState>,
+    theme: &ActiveTheme,
+) -> AnyElement {
+    match search_state {
+        Some(search_state) => {
+            let elements = super::text_highlight::render_text_with_search(
+                &text,
+                Some(search_state),
+                MatchColors::from_theme(theme.colors),
+            );
+            div()
+                .flex()
+                .flex_row()
+                .children(elements)
+                .into_any_element()
+        }
+        None => div().child(text).into_any_element(),
+    }
+}
+
+/// Owner of rendered markdown that decides what a link click does
+///
+/// The default opens the URL in the system browser; `MarkdownViewer` first
+/// offers the click to its event subscribers and handles `#anchor` links and
+/// links to other markdown files itself.
+pub trait LinkHandler: Sized + 'static {
+    fn open_link(&mut self, url: &str, _cx: &mut Context<Self>) {
+        open_in_browser(url);
+    }
+
+    /// Copy a permalink to the heading with `anchor`, as `file.md#anchor`
+    /// when `with_file` is set
+    fn copy_heading_anchor(&mut self, anchor: &str, _with_file: bool, cx: &mut Context<Self>) {
+        cx.write_to_clipboard(ClipboardItem::new_string(format!("#{}", anchor)));
+    }
+
+    /// Show the image cached under `key` enlarged; ignored by default
+    fn open_image(&mut self, _key: &str, _alt: &str, _cx: &mut Context<Self>) {}
+}
+
+/// The `#fragment` of a link, if it has a non-empty one
+///
+/// `#usage` and `guide.md#usage` both point at the `usage` heading anchor.
+pub fn link_anchor(url: &str) -> Option<&str> {
+    url.split_once('#')
+        .map(|(_, anchor)| anchor)
+        .filter(|anchor| !anchor.is_empty())
+}
+
+/// Open `url` with [`open_url`] on a background thread, logging the outcome
+pub fn open_in_browser(url: &str) {
+    let url_to_open = url.to_string();
+    std::thread::spawn(move || match open_url(&url_to_open) {
+        Ok(_) => debug!("Successfully spawned open command for {}", url_to_open),
+        Err(e) => error!("Failed to open URL '{}': {}", url_to_open, e),
+    });
+}
+
+/// Whether the keyboard focus is on the link to `url`
+fn is_link_focused(focused_element: Option<&super::viewer::FocusableElement>, url: &str) -> bool {
+    matches!(focused_element, Some(super::viewer::FocusableElement::Link(focused_url)) if focused_url == url)
+}
+
+/// Render a link that opens `url` in the system browser when clicked
+///
+/// An empty URL is rendered as plain text without a click handler. Search
+/// matches in the link text are highlighted like in plain text.
+fn render_link<T: LinkHandler>(
+    url: String,
+    link_text: String,
+    is_focused: bool,
+    search_state: Option<&super::search::SearchState>,
+    theme: &ActiveTheme,
+    cx: &mut Context<T>,
+) -> AnyElement {
+    let link_text = render_text(link_text, search_state, theme);
+    match url.trim() {
+        "" => div()
+            .text_color(theme.colors.text_color)
+            .child(link_text)
+            .into_any_element(),
         _ => {
-            for child in node.children() {
-                out.push_str(&collect_text(child));
-            }
+            // clickable
+            let click_url = url.clone();
+            div()
+                .id(SharedString::from(url))
+                .text_color(theme.colors.link_color)
+                .underline()
+                .interactive(
+                    Affordance::Link,
+                    ClickState::default().focused(is_focused),
+                    theme.colors,
+                )
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, _, _, cx| {
+                        debug!("Mouse down detected on link: {}", click_url);
+                        this.open_link(&click_url, cx);
+                    }),
+                )
+                .child(link_text)
+                .into_any_element()
         }
     }
-    out
 }
 
 /// Render a Markdown AST node to a GPUI element with context
 ///
 /// This internal function accepts an optional markdown file path for resolving relative image paths.
 #[allow(clippy::too_many_arguments)]
-fn render_markdown_ast_internal<'a, T: 'static>(
-    node: &'a AstNode<'a>,
+fn render_markdown_ast_internal<T: LinkHandler>(
+    node: &DocNode,
     markdown_file_path: Option<&Path>,
     search_state: Option<&super::search::SearchState>,
     viewport_width: f32,
-    theme_colors: &ThemeColors,
+    theme: &ActiveTheme,
     cx: &mut Context<T>,
-    image_loader: &mut dyn FnMut(&str) -> Option<ImageSource>,
+    images: &mut ImageCache,
     focused_element: Option<&super::viewer::FocusableElement>,
 ) -> AnyElement {
-    match &node.data.borrow().value {
+    match &node.value {
         NodeValue::Document => div()
             .flex_col()
             .children(node.children().map(|child| {
@@ -194,9 +419,9 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                     markdown_file_path,
                     search_state,
                     viewport_width,
-                    theme_colors,
+                    theme,
                     cx,
-                    image_loader,
+                    images,
                     focused_element,
                 )
             }))
@@ -204,12 +429,8 @@ fn render_markdown_ast_internal<'a, T: 'static>(
 
         NodeValue::Paragraph => {
             // Avoid extra spacing inside list items.
-            let is_in_list_item = node
-                .parent()
-                .is_some_and(|p| matches!(p.data.borrow().value, NodeValue::Item(_)));
-
             let mut p = div().w_full().flex().flex_row().flex_wrap();
-            if !is_in_list_item {
+            if !node.in_list_item {
                 p = p.mb_2();
             }
             p.children(node.children().map(|child| {
@@ -218,9 +439,9 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                     markdown_file_path,
                     search_state,
                     viewport_width,
-                    theme_colors,
+                    theme,
                     cx,
-                    image_loader,
+                    images,
                     focused_element,
                 )
             }))
@@ -236,12 +457,30 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                 5 => px(H5_SIZE),
                 _ => px(H6_SIZE),
             };
+            // A "#" shown on hover copies the heading's permalink
+            let permalink = node.anchor.clone().map(|anchor| {
+                div()
+                    .id(SharedString::from(format!("permalink-{}", anchor)))
+                    .ml_2()
+                    .text_color(theme.colors.link_color)
+                    .interactive(Affordance::Link, ClickState::default(), theme.colors)
+                    .invisible()
+                    .group_hover("heading", |style| style.visible())
+                    .child("#")
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, event: &MouseDownEvent, _, cx| {
+                            this.copy_heading_anchor(&anchor, event.modifiers.shift, cx);
+                        }),
+                    )
+            });
             {
                 div()
                     .w_full()
                     .flex()
                     .flex_row()
                     .flex_wrap()
+                    .group("heading")
                     .text_size(text_size)
                     .font_weight(FontWeight::SEMIBOLD)
                     .mt(px((heading.level == 1) as u8 as f32 * 4.0))
@@ -251,109 +490,150 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                             markdown_file_path,
                             search_state,
                             viewport_width,
-                            theme_colors,
+                            theme,
                             cx,
-                            image_loader,
+                            images,
                             focused_element,
                         )
                     }))
+                    .children(permalink)
                     .into_any_element()
             }
         }
 
         NodeValue::Text(text) => {
             let text_str = String::from_utf8_lossy(text.as_bytes()).to_string();
-
-            // Use search highlighting if search is active
-            match search_state {
-                Some(search_state) => {
-                    let elements = super::text_highlight::render_text_with_search(
-                        &text_str,
-                        Some(search_state),
-                    );
-                    div()
-                        .flex()
-                        .flex_row()
-                        .children(elements)
-                        .into_any_element()
-                }
-                None => div().child(text_str).into_any_element(),
-            }
+            render_text(text_str, search_state, theme)
         }
 
         NodeValue::Code(code) => div()
             .font_family(CODE_FONT)
-            .bg(theme_colors.code_bg_color)
-            .text_color(theme_colors.text_color)
+            .bg(theme.colors.code_bg_color)
+            .text_color(theme.colors.text_color)
             .px_1()
             .rounded_sm()
-            .child(String::from_utf8_lossy(code.literal.as_bytes()).to_string())
+            .child(render_text(
+                String::from_utf8_lossy(code.literal.as_bytes()).to_string(),
+                search_state,
+                theme,
+            ))
             .into_any_element(),
 
+        NodeValue::Math(math) => render_math(&math.literal, math.display_math, theme),
+
         NodeValue::CodeBlock(code_block) => {
             let language = code_block.info.clone();
             let code = code_block.literal.clone();
-            render_highlighted_code_block(code, language, theme_colors, cx)
+            render_highlighted_code_block(code, language, search_state, theme, cx)
         }
 
         NodeValue::List(list) => {
             let mut items = Vec::new();
-            for item in node.children() {
-                let marker = match list.list_type {
-                    comrak::nodes::ListType::Bullet => "•".to_string(),
-                    comrak::nodes::ListType::Ordered => format!("{}.", items.len() + 1),
-                };
+            for (index, item) in node.children().enumerate() {
+                let marker = node.item_marker(index).unwrap_or_default();
                 let content = div().w_full().children(item.children().map(|child| {
                     render_markdown_ast_internal(
                         child,
                         markdown_file_path,
                         search_state,
                         viewport_width,
-                        theme_colors,
+                        theme,
                         cx,
-                        image_loader,
+                        images,
                         focused_element,
                     )
                 }));
-                items.push(
+                // Loose lists (items separated by blank lines) get more room
+                let item = div().flex().w_full().child(
                     div()
-                        .flex()
-                        .w_full()
-                        .mb_1()
-                        .child(div().mr_2().child(marker))
-                        .child(content),
+                        .flex_none()
+                        .min_w(px(LIST_MARKER_WIDTH))
+                        .mr_2()
+                        .child(marker),
                 );
+                items.push(match list.tight {
+                    true => item.mb_1().child(content),
+                    false => item.mb_3().child(content),
+                });
             }
+            // Nested lists sit in their parent item, so each level is
+            // indented past the marker of the one above
             div().flex_col().pl_4().children(items).into_any_element()
         }
 
         NodeValue::Image(link) => {
-            use super::file_handling::resolve_image_path;
-
             let image_url = link.url.clone();
-            let alt_text = collect_text(node);
+            let alt_text = node.text();
 
             debug!("Rendering image '{}' -> '{}'", alt_text, image_url);
 
-            // Resolve image path
-            let resolved_path = match markdown_file_path {
-                Some(md_path) => resolve_image_path(&image_url, md_path),
-                None => image_url.to_string(),
-            };
+            let resolved_path = images.resolve(&image_url, markdown_file_path);
 
             debug!("Resolved image path: {}", resolved_path);
 
-            match image_loader(&resolved_path) {
-                Some(source) => div()
+            match images.image_for(&resolved_path) {
+                Some(source) => {
+                    let key = resolved_path.clone();
+                    div()
+                        .w_full()
+                        .flex()
+                        .justify_center()
+                        .my_2()
+                        .child(
+                            div()
+                                .id(SharedString::from(format!("image-{}", resolved_path)))
+                                .interactive(
+                                    Affordance::Button,
+                                    ClickState::default(),
+                                    theme.colors,
+                                )
+                                .on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(move |this, event: &MouseDownEvent, _, cx| {
+                                        // Alt-click shows the block in the source view
+                                        if !event.modifiers.alt {
+                                            this.open_image(&key, &alt_text, cx);
+                                        }
+                                    }),
+                                )
+                                .child(
+                                    img(source)
+                                        .w(px(IMAGE_MAX_WIDTH))
+                                        .object_fit(gpui::ObjectFit::Contain)
+                                        .rounded(px(IMAGE_BORDER_RADIUS)),
+                                ),
+                        )
+                        .into_any_element()
+                }
+                // Failed images show their alt text in place of the image,
+                // so the document stays readable offline
+                None if images.failed(&resolved_path) => div()
                     .w_full()
-                    .flex()
-                    .justify_center()
                     .my_2()
+                    .px_4()
+                    .py_3()
+                    .border_l_4()
+                    .border_color(theme.colors.image_placeholder_border_color)
+                    .bg(theme.colors.image_placeholder_bg_color)
+                    .child(
+                        div()
+                            .text_size(px(12.0))
+                            .text_color(theme.colors.image_placeholder_label_color)
+                            .mb_1()
+                            .child("⚠️ Image not available"),
+                    )
+                    .child(div().text_color(theme.colors.text_color).italic().child(
+                        match alt_text.trim().is_empty() {
+                            false => alt_text,
+                            true => "(no alt text)".to_string(),
+                        },
+                    ))
                     .child(
-                        img(source)
-                            .w(px(IMAGE_MAX_WIDTH))
-                            .object_fit(gpui::ObjectFit::Contain)
-                            .rounded(px(IMAGE_BORDER_RADIUS)),
+                        div()
+                            .text_size(px(12.0))
+                            .text_color(theme.colors.image_placeholder_path_color)
+                            .mt_1()
+                            .child(resolved_path),
                     )
                     .into_any_element(),
                 None => {
@@ -365,33 +645,18 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                         .items_center()
                         .my_2()
                         .p_4()
-                        .bg(Rgba {
-                            r: 0.95,
-                            g: 0.95,
-                            b: 0.95,
-                            a: 1.0,
-                        })
+                        .bg(theme.colors.image_placeholder_bg_color)
                         .border_1()
-                        .border_color(Rgba {
-                            r: 0.8,
-                            g: 0.8,
-                            b: 0.8,
-                            a: 1.0,
-                        })
+                        .border_color(theme.colors.image_placeholder_border_color)
                         .rounded(px(IMAGE_BORDER_RADIUS))
                         .child(
                             div()
-                                .text_color(Rgba {
-                                    r: 0.4,
-                                    g: 0.4,
-                                    b: 0.4,
-                                    a: 1.0,
-                                })
+                                .text_color(theme.colors.image_placeholder_label_color)
                                 .font_weight(FontWeight::BOLD)
                                 .mb_2()
                                 .child("🖼️ Image"),
                         )
-                        .child(div().text_color(theme_colors.text_color).child(
+                        .child(div().text_color(theme.colors.text_color).child(
                             match alt_text.is_empty() {
                                 false => alt_text,
                                 true => "Image".to_string(),
@@ -400,12 +665,7 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                         .child(
                             div()
                                 .text_size(px(12.0))
-                                .text_color(Rgba {
-                                    r: 0.5,
-                                    g: 0.5,
-                                    b: 0.5,
-                                    a: 1.0,
-                                })
+                                .text_color(theme.colors.image_placeholder_path_color)
                                 .mt_1()
                                 .child(resolved_path),
                         )
@@ -417,57 +677,69 @@ fn render_markdown_ast_internal<'a, T: 'static>(
         NodeValue::Link(link) => {
             // Convert URL to owned String for capture in closure
             let url = link.url.clone();
-            let link_text = collect_text(node);
+            let link_text = node.text();
 
             debug!("Rendering link '{}' -> '{}'", link_text, url);
 
-            // Check if this link is currently focused
-            let is_focused = match focused_element {
-                Some(focused_el) => {
-                    matches!(focused_el, super::viewer::FocusableElement::Link(focused_url) if focused_url == &url)
-                }
-                None => false,
-            };
+            let is_focused = is_link_focused(focused_element, &url);
+            render_link(url, link_text, is_focused, search_state, theme, cx)
+        }
 
-            // If URL is empty, render it as plain text (muted) and do not attach
-            // a click handler. Otherwise, style it as a link and attach a handler
-            // that opens the URL in the system browser.
-            match url.trim() {
-                "" => div()
-                    .text_color(theme_colors.text_color)
-                    .child(link_text)
-                    .into_any_element(),
-                _ => {
-                    // clickable
-                    let click_url = url.clone();
-                    div()
-                        .text_color(theme_colors.link_color)
-                        .underline()
-                        .cursor_pointer()
-                        .when(is_focused, |div| div.font_weight(FontWeight::BOLD))
-                        .hover(|style| style.text_color(theme_colors.hover_link_color))
-                        .id(SharedString::from(url.clone()))
-                        .on_mouse_down(
-                            MouseButton::Left,
-                            cx.listener(move |_, _, _, _| {
-                                debug!("Mouse down detected on link: {}", click_url);
-                                // Log and open the URL on a background thread.
-                                let url_to_open = click_url.clone();
-                                std::thread::spawn(move || match open_url(&url_to_open) {
-                                    Ok(_) => {
-                                        debug!(
-                                            "Successfully spawned open command for {}",
-                                            url_to_open
-                                        )
-                                    }
-                                    Err(e) => error!("Failed to open URL '{}': {}", url_to_open, e),
-                                });
-                            }),
-                        )
-                        .child(link_text)
-                        .into_any_element()
-                }
-            }
+        NodeValue::FootnoteReference(reference) => {
+            let url = format!("#{}", footnote_anchor(&reference.name));
+            let is_focused = is_link_focused(focused_element, &url);
+            div()
+                .relative()
+                .top(px(-FOOTNOTE_REF_SHIFT))
+                .text_size(px(FOOTNOTE_REF_SIZE))
+                .child(render_link(
+                    url,
+                    reference.ix.to_string(),
+                    is_focused,
+                    search_state,
+                    theme,
+                    cx,
+                ))
+                .into_any_element()
+        }
+
+        // Definitions sit at the end of the document and form the footnote section
+        NodeValue::FootnoteDefinition(definition) => {
+            let number = node.footnote_number.unwrap_or(1);
+            let back_url = format!("#{}", footnote_ref_anchor(&definition.name));
+            let is_focused = is_link_focused(focused_element, &back_url);
+            div()
+                .flex()
+                .w_full()
+                .mb_1()
+                .when(number == 1, |el| {
+                    el.mt_4()
+                        .pt_2()
+                        .border_t_1()
+                        .border_color(theme.colors.table_border_color)
+                })
+                .child(div().mr_2().child(format!("{}.", number)))
+                .child(div().flex_1().children(node.children().map(|child| {
+                    render_markdown_ast_internal(
+                        child,
+                        markdown_file_path,
+                        search_state,
+                        viewport_width,
+                        theme,
+                        cx,
+                        images,
+                        focused_element,
+                    )
+                })))
+                .child(div().ml_2().child(render_link(
+                    back_url,
+                    "↩".to_string(),
+                    is_focused,
+                    search_state,
+                    theme,
+                    cx,
+                )))
+                .into_any_element()
         }
 
         NodeValue::Strong => div()
@@ -478,9 +750,9 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                     markdown_file_path,
                     search_state,
                     viewport_width,
-                    theme_colors,
+                    theme,
                     cx,
-                    image_loader,
+                    images,
                     focused_element,
                 )
             }))
@@ -494,9 +766,9 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                     markdown_file_path,
                     search_state,
                     viewport_width,
-                    theme_colors,
+                    theme,
                     cx,
-                    image_loader,
+                    images,
                     focused_element,
                 )
             }))
@@ -510,9 +782,9 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                     markdown_file_path,
                     search_state,
                     viewport_width,
-                    theme_colors,
+                    theme,
                     cx,
-                    image_loader,
+                    images,
                     focused_element,
                 )
             }))
@@ -520,7 +792,7 @@ fn render_markdown_ast_internal<'a, T: 'static>(
 
         NodeValue::BlockQuote => div()
             .border_l_4()
-            .border_color(theme_colors.blockquote_border_color)
+            .border_color(theme.colors.blockquote_border_color)
             .pl_4()
             .italic()
             .children(node.children().map(|child| {
@@ -529,14 +801,52 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                     markdown_file_path,
                     search_state,
                     viewport_width,
-                    theme_colors,
+                    theme,
                     cx,
-                    image_loader,
+                    images,
                     focused_element,
                 )
             }))
             .into_any_element(),
 
+        // GitHub alerts: a tinted box with an icon and title in the accent color
+        NodeValue::Alert(alert) => {
+            let (accent, background) = theme.colors.alert_colors(alert.alert_type);
+            div()
+                .w_full()
+                .mb_2()
+                .border_l_4()
+                .border_color(accent)
+                .bg(background)
+                .rounded_r(px(ALERT_BORDER_RADIUS))
+                .px_4()
+                .py_2()
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .mb_1()
+                        .text_color(accent)
+                        .font_weight(FontWeight::SEMIBOLD)
+                        .child(alert_icon(alert.alert_type))
+                        .child(alert_title(alert)),
+                )
+                .children(node.children().map(|child| {
+                    render_markdown_ast_internal(
+                        child,
+                        markdown_file_path,
+                        search_state,
+                        viewport_width,
+                        theme,
+                        cx,
+                        images,
+                        focused_element,
+                    )
+                }))
+                .into_any_element()
+        }
+
         // Table rendering with responsive column widths
         NodeValue::Table(table_data) => {
             // Count columns from first row
@@ -555,7 +865,7 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                 .w_full()
                 .my_2()
                 .border_1()
-                .border_color(theme_colors.table_border_color)
+                .border_color(theme.colors.table_border_color)
                 .children(node.children().map(|row| {
                     render_table_row(
                         row,
@@ -564,9 +874,9 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                         markdown_file_path,
                         search_state,
                         viewport_width,
-                        theme_colors,
+                        theme,
                         cx,
-                        image_loader,
+                        images,
                         focused_element,
                     )
                 }))
@@ -584,9 +894,9 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                         markdown_file_path,
                         search_state,
                         viewport_width,
-                        theme_colors,
+                        theme,
                         cx,
-                        image_loader,
+                        images,
                         focused_element,
                     )
                 }))
@@ -603,15 +913,57 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                         markdown_file_path,
                         search_state,
                         viewport_width,
-                        theme_colors,
+                        theme,
                         cx,
-                        image_loader,
+                        images,
                         focused_element,
                     )
                 }))
                 .into_any_element()
         }
 
+        // Raw HTML: the supported subset is rendered, other tags show their content
+        NodeValue::HtmlBlock(html) => div()
+            .w_full()
+            .flex()
+            .flex_row()
+            .flex_wrap()
+            .items_center()
+            .children(render_html_nodes(
+                &raw_html::parse(&html.literal),
+                markdown_file_path,
+                search_state,
+                viewport_width,
+                theme,
+                cx,
+                images,
+                focused_element,
+            ))
+            .into_any_element(),
+
+        // Inline tags come one per node, so `<b>` and `</b>` style nothing
+        // and only `<br>`, `<img>` and complete elements show up
+        NodeValue::HtmlInline(html) => {
+            let mut elements = render_html_nodes(
+                &raw_html::parse(html),
+                markdown_file_path,
+                search_state,
+                viewport_width,
+                theme,
+                cx,
+                images,
+                focused_element,
+            );
+            match elements.len() {
+                1 => elements.remove(0),
+                _ => div()
+                    .flex()
+                    .flex_row()
+                    .children(elements)
+                    .into_any_element(),
+            }
+        }
+
         // Fallback: walk children
         _ => div()
             .children(node.children().map(|child| {
@@ -620,9 +972,9 @@ fn render_markdown_ast_internal<'a, T: 'static>(
                     markdown_file_path,
                     search_state,
                     viewport_width,
-                    theme_colors,
+                    theme,
                     cx,
-                    image_loader,
+                    images,
                     focused_element,
                 )
             }))
@@ -636,20 +988,20 @@ fn render_markdown_ast_internal<'a, T: 'static>(
 /// Render a Markdown AST node to a GPUI element
 ///
 /// This is the simplest rendering function that uses default settings.
-pub fn render_markdown_ast<'a, T: 'static>(
+pub fn render_markdown_ast<'a, T: LinkHandler>(
     node: &'a AstNode<'a>,
-    theme_colors: &ThemeColors,
+    theme: &ActiveTheme,
     cx: &mut Context<T>,
 ) -> AnyElement {
     const DEFAULT_VIEWPORT_WIDTH: f32 = 1200.0;
     render_markdown_ast_internal(
-        node,
+        &DocNode::from_ast(node),
         None,
         None,
         DEFAULT_VIEWPORT_WIDTH,
-        theme_colors,
+        theme,
         cx,
-        &mut |_| None,
+        &mut ImageCache::default(),
         None,
     )
 }
@@ -657,22 +1009,22 @@ pub fn render_markdown_ast<'a, T: 'static>(
 /// Render a Markdown AST node to a GPUI element with markdown file path context
 ///
 /// This version accepts the markdown file path to enable proper resolution of relative image paths.
-pub fn render_markdown_ast_with_loader<'a, T: 'static>(
+pub fn render_markdown_ast_with_loader<'a, T: LinkHandler>(
     node: &'a AstNode<'a>,
     markdown_file_path: Option<&Path>,
-    theme_colors: &ThemeColors,
+    theme: &ActiveTheme,
     cx: &mut Context<T>,
-    image_loader: &mut dyn FnMut(&str) -> Option<ImageSource>,
+    images: &mut ImageCache,
 ) -> AnyElement {
     const DEFAULT_VIEWPORT_WIDTH: f32 = 1200.0;
     render_markdown_ast_internal(
-        node,
+        &DocNode::from_ast(node),
         markdown_file_path,
         None,
         DEFAULT_VIEWPORT_WIDTH,
-        theme_colors,
+        theme,
         cx,
-        image_loader,
+        images,
         None,
     )
 }
@@ -681,14 +1033,42 @@ pub fn render_markdown_ast_with_loader<'a, T: 'static>(
 ///
 /// This version accepts search state to highlight matching text.
 #[allow(clippy::too_many_arguments)]
-pub fn render_markdown_ast_with_search<'a, T: 'static>(
+pub fn render_markdown_ast_with_search<'a, T: LinkHandler>(
     node: &'a AstNode<'a>,
     markdown_file_path: Option<&Path>,
     search_state: Option<&super::search::SearchState>,
     viewport_width: f32,
-    theme_colors: &ThemeColors,
+    theme: &ActiveTheme,
     cx: &mut Context<T>,
-    image_loader: &mut dyn FnMut(&str) -> Option<ImageSource>,
+    images: &mut ImageCache,
+    focused_element: Option<&super::viewer::FocusableElement>,
+) -> AnyElement {
+    render_document_node(
+        &DocNode::from_ast(node),
+        markdown_file_path,
+        search_state,
+        viewport_width,
+        theme,
+        cx,
+        images,
+        focused_element,
+    )
+}
+
+/// Render a node of a document parsed ahead of time, with search
+/// highlighting
+///
+/// The viewer keeps its parsed document between frames and renders its
+/// blocks through this.
+#[allow(clippy::too_many_arguments)]
+pub fn render_document_node<T: LinkHandler>(
+    node: &DocNode,
+    markdown_file_path: Option<&Path>,
+    search_state: Option<&super::search::SearchState>,
+    viewport_width: f32,
+    theme: &ActiveTheme,
+    cx: &mut Context<T>,
+    images: &mut ImageCache,
     focused_element: Option<&super::viewer::FocusableElement>,
 ) -> AnyElement {
     render_markdown_ast_internal(
@@ -696,38 +1076,81 @@ pub fn render_markdown_ast_with_search<'a, T: 'static>(
         markdown_file_path,
         search_state,
         viewport_width,
-        theme_colors,
+        theme,
         cx,
-        image_loader,
+        images,
         focused_element,
     )
 }
 
+/// Render content as plain text with no markdown semantics
+///
+/// Each source line becomes its own row so line breaks and blank lines are
+/// preserved exactly; search matches are highlighted as in markdown mode.
+pub fn render_plain_text(
+    content: &str,
+    search_state: Option<&super::search::SearchState>,
+    match_colors: MatchColors,
+    font_family: Option<SharedString>,
+) -> AnyElement {
+    let lines = render_plain_text_lines(content, search_state, match_colors);
+    let container = div().flex_col().w_full().children(lines);
+    match font_family {
+        Some(font) => container.font_family(font).into_any_element(),
+        None => container.into_any_element(),
+    }
+}
+
+/// One row per source line of plain text, as laid out by [`render_plain_text`]
+pub fn render_plain_text_lines(
+    content: &str,
+    search_state: Option<&super::search::SearchState>,
+    match_colors: MatchColors,
+) -> Vec<AnyElement> {
+    content
+        .lines()
+        .map(|line| match line.is_empty() {
+            // Keep blank lines at full height
+            true => div().child(" ").into_any_element(),
+            false => div()
+                .flex()
+                .flex_row()
+                .flex_wrap()
+                .children(super::text_highlight::render_text_with_search(
+                    line,
+                    search_state,
+                    match_colors,
+                ))
+                .into_any_element(),
+        })
+        .collect()
+}
+
 /// Render a table row with proper alignment and header styling
 #[allow(clippy::too_many_arguments)]
-fn render_table_row<'a, T: 'static>(
-    row_node: &'a AstNode<'a>,
+fn render_table_row<T: LinkHandler>(
+    row_node: &DocNode,
     alignments: &[comrak::nodes::TableAlignment],
     column_width: f32,
     markdown_file_path: Option<&Path>,
     search_state: Option<&super::search::SearchState>,
     viewport_width: f32,
-    theme_colors: &ThemeColors,
+    theme: &ActiveTheme,
     cx: &mut Context<T>,
-    image_loader: &mut dyn FnMut(&str) -> Option<ImageSource>,
+    images: &mut ImageCache,
     focused_element: Option<&super::viewer::FocusableElement>,
 ) -> AnyElement {
-    let is_header = matches!(row_node.data.borrow().value, NodeValue::TableRow(true));
+    let is_header = matches!(row_node.value, NodeValue::TableRow(true));
 
     let mut row_div = div()
         .flex()
         .w_full()
         .border_b_1()
-        .border_color(theme_colors.table_border_color);
+        .border_color(theme.colors.table_border_color);
 
     if is_header {
         row_div = row_div
-            .bg(theme_colors.table_header_bg)
+            .bg(theme.colors.table_header_bg)
             .font_weight(FontWeight::BOLD);
     }
 
@@ -745,9 +1168,9 @@ fn render_table_row<'a, T: 'static>(
                 markdown_file_path,
                 search_state,
                 viewport_width,
-                theme_colors,
+                theme,
                 cx,
-                image_loader,
+                images,
                 focused_element,
             )
         })
@@ -758,17 +1181,17 @@ fn render_table_row<'a, T: 'static>(
 
 /// Render a table cell with alignment and responsive width
 #[allow(clippy::too_many_arguments)]
-fn render_table_cell<'a, T: 'static>(
-    cell_node: &'a AstNode<'a>,
+fn render_table_cell<T: LinkHandler>(
+    cell_node: &DocNode,
     alignment: Option<&comrak::nodes::TableAlignment>,
     column_width: f32,
     is_last_cell: bool,
     markdown_file_path: Option<&Path>,
     search_state: Option<&super::search::SearchState>,
     viewport_width: f32,
-    theme_colors: &ThemeColors,
+    theme: &ActiveTheme,
     cx: &mut Context<T>,
-    image_loader: &mut dyn FnMut(&str) -> Option<ImageSource>,
+    images: &mut ImageCache,
     focused_element: Option<&super::viewer::FocusableElement>,
 ) -> AnyElement {
     use comrak::nodes::TableAlignment;
@@ -784,7 +1207,7 @@ fn render_table_cell<'a, T: 'static>(
     if !is_last_cell {
         cell_div = cell_div
             .border_r_1()
-            .border_color(theme_colors.table_border_color);
+            .border_color(theme.colors.table_border_color);
     }
 
     // Apply alignment
@@ -802,15 +1225,323 @@ fn render_table_cell<'a, T: 'static>(
                 markdown_file_path,
                 search_state,
                 viewport_width,
-                theme_colors,
+                theme,
                 cx,
-                image_loader,
+                images,
                 focused_element,
             )
         }))
         .into_any_element()
 }
 
+/// Render the nodes of a raw HTML fragment
+#[allow(clippy::too_many_arguments)]
+fn render_html_nodes<T: LinkHandler>(
+    nodes: &[HtmlNode],
+    markdown_file_path: Option<&Path>,
+    search_state: Option<&super::search::SearchState>,
+    viewport_width: f32,
+    theme: &ActiveTheme,
+    cx: &mut Context<T>,
+    images: &mut ImageCache,
+    focused_element: Option<&super::viewer::FocusableElement>,
+) -> Vec<AnyElement> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            HtmlNode::Text(_) => node
+                .visible_text()
+                .map(|text| render_text(text, search_state, theme)),
+            HtmlNode::Element(element) => Some(render_html_element(
+                element,
+                markdown_file_path,
+                search_state,
+                viewport_width,
+                theme,
+                cx,
+                images,
+                focused_element,
+            )),
+        })
+        .collect()
+}
+
+/// Render one element of the supported HTML subset
+///
+/// Block elements take the full width, so in the wrapping rows their
+/// containers lay out they start lines of their own; `<br>` is an empty
+/// full-width element for the same reason. Unknown tags render their content.
+#[allow(clippy::too_many_arguments)]
+fn render_html_element<T: LinkHandler>(
+    element: &HtmlElement,
+    markdown_file_path: Option<&Path>,
+    search_state: Option<&super::search::SearchState>,
+    viewport_width: f32,
+    theme: &ActiveTheme,
+    cx: &mut Context<T>,
+    images: &mut ImageCache,
+    focused_element: Option<&super::viewer::FocusableElement>,
+) -> AnyElement {
+    let mut children = |nodes: &[HtmlNode], cx: &mut Context<T>| {
+        render_html_nodes(
+            nodes,
+            markdown_file_path,
+            search_state,
+            viewport_width,
+            theme,
+            cx,
+            images,
+            focused_element,
+        )
+    };
+    let row = || div().flex().flex_row().flex_wrap().items_center();
+    let block = || div().w_full().flex().flex_row().flex_wrap().items_center();
+
+    match element.tag.as_str() {
+        "br" => div().w_full().into_any_element(),
+        "hr" => div()
+            .w_full()
+            .my_2()
+            .border_t_1()
+            .border_color(theme.colors.table_border_color)
+            .into_any_element(),
+        "img" => render_html_image(element, markdown_file_path, images, theme),
+        "a" => {
+            let url = element.attr("href").unwrap_or_default().to_string();
+            let is_focused = is_link_focused(focused_element, &url);
+            match elements(&element.children).next() {
+                // Plain link text
+                None => render_link(url, element.text(), is_focused, search_state, theme, cx),
+                // Linked images, e.g. badges
+                Some(_) if url.trim().is_empty() => row()
+                    .children(children(&element.children, cx))
+                    .into_any_element(),
+                Some(_) => {
+                    let click_url = url.clone();
+                    row()
+                        .id(SharedString::from(url))
+                        .interactive(
+                            Affordance::Link,
+                            ClickState::default().focused(is_focused),
+                            theme.colors,
+                        )
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, _, cx| this.open_link(&click_url, cx)),
+                        )
+                        .children(children(&element.children, cx))
+                        .into_any_element()
+                }
+            }
+        }
+        "b" | "strong" => row()
+            .font_weight(FontWeight::BOLD)
+            .children(children(&element.children, cx))
+            .into_any_element(),
+        "i" | "em" => row()
+            .italic()
+            .children(children(&element.children, cx))
+            .into_any_element(),
+        "s" | "del" | "strike" => row()
+            .line_through()
+            .children(children(&element.children, cx))
+            .into_any_element(),
+        "code" | "kbd" | "tt" => div()
+            .font_family(CODE_FONT)
+            .bg(theme.colors.code_bg_color)
+            .px_1()
+            .rounded_sm()
+            .child(render_text(element.text(), search_state, theme))
+            .into_any_element(),
+        "sub" | "sup" => row()
+            .text_size(px(FOOTNOTE_REF_SIZE))
+            .children(children(&element.children, cx))
+            .into_any_element(),
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let text_size = match element.tag.as_str() {
+                "h1" => H1_SIZE,
+                "h2" => H2_SIZE,
+                "h3" => H3_SIZE,
+                "h4" => H4_SIZE,
+                "h5" => H5_SIZE,
+                _ => H6_SIZE,
+            };
+            block()
+                .when(element.is_centered(), |el| el.justify_center())
+                .text_size(px(text_size))
+                .font_weight(FontWeight::SEMIBOLD)
+                .children(children(&element.children, cx))
+                .into_any_element()
+        }
+        "pre" => render_highlighted_code_block(
+            element.raw_text(),
+            String::new(),
+            search_state,
+            theme,
+            cx,
+        ),
+        "blockquote" => block()
+            .border_l_4()
+            .border_color(theme.colors.blockquote_border_color)
+            .pl_4()
+            .italic()
+            .children(children(&element.children, cx))
+            .into_any_element(),
+        // Shown open, with the summary as its title
+        "details" => block()
+            .mb_2()
+            .children(children(&element.children, cx))
+            .into_any_element(),
+        "summary" => block()
+            .font_weight(FontWeight::BOLD)
+            .child(div().mr_2().child("▾"))
+            .children(children(&element.children, cx))
+            .into_any_element(),
+        "ul" | "ol" => {
+            let ordered = element.tag == "ol";
+            let start = element
+                .attr("start")
+                .and_then(|start| start.parse::<usize>().ok())
+                .unwrap_or(1);
+            let items: Vec<AnyElement> = elements(&element.children)
+                .filter(|item| item.tag == "li")
+                .enumerate()
+                .map(|(index, item)| {
+                    let marker = match ordered {
+                        true => format!("{}.", start + index),
+                        false => "•".to_string(),
+                    };
+                    div()
+                        .flex()
+                        .w_full()
+                        .mb_1()
+                        .child(
+                            div()
+                                .flex_none()
+                                .min_w(px(LIST_MARKER_WIDTH))
+                                .mr_2()
+                                .child(marker),
+                        )
+                        .child(row().flex_1().children(children(&item.children, cx)))
+                        .into_any_element()
+                })
+                .collect();
+            div()
+                .w_full()
+                .flex_col()
+                .pl_4()
+                .children(items)
+                .into_any_element()
+        }
+        "table" => {
+            let rows = element.table_rows();
+            let num_columns = rows
+                .iter()
+                .map(|row| elements(&row.children).count())
+                .max()
+                .unwrap_or(0);
+            let (column_width, _) = calculate_column_width(num_columns, viewport_width);
+            let rendered: Vec<AnyElement> = rows
+                .into_iter()
+                .map(|row| {
+                    let cells: Vec<AnyElement> = elements(&row.children)
+                        .filter(|cell| cell.tag == "td" || cell.tag == "th")
+                        .map(|cell| {
+                            div()
+                                .w(px(column_width))
+                                .min_w(px(MIN_COLUMN_WIDTH))
+                                .p(px(TABLE_CELL_PADDING))
+                                .flex_shrink_0()
+                                .overflow_hidden()
+                                .flex()
+                                .flex_row()
+                                .flex_wrap()
+                                .when(cell.tag == "th", |el| {
+                                    el.bg(theme.colors.table_header_bg)
+                                        .font_weight(FontWeight::BOLD)
+                                })
+                                .when(cell.is_centered(), |el| el.justify_center())
+                                .children(children(&cell.children, cx))
+                                .into_any_element()
+                        })
+                        .collect();
+                    div()
+                        .flex()
+                        .w_full()
+                        .border_b_1()
+                        .border_color(theme.colors.table_border_color)
+                        .children(cells)
+                        .into_any_element()
+                })
+                .collect();
+            div()
+                .flex_col()
+                .w_full()
+                .my_2()
+                .border_1()
+                .border_color(theme.colors.table_border_color)
+                .children(rendered)
+                .into_any_element()
+        }
+        _ if element.is_block() => block()
+            .when(element.is_centered(), |el| el.justify_center())
+            .when(element.tag == "p", |el| el.mb_2())
+            .children(children(&element.children, cx))
+            .into_any_element(),
+        _ => row()
+            .children(children(&element.children, cx))
+            .into_any_element(),
+    }
+}
+
+/// Render an `<img>`, sized by its `width`/`height` attributes and shown
+/// inline so badges sit side by side
+fn render_html_image(
+    element: &HtmlElement,
+    markdown_file_path: Option<&Path>,
+    images: &mut ImageCache,
+    theme: &ActiveTheme,
+) -> AnyElement {
+    let Some(src) = element.attr("src") else {
+        return div().into_any_element();
+    };
+    let resolved_path = images.resolve(src, markdown_file_path);
+    let width = element.width().map(|width| width.min(IMAGE_MAX_WIDTH));
+    let height = element
+        .attr("height")
+        .and_then(|height| height.trim().trim_end_matches("px").parse::<f32>().ok());
+    match images.image_for(&resolved_path) {
+        Some(source) => img(source)
+            .max_w(px(IMAGE_MAX_WIDTH))
+            .when_some(width, |img, width| img.w(px(width)))
+            .when_some(height, |img, height| img.h(px(height)))
+            .object_fit(gpui::ObjectFit::Contain)
+            .into_any_element(),
+        None => div()
+            .m_1()
+            .px_2()
+            .py_1()
+            .bg(theme.colors.image_placeholder_bg_color)
+            .border_1()
+            .border_color(theme.colors.image_placeholder_border_color)
+            .rounded(px(IMAGE_BORDER_RADIUS))
+            .text_size(px(12.0))
+            .text_color(theme.colors.image_placeholder_label_color)
+            .when_some(width, |el, width| el.w(px(width)))
+            .child(
+                match (
+                    element.attr("alt").filter(|alt| !alt.is_empty()),
+                    images.failed(&resolved_path),
+                ) {
+                    (Some(alt), _) => alt.to_string(),
+                    (None, true) => "⚠️ Image not available".to_string(),
+                    (None, false) => "🖼️ Image".to_string(),
+                },
+            )
+            .into_any_element(),
+    }
+}
+
 /// Open a URL in the default browser
 ///
 /// Uses platform-specific commands to open URLs in the system's default browser.
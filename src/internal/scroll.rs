@@ -1,10 +1,34 @@
 //! Scroll state management for the markdown viewer
 //!
 //! This module handles all scrolling logic including smooth scrolling,
-//! bounds checking, and scroll state persistence.
+//! bounds checking, and dragging the scroll thumb. Scroll positions are
+//! persisted per file by [`crate::internal::file_state`].
+//!
+//! Keyboard navigation, TOC jumps and search set a target with
+//! [`ScrollState::smooth_scroll_to`]; the viewer's frame timer then calls
+//! [`ScrollState::update_smooth_scroll`] until the target is reached. Direct
+//! scrolling (wheel, held keys, or setting `scroll_y`) stops a running
+//! animation.
+//!
+//! Whatever the input, `scroll_y` stays within `[0, max_scroll_y]` and is
+//! never NaN: a NaN position renders an empty view. Non-finite amounts,
+//! targets and sizes (e.g. from a zero-height viewport) are ignored.
 
 use tracing::trace;
 
+use crate::config::Easing;
+
+/// A smooth scroll under way
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Animation {
+    /// Position the animation started from
+    from: f32,
+    /// Seconds since it started
+    elapsed: f32,
+    /// Position it last set, to notice when something else moved the view
+    last: f32,
+}
+
 /// Scroll state for the markdown viewer
 #[derive(Debug, Clone, PartialEq)]
 pub struct ScrollState {
@@ -15,6 +39,7 @@ pub struct ScrollState {
     pub is_dragging: bool,      // For scroll thumb dragging
     pub drag_start_y: f32,      // Starting position when dragging
     pub drag_start_scroll: f32, // Starting scroll position when dragging
+    animation: Option<Animation>,
 }
 
 impl Default for ScrollState {
@@ -27,6 +52,7 @@ impl Default for ScrollState {
             is_dragging: false,
             drag_start_y: 0.0,
             drag_start_scroll: 0.0,
+            animation: None,
         }
     }
 }
@@ -36,16 +62,30 @@ impl ScrollState {
         Self::default()
     }
 
+    /// `y` within `[0, max_scroll_y]`, with NaN at the top
+    fn clamped(&self, y: f32) -> f32 {
+        match y.is_nan() {
+            true => 0.0,
+            false => y.clamp(0.0, self.max_scroll_y),
+        }
+    }
+
     /// Scroll up by the specified amount, ensuring we don't go negative
     pub fn scroll_up(&mut self, amount: f32) {
         trace!("Scrolling up by {}", amount);
-        self.scroll_y = (self.scroll_y - amount).max(0.0);
+        self.animation = None;
+        if amount.is_finite() {
+            self.scroll_y = self.clamped(self.scroll_y - amount);
+        }
     }
 
     /// Scroll down by the specified amount, respecting max scroll
     pub fn scroll_down(&mut self, amount: f32) {
         trace!("Scrolling down by {}", amount);
-        self.scroll_y = (self.scroll_y + amount).min(self.max_scroll_y);
+        self.animation = None;
+        if amount.is_finite() {
+            self.scroll_y = self.clamped(self.scroll_y + amount);
+        }
     }
 
     /// Scroll up by one page (80% of viewport height)
@@ -62,43 +102,97 @@ impl ScrollState {
 
     /// Scroll to the top of the document
     pub fn scroll_to_top(&mut self) {
+        self.animation = None;
         self.scroll_y = 0.0;
     }
 
     /// Scroll to the bottom of the document
     pub fn scroll_to_bottom(&mut self) {
+        self.animation = None;
         self.scroll_y = self.max_scroll_y;
     }
 
     /// Set the maximum scroll position based on content and viewport height
     pub fn set_max_scroll(&mut self, content_height: f32, viewport_height: f32) {
-        self.max_scroll_y = (content_height - viewport_height).max(0.0);
+        let max_scroll_y = content_height - viewport_height;
+        if max_scroll_y.is_finite() {
+            self.max_scroll_y = max_scroll_y.max(0.0);
+        }
         // Clamp current scroll to new bounds
-        self.scroll_y = self.scroll_y.min(self.max_scroll_y);
+        self.scroll_y = self.clamped(self.scroll_y);
+        self.target_scroll_y = self.clamped(self.target_scroll_y);
     }
 
     /// Re-clamp the current scroll position to valid bounds
     pub fn reclamp(&mut self) {
-        self.scroll_y = self.scroll_y.max(0.0).min(self.max_scroll_y);
+        self.scroll_y = self.clamped(self.scroll_y);
     }
 
     /// Smooth scroll to a target position
+    ///
+    /// A running animation is redirected from where it is now.
     pub fn smooth_scroll_to(&mut self, target: f32) {
-        self.target_scroll_y = target.clamp(0.0, self.max_scroll_y);
+        if !target.is_finite() {
+            return;
+        }
+        self.target_scroll_y = self.clamped(target);
+        self.animation = Some(Animation {
+            from: self.scroll_y,
+            elapsed: 0.0,
+            last: self.scroll_y,
+        });
     }
 
-    /// Update smooth scrolling animation
-    pub fn update_smooth_scroll(&mut self, delta_time: f32) {
-        let diff = self.target_scroll_y - self.scroll_y;
-        match diff.abs() {
-            x if x > 0.1 => {
-                let lerp_factor = (delta_time * 10.0).min(1.0);
-                self.scroll_y += diff * lerp_factor;
-            }
-            _ => {
+    /// Whether a smooth scroll is under way
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+
+    /// Where scrolling is heading: the animation's target, or the current
+    /// position when nothing is animating
+    ///
+    /// Repeated key presses step on from here so none of them is lost.
+    pub fn destination(&self) -> f32 {
+        match self.animation {
+            Some(_) => self.target_scroll_y,
+            None => self.scroll_y,
+        }
+    }
+
+    /// Advance the animation by `delta_time` seconds of `duration`; returns
+    /// whether it is still running
+    pub fn update_smooth_scroll(&mut self, delta_time: f32, duration: f32, easing: Easing) -> bool {
+        let Some(animation) = self.animation else {
+            return false;
+        };
+        if (self.scroll_y - animation.last).abs() > 0.5 {
+            trace!("Scroll moved during animation, stopping it");
+            self.animation = None;
+            return false;
+        }
+        let elapsed = animation.elapsed + delta_time.max(0.0);
+        // Also ends animations with a NaN duration or elapsed time
+        let progress = match duration > 0.0 && elapsed.is_finite() {
+            true => elapsed / duration,
+            false => 1.0,
+        };
+        match progress >= 1.0 {
+            true => {
                 self.scroll_y = self.target_scroll_y;
+                self.animation = None;
+            }
+            false => {
+                let from = animation.from;
+                self.scroll_y =
+                    self.clamped(from + (self.target_scroll_y - from) * easing.apply(progress));
+                self.animation = Some(Animation {
+                    from,
+                    elapsed,
+                    last: self.scroll_y,
+                });
             }
         }
+        self.is_animating()
     }
 
     /// Start dragging the scroll thumb
@@ -115,39 +209,65 @@ impl ScrollState {
 
     /// Update scroll position based on drag
     pub fn update_drag(&mut self, mouse_y: f32, viewport_height: f32) {
-        if self.is_dragging && self.max_scroll_y > 0.0 {
+        if self.is_dragging && self.max_scroll_y > 0.0 && viewport_height > 0.0 {
             let drag_delta = mouse_y - self.drag_start_y;
             let scroll_delta = (drag_delta / viewport_height) * self.max_scroll_y;
             let new_scroll = (self.drag_start_scroll + scroll_delta).clamp(0.0, self.max_scroll_y);
             self.smooth_scroll_to(new_scroll);
         }
     }
+}
+
+/// Continuous scrolling while a scroll key (j/k or an arrow) is held
+///
+/// The first key press scrolls one step as usual; once the OS starts repeating
+/// the key, a frame timer scrolls at `speed`, accelerating the longer the key
+/// is held, until the key is released.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeldScroll {
+    /// The held key, if any
+    pub key: Option<String>,
+    /// +1.0 scrolls down, -1.0 scrolls up
+    pub direction: f32,
+    /// Seconds the key has been held
+    pub held_for: f32,
+}
 
-    /// Save scroll state to a file
-    pub fn save_scroll_state(&self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let content = format!(
-            "scroll_y: {}\ntarget_scroll_y: {}\nmax_scroll_y: {}",
-            self.scroll_y, self.target_scroll_y, self.max_scroll_y
-        );
-        std::fs::write(file_path, content)?;
-        Ok(())
-    }
-
-    /// Load scroll state from a file
-    pub fn load_scroll_state(&mut self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let content = std::fs::read_to_string(file_path)?;
-        for line in content.lines() {
-            if let Some((key, value)) = line.split_once(": ")
-                && let Ok(val) = value.parse::<f32>()
-            {
-                match key {
-                    "scroll_y" => self.scroll_y = val.max(0.0).min(self.max_scroll_y),
-                    "target_scroll_y" => self.target_scroll_y = val.max(0.0).min(self.max_scroll_y),
-                    "max_scroll_y" => self.max_scroll_y = val.max(0.0),
-                    _ => {}
-                }
+impl HeldScroll {
+    /// Start scrolling for `key`; returns false if it is already held
+    pub fn start(&mut self, key: &str, direction: f32) -> bool {
+        match self.key.as_deref() == Some(key) {
+            true => false,
+            false => {
+                self.key = Some(key.to_string());
+                self.direction = direction;
+                self.held_for = 0.0;
+                true
             }
         }
-        Ok(())
+    }
+
+    /// Stop scrolling when `key` is released
+    pub fn release(&mut self, key: &str) {
+        if self.key.as_deref() == Some(key) {
+            *self = Self::default();
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Pixels to scroll for a frame of `delta_time` seconds
+    ///
+    /// Speed starts at `speed` pixels per second and grows by `acceleration`
+    /// times that per second held, capped at four times the base speed.
+    pub fn advance(&mut self, delta_time: f32, speed: f32, acceleration: f32) -> f32 {
+        if !self.is_active() {
+            return 0.0;
+        }
+        self.held_for += delta_time;
+        let factor = (1.0 + acceleration * self.held_for).min(4.0);
+        self.direction * speed * factor * delta_time
     }
 }
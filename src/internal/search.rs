@@ -1,10 +1,14 @@
 //! Search functionality for the markdown viewer
 //!
 //! This module provides text search capabilities including:
-//! - Case-insensitive search
+//! - Plain (case-insensitive), case-sensitive, whole-word and regex search
+//! - An index that refines the matches of the previous query while typing
 //! - Match tracking and navigation
 //! - Position information for highlighting
 
+use regex::Regex;
+use std::cell::Cell;
+
 /// Represents a single match position in the text
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MatchPosition {
@@ -14,23 +18,256 @@ pub struct MatchPosition {
     pub end: usize,
 }
 
+/// End of the document that match navigation wrapped around to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchWrap {
+    /// Moved past the last match back to the first
+    ToTop,
+    /// Moved before the first match to the last
+    ToBottom,
+}
+
+/// How the query is matched against the text
+///
+/// Toggled in the search overlay with Alt+R (regex), Alt+W (whole word) and
+/// Alt+C (match case); toggling the active mode goes back to plain search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Literal text, ignoring case
+    #[default]
+    Plain,
+    /// Regular expression, matching case unless it starts with `(?i)`
+    Regex,
+    /// Literal text as whole words, ignoring case
+    WholeWord,
+    /// Literal text, matching case
+    CaseSensitive,
+}
+
+impl SearchMode {
+    /// Short name shown in the search overlay
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Plain => "Plain",
+            SearchMode::Regex => "Regex",
+            SearchMode::WholeWord => "Whole Word",
+            SearchMode::CaseSensitive => "Match Case",
+        }
+    }
+
+    /// The mode after toggling `mode`: `mode`, or plain when it is already on
+    pub fn toggle(self, mode: SearchMode) -> SearchMode {
+        match self == mode {
+            true => SearchMode::Plain,
+            false => mode,
+        }
+    }
+
+    /// Compile `query` into the pattern this mode searches with
+    pub fn pattern(self, query: &str) -> Result<Regex, regex::Error> {
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+        let source = match self {
+            SearchMode::Plain => format!("(?i){}", regex::escape(query)),
+            SearchMode::Regex => query.to_string(),
+            SearchMode::WholeWord => {
+                // `\b` next to punctuation would require a word character there
+                let start = match query.starts_with(is_word_char) {
+                    true => r"\b",
+                    false => "",
+                };
+                let end = match query.ends_with(is_word_char) {
+                    true => r"\b",
+                    false => "",
+                };
+                format!("(?i){}{}{}", start, regex::escape(query), end)
+            }
+            SearchMode::CaseSensitive => regex::escape(query),
+        };
+        Regex::new(&source)
+    }
+}
+
+/// Document text prepared once for the searches typed against it
+///
+/// Searching a multi-megabyte document from scratch on every keystroke lags,
+/// so the index keeps a lowercased copy of the document for the literal
+/// modes, rebuilt only when the content revision changes, and every
+/// (overlapping) start of the last literal query. When the next query
+/// extends that one, as it does while typing, only those starts are checked
+/// again instead of the whole document.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    /// Content revision the index was prepared for
+    revision: Option<u64>,
+    /// Lowercased document; `None` when lowercasing would move byte offsets
+    folded: Option<String>,
+    /// Starts of the last literal query
+    previous: Option<Occurrences>,
+}
+
+/// Every start of a literal needle in the document, overlapping ones included
+#[derive(Debug, Clone)]
+struct Occurrences {
+    needle: String,
+    /// Whether `needle` was searched for in the lowercased document
+    fold: bool,
+    starts: Vec<usize>,
+}
+
+impl SearchIndex {
+    /// Prepare the index for `text`, unless it already holds revision `revision`
+    pub fn prepare(&mut self, text: &str, revision: u64) {
+        if self.revision == Some(revision) {
+            return;
+        }
+        *self = Self {
+            revision: Some(revision),
+            folded: fold_case(text),
+            previous: None,
+        };
+    }
+
+    /// Every start of `needle` in `text`, or in its lowercased copy when
+    /// `fold` is set; `None` when there is no usable lowercased copy
+    fn occurrences(&mut self, text: &str, needle: &str, fold: bool) -> Option<&[usize]> {
+        let haystack = match fold {
+            true => self.folded.as_deref()?,
+            false => text,
+        };
+        let starts = match self.previous.take() {
+            // Every occurrence of the longer needle starts where the shorter one did
+            Some(previous) if previous.fold == fold && needle.starts_with(&previous.needle) => {
+                previous
+                    .starts
+                    .into_iter()
+                    .filter(|&start| haystack[start..].starts_with(needle))
+                    .collect()
+            }
+            _ => occurrences(haystack, needle),
+        };
+        let previous = self.previous.insert(Occurrences {
+            needle: needle.to_string(),
+            fold,
+            starts,
+        });
+        Some(&previous.starts)
+    }
+}
+
+/// A query of a literal mode, ready to be looked up without a regex
+#[derive(Debug, Clone)]
+struct Literal {
+    /// The query, lowercased when `fold` is set
+    needle: String,
+    /// Whether case is ignored
+    fold: bool,
+    /// Whether matches must not touch word characters on either side
+    whole_word: bool,
+}
+
+impl Literal {
+    /// The literal form of `query` in `mode`, if it has one
+    fn new(query: &str, mode: SearchMode) -> Option<Self> {
+        let (fold, whole_word) = match mode {
+            SearchMode::Plain => (true, false),
+            SearchMode::WholeWord => (true, true),
+            SearchMode::CaseSensitive => (false, false),
+            SearchMode::Regex => return None,
+        };
+        let needle = match fold {
+            true => fold_case(query)?,
+            false => query.to_string(),
+        };
+        Some(Self {
+            needle,
+            fold,
+            whole_word,
+        })
+    }
+
+    /// Non-overlapping matches among the occurrences at `starts` in `text`
+    fn matches(&self, text: &str, starts: &[usize]) -> Vec<MatchPosition> {
+        let len = self.needle.len();
+        let mut matches: Vec<MatchPosition> = Vec::new();
+        for &start in starts {
+            let free = matches.last().is_none_or(|last| start >= last.end);
+            if free && (!self.whole_word || is_whole_word(text, start, start + len)) {
+                matches.push(MatchPosition {
+                    start,
+                    end: start + len,
+                });
+            }
+        }
+        matches
+    }
+
+    /// Matches in `text` without an index; `None` when `text` cannot be
+    /// lowercased in place
+    fn find(&self, text: &str) -> Option<Vec<MatchPosition>> {
+        let starts = match self.fold {
+            true => occurrences(&fold_case(text)?, &self.needle),
+            false => occurrences(text, &self.needle),
+        };
+        Some(self.matches(text, &starts))
+    }
+}
+
 /// Search state tracking query, matches, and current position
 #[derive(Debug, Clone)]
 pub struct SearchState {
     /// Current search query
     query: String,
+    /// How the query is matched
+    mode: SearchMode,
+    /// Compiled query; `None` for an empty query or an invalid regex
+    pattern: Option<Regex>,
+    /// The query as literal text, looked up without the regex when possible
+    literal: Option<Literal>,
+    /// Why the query is not a valid regex
+    error: Option<String>,
     /// All match positions in the text
     matches: Vec<MatchPosition>,
     /// Index of the currently selected match (if any)
     current_index: Option<usize>,
+    /// Whether the last navigation wrapped around
+    wrapped: Option<SearchWrap>,
+    /// Matches drawn so far in the current frame, to find the current one
+    rendered_matches: Cell<usize>,
 }
 
 impl SearchState {
-    /// Create a new search state with the given query and text
-    pub fn new(query: String, text: &str) -> Self {
-        let matches = match query.as_str() {
-            "" => Vec::new(),
-            _ => find_matches(&query, text),
+    /// Create a new search state with the given query, text and mode
+    ///
+    /// A query that is not a valid regex has no matches and reports why
+    /// through [`Self::error`].
+    pub fn new(query: String, text: &str, mode: SearchMode) -> Self {
+        let mut index = SearchIndex::default();
+        index.prepare(text, 0);
+        Self::indexed(query, text, mode, &mut index)
+    }
+
+    /// Like [`Self::new`], reusing the work `index` kept from earlier
+    /// searches of the same text
+    ///
+    /// `index` must have been prepared for `text`.
+    pub fn indexed(query: String, text: &str, mode: SearchMode, index: &mut SearchIndex) -> Self {
+        let (pattern, error) = match query.as_str() {
+            "" => (None, None),
+            _ => match mode.pattern(&query) {
+                Ok(pattern) => (Some(pattern), None),
+                Err(e) => (None, Some(e.to_string())),
+            },
+        };
+        let literal = pattern.as_ref().and_then(|_| Literal::new(&query, mode));
+        let matches = match (&literal, &pattern) {
+            (Some(literal), Some(pattern)) => {
+                match index.occurrences(text, &literal.needle, literal.fold) {
+                    Some(starts) => literal.matches(text, starts),
+                    None => find_matches(pattern, text),
+                }
+            }
+            (None, Some(pattern)) => find_matches(pattern, text),
+            _ => Vec::new(),
         };
 
         let current_index = match matches.as_slice() {
@@ -40,8 +277,14 @@ impl SearchState {
 
         Self {
             query,
+            mode,
+            pattern,
+            literal,
+            error,
             matches,
             current_index,
+            wrapped: None,
+            rendered_matches: Cell::new(0),
         }
     }
 
@@ -50,6 +293,16 @@ impl SearchState {
         &self.query
     }
 
+    /// How the query is matched
+    pub fn mode(&self) -> SearchMode {
+        self.mode
+    }
+
+    /// Why the query could not be compiled, in regex mode
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
     /// Get all match positions
     pub fn matches(&self) -> &[MatchPosition] {
         &self.matches
@@ -76,10 +329,13 @@ impl SearchState {
             return;
         }
 
-        self.current_index = Some(match self.current_index {
-            Some(i) if i + 1 < self.matches.len() => i + 1,
-            _ => 0, // Wrap to first
-        });
+        let (index, wrapped) = match self.current_index {
+            Some(i) if i + 1 < self.matches.len() => (i + 1, None),
+            Some(_) => (0, Some(SearchWrap::ToTop)), // Wrap to first
+            None => (0, None),
+        };
+        self.current_index = Some(index);
+        self.wrapped = wrapped;
     }
 
     /// Move to the previous match (wraps around)
@@ -88,10 +344,81 @@ impl SearchState {
             return;
         }
 
-        self.current_index = Some(match self.current_index {
-            Some(0) | None => self.matches.len() - 1, // Wrap to last
-            Some(i) => i - 1,
-        });
+        let (index, wrapped) = match self.current_index {
+            Some(0) => (self.matches.len() - 1, Some(SearchWrap::ToBottom)), // Wrap to last
+            None => (self.matches.len() - 1, None),
+            Some(i) => (i - 1, None),
+        };
+        self.current_index = Some(index);
+        self.wrapped = wrapped;
+    }
+
+    /// Make the match at `index` (0-based) the current one
+    pub fn select_match(&mut self, index: usize) {
+        if index < self.matches.len() {
+            self.current_index = Some(index);
+            self.wrapped = None;
+        }
+    }
+
+    /// Whether the last next/previous move wrapped around the document
+    pub fn wrapped(&self) -> Option<SearchWrap> {
+        self.wrapped
+    }
+
+    /// Start counting rendered matches for a new frame
+    pub fn begin_render(&self) {
+        self.rendered_matches.set(0);
+    }
+
+    /// Count one rendered match; returns whether it is the current match
+    ///
+    /// Renderers draw matches in document order, so the n-th match drawn in a
+    /// frame is the n-th match in the text.
+    pub fn next_rendered_is_current(&self) -> bool {
+        let index = self.rendered_matches.get();
+        self.rendered_matches.set(index + 1);
+        self.current_index == Some(index)
+    }
+
+    /// Matches of the query in `text`, which may be any part of the document
+    ///
+    /// Renderers highlight text segment by segment with these, so the
+    /// highlights follow the same mode as the match count.
+    pub fn matches_in(&self, text: &str) -> Vec<MatchPosition> {
+        match (&self.literal, &self.pattern) {
+            _ if self.matches.is_empty() => Vec::new(),
+            (Some(literal), Some(pattern)) => literal
+                .find(text)
+                .unwrap_or_else(|| find_matches(pattern, text)),
+            (None, Some(pattern)) => find_matches(pattern, text),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether `text` contains a match of the query
+    ///
+    /// Lets renderers keep their own styling for text without matches.
+    pub fn has_match_in(&self, text: &str) -> bool {
+        !self.matches_in(text).is_empty()
+    }
+
+    /// Lines (1-based, ascending, without repeats) holding a match in `text`
+    pub fn matched_lines(&self, text: &str) -> Vec<usize> {
+        let mut lines = Vec::new();
+        let (mut line, mut scanned) = (1, 0);
+        for m in &self.matches {
+            let start = m.start.min(text.len()).max(scanned);
+            line += text.as_bytes()[scanned..start]
+                .iter()
+                .filter(|&&byte| byte == b'\n')
+                .count();
+            scanned = start;
+            if lines.last() != Some(&line) {
+                lines.push(line);
+            }
+        }
+        lines
     }
 
     /// Check if a byte position is within any match
@@ -107,27 +434,59 @@ impl SearchState {
     }
 }
 
-/// Find all case-insensitive matches of the query in the text
-fn find_matches(query: &str, text: &str) -> Vec<MatchPosition> {
-    let query_lower = query.to_lowercase();
-    let text_lower = text.to_lowercase();
-
-    let mut matches = Vec::new();
-    let mut start = 0;
-
-    while let Some(pos) = text_lower[start..].find(&query_lower) {
-        let match_start = start + pos;
-        let match_end = match_start + query.len();
+/// Find all non-empty matches of the pattern in the text
+///
+/// Empty matches (e.g. of `a*`) cannot be highlighted or scrolled to.
+fn find_matches(pattern: &Regex, text: &str) -> Vec<MatchPosition> {
+    pattern
+        .find_iter(text)
+        .filter(|m| !m.is_empty())
+        .map(|m| MatchPosition {
+            start: m.start(),
+            end: m.end(),
+        })
+        .collect()
+}
 
-        matches.push(MatchPosition {
-            start: match_start,
-            end: match_end,
-        });
+/// Lowercase `text` character by character, keeping every byte offset
+///
+/// Returns `None` when a character lowercases to a different length (e.g.
+/// `İ`), as offsets into the copy would then not fit the original.
+fn fold_case(text: &str) -> Option<String> {
+    if text.is_ascii() {
+        return Some(text.to_ascii_lowercase());
+    }
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        let mut lower = c.to_lowercase();
+        match (lower.next(), lower.next()) {
+            (Some(l), None) if l.len_utf8() == c.len_utf8() => folded.push(l),
+            _ => return None,
+        }
+    }
+    Some(folded)
+}
 
-        start = match_end;
+/// Every start of `needle` in `haystack`, overlapping ones included
+fn occurrences(haystack: &str, needle: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut from = 0;
+    while let Some(found) = haystack[from..].find(needle) {
+        let start = from + found;
+        starts.push(start);
+        from = start + haystack[start..].chars().next().map_or(1, char::len_utf8);
     }
+    starts
+}
 
-    matches
+/// Whether the match at `start..end` stands as a whole word: an end that is
+/// a word character must not touch another one, like `\b` around the query
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let found = &text[start..end];
+    let open = !found.starts_with(is_word_char) || !text[..start].ends_with(is_word_char);
+    let close = !found.ends_with(is_word_char) || !text[end..].starts_with(is_word_char);
+    open && close
 }
 
 #[cfg(test)]
@@ -137,7 +496,7 @@ mod tests {
     #[test]
     fn test_find_single_match() {
         let text = "Hello world";
-        let state = SearchState::new("world".to_string(), text);
+        let state = SearchState::new("world".to_string(), text, SearchMode::Plain);
 
         assert_eq!(state.match_count(), 1);
         assert_eq!(state.current_match_number(), Some(1));
@@ -147,10 +506,118 @@ mod tests {
         assert_eq!(m.end, 11);
     }
 
+    #[test]
+    fn matched_lines_are_deduplicated() {
+        let text = "foo foo\nbar\nfoo\nbaz foo";
+        let state = SearchState::new("foo".to_string(), text, SearchMode::Plain);
+        assert_eq!(state.matched_lines(text), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn select_match_ignores_out_of_range_indices() {
+        let mut state = SearchState::new("a".to_string(), "a a a", SearchMode::Plain);
+        state.select_match(2);
+        assert_eq!(state.current_match_number(), Some(3));
+        state.select_match(7);
+        assert_eq!(state.current_match_number(), Some(3));
+    }
+
+    #[test]
+    fn has_match_in_checks_other_text_case_insensitively() {
+        let state = SearchState::new("Parse".to_string(), "fn parse() {}", SearchMode::Plain);
+        assert!(state.has_match_in("let ast = PARSE(input);"));
+        assert!(!state.has_match_in("let ast = build(input);"));
+        let empty = SearchState::new("parse".to_string(), "no hits here", SearchMode::Plain);
+        assert!(!empty.has_match_in("parse"));
+    }
+
+    #[test]
+    fn regex_mode_matches_patterns() {
+        let text = "fn parse(input) {}\nfn build() {}\nparse(x)";
+        let state = SearchState::new(r"fn \w+\(".to_string(), text, SearchMode::Regex);
+        assert_eq!(state.match_count(), 2);
+        assert_eq!(state.matches()[1].start, 19);
+        assert!(state.has_match_in("fn other()"));
+        assert!(!state.has_match_in("other()"));
+
+        let invalid = SearchState::new("fn (".to_string(), text, SearchMode::Regex);
+        assert_eq!(invalid.match_count(), 0);
+        assert!(invalid.error().is_some());
+
+        let empty_matches = SearchState::new("z*".to_string(), text, SearchMode::Regex);
+        assert_eq!(empty_matches.match_count(), 0);
+    }
+
+    #[test]
+    fn whole_word_and_case_sensitive_modes() {
+        let text = "Parse parser parse reparse";
+        let words = SearchState::new("parse".to_string(), text, SearchMode::WholeWord);
+        let starts: Vec<usize> = words.matches().iter().map(|m| m.start).collect();
+        assert_eq!(starts, vec![0, 13]);
+
+        let case = SearchState::new("parse".to_string(), text, SearchMode::CaseSensitive);
+        assert_eq!(case.match_count(), 3);
+        assert_eq!(case.matches_in("PARSE Parse"), Vec::new());
+
+        let punctuation = SearchState::new("(x)".to_string(), "f(x) g (x)", SearchMode::WholeWord);
+        assert_eq!(punctuation.match_count(), 2);
+    }
+
+    #[test]
+    fn typing_refines_the_previous_matches() {
+        let text = "aaab Parser parse PARSED parsing";
+        let mut index = SearchIndex::default();
+        index.prepare(text, 1);
+
+        let mut counts = Vec::new();
+        for query in ["p", "pa", "par", "pars", "parse", "parsed"] {
+            let state =
+                SearchState::indexed(query.to_string(), text, SearchMode::Plain, &mut index);
+            let scanned = SearchState::new(query.to_string(), text, SearchMode::Plain);
+            assert_eq!(state.matches(), scanned.matches());
+            counts.push(state.match_count());
+        }
+        assert_eq!(counts, vec![4, 4, 4, 4, 3, 1]);
+
+        // Overlapping starts of a shorter query are kept for longer ones
+        for query in ["aa", "aab"] {
+            let state =
+                SearchState::indexed(query.to_string(), text, SearchMode::Plain, &mut index);
+            assert_eq!(state.match_count(), 1);
+        }
+        let words =
+            SearchState::indexed("parse".to_string(), text, SearchMode::WholeWord, &mut index);
+        assert_eq!(words.matches(), &[MatchPosition { start: 12, end: 17 }]);
+    }
+
+    #[test]
+    fn text_that_lowercases_to_another_length_falls_back_to_the_regex() {
+        let text = "İstanbul istanbul";
+        let state = SearchState::new("stanbul".to_string(), text, SearchMode::Plain);
+        assert_eq!(state.match_count(), 2);
+        assert_eq!(state.matches_in("ISTANBUL").len(), 1);
+    }
+
+    #[test]
+    fn toggling_the_active_mode_returns_to_plain() {
+        assert_eq!(
+            SearchMode::Plain.toggle(SearchMode::Regex),
+            SearchMode::Regex
+        );
+        assert_eq!(
+            SearchMode::Regex.toggle(SearchMode::WholeWord),
+            SearchMode::WholeWord
+        );
+        assert_eq!(
+            SearchMode::Regex.toggle(SearchMode::Regex),
+            SearchMode::Plain
+        );
+    }
+
     #[test]
     fn test_find_multiple_matches() {
         let text = "foo bar foo baz foo";
-        let state = SearchState::new("foo".to_string(), text);
+        let state = SearchState::new("foo".to_string(), text, SearchMode::Plain);
 
         assert_eq!(state.match_count(), 3);
         assert_eq!(state.matches()[0].start, 0);
@@ -158,10 +625,23 @@ mod tests {
         assert_eq!(state.matches()[2].start, 16);
     }
 
+    #[test]
+    fn windows_line_endings_give_the_same_matches() {
+        let lf = "# Notes\n\nfoo on line 3\n\n- foo\n- bar foo\n";
+        let (crlf, _) = crate::internal::text_format::prepare(lf.replace('\n', "\r\n"));
+        let find = |text: &str| SearchState::new("foo".to_string(), text, SearchMode::Plain);
+
+        let starts = |state: &SearchState| -> Vec<usize> {
+            state.matches().iter().map(|m| m.start).collect()
+        };
+        assert_eq!(starts(&find(&crlf)), starts(&find(lf)));
+        assert_eq!(find(&crlf).matched_lines(&crlf), vec![3, 5, 6]);
+    }
+
     #[test]
     fn test_case_insensitive() {
         let text = "Markdown MARKDOWN markdown";
-        let state = SearchState::new("markdown".to_string(), text);
+        let state = SearchState::new("markdown".to_string(), text, SearchMode::Plain);
 
         assert_eq!(state.match_count(), 3);
     }
@@ -169,7 +649,7 @@ mod tests {
     #[test]
     fn test_no_matches() {
         let text = "Hello world";
-        let state = SearchState::new("xyz".to_string(), text);
+        let state = SearchState::new("xyz".to_string(), text, SearchMode::Plain);
 
         assert_eq!(state.match_count(), 0);
         assert_eq!(state.current_match_number(), None);
@@ -178,15 +658,40 @@ mod tests {
     #[test]
     fn test_empty_query() {
         let text = "Hello world";
-        let state = SearchState::new("".to_string(), text);
+        let state = SearchState::new("".to_string(), text, SearchMode::Plain);
 
         assert_eq!(state.match_count(), 0);
     }
 
+    #[test]
+    fn wrapping_is_reported_until_the_next_move() {
+        let mut state = SearchState::new("a".to_string(), "a b a", SearchMode::Plain);
+        state.next_match();
+        assert_eq!(state.wrapped(), None);
+        state.next_match();
+        assert_eq!(state.wrapped(), Some(SearchWrap::ToTop));
+        state.prev_match();
+        assert_eq!(state.wrapped(), Some(SearchWrap::ToBottom));
+        state.prev_match();
+        assert_eq!(state.wrapped(), None);
+    }
+
+    #[test]
+    fn current_match_is_found_among_rendered_matches() {
+        let mut state = SearchState::new("a".to_string(), "a b a b a", SearchMode::Plain);
+        state.next_match();
+        state.begin_render();
+        let rendered: Vec<bool> = (0..3).map(|_| state.next_rendered_is_current()).collect();
+        assert_eq!(rendered, vec![false, true, false]);
+
+        state.begin_render();
+        assert!(!state.next_rendered_is_current());
+    }
+
     #[test]
     fn test_navigation_forward() {
         let text = "a b a b a";
-        let mut state = SearchState::new("a".to_string(), text);
+        let mut state = SearchState::new("a".to_string(), text, SearchMode::Plain);
 
         assert_eq!(state.current_match_number(), Some(1));
 
@@ -204,7 +709,7 @@ mod tests {
     #[test]
     fn test_navigation_backward() {
         let text = "a b a b a";
-        let mut state = SearchState::new("a".to_string(), text);
+        let mut state = SearchState::new("a".to_string(), text, SearchMode::Plain);
 
         // Start at first, go back wraps to last
         state.prev_match();
@@ -220,7 +725,7 @@ mod tests {
     #[test]
     fn test_is_match_at() {
         let text = "Hello world";
-        let state = SearchState::new("world".to_string(), text);
+        let state = SearchState::new("world".to_string(), text, SearchMode::Plain);
 
         assert!(!state.is_match_at(0));
         assert!(!state.is_match_at(5));
@@ -232,7 +737,7 @@ mod tests {
     #[test]
     fn test_is_current_match_at() {
         let text = "foo bar foo";
-        let mut state = SearchState::new("foo".to_string(), text);
+        let mut state = SearchState::new("foo".to_string(), text, SearchMode::Plain);
 
         // First match is current
         assert!(state.is_current_match_at(0));
@@ -0,0 +1,71 @@
+//! Split-pane source view
+//!
+//! Cmd+Shift+V shows the raw markdown on the left of the rendered document.
+//! The two panes scroll together by source line: the line at the top of the
+//! rendered pane (taken from the block map) is also the line at the top of
+//! the source pane, including how far into it the view is. Scrolling the
+//! source pane moves the rendered pane the same way, so the source is only
+//! ever positioned through the rendered scroll offset.
+
+use std::ops::Range;
+
+use crate::internal::block_map::{BlockMap, LinePosition};
+use crate::internal::style::SOURCE_LINE_HEIGHT;
+
+/// Source line (with the fraction scrolled past it) at rendered Y `y`
+fn line_position(map: &BlockMap, y: f32) -> f32 {
+    let position = map.position_at(y);
+    position.line as f32 + position.fraction
+}
+
+/// Scroll offset of the source pane matching the rendered pane's `rendered_y`
+pub fn source_scroll_for(map: &BlockMap, rendered_y: f32) -> f32 {
+    line_position(map, rendered_y) * SOURCE_LINE_HEIGHT
+}
+
+/// Rendered scroll offset matching the source pane's `source_y`
+pub fn rendered_scroll_for(map: &BlockMap, source_y: f32) -> f32 {
+    let position = source_y.max(0.0) / SOURCE_LINE_HEIGHT;
+    map.y_for_position(LinePosition {
+        line: position.floor() as usize,
+        fraction: position.fract(),
+    })
+}
+
+/// Source lines at least partly visible in a pane `height` tall scrolled to
+/// `source_y`, out of `total` lines
+pub fn visible_lines(source_y: f32, height: f32, total: usize) -> Range<usize> {
+    let first = (source_y.max(0.0) / SOURCE_LINE_HEIGHT).floor() as usize;
+    let last = ((source_y.max(0.0) + height) / SOURCE_LINE_HEIGHT).ceil() as usize;
+    first.min(total)..last.min(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panes_scroll_together_by_line() {
+        // Lines 0..3 rendered 10, 30 and 5 pixels tall below a 16px offset
+        let map = BlockMap::from_lines(&[0.0, 10.0, 40.0], 45.0, 16.0);
+
+        assert_eq!(source_scroll_for(&map, 0.0), 0.0);
+        assert_eq!(source_scroll_for(&map, 16.0), 0.0);
+        assert_eq!(source_scroll_for(&map, 41.0), 1.5 * SOURCE_LINE_HEIGHT);
+        assert_eq!(source_scroll_for(&map, 56.0), 2.0 * SOURCE_LINE_HEIGHT);
+
+        assert_eq!(rendered_scroll_for(&map, 1.5 * SOURCE_LINE_HEIGHT), 41.0);
+        assert_eq!(rendered_scroll_for(&map, -5.0), 16.0);
+        // Past the last line stays at the bottom
+        assert_eq!(rendered_scroll_for(&map, 10.0 * SOURCE_LINE_HEIGHT), 61.0);
+    }
+
+    #[test]
+    fn only_visible_lines_are_drawn() {
+        let line = SOURCE_LINE_HEIGHT;
+        assert_eq!(visible_lines(0.0, 3.0 * line, 100), 0..3);
+        assert_eq!(visible_lines(2.5 * line, 3.0 * line, 100), 2..6);
+        assert_eq!(visible_lines(98.0 * line, 3.0 * line, 100), 98..100);
+        assert_eq!(visible_lines(0.0, 3.0 * line, 0), 0..0);
+    }
+}
@@ -137,6 +137,30 @@ pub const MIN_COLUMN_WIDTH: f32 = 150.0;
 /// Total horizontal padding for tables (left + right margins/padding)
 pub const TABLE_HORIZONTAL_PADDING: f32 = 32.0;
 
+// ---- Math Styling ----------------------------------------------------------
+
+/// Text size of superscripts and subscripts in formulas
+pub const MATH_SCRIPT_SIZE: f32 = 13.0;
+
+/// How far superscripts are raised and subscripts lowered
+pub const MATH_SCRIPT_SHIFT: f32 = 7.0;
+
+/// Text size of footnote reference numbers
+pub const FOOTNOTE_REF_SIZE: f32 = 12.0;
+
+/// How far footnote reference numbers are raised
+pub const FOOTNOTE_REF_SHIFT: f32 = 6.0;
+
+// ---- List Styling ----------------------------------------------------------
+
+/// Room kept for list markers so item text lines up up to "99."
+pub const LIST_MARKER_WIDTH: f32 = 24.0;
+
+// ---- Alert Styling ---------------------------------------------------------
+
+/// Corner radius on the open (right) side of `> [!NOTE]` alert boxes
+pub const ALERT_BORDER_RADIUS: f32 = 4.0;
+
 // ---- Text Sizes ------------------------------------------------------------
 
 pub const BASE_TEXT_SIZE: f32 = 19.2;
@@ -162,18 +186,10 @@ pub const CONTENT_HEIGHT_BUFFER: f32 = 400.0;
 /// Higher values = more conservative (more wrapped lines estimated)
 pub const CHAR_WIDTH_MULTIPLIER: f32 = 0.4;
 
-/// Minimal safety margin at bottom to account for any rendering variance
+/// Scroll range past the end of the content, keeping the last lines clear of
+/// the status bar
 pub const BOTTOM_SCROLL_PADDING: f32 = 120.0;
 
-/// Scaling multiplier applied to estimated content height
-/// Accounts for cumulative inter-element spacing not captured in per-line estimation
-/// 1.02 = 2% extra height to account for margins between paragraphs, lists, etc.
-pub const CONTENT_HEIGHT_SCALE: f32 = 1.02;
-
-/// Average vertical spacing per block element (paragraph, list, table, code block)
-/// Accounts for .mb_2(), .my_2(), .p_3() margins in rendering
-pub const BLOCK_ELEMENT_SPACING: f32 = 4.0;
-
 /// Default viewport height used when window dimensions are unavailable
 /// Default viewport height used when window dimensions are unavailable
 pub const DEFAULT_VIEWPORT_HEIGHT: f32 = 800.0;
@@ -250,6 +266,69 @@ pub const TOC_TOGGLE_HOVER_COLOR: Rgba = Rgba {
     a: 1.0,
 };
 
+// ---- File Tree Styling -------------------------------------------------
+
+/// Width of the file tree sidebar when visible
+pub const FILE_TREE_WIDTH: f32 = 260.0;
+
+/// Height of one file or folder row in the file tree
+pub const FILE_TREE_ROW_HEIGHT: f32 = 24.0;
+
+/// Height of the file tree's header showing the directory name
+pub const FILE_TREE_HEADER_HEIGHT: f32 = 36.0;
+
+/// Indentation per folder level in the file tree
+pub const FILE_TREE_INDENT: f32 = 14.0;
+
+// ---- Source View Styling -----------------------------------------------
+
+/// Height of one source line in the split-pane source view
+pub const SOURCE_LINE_HEIGHT: f32 = 20.0;
+
+/// Text size of the source view
+pub const SOURCE_TEXT_SIZE: f32 = 13.0;
+
+/// Width of the line number gutter in the source view
+pub const SOURCE_GUTTER_WIDTH: f32 = 48.0;
+
+// ---- Status Bar Styling -------------------------------------------------
+
+/// Width beyond which the current section name in the status bar is cut off
+pub const STATUS_SECTION_MAX_WIDTH: f32 = 240.0;
+
+// ---- Notification Styling ----------------------------------------------
+
+/// Height of the bar showing how long a timed toast stays on screen
+pub const NOTIFICATION_PROGRESS_HEIGHT: f32 = 3.0;
+
+// ---- Bookmarks Overlay Styling -----------------------------------------
+
+/// Height of one row in the bookmarks list in pixels, fitting the line's
+/// heading and its text
+pub const BOOKMARK_ROW_HEIGHT: f32 = 52.0;
+
+/// Width of the bookmarks panel in pixels
+pub const BOOKMARKS_PANEL_WIDTH: f32 = 360.0;
+
+/// Gap kept between the bookmarks panel and the window's top and bottom edges
+pub const BOOKMARKS_PANEL_MARGIN: f32 = 48.0;
+
+/// Height of the bookmarks panel's header, hint line and padding
+pub const BOOKMARKS_PANEL_CHROME: f32 = 96.0;
+
+// ---- Image Report Styling ----------------------------------------------
+
+/// Height beyond which the image report's list scrolls
+pub const IMAGE_REPORT_MAX_HEIGHT: f32 = 420.0;
+
+// ---- Project Search Styling --------------------------------------------
+
+/// Width of the project search panel in pixels
+pub const PROJECT_SEARCH_WIDTH: f32 = 720.0;
+
+/// Result lines shown at once; the list follows the selection
+pub const PROJECT_SEARCH_VISIBLE_RESULTS: usize = 14;
+
 // ---- Go-to-Line Overlay Styling -----------------------------------------
 
 /// Background color for go-to-line overlay (light cyan/blue)
@@ -289,6 +368,17 @@ pub const FOCUS_BG_COLOR: Rgba = Rgba {
 /// Focus ring width in pixels
 pub const FOCUS_RING_WIDTH: f32 = 2.0;
 
+// ---- Interaction States ------------------------------------------------
+
+/// Opacity of a filled button under the mouse
+pub const HOVER_FADE_OPACITY: f32 = 0.8;
+
+/// Opacity of a filled button or link while the mouse is pressed on it
+pub const PRESSED_FADE_OPACITY: f32 = 0.6;
+
+/// Opacity of clickable elements that are disabled
+pub const DISABLED_OPACITY: f32 = 0.5;
+
 // ---- Theme-based Color Access -----------------------------------------
 
 /// Get theme colors for the given theme name
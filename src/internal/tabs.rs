@@ -0,0 +1,265 @@
+//! Open documents as tabs
+//!
+//! Every open document keeps its own [`DocumentState`] and the viewer renders
+//! the one of the active tab, so scroll position, search, the match filter,
+//! bookmarks, marks, the TOC and the link history survive switching between
+//! files. Closed tabs are kept in [`ClosedTabs`] so they can be reopened.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+
+use crate::config::RenderMode;
+use crate::internal::block_map::LinePosition;
+use crate::internal::bookmark_anchor::{self, AnchorIndex, LineAnchor};
+use crate::internal::file_handling::{load_markdown_content, load_markdown_content_unchecked};
+use crate::internal::file_state::FileState;
+use crate::internal::filter::FilterView;
+use crate::internal::history::NavigationHistory;
+use crate::internal::scroll::ScrollState;
+use crate::internal::search::SearchState;
+use crate::internal::text_format::TextFormat;
+use crate::internal::toc::TableOfContents;
+
+/// Highest tab number reachable with Cmd+1..9
+pub const MAX_NUMBERED_TABS: usize = 9;
+
+/// Closed tabs remembered for reopening
+pub const MAX_CLOSED_TABS: usize = 20;
+
+/// Per-document state of an open tab
+#[derive(Debug, Clone, Default)]
+pub struct DocumentState {
+    pub path: PathBuf,
+    pub content: String,
+    /// Encoding and line endings of the file
+    pub text_format: TextFormat,
+    /// Whether the content is rendered as markdown or plain text
+    pub render_mode: RenderMode,
+    pub scroll_state: ScrollState,
+    /// Search state (None when search is not active)
+    pub search_state: Option<SearchState>,
+    /// Current search input text
+    pub search_input: String,
+    /// Set while only blocks with search matches are shown; `content` keeps
+    /// the full document
+    pub filter: Option<FilterView>,
+    /// Bookmarked line numbers
+    pub bookmarks: Vec<usize>,
+    /// Content anchors of bookmarks, keyed by line; bookmarks added since the
+    /// last reload are anchored when the file next changes
+    pub bookmark_anchors: BTreeMap<usize, LineAnchor>,
+    /// Bookmarks whose text was not found after the last reload
+    pub stale_bookmarks: BTreeSet<usize>,
+    /// Marks and the line (and point within it) at the top of the view when
+    /// they were set
+    pub marks: HashMap<char, LinePosition>,
+    /// Table of contents extracted from the content
+    pub toc: TableOfContents,
+    /// TOC sidebar scroll position
+    pub toc_scroll_y: f32,
+    /// Documents left by following links (Cmd+Left / Cmd+Right)
+    pub history: NavigationHistory,
+    /// Modification time of the file when it was last loaded
+    pub loaded_mtime: Option<SystemTime>,
+    /// Set when the file was opened past the size and binary guards, so
+    /// reloads skip them too
+    pub skip_guards: bool,
+}
+
+impl DocumentState {
+    /// State of a document that has not been scrolled or searched yet
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            ..Self::default()
+        }
+    }
+
+    /// What to remember of the document once it is left
+    pub fn file_state(&self) -> FileState {
+        FileState {
+            path: self.path.to_string_lossy().to_string(),
+            scroll_y: self.scroll_state.scroll_y,
+            bookmarks: self.bookmarks.clone(),
+            bookmark_anchors: match self.bookmarks.is_empty() {
+                true => BTreeMap::new(),
+                false => bookmark_anchor::anchors_for(
+                    &self.bookmarks,
+                    &self.bookmark_anchors,
+                    &AnchorIndex::new(&self.content),
+                ),
+            },
+            search: self
+                .search_state
+                .as_ref()
+                .map(|state| state.query().to_string())
+                .filter(|query| !query.is_empty()),
+        }
+    }
+
+    /// Read the file again, past the guards if it was opened past them
+    pub fn read_file(&self) -> Result<String> {
+        let path = self.path.to_string_lossy();
+        match self.skip_guards {
+            true => load_markdown_content_unchecked(&path),
+            false => load_markdown_content(&path),
+        }
+    }
+
+    /// Markdown the viewer renders: the filtered view while one is active,
+    /// otherwise the document
+    pub fn shown_content(&self) -> &str {
+        match &self.filter {
+            Some(view) => &view.content,
+            None => &self.content,
+        }
+    }
+
+    /// Document line (0-based) of line `line` of the shown content
+    pub fn source_line(&self, line: usize) -> usize {
+        match &self.filter {
+            Some(view) => view.source_line(line),
+            None => line,
+        }
+    }
+
+    /// Line (0-based) of the shown content for document line `line`
+    pub fn shown_line(&self, line: usize) -> usize {
+        match &self.filter {
+            Some(view) => view.shown_line(line),
+            None => line,
+        }
+    }
+
+    /// File name shown on the tab
+    pub fn title(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Untitled".to_string())
+    }
+}
+
+/// Index of the tab showing `path`
+pub fn find_tab(tabs: &[DocumentState], path: &Path) -> Option<usize> {
+    tabs.iter().position(|tab| tab.path == path)
+}
+
+/// Tab to activate after closing `closed`: the one to its right, or the new
+/// last tab when the rightmost was closed
+pub fn tab_after_close(closed: usize, remaining: usize) -> usize {
+    closed.min(remaining.saturating_sub(1))
+}
+
+/// A tab that was closed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosedTab {
+    pub path: PathBuf,
+    /// Position the tab had
+    pub index: usize,
+}
+
+/// Recently closed tabs, most recent last
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClosedTabs {
+    closed: Vec<ClosedTab>,
+}
+
+impl ClosedTabs {
+    /// Remember that the tab at `index` showing `path` was closed
+    pub fn push(&mut self, path: PathBuf, index: usize) {
+        self.closed.retain(|tab| tab.path != path);
+        self.closed.push(ClosedTab { path, index });
+        if self.closed.len() > MAX_CLOSED_TABS {
+            self.closed.remove(0);
+        }
+    }
+
+    /// Take the most recently closed tab that is not open again in `tabs`
+    pub fn pop(&mut self, tabs: &[DocumentState]) -> Option<ClosedTab> {
+        while let Some(tab) = self.closed.pop() {
+            if find_tab(tabs, &tab.path).is_none() {
+                return Some(tab);
+            }
+        }
+        None
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.closed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabs_are_found_by_path_and_titled_by_file_name() {
+        let tabs = vec![
+            DocumentState::new(PathBuf::from("/docs/README.md")),
+            DocumentState::new(PathBuf::from("/docs/guide/intro.md")),
+        ];
+        assert_eq!(find_tab(&tabs, Path::new("/docs/guide/intro.md")), Some(1));
+        assert_eq!(find_tab(&tabs, Path::new("/docs/missing.md")), None);
+        assert_eq!(tabs[0].title(), "README.md");
+        assert_eq!(DocumentState::default().title(), "Untitled");
+    }
+
+    #[test]
+    fn closed_tabs_reopen_most_recent_first() {
+        let mut closed = ClosedTabs::default();
+        closed.push(PathBuf::from("/docs/a.md"), 1);
+        closed.push(PathBuf::from("/docs/b.md"), 2);
+        closed.push(PathBuf::from("/docs/a.md"), 0);
+
+        // b.md was opened again in the meantime, so it is skipped
+        let open = vec![DocumentState::new(PathBuf::from("/docs/b.md"))];
+        assert_eq!(
+            closed.pop(&open),
+            Some(ClosedTab {
+                path: PathBuf::from("/docs/a.md"),
+                index: 0,
+            })
+        );
+        assert_eq!(closed.pop(&open), None);
+        assert!(closed.is_empty());
+    }
+
+    #[test]
+    fn closing_a_tab_activates_its_right_neighbour() {
+        assert_eq!(tab_after_close(0, 2), 0);
+        assert_eq!(tab_after_close(1, 2), 1);
+        assert_eq!(tab_after_close(2, 2), 1);
+        assert_eq!(tab_after_close(0, 0), 0);
+    }
+
+    #[test]
+    fn confirmed_large_file_survives_reload() {
+        use crate::internal::file_handling::{LoadGuardError, MAX_FILE_SIZE_BYTES};
+
+        let path = std::env::temp_dir().join("mdv_confirmed_reload_test.md");
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_len(MAX_FILE_SIZE_BYTES + 1).unwrap();
+
+        // Reloads read the file the way it was opened
+        let guarded = DocumentState::new(path.clone()).read_file();
+        let confirmed = DocumentState {
+            skip_guards: true,
+            ..DocumentState::new(path.clone())
+        }
+        .read_file();
+
+        std::fs::remove_file(&path).ok();
+        assert!(
+            guarded
+                .unwrap_err()
+                .downcast_ref::<LoadGuardError>()
+                .is_some()
+        );
+        assert_eq!(confirmed.unwrap().len() as u64, MAX_FILE_SIZE_BYTES + 1);
+    }
+}
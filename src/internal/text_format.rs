@@ -0,0 +1,137 @@
+//! Encoding and line endings of a loaded document
+//!
+//! Files are read as UTF-8, with or without a byte order mark. Search
+//! offsets, the block map and line counting all work on `\n`, so a `\r`
+//! left before it shifted every offset past the first line and made jumps
+//! land progressively further off in files written on Windows. [`prepare`]
+//! therefore turns CRLF (and lone CR) line endings into LF as a file is
+//! loaded; [`TextFormat`] keeps what the file on disk uses for the status
+//! bar. The file itself is never changed.
+
+/// Text encoding of a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    /// UTF-8 starting with a byte order mark
+    Utf8Bom,
+}
+
+impl Encoding {
+    pub fn label(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf8Bom => "UTF-8 BOM",
+        }
+    }
+}
+
+/// How the lines of a document end on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+    /// Lone CR, as written by classic Mac OS
+    Cr,
+    /// More than one kind
+    Mixed,
+}
+
+impl LineEnding {
+    pub fn label(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+            LineEnding::Cr => "CR",
+            LineEnding::Mixed => "Mixed",
+        }
+    }
+}
+
+/// What was detected about a document's text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextFormat {
+    pub encoding: Encoding,
+    pub line_ending: LineEnding,
+}
+
+impl TextFormat {
+    /// Detect the encoding and line endings of `content`
+    pub fn detect(content: &str) -> Self {
+        let crlf = content.matches("\r\n").count();
+        let lf = content.matches('\n').count() - crlf;
+        let cr = content.matches('\r').count() - crlf;
+        Self {
+            encoding: match content.starts_with('\u{feff}') {
+                true => Encoding::Utf8Bom,
+                false => Encoding::Utf8,
+            },
+            line_ending: match (crlf, lf, cr) {
+                (0, _, 0) => LineEnding::Lf,
+                (_, 0, 0) => LineEnding::Crlf,
+                (0, 0, _) => LineEnding::Cr,
+                _ => LineEnding::Mixed,
+            },
+        }
+    }
+
+    /// Status bar text, e.g. "UTF-8 · CRLF"
+    pub fn label(&self) -> String {
+        format!("{} · {}", self.encoding.label(), self.line_ending.label())
+    }
+}
+
+/// `content` with every CRLF and lone CR turned into LF
+pub fn normalize(content: &str) -> String {
+    content.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Detect the format of freshly loaded `content` and normalize its line
+/// endings to LF
+pub fn prepare(content: String) -> (String, TextFormat) {
+    let format = TextFormat::detect(&content);
+    match format.line_ending {
+        LineEnding::Lf => (content, format),
+        _ => (normalize(&content), format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The same document with LF and with CRLF line endings
+    const LF_DOC: &str = "# Title\n\nSome *text*\n\n- one\n- two\n\n```\ncode\n```\n";
+    const CRLF_DOC: &str =
+        "# Title\r\n\r\nSome *text*\r\n\r\n- one\r\n- two\r\n\r\n```\r\ncode\r\n```\r\n";
+
+    #[test]
+    fn line_endings_and_bom_are_detected() {
+        let unix = TextFormat::detect(LF_DOC);
+        assert_eq!(unix.line_ending, LineEnding::Lf);
+        assert_eq!(unix.label(), "UTF-8 · LF");
+
+        let windows = TextFormat::detect(&format!("\u{feff}{}", CRLF_DOC));
+        assert_eq!(windows.encoding, Encoding::Utf8Bom);
+        assert_eq!(windows.label(), "UTF-8 BOM · CRLF");
+
+        assert_eq!(TextFormat::detect("a\rb\r").line_ending, LineEnding::Cr);
+        assert_eq!(
+            TextFormat::detect("a\r\nb\nc").line_ending,
+            LineEnding::Mixed
+        );
+        assert_eq!(TextFormat::detect("").line_ending, LineEnding::Lf);
+    }
+
+    #[test]
+    fn loading_normalizes_every_line_ending_to_lf() {
+        let (content, format) = prepare(CRLF_DOC.to_string());
+        assert_eq!(content, LF_DOC);
+        // The status bar still reports what the file uses
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+
+        assert_eq!(prepare("a\rb\r\nc\n".to_string()).0, "a\nb\nc\n");
+        assert_eq!(prepare(LF_DOC.to_string()).0, LF_DOC);
+    }
+}
@@ -1,9 +1,45 @@
-//! Helper function to render text with search highlighting
-use super::search::SearchState;
-use gpui::{AnyElement, IntoElement, ParentElement, Styled, div};
+//! Helper functions to render text with search highlighting
+use super::search::{MatchPosition, SearchState};
+use super::theme::ThemeColors;
+use gpui::{
+    Animation, AnimationExt, AnyElement, Hsla, IntoElement, ParentElement, Rgba, Styled, div,
+    pulsating_between,
+};
+use std::time::Duration;
+
+/// How long one pulse of the current match's border takes
+const CURRENT_MATCH_PULSE: Duration = Duration::from_millis(1200);
+
+/// Colors of search matches
+#[derive(Debug, Clone, Copy)]
+pub struct MatchColors {
+    /// Background of every match
+    pub highlight: Rgba,
+    /// Background of the current match
+    pub current: Rgba,
+    /// Pulsing border around the current match
+    pub current_border: Rgba,
+}
+
+impl MatchColors {
+    pub fn from_theme(colors: &ThemeColors) -> Self {
+        Self {
+            highlight: colors.search_bg_color,
+            current: colors.current_match_bg_color,
+            current_border: colors.current_match_border_color,
+        }
+    }
+}
 
 /// Render text with search match highlighting
-pub fn render_text_with_search(text: &str, search_state: Option<&SearchState>) -> Vec<AnyElement> {
+///
+/// Matches are drawn on the theme's search background; the current match
+/// gets its own background and a pulsing border.
+pub fn render_text_with_search(
+    text: &str,
+    search_state: Option<&SearchState>,
+    colors: MatchColors,
+) -> Vec<AnyElement> {
     let Some(search_state) = search_state else {
         // No search active, render plain text
         return vec![div().child(text.to_string()).into_any_element()];
@@ -15,42 +51,33 @@ pub fn render_text_with_search(text: &str, search_state: Option<&SearchState>) -
     }
 
     // Find matches in this specific text segment
-    let text_lower = text.to_lowercase();
-    let query = search_state.query();
-
-    if query.is_empty() {
+    let segment_matches = search_state.matches_in(text);
+    if segment_matches.is_empty() {
         return vec![div().child(text.to_string()).into_any_element()];
     }
 
-    let query_lower = query.to_lowercase();
     let mut elements = Vec::new();
     let mut last_end = 0;
 
-    // Find all matches in this text
-    let mut start = 0;
-    while let Some(pos) = text_lower[start..].find(&query_lower) {
-        let match_start = start + pos;
-        let match_end = match_start + query.len();
-
+    for m in segment_matches {
         // Add text before match
-        if match_start > last_end {
+        if m.start > last_end {
             elements.push(
                 div()
-                    .child(text[last_end..match_start].to_string())
+                    .child(text[last_end..m.start].to_string())
                     .into_any_element(),
             );
         }
 
-        // Highlight match in yellow
-        elements.push(
-            div()
-                .bg(super::style::SEARCH_BG_COLOR)
-                .child(text[match_start..match_end].to_string())
-                .into_any_element(),
-        );
+        // Highlight match with the theme's search color
+        let matched = text[m.start..m.end].to_string();
+        elements.push(render_match(
+            matched,
+            search_state.next_rendered_is_current(),
+            colors,
+        ));
 
-        last_end = match_end;
-        start = match_end;
+        last_end = m.end;
     }
 
     // Add remaining text
@@ -60,3 +87,110 @@ pub fn render_text_with_search(text: &str, search_state: Option<&SearchState>) -
 
     elements
 }
+
+/// A match on the search background, or the current match with its own
+/// background and a pulsing border
+pub fn render_match(
+    content: impl IntoElement,
+    is_current: bool,
+    colors: MatchColors,
+) -> AnyElement {
+    match is_current {
+        true => render_current_match(content, colors),
+        false => div().bg(colors.highlight).child(content).into_any_element(),
+    }
+}
+
+/// The current match: its own background and a pulsing border
+fn render_current_match(content: impl IntoElement, colors: MatchColors) -> AnyElement {
+    let border = Hsla::from(colors.current_border);
+    div()
+        .bg(colors.current)
+        .border_1()
+        .border_color(border)
+        .rounded_sm()
+        .child(content)
+        .with_animation(
+            "current-search-match",
+            Animation::new(CURRENT_MATCH_PULSE)
+                .repeat()
+                .with_easing(pulsating_between(0.3, 1.0)),
+            move |element, delta| element.border_color(border.opacity(delta)),
+        )
+        .into_any_element()
+}
+
+/// Part of a styled run of text, cut where search matches start and end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchedPiece<'a, S> {
+    pub style: S,
+    pub text: &'a str,
+    /// Index of the match the piece belongs to, if any
+    pub in_match: Option<usize>,
+}
+
+/// Cut consecutive styled `runs` (e.g. syntax-highlighted spans of one line)
+/// at the boundaries of `matches`, byte ranges over the runs joined together
+///
+/// A match spanning several runs yields one piece per run, each keeping its
+/// run's style.
+pub fn split_runs_at_matches<'a, S: Copy>(
+    runs: &[(S, &'a str)],
+    matches: &[MatchPosition],
+) -> Vec<MatchedPiece<'a, S>> {
+    let mut pieces = Vec::new();
+    let mut offset = 0;
+    for &(style, text) in runs {
+        let end = offset + text.len();
+        let mut cuts: Vec<usize> = matches
+            .iter()
+            .flat_map(|m| [m.start, m.end])
+            .filter(|&cut| cut > offset && cut < end && text.is_char_boundary(cut - offset))
+            .collect();
+        cuts.push(offset);
+        cuts.push(end);
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        for bounds in cuts.windows(2) {
+            let (start, stop) = (bounds[0], bounds[1]);
+            pieces.push(MatchedPiece {
+                style,
+                text: &text[start - offset..stop - offset],
+                in_match: matches
+                    .iter()
+                    .position(|m| m.start <= start && start < m.end),
+            });
+        }
+        offset = end;
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_are_cut_at_match_boundaries() {
+        // `let value = 1;` highlighted as keyword, name and the rest
+        let runs = [(1, "let"), (2, " value"), (3, " = 1;")];
+        let matches = [MatchPosition { start: 4, end: 10 }];
+        let pieces = split_runs_at_matches(&runs, &matches);
+        let summary: Vec<(i32, &str, Option<usize>)> = pieces
+            .iter()
+            .map(|piece| (piece.style, piece.text, piece.in_match))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "let", None),
+                (2, " ", None),
+                (2, "value", Some(0)),
+                (3, " ", Some(0)),
+                (3, "= 1;", None),
+            ]
+        );
+        assert_eq!(split_runs_at_matches(&runs, &[]).len(), 3);
+    }
+}
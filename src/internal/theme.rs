@@ -5,16 +5,23 @@
  */
 
 use anyhow::Result;
+use comrak::nodes::AlertType;
 use gpui::Rgba;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::OnceLock;
+use syntect::highlighting::{Theme as SyntectTheme, ThemeSet};
 use tracing::{error, info, warn};
 
+use super::contrast;
+
 /// Global theme registry
 static THEME_REGISTRY: OnceLock<ThemeRegistry> = OnceLock::new();
 
+/// Bundled syntect themes used for code highlighting
+static SYNTECT_THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+
 /// Initialize the theme registry
 pub fn init(themes_dir: impl AsRef<Path>) -> Result<()> {
     let registry = ThemeRegistry::load_from_dir(themes_dir)?;
@@ -31,6 +38,16 @@ pub fn registry() -> &'static ThemeRegistry {
     })
 }
 
+/// Default theme for tests, loaded from the bundled `themes/` directory
+#[cfg(test)]
+pub fn test_theme() -> ActiveTheme {
+    THEME_REGISTRY.get_or_init(|| {
+        ThemeRegistry::load_from_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("themes"))
+            .expect("bundled themes should load")
+    });
+    ActiveTheme::resolve("Zoegi Light")
+}
+
 /// Theme variant (Light or Dark) - Deprecated enum usage in config, but useful for logic
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
 pub enum ThemeMode {
@@ -60,6 +77,41 @@ impl ThemeMode {
     }
 }
 
+/// Syntect theme for code highlighting in the given mode
+pub fn syntect_theme(mode: ThemeMode) -> &'static SyntectTheme {
+    let theme_set = SYNTECT_THEME_SET.get_or_init(ThemeSet::load_defaults);
+    theme_set
+        .themes
+        .get(mode.syntect_theme())
+        .or_else(|| theme_set.themes.values().next())
+        .expect("syntect ships default themes")
+}
+
+/// Everything rendering needs from the selected theme, resolved together
+///
+/// UI colors (including image placeholders) and the code highlighting theme
+/// are looked up once when the theme is selected. Switching themes replaces the
+/// whole bundle so no part of the UI keeps colors from the previous mode.
+#[derive(Debug, Clone)]
+pub struct ActiveTheme {
+    /// Theme name as selected in the config (may differ from `colors.name` on fallback)
+    pub name: String,
+    pub colors: &'static ThemeColors,
+    pub syntect_theme: &'static SyntectTheme,
+}
+
+impl ActiveTheme {
+    /// Resolve the theme registered under `name`, falling back to the default theme
+    pub fn resolve(name: &str) -> Self {
+        let colors = crate::internal::style::get_theme_colors(name);
+        Self {
+            name: name.to_string(),
+            colors,
+            syntect_theme: syntect_theme(colors.mode),
+        }
+    }
+}
+
 /// JSON Schema for top-level theme file
 #[derive(Debug, Deserialize)]
 struct ThemeFile {
@@ -89,6 +141,7 @@ pub struct ThemeColors {
     pub copy_button_text_color: Rgba,
     pub search_bg_color: Rgba,
     pub current_match_bg_color: Rgba,
+    pub current_match_border_color: Rgba,
     pub blockquote_border_color: Rgba,
     pub link_color: Rgba,
     pub hover_link_color: Rgba,
@@ -110,6 +163,27 @@ pub struct ThemeColors {
     pub pdf_error_bg_color: Rgba,
     pub pdf_warning_bg_color: Rgba,
     pub pdf_notification_text_color: Rgba,
+    pub search_overlay_bg_color: Rgba,
+    pub search_overlay_text_color: Rgba,
+    pub file_deleted_bg_color: Rgba,
+    pub file_deleted_text_color: Rgba,
+    pub overlay_backdrop_color: Rgba,
+    pub panel_border_color: Rgba,
+    pub focus_bg_color: Rgba,
+    pub image_placeholder_bg_color: Rgba,
+    pub image_placeholder_border_color: Rgba,
+    pub image_placeholder_label_color: Rgba,
+    pub image_placeholder_path_color: Rgba,
+    pub alert_note_color: Rgba,
+    pub alert_note_bg_color: Rgba,
+    pub alert_tip_color: Rgba,
+    pub alert_tip_bg_color: Rgba,
+    pub alert_important_color: Rgba,
+    pub alert_important_bg_color: Rgba,
+    pub alert_warning_color: Rgba,
+    pub alert_warning_bg_color: Rgba,
+    pub alert_caution_color: Rgba,
+    pub alert_caution_bg_color: Rgba,
 }
 
 impl Default for ThemeColors {
@@ -125,7 +199,7 @@ impl Default for ThemeColors {
 
 impl ThemeColors {
     /// Create ThemeColors from JSON data
-    fn from_json(
+    pub(crate) fn from_json(
         name: &str,
         mode: ThemeMode,
         colors: &HashMap<String, String>,
@@ -166,6 +240,28 @@ impl ThemeColors {
             rgba_from_hex(default)
         };
 
+        // GitHub alerts: colors.base.* accents (GitHub's own per mode by
+        // default) over a faint tint of the same color unless
+        // colors.alert.<type>.background is set
+        let alert_accent = |key: &str, light: &str, dark: &str| {
+            get_color(
+                key,
+                match mode {
+                    ThemeMode::Light => light,
+                    ThemeMode::Dark => dark,
+                },
+            )
+        };
+        let alert_bg = |key: &str, accent: Rgba| match colors.get(key) {
+            Some(hex) => rgba_from_hex(hex),
+            None => Rgba { a: 0.1, ..accent },
+        };
+        let note = alert_accent("base.blue", "#0969daff", "#4493f8ff");
+        let tip = alert_accent("base.green", "#1a7f37ff", "#3fb950ff");
+        let important = alert_accent("base.magenta", "#8250dfff", "#ab7df8ff");
+        let warning = alert_accent("base.yellow", "#9a6700ff", "#d29922ff");
+        let caution = alert_accent("base.red", "#d1242fff", "#f85149ff");
+
         Self {
             name: name.to_string(),
             mode,
@@ -199,6 +295,9 @@ impl ThemeColors {
             // colors.selection.background: "#568b9926"
             current_match_bg_color: get_color("selection.background", "#568b9926"),
 
+            // colors.primary.background: "#377961ff"
+            current_match_border_color: get_color("primary.background", "#377961ff"),
+
             // colors.border: "#0000001a"
             blockquote_border_color: get_color("border", "#0000001a"),
 
@@ -280,6 +379,77 @@ impl ThemeColors {
 
             // colors.primary.foreground: "#ffffffff"
             pdf_notification_text_color: get_color("primary.foreground", "#ffffffff"),
+
+            // highlight.warning.background, falling back to the classic yellow search bar
+            search_overlay_bg_color: get_hl("warning.background", "#fff299f2"),
+
+            // colors.foreground reads well on the theme's own warning background
+            search_overlay_text_color: get_color("foreground", "#000000ff"),
+
+            // colors.danger.background / colors.danger.foreground
+            file_deleted_bg_color: get_color("danger.background", "#ff6666f2"),
+            file_deleted_text_color: get_color("danger.foreground", "#ffffffff"),
+
+            // Dim layer behind modal overlays (help, file finder)
+            overlay_backdrop_color: get_color("overlay", "#000000b3"),
+
+            // colors.border
+            panel_border_color: get_color("border", "#ccccccff"),
+
+            // colors.list.active.background: translucent highlight for keyboard focus
+            focus_bg_color: get_color("list.active.background", "#add8ff40"),
+
+            // colors.muted.* for the image placeholder, with defaults per mode
+            image_placeholder_bg_color: get_color(
+                "muted.background",
+                match mode {
+                    ThemeMode::Light => "#f2f2f2ff",
+                    ThemeMode::Dark => "#2e2e2eff",
+                },
+            ),
+            image_placeholder_border_color: get_color(
+                "border",
+                match mode {
+                    ThemeMode::Light => "#ccccccff",
+                    ThemeMode::Dark => "#4d4d4dff",
+                },
+            ),
+            image_placeholder_label_color: get_color(
+                "muted.foreground",
+                match mode {
+                    ThemeMode::Light => "#666666ff",
+                    ThemeMode::Dark => "#b3b3b3ff",
+                },
+            ),
+            image_placeholder_path_color: get_color(
+                "muted.foreground",
+                match mode {
+                    ThemeMode::Light => "#808080ff",
+                    ThemeMode::Dark => "#999999ff",
+                },
+            ),
+
+            alert_note_color: note,
+            alert_note_bg_color: alert_bg("alert.note.background", note),
+            alert_tip_color: tip,
+            alert_tip_bg_color: alert_bg("alert.tip.background", tip),
+            alert_important_color: important,
+            alert_important_bg_color: alert_bg("alert.important.background", important),
+            alert_warning_color: warning,
+            alert_warning_bg_color: alert_bg("alert.warning.background", warning),
+            alert_caution_color: caution,
+            alert_caution_bg_color: alert_bg("alert.caution.background", caution),
+        }
+    }
+
+    /// Accent (border, icon and title) and background colors of a GitHub alert
+    pub fn alert_colors(&self, alert_type: AlertType) -> (Rgba, Rgba) {
+        match alert_type {
+            AlertType::Note => (self.alert_note_color, self.alert_note_bg_color),
+            AlertType::Tip => (self.alert_tip_color, self.alert_tip_bg_color),
+            AlertType::Important => (self.alert_important_color, self.alert_important_bg_color),
+            AlertType::Warning => (self.alert_warning_color, self.alert_warning_bg_color),
+            AlertType::Caution => (self.alert_caution_color, self.alert_caution_bg_color),
         }
     }
 }
@@ -309,6 +479,9 @@ impl ThemeRegistry {
                     Ok((family_name, variants)) => {
                         let mut variant_names = Vec::new();
                         for variant in variants {
+                            for issue in contrast::check_theme(&variant) {
+                                warn!("Theme '{}': {}", variant.name, issue);
+                            }
                             let name = variant.name.clone();
                             variant_names.push(name.clone());
                             themes.insert(name, variant);
@@ -470,6 +643,41 @@ mod tests {
         (a - b).abs() < EPS
     }
 
+    #[test]
+    fn syntect_theme_follows_mode() {
+        let light = syntect_theme(ThemeMode::Light);
+        let dark = syntect_theme(ThemeMode::Dark);
+        assert_ne!(light.name, dark.name);
+    }
+
+    #[test]
+    fn placeholder_colors_default_per_mode() {
+        let light = ThemeColors::from_json(
+            "Light",
+            ThemeMode::Light,
+            &HashMap::new(),
+            &serde_json::Value::Null,
+        );
+        let dark = ThemeColors::from_json(
+            "Dark",
+            ThemeMode::Dark,
+            &HashMap::new(),
+            &serde_json::Value::Null,
+        );
+        assert_ne!(
+            light.image_placeholder_bg_color,
+            dark.image_placeholder_bg_color
+        );
+
+        let colors = HashMap::from([("muted.background".to_string(), "#123456ff".to_string())]);
+        let themed =
+            ThemeColors::from_json("Themed", ThemeMode::Dark, &colors, &serde_json::Value::Null);
+        assert_eq!(
+            themed.image_placeholder_bg_color,
+            rgba_from_hex("#123456ff")
+        );
+    }
+
     #[test]
     fn test_full_hex_with_hash() {
         // #11223344 -> r=17,g=34,b=51,a=68
@@ -1,9 +1,22 @@
 //! Table of Contents (TOC) module
 //!
 //! Extracts headings from Markdown documents and provides hierarchical navigation structure.
+//! Every heading also gets a GitHub-style anchor so `[Usage](#usage)` links
+//! can scroll to it. Footnotes get `#fn-<name>` anchors on their definitions
+//! and `#fnref-<name>` anchors on their first reference, for the jump to a
+//! footnote and the back-link from it.
 
 use comrak::arena_tree::Node;
 use comrak::nodes::{Ast, NodeValue};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+
+use crate::internal::inspect::{node_text, slugify};
+use crate::internal::search::MatchPosition;
+
+/// How far below the top of the viewport a heading must be before its section
+/// is highlighted, so the section is well into view first
+pub const SECTION_HIGHLIGHT_OFFSET: f32 = 100.0;
 
 /// A single entry in the table of contents
 #[derive(Debug, Clone)]
@@ -14,6 +27,68 @@ pub struct TocEntry {
     pub level: u8,
     /// Approximate vertical position in the document (line-based)
     pub line_number: usize,
+    /// Anchor slug of the heading, as `#anchor` links name it
+    pub anchor: String,
+}
+
+/// Search matches falling into one TOC section
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionMatches {
+    /// Heading text, or "Top" for matches before the first TOC entry
+    pub title: String,
+    pub count: usize,
+    /// Index of the section's first match in the search results
+    pub first_match: usize,
+}
+
+/// Something the TOC's right-click menu does with an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TocMenuAction {
+    /// Copy a `[Heading](#anchor)` link to the clipboard
+    CopyLink,
+    /// Export the section as HTML next to the document
+    ExportSection,
+    /// Collapse every other branch of the TOC
+    CollapseOthers,
+    /// Open every collapsed entry again
+    ExpandAll,
+    /// Set a mark (the next key pressed) at the heading
+    SetMark,
+}
+
+impl TocMenuAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            TocMenuAction::CopyLink => "Copy heading link",
+            TocMenuAction::ExportSection => "Export section",
+            TocMenuAction::CollapseOthers => "Collapse others",
+            TocMenuAction::ExpandAll => "Expand all",
+            TocMenuAction::SetMark => "Set mark here",
+        }
+    }
+
+    /// Items of the menu; "Expand all" is offered while entries are collapsed
+    pub fn items(any_collapsed: bool) -> Vec<Self> {
+        let mut items = vec![
+            TocMenuAction::CopyLink,
+            TocMenuAction::ExportSection,
+            TocMenuAction::CollapseOthers,
+        ];
+        if any_collapsed {
+            items.push(TocMenuAction::ExpandAll);
+        }
+        items.push(TocMenuAction::SetMark);
+        items
+    }
+}
+
+/// The TOC's right-click menu while it is open
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TocMenu {
+    /// Entry the menu was opened on
+    pub entry: usize,
+    /// Window y of the click, where the menu opens
+    pub y: f32,
 }
 
 /// Table of Contents for a Markdown document
@@ -21,6 +96,48 @@ pub struct TocEntry {
 pub struct TableOfContents {
     /// List of heading entries in document order
     pub entries: Vec<TocEntry>,
+    /// Anchor slug of every heading (all levels) to its line (0-based)
+    ///
+    /// Repeated slugs get `-1`, `-2`, ... suffixes like on GitHub.
+    pub anchors: HashMap<String, usize>,
+    heading_anchors: HeadingAnchors,
+}
+
+/// Anchors given to the headings of one document, in document order
+///
+/// A heading's anchor is its slug, with `-1`, `-2`, ... appended to repeats
+/// of an earlier anchor like on GitHub. The TOC, the rendered headings and
+/// the HTML export all number headings through this, so an anchor copied
+/// from one works in the others.
+#[derive(Debug, Clone, Default)]
+pub struct HeadingAnchors {
+    used: HashSet<String>,
+}
+
+impl HeadingAnchors {
+    /// Anchor of the next heading in the document, titled `text`
+    pub fn assign(&mut self, text: &str) -> String {
+        let slug = slugify(text);
+        let unique = (0..)
+            .map(|n| match n {
+                0 => slug.clone(),
+                n => format!("{}-{}", slug, n),
+            })
+            .find(|candidate| !self.used.contains(candidate))
+            .unwrap_or(slug);
+        self.used.insert(unique.clone());
+        unique
+    }
+}
+
+/// Anchor of a footnote's definition, e.g. `fn-note` for `[^note]`
+pub fn footnote_anchor(name: &str) -> String {
+    format!("fn-{}", slugify(name))
+}
+
+/// Anchor of a footnote's first reference, the target of its back-link
+pub fn footnote_ref_anchor(name: &str) -> String {
+    format!("fnref-{}", slugify(name))
 }
 
 impl TableOfContents {
@@ -28,6 +145,8 @@ impl TableOfContents {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            anchors: HashMap::new(),
+            heading_anchors: HeadingAnchors::default(),
         }
     }
 
@@ -42,21 +161,36 @@ impl TableOfContents {
     fn extract_headings<'a>(&mut self, node: &'a Node<'a, std::cell::RefCell<Ast>>) {
         let ast = node.data.borrow();
 
-        // Check if this node is a heading
-        if let NodeValue::Heading(heading) = &ast.value {
-            let level = heading.level;
-            // Only include levels 2, 3, and 4 as requested
-            if (2..=4).contains(&level) {
-                let text = extract_text_from_node(node);
-                // sourcepos.start.line is 1-based, convert to 0-based
-                let line_number = ast.sourcepos.start.line.saturating_sub(1);
-
-                self.entries.push(TocEntry {
-                    text,
-                    level,
-                    line_number,
-                });
+        // sourcepos.start.line is 1-based, convert to 0-based
+        let line_number = ast.sourcepos.start.line.saturating_sub(1);
+        match &ast.value {
+            NodeValue::Heading(heading) => {
+                let level = heading.level;
+                let anchor = self.heading_anchors.assign(&node_text(node));
+                self.anchors.insert(anchor.clone(), line_number);
+
+                // Only include levels 2, 3, and 4 as requested
+                if (2..=4).contains(&level) {
+                    let text = extract_text_from_node(node);
+
+                    self.entries.push(TocEntry {
+                        text,
+                        level,
+                        line_number,
+                        anchor,
+                    });
+                }
             }
+            NodeValue::FootnoteDefinition(definition) => {
+                self.anchors
+                    .insert(footnote_anchor(&definition.name), line_number);
+            }
+            NodeValue::FootnoteReference(reference) => {
+                self.anchors
+                    .entry(footnote_ref_anchor(&reference.name))
+                    .or_insert(line_number);
+            }
+            _ => {}
         }
 
         // Recursively process children
@@ -65,21 +199,33 @@ impl TableOfContents {
         }
     }
 
+    /// Line (0-based) of the heading an `#anchor` link points to
+    ///
+    /// The leading `#` is optional; the anchor is slugified first, so
+    /// `#Usage` and `#my%20section` find `usage` and `my-section`.
+    pub fn line_for_anchor(&self, anchor: &str) -> Option<usize> {
+        let anchor = anchor.strip_prefix('#').unwrap_or(anchor);
+        self.anchors
+            .get(&slugify(&anchor.replace("%20", " ")))
+            .copied()
+    }
+
     /// Find the current active section based on scroll position
     /// Returns the index of the TocEntry, or None if no entries
     pub fn find_current_section(&self, scroll_y: f32, line_height: f32) -> Option<usize> {
+        let adjusted_scroll_y = (scroll_y + SECTION_HIGHLIGHT_OFFSET).max(0.0);
+
+        // Convert scroll position to approximate line number
+        self.find_section_at_line((adjusted_scroll_y / line_height) as usize)
+    }
+
+    /// Index of the section containing `current_line` (0-based), or None if
+    /// there are no entries
+    pub fn find_section_at_line(&self, current_line: usize) -> Option<usize> {
         if self.entries.is_empty() {
             return None;
         }
 
-        // Add offset so highlighting doesn't trigger too early
-        // This ensures the section is well into view before highlighting
-        const HIGHLIGHT_OFFSET: f32 = 100.0;
-        let adjusted_scroll_y = (scroll_y + HIGHLIGHT_OFFSET).max(0.0);
-
-        // Convert scroll position to approximate line number
-        let current_line = (adjusted_scroll_y / line_height) as usize;
-
         // Find the last entry whose line_number is <= current_line
         let mut current_idx = None;
         for (idx, entry) in self.entries.iter().enumerate() {
@@ -91,6 +237,108 @@ impl TableOfContents {
 
         current_idx
     }
+
+    /// Source lines (0-based) of entry `index`'s section: from its heading up
+    /// to the next entry of the same or a higher level, or `line_count`
+    pub fn section_lines(&self, index: usize, line_count: usize) -> Range<usize> {
+        let Some(entry) = self.entries.get(index) else {
+            return line_count..line_count;
+        };
+        let end = self.entries[index + 1..]
+            .iter()
+            .find(|next| next.level <= entry.level)
+            .map_or(line_count, |next| next.line_number);
+        entry.line_number.min(line_count)..end.min(line_count)
+    }
+
+    /// Whether entry `index` has subsections listed below it
+    pub fn has_children(&self, index: usize) -> bool {
+        match (self.entries.get(index), self.entries.get(index + 1)) {
+            (Some(entry), Some(next)) => next.level > entry.level,
+            _ => false,
+        }
+    }
+
+    /// Indices of the entries enclosing entry `index`, outermost first
+    fn ancestors(&self, index: usize) -> Vec<usize> {
+        let mut ancestors = Vec::new();
+        let mut level = self.entries.get(index).map_or(0, |entry| entry.level);
+        for (idx, entry) in self.entries[..index.min(self.entries.len())]
+            .iter()
+            .enumerate()
+            .rev()
+        {
+            if entry.level < level {
+                ancestors.insert(0, idx);
+                level = entry.level;
+            }
+        }
+        ancestors
+    }
+
+    /// Entries to collapse so only entry `index`, its subsections and the
+    /// entries enclosing it stay open
+    pub fn collapse_others(&self, index: usize) -> BTreeSet<usize> {
+        let ancestors = self.ancestors(index);
+        (0..self.entries.len())
+            .filter(|&idx| idx != index && !ancestors.contains(&idx) && self.has_children(idx))
+            .collect()
+    }
+
+    /// Indices of the entries shown when the entries in `collapsed` hide
+    /// their subsections
+    pub fn visible_entries(&self, collapsed: &BTreeSet<usize>) -> Vec<usize> {
+        let mut visible = Vec::new();
+        // Level of the collapsed entry whose subsections are being skipped
+        let mut hiding_below: Option<u8> = None;
+        for (idx, entry) in self.entries.iter().enumerate() {
+            match hiding_below {
+                Some(level) if entry.level > level => continue,
+                _ => hiding_below = None,
+            }
+            visible.push(idx);
+            if collapsed.contains(&idx) {
+                hiding_below = Some(entry.level);
+            }
+        }
+        visible
+    }
+
+    /// Group search matches in `content` by the TOC section they fall in
+    ///
+    /// Sections appear in document order; those without matches are left out.
+    pub fn matches_by_section(
+        &self,
+        content: &str,
+        matches: &[MatchPosition],
+    ) -> Vec<SectionMatches> {
+        let mut groups: Vec<(Option<usize>, SectionMatches)> = Vec::new();
+        let (mut line, mut scanned) = (0, 0);
+        for (index, m) in matches.iter().enumerate() {
+            let start = m.start.min(content.len()).max(scanned);
+            line += content.as_bytes()[scanned..start]
+                .iter()
+                .filter(|&&byte| byte == b'\n')
+                .count();
+            scanned = start;
+
+            let section = self.find_section_at_line(line);
+            match groups.last_mut() {
+                Some((last, group)) if *last == section => group.count += 1,
+                _ => groups.push((
+                    section,
+                    SectionMatches {
+                        title: section
+                            .map(|idx| self.entries[idx].text.clone())
+                            .unwrap_or_else(|| "Top".to_string()),
+                        count: 1,
+                        first_match: index,
+                    },
+                )),
+            }
+        }
+        groups.into_iter().map(|(_, group)| group).collect()
+    }
 }
 
 /// Extract plain text content from a node and its children
@@ -168,6 +416,85 @@ mod tests {
         assert_eq!(toc.entries[2].level, 2);
     }
 
+    #[test]
+    fn anchors_cover_all_headings_and_suffix_repeats() {
+        let arena = Arena::new();
+        let options = Options::default();
+        let markdown = "# Title\n\n## Usage\n\n### Usage\n\n#### `run()` Options!\n";
+        let root = parse_document(&arena, markdown, &options);
+        let toc = TableOfContents::from_ast(root);
+
+        assert_eq!(toc.line_for_anchor("#title"), Some(0));
+        assert_eq!(toc.line_for_anchor("usage"), Some(2));
+        assert_eq!(toc.line_for_anchor("#usage-1"), Some(4));
+        assert_eq!(toc.line_for_anchor("#Run-Options"), Some(6));
+        assert_eq!(toc.line_for_anchor("#missing"), None);
+    }
+
+    #[test]
+    fn heading_anchors_skip_suffixes_already_taken() {
+        let mut anchors = HeadingAnchors::default();
+        let assigned: Vec<_> = ["A-1", "A", "A", "a"]
+            .iter()
+            .map(|text| anchors.assign(text))
+            .collect();
+        assert_eq!(assigned, vec!["a-1", "a", "a-2", "a-3"]);
+    }
+
+    #[test]
+    fn footnotes_anchor_their_definition_and_first_reference() {
+        let arena = Arena::new();
+        let markdown = "Intro[^note].\n\nAgain[^note].\n\n[^note]: The note.\n";
+        let root = parse_document(&arena, markdown, &crate::internal::render::parse_options());
+        let toc = TableOfContents::from_ast(root);
+
+        assert_eq!(toc.line_for_anchor("#fn-note"), Some(4));
+        assert_eq!(toc.line_for_anchor("#fnref-note"), Some(0));
+        assert_eq!(footnote_anchor("My Note"), "fn-my-note");
+    }
+
+    #[test]
+    fn matches_are_counted_per_section() {
+        let arena = Arena::new();
+        let options = Options::default();
+        let markdown = "intro foo\n## Installation\nfoo foo\n## Usage\nbar\n## Config\nfoo\n";
+        let root = parse_document(&arena, markdown, &options);
+        let toc = TableOfContents::from_ast(root);
+        let state = crate::internal::search::SearchState::new(
+            "foo".to_string(),
+            markdown,
+            crate::internal::search::SearchMode::Plain,
+        );
+
+        let sections = toc.matches_by_section(markdown, state.matches());
+        let summary: Vec<_> = sections
+            .iter()
+            .map(|s| (s.title.as_str(), s.count, s.first_match))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("Top", 1, 0), ("Installation", 2, 1), ("Config", 1, 3)]
+        );
+    }
+
+    #[test]
+    fn sections_collapse_around_an_entry() {
+        let arena = Arena::new();
+        let markdown = "## Intro\n\n### Goals\n\n## Usage\n\n### Install\n\n#### Linux\n\n### Run\n\n## Usage\n";
+        let root = parse_document(&arena, markdown, &Options::default());
+        let toc = TableOfContents::from_ast(root);
+
+        assert_eq!(toc.entries[6].anchor, "usage-1");
+        assert_eq!(toc.section_lines(2, 14), 4..12);
+        assert_eq!(toc.section_lines(3, 14), 6..10);
+        assert_eq!(toc.section_lines(6, 14), 12..14);
+
+        let collapsed = toc.collapse_others(4);
+        assert_eq!(collapsed, BTreeSet::from([0]));
+        assert_eq!(toc.visible_entries(&collapsed), vec![0, 2, 3, 4, 5, 6]);
+        assert_eq!(toc.visible_entries(&BTreeSet::from([2])), vec![0, 1, 2, 6]);
+    }
+
     #[test]
     fn test_find_current_section() {
         let mut toc = TableOfContents::new();
@@ -175,16 +502,19 @@ mod tests {
             text: "Section 1".to_string(),
             level: 2,
             line_number: 0,
+            anchor: "section-1".to_string(),
         });
         toc.entries.push(TocEntry {
             text: "Section 2".to_string(),
             level: 2,
             line_number: 10,
+            anchor: "section-2".to_string(),
         });
         toc.entries.push(TocEntry {
             text: "Section 3".to_string(),
             level: 2,
             line_number: 20,
+            anchor: "section-3".to_string(),
         });
 
         // At line 2 (scroll_y = 40), adjusted = 140 (line 7) -> Section 1 (starts at 0)
@@ -0,0 +1,148 @@
+//! First-run tour
+//!
+//! The first time the viewer starts (no state file yet) a short tour walks
+//! through search, the table of contents, themes and export, one card per
+//! feature with its shortcut for the current platform. Right, Enter or Space
+//! go to the next card, Left to the previous one, and Escape skips the rest.
+//! Once finished or skipped, the tour is recorded as seen in the state file
+//! and not shown again.
+
+use crate::internal::keymap::{Binding, Modifier, Platform};
+
+/// One card of the tour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TourStep {
+    pub title: &'static str,
+    pub body: &'static str,
+    /// Shortcut that opens the feature
+    pub binding: Binding,
+}
+
+/// Cards of the tour, in order
+pub const TOUR_STEPS: &[TourStep] = &[
+    TourStep {
+        title: "Search",
+        body: "Find text in the document. Enter jumps to the next match, and Alt+R / W / C \
+               switch to regex, whole word or match case.",
+        binding: Binding {
+            modifiers: &[Modifier::Primary],
+            key: "F",
+            description: "Search",
+        },
+    },
+    TourStep {
+        title: "Table of Contents",
+        body: "Open the sidebar listing every heading; click one to jump to it. The ☰ \
+               button in the top right corner does the same.",
+        binding: Binding {
+            modifiers: &[Modifier::Platform],
+            key: "Z",
+            description: "Toggle TOC",
+        },
+    },
+    TourStep {
+        title: "Themes",
+        body: "Switch between light and dark, and cycle through the theme families with \
+               the shortcut plus Shift + N.",
+        binding: Binding {
+            modifiers: &[Modifier::Primary, Modifier::Shift],
+            key: "T",
+            description: "Toggle Theme",
+        },
+    },
+    TourStep {
+        title: "Export",
+        body: "Save the document as a PDF next to it. Add Shift for HTML or Alt for EPUB; \
+               every shortcut is listed in the help overlay.",
+        binding: Binding {
+            modifiers: &[Modifier::Primary],
+            key: "E",
+            description: "Export to PDF",
+        },
+    },
+];
+
+/// Result of a key press in the tour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TourOutcome {
+    /// The tour stays open
+    Pending,
+    /// The last card was passed or the tour was skipped
+    Finished,
+}
+
+/// Position in the tour
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tour {
+    /// Index of the card shown
+    pub step: usize,
+}
+
+impl Tour {
+    /// The card shown
+    pub fn current(&self) -> &'static TourStep {
+        &TOUR_STEPS[self.step.min(TOUR_STEPS.len() - 1)]
+    }
+
+    /// Shortcut of the card shown, with the key names of `platform`
+    pub fn shortcut(&self, platform: Platform) -> String {
+        self.current().binding.label(platform)
+    }
+
+    /// Whether the card shown is the last one
+    pub fn is_last(&self) -> bool {
+        self.step + 1 >= TOUR_STEPS.len()
+    }
+
+    /// Go to the next card, finishing after the last one
+    pub fn advance(&mut self) -> TourOutcome {
+        match self.is_last() {
+            true => TourOutcome::Finished,
+            false => {
+                self.step += 1;
+                TourOutcome::Pending
+            }
+        }
+    }
+
+    /// Handle a key press; every key is consumed while the tour is open
+    pub fn handle_key(&mut self, key: &str) -> TourOutcome {
+        match key {
+            "right" | "enter" | "space" => self.advance(),
+            "left" => {
+                self.step = self.step.saturating_sub(1);
+                TourOutcome::Pending
+            }
+            "escape" => TourOutcome::Finished,
+            _ => TourOutcome::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tour_walks_through_every_card() {
+        let mut tour = Tour::default();
+        assert_eq!(tour.current().title, "Search");
+        assert_eq!(tour.shortcut(Platform::Linux), "Ctrl + F");
+        assert_eq!(tour.handle_key("left"), TourOutcome::Pending);
+        assert_eq!(tour.step, 0);
+
+        for _ in 1..TOUR_STEPS.len() {
+            assert_eq!(tour.handle_key("enter"), TourOutcome::Pending);
+        }
+        assert!(tour.is_last());
+        assert_eq!(tour.shortcut(Platform::MacOs), "Cmd + E");
+        assert_eq!(tour.handle_key("q"), TourOutcome::Pending);
+        assert_eq!(tour.handle_key("right"), TourOutcome::Finished);
+    }
+
+    #[test]
+    fn escape_skips_the_tour() {
+        let mut tour = Tour::default();
+        assert_eq!(tour.handle_key("escape"), TourOutcome::Finished);
+    }
+}
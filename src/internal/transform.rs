@@ -0,0 +1,227 @@
+//! AST transform pipeline
+//!
+//! Transform passes run between `parse_document` and rendering, on every
+//! backend (GUI, TOC, keyboard focus and terminal output). Each pass rewrites
+//! the comrak tree in place, e.g. to rewrite links, annotate lint findings or
+//! expand shorthands. New nodes are allocated in the arena the tree was
+//! parsed into.
+//!
+//! Passes are registered once at startup with an order; lower orders run
+//! first and passes with the same order run in registration order. Plugin
+//! `transform_ast` hooks run as a single pass at [`PLUGIN_PASS_ORDER`]. A
+//! failing pass is reported and skipped; the remaining passes still run.
+
+use anyhow::Result;
+use comrak::Arena;
+use comrak::nodes::AstNode;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+/// Order at which plugin `transform_ast` hooks run
+pub const PLUGIN_PASS_ORDER: i32 = 0;
+
+/// What a pass knows about the document being transformed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransformContext<'p> {
+    /// Path of the markdown file, when the content came from one
+    pub path: Option<&'p Path>,
+}
+
+/// A single AST transformation step
+pub trait TransformPass: Send + Sync {
+    /// Name used in logs and error reports
+    fn name(&self) -> &str;
+
+    /// Rewrite the document in place
+    ///
+    /// Runs on every parse (the GUI parses once per content change), so it
+    /// should be cheap.
+    fn run<'a>(
+        &self,
+        arena: &'a Arena<'a>,
+        root: &'a AstNode<'a>,
+        context: &TransformContext,
+    ) -> Result<()>;
+}
+
+/// A pass that failed while transforming a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformError {
+    pub pass: String,
+    pub message: String,
+}
+
+impl fmt::Display for TransformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pass, self.message)
+    }
+}
+
+struct RegisteredPass {
+    order: i32,
+    pass: Arc<dyn TransformPass>,
+}
+
+static PASSES: RwLock<Vec<RegisteredPass>> = RwLock::new(Vec::new());
+
+/// Register a pass to run at `order` (lower runs first)
+pub fn register_pass(order: i32, pass: impl TransformPass + 'static) {
+    info!(
+        "Registered transform pass: {} (order {})",
+        pass.name(),
+        order
+    );
+    let mut passes = PASSES.write().unwrap_or_else(|e| e.into_inner());
+    // Insert after every pass with the same or a lower order to keep ties stable
+    let index = passes.partition_point(|registered| registered.order <= order);
+    passes.insert(
+        index,
+        RegisteredPass {
+            order,
+            pass: Arc::new(pass),
+        },
+    );
+}
+
+/// Run plugin `transform_ast` hooks as part of the pipeline
+struct PluginPass;
+
+impl TransformPass for PluginPass {
+    fn name(&self) -> &str {
+        "plugins"
+    }
+
+    fn run<'a>(
+        &self,
+        _arena: &'a Arena<'a>,
+        root: &'a AstNode<'a>,
+        _context: &TransformContext,
+    ) -> Result<()> {
+        crate::internal::plugin::transform_ast(root);
+        Ok(())
+    }
+}
+
+/// Run every registered pass over `root`, parsed into `arena`, in order
+///
+/// Returns the passes that failed; their errors are also logged.
+pub fn apply<'a>(
+    arena: &'a Arena<'a>,
+    root: &'a AstNode<'a>,
+    context: &TransformContext,
+) -> Vec<TransformError> {
+    let passes: Vec<(i32, Arc<dyn TransformPass>)> = PASSES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|registered| (registered.order, registered.pass.clone()))
+        .collect();
+
+    // Splice the plugin hooks in after the passes that run before them
+    let plugin_index = passes.partition_point(|(order, _)| *order <= PLUGIN_PASS_ORDER);
+    let plugin_pass: Arc<dyn TransformPass> = Arc::new(PluginPass);
+    let ordered = passes[..plugin_index]
+        .iter()
+        .map(|(_, pass)| pass)
+        .chain(std::iter::once(&plugin_pass))
+        .chain(passes[plugin_index..].iter().map(|(_, pass)| pass));
+
+    ordered
+        .filter_map(|pass| {
+            pass.run(arena, root, context).err().map(|e| {
+                warn!("Transform pass '{}' failed: {:#}", pass.name(), e);
+                TransformError {
+                    pass: pass.name().to_string(),
+                    message: format!("{:#}", e),
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use comrak::nodes::NodeValue;
+    use comrak::{Arena, parse_document};
+    use std::sync::Mutex;
+
+    /// Order in which the test passes ran
+    static RUNS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// Records its name when run on the test document, optionally failing
+    struct RecordingPass {
+        name: &'static str,
+        fail: bool,
+    }
+
+    impl TransformPass for RecordingPass {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn run<'a>(
+            &self,
+            _arena: &'a Arena<'a>,
+            root: &'a AstNode<'a>,
+            _context: &TransformContext,
+        ) -> Result<()> {
+            // Passes are global, so only react to this test's document
+            let is_test_document = root.descendants().any(|node| {
+                matches!(&node.data.borrow().value, NodeValue::Text(text) if text.contains("pipeline marker"))
+            });
+            if !is_test_document {
+                return Ok(());
+            }
+            RUNS.lock().unwrap().push(self.name.to_string());
+            match self.fail {
+                true => Err(anyhow::anyhow!("broken")),
+                false => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn passes_run_in_order_and_report_failures() {
+        register_pass(
+            10,
+            RecordingPass {
+                name: "late",
+                fail: false,
+            },
+        );
+        register_pass(
+            -10,
+            RecordingPass {
+                name: "early",
+                fail: false,
+            },
+        );
+        register_pass(
+            10,
+            RecordingPass {
+                name: "late-failing",
+                fail: true,
+            },
+        );
+
+        let arena = Arena::new();
+        let root = parse_document(
+            &arena,
+            "pipeline marker",
+            &crate::internal::render::parse_options(),
+        );
+        let errors = apply(&arena, root, &TransformContext::default());
+
+        assert_eq!(*RUNS.lock().unwrap(), vec!["early", "late", "late-failing"]);
+        assert_eq!(
+            errors,
+            vec![TransformError {
+                pass: "late-failing".to_string(),
+                message: "broken".to_string(),
+            }]
+        );
+    }
+}
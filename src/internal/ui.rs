@@ -1,24 +1,61 @@
-use gpui::{FontWeight, IntoElement, Rgba, div, prelude::*, px};
+use gpui::{FontWeight, IntoElement, SharedString, div, prelude::*, px, relative};
 
+use crate::config::RenderMode;
+use crate::internal::bookmark_list::{line_snippet, list_height};
+use crate::internal::dialog::DialogButton;
+use crate::internal::file_tree::rows_height;
 use crate::internal::help_overlay::help_panel;
-use crate::internal::style::{GOTO_LINE_OVERLAY_BG_COLOR, GOTO_LINE_OVERLAY_TEXT_COLOR};
+use crate::internal::interaction::{Affordance, ClickState, Interactive};
+use crate::internal::keymap::Platform;
+use crate::internal::notifications::NotificationKind;
+use crate::internal::search::{SearchMode, SearchWrap};
+use crate::internal::style::{
+    BOOKMARK_ROW_HEIGHT, BOOKMARKS_PANEL_WIDTH, IMAGE_MAX_WIDTH, IMAGE_REPORT_MAX_HEIGHT,
+    NOTIFICATION_PROGRESS_HEIGHT, PROJECT_SEARCH_VISIBLE_RESULTS, PROJECT_SEARCH_WIDTH,
+    STATUS_SECTION_MAX_WIDTH,
+};
+use crate::internal::tabs::MAX_NUMBERED_TABS;
+use crate::internal::tour::{TOUR_STEPS, TourOutcome};
 use crate::internal::viewer::MarkdownViewer;
 
+/// Window title for the open file, e.g. `README.md — Markdown Viewer`
+///
+/// A leading `●` marks a reload that happened while the window was unfocused.
+pub fn window_title(app_title: &str, file_path: &std::path::Path, unseen_reload: bool) -> String {
+    let title = match file_path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => format!("{} — {}", name, app_title),
+        None => app_title.to_string(),
+    };
+    match unseen_reload {
+        true => format!("● {}", title),
+        false => title,
+    }
+}
+
 pub fn render_status_bar(
     viewer: &MarkdownViewer,
     theme_colors: &crate::internal::theme::ThemeColors,
     cx: &mut gpui::Context<MarkdownViewer>,
 ) -> impl IntoElement {
     let filename = viewer
-        .markdown_file_path
+        .document()
+        .path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("Untitled")
         .to_string();
 
-    let total_lines = viewer.markdown_content.lines().count().max(1);
+    let total_lines = viewer.document().content.lines().count().max(1);
     let current_line = viewer.get_current_line_number();
     let percentage = (current_line as f32 / total_lines as f32 * 100.0) as usize;
+    // There is no caret to report a column for, so the section being read is
+    // shown next to the line instead
+    let section = viewer
+        .document()
+        .toc
+        .find_section_at_line(viewer.document().shown_line(current_line - 1))
+        .and_then(|index| viewer.document().toc.entries.get(index))
+        .map(|entry| entry.text.clone());
 
     div()
         .absolute()
@@ -39,24 +76,115 @@ pub fn render_status_bar(
             div()
                 .flex()
                 .gap_4()
-                .child(div().font_weight(FontWeight::BOLD).child(filename))
-                .child(format!("{} lines", total_lines)),
+                .child(
+                    div()
+                        .id("status-file")
+                        .rounded_sm()
+                        .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                        .font_weight(FontWeight::BOLD)
+                        .child(filename)
+                        .on_mouse_down(
+                            gpui::MouseButton::Left,
+                            cx.listener(|this, _, _, cx| this.copy_file_path(cx)),
+                        ),
+                )
+                .when_some(viewer.file_queue.position(), |el, (current, total)| {
+                    el.child(format!("File {}/{}", current, total))
+                })
+                .child(format!("{} lines", total_lines))
+                .when(viewer.document().render_mode == RenderMode::Plain, |el| {
+                    el.child("Plain Text")
+                })
+                .when(viewer.changed_on_disk, |el| {
+                    el.child(
+                        div()
+                            .text_color(theme_colors.toc_text_color)
+                            .child("Changed on disk (Cmd+R to reload)"),
+                    )
+                })
+                .when(viewer.network.is_offline(), |el| {
+                    el.child(
+                        div()
+                            .id("status-offline")
+                            .rounded_sm()
+                            .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                            .text_color(theme_colors.toc_text_color)
+                            .child("Offline")
+                            .on_mouse_down(
+                                gpui::MouseButton::Left,
+                                cx.listener(|this, _, _, cx| {
+                                    this.toggle_offline();
+                                    cx.notify();
+                                }),
+                            ),
+                    )
+                }),
         )
         .child(
             div()
                 .flex()
                 .gap_4()
-                .child(format!("Ln {}, Col 1", current_line)) // Col is always 1 for now
-                .child(format!("{}%", percentage)),
+                .child(
+                    div()
+                        .id("status-line")
+                        .rounded_sm()
+                        .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                        .child(format!("Ln {}", current_line))
+                        .on_mouse_down(
+                            gpui::MouseButton::Left,
+                            cx.listener(|this, _, _, cx| {
+                                this.open_goto_prompt(false);
+                                cx.notify();
+                            }),
+                        ),
+                )
+                .when_some(section, |el, section| {
+                    el.child(
+                        div()
+                            .max_w(px(STATUS_SECTION_MAX_WIDTH))
+                            .truncate()
+                            .child(section),
+                    )
+                })
+                .child(
+                    div()
+                        .id("status-percent")
+                        .rounded_sm()
+                        .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                        .child(format!("{}%", percentage))
+                        .on_mouse_down(
+                            gpui::MouseButton::Left,
+                            cx.listener(|this, _, _, cx| {
+                                this.open_goto_prompt(true);
+                                cx.notify();
+                            }),
+                        ),
+                ),
         )
         .child(
             div()
                 .flex()
                 .gap_4()
-                .child(viewer.config.theme.theme.clone())
+                .child(viewer.document().text_format.label())
+                .child(
+                    div()
+                        .id("status-theme")
+                        .rounded_sm()
+                        .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                        .child(viewer.config.theme.theme.clone())
+                        .on_mouse_down(
+                            gpui::MouseButton::Left,
+                            cx.listener(|this, _, _, cx| {
+                                this.open_theme_picker();
+                                cx.notify();
+                            }),
+                        ),
+                )
                 .child(
                     div()
-                        .cursor_pointer()
+                        .id("status-help")
+                        .rounded_sm()
+                        .interactive(Affordance::Row, ClickState::default(), theme_colors)
                         .font_weight(FontWeight::BOLD)
                         .child("Help")
                         .on_mouse_down(
@@ -67,25 +195,172 @@ pub fn render_status_bar(
                             }),
                         ),
                 )
-                .child(format!("v{}", env!("CARGO_PKG_VERSION"))),
+                .child(
+                    div()
+                        .id("status-version")
+                        .rounded_sm()
+                        .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                        .child(format!("v{}", env!("CARGO_PKG_VERSION")))
+                        .on_mouse_down(
+                            gpui::MouseButton::Left,
+                            cx.listener(|this, _, _, cx| {
+                                this.toggle_about();
+                                cx.notify();
+                            }),
+                        ),
+                ),
         )
 }
 
-pub fn render_search_overlay(viewer: &MarkdownViewer) -> Option<impl IntoElement> {
-    match &viewer.search_state {
+/// Tab bar above the status bar, shown while more than one file is open
+pub fn render_tab_bar(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    if viewer.tabs.len() <= 1 || viewer.peek_mode {
+        return None;
+    }
+
+    let tabs = viewer.tabs.iter().enumerate().map(|(index, tab)| {
+        let is_active = index == viewer.active_tab;
+        let title = match index < MAX_NUMBERED_TABS {
+            true => format!("{} {}", index + 1, tab.title()),
+            false => tab.title(),
+        };
+        div()
+            .id(("tab", index))
+            .flex()
+            .items_center()
+            .gap_2()
+            .px_3()
+            .h_full()
+            .border_r_1()
+            .border_color(theme_colors.toc_border_color)
+            .interactive(Affordance::Row, ClickState::default(), theme_colors)
+            .when(is_active, |el| {
+                el.bg(theme_colors.bg_color).font_weight(FontWeight::BOLD)
+            })
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(move |this, _, _, cx| this.switch_tab(index, cx)),
+            )
+            .child(title)
+            .child(
+                div()
+                    .id(("close-tab", index))
+                    .px_1()
+                    .rounded_sm()
+                    .interactive(Affordance::Button, ClickState::default(), theme_colors)
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _, _, cx| {
+                            cx.stop_propagation();
+                            this.close_tab(index, cx);
+                        }),
+                    )
+                    .child("×"),
+            )
+    });
+
+    Some(
+        div()
+            .absolute()
+            .bottom(px(30.0))
+            .left_0()
+            .right_0()
+            .h(px(28.0))
+            .flex()
+            .flex_row()
+            .bg(theme_colors.toc_bg_color)
+            .border_t_1()
+            .border_color(theme_colors.toc_border_color)
+            .text_size(px(12.0))
+            .text_color(theme_colors.text_color)
+            .children(tabs),
+    )
+}
+
+/// Search bar with the match count and, for documents with a TOC, a per-section
+/// breakdown whose entries jump to the section's first match
+pub fn render_search_overlay(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    match &viewer.document().search_state {
         Some(search_state) => {
-            let match_info = match (search_state.match_count(), viewer.search_input.is_empty()) {
+            let label = match (viewer.search_highlights_kept, search_state.mode()) {
+                (true, _) => "Highlights (n/N: step, Esc: clear)".to_string(),
+                (false, SearchMode::Plain) => "Search".to_string(),
+                (false, mode) => format!("Search [{}]", mode.label()),
+            };
+            let match_info = match (
+                search_state.match_count(),
+                viewer.document().search_input.is_empty(),
+            ) {
                 (n, _) if n > 0 => format!(
-                    "Search: \"{}\" ({} of {} matches)",
-                    viewer.search_input,
+                    "{}: \"{}\" ({} of {} matches)",
+                    label,
+                    viewer.document().search_input,
                     search_state.current_match_number().unwrap_or(0),
                     search_state.match_count()
                 ),
-                (0, true) => "Search: (type to search)".to_string(),
-                (0, false) => format!("Search: \"{}\" (no matches)", viewer.search_input),
+                (0, true) => format!("{}: (type to search, Alt+R/W/C: regex/word/case)", label),
+                (0, false) => match search_state.error() {
+                    Some(_) => format!(
+                        "{}: \"{}\" (invalid regex)",
+                        label,
+                        viewer.document().search_input
+                    ),
+                    None => format!(
+                        "{}: \"{}\" (no matches)",
+                        label,
+                        viewer.document().search_input
+                    ),
+                },
                 // Fallback arm, though all cases are covered above
-                _ => "Search: (type to search)".to_string(),
+                _ => format!("{}: (type to search)", label),
+            };
+
+            let wrap_notice = search_state
+                .wrapped()
+                .map(|wrap| match wrap {
+                    SearchWrap::ToTop => "Wrapped to top",
+                    SearchWrap::ToBottom => "Wrapped to bottom",
+                })
+                .or(viewer
+                    .document()
+                    .filter
+                    .as_ref()
+                    .map(|view| match view.context {
+                        0 => "Filtered (Esc for full view)",
+                        _ => "Filtered ±1 block (Esc for full view)",
+                    }));
+
+            let sections = match viewer.document().toc.entries.is_empty() {
+                true => Vec::new(),
+                false => viewer
+                    .document()
+                    .toc
+                    .matches_by_section(viewer.document().shown_content(), search_state.matches()),
             };
+            let section_links = sections.into_iter().map(|section| {
+                let first_match = section.first_match;
+                div()
+                    .id(("search-section", first_match))
+                    .px_1()
+                    .rounded_sm()
+                    .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _, _, cx| {
+                            this.jump_to_match(first_match);
+                            cx.notify();
+                        }),
+                    )
+                    .child(format!("{}: {}", section.title, section.count))
+            });
 
             Some(
                 div()
@@ -93,35 +368,54 @@ pub fn render_search_overlay(viewer: &MarkdownViewer) -> Option<impl IntoElement
                     .top_0()
                     .left_0()
                     .right_0()
-                    .bg(Rgba {
-                        r: 1.0,
-                        g: 0.95,
-                        b: 0.6,
-                        a: 0.95,
-                    })
-                    .text_color(Rgba {
-                        r: 0.0,
-                        g: 0.0,
-                        b: 0.0,
-                        a: 1.0,
-                    })
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .bg(theme_colors.search_overlay_bg_color)
+                    .text_color(theme_colors.search_overlay_text_color)
                     .px_4()
                     .py_2()
                     .text_size(px(14.0))
-                    .child(match_info),
+                    .child(
+                        div()
+                            .flex()
+                            .flex_row()
+                            .justify_between()
+                            .child(match_info)
+                            .children(wrap_notice.map(|notice| {
+                                div().font_weight(FontWeight::SEMIBOLD).child(notice)
+                            })),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .flex_row()
+                            .flex_wrap()
+                            .gap_2()
+                            .text_size(px(12.0))
+                            .children(section_links),
+                    ),
             )
         }
         None => None,
     }
 }
 
-pub fn render_goto_line_overlay(viewer: &MarkdownViewer) -> Option<impl IntoElement> {
+pub fn render_goto_line_overlay(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+) -> Option<impl IntoElement> {
     match viewer.show_goto_line {
         true => {
-            let total_lines = viewer.markdown_content.lines().count();
-            let display_text = match viewer.goto_line_input.as_str() {
-                "" => format!("Go to line: (1-{})", total_lines),
-                input => match MarkdownViewer::parse_line_number(input) {
+            let total_lines = viewer.document().content.lines().count();
+            let display_text = match (viewer.goto_percent, viewer.goto_line_input.as_str()) {
+                (true, "") => "Go to percent: (0-100)".to_string(),
+                (true, input) => match MarkdownViewer::parse_percent(input) {
+                    Some(_) => format!("Go to percent: \"{}%\"", input),
+                    None => format!("Go to percent: \"{}\" (invalid)", input),
+                },
+                (false, "") => format!("Go to line: (1-{})", total_lines),
+                (false, input) => match MarkdownViewer::parse_line_number(input) {
                     Some(line_number) if line_number > total_lines => format!(
                         "Go to line: \"{}\" (exceeds max: {})",
                         viewer.goto_line_input, total_lines
@@ -137,8 +431,8 @@ pub fn render_goto_line_overlay(viewer: &MarkdownViewer) -> Option<impl IntoElem
                     .top_0()
                     .left_0()
                     .right_0()
-                    .bg(GOTO_LINE_OVERLAY_BG_COLOR)
-                    .text_color(GOTO_LINE_OVERLAY_TEXT_COLOR)
+                    .bg(theme_colors.goto_line_overlay_bg_color)
+                    .text_color(theme_colors.goto_line_overlay_text_color)
                     .px_4()
                     .py_2()
                     .text_size(px(14.0))
@@ -161,12 +455,7 @@ pub fn render_help_overlay(
                 .left_0()
                 .right_0()
                 .bottom_0()
-                .bg(Rgba {
-                    r: 0.0,
-                    g: 0.0,
-                    b: 0.0,
-                    a: 0.8,
-                })
+                .bg(theme_colors.overlay_backdrop_color)
                 .flex()
                 .items_center()
                 .justify_center()
@@ -176,7 +465,10 @@ pub fn render_help_overlay(
     }
 }
 
-pub fn render_file_deleted_overlay(viewer: &MarkdownViewer) -> Option<impl IntoElement> {
+pub fn render_file_deleted_overlay(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+) -> Option<impl IntoElement> {
     match viewer.file_deleted {
         true => Some(
             div()
@@ -184,18 +476,8 @@ pub fn render_file_deleted_overlay(viewer: &MarkdownViewer) -> Option<impl IntoE
                 .top_0()
                 .left_0()
                 .right_0()
-                .bg(Rgba {
-                    r: 1.0,
-                    g: 0.4,
-                    b: 0.4,
-                    a: 0.95,
-                })
-                .text_color(Rgba {
-                    r: 1.0,
-                    g: 1.0,
-                    b: 1.0,
-                    a: 1.0,
-                })
+                .bg(theme_colors.file_deleted_bg_color)
+                .text_color(theme_colors.file_deleted_text_color)
                 .px_4()
                 .py_2()
                 .text_size(px(14.0))
@@ -206,187 +488,515 @@ pub fn render_file_deleted_overlay(viewer: &MarkdownViewer) -> Option<impl IntoE
     }
 }
 
-pub fn render_pdf_export_overlay(
+/// Modal confirmation dialog over a dimmed backdrop
+///
+/// The backdrop swallows clicks so nothing behind the dialog reacts; keys
+/// are routed to the dialog by `events::handle_key_down`.
+pub fn render_confirm_dialog(
     viewer: &MarkdownViewer,
     theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
 ) -> Option<impl IntoElement> {
-    match &viewer.pdf_export_message {
-        Some(message) => {
-            let (bg_color, icon) = match viewer.pdf_export_success {
-                true => (theme_colors.pdf_success_bg_color, "✓"),
-                false => (theme_colors.pdf_error_bg_color, "✗"),
-            };
+    let dialog = viewer.confirm_dialog.as_ref()?;
 
-            Some(
-                div()
-                    .absolute()
-                    .top_0()
-                    .left_0()
-                    .right_0()
-                    .bg(bg_color)
-                    .text_color(theme_colors.pdf_notification_text_color)
-                    .px_4()
-                    .py_2()
-                    .text_size(px(14.0))
-                    .font_weight(FontWeight::BOLD)
-                    .child(format!("{} {}", icon, message)),
+    let button = |id: &'static str, label: &'static str, button: DialogButton| {
+        let is_focused = dialog.focused == button;
+        div()
+            .id(id)
+            .px_4()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .text_color(theme_colors.text_color)
+            .border_color(match is_focused {
+                true => theme_colors.current_match_border_color,
+                false => theme_colors.toc_border_color,
+            })
+            .interactive(
+                Affordance::Row,
+                ClickState::default().focused(is_focused),
+                theme_colors,
             )
-        }
-        None => None,
-    }
-}
-
-pub fn render_pdf_overwrite_confirm(
-    viewer: &MarkdownViewer,
-    theme_colors: &crate::internal::theme::ThemeColors,
-) -> Option<impl IntoElement> {
-    match viewer.show_pdf_overwrite_confirm {
-        true => {
-            let filename = viewer
-                .pdf_overwrite_path
-                .as_ref()
-                .and_then(|p| p.file_name())
-                .and_then(|n| n.to_str())
-                .unwrap_or("output.pdf");
-
-            Some(
-                div()
-                    .absolute()
-                    .top_0()
-                    .left_0()
-                    .right_0()
-                    .bg(theme_colors.pdf_warning_bg_color)
-                    .text_color(theme_colors.text_color)
-                    .px_4()
-                    .py_2()
-                    .text_size(px(14.0))
-                    .font_weight(FontWeight::BOLD)
-                    .child(format!("⚠ {} already exists. Overwrite? (Y/N)", filename)),
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(move |this, _, _, cx| {
+                    if let Some(dialog) = this.confirm_dialog.as_ref() {
+                        let outcome = dialog.press(button);
+                        this.finish_dialog(outcome, cx);
+                    }
+                    cx.stop_propagation();
+                    cx.notify();
+                }),
             )
-        }
-        false => None,
-    }
-}
-
-pub fn render_toc_sidebar(
-    viewer: &mut MarkdownViewer,
-    theme_colors: &crate::internal::theme::ThemeColors,
-    cx: &mut gpui::Context<MarkdownViewer>,
-) -> Option<impl IntoElement> {
-    if !viewer.show_toc || viewer.toc.entries.is_empty() {
-        return None;
-    }
-
-    use crate::internal::style::{TOC_INDENT_PER_LEVEL, TOC_WIDTH};
-
-    let avg_line_height =
-        viewer.config.theme.base_text_size * viewer.config.theme.line_height_multiplier;
-    let current_section_idx = viewer
-        .toc
-        .find_current_section(viewer.scroll_state.scroll_y, avg_line_height);
-
-    let toc_entries = viewer
-        .toc
-        .entries
-        .iter()
-        .enumerate()
-        .map(|(idx, entry)| {
-            let is_active = current_section_idx == Some(idx);
-            let indent = (entry.level as f32 - 1.0) * TOC_INDENT_PER_LEVEL;
-            let line_number = entry.line_number;
-
-            // Note: TOC items are NOT tracked as focusable (excluded from tab navigation)
-
-            div()
-                .px(px(8.0 + indent))
-                .py_1()
-                .text_size(px(13.0))
-                .text_color(theme_colors.toc_text_color)
-                .cursor_pointer()
-                .when(is_active, |div| div.bg(theme_colors.toc_active_color))
-                .hover(|div| div.bg(theme_colors.toc_hover_color))
-                .on_mouse_down(
-                    gpui::MouseButton::Left,
-                    cx.listener(move |this, _event, _, cx| {
-                        // Calculate target scroll position based on line number using smart logic
-                        let target_y = this.calculate_y_for_line(line_number);
-                        this.scroll_state.scroll_y = target_y.min(this.scroll_state.max_scroll_y);
-                        cx.notify();
-                    }),
-                )
-                .child(entry.text.clone())
-        })
-        .collect::<Vec<_>>();
+            .child(label)
+    };
 
     Some(
         div()
+            .id("confirm-dialog-backdrop")
             .absolute()
             .top_0()
+            .left_0()
             .right_0()
             .bottom_0()
-            .w(px(TOC_WIDTH))
-            .bg(theme_colors.toc_bg_color)
-            .border_l_1()
-            .border_color(theme_colors.toc_border_color)
-            .overflow_hidden()
-            .on_scroll_wheel(cx.listener(|this, event: &gpui::ScrollWheelEvent, _, cx| {
-                let delta = event
-                    .delta
-                    .pixel_delta(px(this.config.theme.base_text_size))
-                    .y;
-                let delta_f32: f32 = delta.into();
-
-                // Scroll TOC
-                // On macOS with natural scrolling, delta is already in the correct direction
-                // Positive delta = scroll down (content moves up)
-                // Negative delta = scroll up (content moves down)
-                // Simply apply the delta directly
-                this.toc_scroll_y =
-                    (this.toc_scroll_y - delta_f32).clamp(0.0, this.toc_max_scroll_y);
-                cx.notify();
-            }))
+            .bg(theme_colors.overlay_backdrop_color)
+            .occlude()
+            .flex()
+            .items_center()
+            .justify_center()
             .child(
                 div()
+                    .w(px(420.0))
+                    .bg(theme_colors.bg_color)
+                    .border_1()
+                    .border_color(theme_colors.panel_border_color)
+                    .shadow_xl()
+                    .rounded_lg()
+                    .p_5()
                     .flex()
                     .flex_col()
-                    .gap_1()
-                    .pt_4()
-                    .pb_4()
-                    .relative()
-                    .top(px(-viewer.toc_scroll_y))
-                    .children(toc_entries),
+                    .gap_3()
+                    .text_color(theme_colors.text_color)
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(px(16.0))
+                            .child(format!("⚠ {}", dialog.title)),
+                    )
+                    .child(div().text_size(px(14.0)).child(dialog.message.clone()))
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .pt_2()
+                            .text_size(px(14.0))
+                            .child(button(
+                                "confirm-dialog-cancel",
+                                dialog.cancel_label,
+                                DialogButton::Cancel,
+                            ))
+                            .child(button(
+                                "confirm-dialog-confirm",
+                                dialog.confirm_label,
+                                DialogButton::Confirm,
+                            )),
+                    ),
             ),
     )
 }
 
-pub fn render_toc_toggle_button(
-    viewer: &mut MarkdownViewer,
+/// About overlay: version, build, themes and config path, with a button
+/// copying them as diagnostics for bug reports
+pub fn render_about_overlay(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
     cx: &mut gpui::Context<MarkdownViewer>,
-) -> impl IntoElement {
-    use crate::internal::style::{
-        TOC_TOGGLE_BG_COLOR, TOC_TOGGLE_HOVER_COLOR, TOC_TOGGLE_TEXT_COLOR,
+) -> Option<impl IntoElement> {
+    let about = viewer.about.as_ref()?;
+
+    let button = |id: &'static str, label: &'static str, copy: bool| {
+        div()
+            .id(id)
+            .px_4()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .text_color(theme_colors.text_color)
+            .border_color(theme_colors.toc_border_color)
+            .interactive(Affordance::Row, ClickState::default(), theme_colors)
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(move |this, _, _, cx| {
+                    match copy {
+                        true => this.copy_diagnostics(cx),
+                        false => this.about = None,
+                    }
+                    cx.stop_propagation();
+                    cx.notify();
+                }),
+            )
+            .child(label)
+    };
+
+    Some(
+        div()
+            .id("about-backdrop")
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bottom_0()
+            .bg(theme_colors.overlay_backdrop_color)
+            .occlude()
+            .flex()
+            .items_center()
+            .justify_center()
+            .child(
+                div()
+                    .w(px(520.0))
+                    .bg(theme_colors.bg_color)
+                    .border_1()
+                    .border_color(theme_colors.panel_border_color)
+                    .shadow_xl()
+                    .rounded_lg()
+                    .p_5()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .text_color(theme_colors.text_color)
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(px(16.0))
+                            .pb_1()
+                            .child("Markdown Viewer"),
+                    )
+                    .children(about.rows().into_iter().map(|(label, value)| {
+                        div()
+                            .flex()
+                            .gap_3()
+                            .text_size(px(13.0))
+                            .child(div().w(px(130.0)).flex_none().opacity(0.6).child(label))
+                            .child(div().flex_1().min_w_0().child(value))
+                    }))
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .pt_3()
+                            .text_size(px(14.0))
+                            .child(button("about-copy", "Copy Diagnostics", true))
+                            .child(button("about-close", "Close", false)),
+                    ),
+            ),
+    )
+}
+
+/// First-run tour card over a dimmed backdrop
+///
+/// Like the confirmation dialog, the backdrop swallows clicks; keys are
+/// routed to the tour by `events::handle_key_down`.
+pub fn render_tour(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    let tour = viewer.tour.as_ref()?;
+    let step = tour.current();
+
+    let button = |id: &'static str, label: &'static str, key: &'static str, primary: bool| {
+        div()
+            .id(id)
+            .px_4()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .text_color(theme_colors.text_color)
+            .border_color(match primary {
+                true => theme_colors.current_match_border_color,
+                false => theme_colors.toc_border_color,
+            })
+            .interactive(
+                Affordance::Row,
+                ClickState::default().focused(primary),
+                theme_colors,
+            )
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(move |this, _, _, cx| {
+                    let outcome = match this.tour.as_mut() {
+                        Some(tour) => tour.handle_key(key),
+                        None => TourOutcome::Pending,
+                    };
+                    this.finish_tour(outcome);
+                    cx.stop_propagation();
+                    cx.notify();
+                }),
+            )
+            .child(label)
     };
 
+    Some(
+        div()
+            .id("tour-backdrop")
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bottom_0()
+            .bg(theme_colors.overlay_backdrop_color)
+            .occlude()
+            .flex()
+            .items_center()
+            .justify_center()
+            .child(
+                div()
+                    .w(px(420.0))
+                    .bg(theme_colors.bg_color)
+                    .border_1()
+                    .border_color(theme_colors.panel_border_color)
+                    .shadow_xl()
+                    .rounded_lg()
+                    .p_5()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .text_color(theme_colors.text_color)
+                    .child(
+                        div()
+                            .flex()
+                            .justify_between()
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::BOLD)
+                                    .text_size(px(16.0))
+                                    .child(step.title),
+                            )
+                            .child(div().text_size(px(12.0)).opacity(0.6).child(format!(
+                                "{}/{}",
+                                tour.step + 1,
+                                TOUR_STEPS.len()
+                            ))),
+                    )
+                    .child(div().text_size(px(14.0)).child(step.body))
+                    .child(
+                        div()
+                            .text_size(px(13.0))
+                            .font_weight(FontWeight::BOLD)
+                            .child(tour.shortcut(Platform::current())),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .pt_2()
+                            .text_size(px(14.0))
+                            .child(button("tour-skip", "Skip", "escape", false))
+                            .when(tour.step > 0, |div| {
+                                div.child(button("tour-back", "Back", "left", false))
+                            })
+                            .child(button(
+                                "tour-next",
+                                match tour.is_last() {
+                                    true => "Done",
+                                    false => "Next",
+                                },
+                                "right",
+                                true,
+                            )),
+                    ),
+            ),
+    )
+}
+
+pub fn render_toc_sidebar(
+    viewer: &mut MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    if !viewer.show_toc || viewer.document().toc.entries.is_empty() {
+        return None;
+    }
+
+    use crate::internal::style::{TOC_INDENT_PER_LEVEL, TOC_WIDTH};
+    use crate::internal::toc::SECTION_HIGHLIGHT_OFFSET;
+
+    let current_line = viewer
+        .block_map
+        .line_at_y(viewer.document().scroll_state.scroll_y + SECTION_HIGHLIGHT_OFFSET);
+    let current_section_idx = viewer.document().toc.find_section_at_line(current_line);
+
+    let toc_entries = viewer
+        .document()
+        .toc
+        .visible_entries(&viewer.toc_collapsed)
+        .into_iter()
+        .map(|idx| {
+            let entry = &viewer.document().toc.entries[idx];
+            let is_active = current_section_idx == Some(idx);
+            let indent = (entry.level as f32 - 1.0) * TOC_INDENT_PER_LEVEL;
+            let line_number = entry.line_number;
+            let label = match viewer.toc_collapsed.contains(&idx) {
+                true => format!("▸ {}", entry.text),
+                false => entry.text.clone(),
+            };
+
+            // Note: TOC items are NOT tracked as focusable (excluded from tab navigation)
+
+            div()
+                .id(("toc-entry", idx))
+                .px(px(8.0 + indent))
+                .py_1()
+                .text_size(px(13.0))
+                .text_color(theme_colors.toc_text_color)
+                .interactive(
+                    Affordance::Row,
+                    ClickState::default().selected(is_active),
+                    theme_colors,
+                )
+                .on_mouse_down(
+                    gpui::MouseButton::Left,
+                    cx.listener(move |this, _event, _, cx| {
+                        // Calculate target scroll position based on line number using smart logic
+                        let target_y = this.calculate_y_for_line(line_number);
+                        this.scroll_to_y(target_y);
+                        cx.notify();
+                    }),
+                )
+                .on_mouse_down(
+                    gpui::MouseButton::Right,
+                    cx.listener(move |this, event: &gpui::MouseDownEvent, _, cx| {
+                        this.open_toc_menu(idx, f32::from(event.position.y));
+                        cx.notify();
+                    }),
+                )
+                .child(label)
+        })
+        .collect::<Vec<_>>();
+
+    Some(
+        div()
+            .absolute()
+            .top_0()
+            .right_0()
+            .bottom_0()
+            .w(px(TOC_WIDTH))
+            .bg(theme_colors.toc_bg_color)
+            .border_l_1()
+            .border_color(theme_colors.toc_border_color)
+            .overflow_hidden()
+            .on_scroll_wheel(cx.listener(|this, event: &gpui::ScrollWheelEvent, _, cx| {
+                let delta = event
+                    .delta
+                    .pixel_delta(px(this.config.theme.base_text_size))
+                    .y;
+                let delta_f32: f32 = delta.into();
+
+                // Scroll TOC
+                // On macOS with natural scrolling, delta is already in the correct direction
+                // Positive delta = scroll down (content moves up)
+                // Negative delta = scroll up (content moves down)
+                // Simply apply the delta directly
+                this.document_mut().toc_scroll_y =
+                    (this.document().toc_scroll_y - delta_f32).clamp(0.0, this.toc_max_scroll_y);
+                cx.notify();
+            }))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .pt_4()
+                    .pb_4()
+                    .relative()
+                    .top(px(-viewer.document().toc_scroll_y))
+                    .children(toc_entries),
+            ),
+    )
+}
+
+/// Right-click menu of a TOC entry, opened next to the sidebar
+pub fn render_toc_menu(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    use crate::internal::style::TOC_WIDTH;
+    use crate::internal::toc::TocMenuAction;
+
+    const ITEM_HEIGHT: f32 = 28.0;
+
+    let menu = viewer.toc_menu?;
+    let items = TocMenuAction::items(!viewer.toc_collapsed.is_empty());
+    // Keep the whole menu inside the window
+    let height = items.len() as f32 * ITEM_HEIGHT + 8.0;
+    let top = menu.y.min(viewer.viewport_height - height).max(0.0);
+
+    Some(
+        div()
+            .id("toc-menu-backdrop")
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bottom_0()
+            .occlude()
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(|this, _, _, cx| {
+                    this.toc_menu = None;
+                    cx.notify();
+                }),
+            )
+            .on_mouse_down(
+                gpui::MouseButton::Right,
+                cx.listener(|this, _, _, cx| {
+                    this.toc_menu = None;
+                    cx.notify();
+                }),
+            )
+            .child(
+                div()
+                    .absolute()
+                    .top(px(top))
+                    .right(px(TOC_WIDTH - 24.0))
+                    .w(px(180.0))
+                    .py_1()
+                    .bg(theme_colors.bg_color)
+                    .border_1()
+                    .border_color(theme_colors.panel_border_color)
+                    .shadow_xl()
+                    .rounded_md()
+                    .text_size(px(13.0))
+                    .text_color(theme_colors.text_color)
+                    .children(items.into_iter().map(|action| {
+                        div()
+                            .id(action.label())
+                            .h(px(ITEM_HEIGHT))
+                            .px_3()
+                            .flex()
+                            .items_center()
+                            .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                            .on_mouse_down(
+                                gpui::MouseButton::Left,
+                                cx.listener(move |this, _, _, cx| {
+                                    cx.stop_propagation();
+                                    this.run_toc_menu_action(action, cx);
+                                }),
+                            )
+                            .child(action.label())
+                    })),
+            ),
+    )
+}
+
+pub fn render_toc_toggle_button(
+    viewer: &mut MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> impl IntoElement {
     // Note: TOC toggle is NOT tracked as focusable (excluded from tab navigation)
 
     div()
+        .id("toc-toggle")
         .absolute()
         .top_4()
         .right_4()
-        .bg(TOC_TOGGLE_BG_COLOR)
-        .text_color(TOC_TOGGLE_TEXT_COLOR)
+        .bg(theme_colors.toc_toggle_bg_color)
+        .text_color(theme_colors.toc_toggle_text_color)
         .rounded_md()
         .px_3()
         .py_2()
         .text_size(px(18.0))
         .font_weight(FontWeight::BOLD)
-        .cursor_pointer()
-        .hover(|div| div.bg(TOC_TOGGLE_HOVER_COLOR))
+        .interactive(Affordance::Toggle, ClickState::default(), theme_colors)
         .on_mouse_down(
             gpui::MouseButton::Left,
             cx.listener(|this, _event, _, cx| {
-                this.show_toc = !this.show_toc;
-                this.recompute_max_scroll();
+                this.toggle_toc();
                 cx.notify();
             }),
         )
@@ -396,73 +1006,617 @@ pub fn render_toc_toggle_button(
         })
 }
 
-pub fn render_bookmarks_overlay(
-    viewer: &mut MarkdownViewer,
+/// File tree sidebar on the left, for directory sessions
+pub fn render_file_tree(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    use crate::internal::file_tree::RowKind;
+    use crate::internal::style::{
+        FILE_TREE_HEADER_HEIGHT, FILE_TREE_INDENT, FILE_TREE_ROW_HEIGHT, FILE_TREE_WIDTH,
+    };
+
+    let tree = viewer
+        .file_tree
+        .as_ref()
+        .filter(|_| viewer.show_file_tree)?;
+
+    let rows = tree.rows.iter().enumerate().map(|(index, row)| {
+        let is_current = row.path == viewer.document().path;
+        let is_selected = tree.focused && index == tree.selected;
+        let icon = match row.kind {
+            RowKind::Folder { expanded: true } => "▾",
+            RowKind::Folder { expanded: false } => "▸",
+            RowKind::File => " ",
+        };
+        let path = row.path.clone();
+        let kind = row.kind;
+
+        div()
+            .id(("file-tree-row", index))
+            .h(px(FILE_TREE_ROW_HEIGHT))
+            .flex()
+            .items_center()
+            .gap_1()
+            .pl(px(8.0 + row.depth as f32 * FILE_TREE_INDENT))
+            .pr_2()
+            .text_size(px(13.0))
+            .text_color(theme_colors.toc_text_color)
+            .whitespace_nowrap()
+            .overflow_hidden()
+            .interactive(
+                Affordance::Row,
+                ClickState::default()
+                    .selected(is_current)
+                    .focused(is_selected),
+                theme_colors,
+            )
+            .when(is_current, |div| div.font_weight(FontWeight::BOLD))
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(move |this, _, _, cx| {
+                    if let Some(tree) = this.file_tree.as_mut() {
+                        tree.selected = index;
+                        tree.focused = true;
+                    }
+                    match kind {
+                        RowKind::Folder { .. } => {
+                            if let Some(tree) = this.file_tree.as_mut() {
+                                tree.toggle_folder(&path);
+                            }
+                        }
+                        RowKind::File => this.open_tree_file(path.clone(), cx),
+                    }
+                    cx.notify();
+                }),
+            )
+            .child(div().w(px(12.0)).flex_none().child(icon))
+            .child(row.name.clone())
+    });
+
+    let root_name = tree
+        .root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| tree.root.display().to_string());
+
+    Some(
+        div()
+            .absolute()
+            .top_0()
+            .left_0()
+            .bottom_0()
+            .w(px(FILE_TREE_WIDTH))
+            .bg(theme_colors.toc_bg_color)
+            .border_r_1()
+            .border_color(theme_colors.toc_border_color)
+            .overflow_hidden()
+            .on_scroll_wheel(cx.listener(|this, event: &gpui::ScrollWheelEvent, _, cx| {
+                let delta: f32 = event
+                    .delta
+                    .pixel_delta(px(this.config.theme.base_text_size))
+                    .y
+                    .into();
+                let tree_height = rows_height(this.viewport_height);
+                if let Some(tree) = this.file_tree.as_mut() {
+                    tree.scroll_by(-delta, tree_height);
+                }
+                cx.stop_propagation();
+                cx.notify();
+            }))
+            .child(
+                div()
+                    .h(px(FILE_TREE_HEADER_HEIGHT))
+                    .flex()
+                    .items_center()
+                    .px_3()
+                    .border_b_1()
+                    .border_color(theme_colors.toc_border_color)
+                    .text_size(px(13.0))
+                    .font_weight(FontWeight::BOLD)
+                    .text_color(theme_colors.toc_text_color)
+                    .whitespace_nowrap()
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .child(root_name),
+            )
+            .child(
+                div()
+                    .h(px(rows_height(viewer.viewport_height)))
+                    .overflow_hidden()
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .relative()
+                            .top(px(-tree.scroll_y))
+                            .children(rows),
+                    ),
+            ),
+    )
+}
+
+/// Raw markdown next to the rendered document, scrolled to the same line
+///
+/// Only the lines in view are drawn. Scrolling over the pane moves both
+/// panes (see `source_view`).
+pub fn render_source_pane(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    use crate::internal::source_view::visible_lines;
+    use crate::internal::style::{SOURCE_GUTTER_WIDTH, SOURCE_LINE_HEIGHT, SOURCE_TEXT_SIZE};
+
+    let width = viewer.source_pane_width();
+    if width <= 0.0 {
+        return None;
+    }
+    let scroll_y = viewer.source_scroll_y();
+    let total = viewer.document().shown_content().lines().count();
+    let range = visible_lines(scroll_y, viewer.viewport_height, total);
+    let top_line = viewer
+        .block_map
+        .line_at_y(viewer.document().scroll_state.scroll_y);
+
+    let rows = viewer
+        .document()
+        .shown_content()
+        .lines()
+        .enumerate()
+        .skip(range.start)
+        .take(range.len())
+        .map(|(index, line)| {
+            div()
+                .h(px(SOURCE_LINE_HEIGHT))
+                .flex()
+                .items_center()
+                .whitespace_nowrap()
+                .when(index == top_line, |div| {
+                    div.bg(theme_colors.toc_active_color)
+                })
+                // The block last Alt+clicked in the rendered pane
+                .when(
+                    viewer
+                        .source_focus
+                        .as_ref()
+                        .is_some_and(|lines| lines.contains(&index)),
+                    |div| div.bg(theme_colors.focus_bg_color),
+                )
+                .child(
+                    div()
+                        .w(px(SOURCE_GUTTER_WIDTH))
+                        .flex_none()
+                        .pr_2()
+                        .flex()
+                        .justify_end()
+                        .text_color(theme_colors.code_line_color)
+                        .child((index + 1).to_string()),
+                )
+                .child(div().flex_1().overflow_hidden().child(line.to_string()))
+        });
+
+    Some(
+        div()
+            .id("source-pane")
+            .absolute()
+            .top_0()
+            .bottom_0()
+            .left(px(viewer.file_tree_width()))
+            .w(px(width))
+            .bg(theme_colors.code_bg_color)
+            .border_r_1()
+            .border_color(theme_colors.toc_border_color)
+            .overflow_hidden()
+            .font_family(viewer.config.theme.code_font.clone())
+            .text_size(px(SOURCE_TEXT_SIZE))
+            .text_color(theme_colors.text_color)
+            .on_scroll_wheel(cx.listener(crate::internal::events::handle_source_scroll_wheel))
+            .child(
+                div()
+                    .absolute()
+                    .left_0()
+                    .right_0()
+                    .top(px(range.start as f32 * SOURCE_LINE_HEIGHT - scroll_y))
+                    .flex()
+                    .flex_col()
+                    .children(rows),
+            ),
+    )
+}
+
+pub fn render_bookmarks_overlay(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    if !viewer.show_bookmarks {
+        return None;
+    }
+
+    use crate::internal::viewer::FocusableElement;
+
+    // Focus entries for the overlay are registered by `refresh_focusable_elements`
+    let focused = viewer.focused_element();
+
+    let bookmarks_list = match viewer.document().bookmarks.as_slice() {
+        [] => div()
+            .flex()
+            .items_center()
+            .justify_center()
+            .py_4()
+            .text_color(theme_colors.text_color)
+            .child("No bookmarks yet. Press Cmd+D to add one."),
+        entries => div()
+            .h(px(
+                list_height(viewer.viewport_height).min(entries.len() as f32 * BOOKMARK_ROW_HEIGHT)
+            ))
+            .overflow_hidden()
+            .on_scroll_wheel(cx.listener(|this, event: &gpui::ScrollWheelEvent, _, cx| {
+                let delta: f32 = event
+                    .delta
+                    .pixel_delta(px(this.config.theme.base_text_size))
+                    .y
+                    .into();
+                let count = this.document().bookmarks.len();
+                let viewport_height = this.viewport_height;
+                this.bookmark_list.scroll_by(-delta, count, viewport_height);
+                cx.stop_propagation();
+                cx.notify();
+            }))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .relative()
+                    .top(px(-viewer.bookmark_list.scroll_y))
+                    .children(
+                        entries
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, &line_number)| {
+                                let is_focused =
+                                    focused == Some(&FocusableElement::BookmarkItem(line_number));
+                                let is_selected = idx == viewer.bookmark_list.selected;
+                                let location = match viewer
+                                    .document()
+                                    .toc
+                                    .find_section_at_line(
+                                        viewer.document().shown_line(line_number.saturating_sub(1)),
+                                    )
+                                    .and_then(|index| viewer.document().toc.entries.get(index))
+                                {
+                                    Some(heading) => {
+                                        format!("Line {} · {}", line_number, heading.text)
+                                    }
+                                    None => format!("Line {}", line_number),
+                                };
+                                // The bookmarked text was not found after the file changed
+                                let location = match viewer
+                                    .document()
+                                    .stale_bookmarks
+                                    .contains(&line_number)
+                                {
+                                    true => format!("{} · stale", location),
+                                    false => location,
+                                };
+                                let snippet = line_snippet(&viewer.document().content, line_number)
+                                    .unwrap_or_else(|| "(blank line)".to_string());
+
+                                div()
+                                    .id(("bookmark", idx))
+                                    .h(px(BOOKMARK_ROW_HEIGHT))
+                                    .flex()
+                                    .flex_col()
+                                    .justify_center()
+                                    .overflow_hidden()
+                                    .px_4()
+                                    .rounded_sm()
+                                    .interactive(
+                                        Affordance::Row,
+                                        ClickState::default()
+                                            .selected(is_selected)
+                                            .focused(is_focused),
+                                        theme_colors,
+                                    )
+                                    .text_color(theme_colors.text_color)
+                                    .on_mouse_down(
+                                        gpui::MouseButton::Left,
+                                        cx.listener(move |this, _, _, cx| {
+                                            this.bookmark_list.selected = idx;
+                                            this.open_selected_bookmark();
+                                            cx.notify();
+                                        }),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .opacity(0.6)
+                                            .whitespace_nowrap()
+                                            .overflow_hidden()
+                                            .text_ellipsis()
+                                            .child(location),
+                                    )
+                                    .child(
+                                        div()
+                                            .whitespace_nowrap()
+                                            .overflow_hidden()
+                                            .text_ellipsis()
+                                            .child(snippet),
+                                    )
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+            ),
+    };
+
+    let close_button_focused = focused == Some(&FocusableElement::BookmarksCloseButton);
+
+    Some(
+        div()
+            .absolute()
+            .top_12()
+            .right_12()
+            .w(px(BOOKMARKS_PANEL_WIDTH))
+            .bg(theme_colors.bg_color)
+            .border_1()
+            .border_color(theme_colors.toc_border_color)
+            .shadow_lg()
+            .rounded_md()
+            .p_4()
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex()
+                            .justify_between()
+                            .items_center()
+                            .pb_2()
+                            .border_b_1()
+                            .border_color(theme_colors.toc_border_color)
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::BOLD)
+                                    .text_color(theme_colors.text_color)
+                                    .child("Bookmarks"),
+                            )
+                            .child(
+                                div()
+                                    .id("bookmarks-close")
+                                    .px_1()
+                                    .rounded_sm()
+                                    .text_color(theme_colors.text_color)
+                                    .interactive(
+                                        Affordance::Row,
+                                        ClickState::default().focused(close_button_focused),
+                                        theme_colors,
+                                    )
+                                    .on_mouse_down(
+                                        gpui::MouseButton::Left,
+                                        cx.listener(|this, _, _, cx| {
+                                            this.show_bookmarks = false;
+                                            cx.notify();
+                                        }),
+                                    )
+                                    .child("✕"),
+                            ),
+                    )
+                    .child(bookmarks_list)
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme_colors.text_color)
+                            .opacity(0.7)
+                            .child("Up/Down to select, Enter to jump, Delete to remove"),
+                    ),
+            ),
+    )
+}
+
+/// List of recent exports, newest first (Cmd+Shift+L)
+///
+/// Clicking an export of the open document repeats it; other exports are
+/// opened with their default application.
+pub fn render_export_history_overlay(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    if !viewer.show_export_history {
+        return None;
+    }
+
+    let current_document = viewer.document().path.to_string_lossy().to_string();
+    let exports_list = match viewer.config.export_history.as_slice() {
+        [] => div()
+            .flex()
+            .items_center()
+            .justify_center()
+            .py_4()
+            .text_color(theme_colors.text_color)
+            .child("No exports yet. Press Cmd+E to export a PDF."),
+        records => div().flex().flex_col().gap_1().children(
+            records
+                .iter()
+                .map(|record| {
+                    let is_current = record.document == current_document;
+                    let document_name = std::path::Path::new(&record.document)
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or(&record.document)
+                        .to_string();
+                    let destination = record.destination.clone();
+                    let format = record.format;
+
+                    div()
+                        .id(SharedString::from(record.destination.clone()))
+                        .px_4()
+                        .py_2()
+                        .interactive(Affordance::Row, ClickState::default(), theme_colors)
+                        .text_color(theme_colors.text_color)
+                        .on_mouse_down(
+                            gpui::MouseButton::Left,
+                            cx.listener(move |this, _, _, cx| {
+                                let destination = std::path::Path::new(&destination);
+                                match is_current {
+                                    true => this.export_to(format, destination),
+                                    false => cx.open_with_system(destination),
+                                }
+                                this.show_export_history = false;
+                                cx.notify();
+                            }),
+                        )
+                        .child(
+                            div()
+                                .flex()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .font_weight(FontWeight::BOLD)
+                                        .child(record.format.label()),
+                                )
+                                .child(document_name)
+                                .when(is_current, |div| div.child("(re-export)")),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(theme_colors.toc_text_color)
+                                .child(record.destination.clone()),
+                        )
+                })
+                .collect::<Vec<_>>(),
+        ),
+    };
+
+    Some(
+        div()
+            .absolute()
+            .top_12()
+            .right_12()
+            .w(px(420.0))
+            .bg(theme_colors.bg_color)
+            .border_1()
+            .border_color(theme_colors.toc_border_color)
+            .shadow_lg()
+            .rounded_md()
+            .p_4()
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex()
+                            .justify_between()
+                            .items_center()
+                            .pb_2()
+                            .border_b_1()
+                            .border_color(theme_colors.toc_border_color)
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::BOLD)
+                                    .text_color(theme_colors.text_color)
+                                    .child("Exports"),
+                            )
+                            .child(
+                                div()
+                                    .id("exports-close")
+                                    .px_1()
+                                    .rounded_sm()
+                                    .text_color(theme_colors.text_color)
+                                    .interactive(
+                                        Affordance::Row,
+                                        ClickState::default(),
+                                        theme_colors,
+                                    )
+                                    .on_mouse_down(
+                                        gpui::MouseButton::Left,
+                                        cx.listener(|this, _, _, cx| {
+                                            this.show_export_history = false;
+                                            cx.notify();
+                                        }),
+                                    )
+                                    .child("✕"),
+                            ),
+                    )
+                    .child(exports_list),
+            ),
+    )
+}
+
+/// Images that failed to load or have no alt text (Cmd+Shift+I)
+///
+/// Clicking an entry jumps to the image's line.
+pub fn render_image_report_overlay(
+    viewer: &MarkdownViewer,
     theme_colors: &crate::internal::theme::ThemeColors,
     cx: &mut gpui::Context<MarkdownViewer>,
 ) -> Option<impl IntoElement> {
-    if !viewer.show_bookmarks {
-        return None;
-    }
-
-    use crate::internal::style::FOCUS_BG_COLOR;
-    use crate::internal::viewer::FocusableElement;
+    let issues = viewer.image_report.as_ref()?;
 
-    let bookmarks_list = match viewer.bookmarks.as_slice() {
+    let issue_list = match issues.as_slice() {
         [] => div()
             .flex()
             .items_center()
             .justify_center()
             .py_4()
             .text_color(theme_colors.text_color)
-            .child("No bookmarks yet. Press Cmd+D to add one."),
-        entries => div().flex().flex_col().gap_1().children(
-            entries
+            .child("All images loaded and have alt text."),
+        issues => div().flex().flex_col().gap_1().children(
+            issues
                 .iter()
                 .enumerate()
-                .map(|(idx, &line_number)| {
-                    // Track this bookmark item as focusable
-                    let element_index = viewer.focusable_elements.len();
-                    viewer
-                        .focusable_elements
-                        .push(FocusableElement::BookmarkItem(line_number));
-
-                    let is_focused = viewer.current_focus_index == Some(element_index);
-
+                .map(|(index, issue)| {
+                    let line_number = issue.line;
                     div()
+                        .id(("image-issue", index))
                         .px_4()
                         .py_2()
-                        .cursor_pointer()
-                        .when(is_focused, |div| div.bg(FOCUS_BG_COLOR))
-                        .hover(|div| div.bg(theme_colors.toc_hover_color))
+                        .interactive(Affordance::Row, ClickState::default(), theme_colors)
                         .text_color(theme_colors.text_color)
                         .on_mouse_down(
                             gpui::MouseButton::Left,
                             cx.listener(move |this, _, _, cx| {
-                                let _ = this.scroll_to_line(line_number);
-                                this.show_bookmarks = false;
+                                this.open_image_issue(line_number);
                                 cx.notify();
                             }),
                         )
-                        .child(format!("Bookmark {}: Line {}", idx + 1, line_number))
+                        .child(
+                            div()
+                                .flex()
+                                .gap_2()
+                                .child(format!("Ln {}", line_number))
+                                .child(div().font_weight(FontWeight::BOLD).child(issue.problem())),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(theme_colors.toc_text_color)
+                                .truncate()
+                                .child(issue.url.clone()),
+                        )
                 })
                 .collect::<Vec<_>>(),
         ),
     };
-
-    // Track close button as focusable
-    let close_button_index = viewer.focusable_elements.len();
-    viewer
-        .focusable_elements
-        .push(FocusableElement::BookmarksCloseButton);
-    let close_button_focused = viewer.current_focus_index == Some(close_button_index);
+    let issue_list = div()
+        .id("image-report-list")
+        .max_h(px(IMAGE_REPORT_MAX_HEIGHT))
+        .overflow_y_scroll()
+        .child(issue_list);
 
     Some(
         div()
             .absolute()
             .top_12()
             .right_12()
-            .w(px(300.0))
+            .w(px(420.0))
             .bg(theme_colors.bg_color)
             .border_1()
             .border_color(theme_colors.toc_border_color)
@@ -486,57 +1640,600 @@ pub fn render_bookmarks_overlay(
                                 div()
                                     .font_weight(FontWeight::BOLD)
                                     .text_color(theme_colors.text_color)
-                                    .child("Bookmarks"),
+                                    .child(format!("Image Report ({})", issues.len())),
                             )
                             .child(
                                 div()
-                                    .cursor_pointer()
+                                    .id("image-report-close")
+                                    .px_1()
+                                    .rounded_sm()
                                     .text_color(theme_colors.text_color)
-                                    .when(close_button_focused, |div| div.bg(FOCUS_BG_COLOR).px_1())
+                                    .interactive(
+                                        Affordance::Row,
+                                        ClickState::default(),
+                                        theme_colors,
+                                    )
                                     .on_mouse_down(
                                         gpui::MouseButton::Left,
                                         cx.listener(|this, _, _, cx| {
-                                            this.show_bookmarks = false;
+                                            this.image_report = None;
                                             cx.notify();
                                         }),
                                     )
                                     .child("✕"),
                             ),
                     )
-                    .child(bookmarks_list),
+                    .child(issue_list),
+            ),
+    )
+}
+
+/// The clicked image over the whole window, at its native size times the
+/// lightbox zoom
+///
+/// The scroll wheel zooms, dragging pans and clicking ✕ or pressing Esc
+/// closes it.
+pub fn render_lightbox(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    use crate::internal::image_loader::ImageState;
+
+    let lightbox = viewer.lightbox.as_ref()?;
+    let Some(ImageState::Loaded(source)) = viewer.image_cache.get(&lightbox.key) else {
+        return None;
+    };
+    let native = match source {
+        gpui::ImageSource::Render(image) => {
+            let size = image.size(0);
+            (size.width.0 as f32, size.height.0 as f32)
+        }
+        _ => (IMAGE_MAX_WIDTH, IMAGE_MAX_WIDTH),
+    };
+    let (width, height) = lightbox.scaled(native);
+    let (offset_x, offset_y) = lightbox.offset;
+
+    Some(
+        div()
+            .id("lightbox")
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bottom_0()
+            .bg(theme_colors.overlay_backdrop_color)
+            .occlude()
+            .overflow_hidden()
+            .flex()
+            .items_center()
+            .justify_center()
+            .on_scroll_wheel(cx.listener(crate::internal::events::handle_lightbox_scroll_wheel))
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(|this, event: &gpui::MouseDownEvent, _, cx| {
+                    if let Some(lightbox) = this.lightbox.as_mut() {
+                        lightbox
+                            .start_drag((f32::from(event.position.x), f32::from(event.position.y)));
+                    }
+                    cx.stop_propagation();
+                }),
+            )
+            .on_mouse_move(cx.listener(|this, event: &gpui::MouseMoveEvent, _, cx| {
+                if event.pressed_button == Some(gpui::MouseButton::Left)
+                    && let Some(lightbox) = this.lightbox.as_mut()
+                {
+                    lightbox.drag_to((f32::from(event.position.x), f32::from(event.position.y)));
+                    cx.notify();
+                }
+            }))
+            .on_mouse_up(
+                gpui::MouseButton::Left,
+                cx.listener(|this, _, _, _| {
+                    if let Some(lightbox) = this.lightbox.as_mut() {
+                        lightbox.end_drag();
+                    }
+                }),
+            )
+            .child(
+                gpui::img(source.clone())
+                    .flex_none()
+                    .w(px(width))
+                    .h(px(height))
+                    .relative()
+                    .left(px(offset_x))
+                    .top(px(offset_y)),
+            )
+            .child(
+                div()
+                    .absolute()
+                    .bottom_4()
+                    .left_0()
+                    .right_0()
+                    .flex()
+                    .justify_center()
+                    .text_size(px(13.0))
+                    .text_color(theme_colors.pdf_notification_text_color)
+                    .child(format!(
+                        "{}{:.0}% · Scroll to zoom, drag to pan, Esc to close",
+                        match lightbox.alt.is_empty() {
+                            true => String::new(),
+                            false => format!("{} · ", lightbox.alt),
+                        },
+                        lightbox.zoom * 100.0
+                    )),
+            )
+            .child(
+                div()
+                    .id("lightbox-close")
+                    .absolute()
+                    .top_4()
+                    .right_4()
+                    .px_3()
+                    .py_1()
+                    .rounded_md()
+                    .bg(theme_colors.bg_color)
+                    .text_color(theme_colors.text_color)
+                    .interactive(Affordance::Button, ClickState::default(), theme_colors)
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(|this, _, _, cx| {
+                            this.lightbox = None;
+                            cx.stop_propagation();
+                            cx.notify();
+                        }),
+                    )
+                    .child("✕"),
             ),
     )
 }
 
-pub fn render_search_history_notification(
+/// Stack the toasts along the top of the window, newest at the bottom
+///
+/// Clicking a toast closes it; timed toasts show the time left as a thin
+/// bar along their bottom edge.
+pub fn render_notifications(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    let now = std::time::Instant::now();
+    match viewer.notifications.is_empty() {
+        true => None,
+        false => {
+            Some(
+                div()
+                    .absolute()
+                    .top_0()
+                    .left_0()
+                    .right_0()
+                    .flex_col()
+                    .children(viewer.notifications.iter().enumerate().map(
+                        |(index, notification)| {
+                            let bg_color = match notification.kind {
+                                NotificationKind::Error => theme_colors.pdf_error_bg_color,
+                                NotificationKind::Success | NotificationKind::Info => {
+                                    theme_colors.pdf_success_bg_color
+                                }
+                            };
+                            let source = notification.source;
+                            div()
+                                .id(("notification", index))
+                                .bg(bg_color)
+                                .text_color(theme_colors.pdf_notification_text_color)
+                                .interactive(
+                                    Affordance::Button,
+                                    ClickState::default(),
+                                    theme_colors,
+                                )
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(move |this, _, _, cx| {
+                                        this.notifications.dismiss(source);
+                                        cx.notify();
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .flex()
+                                        .items_center()
+                                        .gap_2()
+                                        .px_4()
+                                        .py_2()
+                                        .text_size(px(14.0))
+                                        .font_weight(FontWeight::BOLD)
+                                        .child(format!(
+                                            "{} {} (Click to dismiss)",
+                                            notification.kind.icon(),
+                                            notification.message
+                                        ))
+                                        .children(notification.actions.iter().enumerate().map(
+                                            |(index, action)| {
+                                                let label = action.label();
+                                                let action = action.clone();
+                                                div()
+                                                    .id(("notification-action", index))
+                                                    .px_2()
+                                                    .rounded_sm()
+                                                    .border_1()
+                                                    .border_color(
+                                                        theme_colors.pdf_notification_text_color,
+                                                    )
+                                                    .interactive(
+                                                        Affordance::Button,
+                                                        ClickState::default(),
+                                                        theme_colors,
+                                                    )
+                                                    .on_mouse_down(
+                                                        gpui::MouseButton::Left,
+                                                        cx.listener(move |this, _, _, cx| {
+                                                            cx.stop_propagation();
+                                                            this.run_notification_action(
+                                                                source, &action, cx,
+                                                            );
+                                                        }),
+                                                    )
+                                                    .child(label)
+                                            },
+                                        )),
+                                )
+                                .children(notification.remaining(now).map(|remaining| {
+                                    div()
+                                        .h(px(NOTIFICATION_PROGRESS_HEIGHT))
+                                        .w(relative(remaining))
+                                        .bg(theme_colors.pdf_notification_text_color)
+                                        .opacity(0.6)
+                                }))
+                        },
+                    )),
+            )
+        }
+    }
+}
+
+/// Stack failed transform passes and plugin panels in the bottom-left corner
+pub fn render_plugin_overlays(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+) -> Option<impl IntoElement> {
+    let nothing_to_show = viewer.plugin_overlays.is_empty() && viewer.transform_errors.is_empty();
+    match nothing_to_show || viewer.peek_mode {
+        true => None,
+        false => Some(
+            div()
+                .absolute()
+                .bottom(px(40.0))
+                .left_4()
+                .flex_col()
+                .gap_2()
+                .children(viewer.transform_errors.iter().map(|error| {
+                    div()
+                        .max_w(px(360.0))
+                        .bg(theme_colors.pdf_error_bg_color)
+                        .text_color(theme_colors.pdf_notification_text_color)
+                        .rounded_md()
+                        .px_3()
+                        .py_2()
+                        .text_size(px(13.0))
+                        .child(format!(
+                            "✗ Transform '{}' failed: {}",
+                            error.pass, error.message
+                        ))
+                }))
+                .children(viewer.plugin_overlays.iter().map(|overlay| {
+                    div()
+                        .max_w(px(360.0))
+                        .bg(theme_colors.bg_color)
+                        .text_color(theme_colors.text_color)
+                        .border_1()
+                        .border_color(theme_colors.panel_border_color)
+                        .rounded_md()
+                        .shadow_lg()
+                        .px_3()
+                        .py_2()
+                        .text_size(px(13.0))
+                        .child(
+                            div()
+                                .font_weight(FontWeight::BOLD)
+                                .child(overlay.title.clone()),
+                        )
+                        .child(div().child(overlay.body.clone()))
+                })),
+        ),
+    }
+}
+
+pub fn render_open_file_error(
     viewer: &MarkdownViewer,
     theme_colors: &crate::internal::theme::ThemeColors,
     cx: &mut gpui::Context<MarkdownViewer>,
 ) -> Option<impl IntoElement> {
-    viewer.search_history_message.as_ref().map(|message| {
+    viewer.open_file_error.as_ref().map(|message| {
         div()
+            .id("open-file-error")
             .absolute()
             .top_0()
             .left_0()
             .right_0()
-            .bg(theme_colors.pdf_success_bg_color)
+            .bg(theme_colors.pdf_error_bg_color)
             .text_color(theme_colors.pdf_notification_text_color)
             .px_4()
             .py_2()
             .text_size(px(14.0))
             .font_weight(FontWeight::BOLD)
-            .cursor_pointer()
+            .interactive(Affordance::Button, ClickState::default(), theme_colors)
             .on_mouse_down(
                 gpui::MouseButton::Left,
                 cx.listener(|this, _, _, cx| {
-                    this.search_history_message = None;
+                    this.open_file_error = None;
                     cx.notify();
                 }),
             )
-            .child(format!("ℹ {} (Click to dismiss)", message))
+            .child(format!("✗ {} (Click to dismiss)", message))
     })
 }
 
+/// Error page covering the document when the open file could not be read
+///
+/// The stale content stays hidden behind it until a reload succeeds, from
+/// the Retry button, `Cmd+R` or the file watcher.
+pub fn render_load_error_page(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    let message = viewer.load_error.as_ref()?;
+
+    Some(
+        div()
+            .id("load-error-page")
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bottom(px(match viewer.peek_mode {
+                true => 0.0,
+                false => 30.0, // Keep the status bar visible
+            }))
+            .bg(theme_colors.bg_color)
+            .occlude()
+            .flex()
+            .items_center()
+            .justify_center()
+            .child(
+                div()
+                    .max_w(px(560.0))
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .gap_3()
+                    .text_color(theme_colors.text_color)
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(px(20.0))
+                            .child("⚠ This document could not be read"),
+                    )
+                    .child(
+                        div()
+                            .text_size(px(13.0))
+                            .opacity(0.7)
+                            .child(viewer.document().path.display().to_string()),
+                    )
+                    .child(
+                        div()
+                            .w_full()
+                            .p_3()
+                            .rounded_md()
+                            .bg(theme_colors.pdf_error_bg_color)
+                            .text_color(theme_colors.pdf_notification_text_color)
+                            .text_size(px(14.0))
+                            .child(message.clone()),
+                    )
+                    .child(
+                        div()
+                            .id("load-error-retry")
+                            .px_4()
+                            .py_1()
+                            .rounded_md()
+                            .border_1()
+                            .text_size(px(14.0))
+                            .border_color(theme_colors.current_match_border_color)
+                            .interactive(
+                                Affordance::Row,
+                                ClickState::default().focused(true),
+                                theme_colors,
+                            )
+                            .on_mouse_down(
+                                gpui::MouseButton::Left,
+                                cx.listener(|this, _, _, cx| {
+                                    this.force_reload();
+                                    cx.stop_propagation();
+                                    cx.notify();
+                                }),
+                            )
+                            .child("Retry"),
+                    ),
+            ),
+    )
+}
+
+/// Project search overlay: query input and matching lines grouped by file
+///
+/// Only a window of results around the selection is drawn, with each
+/// file's path above its lines.
+pub fn render_project_search(
+    viewer: &MarkdownViewer,
+    theme_colors: &crate::internal::theme::ThemeColors,
+    cx: &mut gpui::Context<MarkdownViewer>,
+) -> Option<impl IntoElement> {
+    let search = viewer.project_search.as_ref()?;
+
+    let total = search.line_count();
+    let first = search
+        .selected
+        .saturating_sub(PROJECT_SEARCH_VISIBLE_RESULTS / 2)
+        .min(total.saturating_sub(PROJECT_SEARCH_VISIBLE_RESULTS));
+    let visible = first..first + PROJECT_SEARCH_VISIBLE_RESULTS;
+
+    let mut rows = Vec::new();
+    let mut index = 0;
+    for file in &search.results {
+        let lines = index..index + file.lines.len();
+        index = lines.end;
+        if lines.end <= visible.start || lines.start >= visible.end {
+            continue;
+        }
+        let relative = file
+            .path
+            .strip_prefix(&search.root)
+            .unwrap_or(&file.path)
+            .display()
+            .to_string();
+        rows.push(
+            div()
+                .px_2()
+                .pt_2()
+                .font_weight(FontWeight::BOLD)
+                .text_color(theme_colors.text_color)
+                .child(format!("{} ({})", relative, file.lines.len()))
+                .into_any_element(),
+        );
+        for (result_index, line) in lines.zip(&file.lines) {
+            if !visible.contains(&result_index) {
+                continue;
+            }
+            let is_selected = result_index == search.selected;
+            let path = file.path.clone();
+            let line_number = line.line;
+            let preview = &line.preview;
+            rows.push(
+                div()
+                    .id(("project-match", result_index))
+                    .flex()
+                    .gap_2()
+                    .px_2()
+                    .py(px(2.0))
+                    .rounded_sm()
+                    .text_color(theme_colors.text_color)
+                    .interactive(
+                        Affordance::Row,
+                        ClickState::default().selected(is_selected),
+                        theme_colors,
+                    )
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _, _, cx| {
+                            this.open_project_match(path.clone(), line_number, cx);
+                            cx.notify();
+                        }),
+                    )
+                    .child(
+                        div()
+                            .w(px(48.0))
+                            .flex_none()
+                            .opacity(0.6)
+                            .child(format!("{}", line_number)),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .overflow_hidden()
+                            .child(preview[..line.highlight.start].to_string())
+                            .child(
+                                div()
+                                    .bg(theme_colors.search_bg_color)
+                                    .child(preview[line.highlight.clone()].to_string()),
+                            )
+                            .child(preview[line.highlight.end..].to_string()),
+                    )
+                    .into_any_element(),
+            );
+        }
+    }
+
+    let status = match (search.query.is_empty(), search.searching, total) {
+        (true, _, _) => "Type to search every file in the folder".to_string(),
+        (false, true, _) => "Searching…".to_string(),
+        (false, false, 0) => "No matches".to_string(),
+        (false, false, n) => format!(
+            "{}{} matches in {} files",
+            n,
+            match search.truncated {
+                true => "+",
+                false => "",
+            },
+            search.results.len()
+        ),
+    };
+    let label = match viewer.search_mode {
+        SearchMode::Plain => "Search folder:".to_string(),
+        mode => format!("Search folder [{}]:", mode.label()),
+    };
+
+    Some(
+        div()
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bottom_0()
+            .bg(theme_colors.overlay_backdrop_color)
+            .flex()
+            .items_start()
+            .justify_center()
+            .pt(px(80.0))
+            .child(
+                div()
+                    .w(px(PROJECT_SEARCH_WIDTH))
+                    .bg(theme_colors.bg_color)
+                    .border_1()
+                    .border_color(theme_colors.toc_border_color)
+                    .shadow_xl()
+                    .rounded_xl()
+                    .overflow_hidden()
+                    .flex_col()
+                    .child(
+                        div()
+                            .p_4()
+                            .border_b_1()
+                            .border_color(theme_colors.toc_border_color)
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .text_color(theme_colors.text_color)
+                                    .opacity(0.7)
+                                    .child(label),
+                            )
+                            .child(
+                                div()
+                                    .text_color(theme_colors.text_color)
+                                    .font_weight(FontWeight::BOLD)
+                                    .child(format!("{}█", search.query)),
+                            ),
+                    )
+                    .child(div().flex_col().p_2().text_sm().children(rows))
+                    .child(
+                        div()
+                            .px_4()
+                            .py_2()
+                            .bg(theme_colors.toc_bg_color)
+                            .border_t_1()
+                            .border_color(theme_colors.toc_border_color)
+                            .flex()
+                            .justify_between()
+                            .text_xs()
+                            .text_color(theme_colors.text_color)
+                            .opacity(0.7)
+                            .child("Up/Down to navigate, Enter to open, Alt+R/W/C for modes, Esc to close")
+                            .child(status),
+                    ),
+            ),
+    )
+}
+
 pub fn render_file_finder(
     viewer: &MarkdownViewer,
     theme_colors: &crate::internal::theme::ThemeColors,
@@ -558,21 +2255,21 @@ pub fn render_file_finder(
             let path_clone = path.clone();
 
             div()
+                .id(("finder-match", idx))
                 .flex()
                 .px_2()
                 .py_1()
                 .w_full()
                 .rounded_sm()
-                .cursor_pointer()
-                .bg(match is_selected {
-                    true => theme_colors.toc_active_color,
-                    false => gpui::transparent_black().into(),
-                })
-                .hover(|style| style.bg(theme_colors.toc_hover_color))
+                .interactive(
+                    Affordance::Row,
+                    ClickState::default().selected(is_selected),
+                    theme_colors,
+                )
                 .on_mouse_down(
                     gpui::MouseButton::Left,
                     cx.listener(move |this, _, _, cx| {
-                        this.load_file(path_clone.clone(), cx);
+                        this.choose_finder_entry(path_clone.clone(), cx);
                     }),
                 )
                 .child(div().text_color(theme_colors.text_color).child(path_str))
@@ -586,7 +2283,7 @@ pub fn render_file_finder(
             .left_0()
             .right_0()
             .bottom_0()
-            .bg(gpui::rgba(0x00000080)) // Dim background
+            .bg(theme_colors.overlay_backdrop_color) // Dim background
             .flex()
             .items_start()
             .justify_center()
@@ -621,6 +2318,7 @@ pub fn render_file_finder(
                                                     .child(match viewer.finder_mode {
                                                         crate::internal::viewer::FinderMode::AllFiles => "Go to file:",
                                                         crate::internal::viewer::FinderMode::RecentFiles => "Open Recent:",
+                                                        crate::internal::viewer::FinderMode::Themes => "Theme:",
                                                     }),
                                             )
                                             .child(
@@ -1,31 +1,80 @@
-use comrak::{Arena, Options, parse_document};
 use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
 use gpui::{
-    AsyncWindowContext, Context, FocusHandle, ImageSource, IntoElement, Render, RenderImage,
-    WeakEntity, Window, actions, div, prelude::*, px,
+    AnyElement, AsyncApp, AsyncWindowContext, ClipboardItem, Context, Div, FocusHandle,
+    ImageSource, IntoElement, PathPromptOptions, Render, RenderImage, Task, WeakEntity, Window,
+    actions, div, prelude::*, px,
 };
 use notify_debouncer_full::Debouncer;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::{Arc, mpsc::Receiver};
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
-use crate::config::AppConfig;
+use crate::config::{AppConfig, ExportFormat, ExportRecord, RenderMode, SessionState};
+use crate::internal::about::Diagnostics;
+use crate::internal::archive::{self, ArchivePath};
+use crate::internal::block_map::{BlockMap, LinePosition, MeasuredLayout};
+use crate::internal::bookmark_anchor::{self, AnchorIndex};
+use crate::internal::bookmark_list::BookmarkListState;
+use crate::internal::dialog::{ConfirmDialog, DialogAction, DialogOutcome};
+use crate::internal::document::ParsedDocument;
 use crate::internal::events;
-use crate::internal::file_handling::{load_markdown_content, resolve_image_path};
-use crate::internal::file_watcher::FileWatcherEvent;
+use crate::internal::export_history::{last_export, record_export};
+use crate::internal::file_handling::{
+    LoadGuardError, canonical_path, is_supported_extension, load_markdown_content,
+    load_markdown_content_unchecked, modified_time, render_mode_for_path, resolve_link_target,
+    sibling_file,
+};
+use crate::internal::file_queue::FileQueue;
+use crate::internal::file_state::{FILE_STATE_PATH, FileState, FileStates};
+use crate::internal::file_tree::{FileTree, rows_height};
+use crate::internal::file_watcher::{FileWatcherEvent, start_watching};
+use crate::internal::filter::{self, FilterView};
+use crate::internal::focus;
+use crate::internal::history::HistoryEntry;
 use crate::internal::image::rgba_to_bgra;
-use crate::internal::image_loader::fetch_and_decode_image;
-use crate::internal::rendering::render_markdown_ast_with_search;
-use crate::internal::scroll::ScrollState;
-use crate::internal::search::SearchState;
+use crate::internal::image_disk_cache;
+use crate::internal::image_loader::{ImageCache, ImageProvider, ImageState};
+use crate::internal::image_report::{ImageIssue, image_issues};
+use crate::internal::inspect;
+use crate::internal::lightbox::Lightbox;
+use crate::internal::line_map::{self, LineMap};
+use crate::internal::link_rules;
+use crate::internal::menu;
+use crate::internal::network::{self, NetworkStatus};
+use crate::internal::notifications::{
+    NOTIFICATION_TICK, NotificationAction, NotificationKind, NotificationManager,
+    NotificationSource,
+};
+use crate::internal::plugin::{self, PluginOverlay};
+use crate::internal::print::{PrintJob, write_print_document};
+use crate::internal::project_search::{
+    PROJECT_SEARCH_DEBOUNCE, ProjectSearch, match_index_at_line, search_directory,
+};
+use crate::internal::rendering::{
+    LinkHandler, link_anchor, open_in_browser, render_document_node, render_plain_text_lines,
+};
+use crate::internal::scroll::HeldScroll;
+use crate::internal::search::{SearchIndex, SearchMode, SearchState};
+use crate::internal::source_view;
 use crate::internal::style::{
-    BLOCK_ELEMENT_SPACING, BOTTOM_SCROLL_PADDING, CHAR_WIDTH_MULTIPLIER, CONTENT_HEIGHT_SCALE,
-    IMAGE_MAX_WIDTH, get_theme_colors,
+    BOTTOM_SCROLL_PADDING, CHAR_WIDTH_MULTIPLIER, IMAGE_MAX_WIDTH, SOURCE_LINE_HEIGHT,
 };
+use crate::internal::tabs::{self, ClosedTabs, DocumentState};
+use crate::internal::text_format;
+use crate::internal::text_highlight::MatchColors;
+use crate::internal::theme::ActiveTheme;
+use crate::internal::toc::{TableOfContents, TocMenu, TocMenuAction};
+use crate::internal::tour::{Tour, TourOutcome};
+use crate::internal::transform::{self, TransformContext, TransformError};
 use crate::internal::ui;
+use crate::internal::viewer_events::{Propagation, Subscribers, SubscriptionId, ViewerEvent};
+use crate::internal::watchdog::{TaskFailure, supervise, supervise_blocking};
 
 // Define search actions
 actions!(search, [ToggleSearch, NextMatch, PrevMatch, ExitSearch]);
@@ -37,6 +86,18 @@ pub const IMAGE_VERTICAL_PADDING: f32 = 16.0;
 pub const PLACEHOLDER_HEIGHT: f32 = 800.0;
 /// Container padding applied by the renderer (.pt_4() + .pb_4() = ~16px * 2)
 pub const CONTAINER_PADDING: f32 = 32.0;
+/// Smallest height of the auto-sized peek window
+pub const PEEK_MIN_HEIGHT: f32 = 200.0;
+
+/// Frame interval of held-key and animated scrolling (~60 fps)
+const SCROLL_FRAME: Duration = Duration::from_millis(16);
+
+/// How often the file is checked for changes while it is not being watched
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Extra attempts at fetching a remote image after a failure that may pass
+/// (see `image_loader::is_transient`)
+const REMOTE_IMAGE_RETRIES: usize = 2;
 
 /// Represents different types of interactive elements that can receive keyboard focus
 #[derive(Debug, Clone, PartialEq)]
@@ -55,37 +116,55 @@ pub enum FocusableElement {
     BookmarksCloseButton,
 }
 
-pub enum ImageState {
-    Loading,
-    Loaded(ImageSource),
-    Error,
-}
-
 #[derive(Debug, Clone, PartialEq)]
 pub enum MarkMode {
     Set,
+    /// Set the mark at a line (0-based) instead of the top of the view
+    SetAt(usize),
     Jump,
 }
 
 pub struct MarkdownViewer {
-    pub markdown_content: String,
-    pub markdown_file_path: PathBuf,
-    pub scroll_state: ScrollState,
+    /// Line and block positions shared by every scroll-to feature, measured
+    /// from the last layout (estimated before the first one)
+    pub block_map: BlockMap,
+    /// Where the rendered blocks landed in the last layout
+    measured_layout: Option<MeasuredLayout>,
+    /// Markdown content parsed for the revision it records, reused by every
+    /// frame until the content changes
+    parsed_document: Option<Rc<ParsedDocument>>,
+    /// Continuous scrolling while a scroll key is held
+    pub held_scroll: HeldScroll,
+    /// Frame timer driving `held_scroll`; dropping it stops the timer
+    held_scroll_task: Option<Task<()>>,
+    /// Frame timer driving a smooth scroll of `scroll_state`
+    scroll_animation_task: Option<Task<()>>,
     pub viewport_height: f32,
     pub viewport_width: f32,
     pub config: AppConfig,
-    pub image_cache: HashMap<String, ImageState>,
+    /// Colors and highlighting theme for `config.theme.theme`
+    pub active_theme: ActiveTheme,
+    pub image_cache: ImageCache,
+    /// Whether remote images are fetched (see `network`)
+    pub network: NetworkStatus,
     /// Per-image displayed heights (in pixels) used to compute content height for scrolling.
     pub image_display_heights: HashMap<String, f32>,
     pub bg_rt: Arc<Runtime>,
-    /// Search state (None when search is not active)
-    pub search_state: Option<SearchState>,
-    /// Current search input text
-    pub search_input: String,
+    /// Whether the search bar gave up the keyboard and only its highlights
+    /// remain; n / N step through them
+    pub search_highlights_kept: bool,
+    /// How search queries are matched (plain, regex, whole word, match case)
+    pub search_mode: SearchMode,
+    /// Lowercased document and last matches, reused while typing a query
+    search_index: SearchIndex,
     /// Focus handle for keyboard events
     pub focus_handle: FocusHandle,
     /// Whether to show the help overlay
     pub show_help: bool,
+    /// Whether the state was flushed for exiting (see `shutdown`)
+    shut_down: bool,
+    /// Diagnostics shown in the About overlay, while it is open
+    pub about: Option<Diagnostics>,
     /// File watcher event receiver
     pub file_watcher_rx: Option<Receiver<FileWatcherEvent>>,
     /// File watcher debouncer (must be kept alive)
@@ -94,42 +173,68 @@ pub struct MarkdownViewer {
         Option<Debouncer<notify::RecommendedWatcher, notify_debouncer_full::FileIdMap>>,
     /// Whether the file has been deleted
     pub file_deleted: bool,
+    /// Set when the file changed on disk while no watcher reloads it
+    pub changed_on_disk: bool,
+    /// When the unwatched file was last compared against `loaded_mtime`
+    last_disk_check: Option<Instant>,
     /// Whether to show the table of contents sidebar
     pub show_toc: bool,
-    /// Table of contents extracted from markdown
-    pub toc: crate::internal::toc::TableOfContents,
-    /// TOC sidebar scroll position
-    pub toc_scroll_y: f32,
     /// TOC sidebar maximum scroll position
     pub toc_max_scroll_y: f32,
+    /// TOC entries whose subsections are hidden
+    pub toc_collapsed: BTreeSet<usize>,
+    /// Right-click menu of a TOC entry, while open
+    pub toc_menu: Option<TocMenu>,
+    /// Files of the launch directory, once the file tree sidebar was opened
+    pub file_tree: Option<FileTree>,
+    /// Whether to show the file tree sidebar
+    pub show_file_tree: bool,
+    /// Whether to show the raw source next to the rendered document
+    pub show_source: bool,
+    /// Source lines of the block last Alt+clicked, highlighted in the source view
+    pub source_focus: Option<Range<usize>>,
     /// Whether go-to-line dialog is active
     pub show_goto_line: bool,
     /// Current go-to-line input text
     pub goto_line_input: String,
+    /// Whether the go-to-line prompt takes a percentage of the document
+    pub goto_percent: bool,
     /// Whether to trigger PDF export
     pub trigger_pdf_export: bool,
-    /// PDF export result message (Some when showing notification)
-    pub pdf_export_message: Option<String>,
-    /// Whether PDF export was successful (for coloring the notification)
-    pub pdf_export_success: bool,
-    /// Whether showing PDF overwrite confirmation
-    pub show_pdf_overwrite_confirm: bool,
-    /// Path of PDF to potentially overwrite
-    pub pdf_overwrite_path: Option<std::path::PathBuf>,
+    /// Modal confirmation dialog; while open it receives every key
+    pub confirm_dialog: Option<ConfirmDialog>,
     /// Current index in search history (None means not browsing history)
     pub search_history_index: Option<usize>,
-    /// List of bookmarked line numbers
-    pub bookmarks: Vec<usize>,
     /// Whether to show the bookmarks overlay
     pub show_bookmarks: bool,
-    /// Message to show when search history is cleared/saved
-    pub search_history_message: Option<String>,
+    /// Selected row and scroll offset of the bookmarks overlay
+    pub bookmark_list: BookmarkListState,
+    /// Whether to show the exports overlay
+    pub show_export_history: bool,
+    /// Broken images and images without alt text, while the image report
+    /// overlay is open
+    pub image_report: Option<Vec<ImageIssue>>,
+    /// Image shown over the whole window, after clicking it
+    pub lightbox: Option<Lightbox>,
     /// List of focusable elements found during render (for keyboard navigation)
     pub focusable_elements: Vec<FocusableElement>,
     /// Index of the currently focused element (None means no focus)
     pub current_focus_index: Option<usize>,
-    /// v0.12.5: Map of marks to scroll positions
-    pub marks: HashMap<char, f32>,
+    /// Revision counter bumped whenever the shown content is replaced or reinterpreted
+    pub content_revision: u64,
+    /// Inputs the focus list was last built from (content revision, open bookmarks overlay)
+    focus_list_key: Option<(u64, Option<Vec<usize>>)>,
+    /// Offer to remap marks after an external change moved their lines
+    pub remap_prompt: Option<RemapPrompt>,
+    /// Export and informational toasts
+    pub notifications: NotificationManager,
+    /// Timer closing timed toasts and redrawing their progress bars
+    notification_task: Option<Task<()>>,
+    /// Error shown after a file chosen in the open dialog could not be opened
+    pub open_file_error: Option<String>,
+    /// Why the open file could not be read on its last (re)load; an error
+    /// page with a Retry button replaces the stale document while it is set
+    pub load_error: Option<String>,
     /// v0.12.5: Current mark mode (Set/Jump)
     pub mark_mode: Option<MarkMode>,
     /// v0.12.5: Track if 'z' was pressed for 'zz' command
@@ -150,6 +255,40 @@ pub struct MarkdownViewer {
     pub matcher: SkimMatcherV2,
     /// v0.13.1: Current mode of the file finder
     pub finder_mode: FinderMode,
+    /// Directory the file finder scans; `None` scans the working directory
+    pub finder_root: Option<PathBuf>,
+    /// Search across the files of the launch directory (Cmd+Shift+F)
+    pub project_search: Option<ProjectSearch>,
+    /// Background search for the latest project search query
+    project_search_task: Option<Task<()>>,
+    /// Files passed on the command line, stepped through with Cmd+] / Cmd+[
+    pub file_queue: FileQueue,
+    /// Open documents, each with its own scroll position, search and marks
+    pub tabs: Vec<DocumentState>,
+    /// Index of the tab whose document is shown in the window
+    pub active_tab: usize,
+    /// Recently closed tabs, for reopening them
+    pub closed_tabs: ClosedTabs,
+    /// Whether the file reloaded while the window was in the background
+    pub unseen_reload: bool,
+    /// Window title last applied, to avoid resetting it every frame
+    pub window_title: String,
+    /// Quick-look mode: no status bar or TOC, closes on Escape or focus loss
+    pub peek_mode: bool,
+    /// Where recently read files were left, restored when they are reopened
+    file_states: FileStates,
+    /// First-run tour, while it is shown
+    pub tour: Option<Tour>,
+    /// Panels contributed by plugins for the current document
+    pub plugin_overlays: Vec<PluginOverlay>,
+    /// Transform passes that failed on the current document
+    pub transform_errors: Vec<TransformError>,
+    /// Embedder callbacks, see [`MarkdownViewer::subscribe`]
+    subscribers: Subscribers,
+    /// Scroll position last reported through `ScrollChanged`
+    reported_scroll_y: f32,
+    /// Search query and match count last reported through `SearchChanged`
+    reported_search: (String, usize),
     /// v0.13.2: Config watcher event receiver
     pub config_watcher_rx: Option<Receiver<FileWatcherEvent>>,
     /// v0.13.2: Config watcher debouncer (must be kept alive)
@@ -158,10 +297,21 @@ pub struct MarkdownViewer {
         Option<Debouncer<notify::RecommendedWatcher, notify_debouncer_full::FileIdMap>>,
 }
 
+/// Pending offer to move marks after the file changed on disk
+#[derive(Debug, Clone)]
+pub struct RemapPrompt {
+    /// Line mapping from the previous content to the reloaded content
+    pub line_map: LineMap,
+    /// Number of marks whose line would change
+    pub moved_marks: usize,
+}
+
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum FinderMode {
     AllFiles,
     RecentFiles,
+    /// Theme names instead of files; choosing one switches the theme
+    Themes,
 }
 
 /// Container for file and config watcher state to reduce constructor arguments
@@ -186,48 +336,67 @@ impl MarkdownViewer {
         let viewport_height = config.window.height;
         let viewport_width = config.window.width;
 
-        // Parse markdown to generate TOC
-        let arena = comrak::Arena::new();
-        let mut options = comrak::Options::default();
-        options.extension.table = true;
-        let root = comrak::parse_document(&arena, &markdown_content, &options);
-        let toc = crate::internal::toc::TableOfContents::from_ast(root);
+        let render_mode = render_mode_for_path(&markdown_file_path, &config.files.extension_modes);
+
+        let active_theme = ActiveTheme::resolve(&config.theme.theme);
+        let notifications = NotificationManager::new(config.notifications.clone());
+        let (markdown_content, text_format) = text_format::prepare(markdown_content);
 
         let mut viewer = Self {
-            markdown_content,
-            markdown_file_path,
-            scroll_state: ScrollState::new(),
+            block_map: BlockMap::default(),
+            measured_layout: None,
+            parsed_document: None,
+            held_scroll: HeldScroll::default(),
+            held_scroll_task: None,
+            scroll_animation_task: None,
             viewport_height,
             viewport_width,
+            network: NetworkStatus::new(config.images.offline),
             config,
-            image_cache: HashMap::new(),
+            active_theme,
+            image_cache: ImageCache::default(),
             image_display_heights: HashMap::new(),
             bg_rt,
-            search_state: None,
-            search_input: String::new(),
+            search_mode: SearchMode::default(),
+            search_index: SearchIndex::default(),
             focus_handle,
             show_help: false,
+            shut_down: false,
+            about: None,
             file_watcher_rx: watcher_state.file_watcher_rx,
             file_watcher: watcher_state.file_watcher,
             file_deleted: false,
+            changed_on_disk: false,
+            last_disk_check: None,
             show_toc: false,
-            toc,
-            toc_scroll_y: 0.0,
+            toc_collapsed: BTreeSet::new(),
+            toc_menu: None,
             toc_max_scroll_y: 0.0,
+            file_tree: None,
+            show_file_tree: false,
+            show_source: false,
+            source_focus: None,
             show_goto_line: false,
             goto_line_input: String::new(),
+            goto_percent: false,
             trigger_pdf_export: false,
-            pdf_export_message: None,
-            pdf_export_success: false,
-            show_pdf_overwrite_confirm: false,
-            pdf_overwrite_path: None,
+            confirm_dialog: None,
             search_history_index: None,
-            bookmarks: Vec::new(),
+            search_highlights_kept: false,
             show_bookmarks: false,
-            search_history_message: None,
+            bookmark_list: BookmarkListState::default(),
+            show_export_history: false,
+            image_report: None,
+            lightbox: None,
+            notifications,
+            notification_task: None,
+            open_file_error: None,
+            load_error: None,
             focusable_elements: Vec::new(),
             current_focus_index: None,
-            marks: HashMap::new(),
+            content_revision: 0,
+            focus_list_key: None,
+            remap_prompt: None,
             mark_mode: None,
             z_pressed_once: false,
             help_page: 0,
@@ -238,18 +407,58 @@ impl MarkdownViewer {
             finder_selected_index: 0,
             matcher: SkimMatcherV2::default(),
             finder_mode: FinderMode::AllFiles,
+            finder_root: None,
+            project_search: None,
+            project_search_task: None,
+            file_queue: FileQueue::default(),
+            tabs: vec![DocumentState {
+                content: markdown_content,
+                text_format,
+                render_mode,
+                loaded_mtime: modified_time(&markdown_file_path),
+                ..DocumentState::new(canonical_path(&markdown_file_path))
+            }],
+            active_tab: 0,
+            closed_tabs: ClosedTabs::default(),
+            unseen_reload: false,
+            window_title: String::new(),
+            peek_mode: false,
+            file_states: FileStates::load_from_file(FILE_STATE_PATH).unwrap_or_else(|e| {
+                warn!("Failed to load file state: {}", e);
+                FileStates::default()
+            }),
+            tour: None,
+            plugin_overlays: Vec::new(),
+            transform_errors: Vec::new(),
+            subscribers: Subscribers::default(),
+            reported_scroll_y: 0.0,
+            reported_search: (String::new(), 0),
             config_watcher_rx: watcher_state.config_watcher_rx,
             config_watcher: watcher_state.config_watcher,
         };
 
+        link_rules::set_rules(&viewer.config.link_rewrites);
+        image_disk_cache::configure(&viewer.config.images);
+        viewer.run_document_hooks();
+        viewer.rebuild_toc();
         viewer.recompute_max_scroll();
         viewer.compute_toc_max_scroll();
         viewer
     }
 
+    /// The document of the active tab, which the window shows
+    pub fn document(&self) -> &DocumentState {
+        &self.tabs[self.active_tab]
+    }
+
+    /// Mutable access to the document of the active tab
+    pub fn document_mut(&mut self) -> &mut DocumentState {
+        &mut self.tabs[self.active_tab]
+    }
+
     /// Compute the maximum scroll position for the TOC sidebar
     pub fn compute_toc_max_scroll(&mut self) {
-        if self.toc.entries.is_empty() {
+        if self.document().toc.entries.is_empty() {
             self.toc_max_scroll_y = 0.0;
             return;
         }
@@ -259,7 +468,12 @@ impl MarkdownViewer {
         const ENTRY_HEIGHT: f32 = 30.0; // Approximate height per entry
         const CONTAINER_PADDING: f32 = 32.0; // pt_4 + pb_4
 
-        let toc_content_height = (self.toc.entries.len() as f32) * ENTRY_HEIGHT + CONTAINER_PADDING;
+        let visible = self
+            .document()
+            .toc
+            .visible_entries(&self.toc_collapsed)
+            .len();
+        let toc_content_height = (visible as f32) * ENTRY_HEIGHT + CONTAINER_PADDING;
         let toc_viewport_height = self.viewport_height;
 
         self.toc_max_scroll_y = (toc_content_height - toc_viewport_height).max(0.0);
@@ -271,21 +485,35 @@ impl MarkdownViewer {
 
         match self.finder_mode {
             FinderMode::RecentFiles => {
-                for path_str in &self.config.recent_files {
-                    files.push(PathBuf::from(path_str));
-                }
+                files.extend(self.config.recent_files.iter().cloned());
+            }
+            FinderMode::Themes => {
+                files.extend(
+                    crate::internal::theme::registry()
+                        .list_names()
+                        .into_iter()
+                        .map(PathBuf::from),
+                );
             }
             FinderMode::AllFiles => {
+                let extensions = self.config.files.all_extensions();
                 let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+                let scan_root = self.finder_root.as_ref().unwrap_or(&current_dir);
+                // Archives list their entries instead of walking the filesystem
+                if archive::is_archive(scan_root) {
+                    match archive::list_entries(scan_root, &extensions) {
+                        Ok(entries) => files.extend(entries.iter().map(ArchivePath::to_path_buf)),
+                        Err(e) => warn!("Failed to list archive {:?}: {}", scan_root, e),
+                    }
+                    self.all_files = files;
+                    self.update_finder_matches();
+                    return;
+                }
                 // Scan for markdown files
-                for entry in WalkDir::new(&current_dir)
-                    .into_iter()
-                    .filter_map(|e| e.ok())
-                {
+                for entry in WalkDir::new(scan_root).into_iter().filter_map(|e| e.ok()) {
                     let path = entry.path();
                     if path.is_file()
-                        && let Some("md" | "markdown" | "txt") =
-                            path.extension().and_then(|s| s.to_str())
+                        && is_supported_extension(&path.to_string_lossy(), &extensions)
                     {
                         // Store relative path if possible for cleaner UI
                         match path.strip_prefix(&current_dir) {
@@ -300,6 +528,135 @@ impl MarkdownViewer {
         self.update_finder_matches();
     }
 
+    /// Open the finder on the list of themes
+    pub fn open_theme_picker(&mut self) {
+        self.show_file_finder = true;
+        self.finder_mode = FinderMode::Themes;
+        self.finder_query.clear();
+        self.refresh_file_list();
+    }
+
+    /// Act on the finder entry `path`: open the file, or switch to the theme
+    pub fn choose_finder_entry(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        match self.finder_mode {
+            FinderMode::Themes => {
+                if let Err(e) = self.set_theme(&path.to_string_lossy()) {
+                    warn!("{}", e);
+                }
+                self.show_file_finder = false;
+                self.finder_query.clear();
+                cx.notify();
+            }
+            FinderMode::AllFiles | FinderMode::RecentFiles => self.open_in_new_tab(path, cx),
+        }
+    }
+
+    /// Open the file finder scoped to `dir` (used when launched on a directory
+    /// or an archive)
+    pub fn open_file_finder_in(&mut self, dir: PathBuf) {
+        self.finder_root = Some(canonical_path(&dir));
+        self.finder_mode = FinderMode::AllFiles;
+        self.finder_query.clear();
+        self.show_file_finder = true;
+        self.refresh_file_list();
+    }
+
+    /// Directory searched by the project search: the directory the viewer
+    /// was launched on, if any (archives are not searched)
+    pub fn project_root(&self) -> Option<&Path> {
+        self.finder_root
+            .as_deref()
+            .filter(|root| root.is_dir() && !archive::is_archive(root))
+    }
+
+    /// Open the project search overlay on the launch directory
+    pub fn open_project_search(&mut self) {
+        if let Some(root) = self.project_root() {
+            info!("Opening project search in {:?}", root);
+            self.project_search = Some(ProjectSearch::new(root.to_path_buf()));
+        }
+    }
+
+    /// Close the project search overlay and drop a running search
+    pub fn close_project_search(&mut self) {
+        self.project_search = None;
+        self.project_search_task = None;
+    }
+
+    /// Search the project for the current query on the background runtime
+    ///
+    /// The search starts after a short pause so typing does not walk the
+    /// directory for every keystroke; results of older queries are dropped.
+    pub fn run_project_search(&mut self, cx: &mut Context<Self>) {
+        let Some(search) = self.project_search.as_mut() else {
+            return;
+        };
+        search.generation += 1;
+        search.searching = !search.query.is_empty();
+        if search.query.is_empty() {
+            search.finish(search.generation, Vec::new());
+            self.project_search_task = None;
+            return;
+        }
+
+        let generation = search.generation;
+        let root = search.root.clone();
+        let query = search.query.clone();
+        let mode = self.search_mode;
+        let extensions = self.config.files.all_extensions();
+        let bg_rt = self.bg_rt.clone();
+        self.project_search_task = Some(cx.spawn(
+            async move |this: WeakEntity<MarkdownViewer>, cx: &mut AsyncApp| {
+                cx.background_executor()
+                    .timer(PROJECT_SEARCH_DEBOUNCE)
+                    .await;
+                let searched = supervise_blocking(&bg_rt, "Searching files", 0, move || {
+                    Ok(search_directory(&root, &query, mode, &extensions))
+                })
+                .await;
+                let _ = this.update(cx, |this, cx| {
+                    let results = match searched {
+                        Ok(results) => results,
+                        Err(failure) => {
+                            this.report_task_failure(&failure);
+                            cx.notify();
+                            Vec::new()
+                        }
+                    };
+                    if let Some(search) = this.project_search.as_mut()
+                        && search.finish(generation, results)
+                    {
+                        cx.notify();
+                    }
+                });
+            },
+        ));
+    }
+
+    /// Open a project search result: the file in a tab, with the query
+    /// highlighted and the match on `line` (1-based) selected
+    pub fn open_project_match(&mut self, path: PathBuf, line: usize, cx: &mut Context<Self>) {
+        let Some(search) = self.project_search.take() else {
+            return;
+        };
+        self.project_search_task = None;
+        self.open_in_new_tab(path.clone(), cx);
+        if self.document().path != canonical_path(&path) {
+            return;
+        }
+        self.set_search(&search.query);
+        let index = self.document().search_state.as_ref().and_then(|state| {
+            match_index_at_line(state.matches(), self.document().shown_content(), line)
+        });
+        match index {
+            Some(index) => self.jump_to_match(index),
+            None => {
+                let _ = self.scroll_to_line(line);
+            }
+        }
+        info!("Opened project search result {:?}:{}", path, line);
+    }
+
     /// Update the fuzzy finder matches based on the current query
     pub fn update_finder_matches(&mut self) {
         self.finder_matches = match self.finder_query.is_empty() {
@@ -322,7 +679,7 @@ impl MarkdownViewer {
                     .collect();
 
                 // Sort by score descending
-                matches.sort_by(|a, b| b.0.cmp(&a.0));
+                matches.sort_by_key(|m| std::cmp::Reverse(m.0));
                 // Cap at 20 results for performance/UI
                 if matches.len() > 20 {
                     matches.truncate(20);
@@ -333,266 +690,1663 @@ impl MarkdownViewer {
         self.finder_selected_index = 0;
     }
 
+    /// Put the open file at the front of the recent files (Cmd+Shift+O)
+    /// and save the config
+    pub fn remember_recent_file(&mut self) {
+        self.config
+            .add_recent_file(&canonical_path(&self.document().path));
+        if let Err(e) = self.config.save_to_file("config.ron") {
+            warn!("Failed to save recent files to config: {}", e);
+        }
+    }
+
     /// Load a new markdown file and reset viewer state
+    ///
+    /// Oversized or binary-looking files are not opened directly; a dialog
+    /// asks first.
     pub fn load_file(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        self.open_file(path, false, false, cx);
+    }
+
+    /// Ask whether to open a file that tripped a load guard, and whether it
+    /// then opens in a new tab
+    pub fn ask_open_anyway(&mut self, guard: &LoadGuardError, new_tab: bool) {
+        info!("Asking before opening: {}", guard);
+        self.show_file_finder = false;
+        self.dismiss_line_remap();
+        self.confirm_dialog = Some(ConfirmDialog::open_anyway(guard, new_tab));
+    }
+
+    /// Open a file the user confirmed, skipping the size/binary checks
+    fn confirm_pending_open(&mut self, path: PathBuf, new_tab: bool, cx: &mut Context<Self>) {
+        info!("Opening {:?} anyway", path);
+        match new_tab {
+            true => self.open_tab(path, true, cx),
+            false => self.open_file(path, true, false, cx),
+        }
+    }
+
+    /// Load `path` into the active document
+    ///
+    /// `new_tab` only tells the open-anyway dialog where the file was headed.
+    fn open_file(
+        &mut self,
+        path: PathBuf,
+        skip_guards: bool,
+        new_tab: bool,
+        cx: &mut Context<Self>,
+    ) {
+        // Canonicalize once so recent files, the queue and the watcher agree on the path
+        let path = canonical_path(&path);
+
         // Load content
         let path_str = path.to_string_lossy().to_string();
-        match crate::internal::file_handling::load_markdown_content(&path_str) {
+        let loaded = match skip_guards {
+            true => load_markdown_content_unchecked(&path_str),
+            false => load_markdown_content(&path_str),
+        };
+        match loaded {
             Ok(content) => {
-                self.markdown_file_path = path.clone();
-                self.markdown_content = content;
-
-                // Update recent files
-                if let Some(pos) = self.config.recent_files.iter().position(|r| r == &path_str) {
-                    self.config.recent_files.remove(pos);
-                }
-                self.config.recent_files.insert(0, path_str.clone());
-                if self.config.recent_files.len() > self.config.max_recent_files {
-                    self.config
-                        .recent_files
-                        .truncate(self.config.max_recent_files);
-                }
-                // Save config
-                if let Err(e) = self.config.save_to_file("config.ron") {
-                    warn!("Failed to save recent files to config: {}", e);
+                let (content, text_format) = text_format::prepare(content);
+                // Remember where the file being left was read up to
+                if !self.document().content.is_empty() {
+                    self.clear_filter();
+                    let left = self.document().file_state();
+                    self.remember_file_states(vec![left]);
                 }
 
+                // The new document starts fresh; only the tab's link history stays
+                let history = std::mem::take(&mut self.document_mut().history);
+                *self.document_mut() = DocumentState {
+                    content,
+                    text_format,
+                    render_mode: render_mode_for_path(&path, &self.config.files.extension_modes),
+                    history,
+                    loaded_mtime: modified_time(&path),
+                    skip_guards,
+                    ..DocumentState::new(path.clone())
+                };
+                self.changed_on_disk = false;
+                self.content_revision += 1;
+                self.file_queue.select(&path);
+                self.rewatch_file();
+
+                self.remember_recent_file();
+
                 // Reset Scroll & State
-                self.scroll_state = ScrollState::new();
-                self.search_state = None;
-                self.search_input.clear();
                 self.search_history_index = None;
-                self.bookmarks.clear();
                 self.show_bookmarks = false;
                 self.show_goto_line = false;
                 self.goto_line_input.clear();
                 self.show_file_finder = false;
                 self.finder_query.clear();
                 self.show_help = false;
-                self.marks.clear();
                 self.mark_mode = None;
+                self.source_focus = None;
+                self.dismiss_line_remap();
+                self.open_file_error = None;
+                self.load_error = None;
+                self.image_cache.clear();
+                self.image_display_heights.clear();
+                self.clear_focus();
 
-                // Re-parse TOC
-                let arena = comrak::Arena::new();
-                let mut options = comrak::Options::default();
-                options.extension.table = true;
-                let root = comrak::parse_document(&arena, &self.markdown_content, &options);
-                self.toc = crate::internal::toc::TableOfContents::from_ast(root);
+                self.run_document_hooks();
+                self.rebuild_toc();
 
                 self.recompute_max_scroll();
                 self.compute_toc_max_scroll();
+                self.restore_file_state();
+                let tree_height = rows_height(self.viewport_height);
+                if let Some(tree) = self.file_tree.as_mut() {
+                    tree.reveal(&path, tree_height);
+                }
 
-                info!("Loaded file: {:?}", self.markdown_file_path);
+                info!("Loaded file: {:?}", self.document().path);
                 cx.notify();
             }
-            Err(e) => {
-                warn!("Failed to load file {:?}: {}", path, e);
+            Err(e) => match e.downcast_ref::<LoadGuardError>() {
+                Some(guard) => {
+                    self.ask_open_anyway(guard, new_tab);
+                    cx.notify();
+                }
+                None => {
+                    warn!("Failed to load file {:?}: {}", path, e);
+                    self.open_file_error = Some(format!("Failed to open {}: {}", path_str, e));
+                    cx.notify();
+                }
+            },
+        }
+    }
+
+    /// Open `path` in a new tab after the current one, or switch to the tab
+    /// already showing it
+    pub fn open_in_new_tab(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        self.open_tab(path, false, cx);
+    }
+
+    /// [`Self::open_in_new_tab`], skipping the size/binary checks for a file
+    /// the user confirmed
+    fn open_tab(&mut self, path: PathBuf, skip_guards: bool, cx: &mut Context<Self>) {
+        let path = canonical_path(&path);
+        if let Some(index) = tabs::find_tab(&self.tabs, &path) {
+            self.switch_tab(index, cx);
+            return;
+        }
+
+        let previous = self.active_tab;
+        self.active_tab = previous + 1;
+        self.tabs.insert(self.active_tab, DocumentState::default());
+        self.open_file(path.clone(), skip_guards, true, cx);
+        // On failure the previous tab is shown again, untouched
+        if self.document().path != path {
+            self.tabs.remove(self.active_tab);
+            self.active_tab = previous;
+            return;
+        }
+        info!("Opened {:?} in tab {}", path, self.active_tab + 1);
+    }
+
+    /// Show the tab at `index`
+    pub fn switch_tab(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+        self.active_tab = index;
+        self.show_active_document();
+        debug!("Switched to tab {}", index + 1);
+        cx.notify();
+    }
+
+    /// Close the tab at `index`; the last remaining tab stays open
+    pub fn close_tab(&mut self, index: usize, cx: &mut Context<Self>) {
+        if self.tabs.len() <= 1 || index >= self.tabs.len() {
+            debug!("Not closing the only open tab");
+            return;
+        }
+        if index == self.active_tab {
+            self.clear_filter();
+        }
+        let closed = self.tabs.remove(index);
+        match index.cmp(&self.active_tab) {
+            std::cmp::Ordering::Less => self.active_tab -= 1,
+            std::cmp::Ordering::Greater => {}
+            std::cmp::Ordering::Equal => {
+                self.active_tab = tabs::tab_after_close(index, self.tabs.len());
+                self.show_active_document();
             }
         }
+        self.remember_file_states(vec![closed.file_state()]);
+        self.closed_tabs.push(closed.path, index);
+        debug!("Closed tab {}", index + 1);
+        cx.notify();
     }
 
-    // Calculate the estimated Y scroll position for a given byte offset
-    pub fn calculate_y_for_offset(&self, target_offset: usize) -> f32 {
-        if target_offset >= self.markdown_content.len() {
-            return self.scroll_state.max_scroll_y;
+    /// Open the most recently closed tab again at its old position
+    ///
+    /// The scroll position, bookmarks and search come back from the file
+    /// state saved when the tab was closed.
+    pub fn reopen_closed_tab(&mut self, cx: &mut Context<Self>) {
+        let Some(closed) = self.closed_tabs.pop(&self.tabs) else {
+            debug!("No closed tab to reopen");
+            return;
+        };
+        self.open_in_new_tab(closed.path.clone(), cx);
+        // Keep a tab that failed to open, or waits in the open-anyway dialog,
+        // for the next reopen
+        let Some(opened) = tabs::find_tab(&self.tabs, &closed.path) else {
+            self.closed_tabs.push(closed.path, closed.index);
+            return;
+        };
+        let index = closed.index.min(self.tabs.len() - 1);
+        let tab = self.tabs.remove(opened);
+        self.tabs.insert(index, tab);
+        self.active_tab = index;
+        info!("Reopened {:?} in tab {}", closed.path, index + 1);
+        cx.notify();
+    }
+
+    /// Show the document of the tab that just became active
+    ///
+    /// Its state is kept as it was left; the file is only re-read when it
+    /// changed on disk while the tab was in the background.
+    fn show_active_document(&mut self) {
+        self.load_error = None;
+        self.content_revision += 1;
+
+        self.search_history_index = None;
+        self.show_bookmarks = false;
+        self.show_goto_line = false;
+        self.goto_line_input.clear();
+        self.mark_mode = None;
+        self.dismiss_line_remap();
+        self.source_focus = None;
+        self.clear_focus();
+        let path = self.document().path.clone();
+        self.file_queue.select(&path);
+        self.rewatch_file();
+
+        if modified_time(&path) != self.document().loaded_mtime {
+            self.reload_from_disk();
         }
+        self.recompute_max_scroll();
+        self.compute_toc_max_scroll();
+    }
 
-        let pre_text = &self.markdown_content[..target_offset];
-        // Count newlines to know how many full lines are before the target
-        let lines_to_sum = pre_text.chars().filter(|&c| c == '\n').count();
+    /// Show the native file-open dialog and load the chosen file
+    ///
+    /// The platform dialog cannot filter by extension, so the selection is
+    /// checked against the supported extensions once it comes back. Choosing
+    /// a zip or epub archive opens the file finder on its contents.
+    pub fn open_file_dialog(&mut self, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Open".into()),
+        });
 
-        let avg_line_height =
-            self.config.theme.base_text_size * self.config.theme.line_height_multiplier;
+        cx.spawn(
+            async move |this: WeakEntity<MarkdownViewer>, cx: &mut AsyncApp| {
+                let path = match receiver.await {
+                    Ok(Ok(Some(paths))) => paths.into_iter().next(),
+                    Ok(Ok(None)) | Err(_) => None,
+                    Ok(Err(e)) => {
+                        warn!("Failed to show file open dialog: {}", e);
+                        None
+                    }
+                };
+                let Some(path) = path else {
+                    debug!("File open dialog cancelled");
+                    return;
+                };
+
+                this.update(cx, |this, cx| {
+                    let extensions = this.config.files.all_extensions();
+                    if archive::is_archive(&path) {
+                        this.open_file_finder_in(path);
+                        cx.notify();
+                        return;
+                    }
+                    match is_supported_extension(&path.to_string_lossy(), &extensions) {
+                        true => this.open_in_new_tab(path, cx),
+                        false => {
+                            warn!("Unsupported file chosen in open dialog: {:?}", path);
+                            this.open_file_error = Some(format!(
+                                "Unsupported file type: {} (supported: {})",
+                                path.display(),
+                                extensions.join(", ")
+                            ));
+                            cx.notify();
+                        }
+                    }
+                })
+                .ok();
+            },
+        )
+        .detach();
+    }
 
-        let mut y = 0.0;
-        let mut in_fenced_code = false;
+    /// Notify plugins and subscribers about the current document
+    fn run_document_hooks(&mut self) {
+        self.plugin_overlays =
+            plugin::document_loaded(&self.document().path, &self.document().content);
+        self.emit(ViewerEvent::DocumentLoaded {
+            path: self.document().path.clone(),
+        });
+    }
 
-        for (i, raw_line) in self.markdown_content.lines().enumerate() {
-            if i >= lines_to_sum {
-                break;
-            }
+    /// Call `callback` for every [`ViewerEvent`] until unsubscribed
+    ///
+    /// Return [`Propagation::Stop`] from a `LinkClicked` handler to keep the
+    /// viewer from opening the link.
+    pub fn subscribe(
+        &mut self,
+        callback: impl FnMut(&ViewerEvent) -> Propagation + 'static,
+    ) -> SubscriptionId {
+        self.subscribers.subscribe(callback)
+    }
 
-            let line = raw_line.trim_start();
+    /// Remove a subscription; returns whether it existed
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.subscribers.unsubscribe(id)
+    }
 
-            // Toggle fenced code block state
-            if line.starts_with("```") {
-                in_fenced_code = !in_fenced_code;
-            }
+    fn emit(&mut self, event: ViewerEvent) -> Propagation {
+        self.subscribers.emit(&event)
+    }
 
-            let weight = match () {
-                _ if in_fenced_code => 1.25,        // code_line_weight
-                _ if line.starts_with('#') => 1.6,  // heading_weight
-                _ if line.starts_with('>') => 1.15, // blockquote_weight
-                _ => 1.0,                           // list_line_weight and normal_line_weight
-            };
+    /// Report scroll and search changes made since the last frame
+    ///
+    /// These change from many key handlers, so they are compared once per
+    /// render instead of emitted at every call site.
+    fn emit_state_changes(&mut self) {
+        let scroll_y = self.document().scroll_state.scroll_y;
+        if scroll_y != self.reported_scroll_y {
+            self.reported_scroll_y = scroll_y;
+            let line = self.get_current_line_number();
+            self.emit(ViewerEvent::ScrollChanged { scroll_y, line });
+        }
 
-            y += avg_line_height * weight;
+        let search = self
+            .document()
+            .search_state
+            .as_ref()
+            .map(|state| (state.query().to_string(), state.match_count()))
+            .unwrap_or_default();
+        if search != self.reported_search {
+            self.reported_search = search.clone();
+            let (query, match_count) = search;
+            self.emit(ViewerEvent::SearchChanged { query, match_count });
+        }
+    }
 
-            // Rough image height estimation
-            // If the line contains an image link that we have loaded, add its height
-            if line.contains("![") && line.contains("](") {
-                // Try to extract path (very rough)
-                if let Some(start) = line.find("](")
-                    && let Some(end) = line[start..].find(')')
-                {
-                    let path = &line[start + 2..start + end];
-                    if let Some(height) = self.image_display_heights.get(path) {
-                        y += height + IMAGE_VERTICAL_PADDING;
-                    }
-                }
+    /// Rebuild the table of contents for the current content
+    ///
+    /// Plain-text content has no headings, so its TOC is empty. Also records
+    /// which transform passes failed on the new content. Collapsed entries
+    /// stay collapsed as long as the headings are unchanged.
+    fn rebuild_toc(&mut self) {
+        let headings = |toc: &TableOfContents| -> Vec<(String, u8)> {
+            toc.entries
+                .iter()
+                .map(|entry| (entry.text.clone(), entry.level))
+                .collect()
+        };
+        let old_headings = headings(&self.document().toc);
+        self.document_mut().toc = match self.document().render_mode {
+            RenderMode::Markdown => {
+                let arena = comrak::Arena::new();
+                let root = comrak::parse_document(
+                    &arena,
+                    self.document().shown_content(),
+                    &crate::internal::render::parse_options(),
+                );
+                self.transform_errors = transform::apply(
+                    &arena,
+                    root,
+                    &TransformContext {
+                        path: Some(&self.document().path),
+                    },
+                );
+                crate::internal::toc::TableOfContents::from_ast(root)
             }
+            RenderMode::Plain => {
+                self.transform_errors.clear();
+                crate::internal::toc::TableOfContents::new()
+            }
+        };
+        if headings(&self.document().toc) != old_headings {
+            self.toc_collapsed.clear();
+            self.toc_menu = None;
         }
+    }
 
-        y
+    /// Switch between markdown and plain-text rendering for the current file
+    pub fn toggle_render_mode(&mut self) {
+        self.document_mut().render_mode = match self.document().render_mode {
+            RenderMode::Markdown => RenderMode::Plain,
+            RenderMode::Plain => RenderMode::Markdown,
+        };
+        info!("Render mode: {:?}", self.document().render_mode);
+        self.content_revision += 1;
+        self.rebuild_toc();
+        self.recompute_max_scroll();
+        self.compute_toc_max_scroll();
     }
 
-    pub fn scroll_to_current_match(&mut self) {
-        if let Some(m) = self.search_state.as_ref().and_then(|s| s.current_match()) {
-            let y = self.calculate_y_for_offset(m.start);
-            // Center the match
-            let target_y = (y - self.viewport_height / 2.0).max(0.0);
-            self.scroll_state.scroll_y = target_y.min(self.scroll_state.max_scroll_y);
+    /// Reload the current file from disk, keeping the scroll position
+    ///
+    /// Clears the deleted banner on success, which also covers a deleted file
+    /// being recreated.
+    fn reload_from_disk(&mut self) {
+        // The filtered view shows the old content
+        self.clear_filter();
+        // Save current scroll position
+        let saved_scroll_y = self.document().scroll_state.scroll_y;
+
+        // Reload file content
+        match self.document().read_file() {
+            Ok(new_content) => {
+                let (new_content, text_format) = text_format::prepare(new_content);
+                self.document_mut().text_format = text_format;
+                let old_content = std::mem::replace(&mut self.document_mut().content, new_content);
+                self.document_mut().loaded_mtime = modified_time(&self.document().path);
+                self.changed_on_disk = false;
+                self.content_revision += 1;
+                self.follow_bookmarks(&old_content);
+                self.source_focus = None;
+                self.offer_line_remap(&old_content);
+
+                self.run_document_hooks();
+                self.rebuild_toc();
+
+                // Drop only images the new content no longer references
+                self.retain_referenced_images();
+                // Restore scroll position
+                self.document_mut().scroll_state.scroll_y = saved_scroll_y;
+                self.recompute_max_scroll();
+                self.compute_toc_max_scroll();
+                // Clear file deleted flag if it was set
+                self.file_deleted = false;
+                self.load_error = None;
+                info!("File reloaded successfully");
+            }
+            Err(e) => {
+                warn!("Failed to reload file: {:#}", e);
+                self.load_error = Some(format!("{:#}", e));
+            }
         }
     }
 
-    /// Calculate the Y position for a specific line number
-    pub fn calculate_y_for_line(&self, line_number: usize) -> f32 {
-        let (height, _, _) = self.calculate_smart_height(Some(line_number));
-        // Add top padding
-        height + 32.0 // CONTAINER_PADDING
+    /// Resolved keys of every image referenced by the current content
+    fn referenced_image_keys(&self) -> HashSet<String> {
+        inspect::inspect_markdown(&self.document().content, Some(&self.document().path))
+            .images
+            .iter()
+            .map(|image| {
+                self.image_cache
+                    .resolve(&image.url, Some(&self.document().path))
+            })
+            .collect()
     }
 
-    /// Parse a line number from input string
-    /// Returns None if the input is invalid (empty, non-numeric, zero, or negative)
-    pub fn parse_line_number(input: &str) -> Option<usize> {
-        input.trim().parse::<usize>().ok().filter(|&n| n > 0)
+    /// Keep cached images whose resolved paths are still referenced after a
+    /// reload, so unchanged images are not fetched again on every save
+    fn retain_referenced_images(&mut self) {
+        let keys = self.referenced_image_keys();
+        let removed = self.image_cache.retain_keys(&keys);
+        self.image_display_heights
+            .retain(|key, _| self.image_cache.contains_key(key));
+        debug!(
+            "Dropped {} stale images after reload ({} still referenced)",
+            removed,
+            keys.len()
+        );
     }
 
-    /// Validate that a line number is within bounds
-    /// Returns an error message if the line number is invalid
-    pub fn validate_line_number(&self, line_number: usize) -> Result<(), String> {
-        let total_lines = self.markdown_content.lines().count();
-        if line_number == 0 {
-            return Err("Line number must be greater than 0".to_string());
-        }
-        if line_number > total_lines {
-            return Err(format!(
-                "Line number {} exceeds total lines ({})",
-                line_number, total_lines
-            ));
-        }
-        Ok(())
+    /// Remember the top of the view as mark `mark`
+    pub fn set_mark(&mut self, mark: char) {
+        let mut position = self
+            .block_map
+            .position_at(self.document().scroll_state.scroll_y);
+        position.line = self.document().source_line(position.line);
+        debug!("Set mark '{}' at {:?}", mark, position);
+        self.document_mut().marks.insert(mark, position);
     }
 
-    /// Scroll to a specific line number
-    /// Centers the line in the viewport if possible
-    pub fn scroll_to_line(&mut self, line_number: usize) -> Result<(), String> {
-        self.validate_line_number(line_number)?;
-
-        let target_y = self.calculate_y_for_line(line_number - 1); // Convert to 0-based
-        // Center the line in the viewport
-        let centered_y = (target_y - self.viewport_height / 2.0).max(0.0);
-        // Directly set scroll_y for immediate scrolling (like scroll_to_top/bottom)
-        self.scroll_state.scroll_y = centered_y.min(self.scroll_state.max_scroll_y);
+    /// Set mark `mark` at the start of `line` (0-based)
+    pub fn set_mark_at(&mut self, mark: char, line: usize) {
+        let position = LinePosition {
+            line,
+            fraction: 0.0,
+        };
+        debug!("Set mark '{}' at {:?}", mark, position);
+        self.document_mut().marks.insert(mark, position);
+    }
 
-        Ok(())
+    /// Scroll to mark `mark`; returns false if it is not set
+    pub fn jump_to_mark(&mut self, mark: char) -> bool {
+        let Some(&position) = self.document().marks.get(&mark) else {
+            return false;
+        };
+        let y = self.block_map.y_for_position(LinePosition {
+            line: self.document().shown_line(position.line),
+            ..position
+        });
+        let scroll_state = &mut self.document_mut().scroll_state;
+        scroll_state.scroll_y = y;
+        scroll_state.reclamp();
+        debug!("Jumped to mark '{}' at {:?}", mark, position);
+        true
     }
 
-    /// Get the line number corresponding to the current scroll position
-    pub fn get_current_line_number(&self) -> usize {
-        let scroll_y = self.scroll_state.scroll_y;
-        let avg_line_height =
-            self.config.theme.base_text_size * self.config.theme.line_height_multiplier;
+    /// After a reload, move bookmarks to where their anchored text went
+    ///
+    /// Bookmarks whose text is gone keep their line and are marked stale.
+    fn follow_bookmarks(&mut self, old_content: &str) {
+        if self.document().bookmarks.is_empty() || old_content == self.document().content {
+            return;
+        }
+        let followed = bookmark_anchor::follow(
+            &self.document().bookmarks,
+            &self.document().bookmark_anchors,
+            &AnchorIndex::new(old_content),
+            &AnchorIndex::new(&self.document().content),
+        );
+        info!(
+            "Bookmarks followed their text: {} kept, {} stale",
+            followed.bookmarks.len(),
+            followed.stale.len()
+        );
+        let document = self.document_mut();
+        document.bookmarks = followed.bookmarks;
+        document.bookmark_anchors = followed.anchors;
+        document.stale_bookmarks = followed.stale;
+    }
 
-        // Simple estimation: scroll_y / avg_line_height
-        // This is rough but sufficient for bookmarks in this MVP
-        // A more accurate approach would reverse calculate_y_for_line
-        let line_index = (scroll_y / avg_line_height).floor() as usize;
-        let total_lines = self.markdown_content.lines().count();
-        (line_index + 1).min(total_lines).max(1)
+    /// Drop the anchor of a bookmark that was removed
+    pub fn forget_bookmark_anchor(&mut self, line: usize) {
+        let document = self.document_mut();
+        document.bookmark_anchors.remove(&line);
+        document.stale_bookmarks.remove(&line);
     }
 
-    /// Move focus to the next focusable element (Tab key)
-    pub fn focus_next(&mut self) {
-        if self.focusable_elements.is_empty() {
-            self.current_focus_index = None;
+    /// After a reload, offer to remap marks whose lines moved
+    ///
+    /// The offer is a toast with a Remap button, so it takes no keys meant
+    /// for the search box or other inputs; closing it keeps the lines.
+    fn offer_line_remap(&mut self, old_content: &str) {
+        if self.document().marks.is_empty() || old_content == self.document().content {
             return;
         }
 
-        self.current_focus_index = Some(match self.current_focus_index {
-            None => 0,
-            Some(idx) => {
-                match idx.checked_add(1) {
-                    Some(next) if next < self.focusable_elements.len() => next,
-                    _ => 0, // Wrap around to first element
-                }
-            }
+        let line_map = LineMap::between(old_content, &self.document().content);
+        let moved_marks =
+            line_map.moved_count(self.document().marks.values().map(|mark| mark.line + 1));
+
+        self.dismiss_line_remap();
+        if moved_marks == 0 {
+            return;
+        }
+        info!("External change moved {} marks", moved_marks);
+        self.remap_prompt = Some(RemapPrompt {
+            line_map,
+            moved_marks,
         });
-        debug!(
-            "Focus next: index {:?}/{}",
-            self.current_focus_index,
-            self.focusable_elements.len()
+        self.notifications.show_with_actions(
+            NotificationSource::Remap,
+            NotificationKind::Info,
+            line_map::remap_offer(moved_marks),
+            vec![NotificationAction::RemapMarks],
         );
     }
 
-    /// Move focus to the previous focusable element (Shift+Tab key)
-    pub fn focus_previous(&mut self) {
-        if self.focusable_elements.is_empty() {
-            self.current_focus_index = None;
+    /// Move marks to the lines their text moved to
+    pub fn apply_line_remap(&mut self) {
+        let Some(prompt) = self.remap_prompt.take() else {
             return;
+        };
+
+        for position in self.document_mut().marks.values_mut() {
+            position.line = prompt.line_map.remap(position.line + 1).saturating_sub(1);
         }
 
-        self.current_focus_index = Some(match self.current_focus_index {
-            None => self.focusable_elements.len() - 1,
-            Some(idx) => {
-                match idx {
-                    0 => self.focusable_elements.len() - 1, // Wrap around to last element
-                    _ => idx - 1,
-                }
-            }
-        });
-        debug!(
-            "Focus previous: index {:?}/{}",
-            self.current_focus_index,
-            self.focusable_elements.len()
-        );
+        info!("Remapped {} marks", prompt.moved_marks);
     }
 
-    /// Clear keyboard focus
-    pub fn clear_focus(&mut self) {
-        self.current_focus_index = None;
-        debug!("Cleared keyboard focus");
+    /// Re-read the current file from disk (Cmd+R), whether or not it is watched
+    ///
+    /// This is also Retry on the error page; a file that could be read again
+    /// is watched anew in case its disk went away and came back.
+    pub fn force_reload(&mut self) {
+        info!("Forced reload: {:?}", self.document().path);
+        let failed_before = self.load_error.is_some();
+        self.reload_from_disk();
+        if failed_before && self.load_error.is_none() {
+            self.rewatch_file();
+        }
     }
 
-    /// Activate the currently focused element (Enter key)
-    /// Returns true if an action was performed
-    pub fn activate_focused_element(&mut self) -> bool {
-        if let Some(idx) = self.current_focus_index
-            && let Some(element) = self.focusable_elements.get(idx).cloned()
+    /// Flag the file as changed on disk when it is not watched
+    ///
+    /// Without a watcher nothing reloads the file, so its modification time is
+    /// compared against the one it was loaded with, at most every
+    /// [`DISK_CHECK_INTERVAL`].
+    fn check_changed_on_disk(&mut self) {
+        if self.file_watcher.is_some() || self.changed_on_disk {
+            return;
+        }
+        let now = Instant::now();
+        if self
+            .last_disk_check
+            .is_some_and(|checked| now.duration_since(checked) < DISK_CHECK_INTERVAL)
         {
-            match element {
-                FocusableElement::Link(url) => {
-                    debug!("Activating focused link: {}", url);
-                    // Open URL in browser
-                    let url_clone = url.clone();
-                    std::thread::spawn(move || {
-                        if let Err(e) = crate::internal::rendering::open_url(&url_clone) {
-                            warn!("Failed to open URL '{}': {}", url_clone, e);
-                        }
-                    });
+            return;
+        }
+        self.last_disk_check = Some(now);
+
+        let current = modified_time(&self.document().path);
+        if current.is_some() && current != self.document().loaded_mtime {
+            debug!("File changed on disk: {:?}", self.document().path);
+            self.changed_on_disk = true;
+        }
+    }
+
+    /// Keep marks on their original line numbers
+    pub fn dismiss_line_remap(&mut self) {
+        self.remap_prompt = None;
+        self.notifications.dismiss(NotificationSource::Remap);
+    }
+
+    /// Keep showing the current file after it was renamed or moved to `to`
+    ///
+    /// The path is updated everywhere it is shown or remembered (window title,
+    /// tab, file queue, recent files) and the watcher moves to the new path.
+    /// For a file inside an archive, `to` is where the archive went.
+    fn follow_rename(&mut self, to: PathBuf) {
+        let from = self.document().path.clone();
+        let to = match ArchivePath::parse(&from.to_string_lossy()) {
+            Some(archive_path) => {
+                ArchivePath::new(canonical_path(&to), archive_path.entry).to_path_buf()
+            }
+            None => canonical_path(&to),
+        };
+        info!("File renamed: {:?} -> {:?}", from, to);
+
+        self.document_mut().path = to.clone();
+        self.file_queue.rename(&from, &to);
+        let mut recent_changed = false;
+        for recent in self.config.recent_files.iter_mut() {
+            if canonical_path(recent) == from {
+                *recent = to.clone();
+                recent_changed = true;
+            }
+        }
+        if recent_changed && let Err(e) = self.config.save_to_file("config.ron") {
+            warn!("Failed to save recent files to config: {}", e);
+        }
+
+        self.document_mut().render_mode =
+            render_mode_for_path(&to, &self.config.files.extension_modes);
+        self.rewatch_file();
+        // Pick up anything written together with the rename
+        self.reload_from_disk();
+    }
+
+    /// Point the file watcher at the current file, replacing any previous watch
+    fn rewatch_file(&mut self) {
+        self.file_deleted = false;
+        if !self.config.file_watcher.enabled {
+            return;
+        }
+
+        // Drop the old watcher first so it stops delivering events for the previous file
+        self.file_watcher = None;
+        self.file_watcher_rx = None;
+
+        let abs_path = canonical_path(&self.document().path);
+        match start_watching(&abs_path, self.config.file_watcher.debounce_ms) {
+            Ok((rx, debouncer)) => {
+                info!("File watcher moved to: {:?}", abs_path);
+                self.file_watcher_rx = Some(rx);
+                self.file_watcher = Some(debouncer);
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to watch {:?}: {:?}. Continuing without auto-reload.",
+                    abs_path, e
+                );
+            }
+        }
+    }
+
+    /// Open the alphabetically next (or previous) supported file in the current directory
+    pub fn open_sibling_file(&mut self, forward: bool, cx: &mut Context<Self>) {
+        match sibling_file(
+            &self.document().path,
+            &self.config.files.all_extensions(),
+            forward,
+        ) {
+            Some(path) => self.load_file(path, cx),
+            None => debug!(
+                "No {} file in directory of {:?}",
+                match forward {
+                    true => "next",
+                    false => "previous",
+                },
+                self.document().path
+            ),
+        }
+    }
+
+    /// Open the next file from the command-line queue, if any
+    pub fn open_next_queued_file(&mut self, cx: &mut Context<Self>) {
+        match self.file_queue.advance().map(Path::to_path_buf) {
+            Some(path) => self.load_file(path, cx),
+            None => debug!("Already at the last queued file"),
+        }
+    }
+
+    /// Open the previous file from the command-line queue, if any
+    pub fn open_previous_queued_file(&mut self, cx: &mut Context<Self>) {
+        match self.file_queue.go_back().map(Path::to_path_buf) {
+            Some(path) => self.load_file(path, cx),
+            None => debug!("Already at the first queued file"),
+        }
+    }
+
+    /// Open a linked document in the current tab, remembering where the
+    /// reader was so Cmd+Left can return there
+    pub fn follow_link(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let current = self.history_entry();
+        self.load_file(path, cx);
+        // On failure the current document is still shown
+        if self.document().path != current.path {
+            self.document_mut().history.push(current);
+        }
+    }
+
+    /// Go back to the document a link was followed from
+    pub fn navigate_back(&mut self, cx: &mut Context<Self>) {
+        let current = self.history_entry();
+        match self.document_mut().history.go_back(current) {
+            Some(entry) => self.open_history_entry(entry, cx),
+            None => debug!("No link to go back from"),
+        }
+    }
+
+    /// Retrace a link after going back
+    pub fn navigate_forward(&mut self, cx: &mut Context<Self>) {
+        let current = self.history_entry();
+        match self.document_mut().history.go_forward(current) {
+            Some(entry) => self.open_history_entry(entry, cx),
+            None => debug!("No link to go forward to"),
+        }
+    }
+
+    fn history_entry(&self) -> HistoryEntry {
+        HistoryEntry::new(
+            self.document().path.clone(),
+            self.document().scroll_state.scroll_y,
+        )
+    }
+
+    /// Reopen a history entry at the scroll position it was left at
+    ///
+    /// Entries for the current file (left by an `#anchor` jump) only scroll.
+    fn open_history_entry(&mut self, entry: HistoryEntry, cx: &mut Context<Self>) {
+        debug!("Navigating to {:?} at y={}", entry.path, entry.scroll_y);
+        if entry.path != self.document().path {
+            self.load_file(entry.path, cx);
+        }
+        self.document_mut().scroll_state.scroll_y = entry
+            .scroll_y
+            .min(self.document().scroll_state.max_scroll_y);
+    }
+
+    /// Scroll to the heading an in-page `#anchor` link points to, remembering
+    /// the current position so Cmd+Left can return to it
+    pub fn follow_anchor(&mut self, anchor: &str) {
+        let current = self.history_entry();
+        if self.scroll_to_anchor(anchor) {
+            self.document_mut().history.push(current);
+        }
+    }
+
+    /// Scroll to the heading with anchor `anchor`; returns false if none has it
+    pub fn scroll_to_anchor(&mut self, anchor: &str) -> bool {
+        match self.document().toc.line_for_anchor(anchor) {
+            Some(line) => {
+                debug!("Anchor {} is at line {}", anchor, line + 1);
+                self.scroll_to_line(self.document().source_line(line) + 1)
+                    .is_ok()
+            }
+            None => {
+                debug!("No heading for anchor {}", anchor);
+                false
+            }
+        }
+    }
+
+    /// Y scroll position of the line holding byte `target_offset`
+    pub fn calculate_y_for_offset(&self, target_offset: usize) -> f32 {
+        self.block_map
+            .y_for_offset(self.document().shown_content(), target_offset)
+    }
+
+    /// Keep scrolling while `key` is held (called for OS key repeats)
+    ///
+    /// The first repeat starts a frame timer that scrolls smoothly, with
+    /// acceleration, until [`Self::stop_held_scroll`] sees the key released.
+    pub fn start_held_scroll(&mut self, key: &str, direction: f32, cx: &mut Context<Self>) {
+        if !self.held_scroll.start(key, direction) {
+            return;
+        }
+        debug!("Continuous scrolling started for '{}'", key);
+        self.held_scroll_task = Some(cx.spawn(
+            async move |this: WeakEntity<MarkdownViewer>, cx: &mut AsyncApp| {
+                loop {
+                    cx.background_executor().timer(SCROLL_FRAME).await;
+                    let active = this
+                        .update(cx, |this, cx| this.advance_held_scroll(cx))
+                        .unwrap_or(false);
+                    if !active {
+                        break;
+                    }
+                }
+            },
+        ));
+    }
+
+    /// Run a toast button and close the toast it belongs to
+    pub fn run_notification_action(
+        &mut self,
+        source: NotificationSource,
+        action: &NotificationAction,
+        cx: &mut Context<Self>,
+    ) {
+        match action {
+            NotificationAction::Open(path) => {
+                info!("Opening {:?} with the system viewer", path);
+                cx.open_with_system(path);
+            }
+            NotificationAction::Reveal(path) => {
+                info!("Revealing {:?} in the file manager", path);
+                cx.reveal_path(path);
+            }
+            NotificationAction::RemapMarks => {
+                debug!("User accepted mark remap");
+                self.apply_line_remap();
+            }
+        }
+        self.notifications.dismiss(source);
+        cx.notify();
+    }
+
+    /// Start the toast timer while a timed toast is shown
+    ///
+    /// The timer closes expired toasts and redraws the progress bars of the
+    /// others, and stops once no timed toast is left.
+    fn run_notification_timer(&mut self, cx: &mut Context<Self>) {
+        if self.notification_task.is_some() || !self.notifications.has_timers() {
+            return;
+        }
+        self.notification_task = Some(cx.spawn(
+            async move |this: WeakEntity<MarkdownViewer>, cx: &mut AsyncApp| {
+                loop {
+                    cx.background_executor().timer(NOTIFICATION_TICK).await;
+                    let active = this
+                        .update(cx, |this, cx| {
+                            this.notifications.remove_expired(Instant::now());
+                            cx.notify();
+                            let active = this.notifications.has_timers();
+                            if !active {
+                                this.notification_task = None;
+                            }
+                            active
+                        })
+                        .unwrap_or(false);
+                    if !active {
+                        break;
+                    }
+                }
+            },
+        ));
+    }
+
+    /// Scroll to `y`, animated unless animation is turned off in the config
+    pub fn scroll_to_y(&mut self, y: f32) {
+        match self.config.animation.enabled {
+            true => self.document_mut().scroll_state.smooth_scroll_to(y),
+            false => {
+                let scroll_state = &mut self.document_mut().scroll_state;
+                scroll_state.scroll_y = y;
+                scroll_state.reclamp();
+            }
+        }
+    }
+
+    /// Scroll by `delta` pixels (negative scrolls up) from where scrolling
+    /// is heading, animated like [`Self::scroll_to_y`]
+    pub fn scroll_by(&mut self, delta: f32) {
+        self.scroll_to_y(self.document().scroll_state.destination() + delta);
+    }
+
+    /// Start the frame timer while a smooth scroll is under way
+    ///
+    /// The timer steps the animation each frame and stops once the target
+    /// is reached or direct scrolling took over.
+    fn run_scroll_animation(&mut self, cx: &mut Context<Self>) {
+        if self.scroll_animation_task.is_some() || !self.document().scroll_state.is_animating() {
+            return;
+        }
+        self.scroll_animation_task = Some(cx.spawn(
+            async move |this: WeakEntity<MarkdownViewer>, cx: &mut AsyncApp| {
+                loop {
+                    cx.background_executor().timer(SCROLL_FRAME).await;
+                    let active = this
+                        .update(cx, |this, cx| {
+                            let duration = this.config.animation.duration_ms as f32 / 1000.0;
+                            let easing = this.config.animation.easing;
+                            let active = this.document_mut().scroll_state.update_smooth_scroll(
+                                SCROLL_FRAME.as_secs_f32(),
+                                duration,
+                                easing,
+                            );
+                            cx.notify();
+                            if !active {
+                                this.scroll_animation_task = None;
+                            }
+                            active
+                        })
+                        .unwrap_or(false);
+                    if !active {
+                        break;
+                    }
+                }
+            },
+        ));
+    }
+
+    /// Stop continuous scrolling when `key` is released
+    pub fn stop_held_scroll(&mut self, key: &str) {
+        self.held_scroll.release(key);
+        if !self.held_scroll.is_active() && self.held_scroll_task.take().is_some() {
+            debug!("Continuous scrolling stopped for '{}'", key);
+        }
+    }
+
+    /// Scroll one frame of held-key scrolling; returns whether it continues
+    fn advance_held_scroll(&mut self, cx: &mut Context<Self>) -> bool {
+        let delta = self.held_scroll.advance(
+            SCROLL_FRAME.as_secs_f32(),
+            self.config.scroll.key_hold_speed,
+            self.config.scroll.key_hold_acceleration,
+        );
+        match delta > 0.0 {
+            true => self.document_mut().scroll_state.scroll_down(delta),
+            false => self.document_mut().scroll_state.scroll_up(-delta),
+        }
+        cx.notify();
+        self.held_scroll.is_active()
+    }
+
+    /// Scroll so the current match's line sits in the middle of the viewport
+    pub fn scroll_to_current_match(&mut self) {
+        if let Some(m) = self
+            .document()
+            .search_state
+            .as_ref()
+            .and_then(|s| s.current_match())
+        {
+            let line_top = self.calculate_y_for_offset(m.start);
+            let line_height =
+                self.config.theme.base_text_size * self.config.theme.line_height_multiplier;
+            // Center the match
+            let target_y = (line_top + line_height / 2.0 - self.viewport_height / 2.0).max(0.0);
+            self.scroll_to_y(target_y);
+        }
+    }
+
+    /// Bookmark every line holding a search match (Cmd+Shift+D)
+    ///
+    /// Lines already bookmarked are kept once. Returns how many were added.
+    pub fn bookmark_all_matches(&mut self) -> usize {
+        let Some(search_state) = self.document().search_state.as_ref() else {
+            return 0;
+        };
+        let lines: Vec<usize> = search_state
+            .matched_lines(self.document().shown_content())
+            .into_iter()
+            .map(|line| self.document().source_line(line - 1) + 1)
+            .collect();
+        let bookmarks = &mut self.document_mut().bookmarks;
+        let before = bookmarks.len();
+        bookmarks.extend(lines);
+        bookmarks.sort();
+        bookmarks.dedup();
+        let added = bookmarks.len() - before;
+        info!("Bookmarked {} lines with matches", added);
+        added
+    }
+
+    /// Make search match `index` (0-based) current and scroll to it
+    pub fn jump_to_match(&mut self, index: usize) {
+        if let Some(search_state) = self.document_mut().search_state.as_mut() {
+            search_state.select_match(index);
+            self.scroll_to_current_match();
+        }
+    }
+
+    /// Rebuild line and block positions after the content or layout changed
+    ///
+    /// The measured layout of the current content is used once there is one.
+    fn rebuild_block_map(&mut self) {
+        if let Some(layout) = self
+            .measured_layout
+            .as_ref()
+            .filter(|layout| layout.revision == self.content_revision)
+        {
+            self.block_map = layout.block_map(
+                self.document().shown_content().lines().count(),
+                CONTAINER_PADDING,
+            );
+            return;
+        }
+        let mut line_tops = Vec::new();
+        let content_height = self.estimate_content_height(Some(&mut line_tops));
+        self.block_map = match self.document().render_mode {
+            RenderMode::Markdown => BlockMap::from_line_ranges(
+                self.parsed_document().root.block_ranges(),
+                &line_tops,
+                content_height,
+                CONTAINER_PADDING,
+            ),
+            RenderMode::Plain => {
+                BlockMap::from_lines(&line_tops, content_height, CONTAINER_PADDING)
+            }
+        };
+    }
+
+    /// Scroll one page down, snapping to the block cut off at the bottom
+    ///
+    /// `scroll.page_overlap_lines` lines of the previous page stay visible.
+    pub fn page_down(&mut self) {
+        let (overlap, fallback) = self.page_overlap_and_fallback();
+        let target = self.block_map.page_down_target(
+            self.document().scroll_state.destination(),
+            self.viewport_height,
+            overlap,
+            fallback,
+        );
+        self.scroll_to_y(target);
+    }
+
+    /// Scroll one page up, starting the new page at a block boundary
+    pub fn page_up(&mut self) {
+        let (overlap, fallback) = self.page_overlap_and_fallback();
+        let target = self.block_map.page_up_target(
+            self.document().scroll_state.destination(),
+            self.viewport_height,
+            overlap,
+            fallback,
+        );
+        self.scroll_to_y(target);
+    }
+
+    /// Page overlap in pixels, and the plain page step used for blocks taller
+    /// than the viewport
+    fn page_overlap_and_fallback(&self) -> (f32, f32) {
+        let avg_line_height =
+            self.config.theme.base_text_size * self.config.theme.line_height_multiplier;
+        (
+            self.config.scroll.page_overlap_lines as f32 * avg_line_height,
+            self.viewport_height * self.config.scroll.page_scroll_percentage,
+        )
+    }
+
+    /// Calculate the Y position for a specific line number (0-based)
+    pub fn calculate_y_for_line(&self, line_number: usize) -> f32 {
+        self.block_map.y_for_line(line_number)
+    }
+
+    /// Parse a line number from input string
+    /// Returns None if the input is invalid (empty, non-numeric, zero, or negative)
+    pub fn parse_line_number(input: &str) -> Option<usize> {
+        input.trim().parse::<usize>().ok().filter(|&n| n > 0)
+    }
+
+    /// Parse a percentage (0-100) from the jump-to-percent prompt
+    pub fn parse_percent(input: &str) -> Option<usize> {
+        input
+            .trim()
+            .trim_end_matches('%')
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n <= 100)
+    }
+
+    /// Open the go-to-line prompt, for a line or a percentage of the document
+    ///
+    /// The bookmarks overlay closes, since the prompt takes its keys.
+    pub fn open_goto_prompt(&mut self, percent: bool) {
+        self.show_goto_line = true;
+        self.show_bookmarks = false;
+        self.goto_percent = percent;
+        self.goto_line_input.clear();
+    }
+
+    /// Scroll so the status bar shows `percent`, i.e. the line that far into
+    /// the document is at the top
+    pub fn scroll_to_percent(&mut self, percent: usize) {
+        let total_lines = self.document().shown_content().lines().count().max(1);
+        let line = (total_lines * percent.min(100) / 100).max(1);
+        let target_y = self.calculate_y_for_line(line - 1);
+        self.document_mut().scroll_state.scroll_y =
+            target_y.min(self.document().scroll_state.max_scroll_y);
+    }
+
+    /// Open the About overlay with fresh diagnostics, or close it
+    pub fn toggle_about(&mut self) {
+        self.about = match self.about {
+            Some(_) => None,
+            None => Some(Diagnostics::collect(
+                &self.config.theme.theme,
+                crate::internal::theme::registry().list_names().len(),
+                Path::new("config.ron"),
+            )),
+        };
+    }
+
+    /// Open the image report with the images that failed to load so far
+    /// or have no alt text, or close it
+    pub fn toggle_image_report(&mut self) {
+        self.image_report = match self.image_report {
+            Some(_) => None,
+            None => {
+                let summary = inspect::inspect_markdown(
+                    &self.document().content,
+                    Some(&self.document().path),
+                );
+                Some(image_issues(&summary.images, |url| {
+                    let key = self.image_cache.resolve(url, Some(&self.document().path));
+                    self.image_cache.failed(&key)
+                }))
+            }
+        };
+    }
+
+    /// Jump to the image report entry on `line_number` and close the report
+    pub fn open_image_issue(&mut self, line_number: usize) {
+        debug!("Jumping to image on line {}", line_number);
+        let _ = self.scroll_to_line(line_number);
+        self.image_report = None;
+    }
+
+    /// Copy the About overlay's diagnostics to the clipboard for a bug report
+    pub fn copy_diagnostics(&mut self, cx: &mut Context<Self>) {
+        let Some(about) = self.about.as_ref() else {
+            return;
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(about.report()));
+        self.notifications.show(
+            NotificationSource::Message,
+            NotificationKind::Info,
+            "Copied diagnostics",
+        );
+        cx.notify();
+    }
+
+    /// Copy the full path of the current file to the clipboard
+    pub fn copy_file_path(&mut self, cx: &mut Context<Self>) {
+        let path = self.document().path.to_string_lossy().to_string();
+        cx.write_to_clipboard(ClipboardItem::new_string(path.clone()));
+        self.notifications.show(
+            NotificationSource::Message,
+            NotificationKind::Info,
+            format!("Copied {}", path),
+        );
+        cx.notify();
+    }
+
+    /// Validate that a line number is within bounds
+    /// Returns an error message if the line number is invalid
+    pub fn validate_line_number(&self, line_number: usize) -> Result<(), String> {
+        let total_lines = self.document().content.lines().count();
+        if line_number == 0 {
+            return Err("Line number must be greater than 0".to_string());
+        }
+        if line_number > total_lines {
+            return Err(format!(
+                "Line number {} exceeds total lines ({})",
+                line_number, total_lines
+            ));
+        }
+        Ok(())
+    }
+
+    /// Scroll to a specific line number of the document
+    /// Centers the line in the viewport if possible
+    pub fn scroll_to_line(&mut self, line_number: usize) -> Result<(), String> {
+        self.validate_line_number(line_number)?;
+
+        // Convert to 0-based, then to the line shown for it in the filter view
+        let target_y = self.calculate_y_for_line(self.document().shown_line(line_number - 1));
+        // Center the line in the viewport
+        let centered_y = (target_y - self.viewport_height / 2.0).max(0.0);
+        // Directly set scroll_y for immediate scrolling (like scroll_to_top/bottom)
+        self.document_mut().scroll_state.scroll_y =
+            centered_y.min(self.document().scroll_state.max_scroll_y);
+
+        Ok(())
+    }
+
+    /// Scroll to the first heading matching `heading`
+    ///
+    /// Matches the heading text (case-insensitive) or its anchor slug, with or
+    /// without a leading `#`.
+    pub fn scroll_to_heading(&mut self, heading: &str) -> Result<(), String> {
+        let summary =
+            inspect::inspect_markdown(&self.document().content, Some(&self.document().path));
+        let line = inspect::find_heading(&summary.headings, heading)
+            .map(|info| info.line)
+            .ok_or_else(|| format!("No heading matches '{}'", heading))?;
+        self.scroll_to_line(line)
+    }
+
+    /// Search for `query` and scroll to the first match
+    ///
+    /// Opens the search overlay like typing the query would; an empty query
+    /// closes it.
+    pub fn set_search(&mut self, query: &str) {
+        self.search_history_index = None;
+        match query.is_empty() {
+            true => {
+                self.clear_filter();
+                let document = self.document_mut();
+                document.search_state = None;
+                document.search_input.clear();
+            }
+            false => {
+                self.document_mut().search_input = query.to_string();
+                self.search_highlights_kept = false;
+                self.update_search();
+            }
+        }
+    }
+
+    /// Whether the search bar is open and takes typed keys
+    pub fn search_input_active(&self) -> bool {
+        self.document().search_state.is_some() && !self.search_highlights_kept
+    }
+
+    /// Step to the next (or, with `forward` false, previous) search match
+    /// and scroll to it
+    pub fn step_search_match(&mut self, forward: bool) {
+        if let Some(state) = &mut self.document_mut().search_state {
+            match forward {
+                true => state.next_match(),
+                false => state.prev_match(),
+            }
+            debug!("Search match: {:?}", state.current_match_number());
+            self.scroll_to_current_match();
+        }
+    }
+
+    /// Search the document for `search_input` and scroll to the first match
+    ///
+    /// While filtering, the filtered view is rebuilt for the new query first.
+    pub fn update_search(&mut self) {
+        if let Some(view) = self.document().filter.as_ref() {
+            let context = view.context;
+            self.apply_filter(context);
+            return;
+        }
+        self.document_mut().search_state = Some(self.indexed_search());
+        self.scroll_to_current_match();
+    }
+
+    /// Search the active document for its search input, reusing the index
+    /// while the content is unchanged
+    fn indexed_search(&mut self) -> SearchState {
+        let document = &self.tabs[self.active_tab];
+        self.search_index
+            .prepare(document.shown_content(), self.content_revision);
+        SearchState::indexed(
+            document.search_input.clone(),
+            document.shown_content(),
+            self.search_mode,
+            &mut self.search_index,
+        )
+    }
+
+    /// Toggle `mode` for the search overlay and search again (Alt+R/W/C)
+    ///
+    /// Toggling the active mode goes back to plain search.
+    pub fn toggle_search_mode(&mut self, mode: SearchMode) {
+        self.search_mode = self.search_mode.toggle(mode);
+        info!("Search mode: {}", self.search_mode.label());
+        self.update_search();
+    }
+
+    /// Show only blocks with search matches, or toggle one block of context
+    /// when already filtering (Cmd+Shift+F)
+    pub fn toggle_filter(&mut self) {
+        let context = match &self.document().filter {
+            Some(view) => 1 - view.context.min(1),
+            None => 0,
+        };
+        self.apply_filter(context);
+    }
+
+    /// Filter the full document for the current query with `context` blocks
+    /// around each match
+    fn apply_filter(&mut self, context: usize) {
+        let (filtered, source_lines) = filter::filter_content(
+            &self.document().content,
+            &self.document().search_input,
+            self.search_mode,
+            context,
+        );
+        info!(
+            "Filtering for '{}' with {} block(s) of context",
+            self.document().search_input,
+            context
+        );
+        self.document_mut().filter = Some(FilterView {
+            content: filtered,
+            context,
+            source_lines,
+        });
+        self.content_revision += 1;
+        self.document_mut().search_state = Some(self.indexed_search());
+        self.rebuild_toc();
+        self.recompute_max_scroll();
+        self.compute_toc_max_scroll();
+        self.document_mut().scroll_state.scroll_y = 0.0;
+        self.scroll_to_current_match();
+    }
+
+    /// Restore the full document, keeping the current match and position
+    ///
+    /// Returns false when no filter was active.
+    pub fn clear_filter(&mut self) -> bool {
+        let Some(view) = self.document_mut().filter.take() else {
+            return false;
+        };
+        let current_line = self
+            .block_map
+            .line_at_y(self.document().scroll_state.scroll_y);
+        let current_match = self
+            .document()
+            .search_state
+            .as_ref()
+            .and_then(|state| state.current_match_number());
+
+        let source_line = view.source_line(current_line);
+
+        self.content_revision += 1;
+        self.rebuild_toc();
+        self.recompute_max_scroll();
+        self.compute_toc_max_scroll();
+        self.document_mut().scroll_state.scroll_y = self
+            .calculate_y_for_line(source_line)
+            .min(self.document().scroll_state.max_scroll_y);
+        if self.document().search_state.is_some() {
+            let mut state = self.indexed_search();
+            if let Some(number) = current_match {
+                state.select_match(number - 1);
+            }
+            self.document_mut().search_state = Some(state);
+        }
+        info!("Filter cleared");
+        true
+    }
+
+    /// Switch offline mode for remote images on or off
+    ///
+    /// Going online retries the images that failed, e.g. while the network
+    /// was down.
+    pub fn toggle_offline(&mut self) {
+        let offline = !self.network.is_offline();
+        self.network.set_offline(offline);
+        let message = match offline {
+            true => "Offline mode: remote images are not loaded",
+            false => {
+                self.image_cache.forget_failed();
+                "Online: loading remote images"
+            }
+        };
+        self.notifications
+            .show(NotificationSource::Message, NotificationKind::Info, message);
+    }
+
+    /// Show or hide the table of contents sidebar
+    pub fn toggle_toc(&mut self) {
+        self.show_toc = !self.show_toc;
+        self.recompute_max_scroll();
+    }
+
+    /// Show the file tree sidebar with keyboard focus, or hide it
+    /// (Cmd+Shift+Z)
+    ///
+    /// The tree lists the directory the viewer was launched on; without one
+    /// there is nothing to show.
+    pub fn toggle_file_tree(&mut self) {
+        match self.show_file_tree {
+            true => {
+                self.show_file_tree = false;
+                if let Some(tree) = self.file_tree.as_mut() {
+                    tree.focused = false;
+                }
+            }
+            false => {
+                let Some(root) = self.project_root().map(Path::to_path_buf) else {
+                    self.notifications.show(
+                        NotificationSource::Message,
+                        NotificationKind::Info,
+                        "The file tree is available when a folder is opened",
+                    );
+                    return;
+                };
+                let extensions = self.config.files.all_extensions();
+                let path = self.document().path.clone();
+                let tree_height = rows_height(self.viewport_height);
+                let tree = self
+                    .file_tree
+                    .get_or_insert_with(|| FileTree::scan(root, &extensions));
+                tree.focused = true;
+                tree.reveal(&path, tree_height);
+                self.show_file_tree = true;
+            }
+        }
+        self.recompute_max_scroll();
+    }
+
+    /// Show or hide the raw source pane left of the rendered document
+    pub fn toggle_source_view(&mut self) {
+        self.show_source = !self.show_source;
+        self.source_focus = None;
+        info!("Source view toggled: {}", self.show_source);
+        self.recompute_max_scroll();
+    }
+
+    /// Scroll offset of the source pane, following the rendered pane
+    pub fn source_scroll_y(&self) -> f32 {
+        source_view::source_scroll_for(&self.block_map, self.document().scroll_state.scroll_y)
+    }
+
+    /// Scroll the source pane by `delta` pixels (positive scrolls down),
+    /// moving the rendered pane to the same source line
+    pub fn scroll_source_by(&mut self, delta: f32) {
+        let target = source_view::rendered_scroll_for(
+            &self.block_map,
+            (self.source_scroll_y() + delta).max(0.0),
+        );
+        self.document_mut().scroll_state.scroll_y =
+            target.clamp(0.0, self.document().scroll_state.max_scroll_y);
+    }
+
+    /// Show the source of the block at window position (`x`, `y`) (Alt+click)
+    ///
+    /// Opens the source view if needed and highlights the block's lines
+    /// there, scrolling only when they are outside the source pane. Returns
+    /// false for a point outside the rendered document.
+    pub fn jump_to_source_at(&mut self, x: f32, y: f32) -> bool {
+        if x < self.file_tree_width() + self.source_pane_width() {
+            return false;
+        }
+        // Block positions include the container padding, the content only
+        // its top half
+        let y = self.document().scroll_state.scroll_y + y + CONTAINER_PADDING / 2.0;
+        let line = self.block_map.line_at_y(y);
+        let lines = self
+            .block_map
+            .blocks()
+            .iter()
+            .find(|block| block.top <= y && y < block.bottom)
+            .map(|block| block.start_line..block.end_line + 1)
+            .unwrap_or(line..line + 1);
+        info!("Showing source lines {}-{}", lines.start + 1, lines.end);
+
+        if !self.show_source {
+            self.toggle_source_view();
+        }
+        let total = self.document().shown_content().lines().count();
+        let visible =
+            source_view::visible_lines(self.source_scroll_y(), self.viewport_height, total);
+        if !visible.contains(&lines.start) {
+            let target =
+                (lines.start as f32 * SOURCE_LINE_HEIGHT - self.viewport_height / 3.0).max(0.0);
+            self.document_mut().scroll_state.scroll_y =
+                source_view::rendered_scroll_for(&self.block_map, target)
+                    .clamp(0.0, self.document().scroll_state.max_scroll_y);
+        }
+        self.source_focus = Some(lines);
+        true
+    }
+
+    /// Open the file chosen in the file tree, keeping the tree focused
+    pub fn open_tree_file(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        debug!("Opening {:?} from the file tree", path);
+        self.load_file(path, cx);
+        if let Some(tree) = self.file_tree.as_mut() {
+            tree.focused = true;
+        }
+    }
+
+    /// Get the document line number at the current scroll position
+    pub fn get_current_line_number(&self) -> usize {
+        let line_index = self.document().source_line(
+            self.block_map
+                .line_at_y(self.document().scroll_state.scroll_y),
+        );
+        let total_lines = self.document().content.lines().count();
+        (line_index + 1).min(total_lines).max(1)
+    }
+
+    /// Move focus to the next focusable element (Tab key)
+    pub fn focus_next(&mut self) {
+        if self.focusable_elements.is_empty() {
+            self.current_focus_index = None;
+            return;
+        }
+
+        self.current_focus_index = Some(match self.current_focus_index {
+            None => 0,
+            Some(idx) => {
+                match idx.checked_add(1) {
+                    Some(next) if next < self.focusable_elements.len() => next,
+                    _ => 0, // Wrap around to first element
+                }
+            }
+        });
+        debug!(
+            "Focus next: index {:?}/{}",
+            self.current_focus_index,
+            self.focusable_elements.len()
+        );
+    }
+
+    /// Move focus to the previous focusable element (Shift+Tab key)
+    pub fn focus_previous(&mut self) {
+        if self.focusable_elements.is_empty() {
+            self.current_focus_index = None;
+            return;
+        }
+
+        self.current_focus_index = Some(match self.current_focus_index {
+            None => self.focusable_elements.len() - 1,
+            Some(idx) => {
+                match idx {
+                    0 => self.focusable_elements.len() - 1, // Wrap around to last element
+                    _ => idx - 1,
+                }
+            }
+        });
+        debug!(
+            "Focus previous: index {:?}/{}",
+            self.current_focus_index,
+            self.focusable_elements.len()
+        );
+    }
+
+    /// Show or hide the bookmarks overlay, selecting the first bookmark
+    pub fn toggle_bookmarks(&mut self) {
+        self.show_bookmarks = !self.show_bookmarks;
+        self.bookmark_list = BookmarkListState::default();
+    }
+
+    /// Jump to the selected bookmark and close the overlay
+    pub fn open_selected_bookmark(&mut self) {
+        if let Some(&line_number) = self.document().bookmarks.get(self.bookmark_list.selected) {
+            debug!("Jumping to selected bookmark: line {}", line_number);
+            let _ = self.scroll_to_line(line_number);
+            self.show_bookmarks = false;
+        }
+    }
+
+    /// Remove the selected bookmark from the overlay
+    pub fn remove_selected_bookmark(&mut self) {
+        let selected = self.bookmark_list.selected;
+        if selected < self.document().bookmarks.len() {
+            let line_number = self.document_mut().bookmarks.remove(selected);
+            self.forget_bookmark_anchor(line_number);
+            debug!("Removed bookmark at line {}", line_number);
+            self.bookmark_list
+                .clamp(self.document().bookmarks.len(), self.viewport_height);
+        }
+    }
+
+    /// Get the currently focused element, if any
+    pub fn focused_element(&self) -> Option<&FocusableElement> {
+        self.current_focus_index
+            .and_then(|idx| self.focusable_elements.get(idx))
+    }
+
+    /// Rebuild the focusable element list when the document or bookmarks overlay changed
+    ///
+    /// The list is kept across renders so `current_focus_index` stays meaningful.
+    /// When it is rebuilt, focus follows the previously focused element if it still exists.
+    pub fn refresh_focusable_elements(&mut self) {
+        let key = (
+            self.content_revision,
+            self.show_bookmarks
+                .then(|| self.document().bookmarks.clone()),
+        );
+        if self.focus_list_key.as_ref() == Some(&key) {
+            return;
+        }
+
+        // Plain text has no links to focus
+        let content = match self.document().render_mode {
+            RenderMode::Markdown => self.document().shown_content(),
+            RenderMode::Plain => "",
+        };
+        let elements = focus::build_focusable_elements(content, key.1.as_deref());
+        self.current_focus_index = focus::remap_focus_index(
+            &self.focusable_elements,
+            self.current_focus_index,
+            &elements,
+        );
+        debug!(
+            "Rebuilt focusable elements: {} entries, focus {:?}",
+            elements.len(),
+            self.current_focus_index
+        );
+        self.focusable_elements = elements;
+        self.focus_list_key = Some(key);
+    }
+
+    /// Clear keyboard focus
+    pub fn clear_focus(&mut self) {
+        self.current_focus_index = None;
+        debug!("Cleared keyboard focus");
+    }
+
+    /// Activate the currently focused element (Enter key)
+    /// Returns true if an action was performed
+    pub fn activate_focused_element(&mut self, cx: &mut Context<Self>) -> bool {
+        if let Some(idx) = self.current_focus_index
+            && let Some(element) = self.focusable_elements.get(idx).cloned()
+        {
+            match element {
+                FocusableElement::Link(url) => {
+                    debug!("Activating focused link: {}", url);
+                    self.open_link(&url, cx);
                     return true;
                 }
                 FocusableElement::TocItem(line_number) => {
                     debug!("Activating focused TOC item: line {}", line_number);
                     // Navigate to the line
                     let target_y = self.calculate_y_for_line(line_number);
-                    self.scroll_state.scroll_y = target_y.min(self.scroll_state.max_scroll_y);
+                    self.document_mut().scroll_state.scroll_y =
+                        target_y.min(self.document().scroll_state.max_scroll_y);
                     return true;
                 }
                 FocusableElement::TocToggleButton => {
                     debug!("Activating TOC toggle button");
-                    self.show_toc = !self.show_toc;
-                    self.recompute_max_scroll();
+                    self.toggle_toc();
                     return true;
                 }
                 FocusableElement::CopyButton(code) => {
@@ -615,45 +2369,553 @@ impl MarkdownViewer {
                 }
             }
         }
-        false
+        false
+    }
+
+    /// Export the document as `format` to `path` and set the notification
+    ///
+    /// Successful exports are recorded in the export history so they can be
+    /// repeated with [`Self::reexport`].
+    pub fn export_to(&mut self, format: ExportFormat, path: &Path) {
+        debug!(
+            "{} export triggered, output path: {:?}",
+            format.label(),
+            path
+        );
+
+        let result = self.write_export(format, &self.document().content, path);
+        self.notify_export(format, path, &result);
+        if result.is_ok() {
+            self.record_export(format, path);
+        }
+        self.emit(ViewerEvent::ExportFinished {
+            path: path.to_path_buf(),
+            result: result.map_err(|e| e.to_string()),
+        });
+    }
+
+    /// Write `content`, a part of the current document or all of it, as
+    /// `format` to `path`
+    fn write_export(&self, format: ExportFormat, content: &str, path: &Path) -> anyhow::Result<()> {
+        match format {
+            ExportFormat::Pdf => {
+                crate::internal::pdf_export::export_to_pdf(content, path, &self.config.pdf_export)
+            }
+            ExportFormat::Html => crate::internal::html_export::export_to_html(
+                content,
+                &self.document().path,
+                path,
+                &self.active_theme,
+                &self.config.html_export,
+            ),
+            ExportFormat::Epub => crate::internal::epub_export::export_to_epub(
+                content,
+                &self.document().path,
+                path,
+                &self.active_theme,
+            ),
+        }
+    }
+
+    /// Show how an export to `path` went
+    fn notify_export(&mut self, format: ExportFormat, path: &Path, result: &anyhow::Result<()>) {
+        match result {
+            Ok(()) => {
+                info!("Successfully exported {} to {:?}", format.label(), path);
+                let filename = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default();
+                self.notifications.show_with_actions(
+                    NotificationSource::Export,
+                    NotificationKind::Success,
+                    format!("{} exported: {}", format.label(), filename),
+                    NotificationAction::for_file(path),
+                );
+            }
+            Err(e) => {
+                warn!("Failed to export {}: {}", format.label(), e);
+                self.notifications.show(
+                    NotificationSource::Export,
+                    NotificationKind::Error,
+                    format!("{} export failed: {}", format.label(), e),
+                );
+            }
+        }
+    }
+
+    /// Export the document to an HTML file next to it
+    ///
+    /// An existing HTML file is overwritten; it is derived output like a
+    /// snapshot, unlike the PDF which may have been edited separately.
+    pub fn export_html(&mut self) {
+        let html_path = self.document().path.with_extension("html");
+        self.export_to(ExportFormat::Html, &html_path);
+    }
+
+    /// Export the section of TOC entry `index` to `<file>-<anchor>.html`
+    /// next to the document
+    ///
+    /// Section exports are not added to the export history, which repeats
+    /// whole-document exports.
+    pub fn export_section(&mut self, index: usize) {
+        let document = self.document();
+        let Some(entry) = document.toc.entries.get(index) else {
+            return;
+        };
+        let stem = document
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let path = document
+            .path
+            .with_file_name(format!("{}-{}.html", stem, entry.anchor));
+        // The TOC follows the shown content; the section is exported from the
+        // full document
+        let shown_total = document.shown_content().lines().count();
+        let shown = document.toc.section_lines(index, shown_total);
+        let lines = document.source_line(shown.start)..match shown.end < shown_total {
+            true => document.source_line(shown.end),
+            false => document.content.lines().count(),
+        };
+        let section = document
+            .content
+            .lines()
+            .skip(lines.start)
+            .take(lines.len())
+            .collect::<Vec<_>>()
+            .join("\n");
+        debug!("Section export of lines {:?} to {:?}", lines, path);
+        let result = self.write_export(ExportFormat::Html, &section, &path);
+        self.notify_export(ExportFormat::Html, &path, &result);
+    }
+
+    /// Open the right-click menu of TOC entry `entry` at window height `y`
+    pub fn open_toc_menu(&mut self, entry: usize, y: f32) {
+        debug!("TOC menu opened on entry {}", entry);
+        self.toc_menu = Some(TocMenu { entry, y });
+    }
+
+    /// Carry out `action` from the TOC menu and close it
+    pub fn run_toc_menu_action(&mut self, action: TocMenuAction, cx: &mut Context<Self>) {
+        let Some(menu) = self.toc_menu.take() else {
+            return;
+        };
+        let Some(entry) = self.document().toc.entries.get(menu.entry).cloned() else {
+            return;
+        };
+        debug!("TOC menu: {:?} on {:?}", action, entry.text);
+        match action {
+            TocMenuAction::CopyLink => {
+                let link = format!("[{}](#{})", entry.text, entry.anchor);
+                cx.write_to_clipboard(ClipboardItem::new_string(link.clone()));
+                self.notifications.show(
+                    NotificationSource::Message,
+                    NotificationKind::Info,
+                    format!("Copied {}", link),
+                );
+            }
+            TocMenuAction::ExportSection => self.export_section(menu.entry),
+            TocMenuAction::CollapseOthers => {
+                self.toc_collapsed = self.document().toc.collapse_others(menu.entry);
+                self.compute_toc_max_scroll();
+            }
+            TocMenuAction::ExpandAll => {
+                self.toc_collapsed.clear();
+                self.compute_toc_max_scroll();
+            }
+            TocMenuAction::SetMark => {
+                self.mark_mode = Some(MarkMode::SetAt(entry.line_number));
+                self.notifications.show(
+                    NotificationSource::Message,
+                    NotificationKind::Info,
+                    format!("Press a key to mark \"{}\"", entry.text),
+                );
+            }
+        }
+        cx.notify();
+    }
+
+    /// Export the document to an EPUB book next to it
+    pub fn export_epub(&mut self) {
+        let epub_path = self.document().path.with_extension("epub");
+        self.export_to(ExportFormat::Epub, &epub_path);
+    }
+
+    /// Close the confirmation dialog once one of its buttons was pressed,
+    /// carrying out the action when it was confirmed
+    pub fn finish_dialog(&mut self, outcome: DialogOutcome, cx: &mut Context<Self>) {
+        match outcome {
+            DialogOutcome::Pending => {}
+            DialogOutcome::Cancelled => {
+                debug!("Confirmation dialog cancelled");
+                self.confirm_dialog = None;
+            }
+            DialogOutcome::Confirmed(action) => {
+                self.confirm_dialog = None;
+                match action {
+                    DialogAction::OverwritePdf(path) => {
+                        debug!("User confirmed PDF overwrite");
+                        self.export_to(ExportFormat::Pdf, &path);
+                    }
+                    DialogAction::OpenAnyway { path, new_tab } => {
+                        self.confirm_pending_open(path, new_tab, cx)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remember an export in the config's export history
+    fn record_export(&mut self, format: ExportFormat, destination: &Path) {
+        let document = self.document().path.to_string_lossy().to_string();
+        record_export(
+            &mut self.config.export_history,
+            ExportRecord {
+                document,
+                format,
+                destination: destination.to_string_lossy().to_string(),
+            },
+        );
+        if let Err(e) = self.config.save_to_file("config.ron") {
+            warn!("Failed to save export history to config: {}", e);
+        }
+    }
+
+    /// Repeat the document's latest export to the same file (Cmd+Shift+R)
+    ///
+    /// The destination was confirmed when it was first written, so an
+    /// existing file is overwritten without asking again.
+    pub fn reexport(&mut self) {
+        match last_export(&self.config.export_history, &self.document().path).cloned() {
+            Some(record) => {
+                info!(
+                    "Re-exporting {} to {}",
+                    record.format.label(),
+                    record.destination
+                );
+                self.export_to(record.format, Path::new(&record.destination));
+            }
+            None => self.notifications.show(
+                NotificationSource::Message,
+                NotificationKind::Info,
+                "This document has not been exported yet",
+            ),
+        }
+    }
+
+    /// Paginate the document and open it in the browser's print dialog
+    ///
+    /// Pages break where the viewer's block positions say the paper runs
+    /// out; the print page is written to the temp directory. The filter view
+    /// is left first, as the block positions must describe the whole document.
+    pub fn print(&mut self, cx: &mut Context<Self>) {
+        self.clear_filter();
+        let stem = self
+            .document()
+            .path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("document");
+        let print_path = std::env::temp_dir().join(format!("{}-print.html", stem));
+        let job = PrintJob {
+            markdown_content: &self.document().content,
+            markdown_file_path: &self.document().path,
+            render_mode: self.document().render_mode,
+            block_map: &self.block_map,
+            text_width: self.text_width(),
+            text_size: self.config.theme.base_text_size,
+            theme: &self.active_theme,
+        };
+        match write_print_document(&job, &print_path) {
+            Ok(pages) => {
+                cx.open_with_system(&print_path);
+                self.notifications.show(
+                    NotificationSource::Message,
+                    NotificationKind::Info,
+                    format!("Printing {} pages", pages),
+                );
+            }
+            Err(e) => {
+                warn!("Failed to prepare print document: {}", e);
+                self.notifications.show(
+                    NotificationSource::Message,
+                    NotificationKind::Error,
+                    format!("Print failed: {}", e),
+                );
+            }
+        }
+        cx.notify();
+    }
+
+    /// Switch between the light and dark variant of the current theme family
+    pub fn toggle_theme(&mut self) {
+        if let Some(new_theme) =
+            crate::internal::theme::registry().toggle_theme(&self.config.theme.theme)
+        {
+            self.apply_theme(new_theme);
+        }
+    }
+
+    /// Move on to the next theme family
+    pub fn cycle_theme(&mut self) {
+        if let Some(new_theme) =
+            crate::internal::theme::registry().cycle_theme(&self.config.theme.theme)
+        {
+            info!("Cycling theme to: {}", new_theme);
+            self.apply_theme(new_theme);
+        }
+    }
+
+    /// Switch to the theme called `name` and persist the choice
+    ///
+    /// Fails without changing anything when no such theme is loaded.
+    pub fn set_theme(&mut self, name: &str) -> Result<(), String> {
+        match crate::internal::theme::registry().get(name) {
+            Some(_) => {
+                self.apply_theme(name.to_string());
+                Ok(())
+            }
+            None => Err(format!("Unknown theme '{}'", name)),
+        }
+    }
+
+    /// Select a theme, refresh everything derived from it and persist the choice
+    fn apply_theme(&mut self, name: String) {
+        self.config.theme.theme = name;
+        self.sync_active_theme();
+        // Save config to persist theme preference
+        if let Err(e) = self.config.save_to_file("config.ron") {
+            debug!("Failed to save theme preference: {}", e);
+        }
+    }
+
+    /// Re-resolve the active theme if the configured theme name changed
+    ///
+    /// Covers every way the name can change (shortcuts, menu, config reload),
+    /// so code highlighting and placeholders always match the UI colors.
+    fn sync_active_theme(&mut self) {
+        if self.active_theme.name != self.config.theme.theme {
+            info!("Switching theme to: {}", self.config.theme.theme);
+            self.active_theme = ActiveTheme::resolve(&self.config.theme.theme);
+        }
+    }
+
+    /// Change the base text size by `delta` pixels, clamped to 8-64px
+    ///
+    /// Returns whether the size changed.
+    pub fn adjust_text_size(&mut self, delta: f32) -> bool {
+        let new_size = (self.config.theme.base_text_size + delta).clamp(8.0, 64.0);
+        match (new_size - self.config.theme.base_text_size).abs() > 0.01 {
+            true => {
+                self.config.theme.base_text_size = new_size;
+                self.recompute_max_scroll();
+                true
+            }
+            false => false,
+        }
+    }
+
+    /// Capture the state to restore on the next launch
+    pub fn session_state(&self) -> SessionState {
+        SessionState {
+            file: self.document().path.to_string_lossy().to_string(),
+            queue: self
+                .file_queue
+                .files()
+                .iter()
+                .map(|file| file.to_string_lossy().to_string())
+                .collect(),
+            scroll_y: self.document().scroll_state.scroll_y,
+            show_toc: self.show_toc,
+            text_size: self.config.theme.base_text_size,
+        }
+    }
+
+    /// Apply a session saved by a previous launch to the freshly opened file
+    pub fn restore_session(&mut self, session: &SessionState) {
+        if session.text_size > 0.0 {
+            self.config.theme.base_text_size = session.text_size.clamp(8.0, 64.0);
+        }
+        self.show_toc = session.show_toc;
+        self.recompute_max_scroll();
+        self.document_mut().scroll_state.scroll_y = session
+            .scroll_y
+            .clamp(0.0, self.document().scroll_state.max_scroll_y);
+        info!("Restored session for {}", session.file);
+    }
+
+    /// Flush everything kept across runs before the viewer exits
+    ///
+    /// Writes the session (open tabs and scroll position), every tab's
+    /// reading state (bookmarks, search) and the config with its recent
+    /// files and search history. Runs once, from whichever comes first of
+    /// a quit shortcut, closing the window or the app quitting; peek windows
+    /// leave everything alone.
+    pub fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+        if self.peek_mode {
+            debug!("Peek window closing, nothing to save");
+            return;
+        }
+        info!("Shutting down, saving state");
+        self.save_session();
+    }
+
+    /// Save all state and quit the app (Cmd+Q, q, Ctrl+C)
+    pub fn quit(&mut self, cx: &mut Context<Self>) {
+        self.shutdown();
+        cx.quit();
+    }
+
+    /// Record the current session in the config file
+    ///
+    /// Every open tab's reading state is remembered as well.
+    pub fn save_session(&mut self) {
+        if self.finder_root.is_some() && self.document().content.is_empty() {
+            // Launched on a directory without opening a file; nothing to restore
+            return;
+        }
+        self.clear_filter();
+        // The active tab goes last so it is the most recently left
+        let states = self
+            .tabs
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != self.active_tab)
+            .map(|(_, tab)| tab.file_state())
+            .chain(std::iter::once(self.document().file_state()))
+            .collect();
+        self.remember_file_states(states);
+        self.config.session = Some(self.session_state());
+        match self.config.save_to_file("config.ron") {
+            Ok(()) => info!("Session saved"),
+            Err(e) => warn!("Failed to save session: {}", e),
+        }
     }
 
-    /// Perform PDF export and set notification message
-    fn perform_pdf_export(&mut self, pdf_path: &std::path::Path) {
-        debug!("PDF export triggered, output path: {:?}", pdf_path);
+    /// Remember where documents were left and save the state file
+    ///
+    /// Peek windows and documents that were never loaded leave the state
+    /// file alone.
+    fn remember_file_states(&mut self, states: Vec<FileState>) {
+        if self.peek_mode {
+            return;
+        }
+        let before = self.file_states.clone();
+        for state in states.into_iter().filter(|state| !state.path.is_empty()) {
+            self.file_states.remember(state);
+        }
+        if self.file_states != before {
+            self.save_file_states();
+        }
+    }
 
-        // Perform export using pdf_export module with configuration
-        match crate::internal::pdf_export::export_to_pdf(
-            &self.markdown_content,
-            pdf_path,
-            &self.config.pdf_export,
-        ) {
-            Ok(()) => {
-                info!("Successfully exported PDF to {:?}", pdf_path);
-                // Show success notification
-                let filename = pdf_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("output.pdf");
-                self.pdf_export_message = Some(format!("✓ PDF exported: {}", filename));
-                self.pdf_export_success = true;
-            }
-            Err(e) => {
-                warn!("Failed to export PDF: {}", e);
-                // Show error notification
-                self.pdf_export_message = Some(format!("✗ PDF export failed: {}", e));
-                self.pdf_export_success = false;
-            }
+    /// Write the state store to disk
+    fn save_file_states(&self) {
+        if let Err(e) = self.file_states.save_to_file(FILE_STATE_PATH) {
+            warn!("Failed to save file state: {}", e);
         }
     }
 
-    /// Calculates the height of the content using smart logic (wrapping, images, etc.)
-    /// If stop_at_line is Some(n), returns the height up to the start of line n.
-    /// Returns (height, found_image_paths, block_element_count)
-    fn calculate_smart_height(
-        &self,
-        stop_at_line: Option<usize>,
-    ) -> (f32, std::collections::HashSet<String>, usize) {
+    /// Show the first-run tour unless it was already seen
+    pub fn start_tour_if_first_run(&mut self) {
+        if !self.file_states.tour_seen && !self.peek_mode {
+            info!("First run, showing the tour");
+            self.tour = Some(Tour::default());
+        }
+    }
+
+    /// Apply a key press or click in the tour, closing it when finished
+    ///
+    /// A finished or skipped tour is recorded as seen.
+    pub fn finish_tour(&mut self, outcome: TourOutcome) {
+        if outcome == TourOutcome::Finished {
+            self.tour = None;
+            self.file_states.tour_seen = true;
+            self.save_file_states();
+            info!("Tour finished");
+        }
+    }
+
+    /// Pick the current file up where it was last left: its bookmarks,
+    /// active search and scroll position
+    pub fn restore_file_state(&mut self) {
+        let Some(state) = self.file_states.get(&self.document().path).cloned() else {
+            return;
+        };
+        // The file may have changed since it was left; anchors find the
+        // bookmarked text again, and older states without anchors keep lines
+        let index = AnchorIndex::new(&self.document().content);
+        let followed =
+            bookmark_anchor::follow(&state.bookmarks, &state.bookmark_anchors, &index, &index);
+        let document = self.document_mut();
+        document.bookmarks = followed.bookmarks;
+        document.bookmark_anchors = followed.anchors;
+        document.stale_bookmarks = followed.stale;
+        if let Some(query) = &state.search {
+            self.set_search(query);
+        }
+        self.recompute_max_scroll();
+        self.document_mut().scroll_state.scroll_y = state
+            .scroll_y
+            .clamp(0.0, self.document().scroll_state.max_scroll_y);
+        info!("Restored reading state for {}", state.path);
+    }
+
+    /// Window height that fits the whole document, capped at `max_height`
+    ///
+    /// Used to auto-size the peek window so short documents get a small window.
+    pub fn peek_window_height(&self, max_height: f32) -> f32 {
+        let content_height = self.estimate_content_height(None);
+        (content_height + CONTAINER_PADDING * 2.0)
+            .clamp(PEEK_MIN_HEIGHT, max_height.max(PEEK_MIN_HEIGHT))
+    }
+
+    /// Width the document text wraps at, next to the sidebars when shown
+    fn text_width(&self) -> f32 {
+        self.viewport_width
+            - 64.0
+            - self.toc_width()
+            - self.file_tree_width()
+            - self.source_pane_width()
+    }
+
+    /// Width taken by the TOC sidebar
+    fn toc_width(&self) -> f32 {
+        match self.show_toc {
+            true => crate::internal::style::TOC_WIDTH,
+            false => 0.0,
+        }
+    }
+
+    /// Width taken by the source pane: half of what the sidebars leave
+    pub fn source_pane_width(&self) -> f32 {
+        match self.show_source && !self.peek_mode {
+            true => ((self.viewport_width - self.toc_width() - self.file_tree_width()) / 2.0)
+                .floor()
+                .max(0.0),
+            false => 0.0,
+        }
+    }
+
+    /// Width taken by the file tree sidebar
+    pub fn file_tree_width(&self) -> f32 {
+        match self.show_file_tree && !self.peek_mode {
+            true => crate::internal::style::FILE_TREE_WIDTH,
+            false => 0.0,
+        }
+    }
+
+    /// Estimate the height of the content before it has been laid out
+    /// (wrapping, tables, images)
+    ///
+    /// If line_tops is given, the height before each line is pushed onto it.
+    fn estimate_content_height(&self, mut line_tops: Option<&mut Vec<f32>>) -> f32 {
         let avg_line_height =
             self.config.theme.base_text_size * self.config.theme.line_height_multiplier;
 
@@ -667,22 +2929,30 @@ impl MarkdownViewer {
         let mut in_fenced_code = false;
 
         // Estimate wrapping for text lines
-        let effective_width = match self.show_toc {
-            true => self.viewport_width - crate::internal::style::TOC_WIDTH - 64.0,
-            false => self.viewport_width - 64.0,
-        };
+        let effective_width = self.text_width();
         // Use conservative multiplier for variable-width fonts
         let char_width = self.config.theme.base_text_size * CHAR_WIDTH_MULTIPLIER;
         let chars_per_line = (effective_width / char_width).max(20.0);
 
-        let mut smart_text_height = 0.0;
-        let mut found_image_paths = std::collections::HashSet::new();
-        let mut block_element_count: usize = 0;
-        let mut prev_line_empty = true; // Track paragraph boundaries
+        // Plain text: one row per source line, wrapped at the estimated width
+        if self.document().render_mode == RenderMode::Plain {
+            let mut height = 0.0;
+            for line in self.document().shown_content().lines() {
+                if let Some(tops) = line_tops.as_deref_mut() {
+                    tops.push(height);
+                }
+                height += (line.chars().count() as f32 / chars_per_line)
+                    .ceil()
+                    .max(1.0)
+                    * avg_line_height;
+            }
+            return height;
+        }
 
-        for (idx, raw_line) in self.markdown_content.lines().enumerate() {
-            if stop_at_line.is_some_and(|stop_idx| idx >= stop_idx) {
-                break;
+        let mut height = 0.0;
+        for raw_line in self.document().shown_content().lines() {
+            if let Some(tops) = line_tops.as_deref_mut() {
+                tops.push(height);
             }
 
             let line = raw_line.trim_start();
@@ -690,20 +2960,16 @@ impl MarkdownViewer {
             // Toggle fenced code block state
             if line.starts_with("```") {
                 in_fenced_code = !in_fenced_code;
-                if !in_fenced_code {
-                    // End of code block = one block element
-                    block_element_count += 1;
-                }
-                smart_text_height += avg_line_height * code_line_weight;
+                height += avg_line_height * code_line_weight;
                 continue;
             }
 
             if in_fenced_code {
-                smart_text_height += avg_line_height * code_line_weight;
+                height += avg_line_height * code_line_weight;
                 continue;
             }
 
-            // Robust Image Detection & Mixed Content Handling
+            // Images take their loaded height, or a placeholder until loaded
             let mut line_text = line.to_string();
             let mut image_height_on_line = 0.0;
             let mut found_image = false;
@@ -718,21 +2984,14 @@ impl MarkdownViewer {
                         let url = url_part.split_whitespace().next().unwrap_or("").trim();
 
                         if !url.is_empty() {
-                            let resolved_path = resolve_image_path(url, &self.markdown_file_path);
-
-                            // Track this image path
-                            found_image_paths.insert(resolved_path.clone());
-
-                            match self.image_display_heights.get(&resolved_path) {
-                                Some(&height) => {
-                                    image_height_on_line += height + IMAGE_VERTICAL_PADDING;
-                                }
-                                None => {
-                                    // Use PLACEHOLDER_HEIGHT for unloaded images
-                                    image_height_on_line +=
-                                        PLACEHOLDER_HEIGHT + IMAGE_VERTICAL_PADDING;
-                                }
-                            }
+                            let resolved_path =
+                                self.image_cache.resolve(url, Some(&self.document().path));
+                            image_height_on_line += self
+                                .image_display_heights
+                                .get(&resolved_path)
+                                .copied()
+                                .unwrap_or(PLACEHOLDER_HEIGHT)
+                                + IMAGE_VERTICAL_PADDING;
                             found_image = true;
                         }
 
@@ -744,27 +3003,9 @@ impl MarkdownViewer {
             }
 
             if found_image {
-                smart_text_height += image_height_on_line;
-                block_element_count += 1; // Images are block elements
+                height += image_height_on_line;
             }
 
-            // Count block elements: paragraphs (text after empty line), tables, lists
-            let is_table_line = line.starts_with('|');
-            let is_list_line = line.starts_with('-')
-                || line.starts_with('*')
-                || line.starts_with('+')
-                || (line.chars().next().is_some_and(|c| c.is_ascii_digit()) && line.contains(". "));
-            let is_heading = line.starts_with('#');
-
-            if is_table_line || is_heading {
-                block_element_count += 1;
-            } else if !line.is_empty() && prev_line_empty && !is_list_line {
-                // New paragraph (non-empty line after empty line)
-                block_element_count += 1;
-            }
-
-            prev_line_empty = line.is_empty();
-
             let weight = match () {
                 _ if line.starts_with('#') => heading_weight,
                 _ if line.starts_with('>') => blockquote_weight,
@@ -795,114 +3036,139 @@ impl MarkdownViewer {
                 _ => 1.0,
             };
 
-            smart_text_height += visual_lines * avg_line_height * weight;
+            height += visual_lines * avg_line_height * weight;
         }
 
-        (smart_text_height, found_image_paths, block_element_count)
+        height
     }
 
+    /// Size the scrollbar to the document, rebuilding its line positions
+    ///
+    /// The scroll range ends `BOTTOM_SCROLL_PADDING` below the last block so
+    /// it clears the status bar.
     pub fn recompute_max_scroll(&mut self) {
-        let avg_line_height =
-            self.config.theme.base_text_size * self.config.theme.line_height_multiplier;
-
-        // --- Smart Logic (Current) ---
-        let (smart_text_height, found_image_paths, block_count) = self.calculate_smart_height(None);
-
-        // Apply percentage-based scaling + block element spacing
-        let smart_total_height = (smart_text_height * CONTENT_HEIGHT_SCALE)
-            + (block_count as f32 * BLOCK_ELEMENT_SPACING);
-
-        // --- Legacy Logic (Old) ---
-        // Simple line counts + sum of all loaded images
-        let legacy_heading_weight = 1.6;
-        let legacy_code_weight = 1.25;
-        let legacy_quote_weight = 1.15;
-        let legacy_list_weight = 1.0;
-        let legacy_empty_weight = 0.25;
-        let legacy_normal_weight = 1.0;
-
-        let mut legacy_text_height = 0.0;
-        let mut in_fenced_code_legacy = false;
-
-        for raw_line in self.markdown_content.lines() {
-            let line = raw_line.trim_start();
-
-            if line.starts_with("```") {
-                in_fenced_code_legacy = !in_fenced_code_legacy;
-                // Old logic: fence lines had 0 height (continue without adding)
-                continue;
+        self.rebuild_block_map();
+        let content_height = self.block_map.bottom() + BOTTOM_SCROLL_PADDING;
+        debug!(
+            "Scroll range: {:.1}px of content ({})",
+            content_height,
+            match self.has_measured_layout() {
+                true => "measured",
+                false => "estimated",
             }
+        );
+        let viewport_height = self.viewport_height;
+        self.document_mut()
+            .scroll_state
+            .set_max_scroll(content_height, viewport_height);
+    }
 
-            if in_fenced_code_legacy {
-                legacy_text_height += avg_line_height * legacy_code_weight;
-                continue;
+    /// The current content parsed into blocks, parsing it again only after
+    /// `content_revision` moved on
+    fn parsed_document(&mut self) -> Rc<ParsedDocument> {
+        match &self.parsed_document {
+            Some(document) if document.revision == self.content_revision => document.clone(),
+            _ => {
+                let document = Rc::new(ParsedDocument::parse(
+                    self.document().shown_content(),
+                    &self.document().path,
+                    self.content_revision,
+                ));
+                debug!("Parsed content revision {}", self.content_revision);
+                self.parsed_document = Some(document.clone());
+                document
             }
-
-            let weight = match () {
-                _ if line.starts_with('#') => legacy_heading_weight,
-                _ if line.starts_with('>') => legacy_quote_weight,
-                _ if line.starts_with('-')
-                    || line.starts_with('*')
-                    || line.starts_with('+')
-                    || (line.chars().next().is_some_and(|c| c.is_ascii_digit())
-                        && line.contains(". ")) =>
-                {
-                    legacy_list_weight
-                }
-                _ if line.is_empty() => legacy_empty_weight,
-                _ => legacy_normal_weight,
-            };
-
-            legacy_text_height += avg_line_height * weight;
         }
+    }
 
-        let legacy_images_height: f32 = self
-            .image_display_heights
-            .values()
-            .copied()
-            .map(|h| h + IMAGE_VERTICAL_PADDING)
-            .sum();
-
-        let legacy_total_height = legacy_text_height + legacy_images_height;
-
-        // --- Dynamic Image Buffer ---
-        // Count how many images we found vs how many have loaded heights
-        let total_images_found = found_image_paths.len();
-        let loaded_images_count = found_image_paths
-            .iter()
-            .filter(|path| self.image_display_heights.contains_key(*path))
-            .count();
-        let unloaded_images_count = total_images_found.saturating_sub(loaded_images_count);
+    /// Whether the last measured layout belongs to the current content
+    fn has_measured_layout(&self) -> bool {
+        self.measured_layout
+            .as_ref()
+            .is_some_and(|layout| layout.revision == self.content_revision)
+    }
 
-        // Add 500px per unloaded image (reasonable average height for typical web images)
-        // This is more conservative than PLACEHOLDER_HEIGHT but less than IMAGE_MAX_WIDTH
-        let unloaded_image_buffer = (unloaded_images_count as f32) * 500.0;
+    /// Lay out rendered `blocks` (spanning the source lines in `ranges`) one
+    /// below the other, measuring where each one lands
+    ///
+    /// Empty markers before and after the blocks give the content's top edge
+    /// and full height; the measurement is applied once the frame is drawn.
+    fn measured_container(
+        &self,
+        blocks: Vec<AnyElement>,
+        ranges: Vec<(usize, usize)>,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        let revision = self.content_revision;
+        let viewer = cx.weak_entity();
+        div()
+            .flex_col()
+            .w_full()
+            .child(div())
+            .children(blocks)
+            .child(div())
+            .on_children_prepainted(move |bounds, window, _| {
+                let (Some(first), Some(last)) = (bounds.first(), bounds.last()) else {
+                    return;
+                };
+                let origin = first.origin.y;
+                let layout = MeasuredLayout {
+                    revision,
+                    ranges: ranges.clone(),
+                    extents: bounds[1..bounds.len() - 1]
+                        .iter()
+                        .map(|block| {
+                            (
+                                f32::from(block.top() - origin),
+                                f32::from(block.bottom() - origin),
+                            )
+                        })
+                        .collect(),
+                    height: f32::from(last.origin.y - origin),
+                };
+                let viewer = viewer.clone();
+                window.on_next_frame(move |_, cx| {
+                    let _ = viewer.update(cx, |viewer, cx| {
+                        if viewer.apply_measured_layout(layout) {
+                            cx.notify();
+                        }
+                    });
+                });
+            })
+    }
 
-        // --- Hybrid Result ---
-        // Use the maximum of the two estimates + container padding + base buffer + unloaded image buffer
-        // TODO: Revisit this hybrid calculation logic. It's a temporary fix to ensure
-        // both text-heavy (wrapping) and image-heavy (legacy) files scroll correctly.
-        // Ideally, we should have a single unified logic that handles all cases perfectly.
+    /// Take the block positions measured after the last layout
+    ///
+    /// The line at the top of the view stays in place while positions move
+    /// from the estimate (or an earlier layout) to the measured ones. Returns
+    /// whether anything moved, in which case the view needs another frame.
+    pub fn apply_measured_layout(&mut self, layout: MeasuredLayout) -> bool {
+        if layout.revision != self.content_revision
+            || self
+                .measured_layout
+                .as_ref()
+                .is_some_and(|known| !known.differs_from(&layout))
+        {
+            return false;
+        }
+        let scroll_y = self.document().scroll_state.scroll_y;
+        let position = self.block_map.position_at(scroll_y);
 
         debug!(
-            "Scroll calculation: smart={:.1}px (scaled), legacy={:.1}px, blocks={}, images={}/{} loaded, unloaded_buffer={:.1}px",
-            smart_total_height,
-            legacy_total_height,
-            block_count,
-            loaded_images_count,
-            total_images_found,
-            unloaded_image_buffer
+            "Measured {} blocks, {:.1}px high",
+            layout.extents.len(),
+            layout.height
         );
-
-        // Content height = max(smart, legacy) + container padding + image buffer + safety margin
-        // Note: smart_total_height already includes scaling (8%) and block element spacing
-        let content_height = f32::max(smart_total_height, legacy_total_height)
-            + CONTAINER_PADDING
-            + unloaded_image_buffer
-            + BOTTOM_SCROLL_PADDING;
-
-        self.scroll_state
-            .set_max_scroll(content_height, self.viewport_height);
+        self.measured_layout = Some(layout);
+        self.recompute_max_scroll();
+
+        if scroll_y > 0.0 {
+            self.document_mut().scroll_state.scroll_y = self
+                .block_map
+                .y_for_position(position)
+                .clamp(0.0, self.document().scroll_state.max_scroll_y);
+        }
+        true
     }
 
     pub fn load_image(&mut self, path: String, window: &Window, cx: &mut Context<Self>) {
@@ -910,10 +3176,31 @@ impl MarkdownViewer {
             return;
         }
 
+        // Network hiccups are worth another try; a missing local file is not
+        let is_remote = path.starts_with("http://") || path.starts_with("https://");
+        // Offline, remote images show their alt text right away instead of
+        // waiting for the request to time out
+        if is_remote && self.network.is_offline() {
+            debug!("Offline, skipping remote image: {}", path);
+            self.image_cache.insert(path, ImageState::Error);
+            return;
+        }
+
         self.image_cache.insert(path.clone(), ImageState::Loading);
+        // A fetch still running from before a reload will fill in this entry
+        let Some(ticket) = self.image_cache.begin_fetch(&path) else {
+            debug!("Image already being fetched: {}", path);
+            return;
+        };
         let path_for_load = path.clone();
         let path_for_update = path.clone();
         let bg_rt = self.bg_rt.clone();
+        let provider = self.image_cache.provider();
+        let network = self.network.clone();
+        let retries = match is_remote {
+            true => REMOTE_IMAGE_RETRIES,
+            false => 0,
+        };
 
         // Spawn a gpui background task which delegatesthe network + decode work to the dedicated Tokio runtime.
         cx.spawn_in(
@@ -922,69 +3209,102 @@ impl MarkdownViewer {
                 let mut cx = cx.clone();
                 let bg_rt = bg_rt.clone();
                 async move {
-                    // Spawn the network+decode job on the background runtime.
-                    // The background job returns Result<image::DynamicImage, anyhow::Error>.
-                    let join_handle = bg_rt.spawn(async move {
-                        // Delegate fetching + decoding to the centralized image_loader helper.
-                        // This keeps main UI code small and moves network/fallback logic into an internal module.
-                        fetch_and_decode_image(&path_for_load).await
-                    });
-
-                    // Await the join handle produced by the background runtime.
-                    let join_result = join_handle.await;
+                    // Run the network+decode job on the background runtime under
+                    // supervision. Fetching + decoding is delegated to the image
+                    // provider (by default the network/file helpers in the
+                    // image_loader module); the ticket limits concurrent
+                    // downloads, retries and cancels it.
+                    let task = format!("Loading image {}", path_for_load);
+                    let job_ticket = ticket.clone();
+                    let result = supervise(&bg_rt, &task, 0, move || {
+                        let provider = provider.clone();
+                        let path = path_for_load.clone();
+                        let network = network.clone();
+                        let ticket = job_ticket.clone();
+                        async move {
+                            ticket
+                                .run(retries, || async {
+                                    // Another fetch may have found the network down meanwhile
+                                    if is_remote && network.is_offline() {
+                                        anyhow::bail!("Network unavailable");
+                                    }
+                                    provider.fetch(&path).await.inspect_err(|error| {
+                                        if is_remote && network::is_unreachable(error) {
+                                            network.mark_unreachable();
+                                        }
+                                    })
+                                })
+                                .await
+                        }
+                    })
+                    .await;
 
                     // Update gpui state on the UI context thread.
-                    this.update(&mut cx, |this, cx| match join_result {
-                        Ok(Ok(dyn_img)) => {
-                            // Successfully decoded image into DynamicImage. Convert to RGBA and create RenderImage.
-                            let mut rgba = dyn_img.into_rgba8();
-
-                            // GPUI on macOS expects BGRA format, but image crate produces RGBA.
-                            // Convert RGBA -> BGRA before passing to GPUI
-                            rgba_to_bgra(&mut rgba);
-
-                            let orig_w = rgba.width() as f32;
-                            let orig_h = rgba.height() as f32;
-                            // Compute displayed width constrained by IMAGE_MAX_WIDTH (same as rendering).
-                            let displayed_w = match orig_w > IMAGE_MAX_WIDTH {
-                                true => IMAGE_MAX_WIDTH,
-                                false => orig_w,
-                            };
-                            // Maintain aspect ratio for displayed height
-                            let displayed_h = match orig_w {
-                                w if w > 0.0 => (displayed_w / w) * orig_h,
-                                _ => orig_h,
-                            };
-
-                            let frame = image::Frame::new(rgba);
-                            let render_image = RenderImage::new(vec![frame]);
-                            let arc_img = Arc::new(render_image);
-
-                            debug!("Successfully loaded image: {}", path_for_update);
-                            this.image_cache.insert(
-                                path_for_update.clone(),
-                                ImageState::Loaded(ImageSource::Render(arc_img.clone())),
-                            );
-                            this.image_display_heights
-                                .insert(path_for_update.clone(), displayed_h);
-                            // Recompute scroll bounds now that an image height is known
-                            this.recompute_max_scroll();
-                            cx.notify();
+                    this.update(&mut cx, |this, cx| {
+                        // The document moved on; a new fetch fills in the image if needed
+                        if ticket.is_cancelled() {
+                            debug!("Image fetch cancelled: {}", path_for_update);
+                            return;
                         }
-                        Ok(Err(e)) => {
-                            debug!("Failed to load image '{}': {}", path_for_update, e);
-                            this.image_cache
-                                .insert(path_for_update.clone(), ImageState::Error);
-                            this.image_display_heights.remove(&path_for_update);
-                        }
-                        Err(join_err) => {
-                            debug!(
-                                "Image task join error for '{}': {}",
-                                path_for_update, join_err
-                            );
-                            this.image_cache
-                                .insert(path_for_update.clone(), ImageState::Error);
-                            this.image_display_heights.remove(&path_for_update);
+                        this.image_cache.finish_fetch(&path_for_update, &ticket);
+                        match result {
+                            Ok(dyn_img) => {
+                                // Successfully decoded image into DynamicImage. Convert to RGBA and create RenderImage.
+                                let mut rgba = dyn_img.into_rgba8();
+
+                                // GPUI on macOS expects BGRA format, but image crate produces RGBA.
+                                // Convert RGBA -> BGRA before passing to GPUI
+                                rgba_to_bgra(&mut rgba);
+
+                                let orig_w = rgba.width() as f32;
+                                let orig_h = rgba.height() as f32;
+                                // Compute displayed width constrained by IMAGE_MAX_WIDTH (same as rendering).
+                                let displayed_w = match orig_w > IMAGE_MAX_WIDTH {
+                                    true => IMAGE_MAX_WIDTH,
+                                    false => orig_w,
+                                };
+                                // Maintain aspect ratio for displayed height
+                                let displayed_h = match orig_w {
+                                    w if w > 0.0 => (displayed_w / w) * orig_h,
+                                    _ => orig_h,
+                                };
+
+                                let frame = image::Frame::new(rgba);
+                                let render_image = RenderImage::new(vec![frame]);
+                                let arc_img = Arc::new(render_image);
+
+                                debug!("Successfully loaded image: {}", path_for_update);
+                                this.image_cache.insert(
+                                    path_for_update.clone(),
+                                    ImageState::Loaded(ImageSource::Render(arc_img.clone())),
+                                );
+                                this.image_display_heights
+                                    .insert(path_for_update.clone(), displayed_h);
+                                // Recompute scroll bounds now that an image height is known
+                                this.recompute_max_scroll();
+                                cx.notify();
+                            }
+                            Err(failure) => {
+                                debug!("Failed to load image '{}': {}", path_for_update, failure);
+                                this.image_cache
+                                    .insert(path_for_update.clone(), ImageState::Error);
+                                this.image_display_heights.remove(&path_for_update);
+                                // Offline, tell the user once instead of for every image
+                                if is_remote && this.network.is_offline() {
+                                    if this.network.announce() {
+                                        this.notifications.show(
+                                        NotificationSource::Background,
+                                        NotificationKind::Info,
+                                        "Network unavailable: showing alt text for remote images",
+                                    );
+                                    }
+                                    cx.notify();
+                                // A broken local image already shows its placeholder
+                                } else if failure.panicked || is_remote {
+                                    this.report_task_failure(&failure);
+                                    cx.notify();
+                                }
+                            }
                         }
                     })
                     .ok();
@@ -994,14 +3314,36 @@ impl MarkdownViewer {
         .detach();
     }
 
+    /// Tell the user about background work that failed (see `watchdog`)
+    pub fn report_task_failure(&mut self, failure: &TaskFailure) {
+        self.notifications.show(
+            NotificationSource::Background,
+            NotificationKind::Error,
+            failure.to_string(),
+        );
+    }
+
+    /// Serve images through `provider` instead of the file system and HTTP
+    ///
+    /// Already loaded images are dropped and fetched again from the new provider.
+    pub fn set_image_provider(&mut self, provider: Arc<dyn ImageProvider>) {
+        self.image_cache = ImageCache::new(provider);
+        self.image_display_heights.clear();
+        self.recompute_max_scroll();
+    }
+
     /// Reload configuration from file and update state
     pub fn reload_config(&mut self, cx: &mut Context<Self>) {
         info!("Reloading configuration...");
         match AppConfig::load() {
             Ok(new_config) => {
+                self.notifications
+                    .set_config(new_config.notifications.clone());
                 self.config = new_config;
-                // Update window title if changed (requires window handle, can't easily do here without it)
-                // But we can update internal state dependent on config
+                link_rules::set_rules(&self.config.link_rewrites);
+                image_disk_cache::configure(&self.config.images);
+                self.content_revision += 1;
+                // The window title picks up a changed `window.title` on the next render
 
                 // Recompute scroll bounds (font sizes might have changed)
                 self.recompute_max_scroll();
@@ -1015,29 +3357,76 @@ impl MarkdownViewer {
             }
         }
     }
+}
 
-    /// Collect all links from a markdown AST node and add them to focusable_elements
-    fn collect_links_from_ast<'a>(&mut self, node: &'a comrak::nodes::AstNode<'a>) {
-        use comrak::nodes::NodeValue;
-
-        if let NodeValue::Link(link) = &node.data.borrow().value
-            && !link.url.trim().is_empty()
-        {
-            self.focusable_elements
-                .push(FocusableElement::Link(link.url.clone()));
+impl LinkHandler for MarkdownViewer {
+    /// Offer the click to subscribers, then open the link unless one stopped it
+    ///
+    /// `#anchor` links scroll to their heading and relative links to
+    /// supported files open in the viewer (at the anchor, if any); everything
+    /// else goes to the system browser.
+    fn open_link(&mut self, url: &str, cx: &mut Context<Self>) {
+        let event = ViewerEvent::LinkClicked {
+            url: url.to_string(),
+        };
+        if self.emit(event) == Propagation::Stop {
+            debug!("Link click handled by a subscriber: {}", url);
+            return;
         }
-
-        for child in node.children() {
-            self.collect_links_from_ast(child);
+        if url.starts_with('#') {
+            self.follow_anchor(url);
+            cx.notify();
+            return;
+        }
+        match resolve_link_target(
+            url,
+            &self.document().path,
+            &self.config.files.all_extensions(),
+        ) {
+            Some(path) => {
+                let path = canonical_path(&path);
+                self.follow_link(path.clone(), cx);
+                if let Some(anchor) = link_anchor(url)
+                    && self.document().path == path
+                {
+                    self.scroll_to_anchor(anchor);
+                }
+            }
+            None => open_in_browser(url),
         }
     }
+
+    /// Copy `#anchor`, or `file.md#anchor` for linking from other files,
+    /// and confirm with a notification
+    fn copy_heading_anchor(&mut self, anchor: &str, with_file: bool, cx: &mut Context<Self>) {
+        let file_name = self
+            .document()
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        let link = match (with_file, file_name) {
+            (true, Some(file_name)) => format!("{}#{}", file_name, anchor),
+            _ => format!("#{}", anchor),
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(link.clone()));
+        self.notifications.show(
+            NotificationSource::Message,
+            NotificationKind::Info,
+            format!("Copied {}", link),
+        );
+        cx.notify();
+    }
+
+    /// Open the image in the lightbox at its native size
+    fn open_image(&mut self, key: &str, alt: &str, cx: &mut Context<Self>) {
+        debug!("Opening image in lightbox: {}", key);
+        self.lightbox = Some(Lightbox::new(key, alt));
+        cx.notify();
+    }
 }
 
 impl Render for MarkdownViewer {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        // Clear focusable elements list - will be rebuilt during this render pass
-        self.focusable_elements.clear();
-
         // Poll file watcher for events (non-blocking)
         // Collect events first to avoid borrow checker issues
         let mut events = Vec::new();
@@ -1060,7 +3449,7 @@ impl Render for MarkdownViewer {
                 FileWatcherEvent::Modified => {
                     self.reload_config(cx);
                 }
-                FileWatcherEvent::Deleted => {
+                FileWatcherEvent::Deleted | FileWatcherEvent::Renamed(_) => {
                     warn!("Config file deleted!");
                 }
                 FileWatcherEvent::Error(e) => {
@@ -1069,69 +3458,67 @@ impl Render for MarkdownViewer {
             }
         }
 
+        self.check_changed_on_disk();
+        self.run_notification_timer(cx);
+        self.run_scroll_animation(cx);
+
         // Process collected events
+        let mut reloaded = false;
         for event in events {
             match event {
                 FileWatcherEvent::Modified => {
-                    info!("File modified, reloading: {:?}", self.markdown_file_path);
-                    // Save current scroll position
-                    let saved_scroll_y = self.scroll_state.scroll_y;
-
-                    // Reload file content
-                    match self.markdown_file_path.to_str() {
-                        Some(path_str) => {
-                            match load_markdown_content(path_str) {
-                                Ok(new_content) => {
-                                    self.markdown_content = new_content;
-
-                                    // Regenerate TOC
-                                    let arena = comrak::Arena::new();
-                                    let mut options = comrak::Options::default();
-                                    options.extension.table = true;
-                                    let root = comrak::parse_document(
-                                        &arena,
-                                        &self.markdown_content,
-                                        &options,
-                                    );
-                                    self.toc =
-                                        crate::internal::toc::TableOfContents::from_ast(root);
-
-                                    // Clear image cache as images may have changed
-                                    self.image_cache.clear();
-                                    self.image_display_heights.clear();
-                                    // Restore scroll position
-                                    self.scroll_state.scroll_y = saved_scroll_y;
-                                    self.recompute_max_scroll();
-                                    self.compute_toc_max_scroll();
-                                    // Clear file deleted flag if it was set
-                                    self.file_deleted = false;
-                                    info!("File reloaded successfully");
-                                }
-                                Err(e) => {
-                                    warn!("Failed to reload file: {}", e);
-                                }
-                            }
-                        }
-                        None => {
-                            warn!(
-                                "Failed to convert path to string: {:?}",
-                                self.markdown_file_path
-                            );
-                        }
-                    }
+                    info!("File modified, reloading: {:?}", self.document().path);
+                    self.reload_from_disk();
+                    reloaded = true;
+                    cx.notify();
+                }
+                FileWatcherEvent::Deleted
+                    if archive::containing_file(&self.document().path).exists() =>
+                {
+                    info!("File recreated, reloading: {:?}", self.document().path);
+                    self.reload_from_disk();
+                    reloaded = true;
                     cx.notify();
                 }
                 FileWatcherEvent::Deleted => {
-                    info!("File deleted: {:?}", self.markdown_file_path);
+                    info!("File deleted: {:?}", self.document().path);
+                    // Re-establish the watch on the parent directory so the file
+                    // is picked up again if it is recreated with a new inode
+                    self.rewatch_file();
                     self.file_deleted = true;
                     cx.notify();
                 }
+                FileWatcherEvent::Renamed(to) => {
+                    self.follow_rename(to);
+                    reloaded = true;
+                    cx.notify();
+                }
                 FileWatcherEvent::Error(err) => {
                     warn!("File watcher error: {}", err);
                 }
             }
         }
 
+        // Badge the window title when the file reloads in the background so
+        // the change is noticed; focusing the window clears the badge
+        match window.is_window_active() {
+            true => self.unseen_reload = false,
+            false if reloaded => {
+                debug!("File reloaded while window inactive, badging title");
+                self.unseen_reload = true;
+            }
+            false => {}
+        }
+        let title = ui::window_title(
+            &self.config.window.title,
+            &self.document().path,
+            self.unseen_reload,
+        );
+        if title != self.window_title {
+            window.set_window_title(&title);
+            self.window_title = title;
+        }
+
         // Update viewport dimensions if changed
         let viewport_size = window.viewport_size();
         let current_height_f32 = f32::from(viewport_size.height);
@@ -1147,17 +3534,56 @@ impl Render for MarkdownViewer {
             self.recompute_max_scroll();
         }
 
-        let arena = Arena::new();
-        let mut options = Options::default();
-        options.extension.table = true; // Enable GFM tables
-        let root = parse_document(&arena, &self.markdown_content, &options);
-
-        // Collect all links from the markdown AST for keyboard navigation
-        self.collect_links_from_ast(root);
+        // Keep the Tab order in sync with the document and open overlays
+        self.refresh_focusable_elements();
+        self.emit_state_changes();
 
-        debug!("AST parsing complete");
-        let mut missing_images = HashSet::new();
-        let theme_colors = get_theme_colors(&self.config.theme.theme);
+        let focused_element = self.focused_element().cloned();
+        self.sync_active_theme();
+        let theme = self.active_theme.clone();
+        let theme_colors = theme.colors;
+        if let Some(search_state) = &self.document().search_state {
+            search_state.begin_render();
+        }
+        let content = match self.document().render_mode {
+            RenderMode::Markdown => {
+                let document = self.parsed_document();
+
+                // Top-level blocks are laid out directly in the measured container
+                let text_width = self.text_width();
+                let shown = &self.tabs[self.active_tab];
+                let blocks = document
+                    .root
+                    .children()
+                    .map(|block| {
+                        render_document_node(
+                            block,
+                            Some(&shown.path),
+                            shown.search_state.as_ref(),
+                            text_width,
+                            &theme,
+                            cx,
+                            &mut self.image_cache,
+                            focused_element.as_ref(),
+                        )
+                    })
+                    .collect();
+                self.measured_container(blocks, document.root.block_ranges(), cx)
+            }
+            RenderMode::Plain => {
+                let lines = render_plain_text_lines(
+                    self.document().shown_content(),
+                    self.document().search_state.as_ref(),
+                    MatchColors::from_theme(theme_colors),
+                );
+                let ranges = (0..lines.len()).map(|line| (line, line)).collect();
+                let container = self.measured_container(lines, ranges, cx);
+                match self.config.theme.plain_text_monospace {
+                    true => container.font_family(self.config.theme.code_font.clone()),
+                    false => container,
+                }
+            }
+        };
         let element = div()
             .track_focus(&self.focus_handle)
             .flex()
@@ -1179,24 +3605,30 @@ impl Render for MarkdownViewer {
             // Search action handlers
             .on_action(cx.listener(|this, _: &ToggleSearch, _, cx| {
                 debug!("ToggleSearch action triggered");
-                match this.search_state.take() {
+                match this.document_mut().search_state.take() {
                     Some(_) => {
                         // Exit search mode
                         debug!("Exiting search mode");
-                        this.search_input.clear();
+                        this.clear_filter();
+                        this.document_mut().search_state = None;
+                        this.document_mut().search_input.clear();
                     }
                     None => {
                         // Enter search mode
                         debug!("Entering search mode");
-                        this.search_state =
-                            Some(SearchState::new(String::new(), &this.markdown_content));
+                        this.search_highlights_kept = false;
+                        this.document_mut().search_state = Some(SearchState::new(
+                            String::new(),
+                            this.document().shown_content(),
+                            this.search_mode,
+                        ));
                     }
                 }
                 cx.notify();
             }))
             .on_action(cx.listener(|this, _: &NextMatch, _, cx| {
                 debug!("NextMatch action triggered");
-                if let Some(state) = &mut this.search_state {
+                if let Some(state) = &mut this.document_mut().search_state {
                     state.next_match();
                     debug!("Next match: {:?}", state.current_match_number());
                     this.scroll_to_current_match();
@@ -1205,7 +3637,7 @@ impl Render for MarkdownViewer {
             }))
             .on_action(cx.listener(|this, _: &PrevMatch, _, cx| {
                 debug!("PrevMatch action triggered");
-                if let Some(state) = &mut this.search_state {
+                if let Some(state) = &mut this.document_mut().search_state {
                     state.prev_match();
                     debug!("Previous match: {:?}", state.current_match_number());
                     this.scroll_to_current_match();
@@ -1214,62 +3646,151 @@ impl Render for MarkdownViewer {
             }))
             .on_action(cx.listener(|this, _: &ExitSearch, _, cx| {
                 debug!("ExitSearch action triggered");
-                this.search_state = None;
-                this.search_input.clear();
+                this.document_mut().search_state = None;
+                this.document_mut().search_input.clear();
+                cx.notify();
+            }))
+            // Menu bar action handlers
+            .on_action(cx.listener(|this, _: &menu::OpenFile, _, cx| {
+                this.open_file_dialog(cx);
+            }))
+            .on_action(cx.listener(|this, _: &menu::OpenRecent, _, cx| {
+                this.show_file_finder = true;
+                this.finder_mode = FinderMode::RecentFiles;
+                this.refresh_file_list();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ReopenClosedTab, _, cx| {
+                this.reopen_closed_tab(cx);
+            }))
+            .on_action(cx.listener(|this, _: &menu::GoToFile, _, cx| {
+                this.show_file_finder = true;
+                this.finder_mode = FinderMode::AllFiles;
+                this.refresh_file_list();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ExportPdf, _, cx| {
+                this.trigger_pdf_export = true;
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ExportHtml, _, cx| {
+                this.export_html();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ExportEpub, _, cx| {
+                this.export_epub();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::Reexport, _, cx| {
+                this.reexport();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ShowExports, _, cx| {
+                this.show_export_history = true;
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ToggleOffline, _, cx| {
+                this.toggle_offline();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ShowImageReport, _, cx| {
+                this.toggle_image_report();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::Print, _, cx| {
+                this.print(cx);
+            }))
+            .on_action(cx.listener(|this, _: &menu::ToggleToc, _, cx| {
+                this.toggle_toc();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ToggleTheme, _, cx| {
+                this.toggle_theme();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::CycleTheme, _, cx| {
+                this.cycle_theme();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ZoomIn, _, cx| {
+                if this.adjust_text_size(2.0) {
+                    cx.notify();
+                }
+            }))
+            .on_action(cx.listener(|this, _: &menu::ZoomOut, _, cx| {
+                if this.adjust_text_size(-2.0) {
+                    cx.notify();
+                }
+            }))
+            .on_action(cx.listener(|this, _: &menu::ShowAbout, _, cx| {
+                this.toggle_about();
+                cx.notify();
+            }))
+            .on_action(cx.listener(|this, _: &menu::ShowHelp, _, cx| {
+                this.show_help = true;
                 cx.notify();
             }))
             .on_key_down(cx.listener(events::handle_key_down))
+            .on_key_up(cx.listener(events::handle_key_up))
             .on_scroll_wheel(cx.listener(events::handle_scroll_wheel))
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(|this, event: &gpui::MouseDownEvent, _, cx| {
+                    if event.modifiers.alt
+                        && this.jump_to_source_at(
+                            f32::from(event.position.x),
+                            f32::from(event.position.y),
+                        )
+                    {
+                        cx.notify();
+                    }
+                }),
+            )
             .child(
                 div().flex().size_full().overflow_hidden().child(
                     div()
                         .flex_col()
                         .w_full()
                         .pt_4()
-                        .pr(match self.show_toc {
-                            true => px(crate::internal::style::TOC_WIDTH + 32.0),
-                            false => px(32.0),
-                        })
+                        .pr(px(self.toc_width() + 32.0))
                         .pb_4()
-                        .pl_8()
+                        .pl(px(self.file_tree_width() + self.source_pane_width() + 32.0))
                         .relative()
-                        .top(px(-self.scroll_state.scroll_y))
-                        .child(render_markdown_ast_with_search(
-                            root,
-                            Some(&self.markdown_file_path),
-                            self.search_state.as_ref(),
-                            match self.show_toc {
-                                true => {
-                                    self.viewport_width - crate::internal::style::TOC_WIDTH - 64.0
-                                }
-                                false => self.viewport_width - 64.0,
-                            },
-                            theme_colors,
-                            cx,
-                            &mut |path: &str| match self.image_cache.get(path) {
-                                Some(ImageState::Loaded(src)) => Some(src.clone()),
-                                None => {
-                                    missing_images.insert(path.to_string());
-                                    None
-                                }
-                                _ => None,
-                            },
-                            self.current_focus_index
-                                .and_then(|idx| self.focusable_elements.get(idx)),
-                        )),
+                        .top(px(-self.document().scroll_state.scroll_y))
+                        .child(content),
                 ),
             )
-            // Interactive Status Bar
-            .child(ui::render_status_bar(self, theme_colors, cx));
+            // Interactive Status Bar (hidden in peek mode)
+            .when(!self.peek_mode, |el| {
+                el.child(ui::render_status_bar(self, theme_colors, cx))
+            });
+
+        // Raw source next to the rendered document
+        let element = match ui::render_source_pane(self, theme_colors, cx) {
+            Some(pane) => element.child(pane),
+            None => element,
+        };
+
+        // Error page over a document that could not be read
+        let element = match ui::render_load_error_page(self, theme_colors, cx) {
+            Some(page) => element.child(page),
+            None => element,
+        };
+
+        // Tab bar while several files are open
+        let element = match ui::render_tab_bar(self, theme_colors, cx) {
+            Some(tab_bar) => element.child(tab_bar),
+            None => element,
+        };
 
         // Add search indicator overlay if search is active
-        let element = match ui::render_search_overlay(self) {
+        let element = match ui::render_search_overlay(self, theme_colors, cx) {
             Some(overlay) => element.child(overlay),
             None => element,
         };
 
         // Add go-to-line overlay if active
-        let element = match ui::render_goto_line_overlay(self) {
+        let element = match ui::render_goto_line_overlay(self, theme_colors) {
             Some(overlay) => element.child(overlay),
             None => element,
         };
@@ -1280,52 +3801,103 @@ impl Render for MarkdownViewer {
             None => element,
         };
 
-        // Help Overlay
-        let element = match ui::render_help_overlay(self, theme_colors) {
+        // Exports Overlay
+        let element = match ui::render_export_history_overlay(self, theme_colors, cx) {
             Some(overlay) => element.child(overlay),
             None => element,
         };
 
-        // File Deleted Overlay
-        let element = match ui::render_file_deleted_overlay(self) {
+        // Image Report Overlay
+        let element = match ui::render_image_report_overlay(self, theme_colors, cx) {
+            Some(overlay) => element.child(overlay),
+            None => element,
+        };
+
+        // Lightbox
+        let element = match ui::render_lightbox(self, theme_colors, cx) {
             Some(overlay) => element.child(overlay),
             None => element,
         };
 
-        // PDF Export Notification Overlay
-        let element = match ui::render_pdf_export_overlay(self, theme_colors) {
+        // Help Overlay
+        let element = match ui::render_help_overlay(self, theme_colors) {
             Some(overlay) => element.child(overlay),
             None => element,
         };
 
-        // Search History Notification Overlay
-        let element = match ui::render_search_history_notification(self, theme_colors, cx) {
+        // File Deleted Overlay
+        let element = match ui::render_file_deleted_overlay(self, theme_colors) {
             Some(overlay) => element.child(overlay),
             None => element,
         };
 
+        // Export and Message Toasts
+        let element = match ui::render_notifications(self, theme_colors, cx) {
+            Some(notifications) => element.child(notifications),
+            None => element,
+        };
+
         // Fuzzy File Finder Overlay
+        let element = match ui::render_plugin_overlays(self, theme_colors) {
+            Some(overlays) => element.child(overlays),
+            None => element,
+        };
+
+        let element = match ui::render_open_file_error(self, theme_colors, cx) {
+            Some(error) => element.child(error),
+            None => element,
+        };
+
         let element = match ui::render_file_finder(self, theme_colors, cx) {
             Some(overlay) => element.child(overlay),
             None => element,
         };
 
-        // PDF Overwrite Confirmation Overlay
-        let element = match ui::render_pdf_overwrite_confirm(self, theme_colors) {
+        // Project Search Overlay
+        let element = match ui::render_project_search(self, theme_colors, cx) {
+            Some(overlay) => element.child(overlay),
+            None => element,
+        };
+
+        // TOC Sidebar and Toggle Button (hidden in peek mode)
+        let element = match self.peek_mode {
+            true => element,
+            false => {
+                let element = match ui::render_toc_sidebar(self, theme_colors, cx) {
+                    Some(sidebar) => element.child(sidebar),
+                    None => element,
+                };
+                let element = match ui::render_file_tree(self, theme_colors, cx) {
+                    Some(sidebar) => element.child(sidebar),
+                    None => element,
+                };
+                let element = element.child(ui::render_toc_toggle_button(self, theme_colors, cx));
+                match ui::render_toc_menu(self, theme_colors, cx) {
+                    Some(menu) => element.child(menu),
+                    None => element,
+                }
+            }
+        };
+
+        // About Overlay
+        let element = match ui::render_about_overlay(self, theme_colors, cx) {
             Some(overlay) => element.child(overlay),
             None => element,
         };
 
-        // TOC Sidebar
-        let element = match ui::render_toc_sidebar(self, theme_colors, cx) {
-            Some(sidebar) => element.child(sidebar),
+        // First-Run Tour
+        let element = match ui::render_tour(self, theme_colors, cx) {
+            Some(tour) => element.child(tour),
             None => element,
         };
 
-        // TOC Toggle Button
-        let element = element.child(ui::render_toc_toggle_button(self, cx));
+        // Confirmation Dialog, above every other overlay
+        let element = match ui::render_confirm_dialog(self, theme_colors, cx) {
+            Some(dialog) => element.child(dialog),
+            None => element,
+        };
 
-        for path in missing_images {
+        for path in self.image_cache.take_requested() {
             self.load_image(path, window, cx);
         }
 
@@ -1334,7 +3906,7 @@ impl Render for MarkdownViewer {
             self.trigger_pdf_export = false;
 
             // Generate output path from markdown file path
-            let pdf_path = self.markdown_file_path.with_extension("pdf");
+            let pdf_path = self.document().path.with_extension("pdf");
 
             // Check if file already exists
             match pdf_path.exists() {
@@ -1344,28 +3916,17 @@ impl Render for MarkdownViewer {
                         "PDF file already exists, prompting for confirmation: {:?}",
                         pdf_path
                     );
-                    self.show_pdf_overwrite_confirm = true;
-                    self.pdf_overwrite_path = Some(pdf_path);
+                    self.confirm_dialog = Some(ConfirmDialog::overwrite_pdf(pdf_path));
                     cx.notify();
                 }
                 false => {
                     // File doesn't exist, export directly
-                    self.perform_pdf_export(&pdf_path);
+                    self.export_to(ExportFormat::Pdf, &pdf_path);
                     cx.notify();
                 }
             }
         }
 
-        // Handle PDF overwrite confirmation
-        if let Some(pdf_path) = self.pdf_overwrite_path.clone()
-            && !self.show_pdf_overwrite_confirm
-        {
-            // User confirmed, perform export
-            self.perform_pdf_export(&pdf_path);
-            self.pdf_overwrite_path = None;
-            cx.notify();
-        }
-
         element
     }
 }
@@ -0,0 +1,133 @@
+//! Viewer event subscriptions for embedders
+//!
+//! Host applications embedding [`MarkdownViewer`](crate::MarkdownViewer)
+//! subscribe with [`MarkdownViewer::subscribe`](crate::MarkdownViewer::subscribe)
+//! to react to user actions. Subscribers run synchronously in subscription
+//! order; returning [`Propagation::Stop`] from a `LinkClicked` handler
+//! intercepts the click so the viewer does not open the URL itself.
+
+use std::path::PathBuf;
+
+/// Something that happened in the viewer
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewerEvent {
+    /// A document was opened or reloaded from disk
+    DocumentLoaded { path: PathBuf },
+    /// A link was clicked or activated from the keyboard
+    LinkClicked { url: String },
+    /// The scroll position changed
+    ScrollChanged { scroll_y: f32, line: usize },
+    /// The search query changed; an empty query means search was closed
+    SearchChanged { query: String, match_count: usize },
+    /// A PDF, HTML or EPUB export finished, with the error message on failure
+    ExportFinished {
+        path: PathBuf,
+        result: Result<(), String>,
+    },
+}
+
+/// Whether later subscribers and the default action still run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Propagation {
+    Continue,
+    Stop,
+}
+
+/// Handle for removing a subscription again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Callback = Box<dyn FnMut(&ViewerEvent) -> Propagation>;
+
+/// The subscribers of one viewer
+#[derive(Default)]
+pub struct Subscribers {
+    next_id: u64,
+    callbacks: Vec<(SubscriptionId, Callback)>,
+}
+
+impl Subscribers {
+    pub fn subscribe(
+        &mut self,
+        callback: impl FnMut(&ViewerEvent) -> Propagation + 'static,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.callbacks.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Remove a subscription; returns whether it existed
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.callbacks.len();
+        self.callbacks.retain(|(existing, _)| *existing != id);
+        self.callbacks.len() != before
+    }
+
+    /// Deliver `event` to each subscriber until one stops propagation
+    pub fn emit(&mut self, event: &ViewerEvent) -> Propagation {
+        for (_, callback) in &mut self.callbacks {
+            if callback(event) == Propagation::Stop {
+                return Propagation::Stop;
+            }
+        }
+        Propagation::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn link(url: &str) -> ViewerEvent {
+        ViewerEvent::LinkClicked {
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn stop_skips_later_subscribers() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut subscribers = Subscribers::default();
+        let first = seen.clone();
+        subscribers.subscribe(move |event| {
+            first.borrow_mut().push("first");
+            match event {
+                ViewerEvent::LinkClicked { url } if url.starts_with("app:") => Propagation::Stop,
+                _ => Propagation::Continue,
+            }
+        });
+        let second = seen.clone();
+        subscribers.subscribe(move |_| {
+            second.borrow_mut().push("second");
+            Propagation::Continue
+        });
+
+        assert_eq!(subscribers.emit(&link("app:settings")), Propagation::Stop);
+        assert_eq!(*seen.borrow(), vec!["first"]);
+        assert_eq!(
+            subscribers.emit(&link("https://example.com")),
+            Propagation::Continue
+        );
+        assert_eq!(*seen.borrow(), vec!["first", "first", "second"]);
+    }
+
+    #[test]
+    fn unsubscribed_callbacks_are_not_called() {
+        let calls = Rc::new(RefCell::new(0));
+        let mut subscribers = Subscribers::default();
+        let counter = calls.clone();
+        let id = subscribers.subscribe(move |_| {
+            *counter.borrow_mut() += 1;
+            Propagation::Continue
+        });
+
+        subscribers.emit(&link("a"));
+        assert!(subscribers.unsubscribe(id));
+        assert!(!subscribers.unsubscribe(id));
+        subscribers.emit(&link("b"));
+        assert_eq!(*calls.borrow(), 1);
+    }
+}
@@ -0,0 +1,228 @@
+//! Supervision of work on the background runtime
+//!
+//! Image fetches and the project search run on the Tokio runtime next to the
+//! UI. A job that returned an error or panicked used to end up in the log
+//! only; [`supervise`] and [`supervise_blocking`] run a job there, retry it
+//! a given number of times after an error, and hand back a [`TaskFailure`]
+//! naming the task so the viewer can report it as a toast. Panics are not
+//! retried, since running the same job again would most likely panic again.
+
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::runtime::Runtime;
+use tracing::{debug, warn};
+
+/// Pause before the first retry; each further retry waits this much longer
+pub const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// A background job that did not produce a result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskFailure {
+    /// What the job was doing, e.g. "Loading image https://…"
+    pub task: String,
+    /// Times the job was run
+    pub attempts: usize,
+    /// Whether the last attempt panicked rather than returning an error
+    pub panicked: bool,
+    pub message: String,
+}
+
+impl fmt::Display for TaskFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.panicked, self.attempts) {
+            (true, _) => write!(f, "{} crashed: {}", self.task, self.message),
+            (false, 1) => write!(f, "{} failed: {}", self.task, self.message),
+            (false, attempts) => write!(
+                f,
+                "{} failed after {} attempts: {}",
+                self.task, attempts, self.message
+            ),
+        }
+    }
+}
+
+/// Text of a panic payload (the `panic!` message when it has one)
+pub fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+/// What became of one attempt: the value, or whether it panicked and why
+type Attempt<T> = std::result::Result<T, (bool, String)>;
+
+/// Sort a finished attempt into its value or its failure
+fn settle<T>(joined: std::result::Result<Result<T>, tokio::task::JoinError>) -> Attempt<T> {
+    match joined {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err((false, format!("{:#}", e))),
+        Err(e) if e.is_panic() => Err((true, panic_message(e.into_panic()))),
+        Err(e) => Err((false, e.to_string())),
+    }
+}
+
+/// Run the future made by `job` on `runtime`, retrying up to `retries`
+/// times after an error
+pub async fn supervise<T, F, Fut>(
+    runtime: &Runtime,
+    task: &str,
+    retries: usize,
+    job: F,
+) -> std::result::Result<T, TaskFailure>
+where
+    T: Send + 'static,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>> + Send + 'static,
+{
+    let mut attempts = 0;
+    loop {
+        let delay = RETRY_DELAY * attempts as u32;
+        attempts += 1;
+        let future = job();
+        let joined = runtime
+            .spawn(async move {
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                future.await
+            })
+            .await;
+        match settle(joined) {
+            Ok(value) => return Ok(value),
+            Err((panicked, message)) => {
+                if let Some(failure) = give_up(task, attempts, retries, panicked, message) {
+                    return Err(failure);
+                }
+            }
+        }
+    }
+}
+
+/// Run the blocking `job` on `runtime`'s blocking pool, retrying up to
+/// `retries` times after an error
+pub async fn supervise_blocking<T, F>(
+    runtime: &Runtime,
+    task: &str,
+    retries: usize,
+    job: F,
+) -> std::result::Result<T, TaskFailure>
+where
+    T: Send + 'static,
+    F: Fn() -> Result<T> + Clone + Send + 'static,
+{
+    let mut attempts = 0;
+    loop {
+        let delay = RETRY_DELAY * attempts as u32;
+        attempts += 1;
+        let job = job.clone();
+        let joined = runtime
+            .spawn_blocking(move || {
+                std::thread::sleep(delay);
+                job()
+            })
+            .await;
+        match settle(joined) {
+            Ok(value) => return Ok(value),
+            Err((panicked, message)) => {
+                if let Some(failure) = give_up(task, attempts, retries, panicked, message) {
+                    return Err(failure);
+                }
+            }
+        }
+    }
+}
+
+/// Log a failed attempt; the failure to report once no retry is left
+fn give_up(
+    task: &str,
+    attempts: usize,
+    retries: usize,
+    panicked: bool,
+    message: String,
+) -> Option<TaskFailure> {
+    warn!(
+        "{} failed (attempt {}, panicked: {}): {}",
+        task, attempts, panicked, message
+    );
+    match panicked || attempts > retries {
+        true => Some(TaskFailure {
+            task: task.to_string(),
+            attempts,
+            panicked,
+            message,
+        }),
+        false => {
+            debug!("Retrying {}", task);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn errors_are_retried_until_the_job_succeeds() {
+        let runtime = Runtime::new().unwrap();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let job = {
+            let runs = runs.clone();
+            move || {
+                let runs = runs.clone();
+                async move {
+                    match runs.fetch_add(1, Ordering::SeqCst) {
+                        0 => anyhow::bail!("connection reset"),
+                        _ => Ok(7),
+                    }
+                }
+            }
+        };
+        let result = runtime.block_on(supervise(&runtime, "Loading image", 1, job));
+        assert_eq!(result, Ok(7));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        let failure = runtime
+            .block_on(supervise(&runtime, "Loading image", 1, || async {
+                Err::<(), _>(anyhow::anyhow!("not found"))
+            }))
+            .unwrap_err();
+        assert_eq!(failure.attempts, 2);
+        assert_eq!(
+            failure.to_string(),
+            "Loading image failed after 2 attempts: not found"
+        );
+    }
+
+    #[test]
+    fn panics_are_reported_without_retrying() {
+        let runtime = Runtime::new().unwrap();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let job = {
+            let runs = runs.clone();
+            move || -> Result<()> {
+                runs.fetch_add(1, Ordering::SeqCst);
+                panic!("index out of bounds")
+            }
+        };
+        let failure = runtime
+            .block_on(supervise_blocking(&runtime, "Searching files", 3, job))
+            .unwrap_err();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(failure.panicked);
+        assert_eq!(
+            failure.to_string(),
+            "Searching files crashed: index out of bounds"
+        );
+    }
+}
@@ -7,41 +7,62 @@ pub mod config;
 mod internal;
 
 // Re-export public types and functions
+pub use internal::archive;
+pub use internal::block_map::{BlockExtent, BlockMap};
+pub use internal::contrast;
 pub use internal::events;
 pub use internal::file_handling::{
-    is_supported_extension, load_markdown_content, resolve_image_path, resolve_markdown_file_path,
+    LaunchTarget, LoadGuard, LoadGuardError, canonical_path, expand_file_arguments, in_base_dir,
+    is_supported_extension, load_markdown_content, load_markdown_content_unchecked,
+    render_mode_for_path, resolve_image_path, resolve_launch_target, resolve_link_target,
+    resolve_markdown_file_path,
 };
+pub use internal::file_queue::FileQueue;
 pub use internal::file_watcher::{FileWatcherEvent, start_watching};
+pub use internal::filter::FilterView;
+pub use internal::history::{HistoryEntry, NavigationHistory};
+pub use internal::inspect;
+pub use internal::link_rules;
+pub use internal::lint;
+pub use internal::math;
+pub use internal::menu;
+pub use internal::plugin;
+pub use internal::render;
 pub use internal::rendering::{
-    render_markdown_ast, render_markdown_ast_with_loader, render_markdown_ast_with_search,
+    LinkHandler, link_anchor, open_in_browser, render_markdown_ast,
+    render_markdown_ast_with_loader, render_markdown_ast_with_search, render_plain_text,
 };
-pub use internal::scroll::ScrollState;
-pub use internal::search::SearchState;
+pub use internal::scroll::{HeldScroll, ScrollState};
+pub use internal::search::{SearchState, SearchWrap};
 pub use internal::style::*;
+pub use internal::tabs::DocumentState;
+pub use internal::text_highlight::MatchColors;
+pub use internal::transform;
 pub use internal::ui;
-pub use internal::viewer::{ImageState, MarkdownViewer, WatcherState};
+pub use internal::viewer::{MarkdownViewer, WatcherState};
+pub use internal::viewer_events::{Propagation, SubscriptionId, ViewerEvent};
 
 // Re-export internal helpers that are useful to binary targets (controlled exposure)
 pub use internal::image::{rasterize_svg_to_dynamic_image, rgba_to_bgra};
 // Expose high-level image loading helper so binary targets can call it
 // without reaching into private `internal` modules.
-pub use internal::image_loader::fetch_and_decode_image;
+pub use internal::image_loader::{
+    DefaultImageProvider, ImageCache, ImageFuture, ImageProvider, ImageState,
+    fetch_and_decode_image,
+};
 
 // Re-export help overlay builders so binary / integration code can compose the
 // help UI without reaching into the private `internal` module tree.
 pub use internal::help_overlay::{help_panel, shortcut_row};
 
 // Re-export theme system for binary initialization
-pub use internal::theme::{init as init_themes, registry as theme_registry};
+pub use internal::theme::{ActiveTheme, init as init_themes, registry as theme_registry};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use internal::scroll::ScrollState;
-    use std::sync::Mutex;
-
-    // Mutex to serialize tests that manipulate files
-    static FILE_TEST_LOCK: Mutex<()> = Mutex::new(());
+    use internal::scroll::{HeldScroll, ScrollState};
+    use proptest::prelude::*;
 
     // ---- Scroll State Tests ------------------------------------------------
 
@@ -114,6 +135,46 @@ mod tests {
         assert_eq!(state.scroll_y, 500.0);
     }
 
+    #[test]
+    fn smooth_scroll_eases_to_its_target() {
+        use config::Easing;
+        let mut state = ScrollState::new();
+        state.set_max_scroll(2000.0, 500.0);
+        state.smooth_scroll_to(1000.0);
+        assert!(state.is_animating());
+        assert_eq!(state.destination(), 1000.0);
+
+        // Half the time covers more than half the way when easing out
+        assert!(state.update_smooth_scroll(0.075, 0.15, Easing::EaseOut));
+        assert!(state.scroll_y > 500.0 && state.scroll_y < 1000.0);
+        assert!(!state.update_smooth_scroll(0.075, 0.15, Easing::EaseOut));
+        assert_eq!(state.scroll_y, 1000.0);
+
+        // Direct scrolling stops an animation where it is
+        state.smooth_scroll_to(0.0);
+        state.update_smooth_scroll(0.05, 0.15, Easing::Linear);
+        state.scroll_down(10.0);
+        assert!(!state.is_animating());
+        assert_eq!(state.destination(), state.scroll_y);
+    }
+
+    #[test]
+    fn held_scroll_accelerates_until_released() {
+        let mut held = HeldScroll::default();
+        assert_eq!(held.advance(0.1, 600.0, 2.0), 0.0);
+
+        assert!(held.start("j", 1.0));
+        assert!(!held.start("j", 1.0));
+        let first = held.advance(0.1, 600.0, 2.0);
+        let second = held.advance(0.1, 600.0, 2.0);
+        assert!(first > 0.0 && second > first);
+
+        held.release("k");
+        assert!(held.is_active());
+        held.release("j");
+        assert!(!held.is_active());
+    }
+
     #[test]
     fn content_height_estimation_constants_work() {
         // Test that the constants produce reasonable values
@@ -136,6 +197,155 @@ mod tests {
         assert_eq!(DEFAULT_VIEWPORT_HEIGHT, 800.0);
     }
 
+    // ---- Scroll State Property Tests ---------------------------------------
+
+    /// A call the viewer makes on its scroll state
+    #[derive(Debug, Clone)]
+    enum ScrollOp {
+        Up(f32),
+        Down(f32),
+        PageUp,
+        PageDown,
+        Top,
+        Bottom,
+        /// Window resize: new viewport height
+        Resize(f32),
+        /// Zoom: content height scaled by a factor
+        Zoom(f32),
+        /// Reload: the saved position put back, then the bounds recomputed
+        /// for the new content height
+        Reload {
+            scroll_y: f32,
+            content: f32,
+        },
+        SmoothTo(f32),
+        Animate {
+            delta_time: f32,
+            duration: f32,
+            easing: config::Easing,
+        },
+        Drag {
+            from: f32,
+            to: f32,
+        },
+    }
+
+    /// Mostly ordinary values, with the edge cases that have produced NaN
+    fn amount() -> impl Strategy<Value = f32> {
+        prop_oneof![
+            8 => -5_000.0f32..50_000.0,
+            1 => Just(0.0f32),
+            1 => Just(f32::NAN),
+            1 => Just(f32::INFINITY),
+            1 => Just(f32::NEG_INFINITY),
+        ]
+    }
+
+    fn easing() -> impl Strategy<Value = config::Easing> {
+        prop_oneof![
+            Just(config::Easing::Linear),
+            Just(config::Easing::EaseOut),
+            Just(config::Easing::EaseInOut),
+        ]
+    }
+
+    fn scroll_op() -> impl Strategy<Value = ScrollOp> {
+        prop_oneof![
+            amount().prop_map(ScrollOp::Up),
+            amount().prop_map(ScrollOp::Down),
+            Just(ScrollOp::PageUp),
+            Just(ScrollOp::PageDown),
+            Just(ScrollOp::Top),
+            Just(ScrollOp::Bottom),
+            amount().prop_map(ScrollOp::Resize),
+            amount().prop_map(ScrollOp::Zoom),
+            (amount(), amount())
+                .prop_map(|(scroll_y, content)| ScrollOp::Reload { scroll_y, content }),
+            amount().prop_map(ScrollOp::SmoothTo),
+            (amount(), amount(), easing()).prop_map(|(delta_time, duration, easing)| {
+                ScrollOp::Animate {
+                    delta_time,
+                    duration,
+                    easing,
+                }
+            }),
+            (amount(), amount()).prop_map(|(from, to)| ScrollOp::Drag { from, to }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn scroll_y_stays_within_bounds_and_is_never_nan(
+            ops in prop::collection::vec(scroll_op(), 1..64)
+        ) {
+            let mut state = ScrollState::new();
+            let (mut content, mut viewport) = (5_000.0f32, 800.0f32);
+            state.set_max_scroll(content, viewport);
+
+            for op in ops {
+                match op.clone() {
+                    ScrollOp::Up(amount) => state.scroll_up(amount),
+                    ScrollOp::Down(amount) => state.scroll_down(amount),
+                    ScrollOp::PageUp => state.page_up(viewport),
+                    ScrollOp::PageDown => state.page_down(viewport),
+                    ScrollOp::Top => state.scroll_to_top(),
+                    ScrollOp::Bottom => state.scroll_to_bottom(),
+                    ScrollOp::Resize(height) => {
+                        viewport = height;
+                        state.set_max_scroll(content, viewport);
+                    }
+                    ScrollOp::Zoom(factor) => {
+                        content *= factor;
+                        state.set_max_scroll(content, viewport);
+                    }
+                    ScrollOp::Reload { scroll_y, content: height } => {
+                        content = height;
+                        state.scroll_y = scroll_y;
+                        state.set_max_scroll(content, viewport);
+                    }
+                    ScrollOp::SmoothTo(target) => state.smooth_scroll_to(target),
+                    ScrollOp::Animate { delta_time, duration, easing } => {
+                        state.update_smooth_scroll(delta_time, duration, easing);
+                    }
+                    ScrollOp::Drag { from, to } => {
+                        state.start_drag(from);
+                        state.update_drag(to, viewport);
+                        state.stop_drag();
+                    }
+                }
+
+                prop_assert!(!state.scroll_y.is_nan(), "NaN after {:?}", op);
+                prop_assert!(
+                    state.max_scroll_y.is_finite() && state.max_scroll_y >= 0.0,
+                    "max_scroll_y {} after {:?}", state.max_scroll_y, op
+                );
+                prop_assert!(
+                    (0.0..=state.max_scroll_y).contains(&state.scroll_y),
+                    "scroll_y {} outside [0, {}] after {:?}", state.scroll_y, state.max_scroll_y, op
+                );
+                prop_assert!(!state.destination().is_nan(), "NaN destination after {:?}", op);
+            }
+        }
+
+        #[test]
+        fn animations_end_on_their_clamped_target(
+            target in amount(),
+            content in 0.0f32..100_000.0,
+            easing in easing(),
+        ) {
+            let mut state = ScrollState::new();
+            state.set_max_scroll(content, 800.0);
+            state.smooth_scroll_to(target);
+            let expected = state.destination();
+            // A generous number of frames always reaches the end
+            for _ in 0..100 {
+                state.update_smooth_scroll(0.016, 0.15, easing);
+            }
+            prop_assert!(!state.is_animating());
+            prop_assert_eq!(state.scroll_y, expected);
+        }
+    }
+
     // ---- File Extension Tests ----------------------------------------------
 
     #[test]
@@ -172,193 +382,6 @@ mod tests {
         assert!(!is_supported_extension("file", &supported)); // No extension
     }
 
-    #[test]
-    fn resolve_with_unsupported_extension() {
-        let supported = vec!["md".to_string(), "markdown".to_string(), "txt".to_string()];
-
-        // Create a file with unsupported extension
-        std::fs::write("test.pdf", "content").expect("Failed to create test file");
-
-        let result = resolve_markdown_file_path(Some("test.pdf"), &supported);
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err().to_string();
-        assert!(err_msg.contains("Unsupported file format"));
-        assert!(err_msg.contains("md, markdown, txt"));
-
-        // Clean up
-        std::fs::remove_file("test.pdf").ok();
-    }
-
-    #[test]
-    fn resolve_with_markdown_extension() {
-        let supported = vec!["md".to_string(), "markdown".to_string(), "txt".to_string()];
-
-        // Create a .markdown file
-        std::fs::write("test.markdown", "# Test").expect("Failed to create test file");
-
-        let result = resolve_markdown_file_path(Some("test.markdown"), &supported);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "test.markdown");
-
-        // Clean up
-        std::fs::remove_file("test.markdown").ok();
-    }
-
-    #[test]
-    fn resolve_with_txt_extension() {
-        let supported = vec!["md".to_string(), "markdown".to_string(), "txt".to_string()];
-
-        // Create a .txt file
-        std::fs::write("notes.txt", "# Notes").expect("Failed to create test file");
-
-        let result = resolve_markdown_file_path(Some("notes.txt"), &supported);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "notes.txt");
-
-        // Clean up
-        std::fs::remove_file("notes.txt").ok();
-    }
-
-    #[test]
-    fn resolve_markdown_file_path_with_valid_file() {
-        let supported = vec!["md".to_string(), "markdown".to_string(), "txt".to_string()];
-
-        // Create a temporary file for testing
-        let test_content = "# Test\nThis is a test file.";
-        std::fs::write("test_file.md", test_content).expect("Failed to create test file");
-
-        let result = resolve_markdown_file_path(Some("test_file.md"), &supported);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "test_file.md");
-
-        // Clean up
-        std::fs::remove_file("test_file.md").ok();
-    }
-
-    #[test]
-    fn resolve_markdown_file_path_with_nonexistent_file() {
-        let supported = vec!["md".to_string(), "markdown".to_string(), "txt".to_string()];
-        let result = resolve_markdown_file_path(Some("nonexistent_file.md"), &supported);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("File not found"));
-    }
-
-    #[test]
-    fn resolve_markdown_file_path_with_no_path_and_readme_exists() {
-        let _lock = FILE_TEST_LOCK.lock().unwrap();
-
-        // Backup existing files if they exist
-        let readme_backup = std::fs::read_to_string("README.md").ok();
-        let todo_backup = std::fs::read_to_string("TODO.md").ok();
-
-        // Create test files
-        let readme_content = "# README\nProject documentation.";
-        std::fs::write("README.md", readme_content).expect("Failed to create test README");
-
-        let todo_content = "# TODO\nSome todo items.";
-        std::fs::write("TODO.md", todo_content).expect("Failed to create test TODO");
-
-        let supported = vec!["md".to_string(), "markdown".to_string(), "txt".to_string()];
-        let result = resolve_markdown_file_path(None, &supported);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "README.md");
-
-        // Restore original files or clean up
-        match readme_backup {
-            Some(content) => {
-                std::fs::write("README.md", content).ok();
-            }
-            None => {
-                std::fs::remove_file("README.md").ok();
-            }
-        }
-        match todo_backup {
-            Some(content) => {
-                std::fs::write("TODO.md", content).ok();
-            }
-            None => {
-                std::fs::remove_file("TODO.md").ok();
-            }
-        }
-    }
-
-    #[test]
-    fn resolve_markdown_file_path_with_no_path_and_todo_fallback() {
-        let _lock = FILE_TEST_LOCK.lock().unwrap();
-
-        // Backup existing files if they exist
-        let readme_backup = std::fs::read_to_string("README.md").ok();
-        let todo_backup = std::fs::read_to_string("TODO.md").ok();
-
-        // Ensure README.md doesn't exist for this test
-        std::fs::remove_file("README.md").ok();
-
-        let todo_content = "# TODO\nSome todo items.";
-        std::fs::write("TODO.md", todo_content).expect("Failed to create test TODO");
-
-        let supported = vec!["md".to_string(), "markdown".to_string(), "txt".to_string()];
-        let result = resolve_markdown_file_path(None, &supported);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "TODO.md");
-
-        // Restore original files or clean up
-        if let Some(content) = readme_backup {
-            std::fs::write("README.md", content).ok();
-        }
-        match todo_backup {
-            Some(content) => {
-                std::fs::write("TODO.md", content).ok();
-            }
-            None => {
-                std::fs::remove_file("TODO.md").ok();
-            }
-        }
-    }
-
-    #[test]
-    fn resolve_markdown_file_path_with_no_path_and_no_defaults() {
-        let _lock = FILE_TEST_LOCK.lock().unwrap();
-
-        // Backup existing files
-        let readme_backup = std::fs::read_to_string("README.md").ok();
-        let todo_backup = std::fs::read_to_string("TODO.md").ok();
-
-        // Ensure both README.md and TODO.md don't exist
-        std::fs::remove_file("README.md").ok();
-        std::fs::remove_file("TODO.md").ok();
-
-        let supported = vec!["md".to_string(), "markdown".to_string(), "txt".to_string()];
-        let result = resolve_markdown_file_path(None, &supported);
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Default files README.md and TODO.md not found")
-        );
-
-        // Restore original files
-        if let Some(content) = readme_backup {
-            std::fs::write("README.md", content).ok();
-        }
-        if let Some(content) = todo_backup {
-            std::fs::write("TODO.md", content).ok();
-        }
-    }
-
-    #[test]
-    fn load_markdown_content_success() {
-        let test_content = "# Test Content\nThis is test markdown.";
-        std::fs::write("test_load.md", test_content).expect("Failed to create test file");
-
-        let result = load_markdown_content("test_load.md");
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), test_content);
-
-        // Clean up
-        std::fs::remove_file("test_load.md").ok();
-    }
-
     #[test]
     fn load_markdown_content_failure() {
         let result = load_markdown_content("nonexistent_file_xyz.md");
@@ -521,6 +544,16 @@ mod tests {
         assert_eq!(MarkdownViewer::parse_line_number("1.5"), None); // Decimals not allowed
     }
 
+    #[test]
+    fn parse_percent_accepts_zero_to_hundred() {
+        use internal::viewer::MarkdownViewer;
+        assert_eq!(MarkdownViewer::parse_percent("0"), Some(0));
+        assert_eq!(MarkdownViewer::parse_percent(" 50% "), Some(50));
+        assert_eq!(MarkdownViewer::parse_percent("100"), Some(100));
+        assert_eq!(MarkdownViewer::parse_percent("101"), None);
+        assert_eq!(MarkdownViewer::parse_percent(""), None);
+    }
+
     #[test]
     fn validate_line_number_logic() {
         // Test the line counting logic that validate_line_number uses
@@ -583,12 +616,12 @@ mod tests {
         let mut config = AppConfig::default();
         config.theme.theme = "Zoegi Dark".to_string();
 
-        let path = "test_theme_persist.ron";
-        config.save_to_file(path).expect("Failed to save config");
+        let path = std::env::temp_dir().join("mdv_theme_persist_test.ron");
+        config.save_to_file(&path).expect("Failed to save config");
 
-        let loaded = AppConfig::load_from_file(path).expect("Failed to load config");
+        let loaded = AppConfig::load_from_file(&path).expect("Failed to load config");
         assert_eq!(loaded.theme.theme, "Zoegi Dark");
 
-        fs::remove_file(path).ok();
+        fs::remove_file(&path).ok();
     }
 }
@@ -1,25 +1,80 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use gpui::{App, AppContext, Application, WindowOptions};
+use clap::{Parser, Subcommand};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, terminal};
+use gpui::{
+    App, AppContext, Application, WindowBounds, WindowDecorations, WindowKind, WindowOptions, px,
+    size,
+};
 use markdown_viewer::{
-    MarkdownViewer, WatcherState, config::AppConfig, load_markdown_content,
-    resolve_markdown_file_path, start_watching,
+    ActiveTheme, FileQueue, FileWatcherEvent, LaunchTarget, LoadGuardError, MarkdownViewer,
+    WatcherState, canonical_path,
+    config::{AppConfig, RenderMode},
+    expand_file_arguments, inspect, link_rules, lint, load_markdown_content, menu, render,
+    render::terminal::WatchScroll,
+    render_mode_for_path, resolve_launch_target, resolve_markdown_file_path, start_watching,
 };
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::mpsc::TryRecvError;
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
 #[derive(Parser)]
 #[command(name = "markdown_viewer")]
 #[command(about = "A simple markdown viewer")]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Path to the markdown file to view
-    file: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Markdown files, glob patterns or a directory to view
+    files: Vec<String>,
+
+    /// Open a borderless quick-look window that closes on Escape or focus loss
+    #[arg(long)]
+    peek: bool,
+
+    /// Start fresh instead of restoring the previous session
+    #[arg(long)]
+    no_restore: bool,
+
+    /// Print the document to the terminal with ANSI styling instead of opening a window
+    #[arg(long)]
+    tty: bool,
+
+    /// With --tty, re-render to the terminal whenever the file changes
+    #[arg(long, requires = "tty")]
+    watch: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the document structure: headings, links, images, code blocks and word count
+    Inspect {
+        /// Markdown file to inspect
+        file: String,
+
+        /// Emit JSON instead of a readable summary
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check documents for broken links, missing images, duplicate anchors and
+    /// heading level skips, and the configured theme for unreadable colors;
+    /// exits with status 1 when any issue is found
+    Lint {
+        /// Markdown files or glob patterns to check
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
 }
 
 fn main() -> Result<()> {
-    // Initialize tracing subscriber for logging
+    // Initialize tracing subscriber for logging; logs go to stderr so `--tty`
+    // output on stdout can be piped into a pager
     tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
@@ -46,22 +101,116 @@ fn main() -> Result<()> {
 
     debug!("Configuration loaded: {:?}", config);
 
+    load_plugins(&config.plugins);
+
     let args = Args::parse();
+    let supported_extensions = config.files.all_extensions();
+
+    match &args.command {
+        Some(Command::Inspect { file, json }) => return print_inspection(file, *json),
+        Some(Command::Lint { files }) => {
+            return lint_files(files, &config.theme.theme, &supported_extensions);
+        }
+        None => {}
+    }
 
-    // Resolve the file path using our new function
-    let file_path =
-        resolve_markdown_file_path(args.file.as_deref(), &config.files.supported_extensions)
-            .context("Failed to resolve markdown file path")?;
+    if args.tty {
+        return print_to_terminal(&args.files, &config, &supported_extensions, args.watch);
+    }
 
-    // Load the markdown content
-    let markdown_input =
-        load_markdown_content(&file_path).context("Failed to load markdown content")?;
+    // Without file arguments, pick up the previous session if its file still exists
+    let session = match args.files.is_empty() && !args.no_restore && !args.peek {
+        true => config
+            .session
+            .clone()
+            .filter(|session| Path::new(&session.file).is_file()),
+        false => None,
+    };
 
-    info!(
-        "Loaded file: {} ({} bytes)",
-        file_path,
-        markdown_input.len()
-    );
+    // Expand glob arguments; several files are opened as a sequential queue
+    let file_args = match &session {
+        Some(session) => {
+            info!("Restoring session: {}", session.file);
+            let queue = session
+                .queue
+                .iter()
+                .filter(|file| Path::new(file).is_file())
+                .cloned()
+                .collect::<Vec<_>>();
+            match queue.iter().any(|file| file == &session.file) {
+                true => queue,
+                false => vec![session.file.clone()],
+            }
+        }
+        None => expand_file_arguments(&args.files, &supported_extensions)
+            .context("Failed to expand file arguments")?,
+    };
+    let queued_files = match file_args.len() > 1 {
+        true => file_args
+            .iter()
+            .map(|file| {
+                resolve_markdown_file_path(
+                    Path::new("."),
+                    Some(file),
+                    &config.files.default_files,
+                    &supported_extensions,
+                )
+                .map(|resolved| canonical_path(Path::new(&resolved)))
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to resolve markdown file path")?,
+        false => Vec::new(),
+    };
+
+    // Resolve the file (or directory) to open
+    let target = resolve_launch_target(
+        Path::new("."),
+        session
+            .as_ref()
+            .map(|session| session.file.as_str())
+            .or(file_args.first().map(String::as_str)),
+        &config.files.default_files,
+        &supported_extensions,
+    )
+    .context("Failed to resolve markdown file path")?;
+
+    // Load the markdown content; a directory without an index starts empty
+    // and opens the file finder scoped to it, an oversized or binary file
+    // starts empty and asks before opening, and an unreadable file starts on
+    // the error page
+    let mut load_error = None;
+    let (file_path, markdown_input, finder_dir, pending_open) = match target {
+        LaunchTarget::File(file_path) => match load_markdown_content(&file_path) {
+            Ok(markdown_input) => {
+                info!(
+                    "Loaded file: {} ({} bytes)",
+                    file_path,
+                    markdown_input.len()
+                );
+                (file_path, markdown_input, None, None)
+            }
+            Err(e) => match e.downcast::<LoadGuardError>() {
+                Ok(guard) => {
+                    warn!("{}; asking for confirmation", guard);
+                    (file_path, String::new(), None, Some(guard))
+                }
+                Err(e) => {
+                    warn!("Failed to load markdown content: {:#}", e);
+                    load_error = Some(format!("{:#}", e));
+                    (file_path, String::new(), None, None)
+                }
+            },
+        },
+        LaunchTarget::Directory(dir) => {
+            info!("Opening file finder in directory: {:?}", dir);
+            (
+                dir.to_string_lossy().to_string(),
+                String::new(),
+                Some(dir),
+                None,
+            )
+        }
+    };
 
     // Create a dedicated background Tokio runtime for async tasks (image downloads, etc.)
     let bg_rt = Arc::new(
@@ -72,11 +221,11 @@ fn main() -> Result<()> {
     );
 
     // Start file watcher if enabled
-    let (file_watcher_rx, file_watcher) = match config.file_watcher.enabled {
+    let (file_watcher_rx, file_watcher) = match config.file_watcher.enabled && finder_dir.is_none()
+    {
         true => {
-            // Convert to absolute path for file watcher
-            let abs_file_path = std::fs::canonicalize(&file_path)
-                .unwrap_or_else(|_| std::path::PathBuf::from(&file_path));
+            // Convert to canonical path for file watcher
+            let abs_file_path = canonical_path(Path::new(&file_path));
 
             match start_watching(&abs_file_path, config.file_watcher.debounce_ms) {
                 Ok((rx, debouncer)) => {
@@ -102,8 +251,7 @@ fn main() -> Result<()> {
     let config_path = std::path::PathBuf::from("config.ron");
     let (config_watcher_rx, config_watcher) = match config_path.exists() {
         true => {
-            let abs_config_path =
-                std::fs::canonicalize(&config_path).unwrap_or_else(|_| config_path.clone());
+            let abs_config_path = canonical_path(&config_path);
             match start_watching(&abs_config_path, 100) {
                 Ok((rx, debouncer)) => {
                     info!("Config watcher started for: {:?}", abs_config_path);
@@ -123,11 +271,35 @@ fn main() -> Result<()> {
 
     // Run the GUI on the main thread (required by gpui). Background async work will use `bg_rt`.
     Application::new().run(move |app: &mut App| {
+        menu::install(app);
         let window_config = config.clone();
         let file_path_buf = PathBuf::from(file_path.clone());
         let bg_rt = bg_rt.clone();
+        let peek = args.peek;
+        let window_width = config.window.width;
+
+        // Peek mode opens a borderless popup; its height is fitted to the content below
+        let window_options = match peek {
+            true => WindowOptions {
+                window_bounds: Some(WindowBounds::centered(
+                    size(px(window_width), px(config.window.height)),
+                    app,
+                )),
+                titlebar: None,
+                kind: WindowKind::PopUp,
+                is_minimizable: false,
+                window_decorations: Some(WindowDecorations::Client),
+                ..Default::default()
+            },
+            false => WindowOptions::default(),
+        };
+        let max_peek_height = app
+            .primary_display()
+            .map(|display| f32::from(display.bounds().size.height) * 0.8)
+            .unwrap_or(config.window.height);
+
         let window = app
-            .open_window(WindowOptions::default(), move |_, cx| {
+            .open_window(window_options, move |window, cx| {
                 // We can't focus here because we don't have &mut Window
                 cx.new(|cx| {
                     let focus_handle = cx.focus_handle();
@@ -138,7 +310,7 @@ fn main() -> Result<()> {
                         config_watcher,
                     };
 
-                    let viewer = MarkdownViewer::new(
+                    let mut viewer = MarkdownViewer::new(
                         markdown_input.clone(),
                         file_path_buf,
                         window_config,
@@ -146,18 +318,353 @@ fn main() -> Result<()> {
                         focus_handle,
                         watcher_state,
                     );
+                    if let Some(dir) = finder_dir.clone() {
+                        viewer.open_file_finder_in(dir);
+                        // Directories get the file tree sidebar; archives only the finder
+                        if viewer.project_root().is_some() && !peek {
+                            viewer.toggle_file_tree();
+                        }
+                    }
+                    // The launch file counts as opened, like any later load
+                    if finder_dir.is_none()
+                        && pending_open.is_none()
+                        && load_error.is_none()
+                        && !peek
+                    {
+                        viewer.remember_recent_file();
+                    }
+                    if let Some(guard) = &pending_open {
+                        viewer.ask_open_anyway(guard, false);
+                    }
+                    viewer.load_error = load_error.clone();
+                    if !queued_files.is_empty() {
+                        info!("Queued {} files", queued_files.len());
+                        viewer.file_queue = FileQueue::new(queued_files.clone());
+                    }
+                    viewer.restore_file_state();
+                    if let Some(session) = &session {
+                        viewer
+                            .file_queue
+                            .select(&canonical_path(Path::new(&session.file)));
+                        viewer.restore_session(session);
+                    }
+                    viewer.peek_mode = peek;
+                    viewer.start_tour_if_first_run();
+                    match peek {
+                        // Close the peek window as soon as it loses focus; peek
+                        // windows are transient and leave the saved session alone
+                        true => cx
+                            .observe_window_activation(window, |_, window, cx| {
+                                if !window.is_window_active() {
+                                    info!("Peek window lost focus, closing");
+                                    cx.quit();
+                                }
+                            })
+                            .detach(),
+                        false => {
+                            cx.on_app_quit(|viewer: &mut MarkdownViewer, _| {
+                                viewer.shutdown();
+                                async {}
+                            })
+                            .detach();
+                            // Closing the window ends the app; flush its state first
+                            let handle = cx.weak_entity();
+                            window.on_window_should_close(cx, move |_, cx| {
+                                handle.update(cx, |viewer, _| viewer.shutdown()).ok();
+                                true
+                            });
+                        }
+                    }
                     debug!("MarkdownViewer initialized");
                     viewer
                 })
             })
             .unwrap();
 
+        // The viewer has one window; the app ends with it
+        app.on_window_closed(|cx| {
+            if cx.windows().is_empty() {
+                info!("Window closed, quitting");
+                cx.quit();
+            }
+        })
+        .detach();
+
         window
             .update(app, |view, cx, _| {
                 view.focus_handle.focus(cx);
+                if peek {
+                    let height = view.peek_window_height(max_peek_height);
+                    debug!("Fitting peek window to {:.0}px", height);
+                    cx.resize(size(px(window_width), px(height)));
+                }
             })
             .ok();
     });
 
     Ok(())
 }
+
+/// Load the plugin libraries listed in the config
+#[cfg(feature = "plugin-dylib")]
+fn load_plugins(paths: &[String]) {
+    for path in paths {
+        if let Err(e) = markdown_viewer::plugin::load_dylib(Path::new(path)) {
+            warn!("Failed to load plugin: {:?}", e);
+        }
+    }
+}
+
+#[cfg(not(feature = "plugin-dylib"))]
+fn load_plugins(paths: &[String]) {
+    if !paths.is_empty() {
+        warn!(
+            "Ignoring {} configured plugins: built without the `plugin-dylib` feature",
+            paths.len()
+        );
+    }
+}
+
+/// Print the structure of `file` as a summary or as JSON
+fn print_inspection(file: &str, json: bool) -> Result<()> {
+    let content = load_markdown_content(file).context("Failed to load markdown content")?;
+    let summary = inspect::inspect_markdown(&content, Some(Path::new(file)));
+    match json {
+        true => println!(
+            "{}",
+            serde_json::to_string_pretty(&summary).context("Failed to serialize summary")?
+        ),
+        false => print!("{}", summary),
+    }
+    Ok(())
+}
+
+/// Print lint issues for `files` as `file:line: kind: message`, after the
+/// contrast issues of the configured theme
+///
+/// Exits the process with status 1 when any issue is found.
+fn lint_files(files: &[String], theme_name: &str, supported_extensions: &[String]) -> Result<()> {
+    let file_args = expand_file_arguments(files, supported_extensions)
+        .context("Failed to expand file arguments")?;
+    let mut issue_count = 0;
+    for issue in lint::lint_theme(theme_name) {
+        println!("theme '{}': low-contrast: {}", theme_name, issue);
+        issue_count += 1;
+    }
+    for file in &file_args {
+        let content = load_markdown_content(file)
+            .with_context(|| format!("Failed to load markdown content from {}", file))?;
+        for issue in lint::lint_markdown(&content, Path::new(file)) {
+            println!("{}:{}", file, issue);
+            issue_count += 1;
+        }
+    }
+    match issue_count {
+        0 => Ok(()),
+        _ => {
+            eprintln!(
+                "{} issue(s) found in {} file(s) checked",
+                issue_count,
+                file_args.len()
+            );
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Print each file to stdout using the terminal renderer backend
+///
+/// With `watch`, the single file is re-rendered on every change instead.
+fn print_to_terminal(
+    files: &[String],
+    config: &AppConfig,
+    supported_extensions: &[String],
+    watch: bool,
+) -> Result<()> {
+    let file_args = match files.is_empty() {
+        true => vec![
+            resolve_markdown_file_path(
+                Path::new("."),
+                None,
+                &config.files.default_files,
+                supported_extensions,
+            )
+            .context("Failed to resolve markdown file path")?,
+        ],
+        false => expand_file_arguments(files, supported_extensions)
+            .context("Failed to expand file arguments")?,
+    };
+    let theme = ActiveTheme::resolve(&config.theme.theme);
+    link_rules::set_rules(&config.link_rewrites);
+
+    if watch {
+        return match file_args.as_slice() {
+            [file] => watch_in_terminal(file, config, &theme),
+            _ => Err(anyhow::anyhow!("--watch takes a single file")),
+        };
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    for (index, file) in file_args.iter().enumerate() {
+        let output = render_for_terminal(file, config, &theme)?;
+        if index > 0 {
+            writeln!(stdout)?;
+        }
+        write!(stdout, "{}", output)?;
+    }
+    Ok(())
+}
+
+/// Load `file` and render it for the terminal according to its render mode
+fn render_for_terminal(file: &str, config: &AppConfig, theme: &ActiveTheme) -> Result<String> {
+    let content = load_markdown_content(file).context("Failed to load markdown content")?;
+    Ok(
+        match render_mode_for_path(Path::new(file), &config.files.extension_modes) {
+            RenderMode::Markdown => {
+                render::terminal::render_markdown(&content, Some(Path::new(file)), theme)
+            }
+            RenderMode::Plain => content,
+        },
+    )
+}
+
+/// How long watch mode waits for a key before checking the file watcher
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Raw mode on the alternate screen for watch mode, restored when dropped
+struct WatchScreen;
+
+impl WatchScreen {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode().context("Failed to switch the terminal to raw mode")?;
+        let screen = Self;
+        crossterm::execute!(
+            std::io::stdout(),
+            terminal::EnterAlternateScreen,
+            cursor::Hide
+        )?;
+        Ok(screen)
+    }
+}
+
+impl Drop for WatchScreen {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(
+            std::io::stdout(),
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Rows for the document; the last row of the terminal is the status line
+fn watch_height(rows: u16) -> usize {
+    (rows as usize).saturating_sub(1).max(1)
+}
+
+/// The paging action of a key: j/k and the arrows move a line, Space/b and
+/// PageDown/PageUp a page, g/G and Home/End go to either end
+fn watch_scroll_key(code: KeyCode) -> Option<WatchScroll> {
+    match code {
+        KeyCode::Char('j') | KeyCode::Down | KeyCode::Enter => Some(WatchScroll::LineDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(WatchScroll::LineUp),
+        KeyCode::Char(' ') | KeyCode::Char('f') | KeyCode::PageDown => Some(WatchScroll::PageDown),
+        KeyCode::Char('b') | KeyCode::PageUp => Some(WatchScroll::PageUp),
+        KeyCode::Char('g') | KeyCode::Home => Some(WatchScroll::Top),
+        KeyCode::Char('G') | KeyCode::End => Some(WatchScroll::Bottom),
+        _ => None,
+    }
+}
+
+/// Draw one screen of `lines` from `offset`, with a status line below
+fn draw_watch_screen(file: &str, lines: &[String], offset: usize, height: usize) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    write!(stdout, "{}", render::terminal::CLEAR_SCREEN)?;
+    // Raw mode does not turn \n into a carriage return and line feed
+    for line in lines.iter().skip(offset).take(height) {
+        write!(stdout, "{}\r\n", line)?;
+    }
+    let last = (offset + height).min(lines.len());
+    write!(
+        stdout,
+        "\x1b[{}H\x1b[7m {} · lines {}-{} of {} · j/k, Space/b, g/G scroll · q quits \x1b[0m",
+        height + 1,
+        file,
+        (offset + 1).min(last),
+        last,
+        lines.len()
+    )?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Show `file` in the terminal and redraw it each time it changes, until
+/// `q`, Escape or Ctrl+C
+///
+/// One screen is shown at a time, sized to the terminal and paged with the
+/// keys of [`watch_scroll_key`]. The offset is kept across reloads and moves
+/// to follow edits that land outside of it.
+fn watch_in_terminal(file: &str, config: &AppConfig, theme: &ActiveTheme) -> Result<()> {
+    let (rx, _debouncer) = start_watching(Path::new(file), config.file_watcher.debounce_ms)
+        .context("Failed to start file watcher")?;
+    let _screen = WatchScreen::enter()?;
+    let mut height = watch_height(terminal::size().map(|(_, rows)| rows).unwrap_or(24));
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut offset = 0;
+    let mut reload = true;
+    loop {
+        if reload {
+            reload = false;
+            match render_for_terminal(file, config, theme) {
+                Ok(output) => {
+                    let rendered: Vec<String> = output.lines().map(str::to_string).collect();
+                    offset = render::terminal::follow_changes(&lines, &rendered, offset, height);
+                    lines = rendered;
+                }
+                Err(e) => warn!("Failed to reload '{}': {:?}", file, e),
+            }
+            draw_watch_screen(file, &lines, offset, height)?;
+        }
+
+        if event::poll(WATCH_POLL_INTERVAL)? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(());
+                    }
+                    code => {
+                        if let Some(scroll) = watch_scroll_key(code) {
+                            offset =
+                                render::terminal::scroll_watch(offset, scroll, height, lines.len());
+                            draw_watch_screen(file, &lines, offset, height)?;
+                        }
+                    }
+                },
+                Event::Resize(_, rows) => {
+                    height = watch_height(rows);
+                    offset = offset.min(lines.len().saturating_sub(height));
+                    draw_watch_screen(file, &lines, offset, height)?;
+                }
+                _ => {}
+            }
+        }
+
+        // A deleted or renamed file may come back, so keep waiting for it
+        match rx.try_recv() {
+            Ok(FileWatcherEvent::Modified) => reload = true,
+            Ok(FileWatcherEvent::Deleted) => warn!("'{}' was deleted; waiting for it", file),
+            Ok(FileWatcherEvent::Renamed(to)) => {
+                warn!(
+                    "'{}' was renamed to {:?}; waiting for it to return",
+                    file, to
+                )
+            }
+            Ok(FileWatcherEvent::Error(e)) => warn!("File watcher error: {}", e),
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Integration tests for the file watcher against editor save strategies
+//!
+//! Editors rarely write files in place: Vim and VS Code commonly write a
+//! temporary file and rename it over the original, or move the original to a
+//! backup before writing a new file. Each pattern must end in a `Modified`
+//! event so the viewer reloads instead of showing the deleted banner.
+
+use markdown_viewer::{FileWatcherEvent, start_watching};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+
+const DEBOUNCE_MS: u64 = 50;
+
+/// Create an empty scratch directory for one test and return the watched file path
+fn setup(name: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("mdv_atomic_save_{}", name));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("Failed to create test directory");
+    let dir = fs::canonicalize(&dir).expect("Failed to canonicalize test directory");
+    let file = dir.join("doc.md");
+    fs::write(&file, "# Original").expect("Failed to create test file");
+    (dir, file)
+}
+
+/// Wait past the debounce window and drain all events received so far
+fn drain(rx: &Receiver<FileWatcherEvent>) -> Vec<FileWatcherEvent> {
+    thread::sleep(Duration::from_millis(DEBOUNCE_MS * 8));
+    rx.try_iter().collect()
+}
+
+fn assert_ends_modified(events: &[FileWatcherEvent], dir: &Path) {
+    fs::remove_dir_all(dir).ok();
+    assert!(
+        matches!(events.last(), Some(FileWatcherEvent::Modified)),
+        "Save should end with a Modified event, got: {:?}",
+        events
+    );
+}
+
+#[test]
+fn write_temp_then_rename_over_original() {
+    let (dir, file) = setup("rename_over");
+    let (rx, _debouncer) = start_watching(&file, DEBOUNCE_MS).expect("Failed to start watcher");
+    thread::sleep(Duration::from_millis(100));
+
+    // VS Code / `:set backupcopy=no` style: write a sibling then rename it into place
+    let temp = dir.join(".doc.md.tmp");
+    fs::write(&temp, "# Saved").expect("Failed to write temp file");
+    fs::rename(&temp, &file).expect("Failed to rename temp file");
+
+    let events = drain(&rx);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "# Saved");
+    assert_ends_modified(&events, &dir);
+}
+
+#[test]
+fn move_original_to_backup_then_write_new_file() {
+    let (dir, file) = setup("backup_then_write");
+    let (rx, _debouncer) = start_watching(&file, DEBOUNCE_MS).expect("Failed to start watcher");
+    thread::sleep(Duration::from_millis(100));
+
+    // Vim default style: rename the original to a backup, write a fresh file, drop the backup
+    let backup = dir.join("doc.md~");
+    fs::rename(&file, &backup).expect("Failed to move original to backup");
+    fs::write(&file, "# Saved").expect("Failed to write new file");
+    fs::remove_file(&backup).expect("Failed to remove backup");
+
+    let events = drain(&rx);
+    assert_ends_modified(&events, &dir);
+}
+
+#[test]
+fn backup_rename_in_separate_batches_is_never_reported_as_renamed() {
+    let (dir, file) = setup("backup_separate_batches");
+    let (rx, _debouncer) = start_watching(&file, DEBOUNCE_MS).expect("Failed to start watcher");
+    thread::sleep(Duration::from_millis(100));
+
+    // A pause longer than the debounce window puts the rename and the new
+    // file in different batches
+    let backup = dir.join("doc.md~");
+    fs::rename(&file, &backup).expect("Failed to move original to backup");
+    thread::sleep(Duration::from_millis(DEBOUNCE_MS * 3 / 2));
+    fs::write(&file, "# Saved").expect("Failed to write new file");
+    fs::remove_file(&backup).expect("Failed to remove backup");
+
+    let events = drain(&rx);
+    assert!(
+        !events
+            .iter()
+            .any(|event| matches!(event, FileWatcherEvent::Renamed(_))),
+        "A backup-rename save must not follow the backup, got: {:?}",
+        events
+    );
+    assert_ends_modified(&events, &dir);
+}
+
+#[test]
+fn remove_then_create_original() {
+    let (dir, file) = setup("remove_create");
+    let (rx, _debouncer) = start_watching(&file, DEBOUNCE_MS).expect("Failed to start watcher");
+    thread::sleep(Duration::from_millis(100));
+
+    fs::remove_file(&file).expect("Failed to remove original");
+    fs::write(&file, "# Saved").expect("Failed to recreate file");
+
+    let events = drain(&rx);
+    assert_ends_modified(&events, &dir);
+}
+
+#[test]
+fn repeated_atomic_saves_keep_being_detected() {
+    let (dir, file) = setup("repeated");
+    let (rx, _debouncer) = start_watching(&file, DEBOUNCE_MS).expect("Failed to start watcher");
+    thread::sleep(Duration::from_millis(100));
+
+    // The file gets a new inode on every save; the watch must survive that
+    for i in 0..3 {
+        let temp = dir.join(".doc.md.tmp");
+        fs::write(&temp, format!("# Save {}", i)).expect("Failed to write temp file");
+        fs::rename(&temp, &file).expect("Failed to rename temp file");
+
+        let events = drain(&rx);
+        assert!(
+            matches!(events.last(), Some(FileWatcherEvent::Modified)),
+            "Save {} should end with a Modified event, got: {:?}",
+            i,
+            events
+        );
+    }
+
+    fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,202 @@
+//! Integration tests for resolving and loading files
+//!
+//! Every test works in a scratch directory of its own passed as the base
+//! directory, so the tests can run in parallel and never touch README.md or
+//! other files in the working directory.
+
+use markdown_viewer::{
+    LaunchTarget, in_base_dir, load_markdown_content, resolve_launch_target,
+    resolve_markdown_file_path,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A scratch directory, removed again when dropped
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    /// Create an empty scratch directory for one test, with `files` in it
+    fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let dir = std::env::temp_dir().join(format!("mdv_file_handling_{}", name));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).expect("Failed to create test directory");
+        for (file, content) in files {
+            let path = dir.join(file);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect("Failed to create test directory");
+            }
+            fs::write(path, content).expect("Failed to create test file");
+        }
+        Self(dir)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// `file` inside the directory, as the resolver returns it
+    fn file(&self, file: &str) -> String {
+        self.0.join(file).display().to_string()
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+fn default_files() -> Vec<String> {
+    vec!["README.md".to_string(), "TODO.md".to_string()]
+}
+
+fn supported() -> Vec<String> {
+    vec!["md".to_string(), "markdown".to_string(), "txt".to_string()]
+}
+
+#[test]
+fn given_files_resolve_inside_the_base_dir() {
+    let dir = ScratchDir::new(
+        "given",
+        &[
+            ("test_file.md", "# Test"),
+            ("test.markdown", "# Test"),
+            ("notes.txt", "# Notes"),
+        ],
+    );
+    for file in ["test_file.md", "test.markdown", "notes.txt"] {
+        let result =
+            resolve_markdown_file_path(dir.path(), Some(file), &default_files(), &supported());
+        assert_eq!(result.unwrap(), dir.file(file));
+    }
+}
+
+#[test]
+fn absolute_paths_ignore_the_base_dir() {
+    let dir = ScratchDir::new("absolute", &[("doc.md", "# Doc")]);
+    let result = resolve_markdown_file_path(
+        Path::new("/nonexistent"),
+        Some(&dir.file("doc.md")),
+        &default_files(),
+        &supported(),
+    );
+    assert_eq!(result.unwrap(), dir.file("doc.md"));
+}
+
+#[test]
+fn unsupported_extension_is_rejected() {
+    let dir = ScratchDir::new("unsupported", &[("test.pdf", "content")]);
+    let result =
+        resolve_markdown_file_path(dir.path(), Some("test.pdf"), &default_files(), &supported());
+    let err_msg = result.unwrap_err().to_string();
+    assert!(err_msg.contains("Unsupported file format"));
+    assert!(err_msg.contains("md, markdown, txt"));
+}
+
+#[test]
+fn missing_file_is_reported() {
+    let dir = ScratchDir::new("missing", &[]);
+    let result = resolve_markdown_file_path(
+        dir.path(),
+        Some("nonexistent_file.md"),
+        &default_files(),
+        &supported(),
+    );
+    assert!(result.unwrap_err().to_string().contains("File not found"));
+}
+
+#[test]
+fn readme_is_preferred_over_todo() {
+    let dir = ScratchDir::new(
+        "readme_first",
+        &[("README.md", "# README"), ("TODO.md", "# TODO")],
+    );
+    let result = resolve_markdown_file_path(dir.path(), None, &default_files(), &supported());
+    assert_eq!(result.unwrap(), dir.file("README.md"));
+}
+
+#[test]
+fn todo_is_used_without_readme() {
+    let dir = ScratchDir::new("todo_fallback", &[("TODO.md", "# TODO")]);
+    let result = resolve_markdown_file_path(dir.path(), None, &default_files(), &supported());
+    assert_eq!(result.unwrap(), dir.file("TODO.md"));
+}
+
+#[test]
+fn missing_default_files_are_named() {
+    let dir = ScratchDir::new("no_defaults", &[]);
+    let result = resolve_markdown_file_path(dir.path(), None, &default_files(), &supported());
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("Default files README.md and TODO.md not found")
+    );
+}
+
+#[test]
+fn default_globs_expand_inside_the_base_dir() {
+    let dir = ScratchDir::new(
+        "default_glob",
+        &[
+            ("docs/b.md", "# B"),
+            ("docs/a.md", "# A"),
+            ("docs/c.pdf", ""),
+        ],
+    );
+    let defaults = vec!["docs/*".to_string()];
+    let result = resolve_markdown_file_path(dir.path(), None, &defaults, &supported());
+    assert_eq!(result.unwrap(), dir.file("docs/a.md"));
+}
+
+#[test]
+fn launch_directories_resolve_inside_the_base_dir() {
+    let dir = ScratchDir::new(
+        "launch",
+        &[("guide/index.md", "# Index"), ("empty/notes.pdf", "")],
+    );
+    let target = |path: &str| resolve_launch_target(dir.path(), Some(path), &[], &supported());
+    assert_eq!(
+        target("guide").unwrap(),
+        LaunchTarget::File(dir.file("guide/index.md"))
+    );
+    assert_eq!(
+        target("empty").unwrap(),
+        LaunchTarget::Directory(dir.path().join("empty"))
+    );
+}
+
+#[test]
+fn working_dir_base_leaves_paths_as_given() {
+    assert_eq!(
+        in_base_dir(Path::new("."), "README.md"),
+        PathBuf::from("README.md")
+    );
+    assert_eq!(
+        in_base_dir(Path::new(""), "docs/a.md"),
+        PathBuf::from("docs/a.md")
+    );
+    assert_eq!(
+        in_base_dir(Path::new("/repo"), "docs/a.md"),
+        PathBuf::from("/repo/docs/a.md")
+    );
+}
+
+#[test]
+fn resolved_files_load() {
+    let dir = ScratchDir::new(
+        "load",
+        &[("test_load.md", "# Test Content\nThis is test markdown.")],
+    );
+    let path = resolve_markdown_file_path(
+        dir.path(),
+        Some("test_load.md"),
+        &default_files(),
+        &supported(),
+    )
+    .unwrap();
+    assert_eq!(
+        load_markdown_content(&path).unwrap(),
+        "# Test Content\nThis is test markdown."
+    );
+}
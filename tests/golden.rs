@@ -0,0 +1,61 @@
+//! Golden tests for the element outline of the GUI renderer
+//!
+//! Each `tests/golden/<name>.md` fixture is outlined the way the viewer
+//! would render it and compared with `tests/golden/<name>.outline`. After an
+//! intended renderer change, run the tests with `UPDATE_GOLDEN=1` to rewrite
+//! the expected outlines and review the diff.
+
+use markdown_viewer::render::outline::outline;
+use std::fs;
+use std::path::PathBuf;
+
+/// Outline fixture `name` and compare it with its expected outline
+fn check(name: &str) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let fixture = dir.join(format!("{}.md", name));
+    let expected_path = dir.join(format!("{}.outline", name));
+
+    let content = fs::read_to_string(&fixture).expect("Failed to read fixture");
+    let actual = outline(&content, &fixture).to_snapshot();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&expected_path, &actual).expect("Failed to write expected outline");
+        return;
+    }
+    let expected = fs::read_to_string(&expected_path).expect("Failed to read expected outline");
+    assert_eq!(
+        actual, expected,
+        "Outline of {}.md changed; rerun with UPDATE_GOLDEN=1 if intended",
+        name
+    );
+}
+
+#[test]
+fn nested_ordered_and_loose_lists() {
+    check("lists");
+}
+
+#[test]
+fn tables_keep_header_and_alignment() {
+    check("tables");
+}
+
+#[test]
+fn links_images_and_footnotes() {
+    check("links_and_images");
+}
+
+#[test]
+fn headings_quotes_code_and_math() {
+    check("blocks");
+}
+
+#[test]
+fn raw_html_blocks_and_inline_tags() {
+    check("html");
+}
+
+#[test]
+fn github_alerts_keep_type_and_title() {
+    check("alerts");
+}